@@ -37,7 +37,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         pool: pool.clone(),
         model: Arc::new(std::sync::RwLock::new(model)),
         package_models: Arc::new(std::sync::RwLock::new(std::collections::HashMap::new())),
-        tenant_pools: Arc::new(std::sync::RwLock::new(std::collections::HashMap::new())),
+        tenant_pools: Default::default(),
         tenant_registry: Arc::new(_registry),
         storage: None,
         event_client: architect_sdk::events::DecisionHubClient::from_env(),
@@ -45,6 +45,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         dialect,
         extensible_cache: Default::default(),
         cross_package_index: Arc::new(std::sync::RwLock::new(None)),
+        replica_pools: Default::default(),
     };
 
     let app = common_routes_with_ready(state);
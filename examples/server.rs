@@ -18,7 +18,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     dotenvy::dotenv().ok();
     let filter =
         EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("architect_sdk=info"));
-    tracing_subscriber::fmt().with_env_filter(filter).init();
+    // Also exports spans over OTLP with `--features otel` and OTEL_EXPORTER_OTLP_ENDPOINT set.
+    let _telemetry = architect_sdk::telemetry::init_tracing(filter);
 
     let database_url =
         std::env::var("DATABASE_URL").unwrap_or_else(|_| "sqlite://architect.db".into());
@@ -68,7 +69,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         pool: pool.clone(),
         model: Arc::new(RwLock::new(model)),
         package_models: Arc::new(RwLock::new(package_models)),
-        tenant_pools: Arc::new(RwLock::new(HashMap::new())),
+        tenant_pools: Default::default(),
         tenant_registry: Arc::new(tenant_registry),
         storage,
         event_client,
@@ -76,8 +77,12 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         dialect,
         extensible_cache: Default::default(),
         cross_package_index: Arc::new(RwLock::new(None)),
+        replica_pools: Default::default(),
     };
 
+    // Publishes `on: "schedule"` triggers; does nothing unless DECISION_HUB_URL is set.
+    architect_sdk::events::scheduler::spawn_event_scheduler(state.clone());
+
     let api = Router::new()
         .merge(common_routes_with_ready(state.clone()))
         .nest("/api/v1", config_routes(state.clone()))
@@ -0,0 +1,340 @@
+//! Migration plan approvals and risk gating.
+//!
+//! A previewed plan is stored in `_sys_migration_plans` and can be applied by anyone who knows
+//! its id. The [`ApprovalPolicy`] names the step risks and operations that make a plan *gated*:
+//! applying a gated plan is refused until an approval has been recorded with
+//! `POST /config/package/migration/approve/:migration_id`. An approval carries the approver id, an
+//! optional comment and per-step [`StepOverride`]s; a `skip` override keeps that step from running.
+//!
+//! The policy is read from `ARCHITECT_MIGRATION_APPROVAL`: a comma-separated list of risk names
+//! (`may_fail`, `data_will_be_modified`, …) and operation names (`drop_table`, `drop_column`, …).
+//! Unset, empty or `none`, nothing is gated and plans apply as before.
+//!
+//! The [`ApproverPolicy`] decides who may approve. The user who previewed a plan never may. With
+//! `ARCHITECT_MIGRATION_APPROVERS` set, only the listed user ids may; otherwise an authrs
+//! `approveMigration` grant is required when authrs is configured, and the Platform Admin tenant
+//! when it is not.
+
+use crate::error::AppError;
+use crate::migration::{MigrationPlan, MigrationSafety, MigrationStep, StepDecision};
+use serde::{Deserialize, Serialize};
+
+/// Which plan steps need an approval before the plan may be applied.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ApprovalPolicy {
+    /// Risk and operation names (serde snake_case). Empty (the default): nothing is gated.
+    pub gates: Vec<String>,
+}
+
+impl ApprovalPolicy {
+    /// The configured policy (env `ARCHITECT_MIGRATION_APPROVAL`, else no gates).
+    pub fn from_env() -> Self {
+        match std::env::var("ARCHITECT_MIGRATION_APPROVAL") {
+            Ok(value) => Self::parse(&value),
+            Err(_) => Self::default(),
+        }
+    }
+
+    /// Parse a comma-separated gate list. `none` (or an empty list) disables gating.
+    pub fn parse(value: &str) -> Self {
+        let gates: Vec<String> = value
+            .split(',')
+            .map(|g| g.trim().to_ascii_lowercase())
+            .filter(|g| !g.is_empty())
+            .collect();
+        if gates.iter().any(|g| g == "none") {
+            return Self { gates: Vec::new() };
+        }
+        Self { gates }
+    }
+
+    /// Steps that will run and match a gate, by risk or by operation. Warn-only steps have no
+    /// DDL and are never gated.
+    pub fn gated_steps<'p>(&self, plan: &'p MigrationPlan) -> Vec<&'p MigrationStep> {
+        plan.steps
+            .iter()
+            .filter(|step| step.ddl.is_some())
+            .filter(|step| {
+                let risk = risk_name(step);
+                let operation = step.operation.to_string();
+                self.gates.iter().any(|g| *g == risk || *g == operation)
+            })
+            .collect()
+    }
+}
+
+/// Who may approve a migration plan.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ApproverPolicy {
+    /// User ids from `ARCHITECT_MIGRATION_APPROVERS`; `None` when unset.
+    pub approvers: Option<Vec<String>>,
+}
+
+impl ApproverPolicy {
+    /// The configured policy (env `ARCHITECT_MIGRATION_APPROVERS`).
+    pub fn from_env() -> Self {
+        match std::env::var("ARCHITECT_MIGRATION_APPROVERS") {
+            Ok(value) => Self::parse(&value),
+            Err(_) => Self::default(),
+        }
+    }
+
+    /// Parse a comma-separated list of user ids.
+    pub fn parse(value: &str) -> Self {
+        Self {
+            approvers: Some(
+                value
+                    .split(',')
+                    .map(|a| a.trim().to_string())
+                    .filter(|a| !a.is_empty())
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Refuse an approver who previewed the plan (`requested_by`) or who is not authorized.
+    /// Without an allow-list, `authrs_enabled` defers the decision to the caller's authrs check;
+    /// with neither, only the Platform Admin tenant may approve.
+    pub fn check_approver(
+        &self,
+        approver: &str,
+        requested_by: Option<&str>,
+        platform_admin: bool,
+        authrs_enabled: bool,
+    ) -> Result<(), AppError> {
+        if requested_by == Some(approver) {
+            return Err(AppError::Forbidden(format!(
+                "'{}' previewed this migration plan and cannot approve it",
+                approver
+            )));
+        }
+        match &self.approvers {
+            Some(list) if list.iter().any(|a| a == approver) => Ok(()),
+            Some(_) => Err(AppError::Forbidden(format!(
+                "'{}' is not listed in ARCHITECT_MIGRATION_APPROVERS",
+                approver
+            ))),
+            None if authrs_enabled || platform_admin => Ok(()),
+            None => Err(AppError::Forbidden(
+                "migration plans are approved by the Platform Admin, or by users listed in \
+                 ARCHITECT_MIGRATION_APPROVERS or granted approveMigration in authrs"
+                    .into(),
+            )),
+        }
+    }
+}
+
+/// An approver's decision for one step, e.g. `{"step": 4, "decision": "skip", "reason": "…"}`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StepOverride {
+    pub step: usize,
+    #[serde(flatten)]
+    pub decision: StepDecision,
+}
+
+/// An approval recorded against a stored migration plan.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlanApproval {
+    pub approver: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub overrides: Vec<StepOverride>,
+}
+
+/// Reject overrides for steps the plan does not have, or more than one override per step.
+pub fn validate_overrides(
+    plan: &MigrationPlan,
+    overrides: &[StepOverride],
+) -> Result<(), AppError> {
+    for (i, o) in overrides.iter().enumerate() {
+        if !plan.steps.iter().any(|s| s.step == o.step) {
+            return Err(AppError::BadRequest(format!(
+                "override for step {}: the plan has no such step",
+                o.step
+            )));
+        }
+        if overrides[..i].iter().any(|prev| prev.step == o.step) {
+            return Err(AppError::BadRequest(format!(
+                "step {} has more than one override",
+                o.step
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Refuse a gated plan that has no approval. The error lists the gated steps.
+pub fn check_approval(
+    policy: &ApprovalPolicy,
+    plan: &MigrationPlan,
+    approval: Option<&PlanApproval>,
+) -> Result<(), AppError> {
+    if approval.is_some() {
+        return Ok(());
+    }
+    let gated = policy.gated_steps(plan);
+    if gated.is_empty() {
+        return Ok(());
+    }
+    let steps: Vec<String> = gated
+        .iter()
+        .map(|s| format!("{} ({}, {})", s.step, s.operation, risk_name(s)))
+        .collect();
+    Err(AppError::Forbidden(format!(
+        "migration plan requires approval before it can be applied — gated steps: {}",
+        steps.join(", ")
+    )))
+}
+
+/// The plan as approved: steps the approver skipped become warn-only, so the executor records
+/// them as skipped instead of running them.
+pub fn apply_overrides(mut plan: MigrationPlan, approval: &PlanApproval) -> MigrationPlan {
+    for o in &approval.overrides {
+        let StepDecision::Skip(ref reason) = o.decision else {
+            continue;
+        };
+        if let Some(step) = plan.steps.iter_mut().find(|s| s.step == o.step) {
+            step.ddl = None;
+            step.safety = MigrationSafety::WarnOnly;
+            step.risk_detail = Some(format!(
+                "Skipped by approval from {}: {}",
+                approval.approver, reason
+            ));
+        }
+    }
+    plan
+}
+
+fn risk_name(step: &MigrationStep) -> String {
+    serde_json::to_value(&step.risk)
+        .ok()
+        .and_then(|v| v.as_str().map(String::from))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod approval_tests {
+    use super::*;
+    use crate::migration::{MigrationOperation, MigrationRisk};
+
+    fn step(n: usize, op: MigrationOperation, risk: MigrationRisk) -> MigrationStep {
+        MigrationStep::new(
+            op,
+            "app",
+            Some("orders".into()),
+            "total",
+            "column",
+            "test step",
+        )
+        .with_ddl("-- ddl")
+        .with_risk(risk, None)
+        .with_step(n)
+    }
+
+    fn plan() -> MigrationPlan {
+        MigrationPlan {
+            steps: vec![
+                step(1, MigrationOperation::AddColumn, MigrationRisk::None),
+                step(
+                    2,
+                    MigrationOperation::AlterColumnType,
+                    MigrationRisk::MayFail,
+                ),
+                step(3, MigrationOperation::DropColumn, MigrationRisk::None),
+            ],
+        }
+    }
+
+    fn gates() -> ApprovalPolicy {
+        ApprovalPolicy::parse("may_fail,data_will_be_modified,drop_table,drop_column")
+    }
+
+    #[test]
+    fn configured_policy_gates_risky_casts_and_drops() {
+        let gated: Vec<usize> = gates()
+            .gated_steps(&plan())
+            .iter()
+            .map(|s| s.step)
+            .collect();
+        assert_eq!(gated, vec![2, 3]);
+        assert!(check_approval(&gates(), &plan(), None).is_err());
+        assert!(check_approval(&ApprovalPolicy::default(), &plan(), None).is_ok());
+        assert!(check_approval(&ApprovalPolicy::parse("none"), &plan(), None).is_ok());
+        assert_eq!(
+            ApprovalPolicy::parse(" drop_table ,").gates,
+            vec!["drop_table".to_string()]
+        );
+    }
+
+    #[test]
+    fn requesters_and_unlisted_users_cannot_approve() {
+        let open = ApproverPolicy::default();
+        // The previewing user is refused even where anyone else would pass.
+        assert!(open
+            .check_approver("alice", Some("alice"), true, true)
+            .is_err());
+        assert!(open
+            .check_approver("bob", Some("alice"), true, false)
+            .is_ok());
+        assert!(open
+            .check_approver("bob", Some("alice"), false, true)
+            .is_ok());
+        assert!(open
+            .check_approver("bob", Some("alice"), false, false)
+            .is_err());
+
+        let listed = ApproverPolicy::parse(" dba , lead,");
+        assert_eq!(
+            listed.approvers,
+            Some(vec!["dba".to_string(), "lead".to_string()])
+        );
+        assert!(listed
+            .check_approver("dba", Some("alice"), false, false)
+            .is_ok());
+        assert!(listed.check_approver("bob", None, true, true).is_err());
+        assert!(listed
+            .check_approver("dba", Some("dba"), false, false)
+            .is_err());
+    }
+
+    #[test]
+    fn skip_overrides_turn_steps_warn_only() {
+        let approval: PlanApproval = serde_json::from_value(serde_json::json!({
+            "approver": "dba@example.com",
+            "overrides": [
+                { "step": 3, "decision": "skip", "reason": "dropped by hand" },
+                { "step": 2, "decision": "execute" }
+            ]
+        }))
+        .unwrap();
+        assert_eq!(
+            approval.overrides[0].decision,
+            StepDecision::Skip("dropped by hand".into())
+        );
+        validate_overrides(&plan(), &approval.overrides).unwrap();
+        assert!(check_approval(&gates(), &plan(), Some(&approval)).is_ok());
+
+        let approved = apply_overrides(plan(), &approval);
+        assert!(approved.steps[1].ddl.is_some());
+        assert!(approved.steps[2].ddl.is_none());
+        assert!(matches!(
+            approved.steps[2].safety,
+            MigrationSafety::WarnOnly
+        ));
+        assert_eq!(
+            approved.steps[2].risk_detail.as_deref(),
+            Some("Skipped by approval from dba@example.com: dropped by hand")
+        );
+    }
+
+    #[test]
+    fn overrides_must_name_plan_steps_once() {
+        let o = |step| StepOverride {
+            step,
+            decision: StepDecision::Execute,
+        };
+        assert!(validate_overrides(&plan(), &[o(9)]).is_err());
+        assert!(validate_overrides(&plan(), &[o(2), o(2)]).is_err());
+    }
+}
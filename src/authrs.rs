@@ -39,12 +39,38 @@ impl AuthrsClient {
         }))
     }
 
+    /// `GET {AUTHRS_URL}/health`, for the diagnostics probes.
+    pub(crate) async fn health(&self) -> Result<(), String> {
+        crate::diagnostics::probe_health(&self.client, &self.base_url).await
+    }
+
+    #[tracing::instrument(name = "authrs.check", skip_all, fields(action = %action))]
     async fn check(
         &self,
         tenant_id: &str,
         user_id: &str,
         resource: &str,
         action: &str,
+    ) -> Result<bool, AppError> {
+        let start = std::time::Instant::now();
+        let result = self
+            .request_check(tenant_id, user_id, resource, action)
+            .await;
+        let label = match result {
+            Ok(true) => "allowed",
+            Ok(false) => "denied",
+            Err(_) => "error",
+        };
+        crate::metrics::observe_authrs(label, start.elapsed());
+        result
+    }
+
+    async fn request_check(
+        &self,
+        tenant_id: &str,
+        user_id: &str,
+        resource: &str,
+        action: &str,
     ) -> Result<bool, AppError> {
         let url = format!("{}/admin/permissions/check", self.base_url);
         let body = serde_json::json!({
@@ -52,9 +78,7 @@ impl AuthrsClient {
             "resource": resource,
             "action": action,
         });
-        let resp = self
-            .client
-            .post(&url)
+        let resp = crate::telemetry::inject_trace_context(self.client.post(&url))
             .header("X-Tenant-ID", tenant_id)
             .json(&body)
             .send()
@@ -150,3 +174,28 @@ pub async fn check_entity_permission_opt(
 
     Ok(())
 }
+
+/// Check that `user_id` may approve migration plans for `package_id`: action `approveMigration`
+/// on resource `service:{SERVICE_NAME}/package:{package_id}`.
+pub async fn check_migration_approval(
+    client: &AuthrsClient,
+    tenant_id: &str,
+    user_id: &str,
+    package_id: &str,
+) -> Result<(), AppError> {
+    let action = "approveMigration";
+    let resource = format!("service:{}/package:{}", client.service_name, package_id);
+    if !client.check(tenant_id, user_id, &resource, action).await? {
+        tracing::warn!(
+            user_id = %user_id,
+            tenant_id = %tenant_id,
+            resource = %resource,
+            "migration approval denied"
+        );
+        return Err(AppError::Unauthorized(format!(
+            "action '{}' not permitted on '{}'",
+            action, resource
+        )));
+    }
+    Ok(())
+}
@@ -0,0 +1,100 @@
+//! `architect`: check a package offline, for CI.
+//!
+//! ```text
+//! architect lint <package dir|zip> [--against <dir|zip>] [--dialect <name>] [--json] [--deny-warnings]
+//! ```
+//!
+//! Exit codes: 0 clean, 1 errors (or warnings with `--deny-warnings`), 2 usage or I/O errors.
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use architect_sdk::db::{active_dialect, dialect_by_name};
+use architect_sdk::lint::{lint_package, read_package_path};
+
+const USAGE: &str = "usage: architect lint <package dir|zip> [--against <dir|zip>] \
+                     [--dialect <postgres|mysql|sqlite>] [--json] [--deny-warnings]";
+
+struct LintArgs {
+    package: PathBuf,
+    against: Option<PathBuf>,
+    dialect: Option<String>,
+    json: bool,
+    deny_warnings: bool,
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<LintArgs, String> {
+    match args.next().as_deref() {
+        Some("lint") => {}
+        Some(other) => return Err(format!("unknown command '{}'", other)),
+        None => return Err("missing command".into()),
+    }
+    let mut package = None;
+    let mut parsed = LintArgs {
+        package: PathBuf::new(),
+        against: None,
+        dialect: None,
+        json: false,
+        deny_warnings: false,
+    };
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--against" => {
+                parsed.against = Some(args.next().ok_or("--against needs a path")?.into())
+            }
+            "--dialect" => parsed.dialect = Some(args.next().ok_or("--dialect needs a name")?),
+            "--json" => parsed.json = true,
+            "--deny-warnings" => parsed.deny_warnings = true,
+            flag if flag.starts_with("--") => return Err(format!("unknown option '{}'", flag)),
+            path if package.is_none() => package = Some(PathBuf::from(path)),
+            extra => return Err(format!("unexpected argument '{}'", extra)),
+        }
+    }
+    parsed.package = package.ok_or("missing package path")?;
+    Ok(parsed)
+}
+
+fn main() -> ExitCode {
+    let args = match parse_args(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("architect: {}\n{}", e, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+    let dialect = match args.dialect.as_deref() {
+        None => active_dialect(),
+        Some(name) => match dialect_by_name(name) {
+            Some(dialect) => dialect,
+            None => {
+                eprintln!(
+                    "architect: unknown dialect '{}' (expected postgres, mysql or sqlite)",
+                    name
+                );
+                return ExitCode::from(2);
+            }
+        },
+    };
+    let read = |path: &PathBuf| read_package_path(path).map_err(|e| eprintln!("architect: {}", e));
+    let Ok(package) = read(&args.package) else {
+        return ExitCode::from(2);
+    };
+    let against = match args.against.as_ref().map(read).transpose() {
+        Ok(against) => against,
+        Err(()) => return ExitCode::from(2),
+    };
+
+    let report = lint_package(&package, dialect.as_ref(), against.as_deref());
+    if args.json {
+        match serde_json::to_string_pretty(&report) {
+            Ok(json) => println!("{}", json),
+            Err(e) => {
+                eprintln!("architect: {}", e);
+                return ExitCode::from(2);
+            }
+        }
+    } else {
+        print!("{}", report.render_human());
+    }
+    ExitCode::from(report.exit_code(args.deny_warnings) as u8)
+}
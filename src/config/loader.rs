@@ -181,6 +181,7 @@ pub fn resolve(config: &FullConfig) -> Result<ResolvedModel, ConfigError> {
             versioning: table.versioning.clone(),
             mcp: api.mcp.clone(),
             extensible_columns,
+            shadow_columns: Vec::new(),
         };
         entity_by_path.insert(api.path_segment.clone(), entity.clone());
         entities.push(entity);
@@ -214,6 +215,7 @@ pub fn resolve(config: &FullConfig) -> Result<ResolvedModel, ConfigError> {
                 versioning: None,
                 mcp: None,
                 extensible_columns: Vec::new(),
+                shadow_columns: Vec::new(),
             };
             audit_entity
         })
@@ -618,6 +620,7 @@ mod tests {
             versioning: None,
             mcp: None,
             extensible_columns: vec![],
+            shadow_columns: vec![],
         }
     }
 
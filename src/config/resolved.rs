@@ -90,6 +90,21 @@ pub struct ResolvedEntity {
     /// whose per-tenant field definitions live in the KV registry and whose keys are
     /// RSQL-filterable/sortable via the `<column>.<key>` syntax. Empty when none configured.
     pub extensible_columns: Vec<String>,
+    /// Columns part-way through an expand/contract type change. Every write to `column` is
+    /// mirrored into its shadow until the contract phase swaps them. Attached from
+    /// `_sys_column_transitions` by [`crate::transition::attach_shadow_columns`].
+    pub shadow_columns: Vec<ShadowColumn>,
+}
+
+/// A column being retyped through a shadow column (see [`crate::transition`]).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ShadowColumn {
+    pub column: String,
+    pub shadow: String,
+    /// Cast for binding values to the source column (its pre-change type).
+    pub source_cast: Option<String>,
+    /// DDL type of the shadow column; mirrored values are converted to it on assignment.
+    pub target_type: String,
 }
 
 #[derive(Clone, Debug)]
@@ -84,12 +84,32 @@ pub enum ColumnTypeConfig {
     },
 }
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug)]
 pub enum ColumnDefaultConfig {
     Literal(String),
     Expression { expression: String },
 }
 
+/// Serializes to the shapes the deserializer accepts — a bare string for a literal and
+/// `{ "expression": "..." }` for an expression — so configs written out (generated packages)
+/// read back unchanged.
+impl Serialize for ColumnDefaultConfig {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match self {
+            ColumnDefaultConfig::Literal(s) => serializer.serialize_str(s),
+            ColumnDefaultConfig::Expression { expression } => {
+                use serde::ser::SerializeMap;
+                let mut map = serializer.serialize_map(Some(1))?;
+                map.serialize_entry("expression", expression)?;
+                map.end()
+            }
+        }
+    }
+}
+
 impl<'de> Deserialize<'de> for ColumnDefaultConfig {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -258,6 +278,7 @@ pub struct ValidationRule {
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct AssetColumnConfig {
     /// Path prefix template. Supports {yyyy}, {mm}, {dd}, {hh}, {tenant_id}, {entity}.
+    /// Default: `{entity}/{yyyy}/{mm}/{dd}`.
     #[serde(default)]
     pub prefix: Option<String>,
     /// Byte-level compression before upload: "none" | "gzip" | "zstd". Default: "none".
@@ -280,15 +301,35 @@ pub struct EventCondition {
     pub not_null: Option<bool>,
 }
 
+/// Timing for an `on: "schedule"` trigger. At least one of `cron` / `due_column` must be set.
+///
+/// - `cron` only: at every cron occurrence, fire once for each row matching `condition`.
+/// - `due_column` only: on every scheduler tick, fire once for each row whose timestamp in
+///   `due_column` has passed (e.g. `"valid_until"` → an `expired` event).
+/// - both: as `due_column`, but rows are only checked at the cron occurrences.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EventSchedule {
+    /// Cron expression, evaluated in UTC. Accepts the classic 5-field form
+    /// (`min hour dom mon dow`) or the 6/7-field form with leading seconds.
+    #[serde(default)]
+    pub cron: Option<String>,
+    /// Timestamp column (snake_case) whose passing makes a row due.
+    #[serde(default)]
+    pub due_column: Option<String>,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct EntityEventTrigger {
     pub id: String,
-    /// Lifecycle hook: "create" | "update" | "delete" | "archive".
+    /// Lifecycle hook: "create" | "update" | "delete" | "archive" | "schedule".
     pub on: String,
     /// Suffix of the event type sent to decision-hub.
-    /// Defaults to "created" / "updated" / "deleted" / "archived" when omitted.
+    /// Defaults to "created" / "updated" / "deleted" / "archived" / "scheduled" when omitted.
     #[serde(default)]
     pub event_name: Option<String>,
+    /// Required for `on: "schedule"`; ignored for every other lifecycle.
+    #[serde(default)]
+    pub schedule: Option<EventSchedule>,
     /// Only fire when this condition is satisfied against the saved row (snake_case keys).
     #[serde(default)]
     pub condition: Option<EventCondition>,
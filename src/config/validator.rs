@@ -178,11 +178,65 @@ pub fn validate(config: &FullConfig) -> Result<(), ConfigError> {
         if !path_segments.insert(api.path_segment.as_str()) {
             return Err(ConfigError::DuplicatePathSegment(api.path_segment.clone()));
         }
+        for trigger in api.events.iter().filter(|t| t.on == "schedule") {
+            validate_schedule_trigger(config, &api.entity_id, trigger)?;
+        }
     }
 
     Ok(())
 }
 
+/// An `on: "schedule"` trigger needs a parseable cron expression and/or a date/timestamp
+/// `due_column` on the entity's table. Caught here so a bad expression never reaches the scheduler.
+fn validate_schedule_trigger(
+    config: &FullConfig,
+    table_id: &str,
+    trigger: &crate::config::EntityEventTrigger,
+) -> Result<(), ConfigError> {
+    let schedule = trigger
+        .schedule
+        .as_ref()
+        .filter(|s| s.cron.is_some() || s.due_column.is_some())
+        .ok_or_else(|| {
+            ConfigError::Validation(format!(
+                "event trigger '{}': on \"schedule\" requires schedule.cron and/or schedule.due_column",
+                trigger.id
+            ))
+        })?;
+    if let Some(expr) = &schedule.cron {
+        crate::events::scheduler::parse_cron(expr).map_err(|e| {
+            ConfigError::Validation(format!(
+                "event trigger '{}': invalid cron '{}': {}",
+                trigger.id, expr, e
+            ))
+        })?;
+    }
+    if let Some(col) = &schedule.due_column {
+        let column = config
+            .columns
+            .iter()
+            .find(|c| c.table_id == table_id && &c.name == col)
+            .ok_or_else(|| {
+                ConfigError::Validation(format!(
+                    "event trigger '{}': due_column '{}' is not a column of table '{}'",
+                    trigger.id, col, table_id
+                ))
+            })?;
+        if !matches!(
+            crate::db::parse_canonical(&column.type_),
+            crate::db::CanonicalType::Timestamp
+                | crate::db::CanonicalType::TimestampNtz
+                | crate::db::CanonicalType::Date
+        ) {
+            return Err(ConfigError::Validation(format!(
+                "event trigger '{}': due_column '{}' must be a date or timestamp column",
+                trigger.id, col
+            )));
+        }
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -462,4 +516,49 @@ mod tests {
         let c = FullConfig::default();
         assert!(default_schema_id(&c).is_err());
     }
+
+    // --- scheduled event triggers ---
+
+    fn schedule_trigger(
+        cron: Option<&str>,
+        due_column: Option<&str>,
+    ) -> crate::config::EntityEventTrigger {
+        crate::config::EntityEventTrigger {
+            id: "evt".into(),
+            on: "schedule".into(),
+            event_name: None,
+            schedule: Some(crate::config::EventSchedule {
+                cron: cron.map(Into::into),
+                due_column: due_column.map(Into::into),
+            }),
+            condition: None,
+            include: vec![],
+        }
+    }
+
+    #[test]
+    fn schedule_trigger_with_cron_or_timestamp_column_ok() {
+        let mut c = minimal_config();
+        c.columns
+            .push(typed_column("c2", "t1", "due_at", "timestamp"));
+        c.api_entities[0].events = vec![
+            schedule_trigger(Some("0 9 * * 1-5"), None),
+            schedule_trigger(None, Some("due_at")),
+        ];
+        assert!(validate(&c).is_ok());
+    }
+
+    #[test]
+    fn schedule_trigger_invalid_timing_fails() {
+        let mut c = minimal_config();
+        c.api_entities[0].events = vec![schedule_trigger(None, None)];
+        assert!(matches!(validate(&c), Err(ConfigError::Validation(_))));
+
+        c.api_entities[0].events = vec![schedule_trigger(Some("every day"), None)];
+        assert!(matches!(validate(&c), Err(ConfigError::Validation(_))));
+
+        // `id` exists but is a text column.
+        c.api_entities[0].events = vec![schedule_trigger(None, Some("id"))];
+        assert!(matches!(validate(&c), Err(ConfigError::Validation(_))));
+    }
 }
@@ -0,0 +1,477 @@
+//! Declarative data migrations shipped in a package's `data/` folder.
+//!
+//! Packages describe structure; the files under `data/` ship data — reference lists, default
+//! settings, transforms such as renaming an enum value in existing rows. Each file is one data
+//! migration, identified by its path without the extension and applied in id order:
+//!
+//! - `data/<id>.json` with `{"entity": "<path segment>", "rows": [{…}, …]}` inserts rows through
+//!   the CRUD layer (RLS tenants get their tenant column set like any other insert).
+//! - `data/<id>.json` with `{"kv_namespace": "<namespace>", "entries": {"key": value, …}}` writes
+//!   KV entries for the tenant, replacing existing values.
+//! - `data/<id>.sql`, or `data/<id>.<dialect>.sql` (`postgres`, `mysql`, `sqlite`) for one dialect
+//!   only: `;`-separated statements. `{{schema}}` expands to the quoted schema and `{{tenant_id}}`
+//!   to the tenant id as a string literal. A dialect-specific file replaces the generic one.
+//!
+//! Install and upgrade run every migration a tenant has not had yet, each in its own transaction,
+//! and record it in `_sys_package_data_migrations` so it never runs twice. A file whose content
+//! changed after it was applied is reported, not re-run.
+
+use crate::config::{FullConfig, ResolvedModel};
+use crate::db::pool::Pool;
+use crate::db::Dialect;
+use crate::error::AppError;
+use crate::migration::quote;
+use crate::service::{CrudService, TenantExecutor};
+use crate::store::{insert_data_migration, list_data_migrations, qualified_sys_table};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+
+/// Folder inside the package ZIP that holds data migrations.
+pub const DATA_DIR: &str = "data/";
+
+/// Dialect suffixes recognised in `<id>.<dialect>.sql`.
+const DIALECT_SUFFIXES: &[&str] = &["postgres", "mysql", "sqlite"];
+
+/// What a data migration does.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DataMigrationBody {
+    /// Rows inserted into the entity with this path segment.
+    Rows {
+        entity: String,
+        rows: Vec<HashMap<String, Value>>,
+    },
+    /// KV entries written to one of the package's namespaces.
+    Kv {
+        namespace: String,
+        entries: serde_json::Map<String, Value>,
+    },
+    /// SQL statements, placeholders not yet expanded.
+    Sql { statements: Vec<String> },
+}
+
+impl DataMigrationBody {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            DataMigrationBody::Rows { .. } => "rows",
+            DataMigrationBody::Kv { .. } => "kv",
+            DataMigrationBody::Sql { .. } => "sql",
+        }
+    }
+}
+
+/// One file from `data/`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DataMigration {
+    pub id: String,
+    /// SHA-256 of the file content, hex.
+    pub checksum: String,
+    pub body: DataMigrationBody,
+}
+
+/// What install or upgrade did with a package's data migrations for one tenant.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DataMigrationOutcome {
+    pub tenant_id: String,
+    pub applied: Vec<String>,
+    /// Applied earlier, but the file has changed since. Not re-run.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub changed: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct JsonFile {
+    entity: Option<String>,
+    #[serde(default)]
+    rows: Vec<HashMap<String, Value>>,
+    kv_namespace: Option<String>,
+    #[serde(default)]
+    entries: serde_json::Map<String, Value>,
+}
+
+/// Parse the `data/` files of a package (`(path, content)` pairs, paths as in the ZIP) into the
+/// migrations that apply to `dialect`, ordered by id. Files outside `data/` are ignored.
+pub fn parse_data_migrations(
+    files: &[(String, String)],
+    dialect: &dyn Dialect,
+) -> Result<Vec<DataMigration>, AppError> {
+    // id → (is dialect-specific, migration)
+    let mut by_id: BTreeMap<String, (bool, DataMigration)> = BTreeMap::new();
+    for (path, content) in files {
+        let Some(name) = path.strip_prefix(DATA_DIR) else {
+            continue;
+        };
+        let checksum = hex::encode(Sha256::digest(content.as_bytes()));
+        let (id, specific, body) = if let Some(id) = name.strip_suffix(".json") {
+            (id.to_string(), false, parse_json_file(path, content)?)
+        } else if let Some(stem) = name.strip_suffix(".sql") {
+            let (id, file_dialect) = match stem.rsplit_once('.') {
+                Some((id, d)) if DIALECT_SUFFIXES.contains(&d) => (id, Some(d)),
+                _ => (stem, None),
+            };
+            if file_dialect.is_some_and(|d| d != dialect.name()) {
+                continue;
+            }
+            let statements = split_statements(content);
+            (
+                id.to_string(),
+                file_dialect.is_some(),
+                DataMigrationBody::Sql { statements },
+            )
+        } else {
+            continue;
+        };
+        if id.is_empty() {
+            return Err(AppError::BadRequest(format!(
+                "{}: data migration file needs a name",
+                path
+            )));
+        }
+        let migration = DataMigration {
+            id: id.clone(),
+            checksum,
+            body,
+        };
+        match by_id.get(&id) {
+            Some((true, _)) if !specific => {}
+            Some((prev_specific, prev)) if *prev_specific == specific => {
+                return Err(AppError::BadRequest(format!(
+                    "data migration '{}' is defined twice ({} and {})",
+                    id,
+                    prev.body.kind(),
+                    path
+                )));
+            }
+            _ => {
+                by_id.insert(id, (specific, migration));
+            }
+        }
+    }
+    Ok(by_id.into_values().map(|(_, m)| m).collect())
+}
+
+/// Reject migrations that target an entity or KV namespace the package does not define.
+pub fn check_data_migrations(
+    migrations: &[DataMigration],
+    config: &FullConfig,
+    model: &ResolvedModel,
+) -> Result<(), AppError> {
+    for m in migrations {
+        match &m.body {
+            DataMigrationBody::Rows { entity, .. } if model.entity_by_path(entity).is_none() => {
+                return Err(AppError::BadRequest(format!(
+                    "data migration '{}': unknown entity '{}'",
+                    m.id, entity
+                )));
+            }
+            DataMigrationBody::Kv { namespace, .. }
+                if !config.kv_stores.iter().any(|k| k.namespace == *namespace) =>
+            {
+                return Err(AppError::BadRequest(format!(
+                    "data migration '{}': unknown KV namespace '{}'",
+                    m.id, namespace
+                )));
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+fn parse_json_file(path: &str, content: &str) -> Result<DataMigrationBody, AppError> {
+    let file: JsonFile = serde_json::from_str(content)
+        .map_err(|e| AppError::BadRequest(format!("invalid {}: {}", path, e)))?;
+    match (file.entity, file.kv_namespace) {
+        (Some(entity), None) => Ok(DataMigrationBody::Rows {
+            entity,
+            rows: file.rows,
+        }),
+        (None, Some(namespace)) => Ok(DataMigrationBody::Kv {
+            namespace,
+            entries: file.entries,
+        }),
+        _ => Err(AppError::BadRequest(format!(
+            "{}: expected exactly one of 'entity' or 'kv_namespace'",
+            path
+        ))),
+    }
+}
+
+/// Split a SQL file on `;`, ignoring semicolons inside quoted strings and identifiers. Empty
+/// statements are dropped.
+fn split_statements(sql: &str) -> Vec<String> {
+    let mut out = Vec::new();
+    let mut current = String::new();
+    let mut quote: Option<char> = None;
+    for c in sql.chars() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None if c == '\'' || c == '"' => quote = Some(c),
+            None if c == ';' => {
+                if !current.trim().is_empty() {
+                    out.push(current.trim().to_string());
+                }
+                current.clear();
+                continue;
+            }
+            None => {}
+        }
+        current.push(c);
+    }
+    if !current.trim().is_empty() {
+        out.push(current.trim().to_string());
+    }
+    out
+}
+
+/// Where one tenant's data lives.
+pub struct DataMigrationTarget<'a> {
+    /// Tenant data pool (the migration pool of the tenant's context).
+    pub pool: &'a Pool,
+    pub tenant_id: &'a str,
+    /// Schema strategy: the tenant's schema.
+    pub schema_override: Option<&'a str>,
+    /// Schema `{{schema}}` expands to: `schema_override`, else the package's schema.
+    pub schema: &'a str,
+    /// RLS strategy: set the tenant session and tenant column on inserts.
+    pub rls: bool,
+}
+
+/// Run the migrations `target`'s tenant has not had yet. `config_pool` holds the bookkeeping
+/// table and KV data; `model` is the package's resolved model. Stops at the first failing
+/// migration, leaving it and the ones after it for the next install or upgrade.
+pub async fn apply_data_migrations(
+    config_pool: &Pool,
+    target: &DataMigrationTarget<'_>,
+    package_id: &str,
+    package_version: Option<&str>,
+    model: &ResolvedModel,
+    migrations: &[DataMigration],
+    dialect: &dyn Dialect,
+) -> DataMigrationOutcome {
+    let mut outcome = DataMigrationOutcome {
+        tenant_id: target.tenant_id.to_string(),
+        ..Default::default()
+    };
+    if migrations.is_empty() {
+        return outcome;
+    }
+    let done: HashMap<String, String> =
+        match list_data_migrations(config_pool, package_id, target.tenant_id).await {
+            Ok(rows) => rows.into_iter().collect(),
+            Err(e) => {
+                outcome.error = Some(e.to_string());
+                return outcome;
+            }
+        };
+    for m in migrations {
+        if let Some(checksum) = done.get(&m.id) {
+            if *checksum != m.checksum {
+                outcome.changed.push(m.id.clone());
+            }
+            continue;
+        }
+        let result = match &m.body {
+            DataMigrationBody::Rows { entity, rows } => {
+                insert_rows(target, model, entity, rows, dialect).await
+            }
+            DataMigrationBody::Kv { namespace, entries } => {
+                write_kv(
+                    config_pool,
+                    target.tenant_id,
+                    package_id,
+                    namespace,
+                    entries,
+                )
+                .await
+            }
+            DataMigrationBody::Sql { statements } => run_sql(target, statements, dialect).await,
+        };
+        let recorded = match result {
+            Ok(()) => {
+                insert_data_migration(
+                    config_pool,
+                    package_id,
+                    target.tenant_id,
+                    &m.id,
+                    m.body.kind(),
+                    &m.checksum,
+                    package_version,
+                )
+                .await
+            }
+            Err(e) => Err(e),
+        };
+        if let Err(e) = recorded {
+            tracing::warn!(package_id, tenant = target.tenant_id, migration = %m.id, error = %e, "data migration failed");
+            outcome.error = Some(format!("{}: {}", m.id, e));
+            break;
+        }
+        outcome.applied.push(m.id.clone());
+    }
+    outcome
+}
+
+async fn begin_target(
+    target: &DataMigrationTarget<'_>,
+    dialect: &dyn Dialect,
+) -> Result<crate::db::pool::DbTransaction, AppError> {
+    let mut tx = target.pool.begin().await?;
+    if target.rls {
+        if let Some(sql) = dialect.set_tenant_session_sql(target.tenant_id) {
+            sqlx::query(&sql).execute(&mut *tx).await?;
+        }
+    }
+    Ok(tx)
+}
+
+async fn insert_rows(
+    target: &DataMigrationTarget<'_>,
+    model: &ResolvedModel,
+    entity: &str,
+    rows: &[HashMap<String, Value>],
+    dialect: &dyn Dialect,
+) -> Result<(), AppError> {
+    let entity = model
+        .entity_by_path(entity)
+        .ok_or_else(|| AppError::BadRequest(format!("unknown entity '{}'", entity)))?;
+    let rls_tenant_id = target.rls.then_some(target.tenant_id);
+    let mut tx = begin_target(target, dialect).await?;
+    {
+        let mut exec = TenantExecutor::conn(&mut tx, dialect);
+        for row in rows {
+            CrudService::create(
+                &mut exec,
+                entity,
+                row,
+                target.schema_override,
+                rls_tenant_id,
+                None,
+                dialect,
+            )
+            .await?;
+        }
+    }
+    tx.commit().await?;
+    Ok(())
+}
+
+async fn run_sql(
+    target: &DataMigrationTarget<'_>,
+    statements: &[String],
+    dialect: &dyn Dialect,
+) -> Result<(), AppError> {
+    let mut tx = begin_target(target, dialect).await?;
+    for statement in statements {
+        let sql = statement
+            .replace("{{schema}}", &quote(target.schema))
+            .replace(
+                "{{tenant_id}}",
+                &format!("'{}'", target.tenant_id.replace('\'', "''")),
+            );
+        sqlx::query(&sql).execute(&mut *tx).await?;
+    }
+    tx.commit().await?;
+    Ok(())
+}
+
+async fn write_kv(
+    config_pool: &Pool,
+    tenant_id: &str,
+    package_id: &str,
+    namespace: &str,
+    entries: &serde_json::Map<String, Value>,
+) -> Result<(), AppError> {
+    let q = qualified_sys_table("_sys_kv_data");
+    let mut tx = config_pool.begin().await?;
+    for (key, value) in entries {
+        sqlx::query(&format!(
+            "DELETE FROM {} WHERE tenant_id = $1 AND package_id = $2 AND namespace = $3 AND key = $4",
+            q
+        ))
+        .bind(tenant_id)
+        .bind(package_id)
+        .bind(namespace)
+        .bind(key)
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query(&format!(
+            "INSERT INTO {} (tenant_id, package_id, namespace, key, value, updated_at) \
+             VALUES ($1, $2, $3, $4, $5, CURRENT_TIMESTAMP)",
+            q
+        ))
+        .bind(tenant_id)
+        .bind(package_id)
+        .bind(namespace)
+        .bind(key)
+        .bind(value)
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod data_migration_tests {
+    use super::*;
+
+    fn files(entries: &[(&str, &str)]) -> Vec<(String, String)> {
+        entries
+            .iter()
+            .map(|(p, c)| (p.to_string(), c.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn files_are_ordered_by_id_and_dialect_specific_sql_wins() {
+        let dialect = crate::db::active_dialect();
+        let own = format!("data/002_fix.{}.sql", dialect.name());
+        let other = DIALECT_SUFFIXES
+            .iter()
+            .find(|d| **d != dialect.name())
+            .unwrap();
+        let parsed = parse_data_migrations(
+            &files(&[
+                ("data/002_fix.sql", "UPDATE a SET b = 1"),
+                (&own, "UPDATE a SET b = 2; UPDATE a SET c = ';'"),
+                (&format!("data/003_only.{}.sql", other), "SELECT 1"),
+                (
+                    "data/001_countries.json",
+                    r#"{"entity": "countries", "rows": [{"code": "NL"}]}"#,
+                ),
+                ("manifest.json", "{}"),
+            ]),
+            dialect.as_ref(),
+        )
+        .unwrap();
+        let ids: Vec<&str> = parsed.iter().map(|m| m.id.as_str()).collect();
+        assert_eq!(ids, vec!["001_countries", "002_fix"]);
+        assert_eq!(
+            parsed[1].body,
+            DataMigrationBody::Sql {
+                statements: vec!["UPDATE a SET b = 2".into(), "UPDATE a SET c = ';'".into()]
+            }
+        );
+        assert_eq!(parsed[0].checksum.len(), 64);
+    }
+
+    #[test]
+    fn json_files_need_exactly_one_target() {
+        let dialect = crate::db::active_dialect();
+        let parse = |content: &str| {
+            parse_data_migrations(&files(&[("data/x.json", content)]), dialect.as_ref())
+        };
+        assert!(parse(r#"{"rows": []}"#).is_err());
+        assert!(parse(r#"{"entity": "a", "kv_namespace": "b"}"#).is_err());
+        assert!(matches!(
+            parse(r#"{"kv_namespace": "settings", "entries": {"theme": "dark"}}"#).unwrap()[0].body,
+            DataMigrationBody::Kv { .. }
+        ));
+    }
+}
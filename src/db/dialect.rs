@@ -61,6 +61,30 @@ pub trait Dialect: Send + Sync + 'static {
     /// `set_pairs`: pre-built "col = value" pairs for the update branch.
     fn upsert_conflict(&self, conflict_cols: &[&str], set_pairs: &str) -> String;
 
+    /// Convert `expr` to the column type `ddl_type` on assignment (`SET col = …`).
+    /// Postgres/SQLite: `CAST(expr AS type)`.  MySQL: unchanged — assignment converts implicitly
+    /// and `CAST` only accepts a handful of target types.
+    fn assignment_cast(&self, expr: &str, ddl_type: &str) -> String {
+        format!("CAST({} AS {})", expr, ddl_type)
+    }
+
+    /// One batch of a data backfill: update at most `limit` rows of `table` matching `filter`.
+    /// `key` is the (already quoted) primary key expression used to pick the batch; re-running
+    /// the statement until it touches no rows processes the whole table.
+    fn batched_update_sql(
+        &self,
+        table: &str,
+        set: &str,
+        filter: &str,
+        key: &str,
+        limit: u64,
+    ) -> String {
+        format!(
+            "UPDATE {} SET {} WHERE {} IN (SELECT {} FROM {} WHERE {} LIMIT {})",
+            table, set, key, key, table, filter, limit
+        )
+    }
+
     // ── JSON aggregation (related-entity includes) ────────────────────────────
 
     /// Build a scalar subquery returning a single JSON object for a to-one include.
@@ -87,6 +111,13 @@ pub trait Dialect: Send + Sync + 'static {
     /// (SQLite LIKE is case-insensitive for ASCII by default).
     fn case_insensitive_like(&self, col: &str, placeholder: &str) -> String;
 
+    /// Condition: the JSON array in `col` holds the string bound to `placeholder`.
+    /// Postgres: `col @> jsonb_build_array(ph::text)`.  MySQL: `JSON_CONTAINS`.  SQLite:
+    /// `json_each`.
+    fn json_array_contains_text(&self, col: &str, placeholder: &str) -> String {
+        format!("{} @> jsonb_build_array({}::text)", col, placeholder)
+    }
+
     // ── System-table DDL helpers ──────────────────────────────────────────────
 
     /// DDL fragment for a JSON/JSONB payload column (e.g. "JSONB", "JSON", "TEXT").
@@ -129,6 +160,12 @@ pub trait Dialect: Send + Sync + 'static {
         Some(format!("NOW() + INTERVAL '{} hours'", hours))
     }
 
+    /// Expression for the time `seconds` before now, comparable with columns defaulting to
+    /// [`Dialect::now_fn`].
+    fn now_minus_seconds(&self, seconds: i64) -> String {
+        format!("NOW() - INTERVAL '{} seconds'", seconds)
+    }
+
     /// Whether this dialect natively supports row-level security (CREATE POLICY etc.).
     fn supports_rls(&self) -> bool;
 
@@ -156,6 +193,34 @@ pub trait Dialect: Send + Sync + 'static {
         false
     }
 
+    /// Whether the dialect supports the lock-minimising migration mode: `CREATE INDEX
+    /// CONCURRENTLY`, `NOT VALID` constraints with a separate `VALIDATE CONSTRAINT`, and
+    /// per-session `lock_timeout` / `statement_timeout`. Postgres: true.
+    fn supports_online_ddl(&self) -> bool {
+        false
+    }
+
+    /// A query returning a read replica's replay lag in milliseconds (`f64`), 0 when caught up or
+    /// not a replica. `None` when the dialect cannot report lag; replicas are then only checked for
+    /// reachability (see [`crate::replica`]).
+    fn replica_lag_sql(&self) -> Option<&'static str> {
+        None
+    }
+
+    /// Whether DDL statements take part in transactions, so a whole migration plan can run in
+    /// one transaction and roll back as a unit. Postgres and SQLite: true. MySQL: false (every
+    /// DDL statement commits implicitly).
+    fn supports_transactional_ddl(&self) -> bool {
+        false
+    }
+
+    /// `DROP INDEX` for an index on `table`. All three names arrive already quoted.
+    /// Postgres/SQLite: `DROP INDEX IF EXISTS schema.index`.  MySQL: `DROP INDEX index ON table`.
+    fn drop_index_sql(&self, schema: &str, table: &str, index: &str) -> String {
+        let _ = table;
+        format!("DROP INDEX IF EXISTS {}.{}", schema, index)
+    }
+
     // ── Introspection ─────────────────────────────────────────────────────────
     //
     // These queries feed `db::introspect`, which lets the migration executor skip steps whose
@@ -173,20 +238,77 @@ pub trait Dialect: Send + Sync + 'static {
         )
     }
 
-    /// One row per index in `schema`: `(index_name)`. `None` when the dialect cannot report them.
+    /// One row per index in `schema`: `(index_name, table_name)`. `None` when the dialect cannot
+    /// report them.
     fn introspect_indexes_sql(&self, _schema: &str) -> Option<String> {
         None
     }
 
-    /// One row per table constraint in `schema`: `(table_name, constraint_name)`.
-    /// `None` when the dialect cannot report them.
+    /// One row per table constraint in `schema`:
+    /// `(table_name, constraint_name, constraint_type)`, where the type is one of `PRIMARY KEY`,
+    /// `UNIQUE`, `FOREIGN KEY` or `CHECK`. `None` when the dialect cannot report them.
     fn introspect_constraints_sql(&self, schema: &str) -> Option<String> {
         Some(format!(
-            "SELECT table_name, constraint_name FROM information_schema.table_constraints \
+            "SELECT table_name, constraint_name, constraint_type \
+             FROM information_schema.table_constraints \
              WHERE constraint_schema = '{}'",
             escape_literal(schema)
         ))
     }
+
+    // ── Catalog introspection ─────────────────────────────────────────────────
+    //
+    // Richer queries feeding `db::introspect::introspect_catalog`, which reads enough of an
+    // existing schema to generate a package from it. Same rule as above: every value comes back
+    // as text (or NULL), and rows are ordered so multi-column keys read in declaration order.
+    // `None` means the dialect cannot report that part of the catalog.
+
+    /// One row per column in `schema`, ordered by table then ordinal position:
+    /// `(table_name, column_name, full_type, is_nullable 'YES'/'NO', default_expr or NULL,
+    /// auto_increment 'YES'/'NO')`. The type carries its parameters (`varchar(64)`,
+    /// `numeric(10,2)`); the default is the SQL expression as the database reports it.
+    fn catalog_columns_sql(&self, _schema: &str) -> Option<String> {
+        None
+    }
+
+    /// One row per column of every PRIMARY KEY and UNIQUE constraint in `schema`, ordered by
+    /// table, constraint, then key position: `(table_name, constraint_name, constraint_type,
+    /// column_name)`.
+    fn catalog_keys_sql(&self, _schema: &str) -> Option<String> {
+        None
+    }
+
+    /// One row per column of every foreign key in `schema`, ordered by table, constraint, then
+    /// key position: `(table_name, constraint_name, column_name, ref_schema, ref_table,
+    /// ref_column, on_update, on_delete)`. `ref_column` is NULL when the key implicitly
+    /// references the parent's primary key (SQLite).
+    fn catalog_foreign_keys_sql(&self, _schema: &str) -> Option<String> {
+        None
+    }
+
+    /// One row per key of every secondary index in `schema` — indexes backing a primary key,
+    /// unique or foreign key constraint are left out — ordered by table, index, then key
+    /// position: `(table_name, index_name, column_name, expression, is_unique 'YES'/'NO',
+    /// method, where_clause)`. Exactly one of `column_name` / `expression` is non-NULL; both are
+    /// NULL when the dialect cannot print an expression key.
+    fn catalog_indexes_sql(&self, _schema: &str) -> Option<String> {
+        None
+    }
+
+    /// One row per value of every named enum type in `schema`, in declaration order:
+    /// `(enum_name, value)`.
+    fn catalog_enums_sql(&self, _schema: &str) -> Option<String> {
+        None
+    }
+
+    /// Map a physical column type, as reported by [`Dialect::catalog_columns_sql`], back to the
+    /// canonical type whose [`Dialect::ddl_type`] produces it. The default accepts everything
+    /// [`parse_canonical`](super::types::parse_canonical) does.
+    fn canonical_from_physical(&self, physical: &str) -> CanonicalType {
+        super::types::parse_canonical(&crate::config::ColumnTypeConfig::Simple(
+            physical.to_string(),
+        ))
+    }
 }
 
 /// Escape a value for inlining into a single-quoted SQL literal.
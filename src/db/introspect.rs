@@ -9,6 +9,11 @@
 //! [`DbSnapshot`] captures what actually exists so the migration executor can skip those steps
 //! instead of aborting. Everything here is best-effort: if introspection fails the snapshot is
 //! left empty ([`DbSnapshot::introspected`] stays `false`) and every step executes as before.
+//!
+//! [`introspect_catalog`] reads a schema in full — column types and defaults, keys, foreign keys,
+//! secondary indexes and enum types — so a package can be generated from a database that was
+//! never managed by the SDK (see [`crate::reverse`]). Unlike [`introspect`] it fails loudly:
+//! a half-read catalog would produce a package that silently drops objects.
 
 use std::collections::{HashMap, HashSet};
 
@@ -16,6 +21,7 @@ use sqlx::Row;
 
 use super::dialect::Dialect;
 use super::pool::Pool;
+use crate::error::AppError;
 
 /// What the database reports about one column.
 #[derive(Debug, Clone)]
@@ -37,8 +43,12 @@ pub struct DbSnapshot {
     columns: HashMap<String, HashMap<String, ColumnFacts>>,
     /// `schema\u{1}index`, lowercased.
     indexes: HashSet<String>,
-    /// `schema\u{1}table\u{1}constraint`, lowercased.
-    constraints: HashSet<String>,
+    /// `schema\u{1}index` → owning table, lowercased. Only populated by introspection; indexes
+    /// recorded through [`DbSnapshot::add_index`] have no known table.
+    index_tables: HashMap<String, String>,
+    /// `schema\u{1}table\u{1}constraint` → constraint type (`PRIMARY KEY`, `UNIQUE`,
+    /// `FOREIGN KEY`, `CHECK`) when the dialect reports it. Keys lowercased.
+    constraints: HashMap<String, Option<String>>,
     /// Whether at least one introspection query succeeded. When `false`, absence of an object in
     /// this snapshot proves nothing and callers must not skip steps on that basis.
     pub introspected: bool,
@@ -77,13 +87,62 @@ impl DbSnapshot {
     }
 
     pub fn has_constraint(&self, schema: &str, table: &str, constraint: &str) -> bool {
-        self.constraints.contains(&format!(
+        self.constraints.contains_key(&format!(
             "{}\u{1}{}",
             table_key(schema, table),
             constraint.to_lowercase()
         ))
     }
 
+    /// Tables seen in `schema`, lowercased and sorted.
+    pub fn tables(&self, schema: &str) -> Vec<String> {
+        let prefix = format!("{}\u{1}", schema.to_lowercase());
+        let mut out: Vec<String> = self
+            .columns
+            .keys()
+            .filter_map(|k| k.strip_prefix(&prefix).map(String::from))
+            .collect();
+        out.sort();
+        out
+    }
+
+    /// Columns of one table, lowercased and sorted by name. Empty when the table is unknown.
+    pub fn columns_of(&self, schema: &str, table: &str) -> Vec<(&str, &ColumnFacts)> {
+        let mut out: Vec<(&str, &ColumnFacts)> = self
+            .columns
+            .get(&table_key(schema, table))
+            .map(|cols| cols.iter().map(|(n, f)| (n.as_str(), f)).collect())
+            .unwrap_or_default();
+        out.sort_by(|a, b| a.0.cmp(b.0));
+        out
+    }
+
+    /// Indexes introspected on one table, lowercased and sorted.
+    pub fn indexes_on(&self, schema: &str, table: &str) -> Vec<&str> {
+        let prefix = format!("{}\u{1}", schema.to_lowercase());
+        let table = table.to_lowercase();
+        let mut out: Vec<&str> = self
+            .index_tables
+            .iter()
+            .filter(|(k, t)| **t == table && k.starts_with(&prefix))
+            .map(|(k, _)| &k[prefix.len()..])
+            .collect();
+        out.sort();
+        out
+    }
+
+    /// Constraints on one table as `(name, type)`, lowercased and sorted by name.
+    pub fn constraints_on(&self, schema: &str, table: &str) -> Vec<(&str, Option<&str>)> {
+        let prefix = format!("{}\u{1}", table_key(schema, table));
+        let mut out: Vec<(&str, Option<&str>)> = self
+            .constraints
+            .iter()
+            .filter_map(|(k, kind)| k.strip_prefix(&prefix).map(|name| (name, kind.as_deref())))
+            .collect();
+        out.sort_by(|a, b| a.0.cmp(b.0));
+        out
+    }
+
     // ── Mutators — keep the snapshot in step with DDL as it is executed ───────
 
     pub fn add_column(&mut self, schema: &str, table: &str, column: &str, facts: ColumnFacts) {
@@ -137,20 +196,47 @@ impl DbSnapshot {
         ));
     }
 
+    /// Record an index together with the table it belongs to.
+    pub fn add_index_on(&mut self, schema: &str, table: &str, index: &str) {
+        self.add_index(schema, index);
+        self.index_tables.insert(
+            format!("{}\u{1}{}", schema.to_lowercase(), index.to_lowercase()),
+            table.to_lowercase(),
+        );
+    }
+
     pub fn remove_index(&mut self, schema: &str, index: &str) {
-        self.indexes.remove(&format!(
-            "{}\u{1}{}",
-            schema.to_lowercase(),
-            index.to_lowercase()
-        ));
+        let key = format!("{}\u{1}{}", schema.to_lowercase(), index.to_lowercase());
+        self.indexes.remove(&key);
+        self.index_tables.remove(&key);
     }
 
     pub fn add_constraint(&mut self, schema: &str, table: &str, constraint: &str) {
-        self.constraints.insert(format!(
-            "{}\u{1}{}",
-            table_key(schema, table),
-            constraint.to_lowercase()
-        ));
+        self.constraints
+            .entry(format!(
+                "{}\u{1}{}",
+                table_key(schema, table),
+                constraint.to_lowercase()
+            ))
+            .or_insert(None);
+    }
+
+    /// Record a constraint together with its type (`FOREIGN KEY`, `CHECK`, …).
+    pub fn add_constraint_of_type(
+        &mut self,
+        schema: &str,
+        table: &str,
+        constraint: &str,
+        constraint_type: &str,
+    ) {
+        self.constraints.insert(
+            format!(
+                "{}\u{1}{}",
+                table_key(schema, table),
+                constraint.to_lowercase()
+            ),
+            Some(constraint_type.to_uppercase()),
+        );
     }
 
     pub fn remove_constraint(&mut self, schema: &str, table: &str, constraint: &str) {
@@ -207,8 +293,12 @@ pub async fn introspect(pool: &Pool, dialect: &dyn Dialect, schemas: &[String])
             Some(sql) => match sqlx::query(&sql).fetch_all(pool).await {
                 Ok(rows) => {
                     for row in rows {
-                        if let Ok(name) = row.try_get::<String, _>(0) {
-                            snap.add_index(schema, &name);
+                        let Ok(name) = row.try_get::<String, _>(0) else {
+                            continue;
+                        };
+                        match row.try_get::<String, _>(1) {
+                            Ok(table) => snap.add_index_on(schema, &table, &name),
+                            Err(_) => snap.add_index(schema, &name),
                         }
                     }
                 }
@@ -224,9 +314,14 @@ pub async fn introspect(pool: &Pool, dialect: &dyn Dialect, schemas: &[String])
             Some(sql) => match sqlx::query(&sql).fetch_all(pool).await {
                 Ok(rows) => {
                     for row in rows {
-                        match (row.try_get::<String, _>(0), row.try_get::<String, _>(1)) {
-                            (Ok(table), Ok(name)) => snap.add_constraint(schema, &table, &name),
-                            _ => continue,
+                        let (table, name) =
+                            match (row.try_get::<String, _>(0), row.try_get::<String, _>(1)) {
+                                (Ok(t), Ok(n)) => (t, n),
+                                _ => continue,
+                            };
+                        match row.try_get::<String, _>(2) {
+                            Ok(kind) => snap.add_constraint_of_type(schema, &table, &name, &kind),
+                            Err(_) => snap.add_constraint(schema, &table, &name),
                         }
                     }
                 }
@@ -242,6 +337,263 @@ pub async fn introspect(pool: &Pool, dialect: &dyn Dialect, schemas: &[String])
     snap
 }
 
+// ─── Full catalog ─────────────────────────────────────────────────────────────
+
+/// One column as the catalog reports it. Names keep their original case.
+#[derive(Debug, Clone)]
+pub struct CatalogColumn {
+    pub name: String,
+    /// Physical type with parameters (`character varying(64)`, `decimal(10,2)`, `INTEGER`).
+    pub data_type: String,
+    pub nullable: bool,
+    /// DEFAULT as an SQL expression (`'active'::text`, `now()`), `None` when there is none.
+    pub default: Option<String>,
+    /// Serial/identity (Postgres), AUTO_INCREMENT (MySQL) or rowid alias (SQLite).
+    pub auto_increment: bool,
+}
+
+/// One foreign key. `ref_columns` is empty when the key implicitly references the parent's
+/// primary key.
+#[derive(Debug, Clone)]
+pub struct CatalogForeignKey {
+    pub name: String,
+    pub columns: Vec<String>,
+    /// `None` when the parent lives in the same schema (or the dialect has no schemas).
+    pub ref_schema: Option<String>,
+    pub ref_table: String,
+    pub ref_columns: Vec<String>,
+    /// Referential actions, uppercased; `None` for the default `NO ACTION`.
+    pub on_update: Option<String>,
+    pub on_delete: Option<String>,
+}
+
+/// One key of a secondary index.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CatalogIndexKey {
+    Column(String),
+    Expression(String),
+}
+
+/// A secondary index — one that does not back a primary key, unique or foreign key constraint.
+#[derive(Debug, Clone)]
+pub struct CatalogIndex {
+    pub name: String,
+    pub keys: Vec<CatalogIndexKey>,
+    pub unique: bool,
+    /// Access method (`btree`, `gin`, `hash`) when the dialect reports one.
+    pub method: Option<String>,
+    /// Predicate of a partial index.
+    pub where_clause: Option<String>,
+    /// Some key could not be read (an expression the dialect does not print). Such an index
+    /// cannot be reproduced faithfully.
+    pub incomplete: bool,
+}
+
+/// One table with everything hanging off it, columns in ordinal order.
+#[derive(Debug, Clone, Default)]
+pub struct CatalogTable {
+    pub name: String,
+    pub columns: Vec<CatalogColumn>,
+    pub primary_key: Vec<String>,
+    /// Column sets of UNIQUE constraints, in constraint-name order.
+    pub unique: Vec<Vec<String>>,
+    pub foreign_keys: Vec<CatalogForeignKey>,
+    pub indexes: Vec<CatalogIndex>,
+}
+
+impl CatalogTable {
+    pub fn column(&self, name: &str) -> Option<&CatalogColumn> {
+        self.columns
+            .iter()
+            .find(|c| c.name.eq_ignore_ascii_case(name))
+    }
+}
+
+/// A named enum type and its values in declaration order.
+#[derive(Debug, Clone)]
+pub struct CatalogEnum {
+    pub name: String,
+    pub values: Vec<String>,
+}
+
+/// The full catalog of one schema, tables sorted by name.
+#[derive(Debug, Clone, Default)]
+pub struct DbCatalog {
+    pub schema: String,
+    pub tables: Vec<CatalogTable>,
+    pub enums: Vec<CatalogEnum>,
+}
+
+impl DbCatalog {
+    pub fn table(&self, name: &str) -> Option<&CatalogTable> {
+        self.tables
+            .iter()
+            .find(|t| t.name.eq_ignore_ascii_case(name))
+    }
+}
+
+fn text(row: &super::pool::DbRow, idx: usize) -> Option<String> {
+    row.try_get::<Option<String>, _>(idx).ok().flatten()
+}
+
+fn yes(row: &super::pool::DbRow, idx: usize) -> bool {
+    text(row, idx).is_some_and(|v| v.eq_ignore_ascii_case("YES"))
+}
+
+/// `NO ACTION` is what every dialect does when no action is given, so it is not recorded.
+fn referential_action(v: Option<String>) -> Option<String> {
+    v.map(|a| a.trim().to_uppercase())
+        .filter(|a| !a.is_empty() && a != "NO ACTION")
+}
+
+/// Read the full catalog of `schema` from `pool`.
+///
+/// Tables come from [`Dialect::catalog_columns_sql`], which every dialect must support; the
+/// other parts are read when the dialect reports them and left empty otherwise.
+pub async fn introspect_catalog(
+    pool: &Pool,
+    dialect: &dyn Dialect,
+    schema: &str,
+) -> Result<DbCatalog, AppError> {
+    let sql = dialect.catalog_columns_sql(schema).ok_or_else(|| {
+        AppError::BadRequest(format!(
+            "{} does not support catalog introspection",
+            dialect.name()
+        ))
+    })?;
+
+    // Keep first-seen order per table; tables themselves are sorted at the end.
+    let mut tables: Vec<CatalogTable> = Vec::new();
+    let mut by_name: HashMap<String, usize> = HashMap::new();
+    for row in sqlx::query(&sql).fetch_all(pool).await? {
+        let (Some(table), Some(column)) = (text(&row, 0), text(&row, 1)) else {
+            continue;
+        };
+        let idx = *by_name.entry(table.clone()).or_insert_with(|| {
+            tables.push(CatalogTable {
+                name: table.clone(),
+                ..Default::default()
+            });
+            tables.len() - 1
+        });
+        tables[idx].columns.push(CatalogColumn {
+            name: column,
+            data_type: text(&row, 2).unwrap_or_default(),
+            nullable: yes(&row, 3),
+            default: text(&row, 4),
+            auto_increment: yes(&row, 5),
+        });
+    }
+
+    if let Some(sql) = dialect.catalog_keys_sql(schema) {
+        // Rows arrive grouped by (table, constraint); a new group starts a new key.
+        let mut last: Option<(String, String)> = None;
+        for row in sqlx::query(&sql).fetch_all(pool).await? {
+            let (Some(table), Some(name), Some(kind), Some(column)) =
+                (text(&row, 0), text(&row, 1), text(&row, 2), text(&row, 3))
+            else {
+                continue;
+            };
+            let Some(&idx) = by_name.get(&table) else {
+                continue;
+            };
+            let t = &mut tables[idx];
+            if kind.eq_ignore_ascii_case("PRIMARY KEY") {
+                t.primary_key.push(column);
+            } else {
+                let key = (table, name);
+                if last.as_ref() != Some(&key) {
+                    t.unique.push(Vec::new());
+                }
+                t.unique.last_mut().expect("pushed above").push(column);
+                last = Some(key);
+            }
+        }
+    }
+
+    if let Some(sql) = dialect.catalog_foreign_keys_sql(schema) {
+        for row in sqlx::query(&sql).fetch_all(pool).await? {
+            let (Some(table), Some(name), Some(column), Some(ref_table)) =
+                (text(&row, 0), text(&row, 1), text(&row, 2), text(&row, 4))
+            else {
+                continue;
+            };
+            let Some(&idx) = by_name.get(&table) else {
+                continue;
+            };
+            let fks = &mut tables[idx].foreign_keys;
+            if fks.last().map(|fk| fk.name != name).unwrap_or(true) {
+                let ref_schema = text(&row, 3).filter(|s| !s.is_empty() && s != schema);
+                fks.push(CatalogForeignKey {
+                    name,
+                    columns: Vec::new(),
+                    ref_schema,
+                    ref_table,
+                    ref_columns: Vec::new(),
+                    on_update: referential_action(text(&row, 6)),
+                    on_delete: referential_action(text(&row, 7)),
+                });
+            }
+            let fk = fks.last_mut().expect("pushed above");
+            fk.columns.push(column);
+            if let Some(ref_column) = text(&row, 5) {
+                fk.ref_columns.push(ref_column);
+            }
+        }
+    }
+
+    if let Some(sql) = dialect.catalog_indexes_sql(schema) {
+        for row in sqlx::query(&sql).fetch_all(pool).await? {
+            let (Some(table), Some(name)) = (text(&row, 0), text(&row, 1)) else {
+                continue;
+            };
+            let Some(&idx) = by_name.get(&table) else {
+                continue;
+            };
+            let indexes = &mut tables[idx].indexes;
+            if indexes.last().map(|i| i.name != name).unwrap_or(true) {
+                indexes.push(CatalogIndex {
+                    name,
+                    keys: Vec::new(),
+                    unique: yes(&row, 4),
+                    method: text(&row, 5).map(|m| m.to_lowercase()),
+                    where_clause: text(&row, 6).map(|w| w.trim().to_string()),
+                    incomplete: false,
+                });
+            }
+            let index = indexes.last_mut().expect("pushed above");
+            match (text(&row, 2), text(&row, 3)) {
+                (Some(column), _) => index.keys.push(CatalogIndexKey::Column(column)),
+                (None, Some(expr)) => index.keys.push(CatalogIndexKey::Expression(expr)),
+                (None, None) => index.incomplete = true,
+            }
+        }
+    }
+
+    let mut enums: Vec<CatalogEnum> = Vec::new();
+    if let Some(sql) = dialect.catalog_enums_sql(schema) {
+        for row in sqlx::query(&sql).fetch_all(pool).await? {
+            let (Some(name), Some(value)) = (text(&row, 0), text(&row, 1)) else {
+                continue;
+            };
+            if enums.last().map(|e| e.name != name).unwrap_or(true) {
+                enums.push(CatalogEnum {
+                    name,
+                    values: Vec::new(),
+                });
+            }
+            enums.last_mut().expect("pushed above").values.push(value);
+        }
+    }
+
+    tables.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(DbCatalog {
+        schema: schema.to_string(),
+        tables,
+        enums,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -288,4 +640,35 @@ mod tests {
         assert!(snap.has_constraint("APP", "Orders", "fk_orders_user"));
         assert!(!snap.has_constraint("app", "users", "fk_orders_user"));
     }
+
+    #[test]
+    fn per_table_listings() {
+        let mut snap = DbSnapshot::default();
+        snap.add_column("app", "orders", "b", facts());
+        snap.add_column("app", "orders", "a", facts());
+        snap.add_table("app", "users");
+        snap.add_table("other", "ignored");
+        snap.add_index_on("app", "Orders", "orders_a_idx");
+        snap.add_index("app", "tableless_idx");
+        snap.add_constraint_of_type("app", "orders", "fk_orders_user", "foreign key");
+        snap.add_constraint("app", "orders", "orders_chk");
+
+        assert_eq!(snap.tables("APP"), vec!["orders", "users"]);
+        let cols: Vec<&str> = snap
+            .columns_of("app", "orders")
+            .iter()
+            .map(|c| c.0)
+            .collect();
+        assert_eq!(cols, vec!["a", "b"]);
+        assert_eq!(snap.indexes_on("app", "orders"), vec!["orders_a_idx"]);
+        assert_eq!(
+            snap.constraints_on("app", "orders"),
+            vec![
+                ("fk_orders_user", Some("FOREIGN KEY")),
+                ("orders_chk", None)
+            ]
+        );
+        snap.remove_index("app", "orders_a_idx");
+        assert!(snap.indexes_on("app", "orders").is_empty());
+    }
 }
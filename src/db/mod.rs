@@ -6,8 +6,9 @@
 //! # Adding a new dialect
 //! 1. Add a Cargo feature (e.g. `mysql`).
 //! 2. Create `src/db/your_dialect.rs` implementing [`Dialect`].
-//! 3. Gate it with `#[cfg(feature = "your_dialect")]` below.
-//! 4. Add it to `active_dialect()`.
+//! 3. Declare the module below. Dialects are plain SQL builders and are always compiled, so
+//!    DDL can be rendered for any of them (see `migration::render_migrations`).
+//! 4. Add it to `active_dialect()` (gated by its feature) and `dialect_by_name()`.
 //! 5. Add to Cargo.toml features.
 
 pub mod dialect;
@@ -16,25 +17,22 @@ pub mod pool;
 pub mod types;
 
 pub use dialect::Dialect;
-pub use introspect::{introspect, ColumnFacts, DbSnapshot};
+pub use introspect::{
+    introspect, introspect_catalog, CatalogColumn, CatalogEnum, CatalogForeignKey, CatalogIndex,
+    CatalogIndexKey, CatalogTable, ColumnFacts, DbCatalog, DbSnapshot,
+};
 pub use types::{
-    active_cast_name, parse_canonical, type_category, type_category_from_cast, CanonicalType,
-    TypeCategory, TypeSupport,
+    active_cast_name, canonical_name, parse_canonical, type_category, type_category_from_cast,
+    CanonicalType, TypeCategory, TypeSupport,
 };
 
-#[cfg(feature = "postgres")]
 pub mod postgres;
-#[cfg(feature = "postgres")]
 pub use postgres::PostgresDialect;
 
-#[cfg(feature = "mysql")]
 pub mod mysql;
-#[cfg(feature = "mysql")]
 pub use mysql::MySqlDialect;
 
-#[cfg(feature = "sqlite")]
 pub mod sqlite;
-#[cfg(feature = "sqlite")]
 pub use sqlite::SqliteDialect;
 
 /// Construct the compiled-in dialect as a shared reference.
@@ -43,6 +41,16 @@ pub fn active_dialect() -> std::sync::Arc<dyn Dialect> {
     _active_dialect_impl()
 }
 
+/// A dialect by [`Dialect::name`] (`postgres`, `mysql`, `sqlite`), compiled in or not.
+pub fn dialect_by_name(name: &str) -> Option<std::sync::Arc<dyn Dialect>> {
+    match name {
+        "postgres" => Some(std::sync::Arc::new(PostgresDialect)),
+        "mysql" => Some(std::sync::Arc::new(MySqlDialect)),
+        "sqlite" => Some(std::sync::Arc::new(SqliteDialect)),
+        _ => None,
+    }
+}
+
 #[cfg(feature = "postgres")]
 fn _active_dialect_impl() -> std::sync::Arc<dyn Dialect> {
     std::sync::Arc::new(PostgresDialect)
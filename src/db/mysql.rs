@@ -121,6 +121,29 @@ impl Dialect for MySqlDialect {
         format!("ON DUPLICATE KEY UPDATE {}", set_pairs)
     }
 
+    fn assignment_cast(&self, expr: &str, _ddl_type: &str) -> String {
+        expr.to_string()
+    }
+
+    fn batched_update_sql(
+        &self,
+        table: &str,
+        set: &str,
+        filter: &str,
+        _key: &str,
+        limit: u64,
+    ) -> String {
+        // MySQL rejects a subquery on the table being updated; single-table UPDATE takes LIMIT.
+        format!(
+            "UPDATE {} SET {} WHERE {} LIMIT {}",
+            table, set, filter, limit
+        )
+    }
+
+    fn drop_index_sql(&self, schema: &str, table: &str, index: &str) -> String {
+        format!("DROP INDEX {} ON {}.{}", index, schema, table)
+    }
+
     fn to_one_subquery(&self, col_exprs: &[String], from_clause: &str) -> String {
         let pairs = col_exprs
             .iter()
@@ -161,6 +184,10 @@ impl Dialect for MySqlDialect {
         format!("LOWER({}) LIKE LOWER({})", col, placeholder)
     }
 
+    fn json_array_contains_text(&self, col: &str, placeholder: &str) -> String {
+        format!("JSON_CONTAINS({}, JSON_QUOTE({}))", col, placeholder)
+    }
+
     fn sys_json_type(&self) -> &'static str {
         "JSON"
     }
@@ -181,6 +208,10 @@ impl Dialect for MySqlDialect {
         "DATETIME(6)"
     }
 
+    fn now_minus_seconds(&self, seconds: i64) -> String {
+        format!("NOW(6) - INTERVAL {} SECOND", seconds)
+    }
+
     fn supports_rls(&self) -> bool {
         false
     }
@@ -220,7 +251,7 @@ impl Dialect for MySqlDialect {
 
     fn introspect_indexes_sql(&self, schema: &str) -> Option<String> {
         Some(format!(
-            "SELECT DISTINCT INDEX_NAME FROM information_schema.STATISTICS \
+            "SELECT DISTINCT INDEX_NAME, TABLE_NAME FROM information_schema.STATISTICS \
              WHERE TABLE_SCHEMA = '{}'",
             escape_literal(schema)
         ))
@@ -228,9 +259,101 @@ impl Dialect for MySqlDialect {
 
     fn introspect_constraints_sql(&self, schema: &str) -> Option<String> {
         Some(format!(
-            "SELECT TABLE_NAME, CONSTRAINT_NAME FROM information_schema.TABLE_CONSTRAINTS \
+            "SELECT TABLE_NAME, CONSTRAINT_NAME, CONSTRAINT_TYPE \
+             FROM information_schema.TABLE_CONSTRAINTS \
              WHERE CONSTRAINT_SCHEMA = '{}'",
             escape_literal(schema)
         ))
     }
+
+    fn catalog_columns_sql(&self, schema: &str) -> Option<String> {
+        // COLUMN_DEFAULT holds the bare value for literal defaults and the expression for
+        // DEFAULT_GENERATED ones; literals are re-quoted so every dialect reports SQL.
+        Some(format!(
+            "SELECT TABLE_NAME, COLUMN_NAME, COLUMN_TYPE, IS_NULLABLE, \
+                    CASE WHEN COLUMN_DEFAULT IS NULL THEN NULL \
+                         WHEN EXTRA LIKE '%DEFAULT_GENERATED%' THEN COLUMN_DEFAULT \
+                         ELSE CONCAT('''', REPLACE(COLUMN_DEFAULT, '''', ''''''), '''') END, \
+                    CASE WHEN EXTRA LIKE '%auto_increment%' THEN 'YES' ELSE 'NO' END \
+             FROM information_schema.COLUMNS WHERE TABLE_SCHEMA = '{}' \
+             ORDER BY TABLE_NAME, ORDINAL_POSITION",
+            escape_literal(schema)
+        ))
+    }
+
+    fn catalog_keys_sql(&self, schema: &str) -> Option<String> {
+        Some(format!(
+            "SELECT k.TABLE_NAME, k.CONSTRAINT_NAME, c.CONSTRAINT_TYPE, k.COLUMN_NAME \
+             FROM information_schema.KEY_COLUMN_USAGE k \
+             JOIN information_schema.TABLE_CONSTRAINTS c \
+               ON c.CONSTRAINT_SCHEMA = k.CONSTRAINT_SCHEMA AND c.TABLE_NAME = k.TABLE_NAME \
+              AND c.CONSTRAINT_NAME = k.CONSTRAINT_NAME \
+             WHERE k.TABLE_SCHEMA = '{}' AND c.CONSTRAINT_TYPE IN ('PRIMARY KEY', 'UNIQUE') \
+             ORDER BY k.TABLE_NAME, k.CONSTRAINT_NAME, k.ORDINAL_POSITION",
+            escape_literal(schema)
+        ))
+    }
+
+    fn catalog_foreign_keys_sql(&self, schema: &str) -> Option<String> {
+        Some(format!(
+            "SELECT k.TABLE_NAME, k.CONSTRAINT_NAME, k.COLUMN_NAME, \
+                    k.REFERENCED_TABLE_SCHEMA, k.REFERENCED_TABLE_NAME, k.REFERENCED_COLUMN_NAME, \
+                    r.UPDATE_RULE, r.DELETE_RULE \
+             FROM information_schema.KEY_COLUMN_USAGE k \
+             JOIN information_schema.REFERENTIAL_CONSTRAINTS r \
+               ON r.CONSTRAINT_SCHEMA = k.CONSTRAINT_SCHEMA AND r.TABLE_NAME = k.TABLE_NAME \
+              AND r.CONSTRAINT_NAME = k.CONSTRAINT_NAME \
+             WHERE k.TABLE_SCHEMA = '{}' AND k.REFERENCED_TABLE_NAME IS NOT NULL \
+             ORDER BY k.TABLE_NAME, k.CONSTRAINT_NAME, k.ORDINAL_POSITION",
+            escape_literal(schema)
+        ))
+    }
+
+    fn catalog_indexes_sql(&self, schema: &str) -> Option<String> {
+        // InnoDB names the index backing a constraint after the constraint, so matching names
+        // against TABLE_CONSTRAINTS drops primary, unique and foreign key indexes. Functional
+        // key parts have a NULL COLUMN_NAME; their expression is not read (older servers lack
+        // the EXPRESSION column).
+        Some(format!(
+            "SELECT s.TABLE_NAME, s.INDEX_NAME, s.COLUMN_NAME, NULL, \
+                    CASE WHEN s.NON_UNIQUE = 0 THEN 'YES' ELSE 'NO' END, \
+                    LOWER(s.INDEX_TYPE), NULL \
+             FROM information_schema.STATISTICS s \
+             WHERE s.TABLE_SCHEMA = '{}' \
+               AND NOT EXISTS (SELECT 1 FROM information_schema.TABLE_CONSTRAINTS c \
+                               WHERE c.CONSTRAINT_SCHEMA = s.TABLE_SCHEMA \
+                                 AND c.TABLE_NAME = s.TABLE_NAME \
+                                 AND c.CONSTRAINT_NAME = s.INDEX_NAME) \
+             ORDER BY s.TABLE_NAME, s.INDEX_NAME, s.SEQ_IN_INDEX",
+            escape_literal(schema)
+        ))
+    }
+
+    fn canonical_from_physical(&self, physical: &str) -> CanonicalType {
+        let lower = physical.trim().to_lowercase();
+        // Display widths and sign modifiers do not change the canonical type.
+        let base = lower
+            .trim_end_matches(" zerofill")
+            .trim_end_matches(" unsigned");
+        let name = base.split('(').next().unwrap_or(base).trim();
+        match name {
+            // TINYINT(1) is how BOOLEAN is stored; CHAR(36) is how the SDK stores UUIDs.
+            "tinyint" if base == "tinyint(1)" => CanonicalType::Boolean,
+            "char" if base == "char(36)" => CanonicalType::Uuid,
+            "tinyint" | "smallint" => CanonicalType::SmallInt,
+            "mediumint" | "int" | "integer" => CanonicalType::Int,
+            "bigint" => CanonicalType::BigInt,
+            "float" => CanonicalType::Real,
+            "datetime" | "timestamp" => CanonicalType::Timestamp,
+            "tinytext" | "mediumtext" | "longtext" => CanonicalType::Text,
+            "blob" | "tinyblob" | "mediumblob" | "longblob" | "binary" | "varbinary" => {
+                CanonicalType::Bytes
+            }
+            // Inline ENUM('a','b') stays verbatim; the package generator lifts the values out.
+            "enum" => CanonicalType::Custom(physical.to_string()),
+            _ => super::types::parse_canonical(&crate::config::ColumnTypeConfig::Simple(
+                base.to_string(),
+            )),
+        }
+    }
 }
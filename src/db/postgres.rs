@@ -278,6 +278,25 @@ impl Dialect for PostgresDialect {
         true
     }
 
+    fn supports_online_ddl(&self) -> bool {
+        true
+    }
+
+    /// Time since the last replayed transaction, unless everything received has been replayed
+    /// (an idle primary would otherwise look like a growing lag).
+    fn replica_lag_sql(&self) -> Option<&'static str> {
+        Some(
+            "SELECT (CASE WHEN NOT pg_is_in_recovery() \
+             OR pg_last_wal_receive_lsn() = pg_last_wal_replay_lsn() THEN 0 \
+             ELSE COALESCE(EXTRACT(EPOCH FROM (now() - pg_last_xact_replay_timestamp())) * 1000, 0) \
+             END)::float8",
+        )
+    }
+
+    fn supports_transactional_ddl(&self) -> bool {
+        true
+    }
+
     fn is_duplicate_object_code(&self, code: &str) -> bool {
         matches!(
             code,
@@ -307,22 +326,141 @@ impl Dialect for PostgresDialect {
 
     fn introspect_indexes_sql(&self, schema: &str) -> Option<String> {
         Some(format!(
-            "SELECT c.relname::text FROM pg_class c \
+            "SELECT c.relname::text, t.relname::text FROM pg_index i \
+             JOIN pg_class c ON c.oid = i.indexrelid \
+             JOIN pg_class t ON t.oid = i.indrelid \
              JOIN pg_namespace n ON n.oid = c.relnamespace \
-             WHERE n.nspname = '{}' AND c.relkind IN ('i', 'I')",
+             WHERE n.nspname = '{}'",
             escape_literal(schema)
         ))
     }
 
     fn introspect_constraints_sql(&self, schema: &str) -> Option<String> {
         Some(format!(
-            "SELECT t.relname::text, con.conname::text FROM pg_constraint con \
+            "SELECT t.relname::text, con.conname::text, \
+                    CASE con.contype WHEN 'p' THEN 'PRIMARY KEY' WHEN 'u' THEN 'UNIQUE' \
+                         WHEN 'f' THEN 'FOREIGN KEY' WHEN 'c' THEN 'CHECK' \
+                         ELSE con.contype::text END \
+             FROM pg_constraint con \
              JOIN pg_class t ON t.oid = con.conrelid \
              JOIN pg_namespace n ON n.oid = t.relnamespace \
              WHERE n.nspname = '{}'",
             escape_literal(schema)
         ))
     }
+
+    fn catalog_columns_sql(&self, schema: &str) -> Option<String> {
+        // Serial columns are plain integers whose default draws from a sequence; identity
+        // columns carry `attidentity`. Both count as auto-incrementing.
+        Some(format!(
+            "SELECT c.relname::text, a.attname::text, \
+                    format_type(a.atttypid, a.atttypmod)::text, \
+                    CASE WHEN a.attnotnull THEN 'NO' ELSE 'YES' END, \
+                    pg_get_expr(d.adbin, d.adrelid)::text, \
+                    CASE WHEN a.attidentity <> '' \
+                              OR pg_get_expr(d.adbin, d.adrelid) LIKE 'nextval(%' \
+                         THEN 'YES' ELSE 'NO' END \
+             FROM pg_attribute a \
+             JOIN pg_class c ON c.oid = a.attrelid \
+             JOIN pg_namespace n ON n.oid = c.relnamespace \
+             LEFT JOIN pg_attrdef d ON d.adrelid = a.attrelid AND d.adnum = a.attnum \
+             WHERE n.nspname = '{}' AND c.relkind IN ('r', 'p') \
+               AND a.attnum > 0 AND NOT a.attisdropped \
+             ORDER BY c.relname, a.attnum",
+            escape_literal(schema)
+        ))
+    }
+
+    fn catalog_keys_sql(&self, schema: &str) -> Option<String> {
+        Some(format!(
+            "SELECT t.relname::text, con.conname::text, \
+                    CASE con.contype WHEN 'p' THEN 'PRIMARY KEY' ELSE 'UNIQUE' END, \
+                    a.attname::text \
+             FROM pg_constraint con \
+             JOIN pg_class t ON t.oid = con.conrelid \
+             JOIN pg_namespace n ON n.oid = t.relnamespace \
+             CROSS JOIN LATERAL unnest(con.conkey) WITH ORDINALITY AS k(attnum, pos) \
+             JOIN pg_attribute a ON a.attrelid = con.conrelid AND a.attnum = k.attnum \
+             WHERE n.nspname = '{}' AND con.contype IN ('p', 'u') \
+             ORDER BY t.relname, con.conname, k.pos",
+            escape_literal(schema)
+        ))
+    }
+
+    fn catalog_foreign_keys_sql(&self, schema: &str) -> Option<String> {
+        Some(format!(
+            "SELECT t.relname::text, con.conname::text, a.attname::text, \
+                    rn.nspname::text, rt.relname::text, ra.attname::text, \
+                    CASE con.confupdtype WHEN 'c' THEN 'CASCADE' WHEN 'n' THEN 'SET NULL' \
+                         WHEN 'd' THEN 'SET DEFAULT' WHEN 'r' THEN 'RESTRICT' \
+                         ELSE 'NO ACTION' END, \
+                    CASE con.confdeltype WHEN 'c' THEN 'CASCADE' WHEN 'n' THEN 'SET NULL' \
+                         WHEN 'd' THEN 'SET DEFAULT' WHEN 'r' THEN 'RESTRICT' \
+                         ELSE 'NO ACTION' END \
+             FROM pg_constraint con \
+             JOIN pg_class t ON t.oid = con.conrelid \
+             JOIN pg_namespace n ON n.oid = t.relnamespace \
+             JOIN pg_class rt ON rt.oid = con.confrelid \
+             JOIN pg_namespace rn ON rn.oid = rt.relnamespace \
+             CROSS JOIN LATERAL unnest(con.conkey, con.confkey) \
+                  WITH ORDINALITY AS k(attnum, refnum, pos) \
+             JOIN pg_attribute a ON a.attrelid = con.conrelid AND a.attnum = k.attnum \
+             JOIN pg_attribute ra ON ra.attrelid = con.confrelid AND ra.attnum = k.refnum \
+             WHERE n.nspname = '{}' AND con.contype = 'f' \
+             ORDER BY t.relname, con.conname, k.pos",
+            escape_literal(schema)
+        ))
+    }
+
+    fn catalog_indexes_sql(&self, schema: &str) -> Option<String> {
+        // `pg_get_indexdef(oid, n, true)` prints the n-th key — a column name or an expression.
+        // INCLUDE columns (positions past `indnkeyatts`) are not keys and are left out.
+        Some(format!(
+            "SELECT t.relname::text, c.relname::text, \
+                    CASE WHEN k.attnum <> 0 THEN a.attname::text END, \
+                    CASE WHEN k.attnum = 0 \
+                         THEN pg_get_indexdef(i.indexrelid, k.pos::int, true) END, \
+                    CASE WHEN i.indisunique THEN 'YES' ELSE 'NO' END, \
+                    am.amname::text, \
+                    pg_get_expr(i.indpred, i.indrelid)::text \
+             FROM pg_index i \
+             JOIN pg_class c ON c.oid = i.indexrelid \
+             JOIN pg_class t ON t.oid = i.indrelid \
+             JOIN pg_namespace n ON n.oid = t.relnamespace \
+             JOIN pg_am am ON am.oid = c.relam \
+             CROSS JOIN LATERAL unnest(i.indkey::int2[]) WITH ORDINALITY AS k(attnum, pos) \
+             LEFT JOIN pg_attribute a ON a.attrelid = i.indrelid AND a.attnum = k.attnum \
+             WHERE n.nspname = '{}' AND k.pos <= i.indnkeyatts \
+               AND NOT EXISTS (SELECT 1 FROM pg_constraint con \
+                               WHERE con.conindid = i.indexrelid \
+                                 AND con.contype IN ('p', 'u', 'x')) \
+             ORDER BY t.relname, c.relname, k.pos",
+            escape_literal(schema)
+        ))
+    }
+
+    fn catalog_enums_sql(&self, schema: &str) -> Option<String> {
+        Some(format!(
+            "SELECT t.typname::text, e.enumlabel::text \
+             FROM pg_type t \
+             JOIN pg_enum e ON e.enumtypid = t.oid \
+             JOIN pg_namespace n ON n.oid = t.typnamespace \
+             WHERE n.nspname = '{}' \
+             ORDER BY t.typname, e.enumsortorder",
+            escape_literal(schema)
+        ))
+    }
+
+    fn canonical_from_physical(&self, physical: &str) -> CanonicalType {
+        // `format_type` spells TIMESTAMP as "timestamp without time zone", which the canonical
+        // parser treats as the zoned default.
+        match physical.trim().to_lowercase().as_str() {
+            "timestamp without time zone" | "timestamp" => CanonicalType::TimestampNtz,
+            _ => super::types::parse_canonical(&crate::config::ColumnTypeConfig::Simple(
+                physical.to_string(),
+            )),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -163,6 +163,13 @@ impl Dialect for SqliteDialect {
         format!("{} LIKE {}", col, placeholder)
     }
 
+    fn json_array_contains_text(&self, col: &str, placeholder: &str) -> String {
+        format!(
+            "EXISTS (SELECT 1 FROM json_each({}) WHERE json_each.value = {})",
+            col, placeholder
+        )
+    }
+
     fn sys_json_type(&self) -> &'static str {
         "TEXT"
     }
@@ -192,6 +199,11 @@ impl Dialect for SqliteDialect {
         None
     }
 
+    fn now_minus_seconds(&self, seconds: i64) -> String {
+        // Same text format as CURRENT_TIMESTAMP, so the comparison is by time.
+        format!("datetime('now', '-{} seconds')", seconds)
+    }
+
     fn supports_rls(&self) -> bool {
         false
     }
@@ -210,6 +222,10 @@ impl Dialect for SqliteDialect {
 
     // ── Idempotent DDL / introspection ────────────────────────────────────────
 
+    fn supports_transactional_ddl(&self) -> bool {
+        true
+    }
+
     fn is_duplicate_object_code(&self, _code: &str) -> bool {
         // SQLite reports every error as SQLITE_ERROR (code "1"); the executor falls back to
         // message matching ("duplicate column name", "already exists") for this dialect.
@@ -227,11 +243,104 @@ impl Dialect for SqliteDialect {
     }
 
     fn introspect_indexes_sql(&self, _schema: &str) -> Option<String> {
-        Some("SELECT name FROM sqlite_master WHERE type = 'index' AND name IS NOT NULL".to_string())
+        Some(
+            "SELECT name, tbl_name FROM sqlite_master WHERE type = 'index' AND name IS NOT NULL"
+                .to_string(),
+        )
     }
 
     fn introspect_constraints_sql(&self, _schema: &str) -> Option<String> {
         // SQLite does not expose constraint names in a queryable catalog.
         None
     }
+
+    fn catalog_columns_sql(&self, _schema: &str) -> Option<String> {
+        // A lone INTEGER PRIMARY KEY column is an alias for the rowid and auto-increments.
+        Some(
+            "SELECT m.name, p.name, p.type, \
+                    CASE WHEN p.\"notnull\" = 0 THEN 'YES' ELSE 'NO' END, \
+                    p.dflt_value, \
+                    CASE WHEN p.pk = 1 AND upper(p.type) = 'INTEGER' \
+                              AND (SELECT count(*) FROM pragma_table_info(m.name) k \
+                                   WHERE k.pk > 0) = 1 \
+                         THEN 'YES' ELSE 'NO' END \
+             FROM sqlite_master m JOIN pragma_table_info(m.name) p \
+             WHERE m.type = 'table' AND m.name NOT LIKE 'sqlite\\_%' ESCAPE '\\' \
+             ORDER BY m.name, p.cid"
+                .to_string(),
+        )
+    }
+
+    fn catalog_keys_sql(&self, _schema: &str) -> Option<String> {
+        // The primary key has no name in SQLite; unique constraints surface as automatic
+        // indexes whose origin is 'u'.
+        Some(
+            "SELECT tbl, con, kind, col FROM ( \
+                 SELECT m.name AS tbl, 'pk_' || m.name AS con, 'PRIMARY KEY' AS kind, \
+                        p.name AS col, p.pk AS pos \
+                 FROM sqlite_master m JOIN pragma_table_info(m.name) p \
+                 WHERE m.type = 'table' AND p.pk > 0 \
+                 UNION ALL \
+                 SELECT m.name, il.name, 'UNIQUE', ii.name, ii.seqno \
+                 FROM sqlite_master m JOIN pragma_index_list(m.name) il \
+                      JOIN pragma_index_info(il.name) ii \
+                 WHERE m.type = 'table' AND il.origin = 'u' \
+             ) ORDER BY tbl, con, pos"
+                .to_string(),
+        )
+    }
+
+    fn catalog_foreign_keys_sql(&self, _schema: &str) -> Option<String> {
+        // Foreign keys are unnamed; `id` numbers them per table.
+        Some(
+            "SELECT m.name, 'fk_' || m.name || '_' || f.id, f.\"from\", NULL, f.\"table\", \
+                    f.\"to\", f.on_update, f.on_delete \
+             FROM sqlite_master m JOIN pragma_foreign_key_list(m.name) f \
+             WHERE m.type = 'table' \
+             ORDER BY m.name, f.id, f.seq"
+                .to_string(),
+        )
+    }
+
+    fn catalog_indexes_sql(&self, _schema: &str) -> Option<String> {
+        // Only indexes created by CREATE INDEX (origin 'c'). The predicate of a partial index is
+        // only available in the stored CREATE statement, so it is cut out of that text.
+        Some(
+            "SELECT m.name, il.name, ii.name, NULL, \
+                    CASE WHEN il.\"unique\" = 1 THEN 'YES' ELSE 'NO' END, NULL, \
+                    CASE WHEN il.partial = 1 \
+                         THEN substr(s.sql, instr(upper(s.sql), ' WHERE ') + 7) END \
+             FROM sqlite_master m JOIN pragma_index_list(m.name) il \
+                  JOIN pragma_index_info(il.name) ii \
+                  JOIN sqlite_master s ON s.type = 'index' AND s.name = il.name \
+             WHERE m.type = 'table' AND il.origin = 'c' \
+             ORDER BY m.name, il.name, ii.seqno"
+                .to_string(),
+        )
+    }
+
+    fn canonical_from_physical(&self, physical: &str) -> CanonicalType {
+        let parsed = super::types::parse_canonical(&crate::config::ColumnTypeConfig::Simple(
+            physical.to_string(),
+        ));
+        let CanonicalType::Custom(_) = parsed else {
+            return parsed;
+        };
+        // Fall back to SQLite's column affinity rules for declared types the canonical parser
+        // does not know (DATETIME, BLOB, CLOB, NVARCHAR(20), …).
+        let upper = physical.to_uppercase();
+        if upper.contains("DATETIME") || upper.contains("TIMESTAMP") {
+            CanonicalType::Timestamp
+        } else if upper.contains("INT") {
+            CanonicalType::BigInt
+        } else if upper.contains("CHAR") || upper.contains("CLOB") || upper.contains("TEXT") {
+            CanonicalType::Text
+        } else if upper.is_empty() || upper.contains("BLOB") {
+            CanonicalType::Bytes
+        } else if upper.contains("REAL") || upper.contains("FLOA") || upper.contains("DOUB") {
+            CanonicalType::Double
+        } else {
+            parsed
+        }
+    }
 }
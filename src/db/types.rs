@@ -224,6 +224,41 @@ fn two_params(explicit: Option<&[u32]>, inline: &Option<Vec<u32>>) -> Option<(u8
     Some((src[0] as u8, src[1] as u8))
 }
 
+/// The package-config spelling of a canonical type — the inverse of [`parse_canonical`]:
+/// `parse_canonical(&ColumnTypeConfig::Simple(canonical_name(t))) == *t` for every variant.
+pub fn canonical_name(t: &CanonicalType) -> String {
+    match t {
+        CanonicalType::Text => "text".into(),
+        CanonicalType::Varchar(Some(n)) => format!("varchar({})", n),
+        CanonicalType::Varchar(None) => "varchar".into(),
+        CanonicalType::Char(Some(n)) => format!("char({})", n),
+        CanonicalType::Char(None) => "char".into(),
+        CanonicalType::SmallInt => "smallint".into(),
+        CanonicalType::Int => "integer".into(),
+        CanonicalType::BigInt => "bigint".into(),
+        CanonicalType::Real => "real".into(),
+        CanonicalType::Double => "double".into(),
+        CanonicalType::Decimal(Some((p, s))) => format!("numeric({},{})", p, s),
+        CanonicalType::Decimal(None) => "numeric".into(),
+        CanonicalType::Boolean => "boolean".into(),
+        CanonicalType::Uuid => "uuid".into(),
+        CanonicalType::Json => "json".into(),
+        CanonicalType::Jsonb => "jsonb".into(),
+        CanonicalType::Timestamp => "timestamptz".into(),
+        CanonicalType::TimestampNtz => "timestamp_ntz".into(),
+        CanonicalType::Date => "date".into(),
+        CanonicalType::Time => "time".into(),
+        CanonicalType::Timetz => "timetz".into(),
+        CanonicalType::Bytes => "bytes".into(),
+        CanonicalType::Serial => "serial".into(),
+        CanonicalType::BigSerial => "bigserial".into(),
+        CanonicalType::Asset => "asset".into(),
+        CanonicalType::AssetArray => "asset[]".into(),
+        CanonicalType::Array(inner) => format!("{}[]", canonical_name(inner)),
+        CanonicalType::Custom(s) => s.clone(),
+    }
+}
+
 // ─── Dialect-agnostic helpers ─────────────────────────────────────────────────
 
 /// Classify a [`CanonicalType`] into a [`TypeCategory`] for RSQL operator validation.
@@ -0,0 +1,277 @@
+//! Package dependencies declared in `manifest.json`.
+//!
+//! `"dependencies": {"crm": "^1.2", "geo": ">=0.3, <0.5"}` maps package ids to semver ranges
+//! (Cargo syntax; a bare `1.2` means `^1.2`). The older list form `["crm", "geo"]` is still
+//! accepted and matches any version. Install refuses a package whose dependencies are missing or
+//! installed at a version outside the range, upgrade refuses a version that breaks a dependent's
+//! range, and uninstall refuses a package that others depend on. A multi-package install is
+//! ordered so that dependencies come first.
+
+use crate::error::AppError;
+use semver::{Version, VersionReq};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// One entry of a manifest's `dependencies`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackageDependency {
+    pub package_id: String,
+    pub requirement: VersionReq,
+}
+
+/// Parse `dependencies` from a manifest. Absent means no dependencies.
+pub fn parse_dependencies(manifest: &Value) -> Result<Vec<PackageDependency>, AppError> {
+    let any = |package_id: &str| PackageDependency {
+        package_id: package_id.to_string(),
+        requirement: VersionReq::STAR,
+    };
+    match manifest.get("dependencies") {
+        None | Some(Value::Null) => Ok(Vec::new()),
+        Some(Value::Array(ids)) => ids
+            .iter()
+            .map(|id| {
+                id.as_str().map(any).ok_or_else(|| {
+                    AppError::BadRequest("manifest 'dependencies' must list package ids".into())
+                })
+            })
+            .collect(),
+        Some(Value::Object(ranges)) => ranges
+            .iter()
+            .map(|(package_id, range)| {
+                let range = range.as_str().ok_or_else(|| {
+                    AppError::BadRequest(format!(
+                        "dependency '{}': version range must be a string",
+                        package_id
+                    ))
+                })?;
+                let requirement = VersionReq::parse(range).map_err(|e| {
+                    AppError::BadRequest(format!(
+                        "dependency '{}': invalid version range '{}': {}",
+                        package_id, range, e
+                    ))
+                })?;
+                Ok(PackageDependency {
+                    package_id: package_id.clone(),
+                    requirement,
+                })
+            })
+            .collect(),
+        Some(_) => Err(AppError::BadRequest(
+            "manifest 'dependencies' must be an object of package id → version range".into(),
+        )),
+    }
+}
+
+/// `dependencies` as a package id → range object, whichever form the manifest used.
+pub fn dependencies_json(manifest: &Value) -> Value {
+    let deps = parse_dependencies(manifest).unwrap_or_default();
+    Value::Object(
+        deps.into_iter()
+            .map(|d| (d.package_id, Value::String(d.requirement.to_string())))
+            .collect(),
+    )
+}
+
+fn satisfies(requirement: &VersionReq, version: Option<&str>) -> bool {
+    if *requirement == VersionReq::STAR {
+        return true;
+    }
+    version
+        .and_then(|v| Version::parse(v).ok())
+        .is_some_and(|v| requirement.matches(&v))
+}
+
+/// Check `package_id`'s dependencies against the installed packages (id → manifest version).
+pub fn check_dependencies(
+    package_id: &str,
+    dependencies: &[PackageDependency],
+    installed: &HashMap<String, Option<String>>,
+) -> Result<(), AppError> {
+    let mut problems = Vec::new();
+    for dep in dependencies {
+        if dep.package_id == package_id {
+            return Err(AppError::BadRequest(format!(
+                "package '{}' cannot depend on itself",
+                package_id
+            )));
+        }
+        match installed.get(&dep.package_id) {
+            None => problems.push(format!(
+                "{} {} (not installed)",
+                dep.package_id, dep.requirement
+            )),
+            Some(version) if !satisfies(&dep.requirement, version.as_deref()) => {
+                problems.push(format!(
+                    "{} {} (installed: {})",
+                    dep.package_id,
+                    dep.requirement,
+                    version.as_deref().unwrap_or("unknown")
+                ))
+            }
+            Some(_) => {}
+        }
+    }
+    if problems.is_empty() {
+        return Ok(());
+    }
+    Err(AppError::BadRequest(format!(
+        "package '{}' has unmet dependencies: {}; install them first",
+        package_id,
+        problems.join(", ")
+    )))
+}
+
+/// Installed packages (id, manifest) that depend on `package_id`, with their range for it.
+pub fn dependents<'a>(
+    package_id: &str,
+    packages: impl IntoIterator<Item = (&'a str, &'a Value)>,
+) -> Vec<(String, VersionReq)> {
+    packages
+        .into_iter()
+        .filter(|(id, _)| *id != package_id)
+        .filter_map(|(id, manifest)| {
+            parse_dependencies(manifest)
+                .unwrap_or_default()
+                .into_iter()
+                .find(|d| d.package_id == package_id)
+                .map(|d| (id.to_string(), d.requirement))
+        })
+        .collect()
+}
+
+/// Refuse to upgrade `package_id` to `version` when an installed dependent's range excludes it.
+pub fn check_dependents_allow<'a>(
+    package_id: &str,
+    version: &str,
+    packages: impl IntoIterator<Item = (&'a str, &'a Value)>,
+) -> Result<(), AppError> {
+    let broken: Vec<String> = dependents(package_id, packages)
+        .into_iter()
+        .filter(|(_, req)| !satisfies(req, Some(version)))
+        .map(|(id, req)| format!("{} requires {}", id, req))
+        .collect();
+    if broken.is_empty() {
+        return Ok(());
+    }
+    Err(AppError::Conflict(format!(
+        "cannot install '{}' version '{}': {}",
+        package_id,
+        version,
+        broken.join(", ")
+    )))
+}
+
+/// Install order for a batch of packages (id, dependencies): every package comes after the batch
+/// packages it depends on, otherwise input order is kept. Returns indexes into `batch`.
+pub fn install_order(batch: &[(String, Vec<PackageDependency>)]) -> Result<Vec<usize>, AppError> {
+    let index: HashMap<&str, usize> = batch
+        .iter()
+        .enumerate()
+        .map(|(i, (id, _))| (id.as_str(), i))
+        .collect();
+    if index.len() != batch.len() {
+        return Err(AppError::BadRequest(
+            "the same package id appears more than once".into(),
+        ));
+    }
+    let mut order = Vec::with_capacity(batch.len());
+    let mut placed = vec![false; batch.len()];
+    while order.len() < batch.len() {
+        let next = (0..batch.len()).find(|&i| {
+            !placed[i]
+                && batch[i].1.iter().all(|d| {
+                    index
+                        .get(d.package_id.as_str())
+                        .map_or(true, |&j| placed[j] || j == i)
+                })
+        });
+        let Some(i) = next else {
+            let cycle: Vec<&str> = (0..batch.len())
+                .filter(|&i| !placed[i])
+                .map(|i| batch[i].0.as_str())
+                .collect();
+            return Err(AppError::BadRequest(format!(
+                "dependency cycle between packages [{}]",
+                cycle.join(", ")
+            )));
+        };
+        placed[i] = true;
+        order.push(i);
+    }
+    Ok(order)
+}
+
+#[cfg(test)]
+mod dependency_tests {
+    use super::*;
+    use serde_json::json;
+
+    fn deps(manifest: Value) -> Vec<PackageDependency> {
+        parse_dependencies(&manifest).unwrap()
+    }
+
+    #[test]
+    fn ranges_and_legacy_lists_parse() {
+        let parsed = deps(json!({"dependencies": {"crm": "^1.2", "geo": "0.3"}}));
+        assert_eq!(parsed[0].package_id, "crm");
+        assert_eq!(parsed[1].requirement, VersionReq::parse("^0.3").unwrap());
+        assert_eq!(
+            deps(json!({"dependencies": ["crm"]}))[0].requirement,
+            VersionReq::STAR
+        );
+        assert!(deps(json!({})).is_empty());
+        assert!(parse_dependencies(&json!({"dependencies": {"crm": "one"}})).is_err());
+    }
+
+    #[test]
+    fn installed_versions_must_match_ranges() {
+        let installed: HashMap<String, Option<String>> = [
+            ("crm".to_string(), Some("1.4.0".to_string())),
+            ("geo".to_string(), Some("0.2.9".to_string())),
+        ]
+        .into();
+        let ok = deps(json!({"dependencies": {"crm": "^1.2"}}));
+        assert!(check_dependencies("sales", &ok, &installed).is_ok());
+
+        let err = check_dependencies(
+            "sales",
+            &deps(json!({"dependencies": {"geo": "^0.3", "hr": "*"}})),
+            &installed,
+        )
+        .unwrap_err()
+        .to_string();
+        assert!(err.contains("geo ^0.3 (installed: 0.2.9)"), "{}", err);
+        assert!(err.contains("hr * (not installed)"), "{}", err);
+    }
+
+    #[test]
+    fn upgrades_may_not_break_dependents() {
+        let sales = json!({"dependencies": {"crm": "^1.2"}});
+        let packages = [("sales", &sales)];
+        assert!(check_dependents_allow("crm", "1.9.0", packages).is_ok());
+        assert!(check_dependents_allow("crm", "2.0.0", packages).is_err());
+        assert_eq!(dependents("crm", packages)[0].0, "sales");
+    }
+
+    #[test]
+    fn batches_install_dependencies_first() {
+        let batch = vec![
+            (
+                "sales".to_string(),
+                deps(json!({"dependencies": {"crm": "^1"}})),
+            ),
+            (
+                "crm".to_string(),
+                deps(json!({"dependencies": {"core": "*"}})),
+            ),
+            ("core".to_string(), Vec::new()),
+        ];
+        assert_eq!(install_order(&batch).unwrap(), vec![2, 1, 0]);
+
+        let cycle = vec![
+            ("a".to_string(), deps(json!({"dependencies": ["b"]}))),
+            ("b".to_string(), deps(json!({"dependencies": ["a"]}))),
+        ];
+        assert!(install_order(&cycle).is_err());
+    }
+}
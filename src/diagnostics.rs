@@ -0,0 +1,428 @@
+//! Per-component health for `GET /ready?deep=true` and the Platform Admin `GET /diagnostics`
+//! (see [`crate::routes::common_routes_with_ready`]).
+//!
+//! [`run`] checks every component concurrently, each bounded by [`PROBE_TIMEOUT`]:
+//!
+//! | Component | Check |
+//! |---|---|
+//! | `database` | `SELECT 1` on the central pool |
+//! | `tenant_pools` | `SELECT 1` on every cached tenant pool (see [`crate::tenant_pool`]) |
+//! | `storage` | `head` of [`STORAGE_PROBE_PATH`]; a missing object still means reachable |
+//! | `decision_hub`, `authrs` | `GET {url}/health` |
+//! | `migrations` | pending plans, and plans with a `failed` or `compensation_failed` step |
+//! | `models` | loading and resolving the config of every package in the central database |
+//! | `registries` | age of the tenant registry and of the extensible-field registry cache |
+//!
+//! `GET /ready?deep=true` is unauthenticated: it uses [`readiness`], which leaves out `models`
+//! and reuses one report for [`READY_CACHE_TTL`], so probes cannot make the server reload every
+//! package or hammer its dependencies.
+//!
+//! A component is `ok`, `degraded`, `down` or `skipped` (not configured, or nothing to check).
+//! Only step failures from the last 24 hours degrade `migrations`; older ones are still listed.
+//! The report is `down` when the central database is, else `degraded` when any component is
+//! `degraded` or `down`.
+
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::db::pool::Pool;
+use crate::state::AppState;
+
+/// Upper bound on each component check.
+pub const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+/// Object path looked up to check that the storage provider answers.
+pub const STORAGE_PROBE_PATH: &str = ".architect-health-probe";
+/// Failed migration plans listed in the `migrations` detail.
+const FAILED_PLANS_LISTED: i64 = 20;
+/// How long [`readiness`] reuses a report.
+pub const READY_CACHE_TTL: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ComponentStatus {
+    Ok,
+    Degraded,
+    Down,
+    Skipped,
+}
+
+/// One component's status, how long its check took, and what it found.
+#[derive(Debug, Clone, Serialize)]
+pub struct ComponentReport {
+    pub status: ComponentStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latency_ms: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(skip_serializing_if = "Value::is_null")]
+    pub detail: Value,
+}
+
+impl ComponentReport {
+    fn new(status: ComponentStatus, latency_ms: Option<u64>) -> Self {
+        Self {
+            status,
+            latency_ms,
+            error: None,
+            detail: Value::Null,
+        }
+    }
+
+    fn skipped() -> Self {
+        Self::new(ComponentStatus::Skipped, None)
+    }
+
+    fn down(error: String, latency_ms: u64) -> Self {
+        Self {
+            error: Some(error),
+            ..Self::new(ComponentStatus::Down, Some(latency_ms))
+        }
+    }
+
+    /// `ok` or `down` from a reachability check.
+    fn probed(result: Result<(), String>, latency_ms: u64) -> Self {
+        match result {
+            Ok(()) => Self::new(ComponentStatus::Ok, Some(latency_ms)),
+            Err(e) => Self::down(e, latency_ms),
+        }
+    }
+}
+
+/// Every component keyed by name, with the overall status.
+#[derive(Debug, Clone, Serialize)]
+pub struct DiagnosticsReport {
+    pub status: ComponentStatus,
+    pub components: BTreeMap<String, ComponentReport>,
+}
+
+impl DiagnosticsReport {
+    pub fn new(components: BTreeMap<String, ComponentReport>) -> Self {
+        let database_down = components
+            .get("database")
+            .is_some_and(|c| c.status == ComponentStatus::Down);
+        let status = if database_down {
+            ComponentStatus::Down
+        } else if components
+            .values()
+            .any(|c| matches!(c.status, ComponentStatus::Degraded | ComponentStatus::Down))
+        {
+            ComponentStatus::Degraded
+        } else {
+            ComponentStatus::Ok
+        };
+        Self { status, components }
+    }
+
+    /// Component statuses without latencies, errors or details, for unauthenticated probes.
+    pub fn summary(&self) -> BTreeMap<String, ComponentStatus> {
+        self.components
+            .iter()
+            .map(|(name, c)| (name.clone(), c.status))
+            .collect()
+    }
+}
+
+/// Check every component (see the module docs).
+pub async fn run(state: &AppState) -> DiagnosticsReport {
+    check_components(state, true).await
+}
+
+/// A readiness report and when it was taken.
+type CachedReport = (Instant, Arc<DiagnosticsReport>);
+
+/// Every component but `models`, checked at most once per [`READY_CACHE_TTL`]; concurrent
+/// callers wait for the same check.
+pub async fn readiness(state: &AppState) -> Arc<DiagnosticsReport> {
+    static CACHE: OnceLock<tokio::sync::Mutex<Option<CachedReport>>> = OnceLock::new();
+    let mut cached = CACHE
+        .get_or_init(|| tokio::sync::Mutex::new(None))
+        .lock()
+        .await;
+    if let Some((at, report)) = cached.as_ref() {
+        if at.elapsed() < READY_CACHE_TTL {
+            return Arc::clone(report);
+        }
+    }
+    let report = Arc::new(check_components(state, false).await);
+    *cached = Some((Instant::now(), Arc::clone(&report)));
+    report
+}
+
+async fn check_components(state: &AppState, with_models: bool) -> DiagnosticsReport {
+    let models = async {
+        if with_models {
+            Some(check_models(state).await)
+        } else {
+            None
+        }
+    };
+    let (database, tenant_pools, storage, decision_hub, authrs, migrations, models) = tokio::join!(
+        check_database(state),
+        check_tenant_pools(state),
+        check_storage(state),
+        check_decision_hub(state),
+        check_authrs(state),
+        check_migrations(state),
+        models,
+    );
+    let components = [
+        ("database", Some(database)),
+        ("tenant_pools", Some(tenant_pools)),
+        ("storage", Some(storage)),
+        ("decision_hub", Some(decision_hub)),
+        ("authrs", Some(authrs)),
+        ("migrations", Some(migrations)),
+        ("models", models),
+        ("registries", Some(check_registries(state))),
+    ]
+    .into_iter()
+    .filter_map(|(name, report)| Some((name.to_string(), report?)))
+    .collect();
+    DiagnosticsReport::new(components)
+}
+
+/// `GET {base_url}/health`; any 2xx is healthy.
+pub(crate) async fn probe_health(client: &reqwest::Client, base_url: &str) -> Result<(), String> {
+    let url = format!("{}/health", base_url.trim_end_matches('/'));
+    let resp = client.get(&url).send().await.map_err(|e| e.to_string())?;
+    if resp.status().is_success() {
+        Ok(())
+    } else {
+        Err(format!("{} returned {}", url, resp.status()))
+    }
+}
+
+/// Run `check` under [`PROBE_TIMEOUT`], returning its result and duration in milliseconds.
+async fn timed<T>(check: impl Future<Output = Result<T, String>>) -> (Result<T, String>, u64) {
+    let start = Instant::now();
+    let result = match tokio::time::timeout(PROBE_TIMEOUT, check).await {
+        Ok(result) => result,
+        Err(_) => Err(format!("timed out after {}s", PROBE_TIMEOUT.as_secs())),
+    };
+    (result, start.elapsed().as_millis() as u64)
+}
+
+async fn ping(pool: &Pool) -> Result<(), String> {
+    sqlx::query("SELECT 1")
+        .fetch_optional(pool)
+        .await
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+async fn check_database(state: &AppState) -> ComponentReport {
+    let (result, ms) = timed(ping(&state.pool)).await;
+    ComponentReport::probed(result, ms)
+}
+
+async fn check_tenant_pools(state: &AppState) -> ComponentReport {
+    let pools = match state.tenant_pools.cached() {
+        Ok(pools) if pools.is_empty() => return ComponentReport::skipped(),
+        Ok(pools) => pools,
+        Err(e) => return ComponentReport::down(e.to_string(), 0),
+    };
+    let start = Instant::now();
+    let results =
+        futures_util::future::join_all(pools.iter().map(|(tenant_id, pool)| async move {
+            let (result, ms) = timed(ping(pool)).await;
+            (tenant_id.clone(), ComponentReport::probed(result, ms))
+        }))
+        .await;
+    let failed = results
+        .iter()
+        .filter(|(_, r)| r.status != ComponentStatus::Ok)
+        .count();
+    let mut report = ComponentReport::new(
+        if failed == 0 {
+            ComponentStatus::Ok
+        } else {
+            ComponentStatus::Degraded
+        },
+        Some(start.elapsed().as_millis() as u64),
+    );
+    if failed > 0 {
+        report.error = Some(format!(
+            "{} of {} tenant pools unreachable",
+            failed,
+            results.len()
+        ));
+    }
+    report.detail = json!(results.into_iter().collect::<BTreeMap<_, _>>());
+    report
+}
+
+async fn check_storage(state: &AppState) -> ComponentReport {
+    let Some(storage) = &state.storage else {
+        return ComponentReport::skipped();
+    };
+    let (result, ms) = timed(async {
+        storage
+            .head(STORAGE_PROBE_PATH)
+            .await
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    })
+    .await;
+    ComponentReport::probed(result, ms)
+}
+
+async fn check_decision_hub(state: &AppState) -> ComponentReport {
+    let Some(client) = &state.event_client else {
+        return ComponentReport::skipped();
+    };
+    let (result, ms) = timed(client.health()).await;
+    ComponentReport::probed(result, ms)
+}
+
+async fn check_authrs(state: &AppState) -> ComponentReport {
+    let Some(client) = &state.authrs_client else {
+        return ComponentReport::skipped();
+    };
+    let (result, ms) = timed(client.health()).await;
+    ComponentReport::probed(result, ms)
+}
+
+async fn check_migrations(state: &AppState) -> ComponentReport {
+    let (result, ms) = timed(async {
+        let pending = crate::store::count_pending_migration_plans(&state.pool)
+            .await
+            .map_err(|e| e.to_string())?;
+        let failed = crate::store::list_failed_migration_plans(&state.pool, FAILED_PLANS_LISTED)
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok((pending, failed))
+    })
+    .await;
+    let (pending, failed) = match result {
+        Ok(found) => found,
+        Err(e) => return ComponentReport::down(e, ms),
+    };
+    let cutoff = chrono::Utc::now() - chrono::Duration::hours(24);
+    let recent = failed.iter().filter(|p| p.last_failed_at > cutoff).count();
+    let mut report = ComponentReport::new(
+        if recent == 0 {
+            ComponentStatus::Ok
+        } else {
+            ComponentStatus::Degraded
+        },
+        Some(ms),
+    );
+    if recent > 0 {
+        report.error = Some(format!(
+            "{} migration plan(s) had failed steps in the last 24 hours",
+            recent
+        ));
+    }
+    report.detail = json!({ "pending_plans": pending, "failed_plans": failed });
+    report
+}
+
+async fn check_models(state: &AppState) -> ComponentReport {
+    let (result, ms) = timed(async {
+        let package_ids = crate::store::list_package_ids(&state.pool)
+            .await
+            .map_err(|e| e.to_string())?;
+        let mut errors = BTreeMap::new();
+        for package_id in &package_ids {
+            let loaded = crate::config::load_from_pool(&state.pool, package_id)
+                .await
+                .and_then(|config| crate::config::resolve(&config));
+            if let Err(e) = loaded {
+                errors.insert(package_id.clone(), e.to_string());
+            }
+        }
+        Ok((package_ids.len(), errors))
+    })
+    .await;
+    let (packages, errors) = match result {
+        Ok(found) => found,
+        Err(e) => return ComponentReport::down(e, ms),
+    };
+    let mut report = ComponentReport::new(
+        if errors.is_empty() {
+            ComponentStatus::Ok
+        } else {
+            ComponentStatus::Degraded
+        },
+        Some(ms),
+    );
+    if !errors.is_empty() {
+        report.error = Some(format!(
+            "{} of {} packages failed to load",
+            errors.len(),
+            packages
+        ));
+    }
+    report.detail = json!({ "packages": packages, "errors": errors });
+    report
+}
+
+fn check_registries(state: &AppState) -> ComponentReport {
+    let ttl = crate::extensible_fields::REGISTRY_CACHE_TTL;
+    let ages: Vec<Duration> = match state.extensible_cache.read() {
+        Ok(cache) => cache.values().map(|e| e.loaded_at.elapsed()).collect(),
+        Err(_) => return ComponentReport::down("state lock".into(), 0),
+    };
+    let mut report = ComponentReport::new(ComponentStatus::Ok, None);
+    report.detail = json!({
+        "tenant_registry": {
+            "tenants": state.tenant_registry.tenant_ids().len(),
+            "age_secs": state.tenant_registry.loaded_at().map(|t| t.elapsed().as_secs()),
+        },
+        "extensible_fields": {
+            "entries": ages.len(),
+            "oldest_age_secs": ages.iter().max().map(Duration::as_secs),
+            "expired_entries": ages.iter().filter(|age| **age >= ttl).count(),
+            "ttl_secs": ttl.as_secs(),
+        },
+    });
+    report
+}
+
+#[cfg(test)]
+mod diagnostics_tests {
+    use super::*;
+
+    fn report(statuses: &[(&str, ComponentStatus)]) -> DiagnosticsReport {
+        DiagnosticsReport::new(
+            statuses
+                .iter()
+                .map(|(name, status)| (name.to_string(), ComponentReport::new(*status, None)))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn overall_status_follows_components() {
+        use ComponentStatus::*;
+        assert_eq!(report(&[("database", Ok), ("storage", Skipped)]).status, Ok);
+        assert_eq!(
+            report(&[("database", Ok), ("authrs", Down)]).status,
+            Degraded
+        );
+        assert_eq!(report(&[("database", Down), ("models", Ok)]).status, Down);
+    }
+
+    #[test]
+    fn summary_omits_details() {
+        let mut components = BTreeMap::new();
+        components.insert(
+            "authrs".to_string(),
+            ComponentReport::down("connection refused".into(), 3),
+        );
+        let report = DiagnosticsReport::new(components);
+        assert_eq!(
+            serde_json::to_value(report.summary()).unwrap(),
+            json!({ "authrs": "down" })
+        );
+        assert_eq!(
+            serde_json::to_value(&report.components["authrs"]).unwrap(),
+            json!({ "status": "down", "latency_ms": 3, "error": "connection refused" })
+        );
+    }
+}
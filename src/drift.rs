@@ -0,0 +1,1169 @@
+//! Schema drift: how far a live database has moved away from an installed package's config.
+//!
+//! Migration plans diff two configs and assume the database matches the old one exactly.
+//! [`detect_drift`] checks that assumption: it compares one package's [`FullConfig`] with a
+//! [`DbSnapshot`] of a tenant database and lists every table, column, index and constraint that
+//! is missing, unexpected, or shaped differently (nullability, type). [`drift_repair_plan`] turns the
+//! fixable part of that list into an ordinary [`MigrationPlan`], so it can be reviewed and
+//! executed like any upgrade.
+//!
+//! Index and constraint findings are only produced when the snapshot could read them
+//! ([`DbSnapshot::indexes_known`] / [`DbSnapshot::constraints_known`]) — SQLite, for example,
+//! exposes no constraint names. Companion `_audit` / `_history` tables are checked for existence
+//! only; their columns mirror the source table and are reconciled by the migration executor.
+
+use crate::config::types::*;
+use crate::config::FullConfig;
+use crate::db::{parse_canonical, CanonicalType, DbSnapshot, Dialect};
+use crate::error::AppError;
+use crate::migration::{
+    compute_migration_plan, reconcile_step, MigrationOperation, MigrationPlan, MigrationRisk,
+    MigrationSafety, MigrationStep, StepDecision,
+};
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+/// Columns every app table receives on top of its configured ones.
+const SYSTEM_COLUMNS: &[&str] = &[
+    "created_at",
+    "updated_at",
+    "archived_at",
+    "created_by",
+    "updated_by",
+];
+
+/// What kind of difference a [`DriftItem`] describes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DriftKind {
+    MissingTable,
+    ExtraTable,
+    MissingColumn,
+    ExtraColumn,
+    NullabilityMismatch,
+    TypeMismatch,
+    MissingIndex,
+    ExtraIndex,
+    MissingConstraint,
+    ExtraConstraint,
+}
+
+/// One difference between the config and the database.
+#[derive(Debug, Clone, Serialize)]
+pub struct DriftItem {
+    pub kind: DriftKind,
+    pub schema: String,
+    pub table: Option<String>,
+    /// Table, column, index or constraint name.
+    pub object: String,
+    /// "table" | "column" | "index" | "foreign_key" | "check"
+    pub object_type: String,
+    /// What the config describes (type or nullability), for mismatches.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expected: Option<String>,
+    /// What the database reports (type or nullability), for mismatches and extra columns.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub actual: Option<String>,
+    pub description: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DriftSummary {
+    pub total: usize,
+    pub missing: usize,
+    pub extra: usize,
+    pub mismatched: usize,
+}
+
+/// Result of [`detect_drift`] for one database.
+#[derive(Debug, Clone, Serialize)]
+pub struct DriftReport {
+    /// False when the database could not be read. `items` is then empty and proves nothing.
+    pub introspected: bool,
+    pub indexes_checked: bool,
+    pub constraints_checked: bool,
+    pub items: Vec<DriftItem>,
+}
+
+impl DriftReport {
+    /// True when the database was readable and matches the config.
+    pub fn is_clean(&self) -> bool {
+        self.introspected && self.items.is_empty()
+    }
+
+    pub fn summary(&self) -> DriftSummary {
+        let mut s = DriftSummary {
+            total: self.items.len(),
+            ..Default::default()
+        };
+        for item in &self.items {
+            match item.kind {
+                DriftKind::MissingTable
+                | DriftKind::MissingColumn
+                | DriftKind::MissingIndex
+                | DriftKind::MissingConstraint => s.missing += 1,
+                DriftKind::ExtraTable
+                | DriftKind::ExtraColumn
+                | DriftKind::ExtraIndex
+                | DriftKind::ExtraConstraint => s.extra += 1,
+                DriftKind::NullabilityMismatch | DriftKind::TypeMismatch => s.mismatched += 1,
+            }
+        }
+        s
+    }
+}
+
+/// The config object a finding refers to, by id — what [`drift_repair_plan`] needs to undo it.
+enum Source<'a> {
+    Table(&'a str),
+    /// Companion table (`_audit` / `_history`) of the table with this id.
+    Companion(&'a str, &'static str),
+    Column(&'a str),
+    Index(&'a str),
+    Relationship(&'a str),
+    /// Check constraints and everything extra: reported, never repaired automatically.
+    None,
+}
+
+struct Finding<'a> {
+    item: DriftItem,
+    source: Source<'a>,
+}
+
+/// Compare `config` with the database described by `snap`.
+///
+/// `schema_override` and `rls_tenant_column` describe the target the same way they do for
+/// [`compute_migration_plan`]; the RLS tenant column is expected, not extra. `other_packages`
+/// holds every other installed package so their tables are not reported as extra when they
+/// share a schema (always the case on SQLite).
+pub fn detect_drift(
+    config: &FullConfig,
+    snap: &DbSnapshot,
+    schema_override: Option<&str>,
+    rls_tenant_column: Option<&str>,
+    other_packages: &HashMap<String, FullConfig>,
+    dialect: &dyn Dialect,
+) -> DriftReport {
+    DriftReport {
+        introspected: snap.introspected,
+        indexes_checked: snap.introspected && snap.indexes_known,
+        constraints_checked: snap.introspected && snap.constraints_known,
+        items: findings(
+            config,
+            snap,
+            schema_override,
+            rls_tenant_column,
+            other_packages,
+            dialect,
+        )
+        .into_iter()
+        .map(|f| f.item)
+        .collect(),
+    }
+}
+
+/// Build the migration plan that brings the database described by `snap` back in line with
+/// `config`.
+///
+/// The plan is derived rather than hand-written: the config is rewritten to what the database
+/// physically holds (missing objects removed, mismatched columns given their actual type and
+/// nullability) and then diffed against the real config with [`compute_migration_plan`], so
+/// repair steps are exactly the DDL an upgrade would run. Steps whose effect is already present
+/// are dropped via [`reconcile_step`]. Extra objects are listed as `warn_only` steps — nothing
+/// is ever dropped — and missing check constraints are reported but not repaired.
+pub fn drift_repair_plan(
+    config: &FullConfig,
+    snap: &DbSnapshot,
+    schema_override: Option<&str>,
+    rls_tenant_column: Option<&str>,
+    other_packages: &HashMap<String, FullConfig>,
+    dialect: &dyn Dialect,
+) -> Result<MigrationPlan, AppError> {
+    let findings = findings(
+        config,
+        snap,
+        schema_override,
+        rls_tenant_column,
+        other_packages,
+        dialect,
+    );
+    if findings.is_empty() {
+        return Ok(MigrationPlan { steps: vec![] });
+    }
+
+    let pk_columns: HashSet<&str> = config
+        .columns
+        .iter()
+        .filter(|c| {
+            config
+                .tables
+                .iter()
+                .find(|t| t.id == c.table_id)
+                .is_some_and(|t| primary_key_columns(t).contains(&c.name.as_str()))
+        })
+        .map(|c| c.id.as_str())
+        .collect();
+
+    // `present`: the config as it physically exists, minus tables that are missing outright.
+    // API entities play no part in DDL; dropping them keeps validation of this synthetic config
+    // independent of which columns happen to be missing.
+    let mut present = config.clone();
+    present.api_entities.clear();
+    let mut missing_tables: HashSet<&str> = HashSet::new();
+    let mut missing_columns: HashSet<&str> = HashSet::new();
+    for f in &findings {
+        match (f.item.kind, &f.source) {
+            (DriftKind::MissingTable, Source::Table(id)) => {
+                missing_tables.insert(id);
+            }
+            (DriftKind::MissingTable, Source::Companion(id, suffix)) => {
+                if let Some(t) = present.tables.iter_mut().find(|t| t.id == *id) {
+                    if *suffix == "_audit" {
+                        t.audit_log = false;
+                    } else {
+                        t.versioning = None;
+                    }
+                }
+            }
+            // A primary key column cannot be added after the fact; leave it to the operator.
+            (DriftKind::MissingColumn, Source::Column(id)) if !pk_columns.contains(id) => {
+                missing_columns.insert(id);
+            }
+            (DriftKind::NullabilityMismatch, Source::Column(id)) => {
+                if let Some(c) = present.columns.iter_mut().find(|c| c.id == *id) {
+                    c.nullable = !c.nullable;
+                }
+            }
+            (DriftKind::TypeMismatch, Source::Column(id)) => {
+                if let (Some(c), Some(actual)) = (
+                    present.columns.iter_mut().find(|c| c.id == *id),
+                    f.item.actual.as_ref(),
+                ) {
+                    c.type_ = ColumnTypeConfig::Simple(actual.clone());
+                }
+            }
+            (DriftKind::MissingIndex, Source::Index(id)) => {
+                present.indexes.retain(|i| i.id != *id);
+            }
+            (DriftKind::MissingConstraint, Source::Relationship(id)) => {
+                present.relationships.retain(|r| r.id != *id);
+            }
+            _ => {}
+        }
+    }
+    present
+        .columns
+        .retain(|c| !missing_columns.contains(c.id.as_str()));
+    present.relationships.retain(|r| {
+        !missing_columns.contains(r.from_column_id.as_str())
+            && !missing_columns.contains(r.to_column_id.as_str())
+    });
+
+    let mut absent = present.clone();
+    absent
+        .tables
+        .retain(|t| !missing_tables.contains(t.id.as_str()));
+    absent
+        .columns
+        .retain(|c| !missing_tables.contains(c.table_id.as_str()));
+    absent
+        .indexes
+        .retain(|i| !missing_tables.contains(i.table_id.as_str()));
+    absent.relationships.retain(|r| {
+        !missing_tables.contains(r.from_table_id.as_str())
+            && !missing_tables.contains(r.to_table_id.as_str())
+    });
+    // A schema none of whose tables exist was most likely never created: let the plan create it
+    // (and its enums) too. Schemas are only dropped from the baseline when the dialect has them.
+    if schema_override.is_none() && dialect.supports_schemas() {
+        let default_sid = config.schemas.first().map(|s| s.id.clone());
+        let tables_in = |sid: &str, cfg: &FullConfig| {
+            cfg.tables
+                .iter()
+                .filter(|t| t.schema_id.as_deref().or(default_sid.as_deref()) == Some(sid))
+                .count()
+        };
+        let vanished: HashSet<String> = config
+            .schemas
+            .iter()
+            .filter(|s| tables_in(&s.id, config) > 0 && tables_in(&s.id, &absent) == 0)
+            .filter(|s| snap.tables(&s.name).is_empty())
+            .map(|s| s.id.clone())
+            .collect();
+        absent.schemas.retain(|s| !vanished.contains(&s.id));
+        absent.enums.retain(|e| {
+            let sid = e.schema_id.as_deref().or(default_sid.as_deref());
+            !sid.is_some_and(|sid| vanished.contains(sid))
+        });
+    }
+
+    // Two passes: `absent → present` creates the missing tables, `present → config` fixes
+    // columns, companions, indexes and foreign keys on every table, including the new ones.
+    let mut steps = compute_migration_plan(
+        &absent,
+        &present,
+        schema_override,
+        rls_tenant_column,
+        dialect,
+        other_packages,
+    )?
+    .steps;
+    steps.extend(
+        compute_migration_plan(
+            &present,
+            config,
+            schema_override,
+            rls_tenant_column,
+            dialect,
+            other_packages,
+        )?
+        .steps,
+    );
+    steps.retain(|s| !matches!(reconcile_step(s, snap), StepDecision::Skip(_)));
+
+    for f in &findings {
+        if let Some(step) = extra_object_step(&f.item) {
+            steps.push(step);
+        }
+    }
+    for (i, s) in steps.iter_mut().enumerate() {
+        s.step = i + 1;
+    }
+    Ok(MigrationPlan { steps })
+}
+
+/// A `warn_only` step for an object the database has but the config does not describe.
+fn extra_object_step(item: &DriftItem) -> Option<MigrationStep> {
+    let (operation, detail) = match item.kind {
+        DriftKind::ExtraTable => (
+            MigrationOperation::DropTable,
+            "Table NOT dropped (data safety). Run DROP TABLE manually if intended.",
+        ),
+        DriftKind::ExtraColumn => (
+            MigrationOperation::DropColumn,
+            "Column NOT dropped (data safety). Run ALTER TABLE DROP COLUMN manually if intended.",
+        ),
+        DriftKind::ExtraIndex => (
+            MigrationOperation::DropIndex,
+            "Index NOT dropped — it may have been added on purpose. Run DROP INDEX manually if intended.",
+        ),
+        DriftKind::ExtraConstraint if item.object_type == "foreign_key" => (
+            MigrationOperation::DropForeignKey,
+            "Constraint NOT dropped. Run ALTER TABLE DROP CONSTRAINT manually if intended.",
+        ),
+        _ => return None,
+    };
+    Some(
+        MigrationStep::new(
+            operation,
+            item.schema.clone(),
+            item.table.clone(),
+            item.object.clone(),
+            item.object_type.clone(),
+            item.description.clone(),
+        )
+        .with_safety(MigrationSafety::WarnOnly)
+        .with_risk(MigrationRisk::ManualActionRequired, Some(detail.into())),
+    )
+}
+
+fn primary_key_columns(t: &TableConfig) -> Vec<&str> {
+    match &t.primary_key {
+        PrimaryKeyConfig::Single(s) => vec![s.as_str()],
+        PrimaryKeyConfig::Composite(v) => v.iter().map(String::as_str).collect(),
+    }
+}
+
+/// Schema each table of `cfg` lives in, resolved the same way `compute_migration_plan` does.
+fn resolve_schema(cfg: &FullConfig, sid: Option<&str>, schema_override: Option<&str>) -> String {
+    if let Some(o) = schema_override {
+        return o.to_string();
+    }
+    let sid = sid
+        .or_else(|| cfg.schemas.first().map(|s| s.id.as_str()))
+        .unwrap_or("");
+    cfg.schemas
+        .iter()
+        .find(|s| s.id == sid)
+        .map(|s| s.name.clone())
+        .unwrap_or_else(|| sid.to_string())
+}
+
+/// Every physical table a config owns, companions included, as `(schema, table)`.
+fn owned_tables(cfg: &FullConfig, schema_override: Option<&str>) -> Vec<(String, String)> {
+    let mut out = Vec::new();
+    for t in &cfg.tables {
+        let schema = resolve_schema(cfg, t.schema_id.as_deref(), schema_override);
+        if t.audit_log {
+            out.push((schema.clone(), format!("{}_audit", t.name)));
+        }
+        if t.versioning.as_ref().is_some_and(|v| v.enabled) {
+            out.push((schema.clone(), format!("{}_history", t.name)));
+        }
+        out.push((schema, t.name.clone()));
+    }
+    out
+}
+
+/// `None` when the configured and physical types agree; otherwise the type the config expects.
+///
+/// Both sides are mapped to this dialect's DDL type before comparing, so lossy mappings line up
+/// (`uuid` is `CHAR(36)` on MySQL, `boolean` is `INTEGER` on SQLite) and catalog spellings such
+/// as `character varying(64)` match `varchar(64)`. Serial types compare as their integer base.
+/// Custom (enum) types compare by unqualified name, and only where the dialect has named types.
+fn type_mismatch(
+    dialect: &dyn Dialect,
+    configured: &ColumnTypeConfig,
+    actual: &str,
+) -> Option<String> {
+    let expected = parse_canonical(configured);
+    let custom = match &expected {
+        CanonicalType::Custom(s) => Some((s.as_str(), false)),
+        CanonicalType::Array(inner) => match inner.as_ref() {
+            CanonicalType::Custom(s) => Some((s.as_str(), true)),
+            _ => None,
+        },
+        _ => None,
+    };
+    if let Some((name, is_array)) = custom {
+        if !dialect.supports_named_enum_types() {
+            return None;
+        }
+        let unqualified = |s: &str| {
+            let s = s.rsplit('.').next().unwrap_or(s);
+            s.replace('"', "").to_lowercase()
+        };
+        let actual = actual.trim();
+        let (actual_base, actual_array) = match actual.strip_suffix("[]") {
+            Some(base) => (base, true),
+            None => (actual, false),
+        };
+        return (actual_array != is_array || unqualified(actual_base) != unqualified(name))
+            .then(|| dialect.ddl_type(&expected));
+    }
+
+    // Catalog spellings the config parser reads differently: a bare physical `timestamp` has no
+    // time zone, and MySQL reports single-precision floats as `float`.
+    let lowered = actual.trim().to_lowercase();
+    let physical = match lowered.as_str() {
+        "timestamp" | "timestamp without time zone" => "timestamp_ntz",
+        "float" => "real",
+        other => other,
+    };
+    let observed = parse_canonical(&ColumnTypeConfig::Simple(physical.to_string()));
+    let normalize = |t: &CanonicalType| -> String {
+        let base = match t {
+            CanonicalType::Serial => CanonicalType::Int,
+            CanonicalType::BigSerial => CanonicalType::BigInt,
+            other => other.clone(),
+        };
+        dialect
+            .ddl_type(&base)
+            .to_uppercase()
+            .split_whitespace()
+            .collect()
+    };
+    (normalize(&expected) != normalize(&observed)).then(|| dialect.ddl_type(&expected))
+}
+
+#[allow(clippy::too_many_arguments)]
+fn item(
+    kind: DriftKind,
+    schema: &str,
+    table: Option<&str>,
+    object: &str,
+    object_type: &str,
+    expected: Option<String>,
+    actual: Option<String>,
+    description: String,
+) -> DriftItem {
+    DriftItem {
+        kind,
+        schema: schema.to_string(),
+        table: table.map(String::from),
+        object: object.to_string(),
+        object_type: object_type.to_string(),
+        expected,
+        actual,
+        description,
+    }
+}
+
+fn nullability(nullable: bool) -> String {
+    if nullable { "NULL" } else { "NOT NULL" }.to_string()
+}
+
+fn findings<'a>(
+    config: &'a FullConfig,
+    snap: &DbSnapshot,
+    schema_override: Option<&str>,
+    rls_tenant_column: Option<&str>,
+    other_packages: &HashMap<String, FullConfig>,
+    dialect: &dyn Dialect,
+) -> Vec<Finding<'a>> {
+    let mut out: Vec<Finding<'a>> = Vec::new();
+    if !snap.introspected {
+        return out;
+    }
+    let tables_by_id: HashMap<&str, &TableConfig> =
+        config.tables.iter().map(|t| (t.id.as_str(), t)).collect();
+    let cols_by_table: HashMap<&str, Vec<&ColumnConfig>> =
+        config.columns.iter().fold(HashMap::new(), |mut m, c| {
+            m.entry(c.table_id.as_str()).or_default().push(c);
+            m
+        });
+    let schema_of = |sid: Option<&str>| resolve_schema(config, sid, schema_override);
+
+    // ── Tables and columns ───────────────────────────────────────────────────
+    let mut existing: Vec<(&TableConfig, String)> = Vec::new();
+    for t in &config.tables {
+        let schema = schema_of(t.schema_id.as_deref());
+        if !snap.has_table(&schema, &t.name) {
+            out.push(Finding {
+                item: item(
+                    DriftKind::MissingTable,
+                    &schema,
+                    Some(&t.name),
+                    &t.name,
+                    "table",
+                    None,
+                    None,
+                    format!("Table \"{}\".\"{}\" does not exist", schema, t.name),
+                ),
+                source: Source::Table(&t.id),
+            });
+            continue;
+        }
+        for (suffix, enabled) in [
+            ("_audit", t.audit_log),
+            ("_history", t.versioning.as_ref().is_some_and(|v| v.enabled)),
+        ] {
+            let companion = format!("{}{}", t.name, suffix);
+            if enabled && !snap.has_table(&schema, &companion) {
+                out.push(Finding {
+                    item: item(
+                        DriftKind::MissingTable,
+                        &schema,
+                        Some(&companion),
+                        &companion,
+                        "table",
+                        None,
+                        None,
+                        format!(
+                            "Companion table \"{}\".\"{}\" does not exist",
+                            schema, companion
+                        ),
+                    ),
+                    source: Source::Companion(&t.id, suffix),
+                });
+            }
+        }
+
+        let cols = cols_by_table
+            .get(t.id.as_str())
+            .map(|v| v.as_slice())
+            .unwrap_or(&[]);
+        let pk = primary_key_columns(t);
+        for c in cols {
+            let Some(facts) = snap.column(&schema, &t.name, &c.name) else {
+                out.push(Finding {
+                    item: item(
+                        DriftKind::MissingColumn,
+                        &schema,
+                        Some(&t.name),
+                        &c.name,
+                        "column",
+                        Some(dialect.ddl_type(&parse_canonical(&c.type_))),
+                        None,
+                        format!(
+                            "Column \"{}\" is missing from \"{}\".\"{}\"",
+                            c.name, schema, t.name
+                        ),
+                    ),
+                    source: Source::Column(&c.id),
+                });
+                continue;
+            };
+            // Primary key columns are NOT NULL whatever the config says.
+            if !pk.contains(&c.name.as_str()) && facts.nullable != c.nullable {
+                out.push(Finding {
+                    item: item(
+                        DriftKind::NullabilityMismatch,
+                        &schema,
+                        Some(&t.name),
+                        &c.name,
+                        "column",
+                        Some(nullability(c.nullable)),
+                        Some(nullability(facts.nullable)),
+                        format!(
+                            "Column \"{}\".\"{}\".\"{}\" is {} but config says {}",
+                            schema,
+                            t.name,
+                            c.name,
+                            nullability(facts.nullable),
+                            nullability(c.nullable)
+                        ),
+                    ),
+                    source: Source::Column(&c.id),
+                });
+            }
+            if let Some(expected) = type_mismatch(dialect, &c.type_, &facts.data_type) {
+                out.push(Finding {
+                    item: item(
+                        DriftKind::TypeMismatch,
+                        &schema,
+                        Some(&t.name),
+                        &c.name,
+                        "column",
+                        Some(expected.clone()),
+                        Some(facts.data_type.clone()),
+                        format!(
+                            "Column \"{}\".\"{}\".\"{}\" is {} but config says {}",
+                            schema, t.name, c.name, facts.data_type, expected
+                        ),
+                    ),
+                    source: Source::Column(&c.id),
+                });
+            }
+        }
+
+        let known: HashSet<String> = cols
+            .iter()
+            .map(|c| c.name.to_lowercase())
+            .chain(SYSTEM_COLUMNS.iter().map(|s| s.to_string()))
+            .chain(rls_tenant_column.map(str::to_lowercase))
+            .collect();
+        for (name, facts) in snap.columns_of(&schema, &t.name) {
+            if known.contains(name) {
+                continue;
+            }
+            out.push(Finding {
+                item: item(
+                    DriftKind::ExtraColumn,
+                    &schema,
+                    Some(&t.name),
+                    name,
+                    "column",
+                    None,
+                    Some(facts.data_type.clone()),
+                    format!(
+                        "Column \"{}\" on \"{}\".\"{}\" is not in the config",
+                        name, schema, t.name
+                    ),
+                ),
+                source: Source::None,
+            });
+        }
+        existing.push((t, schema));
+    }
+
+    // Tables in this package's schemas that no installed package owns. Without schema support
+    // (SQLite) every package shares one namespace, so ownership is matched by name alone.
+    let by_schema = dialect.supports_schemas();
+    let claim_key = |schema: &str, table: &str| {
+        if by_schema {
+            format!("{}\u{1}{}", schema.to_lowercase(), table.to_lowercase())
+        } else {
+            table.to_lowercase()
+        }
+    };
+    let claimed: HashSet<String> = std::iter::once(config)
+        .chain(other_packages.values())
+        .flat_map(|cfg| owned_tables(cfg, schema_override))
+        .map(|(schema, table)| claim_key(&schema, &table))
+        .collect();
+    let schemas: BTreeSet<String> = config
+        .tables
+        .iter()
+        .map(|t| schema_of(t.schema_id.as_deref()))
+        .collect();
+    for schema in &schemas {
+        for table in snap.tables(schema) {
+            if table.starts_with("_sys_")
+                || table.starts_with("sqlite_")
+                || claimed.contains(&claim_key(schema, &table))
+            {
+                continue;
+            }
+            out.push(Finding {
+                item: item(
+                    DriftKind::ExtraTable,
+                    schema,
+                    Some(&table),
+                    &table,
+                    "table",
+                    None,
+                    None,
+                    format!(
+                        "Table \"{}\".\"{}\" is not owned by any installed package",
+                        schema, table
+                    ),
+                ),
+                source: Source::None,
+            });
+        }
+    }
+
+    // ── Indexes ──────────────────────────────────────────────────────────────
+    if snap.indexes_known {
+        for idx in &config.indexes {
+            let Some(t) = tables_by_id.get(idx.table_id.as_str()) else {
+                continue;
+            };
+            let schema = schema_of(idx.schema_id.as_deref());
+            if !snap.has_index(&schema, &idx.name) {
+                out.push(Finding {
+                    item: item(
+                        DriftKind::MissingIndex,
+                        &schema,
+                        Some(&t.name),
+                        &idx.name,
+                        "index",
+                        None,
+                        None,
+                        format!(
+                            "Index \"{}\" on \"{}\".\"{}\" does not exist",
+                            idx.name, schema, t.name
+                        ),
+                    ),
+                    source: Source::Index(&idx.id),
+                });
+            }
+        }
+        for (t, schema) in &existing {
+            let expected: HashSet<String> = config
+                .indexes
+                .iter()
+                .filter(|i| i.table_id == t.id)
+                .map(|i| i.name.to_lowercase())
+                .collect();
+            // Indexes backing a primary key / unique / foreign key constraint share its name.
+            let constraint_names: HashSet<&str> = snap
+                .constraints_on(schema, &t.name)
+                .into_iter()
+                .map(|(name, _)| name)
+                .collect();
+            for name in snap.indexes_on(schema, &t.name) {
+                if expected.contains(name)
+                    || constraint_names.contains(name)
+                    || name == "primary"
+                    || name.starts_with("sqlite_autoindex_")
+                    // Extensible-field indexes are managed by `extensible_fields`.
+                    || name.starts_with("xf_")
+                {
+                    continue;
+                }
+                out.push(Finding {
+                    item: item(
+                        DriftKind::ExtraIndex,
+                        schema,
+                        Some(&t.name),
+                        name,
+                        "index",
+                        None,
+                        None,
+                        format!(
+                            "Index \"{}\" on \"{}\".\"{}\" is not in the config",
+                            name, schema, t.name
+                        ),
+                    ),
+                    source: Source::None,
+                });
+            }
+        }
+    }
+
+    // ── Constraints (foreign keys and checks) ────────────────────────────────
+    if snap.constraints_known {
+        for rel in &config.relationships {
+            let Some(t) = tables_by_id.get(rel.from_table_id.as_str()) else {
+                continue;
+            };
+            let schema = schema_of(rel.from_schema_id.as_deref());
+            let name = rel.name.as_deref().unwrap_or(&rel.id);
+            if !snap.has_constraint(&schema, &t.name, name) {
+                out.push(Finding {
+                    item: item(
+                        DriftKind::MissingConstraint,
+                        &schema,
+                        Some(&t.name),
+                        name,
+                        "foreign_key",
+                        None,
+                        None,
+                        format!(
+                            "Foreign key \"{}\" on \"{}\".\"{}\" does not exist",
+                            name, schema, t.name
+                        ),
+                    ),
+                    source: Source::Relationship(&rel.id),
+                });
+            }
+        }
+        for (t, schema) in &existing {
+            for ch in &t.check {
+                if !snap.has_constraint(schema, &t.name, &ch.name) {
+                    out.push(Finding {
+                        item: item(
+                            DriftKind::MissingConstraint,
+                            schema,
+                            Some(&t.name),
+                            &ch.name,
+                            "check",
+                            Some(ch.expression.clone()),
+                            None,
+                            format!(
+                                "Check constraint \"{}\" on \"{}\".\"{}\" does not exist",
+                                ch.name, schema, t.name
+                            ),
+                        ),
+                        source: Source::None,
+                    });
+                }
+            }
+            let expected: HashSet<String> = config
+                .relationships
+                .iter()
+                .filter(|r| r.from_table_id == t.id)
+                .map(|r| r.name.as_deref().unwrap_or(&r.id).to_lowercase())
+                .chain(t.check.iter().map(|c| c.name.to_lowercase()))
+                .collect();
+            for (name, kind) in snap.constraints_on(schema, &t.name) {
+                let object_type = match kind {
+                    Some("FOREIGN KEY") => "foreign_key",
+                    Some("CHECK") => "check",
+                    _ => continue,
+                };
+                if expected.contains(name) {
+                    continue;
+                }
+                out.push(Finding {
+                    item: item(
+                        DriftKind::ExtraConstraint,
+                        schema,
+                        Some(&t.name),
+                        name,
+                        object_type,
+                        None,
+                        None,
+                        format!(
+                            "Constraint \"{}\" on \"{}\".\"{}\" is not in the config",
+                            name, schema, t.name
+                        ),
+                    ),
+                    source: Source::None,
+                });
+            }
+        }
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod drift_tests {
+    use super::*;
+    use crate::db::{active_dialect, ColumnFacts};
+
+    fn column(id: &str, name: &str, ty: &str, nullable: bool) -> ColumnConfig {
+        ColumnConfig {
+            id: id.into(),
+            table_id: "t_orders".into(),
+            name: name.into(),
+            type_: ColumnTypeConfig::Simple(ty.into()),
+            nullable,
+            default: None,
+            comment: None,
+            asset: None,
+            extensible: false,
+        }
+    }
+
+    fn table(id: &str, name: &str) -> TableConfig {
+        TableConfig {
+            id: id.into(),
+            schema_id: Some("s1".into()),
+            name: name.into(),
+            comment: None,
+            primary_key: PrimaryKeyConfig::Single("id".into()),
+            unique: vec![],
+            check: vec![],
+            audit_log: false,
+            versioning: None,
+            global: false,
+        }
+    }
+
+    fn config() -> FullConfig {
+        FullConfig {
+            schemas: vec![SchemaConfig {
+                id: "s1".into(),
+                name: "app".into(),
+                comment: None,
+            }],
+            tables: vec![table("t_orders", "orders")],
+            columns: vec![
+                column("c_id", "id", "uuid", false),
+                column("c_note", "note", "text", true),
+                column("c_total", "total", "bigint", false),
+            ],
+            ..Default::default()
+        }
+    }
+
+    fn facts(dialect: &dyn Dialect, ty: &str, nullable: bool) -> ColumnFacts {
+        ColumnFacts {
+            data_type: dialect.ddl_type(&parse_canonical(&ColumnTypeConfig::Simple(ty.into()))),
+            nullable,
+            has_default: false,
+        }
+    }
+
+    /// A snapshot that matches `config()` exactly, system columns included.
+    fn matching_snapshot(dialect: &dyn Dialect) -> DbSnapshot {
+        let mut snap = DbSnapshot::default();
+        snap.introspected = true;
+        snap.add_column("app", "orders", "id", facts(dialect, "uuid", false));
+        snap.add_column("app", "orders", "note", facts(dialect, "text", true));
+        snap.add_column("app", "orders", "total", facts(dialect, "bigint", false));
+        for name in SYSTEM_COLUMNS {
+            snap.add_column("app", "orders", name, facts(dialect, "text", true));
+        }
+        snap
+    }
+
+    fn kinds(report: &DriftReport) -> Vec<(DriftKind, &str)> {
+        report
+            .items
+            .iter()
+            .map(|i| (i.kind, i.object.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn matching_database_is_clean() {
+        let dialect = active_dialect();
+        let snap = matching_snapshot(dialect.as_ref());
+        let report = detect_drift(
+            &config(),
+            &snap,
+            None,
+            None,
+            &HashMap::new(),
+            dialect.as_ref(),
+        );
+        assert!(report.is_clean(), "{:?}", report.items);
+    }
+
+    #[test]
+    fn failed_introspection_reports_nothing() {
+        let dialect = active_dialect();
+        let report = detect_drift(
+            &config(),
+            &DbSnapshot::default(),
+            None,
+            None,
+            &HashMap::new(),
+            dialect.as_ref(),
+        );
+        assert!(!report.introspected);
+        assert!(report.items.is_empty());
+        assert!(!report.is_clean());
+    }
+
+    #[test]
+    fn column_level_drift_is_reported() {
+        let dialect = active_dialect();
+        let mut snap = matching_snapshot(dialect.as_ref());
+        snap.remove_column("app", "orders", "note");
+        snap.add_column(
+            "app",
+            "orders",
+            "legacy",
+            facts(dialect.as_ref(), "text", true),
+        );
+        snap.set_nullable("app", "orders", "total", true);
+        snap.add_column(
+            "app",
+            "orders",
+            "id",
+            facts(dialect.as_ref(), "bytea", false),
+        );
+
+        let report = detect_drift(
+            &config(),
+            &snap,
+            None,
+            None,
+            &HashMap::new(),
+            dialect.as_ref(),
+        );
+        let found = kinds(&report);
+        assert!(
+            found.contains(&(DriftKind::MissingColumn, "note")),
+            "{found:?}"
+        );
+        assert!(
+            found.contains(&(DriftKind::ExtraColumn, "legacy")),
+            "{found:?}"
+        );
+        assert!(
+            found.contains(&(DriftKind::NullabilityMismatch, "total")),
+            "{found:?}"
+        );
+        assert!(
+            found.contains(&(DriftKind::TypeMismatch, "id")),
+            "{found:?}"
+        );
+        let summary = report.summary();
+        assert_eq!(
+            (summary.missing, summary.extra, summary.mismatched),
+            (1, 1, 2)
+        );
+    }
+
+    #[test]
+    fn rls_tenant_column_is_not_extra() {
+        let dialect = active_dialect();
+        let mut snap = matching_snapshot(dialect.as_ref());
+        snap.add_column(
+            "app",
+            "orders",
+            "tenant_id",
+            facts(dialect.as_ref(), "text", true),
+        );
+        let report = detect_drift(
+            &config(),
+            &snap,
+            None,
+            Some("tenant_id"),
+            &HashMap::new(),
+            dialect.as_ref(),
+        );
+        assert!(report.is_clean(), "{:?}", report.items);
+    }
+
+    #[test]
+    fn tables_owned_by_other_packages_are_not_extra() {
+        let dialect = active_dialect();
+        let mut snap = matching_snapshot(dialect.as_ref());
+        snap.add_table("app", "invoices");
+        snap.add_table("app", "scratch");
+        snap.add_table("app", "_sys_packages");
+        let mut other = config();
+        other.tables = vec![table("t_invoices", "invoices")];
+        other.columns.clear();
+        let others = HashMap::from([("billing".to_string(), other)]);
+
+        let report = detect_drift(&config(), &snap, None, None, &others, dialect.as_ref());
+        assert_eq!(kinds(&report), vec![(DriftKind::ExtraTable, "scratch")]);
+    }
+
+    #[test]
+    fn indexes_and_constraints_are_only_checked_when_known() {
+        let dialect = active_dialect();
+        let mut cfg = config();
+        cfg.indexes.push(IndexConfig {
+            id: "i1".into(),
+            schema_id: Some("s1".into()),
+            table_id: "t_orders".into(),
+            name: "orders_total_idx".into(),
+            method: None,
+            unique: false,
+            columns: vec![IndexColumnEntry::Name("total".into())],
+            include: vec![],
+            where_: None,
+            comment: None,
+        });
+        let mut snap = matching_snapshot(dialect.as_ref());
+        assert!(
+            detect_drift(&cfg, &snap, None, None, &HashMap::new(), dialect.as_ref()).is_clean()
+        );
+
+        snap.indexes_known = true;
+        snap.add_index_on("app", "orders", "orders_manual_idx");
+        snap.add_index_on("app", "orders", "orders_pkey");
+        snap.add_constraint_of_type("app", "orders", "orders_pkey", "PRIMARY KEY");
+        let report = detect_drift(&cfg, &snap, None, None, &HashMap::new(), dialect.as_ref());
+        assert_eq!(
+            kinds(&report),
+            vec![
+                (DriftKind::MissingIndex, "orders_total_idx"),
+                (DriftKind::ExtraIndex, "orders_manual_idx"),
+            ]
+        );
+    }
+
+    #[test]
+    fn catalog_type_spellings_match_config_types() {
+        let dialect = active_dialect();
+        let d = dialect.as_ref();
+        let simple = |s: &str| ColumnTypeConfig::Simple(s.into());
+        assert_eq!(type_mismatch(d, &simple("serial"), "integer"), None);
+        assert_eq!(
+            type_mismatch(d, &simple("varchar(64)"), "character varying(64)"),
+            None
+        );
+        assert_eq!(
+            type_mismatch(d, &simple("numeric(10,2)"), "numeric(10, 2)"),
+            None
+        );
+        assert!(type_mismatch(d, &simple("text"), "bytea").is_some());
+    }
+
+    #[test]
+    fn repair_plan_creates_missing_objects_and_warns_about_extras() {
+        let dialect = active_dialect();
+        let mut cfg = config();
+        cfg.tables.push(table("t_items", "items"));
+        cfg.columns.push(ColumnConfig {
+            table_id: "t_items".into(),
+            ..column("c_item_id", "id", "uuid", false)
+        });
+        let mut snap = matching_snapshot(dialect.as_ref());
+        snap.remove_column("app", "orders", "note");
+        snap.add_column(
+            "app",
+            "orders",
+            "legacy",
+            facts(dialect.as_ref(), "text", true),
+        );
+
+        let plan = drift_repair_plan(&cfg, &snap, None, None, &HashMap::new(), dialect.as_ref())
+            .expect("repair plan");
+        let ops: Vec<(String, &str)> = plan
+            .steps
+            .iter()
+            .map(|s| (s.operation.to_string(), s.object.as_str()))
+            .collect();
+        assert!(ops.contains(&("create_table".into(), "items")), "{ops:?}");
+        assert!(ops.contains(&("add_column".into(), "note")), "{ops:?}");
+        let legacy = plan
+            .steps
+            .iter()
+            .find(|s| s.object == "legacy")
+            .expect("extra column step");
+        assert!(matches!(legacy.safety, MigrationSafety::WarnOnly));
+        assert!(legacy.ddl.is_none());
+        assert_eq!(
+            plan.steps.iter().map(|s| s.step).collect::<Vec<_>>(),
+            (1..=plan.steps.len()).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn clean_database_needs_no_repair() {
+        let dialect = active_dialect();
+        let snap = matching_snapshot(dialect.as_ref());
+        let plan = drift_repair_plan(
+            &config(),
+            &snap,
+            None,
+            None,
+            &HashMap::new(),
+            dialect.as_ref(),
+        )
+        .expect("repair plan");
+        assert!(plan.steps.is_empty());
+    }
+}
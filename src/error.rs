@@ -49,6 +49,8 @@ pub enum AppError {
     Unauthorized(String),
     #[error("forbidden: {0}")]
     Forbidden(String),
+    #[error("payload too large: {0}")]
+    PayloadTooLarge(String),
     #[error("bulk validation failed")]
     BulkValidation(Vec<BulkFieldError>),
 }
@@ -152,6 +154,7 @@ impl IntoResponse for AppError {
             AppError::Storage(_) => (StatusCode::INTERNAL_SERVER_ERROR, "storage_error"),
             AppError::Unauthorized(_) => (StatusCode::UNAUTHORIZED, "unauthorized"),
             AppError::Forbidden(_) => (StatusCode::FORBIDDEN, "forbidden"),
+            AppError::PayloadTooLarge(_) => (StatusCode::PAYLOAD_TOO_LARGE, "payload_too_large"),
             AppError::BulkValidation(_) => unreachable!(),
         };
         let body = ErrorBody {
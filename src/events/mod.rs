@@ -5,13 +5,18 @@
 //! to the decision-hub `/evaluate` endpoint inside a detached tokio task — the
 //! HTTP response is already on the wire before the publish begins.
 //!
+//! Triggers with `on: "schedule"` are not tied to a request; see [`scheduler`].
+//!
 //! Event type format: `{package_id}.{table_name}:{event_name}`
 //! Example: `manufacturing_core.materials:published`
 
+pub mod scheduler;
+
 use crate::config::resolved::ResolvedEntity;
 use crate::config::types::{EntityEventTrigger, EventCondition};
 use serde_json::Value;
 use std::sync::Arc;
+use tracing::Instrument;
 
 pub struct DecisionHubClient {
     base_url: String,
@@ -33,7 +38,13 @@ impl DecisionHubClient {
         Some(Arc::new(Self { base_url, client }))
     }
 
-    async fn publish(&self, tenant_id: &str, event_type: &str, context: Value) {
+    /// `GET {DECISION_HUB_URL}/health`, for the diagnostics probes.
+    pub(crate) async fn health(&self) -> Result<(), String> {
+        crate::diagnostics::probe_health(&self.client, &self.base_url).await
+    }
+
+    #[tracing::instrument(name = "decision_hub.publish", skip_all, fields(event_type = %event_type))]
+    pub(crate) async fn publish(&self, tenant_id: &str, event_type: &str, context: Value) {
         let payload = serde_json::json!({
             "tenant_id": tenant_id,
             "event_type": event_type,
@@ -41,8 +52,10 @@ impl DecisionHubClient {
         });
         let url = format!("{}/evaluate", self.base_url);
         log_curl(&url, &payload);
-        match self.client.post(&url).json(&payload).send().await {
+        let request = crate::telemetry::inject_trace_context(self.client.post(&url));
+        match request.json(&payload).send().await {
             Ok(resp) if !resp.status().is_success() => {
+                crate::metrics::record_event_publish("rejected");
                 let status = resp.status().as_u16();
                 let body = resp.text().await.unwrap_or_default();
                 tracing::warn!(
@@ -53,9 +66,11 @@ impl DecisionHubClient {
                 );
             }
             Err(e) => {
+                crate::metrics::record_event_publish("failed");
                 tracing::warn!(event_type = %event_type, error = %e, "decision-hub publish failed");
             }
             Ok(resp) => {
+                crate::metrics::record_event_publish("accepted");
                 // /evaluate answers 200 with {request_id, executions, matched} even when nothing
                 // matched — log the body so a silent no-op is visible without a DB query.
                 let body = resp.text().await.unwrap_or_default();
@@ -102,7 +117,7 @@ fn log_curl(url: &str, payload: &Value) {
 /// `pre_update_row` is the row fetched from DB *before* the update — only supplied for the
 /// "update" lifecycle when the entity has `changed_to` conditions. When present, `changed_to`
 /// requires a genuine transition: the field must have been a different value before the update.
-fn evaluate_condition(
+pub(crate) fn evaluate_condition(
     condition: &EventCondition,
     row: &Value,
     pre_update_row: Option<&Value>,
@@ -127,12 +142,13 @@ fn evaluate_condition(
     true
 }
 
-fn default_event_name(on: &str) -> &str {
+pub(crate) fn default_event_name(on: &str) -> &str {
     match on {
         "create" => "created",
         "update" => "updated",
         "delete" => "deleted",
         "archive" => "archived",
+        "schedule" => "scheduled",
         other => other,
     }
 }
@@ -263,53 +279,57 @@ pub fn spawn_events_with(
     let package_id = entity.package_id.clone();
     let table_name = entity.table_name.clone();
 
-    tokio::spawn(async move {
-        // Cache expansions across triggers: several triggers on one entity usually name the same
-        // includes, and a delete has no row left to read.
-        let mut expanded: std::collections::HashMap<String, Value> =
-            std::collections::HashMap::new();
-
-        for trigger in &triggers {
-            let suffix = trigger
-                .event_name
-                .as_deref()
-                .unwrap_or_else(|| default_event_name(trigger.on.as_str()));
-            let event_type = format!("{}.{}:{}", package_id, table_name, suffix);
-            tracing::info!(
-                tenant_id = %tenant_id,
-                event_type = %event_type,
-                lifecycle = %lifecycle,
-                "publishing decision-hub event"
-            );
+    // Keep the publish spans in the request's trace.
+    tokio::spawn(
+        async move {
+            // Cache expansions across triggers: several triggers on one entity usually name the same
+            // includes, and a delete has no row left to read.
+            let mut expanded: std::collections::HashMap<String, Value> =
+                std::collections::HashMap::new();
+
+            for trigger in &triggers {
+                let suffix = trigger
+                    .event_name
+                    .as_deref()
+                    .unwrap_or_else(|| default_event_name(trigger.on.as_str()));
+                let event_type = format!("{}.{}:{}", package_id, table_name, suffix);
+                tracing::info!(
+                    tenant_id = %tenant_id,
+                    event_type = %event_type,
+                    lifecycle = %lifecycle,
+                    "publishing decision-hub event"
+                );
 
-            let entity_value = match (&include_ctx, trigger.include.is_empty(), lifecycle) {
-                // Nothing requested, no context wired up, or the row is already gone.
-                (_, true, _) | (None, _, _) | (_, _, "delete") => api_row.clone(),
-                (Some(ctx), false, _) => {
-                    let mut names = trigger.include.clone();
-                    names.sort();
-                    names.dedup();
-                    let key = names.join(",");
-                    match expanded.get(&key) {
-                        Some(v) => v.clone(),
-                        None => {
-                            let v = fetch_with_includes(ctx, &names)
-                                .await
-                                .unwrap_or_else(|| api_row.clone());
-                            expanded.insert(key, v.clone());
-                            v
+                let entity_value = match (&include_ctx, trigger.include.is_empty(), lifecycle) {
+                    // Nothing requested, no context wired up, or the row is already gone.
+                    (_, true, _) | (None, _, _) | (_, _, "delete") => api_row.clone(),
+                    (Some(ctx), false, _) => {
+                        let mut names = trigger.include.clone();
+                        names.sort();
+                        names.dedup();
+                        let key = names.join(",");
+                        match expanded.get(&key) {
+                            Some(v) => v.clone(),
+                            None => {
+                                let v = fetch_with_includes(ctx, &names)
+                                    .await
+                                    .unwrap_or_else(|| api_row.clone());
+                                expanded.insert(key, v.clone());
+                                v
+                            }
                         }
                     }
-                }
-            };
-
-            let context = serde_json::json!({
-                "entity": entity_value,
-                "operation": lifecycle,
-            });
-            client.publish(&tenant_id, &event_type, context).await;
+                };
+
+                let context = serde_json::json!({
+                    "entity": entity_value,
+                    "operation": lifecycle,
+                });
+                client.publish(&tenant_id, &event_type, context).await;
+            }
         }
-    });
+        .instrument(tracing::Span::current()),
+    );
 }
 
 /// Re-read the affected row with `names` expanded. Returns `None` on any failure — the caller
@@ -0,0 +1,529 @@
+//! Background scheduler for `on: "schedule"` event triggers.
+//!
+//! Every `EVENT_SCHEDULER_INTERVAL_SECS` (default 60) the scheduler walks each tenant × installed
+//! package, finds entities with schedule triggers and publishes one decision-hub event per
+//! matching row:
+//!
+//! - `cron`: when an occurrence fell since the last claimed one, every row matching `condition`
+//!   fires once for that occurrence. Missed occurrences (downtime) collapse into the latest one.
+//! - `due_column`: every row whose timestamp passed since the trigger's watermark fires once.
+//!   The first run starts one interval in the past, so rows already overdue at deploy time are
+//!   not replayed.
+//!
+//! Delivery is at-most-once: cron occurrences are claimed by advancing a cursor in
+//! `_sys_event_schedule_state`, and each (row, occurrence) is claimed in `_sys_event_schedule_log`
+//! before it is published. Several replicas can run the scheduler side by side; only the claimer
+//! publishes, and a publish that fails after its claim is not retried.
+//! Claims older than the trigger's window (plus one interval) are pruned after each run.
+
+use super::{default_event_name, evaluate_condition, DecisionHubClient};
+use crate::config::resolved::ResolvedEntity;
+use crate::config::types::{EntityEventTrigger, EventCondition};
+use crate::error::AppError;
+use crate::handlers::entity::{
+    get_or_load_package_model, resolve_tenant_context, strip_sensitive_columns, TenantContext,
+};
+use crate::service::{CrudService, TenantExecutor};
+use crate::sql::{FilterNode, RsqlOp, SortSpec};
+use crate::state::AppState;
+use chrono::{DateTime, Duration, SecondsFormat, Utc};
+use serde_json::Value;
+use std::collections::HashSet;
+use std::str::FromStr;
+
+/// Rows fetched per page; each trigger pages until a short page.
+const DEFAULT_BATCH: u32 = 500;
+/// Occurrences further back than this are not searched for, so a long outage never makes the
+/// scheduler iterate over months of per-second cron slots.
+const MAX_CRON_LOOKBACK_DAYS: i64 = 31;
+
+/// Parse a cron expression. Accepts the standard 5-field form (`min hour dom mon dow`) as well as
+/// the 6/7-field form with leading seconds (and trailing year) understood by the `cron` crate.
+pub fn parse_cron(expr: &str) -> Result<cron::Schedule, String> {
+    let expr = expr.trim();
+    let full = if expr.split_whitespace().count() == 5 {
+        format!("0 {}", expr)
+    } else {
+        expr.to_string()
+    };
+    cron::Schedule::from_str(&full).map_err(|e| e.to_string())
+}
+
+/// Latest occurrence of `schedule` in `(since, now]`, if any.
+fn latest_occurrence(
+    schedule: &cron::Schedule,
+    since: DateTime<Utc>,
+    now: DateTime<Utc>,
+) -> Option<DateTime<Utc>> {
+    let since = since.max(now - Duration::days(MAX_CRON_LOOKBACK_DAYS));
+    schedule.after(&since).take_while(|t| *t <= now).last()
+}
+
+fn rfc3339(t: DateTime<Utc>) -> String {
+    t.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+/// Start the scheduler loop. Returns `None` (nothing spawned) when decision-hub publishing is not
+/// configured, since scheduled triggers would have nowhere to go.
+pub fn spawn_event_scheduler(state: AppState) -> Option<tokio::task::JoinHandle<()>> {
+    let client = state.event_client.clone()?;
+    let interval_secs: u64 = std::env::var("EVENT_SCHEDULER_INTERVAL_SECS")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .filter(|n| *n > 0)
+        .unwrap_or(60);
+    let batch: u32 = std::env::var("EVENT_SCHEDULER_BATCH")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .filter(|n| *n > 0)
+        .unwrap_or(DEFAULT_BATCH)
+        .min(1000);
+    tracing::info!(
+        interval_secs = interval_secs,
+        batch = batch,
+        "scheduled event triggers enabled"
+    );
+    Some(tokio::spawn(async move {
+        let interval = std::time::Duration::from_secs(interval_secs);
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            let lookback = Duration::seconds(interval_secs as i64);
+            if let Err(e) =
+                run_scheduled_triggers(&state, &client, Utc::now(), lookback, batch).await
+            {
+                tracing::warn!(error = %e, "event scheduler tick failed");
+            }
+        }
+    }))
+}
+
+/// One scheduler pass over every tenant and installed package. `lookback` is how far before
+/// `now` a trigger that has never run starts looking. Returns the number of events published.
+///
+/// Errors for a single tenant/package are logged and skipped so one broken tenant database cannot
+/// stall the others; only a failure to list packages aborts the pass.
+pub async fn run_scheduled_triggers(
+    state: &AppState,
+    client: &DecisionHubClient,
+    now: DateTime<Utc>,
+    lookback: Duration,
+    batch: u32,
+) -> Result<usize, AppError> {
+    let mut package_ids: Vec<String> = crate::store::list_packages(&state.pool)
+        .await?
+        .into_iter()
+        .map(|p| p.id)
+        .collect();
+    if !package_ids
+        .iter()
+        .any(|p| p == crate::store::DEFAULT_PACKAGE_ID)
+    {
+        package_ids.push(crate::store::DEFAULT_PACKAGE_ID.to_string());
+    }
+
+    let platform = crate::tenant::platform_tenant_id();
+    // Without database-enforced RLS, RLS tenants that share a database would all see the same
+    // rows; visit each shared database once so a row is not published once per tenant.
+    let mut shared_seen: HashSet<(Option<String>, String)> = HashSet::new();
+    let mut published = 0;
+
+    for tenant_id in state.tenant_registry.tenant_ids() {
+        for package_id in &package_ids {
+            let ctx = match resolve_tenant_context(state, Some(&tenant_id), None, Some(package_id))
+                .await
+            {
+                Ok(c) => c,
+                Err(e) => {
+                    tracing::warn!(tenant_id = %tenant_id, error = %e, "event scheduler: tenant skipped");
+                    break;
+                }
+            };
+            let model = match get_or_load_package_model(
+                state,
+                ctx.config_pool(),
+                ctx.package_cache_key(),
+                package_id,
+            )
+            .await
+            {
+                Ok(m) => m,
+                Err(e) => {
+                    tracing::debug!(package_id = %package_id, error = %e, "event scheduler: package skipped");
+                    continue;
+                }
+            };
+            if !model
+                .entities
+                .iter()
+                .any(|e| e.events.iter().any(|t| t.on == "schedule"))
+            {
+                continue;
+            }
+            if matches!(ctx, TenantContext::Rls { .. }) && !state.dialect.supports_rls() {
+                let db = state
+                    .tenant_registry
+                    .get(&tenant_id)
+                    .and_then(|e| e.database_url.clone());
+                if !shared_seen.insert((db, package_id.clone())) {
+                    continue;
+                }
+            }
+
+            for entity in &model.entities {
+                // Global tables are shared by every tenant; only the Platform Admin fires for them.
+                if entity.global && tenant_id != platform {
+                    continue;
+                }
+                for trigger in entity.events.iter().filter(|t| t.on == "schedule") {
+                    match run_trigger(
+                        state, client, &ctx, &tenant_id, entity, trigger, now, lookback, batch,
+                    )
+                    .await
+                    {
+                        Ok(n) => published += n,
+                        Err(e) => tracing::warn!(
+                            tenant_id = %tenant_id,
+                            entity = %entity.path_segment,
+                            trigger = %trigger.id,
+                            error = %e,
+                            "scheduled trigger failed"
+                        ),
+                    }
+                }
+            }
+        }
+    }
+    Ok(published)
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn run_trigger(
+    state: &AppState,
+    client: &DecisionHubClient,
+    ctx: &TenantContext,
+    tenant_id: &str,
+    entity: &ResolvedEntity,
+    trigger: &EntityEventTrigger,
+    now: DateTime<Utc>,
+    lookback: Duration,
+    batch: u32,
+) -> Result<usize, AppError> {
+    let Some(schedule) = trigger.schedule.as_ref() else {
+        return Ok(0);
+    };
+    let trigger_key = format!("{}.{}:{}", entity.package_id, entity.table_name, trigger.id);
+    let config_pool = ctx.config_pool();
+
+    // With a cron expression, nothing happens until an occurrence is claimed; the occurrence then
+    // stands in for "now" below.
+    let until = match &schedule.cron {
+        Some(expr) => {
+            let cron = parse_cron(expr).map_err(|e| {
+                AppError::BadRequest(format!("trigger {}: invalid cron: {}", trigger_key, e))
+            })?;
+            let since =
+                match crate::store::get_schedule_cursor(config_pool, tenant_id, &trigger_key)
+                    .await?
+                {
+                    Some(c) => DateTime::parse_from_rfc3339(&c)
+                        .map(|t| t.with_timezone(&Utc))
+                        .unwrap_or(now - lookback),
+                    None => now - lookback,
+                };
+            let Some(slot) = latest_occurrence(&cron, since, now) else {
+                return Ok(0);
+            };
+            if !crate::store::advance_schedule_cursor(
+                config_pool,
+                tenant_id,
+                &trigger_key,
+                &rfc3339(slot),
+            )
+            .await?
+            {
+                // Another replica claimed this occurrence.
+                return Ok(0);
+            }
+            slot
+        }
+        None => now,
+    };
+
+    let mut filters: Vec<FilterNode> = trigger
+        .condition
+        .as_ref()
+        .and_then(condition_filter)
+        .into_iter()
+        .collect();
+    // Start of the window rows are fetched from; fire records older than it are pruned below.
+    let mut window_start = until;
+    let (fire_key_of, watermark_key): (FireKey, Option<String>) = match &schedule.due_column {
+        Some(col) => {
+            let state_key = format!("{}#due", trigger_key);
+            let since = crate::store::get_schedule_cursor(config_pool, tenant_id, &state_key)
+                .await?
+                .unwrap_or_else(|| rfc3339(until - lookback));
+            if let Ok(t) = DateTime::parse_from_rfc3339(&since) {
+                window_start = t.with_timezone(&Utc);
+            }
+            filters.push(FilterNode::Leaf {
+                field: col.clone(),
+                op: RsqlOp::Ge,
+                values: vec![since],
+            });
+            filters.push(FilterNode::Leaf {
+                field: col.clone(),
+                op: RsqlOp::Le,
+                values: vec![rfc3339(until)],
+            });
+            (FireKey::Column(col.clone()), Some(state_key))
+        }
+        None => (FireKey::Occurrence(rfc3339(until)), None),
+    };
+    let filter = match filters.len() {
+        0 => None,
+        1 => filters.pop(),
+        _ => Some(FilterNode::And(filters)),
+    };
+    let sort: Vec<SortSpec> = entity
+        .pk_columns
+        .iter()
+        .map(|c| SortSpec {
+            field: c.clone(),
+            desc: false,
+        })
+        .collect();
+
+    let trigger_name = trigger
+        .event_name
+        .as_deref()
+        .unwrap_or_else(|| default_event_name(trigger.on.as_str()));
+    let event_type = format!(
+        "{}.{}:{}",
+        entity.package_id, entity.table_name, trigger_name
+    );
+    let mut published = 0;
+    let mut offset = 0;
+    loop {
+        let rows = fetch_page(state, ctx, entity, filter.as_ref(), &sort, batch, offset).await?;
+        let page_len = rows.len() as u32;
+        for raw_row in rows {
+            if let Some(cond) = &trigger.condition {
+                if !evaluate_condition(cond, &raw_row, None) {
+                    continue;
+                }
+            }
+            let row_key = entity
+                .pk_columns
+                .iter()
+                .map(|c| value_text(raw_row.get(c)))
+                .collect::<Vec<_>>()
+                .join(",");
+            let fire_key = match &fire_key_of {
+                FireKey::Occurrence(slot) => slot.clone(),
+                FireKey::Column(col) => value_text(raw_row.get(col)),
+            };
+            if !crate::store::claim_schedule_fire(
+                config_pool,
+                tenant_id,
+                &trigger_key,
+                &row_key,
+                &fire_key,
+            )
+            .await?
+            {
+                continue;
+            }
+            let mut api_row = raw_row;
+            strip_sensitive_columns(&mut api_row, &entity.sensitive_columns);
+            crate::case::value_keys_to_camel_case(&mut api_row);
+            tracing::info!(
+                tenant_id = %tenant_id,
+                event_type = %event_type,
+                "publishing scheduled decision-hub event"
+            );
+            let context = serde_json::json!({
+                "entity": api_row,
+                "operation": "schedule",
+                "scheduled_for": fire_key,
+            });
+            client.publish(tenant_id, &event_type, context).await;
+            published += 1;
+        }
+        if page_len < batch {
+            break;
+        }
+        offset += batch;
+    }
+
+    if let Some(state_key) = watermark_key {
+        crate::store::advance_schedule_cursor(config_pool, tenant_id, &state_key, &rfc3339(until))
+            .await?;
+    }
+    // Keep one extra interval so clock skew between replicas and the database cannot drop a
+    // record that the next pass still needs.
+    let keep = (now - window_start + lookback).num_seconds();
+    crate::store::prune_schedule_log(
+        config_pool,
+        tenant_id,
+        &trigger_key,
+        keep,
+        state.dialect.as_ref(),
+    )
+    .await?;
+    Ok(published)
+}
+
+/// What identifies one firing of a row: the claimed cron occurrence, or the row's due timestamp
+/// (so rescheduling a row to a new due time fires it again).
+enum FireKey {
+    Occurrence(String),
+    Column(String),
+}
+
+/// Push the simple parts of a trigger condition into SQL so only candidate rows are paged in.
+/// `changed_to` has no meaning without a previous row and is evaluated as `equals`.
+fn condition_filter(cond: &EventCondition) -> Option<FilterNode> {
+    let target = cond.equals.as_ref().or(cond.changed_to.as_ref());
+    if let Some(v) = target {
+        if v.is_null() {
+            return Some(FilterNode::Leaf {
+                field: cond.field.clone(),
+                op: RsqlOp::Null(true),
+                values: vec![],
+            });
+        }
+        return Some(FilterNode::Leaf {
+            field: cond.field.clone(),
+            op: RsqlOp::Eq,
+            values: vec![value_text(Some(v))],
+        });
+    }
+    cond.not_null.map(|not_null| FilterNode::Leaf {
+        field: cond.field.clone(),
+        op: RsqlOp::Null(!not_null),
+        values: vec![],
+    })
+}
+
+fn value_text(v: Option<&Value>) -> String {
+    match v {
+        Some(Value::String(s)) => s.clone(),
+        Some(Value::Null) | None => String::new(),
+        Some(v) => v.to_string(),
+    }
+}
+
+/// One page of rows, read inside a tenant-scoped transaction for RLS tenants.
+async fn fetch_page(
+    state: &AppState,
+    ctx: &TenantContext,
+    entity: &ResolvedEntity,
+    filter: Option<&FilterNode>,
+    sort: &[SortSpec],
+    limit: u32,
+    offset: u32,
+) -> Result<Vec<Value>, AppError> {
+    let pool = ctx.migration_pool();
+    let dialect = state.dialect.as_ref();
+    let mut rls_tx = match ctx.rls_tenant_id() {
+        Some(tenant) => {
+            let mut tx = pool.begin().await?;
+            if let Some(sql) = dialect.set_tenant_session_sql(tenant) {
+                sqlx::query(&sql).execute(&mut *tx).await?;
+            }
+            Some(tx)
+        }
+        None => None,
+    };
+    let rows = {
+        let mut executor = match rls_tx.as_mut() {
+            Some(tx) => TenantExecutor::conn(tx, dialect),
+            None => TenantExecutor::pool(pool, dialect),
+        };
+        CrudService::list(
+            &mut executor,
+            entity,
+            filter,
+            sort,
+            Some(limit),
+            Some(offset),
+            &[],
+            ctx.schema_override(),
+            dialect,
+            None,
+        )
+        .await?
+    };
+    if let Some(tx) = rls_tx {
+        tx.commit().await?;
+    }
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod scheduler_tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(h: u32, m: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2025, 3, 10, h, m, 0).unwrap()
+    }
+
+    #[test]
+    fn parse_cron_accepts_five_and_six_fields() {
+        assert!(parse_cron("*/15 * * * *").is_ok());
+        assert!(parse_cron("0 */15 * * * *").is_ok());
+        assert!(parse_cron("not a cron").is_err());
+    }
+
+    #[test]
+    fn latest_occurrence_collapses_missed_slots() {
+        let cron = parse_cron("0 * * * *").unwrap();
+        // 08:30 → 11:10 passes 09:00, 10:00 and 11:00; only the latest fires.
+        assert_eq!(
+            latest_occurrence(&cron, at(8, 30), at(11, 10)),
+            Some(at(11, 0))
+        );
+        // A claimed occurrence is not found again.
+        assert_eq!(latest_occurrence(&cron, at(11, 0), at(11, 59)), None);
+        // The end of the window is inclusive.
+        assert_eq!(
+            latest_occurrence(&cron, at(11, 0), at(12, 0)),
+            Some(at(12, 0))
+        );
+    }
+
+    #[test]
+    fn condition_filter_maps_simple_conditions() {
+        let cond = EventCondition {
+            field: "status".into(),
+            changed_to: None,
+            equals: Some(serde_json::json!("pending")),
+            not_null: None,
+        };
+        match condition_filter(&cond) {
+            Some(FilterNode::Leaf { field, op, values }) => {
+                assert_eq!(field, "status");
+                assert!(matches!(op, RsqlOp::Eq));
+                assert_eq!(values, vec!["pending".to_string()]);
+            }
+            other => panic!("unexpected filter: {:?}", other),
+        }
+        let cond = EventCondition {
+            field: "reminded_at".into(),
+            changed_to: None,
+            equals: None,
+            not_null: Some(false),
+        };
+        assert!(matches!(
+            condition_filter(&cond),
+            Some(FilterNode::Leaf {
+                op: RsqlOp::Null(true),
+                ..
+            })
+        ));
+    }
+}
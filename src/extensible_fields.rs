@@ -537,14 +537,13 @@ fn validate_one(column: &str, def: &ExtensibleFieldDef, val: &Value) -> Result<(
                 }
             }
         }
-        TypeCategory::Bool => {
-            if !val.is_boolean() {
-                return Err(AppError::Validation(format!(
-                    "extensible field '{}' must be a boolean",
-                    label
-                )));
-            }
+        TypeCategory::Bool if !val.is_boolean() => {
+            return Err(AppError::Validation(format!(
+                "extensible field '{}' must be a boolean",
+                label
+            )));
         }
+        TypeCategory::Bool => {}
         TypeCategory::Text
         | TypeCategory::Uuid
         | TypeCategory::Date
@@ -628,6 +627,7 @@ mod tests {
             versioning: None,
             mcp: None,
             extensible_columns: vec![column.into()],
+            shadow_columns: vec![],
         }
     }
 
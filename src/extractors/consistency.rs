@@ -0,0 +1,69 @@
+//! Extract the requested read consistency (`?consistency=strong`).
+
+use async_trait::async_trait;
+use axum::{
+    extract::{FromRequestParts, Query},
+    http::request::Parts,
+};
+use std::collections::HashMap;
+
+use crate::error::AppError;
+use crate::replica::Consistency;
+
+/// Query parameter selecting read consistency.
+pub const CONSISTENCY_PARAM: &str = "consistency";
+
+/// Extractor for `?consistency=` on read endpoints. `strong` reads from the primary; anything
+/// else may be served by a read replica (see [`crate::replica`]).
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ReadConsistency(pub Consistency);
+
+#[async_trait]
+impl<S> FromRequestParts<S> for ReadConsistency
+where
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        // Percent-decoded like every other query parameter; `Consistency::parse` trims it.
+        let Query(params) = Query::<HashMap<String, String>>::try_from_uri(&parts.uri)
+            .map_err(|e| AppError::BadRequest(format!("invalid query string: {}", e)))?;
+        let value = params.get(CONSISTENCY_PARAM).map(String::as_str);
+        Consistency::parse(value).map(ReadConsistency)
+    }
+}
+
+#[cfg(test)]
+mod consistency_tests {
+    use super::*;
+
+    async fn extract(uri: &str) -> Result<Consistency, AppError> {
+        let (mut parts, _) = axum::http::Request::builder()
+            .uri(uri)
+            .body(())
+            .unwrap()
+            .into_parts();
+        ReadConsistency::from_request_parts(&mut parts, &())
+            .await
+            .map(|ReadConsistency(c)| c)
+    }
+
+    #[tokio::test]
+    async fn consistency_is_decoded_and_trimmed() {
+        assert_eq!(extract("/x").await.unwrap(), Consistency::Eventual);
+        assert_eq!(
+            extract("/x?limit=5&consistency=strong").await.unwrap(),
+            Consistency::Strong
+        );
+        assert_eq!(
+            extract("/x?consistency=%20strong+").await.unwrap(),
+            Consistency::Strong
+        );
+        assert_eq!(
+            extract("/x?consistency=%73trong").await.unwrap(),
+            Consistency::Strong
+        );
+        assert!(extract("/x?consistency=bogus").await.is_err());
+    }
+}
@@ -1,2 +1,3 @@
+pub mod consistency;
 pub mod tenant;
 pub mod user;
@@ -1,13 +1,22 @@
-//! Asset handlers: standalone presigned URL generation.
+//! Asset handlers: standalone presigned URL generation, presigned direct uploads, the
+//! decompressing download proxy, orphaned-asset GC, and the local-storage download/upload route.
 
+use crate::config::ResolvedEntity;
 use crate::error::AppError;
 use crate::extractors::tenant::TenantId;
+use crate::extractors::user::UserId;
 use crate::state::AppState;
 use axum::{
-    extract::{Query, State},
+    body::Body,
+    extract::{Path, Query, Request, State},
+    http::header,
     Json,
 };
+use futures_util::TryStreamExt;
+use serde::Deserialize;
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 
 pub async fn sign_asset(
     State(state): State<AppState>,
@@ -46,3 +55,427 @@ pub async fn sign_asset(
         }),
     ))
 }
+
+/// GET /assets/local/*path?expires=&sig= — serve a file from the `local` storage provider.
+///
+/// Counterpart of `LocalProvider::presign_url`. No X-Tenant-ID: like a bucket's presigned URL, the
+/// signature is the authorization, so links work in `<img src>` and plain browser downloads.
+pub async fn download_local_asset(
+    State(state): State<AppState>,
+    Path(path): Path<String>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Result<impl axum::response::IntoResponse, AppError> {
+    let local = state
+        .storage
+        .as_ref()
+        .and_then(|s| s.as_local())
+        .ok_or_else(|| AppError::NotFound("local storage is not configured".into()))?;
+    let expires: i64 = params
+        .get("expires")
+        .and_then(|s| s.parse().ok())
+        .ok_or_else(|| AppError::Forbidden("invalid or expired asset link".into()))?;
+    let sig = params
+        .get("sig")
+        .ok_or_else(|| AppError::Forbidden("invalid or expired asset link".into()))?;
+    local.verify("GET", &path, expires, sig)?;
+
+    let storage: &dyn crate::storage::StorageProvider = local;
+    let meta = storage
+        .head(&path)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("asset not found: {}", path)))?;
+    let data = storage.download(&path).await?;
+    Ok((
+        axum::http::StatusCode::OK,
+        [
+            (
+                header::CONTENT_TYPE,
+                meta.content_type
+                    .unwrap_or_else(|| "application/octet-stream".into()),
+            ),
+            (header::CONTENT_LENGTH, meta.size.to_string()),
+        ],
+        Body::from_stream(data),
+    ))
+}
+
+/// PUT /assets/local/*path?expires=&sig= — receive a direct upload for the `local` storage
+/// provider, the target of a URL issued by `POST /assets/upload-url`.
+///
+/// Bodies over `STORAGE_LOCAL_MAX_UPLOAD_MB` get 413: up front from `Content-Length`, otherwise
+/// as soon as the stream passes the limit, and the partial file is deleted.
+pub async fn upload_local_asset(
+    State(state): State<AppState>,
+    Path(path): Path<String>,
+    Query(params): Query<HashMap<String, String>>,
+    request: Request,
+) -> Result<axum::http::StatusCode, AppError> {
+    let local = state
+        .storage
+        .as_ref()
+        .and_then(|s| s.as_local())
+        .ok_or_else(|| AppError::NotFound("local storage is not configured".into()))?;
+    let expires: i64 = params
+        .get("expires")
+        .and_then(|s| s.parse().ok())
+        .ok_or_else(|| AppError::Forbidden("invalid or expired asset link".into()))?;
+    let sig = params
+        .get("sig")
+        .ok_or_else(|| AppError::Forbidden("invalid or expired asset link".into()))?;
+    local.verify("PUT", &path, expires, sig)?;
+
+    let content_type = request
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("application/octet-stream")
+        .to_string();
+    let max = local.max_upload_bytes();
+    let too_large = || {
+        AppError::PayloadTooLarge(format!(
+            "upload exceeds the local storage limit of {} bytes",
+            max
+        ))
+    };
+    let declared = request
+        .headers()
+        .get(header::CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|s| s.parse::<u64>().ok());
+    if declared.is_some_and(|len| len > max) {
+        return Err(too_large());
+    }
+
+    // The stream can only fail with an io::Error; remember when it was the size limit.
+    let exceeded = Arc::new(AtomicBool::new(false));
+    let data = {
+        let exceeded = exceeded.clone();
+        let mut received = 0u64;
+        request
+            .into_body()
+            .into_data_stream()
+            .map_err(std::io::Error::other)
+            .and_then(move |chunk| {
+                received += chunk.len() as u64;
+                let result = if received > max {
+                    exceeded.store(true, Ordering::Relaxed);
+                    Err(std::io::Error::other("upload size limit exceeded"))
+                } else {
+                    Ok(chunk)
+                };
+                std::future::ready(result)
+            })
+    };
+    let storage: &dyn crate::storage::StorageProvider = local;
+    if let Err(e) = storage.upload(&path, Box::pin(data), &content_type).await {
+        let _ = storage.delete(&path).await;
+        return Err(if exceeded.load(Ordering::Relaxed) {
+            too_large()
+        } else {
+            e
+        });
+    }
+    Ok(axum::http::StatusCode::OK)
+}
+
+/// Find the entity an asset request targets: by path segment in `package_id`'s model for this
+/// tenant, or in the default model.
+async fn resolve_asset_entity(
+    state: &AppState,
+    tenant_id: &str,
+    path_segment: &str,
+    package_id: Option<&str>,
+) -> Result<ResolvedEntity, AppError> {
+    match package_id {
+        Some(package_id) => {
+            let ctx = crate::handlers::entity::resolve_tenant_context(
+                state,
+                Some(tenant_id),
+                None,
+                Some(package_id),
+            )
+            .await?;
+            crate::handlers::entity::get_or_load_package_model(
+                state,
+                ctx.config_pool(),
+                ctx.package_cache_key(),
+                package_id,
+            )
+            .await?
+            .entity_by_path(path_segment)
+            .cloned()
+        }
+        None => state
+            .model
+            .read()
+            .map_err(|_| AppError::BadRequest("state lock".into()))?
+            .entity_by_path(path_segment)
+            .cloned(),
+    }
+    .ok_or_else(|| AppError::NotFound(path_segment.to_string()))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DownloadAssetQuery {
+    /// Object path, as stored in the asset column.
+    pub path: String,
+    /// Entity path segment, as in `/api/v1/:path_segment`.
+    pub entity: String,
+    /// Asset column (camelCase or snake_case) whose `compression` applies to the object.
+    pub column: String,
+    /// Resolve `entity` in this package's model instead of the default one.
+    #[serde(default)]
+    pub package_id: Option<String>,
+}
+
+/// GET /assets/download?path=&entity=&column= — stream a stored asset through the API,
+/// decompressing it on the fly with the column's `compression`.
+///
+/// Needed for compressed columns, whose presigned URLs hand out the compressed bytes. The object
+/// is never buffered whole; the response carries its stored content type, and a
+/// `Content-Length` only when no decompression happens. The path must be held in `column` by a
+/// row the tenant can read (its schema, pool or RLS scope); otherwise 403.
+pub async fn download_asset(
+    State(state): State<AppState>,
+    TenantId(tenant_id_opt): TenantId,
+    UserId(user_id_opt): UserId,
+    Query(query): Query<DownloadAssetQuery>,
+) -> Result<axum::response::Response, AppError> {
+    let tenant_id = tenant_id_opt
+        .as_deref()
+        .filter(|s| !s.is_empty())
+        .ok_or_else(|| AppError::BadRequest("X-Tenant-ID header is required".into()))?;
+    let storage = state.storage.as_ref().ok_or_else(|| {
+        AppError::BadRequest("storage is not configured (set STORAGE_PROVIDER env var)".into())
+    })?;
+
+    let ctx = crate::handlers::entity::resolve_tenant_context(
+        &state,
+        Some(tenant_id),
+        None,
+        query.package_id.as_deref(),
+    )
+    .await?;
+    let entity = resolve_asset_entity(
+        &state,
+        tenant_id,
+        &query.entity,
+        query.package_id.as_deref(),
+    )
+    .await?;
+    crate::authrs::check_entity_permission_opt(
+        &state.authrs_client,
+        Some(tenant_id),
+        user_id_opt.as_deref(),
+        &entity,
+        "get",
+    )
+    .await?;
+    let col_name = crate::case::to_snake_case(&query.column);
+    let col = entity
+        .columns
+        .iter()
+        .find(|c| c.name == col_name && c.is_asset)
+        .ok_or_else(|| {
+            AppError::BadRequest(format!("'{}' is not an asset column", query.column))
+        })?;
+    let compression = col
+        .asset_config
+        .as_ref()
+        .and_then(|c| c.compression.as_deref())
+        .unwrap_or("none");
+    if !crate::handlers::entity::asset_path_readable(&state, &ctx, &entity, col, &query.path)
+        .await?
+    {
+        return Err(AppError::Forbidden(format!(
+            "'{}' is not held by a '{}' row this tenant can read",
+            query.path, query.column
+        )));
+    }
+
+    let meta = storage
+        .head(&query.path)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("asset not found: {}", query.path)))?;
+    let data = storage.download(&query.path).await?;
+    let content_type = meta
+        .content_type
+        .unwrap_or_else(|| "application/octet-stream".into());
+    let mut response = axum::response::Response::builder()
+        .status(axum::http::StatusCode::OK)
+        .header(header::CONTENT_TYPE, content_type);
+    if compression.eq_ignore_ascii_case("none") {
+        response = response.header(header::CONTENT_LENGTH, meta.size);
+    }
+    response
+        .body(Body::from_stream(crate::storage::decompress_stream(
+            data,
+            compression,
+        )))
+        .map_err(|e| AppError::Storage(e.to_string()))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UploadUrlRequest {
+    /// Entity path segment, as in `/api/v1/:path_segment`.
+    pub entity: String,
+    /// Asset column (camelCase or snake_case).
+    pub column: String,
+    /// Original filename; its extension is kept on the object path and checked against
+    /// `allowed_extensions`.
+    pub filename: String,
+    pub content_type: String,
+    /// Resolve `entity` in this package's model instead of the default one.
+    #[serde(default)]
+    pub package_id: Option<String>,
+    /// URL lifetime in seconds (default 900).
+    #[serde(default)]
+    pub expires: Option<u64>,
+}
+
+/// POST /assets/upload-url — presign a direct-to-bucket upload for an asset column.
+///
+/// Returns the URL, the method and headers the client must use, and the object `path` to send
+/// as the column's value on create/update; the path is recorded as issued to this tenant.
+/// Filename, extension and mime type are checked against the column's asset validation up front;
+/// the object's real size and content type are checked when the path is written to a row. Columns
+/// with `compression` configured are rejected — their objects must go through the API so the
+/// server can compress them.
+pub async fn presign_asset_upload(
+    State(state): State<AppState>,
+    TenantId(tenant_id_opt): TenantId,
+    UserId(user_id_opt): UserId,
+    Json(req): Json<UploadUrlRequest>,
+) -> Result<impl axum::response::IntoResponse, AppError> {
+    let tenant_id = tenant_id_opt
+        .as_deref()
+        .filter(|s| !s.is_empty())
+        .ok_or_else(|| AppError::BadRequest("X-Tenant-ID header is required".into()))?;
+    let storage = state.storage.as_ref().ok_or_else(|| {
+        AppError::BadRequest("storage is not configured (set STORAGE_PROVIDER env var)".into())
+    })?;
+
+    let entity =
+        resolve_asset_entity(&state, tenant_id, &req.entity, req.package_id.as_deref()).await?;
+    if !entity
+        .operations
+        .iter()
+        .any(|o| o == "create" || o == "update")
+    {
+        return Err(AppError::BadRequest(format!(
+            "entity '{}' does not allow create or update",
+            entity.path_segment
+        )));
+    }
+    crate::handlers::entity::ensure_global_write_allowed(&entity, Some(tenant_id))?;
+    crate::authrs::check_entity_permission_opt(
+        &state.authrs_client,
+        Some(tenant_id),
+        user_id_opt.as_deref(),
+        &entity,
+        "post",
+    )
+    .await?;
+
+    let col_name = crate::case::to_snake_case(&req.column);
+    let col = entity
+        .columns
+        .iter()
+        .find(|c| c.name == col_name && c.is_asset)
+        .ok_or_else(|| AppError::BadRequest(format!("'{}' is not an asset column", req.column)))?;
+    let asset_cfg = col.asset_config.as_ref();
+    let compression = asset_cfg
+        .and_then(|c| c.compression.as_deref())
+        .unwrap_or("none");
+    if !compression.eq_ignore_ascii_case("none") {
+        return Err(AppError::BadRequest(format!(
+            "column '{}' uses {} compression; upload through the API instead",
+            req.column, compression
+        )));
+    }
+    if let Some(rule) = entity.validation.get(&col_name) {
+        // Size is unknown until the object exists; it is checked when the path is saved.
+        crate::storage::validate_asset_metadata(&col_name, &req.filename, &req.content_type, rule)?;
+    }
+
+    let path =
+        crate::handlers::entity::asset_object_name(&entity, asset_cfg, tenant_id, &req.filename);
+    let expires = req.expires.unwrap_or(900);
+    let upload = storage
+        .presign_upload(&path, &req.content_type, expires)
+        .await?;
+    // Lets create/update accept the path from this tenant whatever the column's prefix.
+    crate::store::record_asset_upload(
+        &state.pool,
+        tenant_id,
+        &path,
+        &entity.table_name,
+        &col_name,
+        state.dialect.as_ref(),
+    )
+    .await?;
+    let headers: serde_json::Map<String, serde_json::Value> = upload
+        .headers
+        .into_iter()
+        .map(|(k, v)| (k, serde_json::Value::String(v)))
+        .collect();
+
+    Ok((
+        axum::http::StatusCode::OK,
+        Json(crate::response::SuccessOne {
+            data: serde_json::json!({
+                "url": upload.url,
+                "method": upload.method,
+                "headers": headers,
+                "path": path,
+                "expires_at": upload.expires_at.to_rfc3339(),
+                "expires_in": upload.expires_in,
+            }),
+            meta: None,
+        }),
+    ))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssetGcQuery {
+    /// Only objects last modified more than this many hours ago can be orphans (default 24).
+    #[serde(default)]
+    pub grace_hours: Option<u32>,
+    /// Delete the orphans found instead of only reporting them.
+    #[serde(default)]
+    pub delete: bool,
+}
+
+/// POST /assets/gc?graceHours=&delete= — find (and optionally delete) stored objects that no
+/// asset column references any more. Restricted to the Platform Admin tenant, since it reads every
+/// tenant's rows. See `storage::gc` for what counts as an orphan.
+pub async fn collect_asset_garbage(
+    State(state): State<AppState>,
+    TenantId(tenant_id_opt): TenantId,
+    Query(query): Query<AssetGcQuery>,
+) -> Result<impl axum::response::IntoResponse, AppError> {
+    let tenant_id = tenant_id_opt
+        .as_deref()
+        .filter(|s| !s.is_empty())
+        .ok_or_else(|| AppError::BadRequest("X-Tenant-ID header is required".into()))?;
+    if tenant_id != crate::tenant::platform_tenant_id() {
+        return Err(AppError::Forbidden(
+            "asset garbage collection is restricted to the Platform Admin".into(),
+        ));
+    }
+    let options = crate::storage::gc::GcOptions {
+        grace: chrono::Duration::hours(i64::from(query.grace_hours.unwrap_or(24))),
+        delete: query.delete,
+    };
+    let report = crate::storage::gc::collect_orphaned_assets(&state, &options).await?;
+    Ok((
+        axum::http::StatusCode::OK,
+        Json(crate::response::SuccessOne {
+            data: report,
+            meta: None,
+        }),
+    ))
+}
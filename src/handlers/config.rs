@@ -192,3 +192,25 @@ get_config_handler!(get_indexes, "indexes");
 get_config_handler!(get_relationships, "relationships");
 get_config_handler!(get_api_entities, "api_entities");
 get_config_handler!(get_kv_stores, "kv_stores");
+
+/// GET /config/tenant-pools — connection pool statistics for tenants with their own database,
+/// with the limits they run under (see `tenant_pool`). Restricted to the Platform Admin tenant.
+pub async fn tenant_pool_stats_handler(
+    State(state): State<AppState>,
+    TenantId(tenant_id_opt): TenantId,
+) -> Result<impl axum::response::IntoResponse, AppError> {
+    let tenant_id = tenant_id_opt
+        .as_deref()
+        .filter(|s| !s.is_empty())
+        .ok_or_else(|| AppError::BadRequest("X-Tenant-ID header is required".into()))?;
+    if tenant_id != crate::tenant::platform_tenant_id() {
+        return Err(AppError::Forbidden(
+            "tenant pool statistics are restricted to the Platform Admin".into(),
+        ));
+    }
+    let stats = state.tenant_pools.stats()?;
+    Ok(Json(crate::response::SuccessOne {
+        data: serde_json::to_value(stats).map_err(|e| AppError::BadRequest(e.to_string()))?,
+        meta: None,
+    }))
+}
@@ -12,15 +12,20 @@ use crate::events::spawn_events;
 use crate::extensible_fields::{
     load_registry, validate_extensible_fields, ExtensibleRegistry, ValidateMode,
 };
+use crate::extractors::consistency::ReadConsistency;
 use crate::extractors::tenant::{ActAsTenant, TenantId};
 use crate::extractors::user::UserId;
+use crate::replica::Consistency;
 use crate::service::{CrudService, RequestValidator, TenantExecutor};
 use crate::sql::{
     parse_rsql, parse_sort, select_history_by_version, select_history_list, FilterNode,
     IncludeSelect,
 };
 use crate::state::AppState;
-use crate::storage::{compress, resolve_prefix, validate_asset_field};
+use crate::storage::{
+    bytes_stream, compress_stream, resolve_prefix, validate_asset_field, validate_asset_metadata,
+    validate_asset_size,
+};
 use crate::store::DEFAULT_PACKAGE_ID;
 use crate::tenant::TenantStrategy;
 use axum::{
@@ -419,20 +424,27 @@ fn query_value_for_column(entity: &ResolvedEntity, col: &str, s: &str) -> Value
     Value::String(s.to_string())
 }
 
-/// Collected file from a multipart upload field.
+/// A file field of a multipart request, already streamed to storage.
 struct UploadedFile {
-    field_name: String,
-    filename: String,
-    content_type: String,
-    data: Vec<u8>,
+    /// Asset column (snake_case) the file was sent for.
+    column: String,
+    /// Storage path the file was uploaded to.
+    path: String,
 }
 
-/// Parse a multipart request into a text body map and a list of file fields.
+/// Parse a multipart request into a text body map and the file fields it carried.
+///
+/// Files are streamed to storage as they arrive, so a request is never held in memory whole;
+/// see [`upload_multipart_file`]. A single-file `asset` column takes the first file sent for it;
+/// further files for that column are skipped.
 ///
 /// Repeated text parts with the same field name are collected into a `Value::Array`
 /// so that callers can send multiple existing paths for an `asset[]` column alongside
 /// new file uploads under the same field name.
 async fn parse_multipart(
+    state: &AppState,
+    entity: &ResolvedEntity,
+    tenant_id: &str,
     mut multipart: axum::extract::Multipart,
 ) -> Result<(HashMap<String, Value>, Vec<UploadedFile>), AppError> {
     // Accumulate all text values per field before converting to Value.
@@ -445,30 +457,52 @@ async fn parse_multipart(
         .map_err(|e| AppError::BadRequest(e.to_string()))?
     {
         let field_name = field.name().unwrap_or("").to_string();
-        let filename = field.file_name().map(|s| s.to_string());
-        let content_type = field
-            .content_type()
-            .unwrap_or("application/octet-stream")
-            .to_string();
-        let data = field
-            .bytes()
-            .await
-            .map_err(|e| AppError::BadRequest(e.to_string()))?
-            .to_vec();
-
-        if let Some(fname) = filename {
-            files.push(UploadedFile {
-                field_name,
-                filename: fname,
-                content_type,
-                data,
-            });
-        } else {
+        let Some(filename) = field.file_name().map(|s| s.to_string()) else {
+            let data = field
+                .bytes()
+                .await
+                .map_err(|e| AppError::BadRequest(e.to_string()))?
+                .to_vec();
             let text = String::from_utf8(data).map_err(|e| {
                 AppError::BadRequest(format!("field '{}' is not valid UTF-8: {}", field_name, e))
             })?;
             text_fields.entry(field_name).or_default().push(text);
+            continue;
+        };
+
+        let col_name = to_snake_case(&field_name);
+        let col = entity
+            .columns
+            .iter()
+            .find(|c| c.name == col_name)
+            .ok_or_else(|| AppError::BadRequest(format!("unknown field: {}", field_name)))?;
+        if !col.is_asset {
+            return Err(AppError::BadRequest(format!(
+                "field '{}' is not an asset column",
+                field_name
+            )));
+        }
+        if !col.asset_is_array && files.iter().any(|f| f.column == col_name) {
+            continue;
         }
+        let content_type = field
+            .content_type()
+            .unwrap_or("application/octet-stream")
+            .to_string();
+        let path = upload_multipart_file(
+            state,
+            entity,
+            tenant_id,
+            col,
+            &filename,
+            &content_type,
+            field,
+        )
+        .await?;
+        files.push(UploadedFile {
+            column: col_name,
+            path,
+        });
     }
 
     // Convert: single value → Value::String, multiple values → Value::Array.
@@ -487,6 +521,89 @@ async fn parse_multipart(
     Ok((body, files))
 }
 
+/// Stream one multipart file field to storage for asset column `col`, compressing it on the fly
+/// with the column's `compression`. Returns the storage path.
+///
+/// Mime type, extension and filename length are validated before the upload starts and
+/// `max_size_mb` while it streams (the upload is cut off as soon as the limit is passed);
+/// `min_size_kb` is checked once the whole file has been received. A file that fails any check
+/// is deleted from storage again.
+async fn upload_multipart_file(
+    state: &AppState,
+    entity: &ResolvedEntity,
+    tenant_id: &str,
+    col: &crate::config::ColumnInfo,
+    filename: &str,
+    content_type: &str,
+    field: axum::extract::multipart::Field<'_>,
+) -> Result<String, AppError> {
+    use futures_util::StreamExt;
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::{Arc, Mutex};
+
+    let storage = state.storage.as_ref().ok_or_else(|| {
+        AppError::BadRequest("storage is not configured (set STORAGE_PROVIDER env var)".into())
+    })?;
+    let rule = entity.validation.get(&col.name);
+    if let Some(rule) = rule {
+        validate_asset_metadata(&col.name, filename, content_type, rule)?;
+    }
+
+    // The stream can only fail with an io::Error; keep the real cause to report afterwards.
+    let received = Arc::new(AtomicU64::new(0));
+    let failure: Arc<Mutex<Option<AppError>>> = Arc::new(Mutex::new(None));
+    let max_mb = rule.and_then(|r| r.max_size_mb);
+    let data = {
+        let (received, failure, col_name) = (received.clone(), failure.clone(), col.name.clone());
+        field.map(move |chunk| {
+            let fail = |err: AppError| {
+                let msg = err.to_string();
+                *failure.lock().unwrap_or_else(|p| p.into_inner()) = Some(err);
+                std::io::Error::other(msg)
+            };
+            let chunk = chunk.map_err(|e| fail(AppError::BadRequest(e.to_string())))?;
+            let total =
+                received.fetch_add(chunk.len() as u64, Ordering::Relaxed) + chunk.len() as u64;
+            if let Some(max_mb) = max_mb.filter(|mb| total > (mb * 1024.0 * 1024.0) as u64) {
+                return Err(fail(AppError::Validation(format!(
+                    "{}: file size exceeds maximum of {:.1} MB",
+                    col_name, max_mb
+                ))));
+            }
+            Ok(chunk)
+        })
+    };
+
+    let asset_cfg = col.asset_config.as_ref();
+    let compression = asset_cfg
+        .and_then(|c| c.compression.as_deref())
+        .unwrap_or("none");
+    let object_name = asset_object_name(entity, asset_cfg, tenant_id, filename);
+    let result = storage
+        .upload(
+            &object_name,
+            compress_stream(Box::pin(data), compression),
+            content_type,
+        )
+        .await
+        .map_err(|e| {
+            failure
+                .lock()
+                .unwrap_or_else(|p| p.into_inner())
+                .take()
+                .unwrap_or(e)
+        })
+        .and_then(|()| match rule {
+            Some(rule) => validate_asset_size(&col.name, received.load(Ordering::Relaxed), rule),
+            None => Ok(()),
+        });
+    if let Err(e) = result {
+        let _ = storage.delete(&object_name).await;
+        return Err(e);
+    }
+    Ok(object_name)
+}
+
 /// Return an error if the entity has asset/asset[] columns but no storage provider is configured.
 /// Called at the top of every write handler so the error is immediate and descriptive.
 fn require_storage_for_assets(state: &AppState, entity: &ResolvedEntity) -> Result<(), AppError> {
@@ -509,121 +626,231 @@ fn require_storage_for_assets(state: &AppState, entity: &ResolvedEntity) -> Resu
     Ok(())
 }
 
-/// Upload all file fields that correspond to asset columns, inserting paths into body.
-/// For `asset[]` columns, multiple files with the same field name are collected into a JSON array.
-async fn process_asset_uploads(
+/// Fresh storage path for an upload to an asset column: the column's prefix template, a random
+/// UUID, and the original filename's extension.
+pub(crate) fn asset_object_name(
+    entity: &ResolvedEntity,
+    asset_cfg: Option<&crate::config::AssetColumnConfig>,
+    tenant_id: &str,
+    filename: &str,
+) -> String {
+    let prefix_template = asset_cfg
+        .and_then(|c| c.prefix.as_deref())
+        .unwrap_or(crate::storage::DEFAULT_ASSET_PREFIX);
+    let prefix = resolve_prefix(prefix_template, tenant_id, &entity.table_name);
+    let ext = std::path::Path::new(filename)
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| format!(".{}", e))
+        .unwrap_or_default();
+    format!("{}/{}{}", prefix, uuid::Uuid::new_v4(), ext)
+}
+
+/// Asset paths the client sent as plain strings, as (column, path) pairs — typically objects it
+/// uploaded itself through `POST /assets/upload-url`. Must be collected before upload processing
+/// turns files and JSON values into paths, so only client-supplied paths are returned.
+fn client_asset_paths(
+    entity: &ResolvedEntity,
+    body: &HashMap<String, Value>,
+) -> Vec<(String, String)> {
+    let mut out = Vec::new();
+    for col in entity.columns.iter().filter(|c| c.is_asset) {
+        match body.get(&col.name) {
+            Some(Value::String(s)) if !s.is_empty() => out.push((col.name.clone(), s.clone())),
+            Some(Value::Array(arr)) => {
+                for v in arr {
+                    if let Value::String(s) = v {
+                        if !s.is_empty() {
+                            out.push((col.name.clone(), s.clone()));
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    out
+}
+
+/// Check client-supplied asset paths before they are written to a row: the object must exist in
+/// storage, its stored content type and size must satisfy the column's asset validation rules,
+/// and the tenant must own it (see [`tenant_owns_asset_path`]). Paths the row already holds
+/// (`old_row`, on update) are kept as-is without a lookup.
+#[allow(clippy::too_many_arguments)]
+async fn verify_client_asset_paths(
     state: &AppState,
+    executor: &mut TenantExecutor<'_>,
+    schema_override: Option<&str>,
     entity: &ResolvedEntity,
     tenant_id: &str,
-    body: &mut HashMap<String, Value>,
-    files: Vec<UploadedFile>,
+    paths: Vec<(String, String)>,
+    old_row: Option<&Value>,
 ) -> Result<(), AppError> {
-    if files.is_empty() {
+    if paths.is_empty() {
         return Ok(());
     }
     let storage = state.storage.as_ref().ok_or_else(|| {
         AppError::BadRequest("storage is not configured (set STORAGE_PROVIDER env var)".into())
     })?;
-
-    // Group files by field name to support asset[] (multiple files per column).
-    let mut groups: std::collections::BTreeMap<String, Vec<UploadedFile>> =
-        std::collections::BTreeMap::new();
-    for file in files {
-        groups
-            .entry(file.field_name.clone())
-            .or_default()
-            .push(file);
+    for (col, path) in paths {
+        if old_row.is_some_and(|row| collect_row_asset_paths(row, &col).contains(&path)) {
+            continue;
+        }
+        let meta = storage.head(&path).await?.ok_or_else(|| {
+            AppError::Validation(format!(
+                "{}: asset '{}' does not exist in storage",
+                col, path
+            ))
+        })?;
+        if let Some(rule) = entity.validation.get(&col) {
+            validate_asset_field(
+                &col,
+                &path,
+                meta.content_type
+                    .as_deref()
+                    .unwrap_or("application/octet-stream"),
+                meta.size as usize,
+                rule,
+            )?;
+        }
+        let Some(column) = entity.columns.iter().find(|c| c.name == col) else {
+            continue;
+        };
+        if !tenant_owns_asset_path(
+            state,
+            executor,
+            schema_override,
+            entity,
+            column,
+            tenant_id,
+            &path,
+        )
+        .await?
+        {
+            return Err(AppError::Validation(format!(
+                "{}: asset '{}' is not an upload by this tenant",
+                col, path
+            )));
+        }
     }
+    Ok(())
+}
 
-    for (field_name, group) in groups {
-        let col_name = to_snake_case(&field_name);
-        let col = entity
-            .columns
-            .iter()
-            .find(|c| c.name == col_name)
-            .ok_or_else(|| AppError::BadRequest(format!("unknown field: {}", field_name)))?;
+/// Whether `tenant_id` may write `path` into asset column `column`. Any one of these suffices:
+/// the path has the shape of an upload by this tenant under a prefix containing `{tenant_id}`
+/// ([`crate::storage::is_tenant_upload`]); it was issued to this tenant for the column by
+/// `POST /assets/upload-url` ([`crate::store::asset_upload_issued`]); or a row the tenant can read
+/// already holds it in that column ([`asset_path_referenced`]). The last two cover columns whose
+/// prefix does not name the tenant, and paths saved before these checks existed.
+#[allow(clippy::too_many_arguments)]
+async fn tenant_owns_asset_path(
+    state: &AppState,
+    executor: &mut TenantExecutor<'_>,
+    schema_override: Option<&str>,
+    entity: &ResolvedEntity,
+    column: &crate::config::ColumnInfo,
+    tenant_id: &str,
+    path: &str,
+) -> Result<bool, AppError> {
+    let template = column
+        .asset_config
+        .as_ref()
+        .and_then(|c| c.prefix.as_deref())
+        .unwrap_or(crate::storage::DEFAULT_ASSET_PREFIX);
+    if crate::storage::is_tenant_upload(template, tenant_id, &entity.table_name, path) {
+        return Ok(true);
+    }
+    if crate::store::asset_upload_issued(
+        &state.pool,
+        tenant_id,
+        path,
+        &entity.table_name,
+        &column.name,
+        state.dialect.as_ref(),
+    )
+    .await?
+    {
+        return Ok(true);
+    }
+    asset_path_referenced(executor, schema_override, entity, column, path).await
+}
 
-        if !col.is_asset {
-            return Err(AppError::BadRequest(format!(
-                "field '{}' is not an asset column",
-                field_name
-            )));
+/// Whether a row visible through `executor` holds `path` in asset column `column` — in the
+/// entity's table or, for versioned entities, its `_history` table. Run it in the tenant's
+/// context (RLS transaction or tenant pool) so only that tenant's rows count.
+pub(crate) async fn asset_path_referenced(
+    executor: &mut TenantExecutor<'_>,
+    schema_override: Option<&str>,
+    entity: &ResolvedEntity,
+    column: &crate::config::ColumnInfo,
+    path: &str,
+) -> Result<bool, AppError> {
+    let versioned = entity.versioning.as_ref().is_some_and(|v| v.enabled);
+    for history in [false, true] {
+        if history && !versioned {
+            continue;
+        }
+        if CrudService::asset_referenced(executor, entity, column, history, path, schema_override)
+            .await?
+        {
+            return Ok(true);
         }
+    }
+    Ok(false)
+}
 
-        if col.asset_is_array {
-            // Seed with any existing paths sent as text parts (Option B merge semantics):
-            // clients re-send paths they want to keep; omitted paths are dropped.
-            let mut paths: Vec<Value> = match body.get(&col_name) {
-                Some(Value::Array(arr)) => arr.clone(),
-                Some(Value::String(s)) if !s.is_empty() => vec![Value::String(s.clone())],
+/// [`asset_path_referenced`] run in `ctx`: an RLS transaction for shared tables, the tenant's
+/// pool and schema otherwise. Used to authorize asset downloads.
+pub(crate) async fn asset_path_readable(
+    state: &AppState,
+    ctx: &TenantContext,
+    entity: &ResolvedEntity,
+    column: &crate::config::ColumnInfo,
+    path: &str,
+) -> Result<bool, AppError> {
+    let mut rls_tx = begin_rls_tx(state, ctx).await?;
+    let (mut executor, schema_override) = match ctx {
+        TenantContext::Pool {
+            pool,
+            schema_override,
+            ..
+        } => (
+            TenantExecutor::pool(pool, state.dialect.as_ref()),
+            schema_override.as_deref(),
+        ),
+        TenantContext::Rls { .. } => (
+            TenantExecutor::conn(&mut *rls_tx.as_mut().unwrap(), state.dialect.as_ref()),
+            None,
+        ),
+    };
+    asset_path_referenced(&mut executor, schema_override, entity, column, path).await
+}
+
+/// Insert the storage paths of uploaded files into `body`. An `asset` column takes its file's
+/// path; an `asset[]` column appends the new paths to the existing paths sent as text parts
+/// (Option B merge semantics: clients re-send paths they want to keep; omitted paths are dropped).
+fn merge_uploaded_assets(
+    entity: &ResolvedEntity,
+    body: &mut HashMap<String, Value>,
+    files: Vec<UploadedFile>,
+) {
+    for file in files {
+        let is_array = entity
+            .columns
+            .iter()
+            .any(|c| c.name == file.column && c.asset_is_array);
+        if is_array {
+            let mut paths: Vec<Value> = match body.remove(&file.column) {
+                Some(Value::Array(arr)) => arr,
+                Some(Value::String(s)) if !s.is_empty() => vec![Value::String(s)],
                 _ => Vec::new(),
             };
-            // Upload new files and append their paths.
-            for file in group {
-                if let Some(rule) = entity.validation.get(&col_name) {
-                    validate_asset_field(
-                        &col_name,
-                        &file.filename,
-                        &file.content_type,
-                        file.data.len(),
-                        rule,
-                    )?;
-                }
-                let asset_cfg = col.asset_config.as_ref();
-                let compression = asset_cfg
-                    .and_then(|c| c.compression.as_deref())
-                    .unwrap_or("none");
-                let data = compress(file.data, compression)?;
-                let prefix_template = asset_cfg
-                    .and_then(|c| c.prefix.as_deref())
-                    .unwrap_or("{entity}/{yyyy}/{mm}/{dd}");
-                let prefix = resolve_prefix(prefix_template, tenant_id, &entity.table_name);
-                let ext = std::path::Path::new(&file.filename)
-                    .extension()
-                    .and_then(|e| e.to_str())
-                    .map(|e| format!(".{}", e))
-                    .unwrap_or_default();
-                let object_name = format!("{}/{}{}", prefix, uuid::Uuid::new_v4(), ext);
-                storage
-                    .upload(&object_name, data, &file.content_type)
-                    .await?;
-                paths.push(Value::String(object_name));
-            }
-            body.insert(col_name, Value::Array(paths));
+            paths.push(Value::String(file.path));
+            body.insert(file.column, Value::Array(paths));
         } else {
-            // Single-file asset: take the first (and expected only) file.
-            let file = group.into_iter().next().unwrap();
-            if let Some(rule) = entity.validation.get(&col_name) {
-                validate_asset_field(
-                    &col_name,
-                    &file.filename,
-                    &file.content_type,
-                    file.data.len(),
-                    rule,
-                )?;
-            }
-            let asset_cfg = col.asset_config.as_ref();
-            let compression = asset_cfg
-                .and_then(|c| c.compression.as_deref())
-                .unwrap_or("none");
-            let data = compress(file.data, compression)?;
-            let prefix_template = asset_cfg
-                .and_then(|c| c.prefix.as_deref())
-                .unwrap_or("{entity}/{yyyy}/{mm}/{dd}");
-            let prefix = resolve_prefix(prefix_template, tenant_id, &entity.table_name);
-            let ext = std::path::Path::new(&file.filename)
-                .extension()
-                .and_then(|e| e.to_str())
-                .map(|e| format!(".{}", e))
-                .unwrap_or_default();
-            let object_name = format!("{}/{}{}", prefix, uuid::Uuid::new_v4(), ext);
-            storage
-                .upload(&object_name, data, &file.content_type)
-                .await?;
-            body.insert(col_name, Value::String(object_name));
+            body.insert(file.column, Value::String(file.path));
         }
     }
-    Ok(())
 }
 
 /// Upload a single JSON value (Object or Array) to storage and return its path.
@@ -660,11 +887,11 @@ async fn upload_json_value(
     let compression = asset_cfg
         .and_then(|c| c.compression.as_deref())
         .unwrap_or("none");
-    let data = compress(data, compression)?;
+    let data = compress_stream(bytes_stream(data), compression);
 
     let prefix_template = asset_cfg
         .and_then(|c| c.prefix.as_deref())
-        .unwrap_or("{entity}/{yyyy}/{mm}/{dd}");
+        .unwrap_or(crate::storage::DEFAULT_ASSET_PREFIX);
     let prefix = resolve_prefix(prefix_template, tenant_id, &entity.table_name);
     let object_name = format!("{}/{}.json", prefix, uuid::Uuid::new_v4());
 
@@ -1135,6 +1362,49 @@ pub async fn resolve_tenant_context(
     }
 }
 
+/// Resolve the context for a read-only request. Like [`resolve_tenant_context`], but entity data
+/// is read from the tenant's read replica when one is configured and usable, unless `consistency`
+/// is strong (see [`crate::replica`]). Config is always read from the primary. Never use the
+/// result for writes.
+pub async fn resolve_read_context(
+    state: &AppState,
+    tenant_id_opt: Option<&str>,
+    act_as_opt: Option<&str>,
+    package_id_opt: Option<&str>,
+    consistency: Consistency,
+) -> Result<TenantContext, AppError> {
+    let mut ctx = resolve_tenant_context(state, tenant_id_opt, act_as_opt, package_id_opt).await?;
+    if consistency == Consistency::Strong {
+        return Ok(ctx);
+    }
+    // resolve_tenant_context has validated both ids; the data belongs to the effective tenant.
+    let eff_id = act_as_opt
+        .filter(|s| !s.is_empty())
+        .or(tenant_id_opt)
+        .unwrap_or_default();
+    let Some(entry) = state.tenant_registry.get(eff_id) else {
+        return Ok(ctx);
+    };
+    let replica_url = match (&entry.replica_url, &entry.database_url) {
+        (Some(url), _) => Some(url.clone()),
+        (None, None) => crate::replica::central_replica_url(),
+        (None, Some(_)) => None,
+    };
+    let Some(replica_url) = replica_url else {
+        return Ok(ctx);
+    };
+    if let Some(replica) = state
+        .replica_pools
+        .read_pool(&replica_url, state.dialect.as_ref())
+        .await
+    {
+        match &mut ctx {
+            TenantContext::Pool { pool, .. } | TenantContext::Rls { pool, .. } => *pool = replica,
+        }
+    }
+    Ok(ctx)
+}
+
 /// Authorize a write (create/update/delete/bulk) against a possibly-`global` entity.
 ///
 /// Global tables are shared across all RLS tenants: readable by everyone, writable only by the
@@ -1165,55 +1435,15 @@ pub async fn get_or_create_tenant_pool(
     tenant_id: &str,
     database_url: &str,
 ) -> Result<crate::db::pool::Pool, AppError> {
-    let existing = {
-        let guard = state
-            .tenant_pools
-            .read()
-            .map_err(|_| AppError::BadRequest("state lock".into()))?;
-        guard.get(tenant_id).cloned()
-    };
-    if let Some(p) = existing {
-        return Ok(p);
-    }
-    let new_pool = {
-        #[cfg(feature = "postgres")]
-        {
-            sqlx::postgres::PgPoolOptions::new()
-                .max_connections(5)
-                .connect(database_url)
-                .await?
-        }
-        #[cfg(feature = "mysql")]
-        {
-            sqlx::mysql::MySqlPoolOptions::new()
-                .max_connections(5)
-                .connect(database_url)
-                .await?
-        }
-        #[cfg(feature = "sqlite")]
-        {
-            sqlx::sqlite::SqlitePoolOptions::new()
-                .max_connections(5)
-                .connect(database_url)
-                .await?
-        }
-        #[cfg(not(any(feature = "postgres", feature = "mysql", feature = "sqlite")))]
-        {
-            return Err(AppError::BadRequest(
-                "no database dialect feature enabled".into(),
-            ));
-        }
-    };
-    {
-        let mut guard = state
-            .tenant_pools
-            .write()
-            .map_err(|_| AppError::BadRequest("state lock".into()))?;
-        guard
-            .entry(tenant_id.to_string())
-            .or_insert_with(|| new_pool.clone());
-    }
-    Ok(new_pool)
+    let options = state
+        .tenant_registry
+        .get(tenant_id)
+        .map(|entry| entry.pool.clone())
+        .unwrap_or_default();
+    state
+        .tenant_pools
+        .get_or_connect(tenant_id, database_url, &options)
+        .await
 }
 
 /// Get resolved model for a package from cache, or load from config_pool and cache it under cache_key.
@@ -1239,6 +1469,9 @@ pub(crate) async fn get_or_load_package_model(
     let model = resolve(&config)
         .map_err(AppError::Config)?
         .with_package_id(package_id);
+    // Columns mid-way through an expand/contract type change are written to both columns.
+    let transitions = crate::transition::load_transitions(config_pool, cache_key).await;
+    let model = crate::transition::attach_shadow_columns(model, &transitions);
     state
         .package_models
         .write()
@@ -1604,15 +1837,17 @@ pub async fn list(
     State(state): State<AppState>,
     TenantId(tenant_id_opt): TenantId,
     ActAsTenant(act_as_opt): ActAsTenant,
+    ReadConsistency(consistency): ReadConsistency,
     UserId(user_id_opt): UserId,
     Path(path_segment): Path<String>,
     Query(params): Query<HashMap<String, String>>,
 ) -> Result<impl axum::response::IntoResponse, AppError> {
-    let ctx = resolve_tenant_context(
+    let ctx = resolve_read_context(
         &state,
         tenant_id_opt.as_deref(),
         act_as_opt.as_deref(),
         None,
+        consistency,
     )
     .await?;
     let mut rls_tx = begin_rls_tx(&state, &ctx).await?;
@@ -1796,6 +2031,7 @@ pub async fn list(
     let count = rows.len() as u64;
     Ok((
         axum::http::StatusCode::OK,
+        crate::metrics::entity_label(&entity),
         Json(crate::response::SuccessMany {
             data: rows,
             meta: crate::response::MetaCount { count },
@@ -1864,20 +2100,34 @@ pub async fn create(
         .unwrap_or(false);
 
     let mut body;
+    let client_paths;
     if is_multipart {
         let multipart = axum::extract::Multipart::from_request(request, &state)
             .await
             .map_err(|e| AppError::BadRequest(e.to_string()))?;
-        let (text_fields, files) = parse_multipart(multipart).await?;
+        let (text_fields, files) =
+            parse_multipart(&state, &entity, &tenant_id_str, multipart).await?;
         body = hashmap_keys_to_snake_case(&text_fields);
-        process_asset_uploads(&state, &entity, &tenant_id_str, &mut body, files).await?;
+        client_paths = client_asset_paths(&entity, &body);
+        merge_uploaded_assets(&entity, &mut body, files);
     } else {
         let Json(json_body) = Json::<Value>::from_request(request, &state)
             .await
             .map_err(|e| AppError::BadRequest(e.to_string()))?;
         body = hashmap_keys_to_snake_case(&body_to_map(json_body)?);
+        client_paths = client_asset_paths(&entity, &body);
         process_json_asset_fields(&state, &entity, &tenant_id_str, &mut body).await?;
     }
+    verify_client_asset_paths(
+        &state,
+        &mut executor,
+        schema_override,
+        &entity,
+        &tenant_id_str,
+        client_paths,
+        None,
+    )
+    .await?;
 
     RequestValidator::validate(&body, &entity.validation)?;
     if let Some(reg) = load_extensible_registry(&state, &entity, tenant_id_opt.as_deref()).await? {
@@ -1915,6 +2165,7 @@ pub async fn create(
     }
     Ok((
         axum::http::StatusCode::CREATED,
+        crate::metrics::entity_label(&entity),
         Json(crate::response::SuccessOne {
             data: row,
             meta: None,
@@ -2162,6 +2413,7 @@ pub async fn create_graph(
 
     Ok((
         axum::http::StatusCode::CREATED,
+        crate::metrics::entity_label(&entity),
         Json(crate::response::SuccessOne {
             data: parent_row,
             meta: None,
@@ -2173,15 +2425,17 @@ pub async fn read(
     State(state): State<AppState>,
     TenantId(tenant_id_opt): TenantId,
     ActAsTenant(act_as_opt): ActAsTenant,
+    ReadConsistency(consistency): ReadConsistency,
     UserId(user_id_opt): UserId,
     Path((path_segment, id_str)): Path<(String, String)>,
     Query(params): Query<HashMap<String, String>>,
 ) -> Result<impl axum::response::IntoResponse, AppError> {
-    let ctx = resolve_tenant_context(
+    let ctx = resolve_read_context(
         &state,
         tenant_id_opt.as_deref(),
         act_as_opt.as_deref(),
         None,
+        consistency,
     )
     .await?;
     let mut rls_tx = begin_rls_tx(&state, &ctx).await?;
@@ -2284,6 +2538,7 @@ pub async fn read(
 
     Ok((
         axum::http::StatusCode::OK,
+        crate::metrics::entity_label(&entity),
         Json(crate::response::SuccessOne {
             data: row,
             meta: None,
@@ -2352,18 +2607,22 @@ pub async fn update(
         .unwrap_or(false);
 
     let mut body;
+    let client_paths;
     if is_multipart {
         let multipart = axum::extract::Multipart::from_request(request, &state)
             .await
             .map_err(|e| AppError::BadRequest(e.to_string()))?;
-        let (text_fields, files) = parse_multipart(multipart).await?;
+        let (text_fields, files) =
+            parse_multipart(&state, &entity, &tenant_id_str, multipart).await?;
         body = hashmap_keys_to_snake_case(&text_fields);
-        process_asset_uploads(&state, &entity, &tenant_id_str, &mut body, files).await?;
+        client_paths = client_asset_paths(&entity, &body);
+        merge_uploaded_assets(&entity, &mut body, files);
     } else {
         let Json(json_body) = Json::<Value>::from_request(request, &state)
             .await
             .map_err(|e| AppError::BadRequest(e.to_string()))?;
         body = hashmap_keys_to_snake_case(&body_to_map(json_body)?);
+        client_paths = client_asset_paths(&entity, &body);
         process_json_asset_fields(&state, &entity, &tenant_id_str, &mut body).await?;
     }
 
@@ -2393,6 +2652,16 @@ pub async fn update(
     } else {
         None
     };
+    verify_client_asset_paths(
+        &state,
+        &mut executor,
+        schema_override,
+        &entity,
+        &tenant_id_str,
+        client_paths,
+        pre_update_row.as_ref(),
+    )
+    .await?;
 
     let mut row = CrudService::update(
         &mut executor,
@@ -2435,6 +2704,7 @@ pub async fn update(
     }
     Ok((
         axum::http::StatusCode::OK,
+        crate::metrics::entity_label(&entity),
         Json(crate::response::SuccessOne {
             data: row,
             meta: None,
@@ -2543,7 +2813,10 @@ pub async fn delete(
             None,
         );
     }
-    Ok(axum::http::StatusCode::NO_CONTENT)
+    Ok((
+        axum::http::StatusCode::NO_CONTENT,
+        crate::metrics::entity_label(&entity),
+    ))
 }
 
 pub async fn bulk_create(
@@ -2705,6 +2978,7 @@ pub async fn bulk_create(
     let count = rows.len() as u64;
     Ok((
         axum::http::StatusCode::CREATED,
+        crate::metrics::entity_label(&entity),
         Json(crate::response::SuccessMany {
             data: rows,
             meta: crate::response::MetaCount { count },
@@ -2852,6 +3126,7 @@ pub async fn bulk_update(
     let count = rows.len() as u64;
     Ok((
         axum::http::StatusCode::OK,
+        crate::metrics::entity_label(&entity),
         Json(crate::response::SuccessMany {
             data: rows,
             meta: crate::response::MetaCount { count },
@@ -2941,6 +3216,7 @@ async fn finish_bulk_delete(
 ) -> Result<
     (
         axum::http::StatusCode,
+        axum::Extension<crate::metrics::EntityLabel>,
         Json<crate::response::SuccessMany<Value>>,
     ),
     AppError,
@@ -2977,6 +3253,7 @@ async fn finish_bulk_delete(
     let count = rows.len() as u64;
     Ok((
         axum::http::StatusCode::OK,
+        crate::metrics::entity_label(entity),
         Json(crate::response::SuccessMany {
             data: rows,
             meta: crate::response::MetaCount { count },
@@ -2990,15 +3267,17 @@ pub async fn list_package(
     State(state): State<AppState>,
     TenantId(tenant_id_opt): TenantId,
     ActAsTenant(act_as_opt): ActAsTenant,
+    ReadConsistency(consistency): ReadConsistency,
     UserId(user_id_opt): UserId,
     Path((package_id, path_segment)): Path<(String, String)>,
     Query(params): Query<HashMap<String, String>>,
 ) -> Result<impl axum::response::IntoResponse, AppError> {
-    let ctx = resolve_tenant_context(
+    let ctx = resolve_read_context(
         &state,
         tenant_id_opt.as_deref(),
         act_as_opt.as_deref(),
         Some(&package_id),
+        consistency,
     )
     .await?;
     let model = get_or_load_package_model(
@@ -3165,6 +3444,7 @@ pub async fn list_package(
     let count = rows.len() as u64;
     Ok((
         axum::http::StatusCode::OK,
+        crate::metrics::entity_label(&entity),
         Json(crate::response::SuccessMany {
             data: rows,
             meta: crate::response::MetaCount { count },
@@ -3236,20 +3516,34 @@ pub async fn create_package(
         .unwrap_or(false);
 
     let mut body;
+    let client_paths;
     if is_multipart {
         let multipart = axum::extract::Multipart::from_request(request, &state)
             .await
             .map_err(|e| AppError::BadRequest(e.to_string()))?;
-        let (text_fields, files) = parse_multipart(multipart).await?;
+        let (text_fields, files) =
+            parse_multipart(&state, &entity, &tenant_id_str, multipart).await?;
         body = hashmap_keys_to_snake_case(&text_fields);
-        process_asset_uploads(&state, &entity, &tenant_id_str, &mut body, files).await?;
+        client_paths = client_asset_paths(&entity, &body);
+        merge_uploaded_assets(&entity, &mut body, files);
     } else {
         let Json(json_body) = Json::<Value>::from_request(request, &state)
             .await
             .map_err(|e| AppError::BadRequest(e.to_string()))?;
         body = hashmap_keys_to_snake_case(&body_to_map(json_body)?);
+        client_paths = client_asset_paths(&entity, &body);
         process_json_asset_fields(&state, &entity, &tenant_id_str, &mut body).await?;
     }
+    verify_client_asset_paths(
+        &state,
+        &mut executor,
+        schema_override,
+        &entity,
+        &tenant_id_str,
+        client_paths,
+        None,
+    )
+    .await?;
 
     RequestValidator::validate(&body, &entity.validation)?;
     if let Some(reg) = load_extensible_registry(&state, &entity, tenant_id_opt.as_deref()).await? {
@@ -3288,6 +3582,7 @@ pub async fn create_package(
     }
     Ok((
         axum::http::StatusCode::CREATED,
+        crate::metrics::entity_label(&entity),
         Json(crate::response::SuccessOne {
             data: row,
             meta: None,
@@ -3538,6 +3833,7 @@ pub async fn create_graph_package(
 
     Ok((
         axum::http::StatusCode::CREATED,
+        crate::metrics::entity_label(&entity),
         Json(crate::response::SuccessOne {
             data: parent_row,
             meta: None,
@@ -3549,15 +3845,17 @@ pub async fn read_package(
     State(state): State<AppState>,
     TenantId(tenant_id_opt): TenantId,
     ActAsTenant(act_as_opt): ActAsTenant,
+    ReadConsistency(consistency): ReadConsistency,
     UserId(user_id_opt): UserId,
     Path((package_id, path_segment, id_str)): Path<(String, String, String)>,
     Query(params): Query<HashMap<String, String>>,
 ) -> Result<impl axum::response::IntoResponse, AppError> {
-    let ctx = resolve_tenant_context(
+    let ctx = resolve_read_context(
         &state,
         tenant_id_opt.as_deref(),
         act_as_opt.as_deref(),
         Some(&package_id),
+        consistency,
     )
     .await?;
     let model = get_or_load_package_model(
@@ -3657,6 +3955,7 @@ pub async fn read_package(
 
     Ok((
         axum::http::StatusCode::OK,
+        crate::metrics::entity_label(&entity),
         Json(crate::response::SuccessOne {
             data: row,
             meta: None,
@@ -3729,18 +4028,22 @@ pub async fn update_package(
         .unwrap_or(false);
 
     let mut body;
+    let client_paths;
     if is_multipart {
         let multipart = axum::extract::Multipart::from_request(request, &state)
             .await
             .map_err(|e| AppError::BadRequest(e.to_string()))?;
-        let (text_fields, files) = parse_multipart(multipart).await?;
+        let (text_fields, files) =
+            parse_multipart(&state, &entity, &tenant_id_str, multipart).await?;
         body = hashmap_keys_to_snake_case(&text_fields);
-        process_asset_uploads(&state, &entity, &tenant_id_str, &mut body, files).await?;
+        client_paths = client_asset_paths(&entity, &body);
+        merge_uploaded_assets(&entity, &mut body, files);
     } else {
         let Json(json_body) = Json::<Value>::from_request(request, &state)
             .await
             .map_err(|e| AppError::BadRequest(e.to_string()))?;
         body = hashmap_keys_to_snake_case(&body_to_map(json_body)?);
+        client_paths = client_asset_paths(&entity, &body);
         process_json_asset_fields(&state, &entity, &tenant_id_str, &mut body).await?;
     }
 
@@ -3763,6 +4066,16 @@ pub async fn update_package(
     } else {
         None
     };
+    verify_client_asset_paths(
+        &state,
+        &mut executor,
+        schema_override,
+        &entity,
+        &tenant_id_str,
+        client_paths,
+        pre_update_row.as_ref(),
+    )
+    .await?;
 
     let mut row = CrudService::update(
         &mut executor,
@@ -3806,6 +4119,7 @@ pub async fn update_package(
     }
     Ok((
         axum::http::StatusCode::OK,
+        crate::metrics::entity_label(&entity),
         Json(crate::response::SuccessOne {
             data: row,
             meta: None,
@@ -3918,7 +4232,10 @@ pub async fn delete_package(
             None,
         );
     }
-    Ok(axum::http::StatusCode::NO_CONTENT)
+    Ok((
+        axum::http::StatusCode::NO_CONTENT,
+        crate::metrics::entity_label(&entity),
+    ))
 }
 
 pub async fn bulk_create_package(
@@ -4085,6 +4402,7 @@ pub async fn bulk_create_package(
     let count = rows.len() as u64;
     Ok((
         axum::http::StatusCode::CREATED,
+        crate::metrics::entity_label(&entity),
         Json(crate::response::SuccessMany {
             data: rows,
             meta: crate::response::MetaCount { count },
@@ -4237,6 +4555,7 @@ pub async fn bulk_update_package(
     let count = rows.len() as u64;
     Ok((
         axum::http::StatusCode::OK,
+        crate::metrics::entity_label(&entity),
         Json(crate::response::SuccessMany {
             data: rows,
             meta: crate::response::MetaCount { count },
@@ -4409,6 +4728,7 @@ pub async fn archive(
     }
     Ok((
         axum::http::StatusCode::OK,
+        crate::metrics::entity_label(&entity),
         Json(crate::response::SuccessOne {
             data: row,
             meta: None,
@@ -4505,6 +4825,7 @@ pub async fn unarchive(
     }
     Ok((
         axum::http::StatusCode::OK,
+        crate::metrics::entity_label(&entity),
         Json(crate::response::SuccessOne {
             data: row,
             meta: None,
@@ -4604,6 +4925,7 @@ pub async fn unarchive_package(
     }
     Ok((
         axum::http::StatusCode::OK,
+        crate::metrics::entity_label(&entity),
         Json(crate::response::SuccessOne {
             data: row,
             meta: None,
@@ -4703,6 +5025,7 @@ pub async fn archive_package(
     }
     Ok((
         axum::http::StatusCode::OK,
+        crate::metrics::entity_label(&entity),
         Json(crate::response::SuccessOne {
             data: row,
             meta: None,
@@ -4718,14 +5041,16 @@ pub async fn list_history(
     State(state): State<AppState>,
     TenantId(tenant_id_opt): TenantId,
     ActAsTenant(act_as_opt): ActAsTenant,
+    ReadConsistency(consistency): ReadConsistency,
     UserId(user_id_opt): UserId,
     Path((path_segment, id_str)): Path<(String, String)>,
 ) -> Result<impl axum::response::IntoResponse, AppError> {
-    let ctx = resolve_tenant_context(
+    let ctx = resolve_read_context(
         &state,
         tenant_id_opt.as_deref(),
         act_as_opt.as_deref(),
         None,
+        consistency,
     )
     .await?;
     let mut rls_tx = begin_rls_tx(&state, &ctx).await?;
@@ -4784,6 +5109,7 @@ pub async fn list_history(
     let count = rows.len();
     Ok((
         axum::http::StatusCode::OK,
+        crate::metrics::entity_label(&entity),
         Json(crate::response::SuccessMany {
             data: rows,
             meta: crate::response::MetaCount {
@@ -4799,6 +5125,7 @@ pub async fn read_history_version(
     State(state): State<AppState>,
     TenantId(tenant_id_opt): TenantId,
     ActAsTenant(act_as_opt): ActAsTenant,
+    ReadConsistency(consistency): ReadConsistency,
     UserId(user_id_opt): UserId,
     Path((path_segment, id_str, version_str)): Path<(String, String, String)>,
 ) -> Result<impl axum::response::IntoResponse, AppError> {
@@ -4806,11 +5133,12 @@ pub async fn read_history_version(
         .parse()
         .map_err(|_| AppError::BadRequest("version must be an integer".into()))?;
 
-    let ctx = resolve_tenant_context(
+    let ctx = resolve_read_context(
         &state,
         tenant_id_opt.as_deref(),
         act_as_opt.as_deref(),
         None,
+        consistency,
     )
     .await?;
     let mut rls_tx = begin_rls_tx(&state, &ctx).await?;
@@ -4866,6 +5194,7 @@ pub async fn read_history_version(
 
     Ok((
         axum::http::StatusCode::OK,
+        crate::metrics::entity_label(&entity),
         Json(crate::response::SuccessOne {
             data: row,
             meta: None,
@@ -4900,6 +5229,7 @@ mod global_write_tests {
             versioning: None,
             mcp: None,
             extensible_columns: vec![],
+            shadow_columns: vec![],
         }
     }
 
@@ -4961,6 +5291,7 @@ mod event_include_tests {
             versioning: None,
             mcp: None,
             extensible_columns: vec![],
+            shadow_columns: vec![],
         }
     }
 
@@ -277,7 +277,7 @@ pub async fn get_registry(
 ) -> Result<impl axum::response::IntoResponse, AppError> {
     let (tenant_id, entity) = resolve_default(&state, tenant_id_opt.as_deref(), &path_segment)?;
     let value = do_get_registry(&state, &tenant_id, &entity, user_id_opt.as_deref()).await?;
-    Ok(success_one_ok(value))
+    Ok((crate::metrics::entity_label(&entity), success_one_ok(value)))
 }
 
 pub async fn put_registry(
@@ -289,7 +289,7 @@ pub async fn put_registry(
 ) -> Result<impl axum::response::IntoResponse, AppError> {
     let (tenant_id, entity) = resolve_default(&state, tenant_id_opt.as_deref(), &path_segment)?;
     let value = do_put_registry(&state, &tenant_id, &entity, user_id_opt.as_deref(), body).await?;
-    Ok(success_one_ok(value))
+    Ok((crate::metrics::entity_label(&entity), success_one_ok(value)))
 }
 
 pub async fn delete_registry_handler(
@@ -301,7 +301,10 @@ pub async fn delete_registry_handler(
     let (tenant_id, entity) = resolve_default(&state, tenant_id_opt.as_deref(), &path_segment)?;
     let removed = do_delete_registry(&state, &tenant_id, &entity, user_id_opt.as_deref()).await?;
     not_found_if_absent(removed, &entity)?;
-    Ok((axum::http::StatusCode::NO_CONTENT, ()))
+    Ok((
+        axum::http::StatusCode::NO_CONTENT,
+        crate::metrics::entity_label(&entity),
+    ))
 }
 
 pub async fn get_indexes(
@@ -312,7 +315,7 @@ pub async fn get_indexes(
 ) -> Result<impl axum::response::IntoResponse, AppError> {
     let (tenant_id, entity) = resolve_default(&state, tenant_id_opt.as_deref(), &path_segment)?;
     let value = do_get_indexes(&state, &tenant_id, &entity, user_id_opt.as_deref()).await?;
-    Ok(success_one_ok(value))
+    Ok((crate::metrics::entity_label(&entity), success_one_ok(value)))
 }
 
 pub async fn apply_indexes_handler(
@@ -323,7 +326,7 @@ pub async fn apply_indexes_handler(
 ) -> Result<impl axum::response::IntoResponse, AppError> {
     let (tenant_id, entity) = resolve_default(&state, tenant_id_opt.as_deref(), &path_segment)?;
     let value = do_apply_indexes(&state, &tenant_id, &entity, user_id_opt.as_deref()).await?;
-    Ok(success_one_ok(value))
+    Ok((crate::metrics::entity_label(&entity), success_one_ok(value)))
 }
 
 // ── Package-scoped handlers ──────────────────────────────────────────────────
@@ -337,7 +340,7 @@ pub async fn get_registry_package(
     let (tenant_id, entity) =
         resolve_package(&state, tenant_id_opt.as_deref(), &package_id, &path_segment).await?;
     let value = do_get_registry(&state, &tenant_id, &entity, user_id_opt.as_deref()).await?;
-    Ok(success_one_ok(value))
+    Ok((crate::metrics::entity_label(&entity), success_one_ok(value)))
 }
 
 pub async fn put_registry_package(
@@ -350,7 +353,7 @@ pub async fn put_registry_package(
     let (tenant_id, entity) =
         resolve_package(&state, tenant_id_opt.as_deref(), &package_id, &path_segment).await?;
     let value = do_put_registry(&state, &tenant_id, &entity, user_id_opt.as_deref(), body).await?;
-    Ok(success_one_ok(value))
+    Ok((crate::metrics::entity_label(&entity), success_one_ok(value)))
 }
 
 pub async fn delete_registry_package(
@@ -363,7 +366,10 @@ pub async fn delete_registry_package(
         resolve_package(&state, tenant_id_opt.as_deref(), &package_id, &path_segment).await?;
     let removed = do_delete_registry(&state, &tenant_id, &entity, user_id_opt.as_deref()).await?;
     not_found_if_absent(removed, &entity)?;
-    Ok((axum::http::StatusCode::NO_CONTENT, ()))
+    Ok((
+        axum::http::StatusCode::NO_CONTENT,
+        crate::metrics::entity_label(&entity),
+    ))
 }
 
 pub async fn get_indexes_package(
@@ -375,7 +381,7 @@ pub async fn get_indexes_package(
     let (tenant_id, entity) =
         resolve_package(&state, tenant_id_opt.as_deref(), &package_id, &path_segment).await?;
     let value = do_get_indexes(&state, &tenant_id, &entity, user_id_opt.as_deref()).await?;
-    Ok(success_one_ok(value))
+    Ok((crate::metrics::entity_label(&entity), success_one_ok(value)))
 }
 
 pub async fn apply_indexes_package(
@@ -387,7 +393,7 @@ pub async fn apply_indexes_package(
     let (tenant_id, entity) =
         resolve_package(&state, tenant_id_opt.as_deref(), &package_id, &path_segment).await?;
     let value = do_apply_indexes(&state, &tenant_id, &entity, user_id_opt.as_deref()).await?;
-    Ok(success_one_ok(value))
+    Ok((crate::metrics::entity_label(&entity), success_one_ok(value)))
 }
 
 fn not_found_if_absent(removed: bool, entity: &ResolvedEntity) -> Result<(), AppError> {
@@ -1,29 +1,55 @@
 //! Package install/uninstall handlers. Install: zip upload, extract manifest + configs, apply configs, store manifest, reload model. Uninstall: revert migrations, delete _sys_* rows and package record. X-Tenant-ID is required.
 //! Config is stored in the architect DB (DATABASE_URL). Schemas/tables are created in ALL registered tenant databases (broadcast). Bootstrap endpoint handles new Database-strategy tenants added after install.
 
-use crate::config::{load_from_pool, resolve, FullConfig};
+use crate::approval::{
+    apply_overrides, check_approval, validate_overrides, ApprovalPolicy, ApproverPolicy,
+    PlanApproval, StepOverride,
+};
+use crate::config::{load_from_pool, resolve, FullConfig, ResolvedModel};
+use crate::data_migration::{
+    apply_data_migrations, check_data_migrations, parse_data_migrations, DataMigration,
+    DataMigrationOutcome, DataMigrationTarget, DATA_DIR,
+};
 use crate::db::pool::Pool;
-use crate::db::{parse_canonical, CanonicalType, Dialect};
+use crate::db::{dialect_by_name, parse_canonical, CanonicalType, Dialect};
+use crate::dependency::{
+    check_dependencies, check_dependents_allow, dependencies_json, dependents, install_order,
+    parse_dependencies,
+};
+use crate::drift::{detect_drift, drift_repair_plan};
 use crate::error::AppError;
+use crate::extractors::consistency::ReadConsistency;
 use crate::extractors::tenant::TenantId;
+use crate::extractors::user::UserId;
 use crate::handlers::config::{reload_model, replace_config};
 use crate::handlers::entity::{get_or_create_tenant_pool, resolve_tenant_context};
 use crate::migration::{
     apply_migrations, apply_rls_to_tables, compute_migration_plan, execute_migration_plan,
-    revert_migrations, MigrationPlan,
+    execute_migration_plan_atomic, render_migrations, revert_migrations, to_online_plan,
+    MigrationMode, MigrationPlan, OnlineMigrationOptions, RLS_TENANT_COLUMN,
 };
+use crate::preflight::probe_plan;
+use crate::replica::Consistency;
+use crate::reverse::{export_manifest, reverse_engineer, write_package_zip, ReverseOptions};
+use crate::signing::{PackageIntegrity, SigningPolicy};
 use crate::state::AppState;
 use crate::store::{
-    count_package_kind, delete_package_and_config, get_migration_plan, get_package,
-    list_package_ids, list_packages, mark_migration_plan_applied, save_migration_plan,
-    upsert_package,
+    approve_migration_plan, count_package_kind, delete_package_and_config, expire_migration_plan,
+    expire_pending_migration_plans, get_migration_plan, get_package, get_package_history_version,
+    list_column_transitions, list_package_ids, list_packages, load_config_rows_at_version,
+    mark_migration_plan_applied, record_package_config_versions, save_migration_plan,
+    set_package_integrity, sys_table_for_kind, upsert_package,
 };
 use crate::tenant::TenantStrategy;
-use axum::extract::{Multipart, Path, State};
+use crate::transition::{
+    attach_shadow_columns, backfill_plan, contract_plan, record_transitions,
+    to_expand_contract_plan, TypeChangeStrategy,
+};
+use axum::extract::{Multipart, Path, Query, State};
 use axum::Json;
 use serde::Deserialize;
 use serde_json::{json, Value};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::io::Cursor;
 use uuid::Uuid;
 use zip::ZipArchive;
@@ -131,7 +157,7 @@ fn config_apply_order() -> Vec<&'static str> {
 }
 
 /// Schema id used when manifest provides the schema name (no separate schemas.json).
-const DEFAULT_SCHEMA_ID: &str = "default";
+pub(crate) const DEFAULT_SCHEMA_ID: &str = "default";
 
 /// Build a `FullConfig` from the in-memory, per-kind config bodies read from the zip — the same
 /// shape `load_from_pool` produces, but without a round trip through the architect DB. Lets the
@@ -239,6 +265,125 @@ fn read_kind_from_zip<R: std::io::Read + std::io::Seek>(
     Ok(merged)
 }
 
+/// Every config kind of a package zip, in apply order, normalized the way install stores it: the
+/// schema comes from the manifest and records without a schema id get the package's.
+fn read_package_bodies<R: std::io::Read + std::io::Seek>(
+    archive: &mut ZipArchive<R>,
+    schema_name: &str,
+) -> Result<Vec<(&'static str, Vec<Value>)>, AppError> {
+    let apply_order = config_apply_order();
+    let mut bodies: Vec<(&'static str, Vec<Value>)> = Vec::with_capacity(apply_order.len());
+    for kind in apply_order {
+        let body: Vec<Value> = if kind == "schemas" {
+            vec![serde_json::json!({ "id": DEFAULT_SCHEMA_ID, "name": schema_name })]
+        } else {
+            let mut body = read_kind_from_zip(archive, kind)?;
+            match kind {
+                "enums" | "tables" | "indexes" => inject_schema_id(&mut body, DEFAULT_SCHEMA_ID),
+                "relationships" => inject_relationship_schema_ids(&mut body, DEFAULT_SCHEMA_ID),
+                _ => {}
+            }
+            body
+        };
+        bodies.push((kind, body));
+    }
+    Ok(bodies)
+}
+
+/// Read a package zip into its manifest and assembled config, exactly as install would, without
+/// touching any database. The manifest must have `id`, `name`, `version` and `schema`.
+pub fn read_package_config(zip_bytes: &[u8]) -> Result<(Value, FullConfig), AppError> {
+    let manifest = read_manifest(zip_bytes)?;
+    for field in ["id", "name", "version", "schema"] {
+        if manifest.get(field).and_then(Value::as_str).is_none() {
+            return Err(AppError::BadRequest(format!(
+                "manifest must have '{}' (string)",
+                field
+            )));
+        }
+    }
+    let schema_name = manifest["schema"].as_str().unwrap_or_default();
+    let mut archive = ZipArchive::new(Cursor::new(zip_bytes))
+        .map_err(|e| AppError::BadRequest(format!("invalid zip: {}", e)))?;
+    let bodies = read_package_bodies(&mut archive, schema_name)?;
+    let config = assemble_config(&bodies)?;
+    Ok((manifest, config))
+}
+
+/// Read the package's `data/` files and parse the data migrations that apply to `dialect`.
+/// `manifest_name` locates the package root inside the zip.
+fn read_data_migrations_from_zip<R: std::io::Read + std::io::Seek>(
+    archive: &mut ZipArchive<R>,
+    manifest_name: &str,
+    dialect: &dyn Dialect,
+) -> Result<Vec<DataMigration>, AppError> {
+    let root = manifest_name.trim_end_matches("manifest.json");
+    let data_prefix = format!("{}{}", root, DATA_DIR);
+    let mut names: Vec<String> = archive
+        .file_names()
+        .filter(|n| n.starts_with(&data_prefix) && !n.ends_with('/'))
+        .map(String::from)
+        .collect();
+    names.sort();
+    let mut files = Vec::with_capacity(names.len());
+    for name in names {
+        let content = read_zip_entry_to_string(archive, &name)?;
+        files.push((name[root.len()..].to_string(), content));
+    }
+    parse_data_migrations(&files, dialect)
+}
+
+/// Run a package's data migrations for each tenant. `schema_name` is the package schema, used for
+/// `{{schema}}` when the tenant has no schema override. A tenant whose context cannot be resolved
+/// is reported with an error and skipped.
+async fn run_data_migrations(
+    state: &AppState,
+    tenant_ids: &[String],
+    package_id: &str,
+    package_version: Option<&str>,
+    schema_name: &str,
+    model: &ResolvedModel,
+    migrations: &[DataMigration],
+) -> Vec<DataMigrationOutcome> {
+    let mut outcomes = Vec::new();
+    if migrations.is_empty() {
+        return outcomes;
+    }
+    for tid in tenant_ids {
+        let ctx = match resolve_tenant_context(state, Some(tid), None, Some(package_id)).await {
+            Ok(ctx) => ctx,
+            Err(e) => {
+                outcomes.push(DataMigrationOutcome {
+                    tenant_id: tid.clone(),
+                    error: Some(e.to_string()),
+                    ..Default::default()
+                });
+                continue;
+            }
+        };
+        let target = DataMigrationTarget {
+            pool: ctx.migration_pool(),
+            tenant_id: tid,
+            schema_override: ctx.schema_override(),
+            schema: ctx.schema_override().unwrap_or(schema_name),
+            rls: ctx.rls_tenant_id().is_some(),
+        };
+        outcomes.push(
+            apply_data_migrations(
+                &state.pool,
+                &target,
+                package_id,
+                package_version,
+                model,
+                migrations,
+                state.dialect.as_ref(),
+            )
+            .await,
+        );
+    }
+    outcomes
+}
+
 // ─── DDL broadcast helpers ───────────────────────────────────────────────────
 
 /// Apply DDL for one target pool — either the full `apply_migrations` (fresh install) or
@@ -356,6 +501,37 @@ async fn apply_ddl_to_pool(
     }
 }
 
+/// Configs of every installed package except `package_id`, keyed by package id. Used for
+/// cross-package FK resolution; packages that fail to load are logged and left out.
+async fn load_other_package_configs(
+    config_pool: &Pool,
+    package_id: &str,
+) -> std::collections::HashMap<String, FullConfig> {
+    match list_package_ids(config_pool).await {
+        Ok(ids) => {
+            let mut map = std::collections::HashMap::new();
+            for pid in ids {
+                if pid == package_id {
+                    continue;
+                }
+                match load_from_pool(config_pool, &pid).await {
+                    Ok(cfg) => {
+                        map.insert(pid, cfg);
+                    }
+                    Err(e) => {
+                        tracing::warn!(pkg = %pid, error = %e, "could not load cross-package config for FK resolution")
+                    }
+                }
+            }
+            map
+        }
+        Err(e) => {
+            tracing::warn!(error = %e, "could not list packages for cross-package FK resolution");
+            std::collections::HashMap::new()
+        }
+    }
+}
+
 /// Apply DDL for a package to every registered tenant database.
 ///
 /// Targets (in order):
@@ -365,11 +541,16 @@ async fn apply_ddl_to_pool(
 ///
 /// Failures on individual targets are collected as outcomes and do NOT abort the broadcast;
 /// the `_sys_*` config has already been committed and must not be rolled back here.
+///
+/// `approved_plan` (a stored plan with its approval overrides applied) runs as-is instead of a
+/// plan recomputed from `old_config`, so targets execute exactly the DDL that was approved.
+#[allow(clippy::too_many_arguments)]
 async fn broadcast_ddl(
     state: &AppState,
     config_pool: &Pool,
     config: &FullConfig,
     old_config: Option<&FullConfig>,
+    approved_plan: Option<MigrationPlan>,
     package_id: &str,
     from_version: Option<&str>,
     to_version: &str,
@@ -377,39 +558,16 @@ async fn broadcast_ddl(
     let mut outcomes = Vec::new();
 
     // Load all other installed packages so cross-package FK resolution works.
-    let cross_package_configs: std::collections::HashMap<String, FullConfig> = {
-        match list_package_ids(config_pool).await {
-            Ok(ids) => {
-                let mut map = std::collections::HashMap::new();
-                for pid in ids {
-                    if pid == package_id {
-                        continue;
-                    }
-                    match load_from_pool(config_pool, &pid).await {
-                        Ok(cfg) => {
-                            map.insert(pid, cfg);
-                        }
-                        Err(e) => {
-                            tracing::warn!(pkg = %pid, error = %e, "could not load cross-package config for FK resolution")
-                        }
-                    }
-                }
-                map
-            }
-            Err(e) => {
-                tracing::warn!(error = %e, "could not list packages for cross-package FK resolution");
-                std::collections::HashMap::new()
-            }
-        }
-    };
+    let cross_package_configs = load_other_package_configs(config_pool, package_id).await;
 
     // Compute the migration plan once for upgrades (pure function, no DB calls).
     // `_rls_tenant_column` is intentionally ignored by compute_migration_plan, so
     // the same plan is valid for both RLS and Database targets. RLS targets get their
     // tenant column / policies reconciled separately in apply_ddl_to_pool via
     // apply_rls_to_tables, since the diff plan never emits RLS DDL.
-    let plan: Option<MigrationPlan> = match old_config {
-        Some(old) => {
+    let plan: Option<MigrationPlan> = match (approved_plan, old_config) {
+        (Some(approved), _) => Some(approved),
+        (None, Some(old)) => {
             match compute_migration_plan(
                 old,
                 config,
@@ -432,7 +590,7 @@ async fn broadcast_ddl(
                 }
             }
         }
-        None => None,
+        (None, None) => None,
     };
 
     // ── 1. Central DB — covers all RLS tenants without a dedicated database_url ──
@@ -535,22 +693,38 @@ async fn broadcast_ddl(
 
 // ─────────────────────────────────────────────────────────────────────────────
 
-/// POST /api/v1/config/package: multipart form with file field containing a zip (manifest.json + config JSONs). X-Tenant-ID required.
-pub async fn install_package(
-    TenantId(tenant_id_opt): TenantId,
-    State(state): State<AppState>,
-    mut multipart: Multipart,
-) -> Result<impl axum::response::IntoResponse, AppError> {
+#[derive(serde::Serialize)]
+pub struct PackageInstallResponse {
+    package: Value,
+    applied: Vec<String>,
+    warnings: Vec<String>,
+    /// DDL execution result for each tenant database that was targeted.
+    tenant_migrations: Vec<TenantMigrationOutcome>,
+    /// Data migrations from the package's `data/` folder, per tenant.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    data_migrations: Vec<DataMigrationOutcome>,
+    /// Content hash and verified signer recorded for the package.
+    integrity: PackageIntegrity,
+}
+
+/// Resolve the `X-Tenant-ID` of an install request to a registered tenant.
+fn require_install_tenant(
+    tenant_id_opt: Option<&str>,
+    state: &AppState,
+) -> Result<String, AppError> {
     let tenant_id = tenant_id_opt
-        .as_deref()
         .filter(|s| !s.is_empty())
         .ok_or_else(|| AppError::BadRequest("X-Tenant-ID header is required".into()))?;
     state
         .tenant_registry
         .get(tenant_id)
         .ok_or_else(|| AppError::NotFound(format!("tenant not found: {}", tenant_id)))?;
+    Ok(tenant_id.to_string())
+}
 
-    let mut zip_bytes: Option<Vec<u8>> = None;
+/// Every `file` / `package` field of a multipart body, in order.
+async fn read_package_fields(multipart: &mut Multipart) -> Result<Vec<Vec<u8>>, AppError> {
+    let mut packages = Vec::new();
     while let Ok(Some(field)) = multipart.next_field().await {
         let name = field.name().unwrap_or("").to_string();
         if name == "file" || name == "package" {
@@ -558,14 +732,150 @@ pub async fn install_package(
                 .bytes()
                 .await
                 .map_err(|e| AppError::BadRequest(e.to_string()))?;
-            zip_bytes = Some(data.to_vec());
-            break;
+            packages.push(data.to_vec());
         }
     }
-    let zip_bytes = zip_bytes.ok_or_else(|| {
-        AppError::BadRequest("missing 'file' or 'package' field in multipart body".into())
-    })?;
+    if packages.is_empty() {
+        return Err(AppError::BadRequest(
+            "missing 'file' or 'package' field in multipart body".into(),
+        ));
+    }
+    Ok(packages)
+}
+
+/// Read `manifest.json` from a package zip.
+fn read_manifest(zip_bytes: &[u8]) -> Result<Value, AppError> {
+    let mut archive = ZipArchive::new(Cursor::new(zip_bytes))
+        .map_err(|e| AppError::BadRequest(format!("invalid zip: {}", e)))?;
+    let manifest_name = archive
+        .file_names()
+        .find(|n| *n == "manifest.json" || n.ends_with("/manifest.json"))
+        .map(String::from)
+        .ok_or_else(|| AppError::BadRequest("zip must contain manifest.json at root".into()))?;
+    let content = read_zip_entry_to_string(&mut archive, &manifest_name)?;
+    serde_json::from_str(&content)
+        .map_err(|e| AppError::BadRequest(format!("invalid manifest.json: {}", e)))
+}
+
+/// POST /api/v1/config/package: multipart form with file field containing a zip (manifest.json + config JSONs). X-Tenant-ID required.
+pub async fn install_package(
+    TenantId(tenant_id_opt): TenantId,
+    State(state): State<AppState>,
+    mut multipart: Multipart,
+) -> Result<impl axum::response::IntoResponse, AppError> {
+    let tenant_id = require_install_tenant(tenant_id_opt.as_deref(), &state)?;
+    let zip_bytes = read_package_fields(&mut multipart)
+        .await?
+        .into_iter()
+        .next()
+        .unwrap_or_default();
+    let installed = install_package_zip(&state, &tenant_id, zip_bytes).await?;
+    Ok((
+        axum::http::StatusCode::OK,
+        Json(crate::response::SuccessOne {
+            data: installed,
+            meta: None,
+        }),
+    ))
+}
+
+/// POST /api/v1/config/package/bulk: multipart form with one `file` (or `package`) field per zip.
+/// Packages are installed in dependency order; the first failure stops the batch and the packages
+/// after it are reported as skipped. X-Tenant-ID required.
+pub async fn install_packages_bulk(
+    TenantId(tenant_id_opt): TenantId,
+    State(state): State<AppState>,
+    mut multipart: Multipart,
+) -> Result<impl axum::response::IntoResponse, AppError> {
+    let tenant_id = require_install_tenant(tenant_id_opt.as_deref(), &state)?;
+    let zips = read_package_fields(&mut multipart).await?;
+
+    let mut batch = Vec::with_capacity(zips.len());
+    for zip_bytes in &zips {
+        let manifest = read_manifest(zip_bytes)?;
+        let id = manifest
+            .get("id")
+            .and_then(Value::as_str)
+            .ok_or_else(|| AppError::BadRequest("manifest must have 'id' (string)".into()))?
+            .to_string();
+        batch.push((id, parse_dependencies(&manifest)?));
+    }
+    let order = install_order(&batch)?;
+
+    let mut zips: Vec<Option<Vec<u8>>> = zips.into_iter().map(Some).collect();
+    let mut results = Vec::with_capacity(order.len());
+    let mut failed = false;
+    for i in order {
+        let package_id = batch[i].0.clone();
+        if failed {
+            results.push(json!({ "package_id": package_id, "status": "skipped" }));
+            continue;
+        }
+        let zip_bytes = zips[i].take().unwrap_or_default();
+        match install_package_zip(&state, &tenant_id, zip_bytes).await {
+            Ok(installed) => results.push(json!({
+                "package_id": package_id,
+                "status": "installed",
+                "result": installed,
+            })),
+            Err(e) => {
+                failed = true;
+                results.push(json!({
+                    "package_id": package_id,
+                    "status": "failed",
+                    "error": e.to_string(),
+                }));
+            }
+        }
+    }
+    Ok((
+        axum::http::StatusCode::OK,
+        Json(crate::response::SuccessOne {
+            data: json!({ "packages": results }),
+            meta: None,
+        }),
+    ))
+}
+
+/// Populate the in-memory ResolvedModel for every tenant cache slot of a package that was just
+/// installed, upgraded or rolled back.
+fn publish_package_model(
+    state: &AppState,
+    package_id: &str,
+    package_cache_key: String,
+    new_model: ResolvedModel,
+) -> Result<(), AppError> {
+    {
+        let mut model_guard = state
+            .model
+            .write()
+            .map_err(|_| AppError::BadRequest("state lock".into()))?;
+        *model_guard = new_model.clone();
+        let mut pkg_guard = state
+            .package_models
+            .write()
+            .map_err(|_| AppError::BadRequest("state lock".into()))?;
+        // Shared key used by all RLS tenants.
+        pkg_guard.insert(package_id.to_string(), new_model.clone());
+        // Per-tenant keys used by each Database-strategy tenant.
+        for (tid, _) in state.tenant_registry.database_tenant_targets() {
+            pkg_guard.insert(format!("{}:{}", package_id, tid), new_model.clone());
+        }
+        // Keep the requesting tenant's own cache slot in sync (covers edge cases).
+        pkg_guard.insert(package_cache_key, new_model);
+    }
+    // Package set changed: drop the cached cross-package index so it rebuilds on the next include.
+    crate::handlers::entity::invalidate_cross_package_index(state);
+    Ok(())
+}
 
+/// Install or upgrade one package zip as `tenant_id`.
+async fn install_package_zip(
+    state: &AppState,
+    tenant_id: &str,
+    zip_bytes: Vec<u8>,
+) -> Result<PackageInstallResponse, AppError> {
+    let integrity = SigningPolicy::from_env().verify(&zip_bytes)?;
     let mut archive = ZipArchive::new(Cursor::new(zip_bytes))
         .map_err(|e| AppError::BadRequest(format!("invalid zip: {}", e)))?;
 
@@ -610,34 +920,33 @@ pub async fn install_package(
             )
         })?;
 
-    let ctx = resolve_tenant_context(&state, Some(tenant_id), None, Some(id)).await?;
+    let ctx = resolve_tenant_context(state, Some(tenant_id), None, Some(id)).await?;
     let config_pool = ctx.config_pool();
     // migration_pool and schema_override are no longer used directly — broadcast_ddl handles all targets.
     let package_cache_key = ctx.package_cache_key().to_string();
 
-    // Check that all declared dependency packages are already installed.
-    if let Some(deps) = manifest_obj.get("dependencies").and_then(Value::as_array) {
-        let installed_ids: std::collections::HashSet<String> =
-            list_package_ids(config_pool).await?.into_iter().collect();
-        let missing: Vec<&str> = deps
-            .iter()
-            .filter_map(Value::as_str)
-            .filter(|dep| !installed_ids.contains(*dep))
-            .collect();
-        if !missing.is_empty() {
-            return Err(AppError::BadRequest(format!(
-                "package '{}' depends on [{}] which are not installed; install them first",
-                id,
-                missing.join(", ")
-            )));
-        }
-    }
-
     let incoming_version = manifest_obj
         .get("version")
         .and_then(Value::as_str)
         .unwrap_or("");
 
+    // Declared dependencies must be installed at a version in range, and an upgrade must stay
+    // within the ranges of the packages that depend on this one.
+    let dependencies = parse_dependencies(&manifest_value)?;
+    let installed_packages = list_packages(config_pool).await?;
+    let installed_versions: HashMap<String, Option<String>> = installed_packages
+        .iter()
+        .map(|p| (p.id.clone(), p.semantic_version.clone()))
+        .collect();
+    check_dependencies(id, &dependencies, &installed_versions)?;
+    check_dependents_allow(
+        id,
+        incoming_version,
+        installed_packages
+            .iter()
+            .map(|p| (p.id.as_str(), &p.payload)),
+    )?;
+
     // For upgrades: load old config BEFORE replacing so we can diff
     let is_upgrade = if let Some(existing) = get_package(config_pool, id).await? {
         if existing.semantic_version.as_deref() == Some(incoming_version) {
@@ -661,30 +970,10 @@ pub async fn install_package(
         None
     };
 
-    let schemas_body: Vec<Value> = vec![serde_json::json!({
-        "id": DEFAULT_SCHEMA_ID,
-        "name": schema_name
-    })];
-
     // Read and normalize every config kind from the zip into memory FIRST. Nothing is written to
     // the architect DB or any tenant DB until the whole package validates and its DDL succeeds, so
     // a bad package can never leave behind orphan `_sys_*` rows or half-created tables.
-    let apply_order = config_apply_order();
-    let mut bodies: Vec<(&'static str, Vec<Value>)> = Vec::with_capacity(apply_order.len());
-    for kind in &apply_order {
-        let body: Vec<Value> = if *kind == "schemas" {
-            schemas_body.clone()
-        } else {
-            let mut body = read_kind_from_zip(&mut archive, kind)?;
-            match *kind {
-                "enums" | "tables" | "indexes" => inject_schema_id(&mut body, DEFAULT_SCHEMA_ID),
-                "relationships" => inject_relationship_schema_ids(&mut body, DEFAULT_SCHEMA_ID),
-                _ => {}
-            }
-            body
-        };
-        bodies.push((*kind, body));
-    }
+    let bodies = read_package_bodies(&mut archive, schema_name)?;
 
     // Assemble and fully validate the config in memory (schema/type/reference checks, including
     // enum schema-prefix typos) before touching any database.
@@ -695,14 +984,18 @@ pub async fn install_package(
 
     // Reject the install if the package contains asset columns but no storage is configured.
     reject_asset_columns_without_storage(&config, &state.storage)?;
+    let data_migrations =
+        read_data_migrations_from_zip(&mut archive, &manifest_name, state.dialect.as_ref())?;
+    check_data_migrations(&data_migrations, &config, &new_model)?;
 
     // Broadcast DDL to every registered tenant database.
     // For a fresh install old_config is None (apply_migrations). For an upgrade it is Some (compute_migration_plan + execute).
     let tenant_outcomes = broadcast_ddl(
-        &state,
+        state,
         config_pool,
         &config,
         old_config.as_ref(),
+        None,
         id,
         old_config
             .as_ref()
@@ -737,55 +1030,39 @@ pub async fn install_package(
         applied.push(kind.to_string());
     }
     upsert_package(config_pool, id, &manifest_value).await?;
+    set_package_integrity(config_pool, id, &integrity).await?;
+    record_package_config_versions(config_pool, id).await?;
+    // Pending upgrade plans were computed against the version that was just replaced.
+    expire_pending_migration_plans(&state.pool, id, None).await?;
 
+    let data_model = new_model.clone();
     let migration_warnings: Vec<String> = tenant_outcomes
         .iter()
         .flat_map(|o| o.warnings.iter().cloned())
         .collect();
 
-    // Populate the in-memory ResolvedModel for every tenant cache slot.
-    {
-        let mut model_guard = state
-            .model
-            .write()
-            .map_err(|_| AppError::BadRequest("state lock".into()))?;
-        *model_guard = new_model.clone();
-        let mut pkg_guard = state
-            .package_models
-            .write()
-            .map_err(|_| AppError::BadRequest("state lock".into()))?;
-        // Shared key used by all RLS tenants.
-        pkg_guard.insert(id.to_string(), new_model.clone());
-        // Per-tenant keys used by each Database-strategy tenant.
-        for (tid, _) in state.tenant_registry.database_tenant_targets() {
-            pkg_guard.insert(format!("{}:{}", id, tid), new_model.clone());
-        }
-        // Keep the requesting tenant's own cache slot in sync (covers edge cases).
-        pkg_guard.insert(package_cache_key, new_model);
-    }
-    // Package set changed: drop the cached cross-package index so it rebuilds on the next include.
-    crate::handlers::entity::invalidate_cross_package_index(&state);
+    publish_package_model(state, id, package_cache_key, new_model)?;
 
-    #[derive(serde::Serialize)]
-    struct PackageInstallResponse {
-        package: Value,
-        applied: Vec<String>,
-        warnings: Vec<String>,
-        /// DDL execution result for each tenant database that was targeted.
-        tenant_migrations: Vec<TenantMigrationOutcome>,
-    }
-    Ok((
-        axum::http::StatusCode::OK,
-        Json(crate::response::SuccessOne {
-            data: PackageInstallResponse {
-                package: manifest_value,
-                applied,
-                warnings: migration_warnings,
-                tenant_migrations: tenant_outcomes,
-            },
-            meta: None,
-        }),
-    ))
+    // Tables exist and config is persisted: ship the package's data to every tenant.
+    let data_outcomes = run_data_migrations(
+        state,
+        &state.tenant_registry.tenant_ids(),
+        id,
+        Some(incoming_version),
+        schema_name,
+        &data_model,
+        &data_migrations,
+    )
+    .await;
+
+    Ok(PackageInstallResponse {
+        package: manifest_value,
+        applied,
+        warnings: migration_warnings,
+        tenant_migrations: tenant_outcomes,
+        data_migrations: data_outcomes,
+        integrity,
+    })
 }
 
 #[derive(Deserialize)]
@@ -820,18 +1097,15 @@ pub async fn uninstall_package(
 
     // Block uninstall if another installed package declares this one as a dependency.
     let all_packages = list_packages(config_pool).await?;
-    let dependents: Vec<String> = all_packages
-        .iter()
-        .filter(|row| row.id != package_id)
-        .filter(|row| {
-            row.payload
-                .get("dependencies")
-                .and_then(Value::as_array)
-                .map(|deps| deps.iter().any(|d| d.as_str() == Some(package_id.as_str())))
-                .unwrap_or(false)
-        })
-        .map(|row| row.id.clone())
-        .collect();
+    let dependents: Vec<String> = dependents(
+        &package_id,
+        all_packages
+            .iter()
+            .map(|row| (row.id.as_str(), &row.payload)),
+    )
+    .into_iter()
+    .map(|(id, _)| id)
+    .collect();
     if !dependents.is_empty() {
         return Err(AppError::Conflict(format!(
             "cannot uninstall '{}': packages [{}] depend on it; uninstall them first",
@@ -845,6 +1119,7 @@ pub async fn uninstall_package(
         .map_err(AppError::Config)?;
     revert_migrations(migration_pool, &config, schema_override).await?;
     delete_package_and_config(config_pool, &package_id).await?;
+    expire_pending_migration_plans(&state.pool, &package_id, None).await?;
 
     {
         state
@@ -948,12 +1223,12 @@ pub async fn list_packages_handler(
             .get("schema")
             .and_then(Value::as_str)
             .map(String::from);
-        let dependencies: Vec<&str> = pkg
-            .payload
-            .get("dependencies")
-            .and_then(Value::as_array)
-            .map(|arr| arr.iter().filter_map(Value::as_str).collect())
-            .unwrap_or_default();
+        let dependencies: Vec<String> = parse_dependencies(&pkg.payload)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|d| d.package_id)
+            .collect();
+        let dependency_ranges = dependencies_json(&pkg.payload);
 
         items.push(json!({
             "id": pkg.id,
@@ -963,6 +1238,7 @@ pub async fn list_packages_handler(
             "installedVersion": pkg.version,
             "updatedAt": pkg.updated_at,
             "dependencies": dependencies,
+            "dependencyRanges": dependency_ranges,
             "stats": {
                 "schemas": schemas,
                 "enums": enums,
@@ -1026,6 +1302,14 @@ pub async fn get_package_handler(
     obj.insert("schema".into(), json!(schema));
     obj.insert("installedVersion".into(), json!(pkg.version));
     obj.insert("updatedAt".into(), json!(pkg.updated_at));
+    obj.insert(
+        "integrity".into(),
+        json!({
+            "contentHash": pkg.content_hash,
+            "signer": pkg.signer,
+            "keyId": pkg.key_id,
+        }),
+    );
     obj.insert("manifest".into(), pkg.payload);
 
     Ok((
@@ -1037,21 +1321,396 @@ pub async fn get_package_handler(
     ))
 }
 
-// ─── Migration preview / apply ───────────────────────────────────────────────
-
-/// POST /api/v1/config/package/migration/preview
-/// Upload a package zip to preview the migration plan without applying any changes.
-/// The returned `migration_id` can be passed to the apply endpoint after review.
-/// X-Tenant-ID required. Only valid for upgrades (package must already be installed).
-pub async fn preview_migration_handler(
-    TenantId(tenant_id_opt): TenantId,
+/// GET /api/v1/config/packages/:package_id/export — the package's stored config as an installable
+/// ZIP: `manifest.json` plus one `{kind}.json` per config kind, in the layout install reads.
+/// Records are ordered by id and object keys sorted, so exporting unchanged config gives the same
+/// bytes and re-installing the export and exporting again round-trips. Works for config posted
+/// through `/config/tables` etc. as well; the `data/` folder and signature are not stored and not
+/// exported.
+pub async fn export_package_handler(
     State(state): State<AppState>,
-    mut multipart: Multipart,
+    Path(PackageIdPath { package_id }): Path<PackageIdPath>,
+    ReadConsistency(consistency): ReadConsistency,
 ) -> Result<impl axum::response::IntoResponse, AppError> {
-    let tenant_id = tenant_id_opt
-        .as_deref()
-        .filter(|s| !s.is_empty())
-        .ok_or_else(|| AppError::BadRequest("X-Tenant-ID header is required".into()))?;
+    // The central DB's replica serves exports unless the caller asks for strong consistency.
+    let mut pool = state.pool.clone();
+    if consistency == Consistency::Eventual {
+        if let Some(url) = crate::replica::central_replica_url() {
+            if let Some(replica) = state
+                .replica_pools
+                .read_pool(&url, state.dialect.as_ref())
+                .await
+            {
+                pool = replica;
+            }
+        }
+    }
+    let installed = get_package(&pool, &package_id).await?;
+    let config = load_from_pool(&pool, &package_id)
+        .await
+        .map_err(AppError::Config)?;
+    if installed.is_none() && config.tables.is_empty() && config.enums.is_empty() {
+        return Err(AppError::NotFound(format!(
+            "package not found: {}",
+            package_id
+        )));
+    }
+    let manifest = export_manifest(&package_id, installed.as_ref().map(|p| &p.payload), &config)?;
+    let bytes = write_package_zip(&manifest, &config)?;
+    let version = manifest
+        .get("version")
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+    let filename = format!("{}-{}.zip", package_id, version).replace('"', "");
+    Ok((
+        axum::http::StatusCode::OK,
+        [
+            (
+                axum::http::header::CONTENT_TYPE,
+                "application/zip".to_string(),
+            ),
+            (
+                axum::http::header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}\"", filename),
+            ),
+        ],
+        bytes,
+    ))
+}
+
+#[derive(Deserialize)]
+pub struct RenderDdlQuery {
+    /// `postgres`, `mysql` or `sqlite`. Defaults to the server's dialect.
+    pub dialect: Option<String>,
+    /// Also render the RLS tenant column and policies (dialects with RLS only).
+    #[serde(default)]
+    pub rls: bool,
+}
+
+/// POST /api/v1/config/package/render-ddl?dialect=: multipart form with a package zip. Returns the
+/// DDL a fresh install would run on `dialect`, in order, with `best_effort` marking statements
+/// whose failure install ignores. Any of the three dialects can be rendered, whichever one the
+/// server runs on. Nothing is executed or stored; cross-package relationships resolve against the
+/// packages installed here.
+pub async fn render_ddl_handler(
+    State(state): State<AppState>,
+    Query(query): Query<RenderDdlQuery>,
+    mut multipart: Multipart,
+) -> Result<impl axum::response::IntoResponse, AppError> {
+    let dialect = match query.dialect.as_deref() {
+        None => state.dialect.clone(),
+        Some(name) => dialect_by_name(name).ok_or_else(|| {
+            AppError::BadRequest(format!(
+                "unknown dialect '{}'; expected postgres, mysql or sqlite",
+                name
+            ))
+        })?,
+    };
+    let zip_bytes = read_package_fields(&mut multipart)
+        .await?
+        .into_iter()
+        .next()
+        .unwrap_or_default();
+    let (manifest, config) = read_package_config(&zip_bytes)?;
+    let package_id = manifest
+        .get("id")
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+    let cross = load_other_package_configs(&state.pool, package_id).await;
+    let rls = query.rls.then_some(RLS_TENANT_COLUMN);
+    let statements = render_migrations(&config, None, rls, dialect.as_ref(), &cross)?;
+
+    Ok((
+        axum::http::StatusCode::OK,
+        Json(crate::response::SuccessOne {
+            data: json!({
+                "package_id": package_id,
+                "version": manifest.get("version"),
+                "dialect": dialect.name(),
+                "statements": statements,
+            }),
+            meta: None,
+        }),
+    ))
+}
+
+#[derive(Deserialize)]
+pub struct DriftQuery {
+    /// Also return, per target, the migration plan that would repair the drift.
+    #[serde(default)]
+    pub repair: bool,
+}
+
+/// One database checked by the drift report.
+struct DriftTarget {
+    target: String,
+    strategy: &'static str,
+    pool: Result<Pool, AppError>,
+    rls_column: Option<&'static str>,
+}
+
+/// GET /api/v1/config/packages/:package_id/drift — compare the installed config with the live
+/// schema of every tenant database (the same targets install/upgrade broadcast DDL to) and report
+/// missing/extra tables, columns, indexes and constraints plus nullability and type mismatches.
+/// With `?repair=true` each target also carries a `repair_plan` in the shape returned by the
+/// migration preview. Read-only: nothing is executed.
+pub async fn package_drift_handler(
+    State(state): State<AppState>,
+    Path(PackageIdPath { package_id }): Path<PackageIdPath>,
+    Query(query): Query<DriftQuery>,
+) -> Result<impl axum::response::IntoResponse, AppError> {
+    let pkg = get_package(&state.pool, &package_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("package not found: {}", package_id)))?;
+    let config = load_from_pool(&state.pool, &package_id)
+        .await
+        .map_err(AppError::Config)?;
+    let others = load_other_package_configs(&state.pool, &package_id).await;
+    let schemas: Vec<String> = config.schemas.iter().map(|s| s.name.clone()).collect();
+    let dialect = state.dialect.as_ref();
+
+    // Same targets as `broadcast_ddl`.
+    let mut targets: Vec<DriftTarget> = Vec::new();
+    if state.tenant_registry.has_shared_rls_tenants() {
+        targets.push(DriftTarget {
+            target: "central_rls_db".to_string(),
+            strategy: "rls",
+            pool: Ok(state.pool.clone()),
+            rls_column: Some(crate::migration::RLS_TENANT_COLUMN),
+        });
+    }
+    let mut seen_rls_urls: HashSet<String> = HashSet::new();
+    for (tid, db_url) in state.tenant_registry.rls_dedicated_db_targets() {
+        if !seen_rls_urls.insert(db_url.clone()) {
+            continue;
+        }
+        let pool = get_or_create_tenant_pool(&state, &tid, &db_url).await;
+        targets.push(DriftTarget {
+            target: tid,
+            strategy: "rls",
+            pool,
+            rls_column: Some(crate::migration::RLS_TENANT_COLUMN),
+        });
+    }
+    for (tid, db_url) in state.tenant_registry.database_tenant_targets() {
+        let pool = get_or_create_tenant_pool(&state, &tid, &db_url).await;
+        targets.push(DriftTarget {
+            target: tid,
+            strategy: "database",
+            pool,
+            rls_column: None,
+        });
+    }
+
+    let mut results: Vec<Value> = Vec::new();
+    let mut drifted = 0usize;
+    for DriftTarget {
+        target,
+        strategy,
+        pool,
+        rls_column,
+    } in targets
+    {
+        let pool = match pool {
+            Ok(p) => p,
+            Err(e) => {
+                tracing::warn!(target = %target, error = %e, "could not connect to tenant DB for drift check");
+                results.push(json!({
+                    "target": target,
+                    "strategy": strategy,
+                    "status": "failed",
+                    "error": format!("connection failed: {}", e),
+                }));
+                continue;
+            }
+        };
+        let snap = crate::db::introspect(&pool, dialect, &schemas).await;
+        let report = detect_drift(&config, &snap, None, rls_column, &others, dialect);
+        let status = if !report.introspected {
+            "unknown"
+        } else if report.is_clean() {
+            "in_sync"
+        } else {
+            drifted += 1;
+            "drifted"
+        };
+        let mut entry = json!({
+            "target": target,
+            "strategy": strategy,
+            "status": status,
+            "summary": report.summary(),
+            "indexes_checked": report.indexes_checked,
+            "constraints_checked": report.constraints_checked,
+            "items": report.items,
+        });
+        if query.repair {
+            let plan = drift_repair_plan(&config, &snap, None, rls_column, &others, dialect)
+                .map_err(|e| AppError::BadRequest(format!("repair plan error: {}", e)))?;
+            entry["repair_plan"] = json!({
+                "summary": plan.summary(),
+                "steps": plan.steps,
+            });
+        }
+        results.push(entry);
+    }
+
+    Ok((
+        axum::http::StatusCode::OK,
+        Json(crate::response::SuccessOne {
+            data: json!({
+                "package_id": package_id,
+                "installed_version": pkg.semantic_version,
+                "drifted": drifted,
+                "targets": results,
+            }),
+            meta: None,
+        }),
+    ))
+}
+
+// ─── Reverse engineering ─────────────────────────────────────────────────────
+
+#[derive(Deserialize)]
+pub struct ReverseEngineerBody {
+    /// Schema to read (the database name on MySQL, `main` on SQLite).
+    pub source_schema: String,
+    /// Manifest of the generated package; `schema` defaults to `source_schema`.
+    #[serde(flatten)]
+    pub package: ReverseOptions,
+}
+
+/// POST /api/v1/config/package/reverse-engineer — read an existing schema from the architect
+/// database (DATABASE_URL) and return a complete package zip for it: manifest, enums, tables,
+/// columns, indexes, relationships and one API entity per table. Read-only. Objects package
+/// config cannot express are left out; their count is in `X-Skipped-Objects` and each is logged.
+pub async fn reverse_engineer_handler(
+    State(state): State<AppState>,
+    Json(body): Json<ReverseEngineerBody>,
+) -> Result<impl axum::response::IntoResponse, AppError> {
+    if body.package.id.trim().is_empty() {
+        return Err(AppError::BadRequest("id is required".into()));
+    }
+    let generated = reverse_engineer(
+        &state.pool,
+        state.dialect.as_ref(),
+        &body.source_schema,
+        &body.package,
+    )
+    .await?;
+    if generated.config.tables.is_empty() {
+        return Err(AppError::NotFound(format!(
+            "no tables with a primary key found in schema '{}'",
+            body.source_schema
+        )));
+    }
+    for item in &generated.skipped {
+        tracing::warn!(package_id = %body.package.id, "reverse engineering skipped {}", item);
+    }
+    let bytes = write_package_zip(&generated.manifest, &generated.config)?;
+    let version = generated
+        .manifest
+        .get("version")
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+    let filename = format!("{}-{}.zip", body.package.id, version).replace('"', "");
+    Ok((
+        axum::http::StatusCode::OK,
+        [
+            (
+                axum::http::header::CONTENT_TYPE,
+                "application/zip".to_string(),
+            ),
+            (
+                axum::http::header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}\"", filename),
+            ),
+            (
+                axum::http::header::HeaderName::from_static("x-skipped-objects"),
+                generated.skipped.len().to_string(),
+            ),
+        ],
+        bytes,
+    ))
+}
+
+// ─── Migration preview / apply ───────────────────────────────────────────────
+
+#[derive(Deserialize)]
+pub struct MigrationPreviewQuery {
+    /// `standard` (default) or `online`.
+    #[serde(default)]
+    pub mode: MigrationMode,
+    /// Online mode: `lock_timeout` per step. Defaults to 5000.
+    pub lock_timeout_ms: Option<u64>,
+    /// Online mode: `statement_timeout` for blocking steps and backfill batches. Unset by default.
+    pub statement_timeout_ms: Option<u64>,
+    /// Online mode: rows per backfill batch. Defaults to 10000.
+    pub batch_size: Option<u64>,
+    /// `in_place` (default) or `expand_contract`: column type changes become a shadow column
+    /// that is backfilled and swapped in later phases (see [`crate::transition`]).
+    #[serde(default)]
+    pub type_changes: TypeChangeStrategy,
+    /// Probe the tenant database for rows the risky steps would fail on or overwrite (see
+    /// [`crate::preflight`]). Defaults to true.
+    #[serde(default = "default_probe")]
+    pub probe: bool,
+}
+
+fn default_probe() -> bool {
+    true
+}
+
+impl MigrationPreviewQuery {
+    /// Online options for this request, or `None` in standard mode.
+    fn online_options(
+        &self,
+        dialect: &dyn Dialect,
+    ) -> Result<Option<OnlineMigrationOptions>, AppError> {
+        if self.mode == MigrationMode::Standard {
+            return Ok(None);
+        }
+        if !dialect.supports_online_ddl() {
+            return Err(AppError::BadRequest(format!(
+                "online migration mode is not supported on {}",
+                dialect.name()
+            )));
+        }
+        let defaults = OnlineMigrationOptions::default();
+        let opts = OnlineMigrationOptions {
+            lock_timeout_ms: self.lock_timeout_ms.unwrap_or(defaults.lock_timeout_ms),
+            statement_timeout_ms: self.statement_timeout_ms.or(defaults.statement_timeout_ms),
+            backfill_batch_size: self.batch_size.unwrap_or(defaults.backfill_batch_size),
+        };
+        if opts.lock_timeout_ms == 0 || opts.backfill_batch_size == 0 {
+            return Err(AppError::BadRequest(
+                "lock_timeout_ms and batch_size must be greater than zero".into(),
+            ));
+        }
+        Ok(Some(opts))
+    }
+}
+
+/// POST /api/v1/config/package/migration/preview
+/// Upload a package zip to preview the migration plan without applying any changes.
+/// The returned `migration_id` can be passed to the apply endpoint after review.
+/// With `?mode=online` (Postgres only) the plan is rewritten to avoid long blocking locks; see
+/// [`to_online_plan`]. Every step of a Postgres plan reports its expected `lock_level`.
+/// With `?type_changes=expand_contract` column type changes only add shadow columns; the copy and
+/// the swap run later through the transition endpoints.
+/// X-Tenant-ID required; X-User-ID, when sent, is recorded as the plan's requester, who may not
+/// approve it. Only valid for upgrades (package must already be installed).
+pub async fn preview_migration_handler(
+    TenantId(tenant_id_opt): TenantId,
+    UserId(user_id): UserId,
+    State(state): State<AppState>,
+    Query(query): Query<MigrationPreviewQuery>,
+    mut multipart: Multipart,
+) -> Result<impl axum::response::IntoResponse, AppError> {
+    let tenant_id = tenant_id_opt
+        .as_deref()
+        .filter(|s| !s.is_empty())
+        .ok_or_else(|| AppError::BadRequest("X-Tenant-ID header is required".into()))?;
+    let online = query.online_options(state.dialect.as_ref())?;
 
     let mut zip_bytes_raw: Option<Vec<u8>> = None;
     while let Ok(Some(field)) = multipart.next_field().await {
@@ -1067,6 +1726,8 @@ pub async fn preview_migration_handler(
     }
     let zip_bytes = zip_bytes_raw
         .ok_or_else(|| AppError::BadRequest("missing 'file' or 'package' field".into()))?;
+    // Reject an unsigned or tampered upgrade before computing anything from it.
+    SigningPolicy::from_env().verify(&zip_bytes)?;
 
     let mut archive = ZipArchive::new(Cursor::new(zip_bytes.clone()))
         .map_err(|e| AppError::BadRequest(format!("invalid zip: {}", e)))?;
@@ -1158,7 +1819,7 @@ pub async fn preview_migration_handler(
     // Deserialize into FullConfig manually using the same logic as load_from_pool
     let new_config = build_full_config_from_values(&all_values)?;
 
-    let plan = compute_migration_plan(
+    let mut plan = compute_migration_plan(
         &old_config,
         &new_config,
         ctx.schema_override(),
@@ -1167,8 +1828,33 @@ pub async fn preview_migration_handler(
         &std::collections::HashMap::new(),
     )
     .map_err(|e| AppError::BadRequest(format!("migration plan error: {}", e)))?;
+    if query.probe {
+        probe_plan(
+            ctx.migration_pool(),
+            &mut plan,
+            &new_config,
+            state.dialect.as_ref(),
+            ctx.rls_tenant_column().is_none(),
+        )
+        .await;
+    }
+    let plan = match query.type_changes {
+        TypeChangeStrategy::ExpandContract => {
+            to_expand_contract_plan(plan, &old_config, &new_config, state.dialect.as_ref())
+        }
+        TypeChangeStrategy::InPlace => plan,
+    };
+    let plan = match &online {
+        Some(opts) => to_online_plan(plan, opts),
+        None => plan,
+    };
 
     let summary = plan.summary();
+    let gated_steps: Vec<usize> = ApprovalPolicy::from_env()
+        .gated_steps(&plan)
+        .iter()
+        .map(|s| s.step)
+        .collect();
     let plan_json = serde_json::to_value(&plan).map_err(|e| AppError::BadRequest(e.to_string()))?;
     let migration_id = Uuid::new_v4().to_string();
 
@@ -1181,6 +1867,7 @@ pub async fn preview_migration_handler(
         incoming_version,
         &plan_json,
         &zip_bytes,
+        requester(&user_id),
     )
     .await?;
 
@@ -1193,6 +1880,10 @@ pub async fn preview_migration_handler(
                 "from_version": from_version,
                 "to_version": incoming_version,
                 "expires_in_hours": 24,
+                "mode": query.mode,
+                "type_changes": query.type_changes,
+                "approval_required": !gated_steps.is_empty(),
+                "gated_steps": gated_steps,
                 "summary": {
                     "total": summary.total,
                     "safe": summary.safe,
@@ -1206,19 +1897,417 @@ pub async fn preview_migration_handler(
     ))
 }
 
+#[derive(Deserialize)]
+pub struct RollbackQuery {
+    /// Previously installed `manifest.version` to return to.
+    pub to_version: String,
+    /// Run the rollback. Without it the reverse plan is only previewed.
+    #[serde(default)]
+    pub apply: bool,
+    /// Plan id returned by the preview; required with `apply=true`.
+    pub migration_id: Option<String>,
+    /// Probe the tenant data for the plan's risky steps (default true).
+    #[serde(default = "default_probe")]
+    pub probe: bool,
+}
+
+/// POST /api/v1/config/package/:package_id/rollback?to_version= — return a package to a version
+/// installed earlier. The historic config is reassembled from the `_sys_*_history` tables using
+/// the config versions recorded with that package version, and diffed against the installed
+/// config into a reverse migration plan. Without `apply=true` the plan is previewed (with probes
+/// and approval gating, like an upgrade preview) and stored in `_sys_migration_plans`; with
+/// `apply=true&migration_id=` the stored plan is broadcast to every tenant database and the
+/// historic config, manifest and integrity are restored. A gated plan needs an approval recorded
+/// with `POST /config/package/migration/approve/:migration_id` first, by someone other than the
+/// X-User-ID that previewed it. X-Tenant-ID required.
+pub async fn rollback_package_handler(
+    TenantId(tenant_id_opt): TenantId,
+    UserId(user_id): UserId,
+    State(state): State<AppState>,
+    Path(UninstallPath { package_id }): Path<UninstallPath>,
+    Query(query): Query<RollbackQuery>,
+) -> Result<impl axum::response::IntoResponse, AppError> {
+    let tenant_id = require_install_tenant(tenant_id_opt.as_deref(), &state)?;
+    let ctx = resolve_tenant_context(&state, Some(&tenant_id), None, Some(&package_id)).await?;
+    let config_pool = ctx.config_pool();
+    let package_cache_key = ctx.package_cache_key().to_string();
+
+    let current = get_package(config_pool, &package_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("package not found: {}", package_id)))?;
+    let to_version = query.to_version.as_str();
+    if current.semantic_version.as_deref() == Some(to_version) {
+        return Err(AppError::Conflict(format!(
+            "package '{}' version '{}' is already installed",
+            package_id, to_version
+        )));
+    }
+    let target = get_package_history_version(config_pool, &package_id, to_version)
+        .await?
+        .ok_or_else(|| {
+            AppError::NotFound(format!(
+                "package '{}' has no installed history for version '{}'",
+                package_id, to_version
+            ))
+        })?;
+    let config_versions = target
+        .config_versions
+        .as_ref()
+        .and_then(Value::as_object)
+        .ok_or_else(|| {
+            AppError::BadRequest(format!(
+                "version '{}' of package '{}' was installed before config versions were \
+                 recorded and cannot be reassembled",
+                to_version, package_id
+            ))
+        })?;
+
+    let mut bodies: Vec<(&'static str, Vec<Value>)> = Vec::new();
+    for kind in config_apply_order() {
+        let table = sys_table_for_kind(kind)
+            .ok_or_else(|| AppError::BadRequest(format!("unknown config kind: {}", kind)))?;
+        let body = match config_versions.get(table).and_then(Value::as_i64) {
+            Some(version) => {
+                load_config_rows_at_version(config_pool, table, &package_id, version).await?
+            }
+            None => Vec::new(),
+        };
+        bodies.push((kind, body));
+    }
+    let historic = assemble_config(&bodies)?;
+    let new_model = resolve(&historic)
+        .map_err(AppError::Config)?
+        .with_package_id(&package_id);
+
+    let installed_packages = list_packages(config_pool).await?;
+    let installed_versions: HashMap<String, Option<String>> = installed_packages
+        .iter()
+        .map(|p| (p.id.clone(), p.semantic_version.clone()))
+        .collect();
+    check_dependencies(
+        &package_id,
+        &parse_dependencies(&target.payload)?,
+        &installed_versions,
+    )?;
+    check_dependents_allow(
+        &package_id,
+        to_version,
+        installed_packages
+            .iter()
+            .map(|p| (p.id.as_str(), &p.payload)),
+    )?;
+
+    let current_config = load_from_pool(config_pool, &package_id)
+        .await
+        .map_err(AppError::Config)?;
+    let cross_package_configs = load_other_package_configs(config_pool, &package_id).await;
+    let mut plan = compute_migration_plan(
+        &current_config,
+        &historic,
+        ctx.schema_override(),
+        ctx.rls_tenant_column(),
+        state.dialect.as_ref(),
+        &cross_package_configs,
+    )
+    .map_err(|e| AppError::BadRequest(format!("migration plan error: {}", e)))?;
+    if query.probe {
+        probe_plan(
+            ctx.migration_pool(),
+            &mut plan,
+            &historic,
+            state.dialect.as_ref(),
+            ctx.rls_tenant_column().is_none(),
+        )
+        .await;
+    }
+    let policy = ApprovalPolicy::from_env();
+    let gated_steps: Vec<usize> = policy.gated_steps(&plan).iter().map(|s| s.step).collect();
+    let summary = plan.summary();
+    let from_version = current.semantic_version.clone();
+
+    if !query.apply {
+        let plan_json =
+            serde_json::to_value(&plan).map_err(|e| AppError::BadRequest(e.to_string()))?;
+        let migration_id = Uuid::new_v4().to_string();
+        // A rollback has no package zip: the historic config comes from the history tables.
+        save_migration_plan(
+            config_pool,
+            &migration_id,
+            &package_id,
+            &tenant_id,
+            from_version.as_deref(),
+            to_version,
+            &plan_json,
+            &[],
+            requester(&user_id),
+        )
+        .await?;
+        return Ok((
+            axum::http::StatusCode::OK,
+            Json(crate::response::SuccessOne {
+                data: json!({
+                    "migration_id": migration_id,
+                    "package_id": package_id,
+                    "from_version": from_version,
+                    "to_version": to_version,
+                    "expires_in_hours": 24,
+                    "applied": false,
+                    "approval_required": !gated_steps.is_empty(),
+                    "gated_steps": gated_steps,
+                    "summary": summary,
+                    "steps": plan.steps,
+                }),
+                meta: None,
+            }),
+        ));
+    }
+
+    let migration_id = query.migration_id.as_deref().ok_or_else(|| {
+        AppError::BadRequest(
+            "apply=true requires the migration_id returned by the rollback preview".into(),
+        )
+    })?;
+    let row = get_migration_plan(config_pool, migration_id)
+        .await?
+        .filter(|row| row.package_id == package_id && row.zip_bytes.is_empty())
+        .ok_or_else(|| {
+            AppError::NotFound(format!(
+                "rollback plan '{}' not found for package '{}'",
+                migration_id, package_id
+            ))
+        })?;
+    if row.status != "pending" {
+        return Err(AppError::Conflict(format!(
+            "migration plan '{}' has status '{}' and cannot be applied",
+            migration_id, row.status
+        )));
+    }
+    if chrono::Utc::now() > row.expires_at {
+        return Err(AppError::BadRequest(format!(
+            "migration plan '{}' expired at {} — re-run preview to generate a new plan",
+            migration_id, row.expires_at
+        )));
+    }
+    if row.tenant_id != tenant_id || row.to_version != to_version {
+        return Err(AppError::BadRequest(format!(
+            "migration plan '{}' was created for tenant '{}' and version '{}'",
+            migration_id, row.tenant_id, row.to_version
+        )));
+    }
+    let stored: MigrationPlan = serde_json::from_value(row.plan_json.clone())
+        .map_err(|e| AppError::BadRequest(format!("corrupted migration plan: {}", e)))?;
+    // The approval covers the previewed DDL; a different reverse plan needs a new preview.
+    let same_ddl = row.from_version == from_version
+        && stored
+            .steps
+            .iter()
+            .map(|s| &s.ddl)
+            .eq(plan.steps.iter().map(|s| &s.ddl));
+    if !same_ddl {
+        expire_migration_plan(config_pool, migration_id).await?;
+        return Err(AppError::Conflict(format!(
+            "rollback plan '{}' no longer matches the installed package — re-run preview",
+            migration_id
+        )));
+    }
+    let approval: Option<PlanApproval> = row
+        .approval_json
+        .clone()
+        .map(serde_json::from_value)
+        .transpose()
+        .map_err(|e| AppError::BadRequest(format!("corrupted plan approval: {}", e)))?;
+    check_approval(&policy, &stored, approval.as_ref())?;
+    let approved_plan = match &approval {
+        Some(approval) => apply_overrides(stored, approval),
+        None => stored,
+    };
+    if !mark_migration_plan_applied(config_pool, migration_id).await? {
+        return Err(AppError::Conflict(format!(
+            "migration plan '{}' was applied by a concurrent request",
+            migration_id
+        )));
+    }
+
+    let tenant_outcomes = broadcast_ddl(
+        &state,
+        config_pool,
+        &historic,
+        Some(&current_config),
+        Some(approved_plan.clone()),
+        &package_id,
+        from_version.as_deref(),
+        to_version,
+    )
+    .await;
+
+    for (kind, body) in bodies {
+        replace_config(config_pool, kind, body, false, &package_id, None).await?;
+    }
+    upsert_package(config_pool, &package_id, &target.payload).await?;
+    set_package_integrity(config_pool, &package_id, &target.integrity).await?;
+    record_package_config_versions(config_pool, &package_id).await?;
+    expire_pending_migration_plans(config_pool, &package_id, Some(migration_id)).await?;
+    publish_package_model(&state, &package_id, package_cache_key, new_model)?;
+
+    let warnings: Vec<String> = tenant_outcomes
+        .iter()
+        .flat_map(|o| o.warnings.iter().cloned())
+        .collect();
+    Ok((
+        axum::http::StatusCode::OK,
+        Json(crate::response::SuccessOne {
+            data: json!({
+                "migration_id": migration_id,
+                "package_id": package_id,
+                "from_version": from_version,
+                "to_version": to_version,
+                "applied": true,
+                "approved_by": approval.map(|a| a.approver),
+                "summary": approved_plan.summary(),
+                "steps": approved_plan.steps,
+                "warnings": warnings,
+                "tenant_migrations": tenant_outcomes,
+            }),
+            meta: None,
+        }),
+    ))
+}
+
+/// The trimmed `X-User-ID` of a preview, recorded as the plan's requester.
+fn requester(user_id: &Option<String>) -> Option<&str> {
+    user_id.as_deref().map(str::trim).filter(|s| !s.is_empty())
+}
+
 #[derive(Deserialize)]
 pub struct MigrationIdPath {
     pub migration_id: String,
 }
 
+#[derive(Deserialize)]
+pub struct ApproveMigrationRequest {
+    pub comment: Option<String>,
+    #[serde(default)]
+    pub overrides: Vec<StepOverride>,
+}
+
+/// POST /api/v1/config/package/migration/approve/:migration_id
+/// Record an approval for a pending plan, so it may be applied when the approval policy gates it
+/// (see [`crate::approval`]). Approving again replaces the earlier approval. X-Tenant-ID and
+/// X-User-ID (the approver) required; the approver must not be the plan's requester and must be
+/// authorized by [`ApproverPolicy`] and, when configured, authrs (`approveMigration`).
+pub async fn approve_migration_handler(
+    TenantId(tenant_id_opt): TenantId,
+    UserId(user_id): UserId,
+    State(state): State<AppState>,
+    Path(MigrationIdPath { migration_id }): Path<MigrationIdPath>,
+    Json(body): Json<ApproveMigrationRequest>,
+) -> Result<impl axum::response::IntoResponse, AppError> {
+    let tenant_id = tenant_id_opt
+        .as_deref()
+        .filter(|s| !s.is_empty())
+        .ok_or_else(|| AppError::BadRequest("X-Tenant-ID header is required".into()))?;
+    let approver = user_id
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .ok_or_else(|| AppError::BadRequest("X-User-ID header is required".into()))?;
+
+    let row = get_migration_plan(&state.pool, &migration_id)
+        .await?
+        .ok_or_else(|| {
+            AppError::NotFound(format!("migration plan '{}' not found", migration_id))
+        })?;
+    if row.status != "pending" {
+        return Err(AppError::Conflict(format!(
+            "migration plan '{}' has status '{}' and cannot be approved",
+            migration_id, row.status
+        )));
+    }
+    if chrono::Utc::now() > row.expires_at {
+        return Err(AppError::BadRequest(format!(
+            "migration plan '{}' expired at {} — re-run preview to generate a new plan",
+            migration_id, row.expires_at
+        )));
+    }
+    if row.tenant_id != tenant_id {
+        return Err(AppError::BadRequest(format!(
+            "migration plan '{}' was created for tenant '{}', not '{}'",
+            migration_id, row.tenant_id, tenant_id
+        )));
+    }
+    ApproverPolicy::from_env().check_approver(
+        &approver,
+        row.requested_by.as_deref(),
+        tenant_id == crate::tenant::platform_tenant_id(),
+        state.authrs_client.is_some(),
+    )?;
+    if let Some(client) = &state.authrs_client {
+        crate::authrs::check_migration_approval(client, tenant_id, &approver, &row.package_id)
+            .await?;
+    }
+
+    let plan: MigrationPlan = serde_json::from_value(row.plan_json.clone())
+        .map_err(|e| AppError::BadRequest(format!("corrupted migration plan: {}", e)))?;
+    validate_overrides(&plan, &body.overrides)?;
+    let approval = PlanApproval {
+        approver,
+        comment: body.comment,
+        overrides: body.overrides,
+    };
+    let approval_json =
+        serde_json::to_value(&approval).map_err(|e| AppError::BadRequest(e.to_string()))?;
+    if !approve_migration_plan(
+        &state.pool,
+        &migration_id,
+        &approval.approver,
+        &approval_json,
+    )
+    .await?
+    {
+        return Err(AppError::Conflict(format!(
+            "migration plan '{}' is no longer pending",
+            migration_id
+        )));
+    }
+    let gated_steps: Vec<usize> = ApprovalPolicy::from_env()
+        .gated_steps(&plan)
+        .iter()
+        .map(|s| s.step)
+        .collect();
+
+    Ok((
+        axum::http::StatusCode::OK,
+        Json(crate::response::SuccessOne {
+            data: json!({
+                "migration_id": migration_id,
+                "approved_by": approval.approver,
+                "comment": approval.comment,
+                "overrides": approval.overrides,
+                "gated_steps": gated_steps,
+            }),
+            meta: None,
+        }),
+    ))
+}
+
+#[derive(Deserialize)]
+pub struct ApplyMigrationQuery {
+    /// All-or-nothing: one transaction where DDL is transactional, compensating steps elsewhere.
+    #[serde(default)]
+    pub atomic: bool,
+}
+
 /// POST /api/v1/config/package/migration/apply/:migration_id
 /// Apply a previously previewed migration plan. Idempotent: calling twice returns 409.
 /// Applies config changes to _sys_* tables, executes DDL against the tenant DB, and writes audit records.
-/// X-Tenant-ID required.
+/// Refused with 403 while the plan has steps gated by the approval policy and no approval, and
+/// with 409 once the installed package version no longer matches the plan's `from_version`.
+/// X-Tenant-ID required. `?atomic=true` rolls back every applied step when a safe step fails
+/// (see [`execute_migration_plan_atomic`]).
 pub async fn apply_migration_handler(
     TenantId(tenant_id_opt): TenantId,
     State(state): State<AppState>,
     Path(MigrationIdPath { migration_id }): Path<MigrationIdPath>,
+    Query(query): Query<ApplyMigrationQuery>,
 ) -> Result<impl axum::response::IntoResponse, AppError> {
     let tenant_id = tenant_id_opt
         .as_deref()
@@ -1259,14 +2348,48 @@ pub async fn apply_migration_handler(
         )));
     }
 
+    if row.zip_bytes.is_empty() {
+        return Err(AppError::BadRequest(format!(
+            "migration plan '{}' is a rollback — apply it with POST /config/package/{}/rollback?to_version={}&apply=true&migration_id={}",
+            migration_id, row.package_id, row.to_version, migration_id
+        )));
+    }
+
+    // The plan is a diff from `from_version`; another install since the preview invalidates it.
+    let installed = get_package(&state.pool, &row.package_id).await?;
+    let installed_version = installed.and_then(|p| p.semantic_version);
+    if installed_version != row.from_version {
+        expire_migration_plan(&state.pool, &migration_id).await?;
+        return Err(AppError::Conflict(format!(
+            "migration plan '{}' was computed from version '{}' but '{}' is installed — re-run preview",
+            migration_id,
+            row.from_version.as_deref().unwrap_or("none"),
+            installed_version.as_deref().unwrap_or("none")
+        )));
+    }
+
     let plan: MigrationPlan = serde_json::from_value(row.plan_json.clone())
         .map_err(|e| AppError::BadRequest(format!("corrupted migration plan: {}", e)))?;
+    let approval: Option<PlanApproval> = row
+        .approval_json
+        .clone()
+        .map(serde_json::from_value)
+        .transpose()
+        .map_err(|e| AppError::BadRequest(format!("corrupted plan approval: {}", e)))?;
+    check_approval(&ApprovalPolicy::from_env(), &plan, approval.as_ref())?;
+    let plan = match &approval {
+        Some(approval) => apply_overrides(plan, approval),
+        None => plan,
+    };
 
     let ctx = resolve_tenant_context(&state, Some(tenant_id), None, Some(&row.package_id)).await?;
     let config_pool = ctx.config_pool();
     let migration_pool = ctx.migration_pool();
     let package_cache_key = ctx.package_cache_key().to_string();
 
+    // The stored zip was verified at preview; check again so a changed key set still applies.
+    let integrity = SigningPolicy::from_env().verify(&row.zip_bytes)?;
+
     // Re-apply configs from the stored zip bytes
     let mut archive = ZipArchive::new(Cursor::new(row.zip_bytes.clone()))
         .map_err(|e| AppError::BadRequest(format!("stored zip corrupted: {}", e)))?;
@@ -1291,6 +2414,8 @@ pub async fn apply_migration_handler(
         .get("schema")
         .and_then(Value::as_str)
         .ok_or_else(|| AppError::BadRequest("manifest missing 'schema'".into()))?;
+    let data_migrations =
+        read_data_migrations_from_zip(&mut archive, &manifest_name, state.dialect.as_ref())?;
 
     let schemas_body = vec![serde_json::json!({ "id": DEFAULT_SCHEMA_ID, "name": schema_name })];
     let apply_order = config_apply_order();
@@ -1310,6 +2435,8 @@ pub async fn apply_migration_handler(
         replace_config(config_pool, kind, body, false, &row.package_id, None).await?;
     }
     upsert_package(config_pool, &row.package_id, &manifest_value).await?;
+    set_package_integrity(config_pool, &row.package_id, &integrity).await?;
+    record_package_config_versions(config_pool, &row.package_id).await?;
 
     // Atomically mark plan as applied (prevents double-apply under concurrent requests)
     let claimed = mark_migration_plan_applied(config_pool, &migration_id).await?;
@@ -1319,18 +2446,43 @@ pub async fn apply_migration_handler(
             migration_id
         )));
     }
+    expire_pending_migration_plans(&state.pool, &row.package_id, Some(&migration_id)).await?;
 
     // Execute the DDL plan with audit
-    let result = execute_migration_plan(
-        migration_pool,
+    let result = if query.atomic {
+        execute_migration_plan_atomic(
+            migration_pool,
+            config_pool,
+            &plan,
+            &migration_id,
+            &row.package_id,
+            tenant_id,
+            row.from_version.as_deref(),
+            &row.to_version,
+            state.dialect.as_ref(),
+        )
+        .await?
+    } else {
+        execute_migration_plan(
+            migration_pool,
+            config_pool,
+            &plan,
+            &migration_id,
+            &row.package_id,
+            tenant_id,
+            row.from_version.as_deref(),
+            &row.to_version,
+            state.dialect.as_ref(),
+        )
+        .await?
+    };
+    record_transitions(
         config_pool,
-        &plan,
-        &migration_id,
+        &package_cache_key,
         &row.package_id,
         tenant_id,
-        row.from_version.as_deref(),
-        &row.to_version,
-        state.dialect.as_ref(),
+        &plan,
+        &result,
     )
     .await?;
 
@@ -1355,6 +2507,8 @@ pub async fn apply_migration_handler(
     let new_model = resolve(&new_config)
         .map_err(AppError::Config)?
         .with_package_id(&row.package_id);
+    let transitions = list_column_transitions(config_pool, &package_cache_key).await?;
+    let new_model = attach_shadow_columns(new_model, &transitions);
     {
         let mut guard = state
             .model
@@ -1365,11 +2519,22 @@ pub async fn apply_migration_handler(
             .package_models
             .write()
             .map_err(|_| AppError::BadRequest("state lock".into()))?
-            .insert(package_cache_key, new_model);
+            .insert(package_cache_key, new_model.clone());
     }
     // Package config changed: drop the cached cross-package index so it rebuilds on the next include.
     crate::handlers::entity::invalidate_cross_package_index(&state);
 
+    let data_outcomes = run_data_migrations(
+        &state,
+        &[tenant_id.to_string()],
+        &row.package_id,
+        Some(&row.to_version),
+        schema_name,
+        &new_model,
+        &data_migrations,
+    )
+    .await;
+
     Ok((
         axum::http::StatusCode::OK,
         Json(crate::response::SuccessOne {
@@ -1383,6 +2548,240 @@ pub async fn apply_migration_handler(
                 "steps_skipped": result.skipped,
                 "warnings": result.warnings,
                 "skipped": result.skips,
+                "data_migrations": data_outcomes,
+            }),
+            meta: None,
+        }),
+    ))
+}
+
+// ─── Expand/contract transitions ────────────────────────────────────────────
+
+#[derive(Deserialize)]
+pub struct TransitionPath {
+    pub package_id: String,
+    /// `backfill` or `contract`.
+    pub phase: String,
+}
+
+#[derive(Deserialize)]
+pub struct TransitionQuery {
+    /// Rows per backfill batch. Defaults to 10000.
+    pub batch_size: Option<u64>,
+}
+
+/// The requested phase's plan for the tenant's target, with everything needed to run it.
+struct TransitionPlan {
+    ctx: crate::handlers::entity::TenantContext,
+    cache_key: String,
+    version: String,
+    plan: MigrationPlan,
+}
+
+async fn transition_plan(
+    state: &AppState,
+    tenant_id: Option<&str>,
+    package_id: &str,
+    phase: &str,
+    batch_size: Option<u64>,
+) -> Result<TransitionPlan, AppError> {
+    let tenant_id = tenant_id
+        .filter(|s| !s.is_empty())
+        .ok_or_else(|| AppError::BadRequest("X-Tenant-ID header is required".into()))?;
+    let batch_size = batch_size.unwrap_or(OnlineMigrationOptions::default().backfill_batch_size);
+    if batch_size == 0 {
+        return Err(AppError::BadRequest(
+            "batch_size must be greater than zero".into(),
+        ));
+    }
+    let pkg = get_package(&state.pool, package_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("package not found: {}", package_id)))?;
+    let ctx = resolve_tenant_context(state, Some(tenant_id), None, Some(package_id)).await?;
+    let cache_key = ctx.package_cache_key().to_string();
+    let config_pool = ctx.config_pool();
+    let config = load_from_pool(config_pool, package_id)
+        .await
+        .map_err(AppError::Config)?;
+    let rows = list_column_transitions(config_pool, &cache_key).await?;
+    let dialect = state.dialect.as_ref();
+    let plan = match phase {
+        "backfill" => backfill_plan(&rows, &config, batch_size, dialect)?,
+        "contract" => contract_plan(&rows, &config, batch_size, dialect)?,
+        other => {
+            return Err(AppError::BadRequest(format!(
+                "unknown transition phase '{}': expected backfill or contract",
+                other
+            )))
+        }
+    };
+    Ok(TransitionPlan {
+        ctx,
+        cache_key,
+        version: pkg.semantic_version.unwrap_or_default(),
+        plan,
+    })
+}
+
+/// GET /api/v1/config/packages/:package_id/transitions
+/// Column type changes in progress for the tenant's target (see [`crate::transition`]): phase,
+/// shadow column and rows backfilled so far. X-Tenant-ID required.
+pub async fn list_transitions_handler(
+    TenantId(tenant_id_opt): TenantId,
+    State(state): State<AppState>,
+    Path(PackageIdPath { package_id }): Path<PackageIdPath>,
+) -> Result<impl axum::response::IntoResponse, AppError> {
+    let tenant_id = tenant_id_opt
+        .as_deref()
+        .filter(|s| !s.is_empty())
+        .ok_or_else(|| AppError::BadRequest("X-Tenant-ID header is required".into()))?;
+    let ctx = resolve_tenant_context(&state, Some(tenant_id), None, Some(&package_id)).await?;
+    let rows = list_column_transitions(ctx.config_pool(), ctx.package_cache_key()).await?;
+    let data: Vec<Value> = rows
+        .iter()
+        .filter(|r| r.package_id == package_id)
+        .map(|r| {
+            json!({
+                "schema": r.schema_name,
+                "table": r.table_name,
+                "column": r.column_name,
+                "shadow_column": r.shadow_column,
+                "from_type": r.from_type,
+                "to_type": r.to_type,
+                "phase": r.phase,
+                "rows_backfilled": r.rows_backfilled,
+            })
+        })
+        .collect();
+    Ok((
+        axum::http::StatusCode::OK,
+        Json(crate::response::SuccessMany {
+            meta: crate::response::MetaCount {
+                count: data.len() as u64,
+            },
+            data,
+        }),
+    ))
+}
+
+/// GET /api/v1/config/packages/:package_id/transitions/:phase/preview
+/// The steps the `backfill` or `contract` phase would run, without running them.
+/// X-Tenant-ID required.
+pub async fn preview_transition_handler(
+    TenantId(tenant_id_opt): TenantId,
+    State(state): State<AppState>,
+    Path(TransitionPath { package_id, phase }): Path<TransitionPath>,
+    Query(query): Query<TransitionQuery>,
+) -> Result<impl axum::response::IntoResponse, AppError> {
+    let TransitionPlan { plan, .. } = transition_plan(
+        &state,
+        tenant_id_opt.as_deref(),
+        &package_id,
+        &phase,
+        query.batch_size,
+    )
+    .await?;
+    let summary = plan.summary();
+    Ok((
+        axum::http::StatusCode::OK,
+        Json(crate::response::SuccessOne {
+            data: json!({
+                "package_id": package_id,
+                "phase": phase,
+                "summary": {
+                    "total": summary.total,
+                    "safe": summary.safe,
+                    "best_effort": summary.best_effort,
+                    "warn_only": summary.warn_only,
+                },
+                "steps": plan.steps,
+            }),
+            meta: None,
+        }),
+    ))
+}
+
+/// POST /api/v1/config/packages/:package_id/transitions/:phase/apply
+/// Run the `backfill` or `contract` phase against the tenant's target. Backfill batches are
+/// audited in `_sys_migration_audit` as they go; a failed batch keeps the rows already copied,
+/// so the phase can simply be re-run. After a contract the entity model stops dual-writing.
+/// X-Tenant-ID required.
+pub async fn apply_transition_handler(
+    TenantId(tenant_id_opt): TenantId,
+    State(state): State<AppState>,
+    Path(TransitionPath { package_id, phase }): Path<TransitionPath>,
+    Query(query): Query<TransitionQuery>,
+) -> Result<impl axum::response::IntoResponse, AppError> {
+    let TransitionPlan {
+        ctx,
+        cache_key,
+        version,
+        plan,
+    } = transition_plan(
+        &state,
+        tenant_id_opt.as_deref(),
+        &package_id,
+        &phase,
+        query.batch_size,
+    )
+    .await?;
+    let tenant_id = tenant_id_opt.as_deref().unwrap_or_default();
+    let config_pool = ctx.config_pool();
+    let migration_id = Uuid::new_v4().to_string();
+
+    let result = execute_migration_plan(
+        ctx.migration_pool(),
+        config_pool,
+        &plan,
+        &migration_id,
+        &package_id,
+        tenant_id,
+        Some(&version),
+        &version,
+        state.dialect.as_ref(),
+    )
+    .await?;
+    record_transitions(
+        config_pool,
+        &cache_key,
+        &package_id,
+        tenant_id,
+        &plan,
+        &result,
+    )
+    .await?;
+
+    // Reload the cached model so dual writes follow the new phase.
+    let config = load_from_pool(config_pool, &package_id)
+        .await
+        .map_err(AppError::Config)?;
+    let transitions = list_column_transitions(config_pool, &cache_key).await?;
+    let model = attach_shadow_columns(
+        resolve(&config)
+            .map_err(AppError::Config)?
+            .with_package_id(&package_id),
+        &transitions,
+    );
+    state
+        .package_models
+        .write()
+        .map_err(|_| AppError::BadRequest("state lock".into()))?
+        .insert(cache_key, model);
+
+    let rows_backfilled: u64 = result.completed_steps.iter().map(|(_, n)| n).sum();
+    Ok((
+        axum::http::StatusCode::OK,
+        Json(crate::response::SuccessOne {
+            data: json!({
+                "migration_id": migration_id,
+                "package_id": package_id,
+                "phase": phase,
+                "steps_applied": result.applied,
+                "steps_warned": result.warned,
+                "steps_skipped": result.skipped,
+                "rows_backfilled": rows_backfilled,
+                "warnings": result.warnings,
+                "skipped": result.skips,
             }),
             meta: None,
         }),
@@ -17,38 +17,61 @@
 #[cfg(feature = "mcp")]
 pub mod mcp;
 
+pub mod approval;
 pub mod authrs;
 pub mod case;
 pub mod config;
+pub mod data_migration;
 pub mod db;
+pub mod dependency;
+pub mod diagnostics;
+pub mod drift;
 pub mod error;
 pub mod events;
 pub mod extensible_fields;
 pub mod extractors;
 pub mod handlers;
+pub mod lint;
+pub mod metrics;
 pub mod migration;
 pub mod openapi;
+pub mod preflight;
+pub mod replica;
 pub mod response;
+pub mod reverse;
 pub mod routes;
 pub mod service;
+pub mod signing;
 pub mod sql;
 pub mod state;
 pub mod storage;
 pub mod store;
+pub mod telemetry;
 pub mod tenant;
+pub mod tenant_pool;
+pub mod transition;
 
 pub use config::{load_from_pool, resolve, FullConfig, ResolvedEntity, ResolvedModel};
 pub use db::{introspect, DbSnapshot};
+pub use drift::{detect_drift, drift_repair_plan, DriftItem, DriftKind, DriftReport};
 pub use error::{AppError, ConfigError};
 pub use migration::{
-    apply_migrations, compute_migration_plan, execute_migration_plan, reconcile_step,
-    MigrationOperation, MigrationPlan, MigrationRisk, MigrationSafety, MigrationStep,
-    MigrationSummary, StepDecision,
+    apply_migrations, compensating_step, compute_migration_plan, execute_migration_plan,
+    execute_migration_plan_atomic, expected_lock_level, reconcile_step, to_online_plan, LockLevel,
+    MigrationMode, MigrationOperation, MigrationPlan, MigrationRisk, MigrationSafety,
+    MigrationStep, MigrationSummary, OnlineMigrationOptions, StepDecision, StepExecution,
 };
 pub use response::{error_body, success_many, success_one};
+pub use reverse::{
+    generate_package, reverse_engineer, write_package_zip, GeneratedPackage, ReverseOptions,
+};
 pub use routes::{common_routes, common_routes_with_ready, config_routes, entity_routes};
 pub use service::{CrudService, TenantExecutor};
 pub use state::AppState;
 pub use storage::{init_storage_provider, StorageProvider};
 pub use store::{create_pool, ensure_database_exists, ensure_sys_tables, DEFAULT_PACKAGE_ID};
 pub use tenant::{load_registry_from_pool, TenantEntry, TenantRegistry, TenantStrategy};
+pub use transition::{
+    attach_shadow_columns, backfill_plan, contract_plan, to_expand_contract_plan, ColumnTransition,
+    TransitionPhase, TypeChangeStrategy,
+};
@@ -0,0 +1,389 @@
+//! Offline package checks, for CI.
+//!
+//! [`lint_package`] reads a package the way `POST /config/package` does and runs the checks
+//! install would run, without a database:
+//!
+//! - `package`: the ZIP and `manifest.json` can be read and every config file parses.
+//! - `validate`: [`validate`] (schema, type and reference checks).
+//! - `resolve`: [`resolve`] builds the runtime model.
+//! - `type_support`: every column type against the target dialect. `Unsupported` types are
+//!   errors, `Degraded` ones warnings.
+//! - `plan`: with a previous version to compare against, [`compute_migration_plan`] from it to
+//!   this one. Steps that carry a risk or only warn are reported as warnings.
+//!
+//! The `architect` binary (feature `cli`) wraps this: `architect lint <package> [--against <old>]
+//! [--dialect <name>] [--json] [--deny-warnings]`.
+
+use std::collections::HashMap;
+use std::io::{Cursor, Write};
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::config::{resolve, validate, FullConfig};
+use crate::db::{parse_canonical, Dialect, TypeSupport};
+use crate::error::AppError;
+use crate::handlers::package::read_package_config;
+use crate::migration::{compute_migration_plan, MigrationPlan, MigrationRisk, MigrationSafety};
+
+/// One problem found in a package.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LintFinding {
+    /// Which check reported it (`package`, `validate`, `resolve`, `type_support`, `plan`).
+    pub check: &'static str,
+    /// `table.column`, table or other object the finding is about, when there is one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub object: Option<String>,
+    pub message: String,
+}
+
+impl LintFinding {
+    fn new(check: &'static str, object: Option<String>, message: impl Into<String>) -> Self {
+        Self {
+            check,
+            object,
+            message: message.into(),
+        }
+    }
+}
+
+/// Outcome of [`lint_package`].
+#[derive(Debug, Clone, Serialize)]
+pub struct LintReport {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub package_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    pub dialect: &'static str,
+    /// Version of the package the plan starts from, when compared against one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub against_version: Option<String>,
+    pub errors: Vec<LintFinding>,
+    pub warnings: Vec<LintFinding>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub plan: Option<MigrationPlan>,
+}
+
+impl LintReport {
+    /// Process exit code: 0 when clean, 1 on errors (or on warnings with `deny_warnings`).
+    pub fn exit_code(&self, deny_warnings: bool) -> i32 {
+        if !self.errors.is_empty() || (deny_warnings && !self.warnings.is_empty()) {
+            1
+        } else {
+            0
+        }
+    }
+
+    /// Plain-text report, one finding per line.
+    pub fn render_human(&self) -> String {
+        let mut out = String::new();
+        let package = match (&self.package_id, &self.version) {
+            (Some(id), Some(v)) => format!("{} {}", id, v),
+            (Some(id), None) => id.clone(),
+            _ => "package".to_string(),
+        };
+        out.push_str(&format!("{} ({})\n", package, self.dialect));
+        if let Some(plan) = &self.plan {
+            let s = plan.summary();
+            out.push_str(&format!(
+                "migration from {}: {} steps ({} safe, {} best effort, {} warn only)\n",
+                self.against_version
+                    .as_deref()
+                    .unwrap_or("previous version"),
+                s.total,
+                s.safe,
+                s.best_effort,
+                s.warn_only
+            ));
+        }
+        for (level, findings) in [("error", &self.errors), ("warning", &self.warnings)] {
+            for f in findings {
+                match &f.object {
+                    Some(object) => out.push_str(&format!(
+                        "{}[{}] {}: {}\n",
+                        level, f.check, object, f.message
+                    )),
+                    None => out.push_str(&format!("{}[{}] {}\n", level, f.check, f.message)),
+                }
+            }
+        }
+        out.push_str(&format!(
+            "{} error(s), {} warning(s)\n",
+            self.errors.len(),
+            self.warnings.len()
+        ));
+        out
+    }
+}
+
+/// Read a package from a directory (zipped in memory) or a ZIP file.
+pub fn read_package_path(path: &Path) -> Result<Vec<u8>, AppError> {
+    let io = |e: std::io::Error| AppError::BadRequest(format!("{}: {}", path.display(), e));
+    if !path.is_dir() {
+        return std::fs::read(path).map_err(io);
+    }
+    let mut files = Vec::new();
+    collect_files(path, path, &mut files).map_err(io)?;
+    files.sort();
+    let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+    for name in files {
+        let bytes = std::fs::read(path.join(&name)).map_err(io)?;
+        zip.start_file(name, zip::write::SimpleFileOptions::default())
+            .map_err(|e| AppError::BadRequest(e.to_string()))?;
+        zip.write_all(&bytes).map_err(io)?;
+    }
+    let out = zip
+        .finish()
+        .map_err(|e| AppError::BadRequest(e.to_string()))?;
+    Ok(out.into_inner())
+}
+
+/// Files below `dir`, as `/`-separated paths relative to `root`.
+fn collect_files(root: &Path, dir: &Path, out: &mut Vec<String>) -> std::io::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_files(root, &path, out)?;
+        } else if let Ok(rel) = path.strip_prefix(root) {
+            let parts: Vec<String> = rel
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy().into_owned())
+                .collect();
+            out.push(parts.join("/"));
+        }
+    }
+    Ok(())
+}
+
+/// Check a package ZIP for `dialect`, and its upgrade from `against` when given.
+pub fn lint_package(zip_bytes: &[u8], dialect: &dyn Dialect, against: Option<&[u8]>) -> LintReport {
+    let mut report = LintReport {
+        package_id: None,
+        version: None,
+        dialect: dialect.name(),
+        against_version: None,
+        errors: Vec::new(),
+        warnings: Vec::new(),
+        plan: None,
+    };
+    let (manifest, config) = match read_package_config(zip_bytes) {
+        Ok(read) => read,
+        Err(e) => {
+            report
+                .errors
+                .push(LintFinding::new("package", None, error_message(e)));
+            return report;
+        }
+    };
+    let text = |key: &str| manifest.get(key).and_then(|v| v.as_str()).map(String::from);
+    report.package_id = text("id");
+    report.version = text("version");
+
+    if let Err(e) = validate(&config) {
+        report
+            .errors
+            .push(LintFinding::new("validate", None, e.to_string()));
+    } else if let Err(e) = resolve(&config) {
+        report
+            .errors
+            .push(LintFinding::new("resolve", None, e.to_string()));
+    }
+    check_type_support(&config, dialect, &mut report);
+
+    if let Some(against) = against {
+        match read_package_config(against) {
+            Ok((old_manifest, old_config)) => {
+                report.against_version = old_manifest
+                    .get("version")
+                    .and_then(|v| v.as_str())
+                    .map(String::from);
+                check_plan(&old_config, &config, dialect, &mut report);
+            }
+            Err(e) => report.errors.push(LintFinding::new(
+                "package",
+                None,
+                format!("previous version: {}", error_message(e)),
+            )),
+        }
+    }
+    report
+}
+
+fn check_type_support(config: &FullConfig, dialect: &dyn Dialect, report: &mut LintReport) {
+    let tables: HashMap<&str, &str> = config
+        .tables
+        .iter()
+        .map(|t| (t.id.as_str(), t.name.as_str()))
+        .collect();
+    for column in &config.columns {
+        let object = Some(format!(
+            "{}.{}",
+            tables
+                .get(column.table_id.as_str())
+                .copied()
+                .unwrap_or(column.table_id.as_str()),
+            column.name
+        ));
+        let canonical = parse_canonical(&column.type_);
+        match dialect.type_support(&canonical) {
+            TypeSupport::Unsupported => report.errors.push(LintFinding::new(
+                "type_support",
+                object,
+                format!(
+                    "type {:?} is not supported on {}",
+                    canonical,
+                    dialect.name()
+                ),
+            )),
+            TypeSupport::Degraded(ddl, lost) => report.warnings.push(LintFinding::new(
+                "type_support",
+                object,
+                format!("stored as {} on {}: {}", ddl, dialect.name(), lost),
+            )),
+            TypeSupport::Native(_) | TypeSupport::Emulated(_) => {}
+        }
+    }
+}
+
+fn check_plan(old: &FullConfig, new: &FullConfig, dialect: &dyn Dialect, report: &mut LintReport) {
+    let plan = match compute_migration_plan(old, new, None, None, dialect, &HashMap::new()) {
+        Ok(plan) => plan,
+        Err(e) => {
+            report
+                .errors
+                .push(LintFinding::new("plan", None, error_message(e)));
+            return;
+        }
+    };
+    for step in &plan.steps {
+        let risky = !matches!(step.risk, MigrationRisk::None);
+        if !risky && !matches!(step.safety, MigrationSafety::WarnOnly) {
+            continue;
+        }
+        let object = match &step.table {
+            Some(table) if *table != step.object => format!("{}.{}", table, step.object),
+            _ => step.object.clone(),
+        };
+        let mut message = format!("step {}: {}", step.step, step.description);
+        if let Some(detail) = &step.risk_detail {
+            message.push_str(&format!(" ({})", detail));
+        }
+        report
+            .warnings
+            .push(LintFinding::new("plan", Some(object), message));
+    }
+    report.plan = Some(plan);
+}
+
+/// `AppError` messages without the HTTP category prefix.
+fn error_message(e: AppError) -> String {
+    match e {
+        AppError::BadRequest(m) | AppError::Validation(m) | AppError::NotFound(m) => m,
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod lint_tests {
+    use super::*;
+    use crate::db::active_dialect;
+    use serde_json::{json, Value};
+
+    fn package(version: &str, columns: Value) -> Vec<u8> {
+        let files = [
+            (
+                "manifest.json",
+                json!({"id": "crm", "name": "CRM", "version": version, "schema": "crm"}),
+            ),
+            (
+                "tables.json",
+                json!([{"id": "contacts", "name": "contacts", "primary_key": "id"}]),
+            ),
+            ("columns.json", columns),
+        ];
+        let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, body) in files {
+            zip.start_file(name, zip::write::SimpleFileOptions::default())
+                .unwrap();
+            zip.write_all(body.to_string().as_bytes()).unwrap();
+        }
+        zip.finish().unwrap().into_inner()
+    }
+
+    fn columns(extra: Value) -> Value {
+        let mut cols = vec![
+            json!({"id": "contacts.id", "table_id": "contacts", "name": "id", "type": "uuid"}),
+        ];
+        if !extra.is_null() {
+            cols.push(extra);
+        }
+        Value::Array(cols)
+    }
+
+    #[test]
+    fn clean_packages_exit_zero() {
+        let report = lint_package(
+            &package("1.0.0", columns(Value::Null)),
+            active_dialect().as_ref(),
+            None,
+        );
+        assert!(report.errors.is_empty(), "{:?}", report.errors);
+        assert_eq!(report.package_id.as_deref(), Some("crm"));
+        assert_eq!(report.exit_code(true), 0);
+        assert!(report.render_human().contains("0 error(s)"));
+    }
+
+    #[test]
+    fn broken_packages_report_errors() {
+        let report = lint_package(b"not a zip", active_dialect().as_ref(), None);
+        assert_eq!(report.errors[0].check, "package");
+        assert_eq!(report.exit_code(false), 1);
+
+        let dangling =
+            columns(json!({"id": "x.name", "table_id": "missing", "name": "name", "type": "text"}));
+        let report = lint_package(&package("1.0.0", dangling), active_dialect().as_ref(), None);
+        assert_eq!(report.errors[0].check, "validate");
+    }
+
+    #[test]
+    fn upgrades_report_risky_steps() {
+        let email = json!({
+            "id": "contacts.email", "table_id": "contacts", "name": "email", "type": "text"
+        });
+        let old = package("1.0.0", columns(email));
+        let new = package("1.1.0", columns(Value::Null));
+        let report = lint_package(&new, active_dialect().as_ref(), Some(&old));
+        assert!(report.errors.is_empty(), "{:?}", report.errors);
+        assert_eq!(report.against_version.as_deref(), Some("1.0.0"));
+        assert_eq!(report.warnings[0].check, "plan");
+        assert_eq!(report.warnings[0].object.as_deref(), Some("contacts.email"));
+        assert_eq!(report.exit_code(false), 0);
+        assert_eq!(report.exit_code(true), 1);
+    }
+
+    #[test]
+    fn directories_are_zipped_with_relative_names() {
+        let dir = std::env::temp_dir().join(format!("architect-lint-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(dir.join("columns")).unwrap();
+        std::fs::write(
+            dir.join("manifest.json"),
+            r#"{"id":"crm","name":"CRM","version":"1.0.0","schema":"crm"}"#,
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("tables.json"),
+            r#"[{"id":"contacts","name":"contacts","primary_key":"id"}]"#,
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("columns").join("contacts.json"),
+            r#"[{"id":"contacts.id","table_id":"contacts","name":"id","type":"uuid"}]"#,
+        )
+        .unwrap();
+        let zip = read_package_path(&dir).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        let (_, config) = read_package_config(&zip).unwrap();
+        assert_eq!(config.columns.len(), 1);
+        assert_eq!(config.tables[0].schema_id.as_deref(), Some("default"));
+    }
+}
@@ -0,0 +1,331 @@
+//! Prometheus metrics for the request pipeline, served in the text format at `GET /metrics`
+//! (see [`crate::routes::common_routes_with_ready`]).
+//!
+//! | Metric | Labels |
+//! |---|---|
+//! | `architect_http_requests_total`, `architect_http_request_duration_seconds` | `entity`, `operation`, `status` |
+//! | `architect_sql_query_duration_seconds` | `method` (the `CrudService` method) |
+//! | `architect_migration_steps_total` | `operation`, `status` (as audited in `_sys_migration_audit`) |
+//! | `architect_event_publish_total` | `result`: `accepted`, `rejected` or `failed` |
+//! | `architect_authrs_request_duration_seconds` | `result`: `allowed`, `denied` or `error` |
+//! | `architect_storage_operation_duration_seconds` | `provider`, `operation`, `result` |
+//! | `architect_db_pool_connections` | `pool` (`central` or `tenant`), `tenant`, `state`: `open`, `idle` or `max` |
+//!
+//! HTTP metrics are recorded by [`track_requests`], a route layer on the entity and config routers.
+//! `entity` is the [`EntityLabel`] a handler put on its response, so only entities of a loaded
+//! model are labelled; everything else (including error responses) is `-`.
+//! `operation` names the entity operation (`list`, `read`, `create`, …); other routes use their
+//! method and route pattern. Pool gauges are read when `/metrics` is scraped.
+
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+use axum::extract::{MatchedPath, Request};
+use axum::middleware::Next;
+use axum::response::Response;
+use axum::Extension;
+use prometheus::{
+    Encoder, HistogramOpts, HistogramTimer, HistogramVec, IntCounterVec, IntGaugeVec, Opts,
+    Registry, TextEncoder,
+};
+
+use crate::config::ResolvedEntity;
+use crate::state::AppState;
+
+struct Metrics {
+    registry: Registry,
+    http_requests: IntCounterVec,
+    http_duration: HistogramVec,
+    sql_duration: HistogramVec,
+    migration_steps: IntCounterVec,
+    event_publish: IntCounterVec,
+    authrs_duration: HistogramVec,
+    storage_duration: HistogramVec,
+    pool_connections: IntGaugeVec,
+}
+
+impl Metrics {
+    fn new() -> Self {
+        fn counter(registry: &Registry, name: &str, help: &str, labels: &[&str]) -> IntCounterVec {
+            let metric = IntCounterVec::new(Opts::new(name, help), labels).expect("valid metric");
+            registry
+                .register(Box::new(metric.clone()))
+                .expect("metric registered once");
+            metric
+        }
+        fn histogram(registry: &Registry, name: &str, help: &str, labels: &[&str]) -> HistogramVec {
+            let metric =
+                HistogramVec::new(HistogramOpts::new(name, help), labels).expect("valid metric");
+            registry
+                .register(Box::new(metric.clone()))
+                .expect("metric registered once");
+            metric
+        }
+        let registry = Registry::new();
+        let pool_connections = IntGaugeVec::new(
+            Opts::new(
+                "architect_db_pool_connections",
+                "Connections of the central and tenant pools",
+            ),
+            &["pool", "tenant", "state"],
+        )
+        .expect("valid metric");
+        registry
+            .register(Box::new(pool_connections.clone()))
+            .expect("metric registered once");
+        Self {
+            http_requests: counter(
+                &registry,
+                "architect_http_requests_total",
+                "HTTP requests handled",
+                &["entity", "operation", "status"],
+            ),
+            http_duration: histogram(
+                &registry,
+                "architect_http_request_duration_seconds",
+                "HTTP request latency",
+                &["entity", "operation", "status"],
+            ),
+            sql_duration: histogram(
+                &registry,
+                "architect_sql_query_duration_seconds",
+                "Time spent in CrudService queries",
+                &["method"],
+            ),
+            migration_steps: counter(
+                &registry,
+                "architect_migration_steps_total",
+                "Migration step outcomes",
+                &["operation", "status"],
+            ),
+            event_publish: counter(
+                &registry,
+                "architect_event_publish_total",
+                "Decision-hub event publishes",
+                &["result"],
+            ),
+            authrs_duration: histogram(
+                &registry,
+                "architect_authrs_request_duration_seconds",
+                "Authrs permission check latency",
+                &["result"],
+            ),
+            storage_duration: histogram(
+                &registry,
+                "architect_storage_operation_duration_seconds",
+                "Storage provider call latency",
+                &["provider", "operation", "result"],
+            ),
+            pool_connections,
+            registry,
+        }
+    }
+}
+
+fn metrics() -> &'static Metrics {
+    static METRICS: OnceLock<Metrics> = OnceLock::new();
+    METRICS.get_or_init(Metrics::new)
+}
+
+/// Time a `CrudService` method; the duration is recorded when the timer is dropped.
+pub fn sql_timer(method: &str) -> HistogramTimer {
+    metrics()
+        .sql_duration
+        .with_label_values(&[method])
+        .start_timer()
+}
+
+/// Count a migration step outcome (`applied`, `skipped`, `failed`, …).
+pub fn record_migration_step(operation: &str, status: &str) {
+    metrics()
+        .migration_steps
+        .with_label_values(&[operation, status])
+        .inc();
+}
+
+/// Count a decision-hub publish: `accepted`, `rejected` (non-2xx) or `failed` (no response).
+pub fn record_event_publish(result: &str) {
+    metrics().event_publish.with_label_values(&[result]).inc();
+}
+
+/// Record an authrs permission check: `allowed`, `denied` or `error`.
+pub fn observe_authrs(result: &str, elapsed: Duration) {
+    metrics()
+        .authrs_duration
+        .with_label_values(&[result])
+        .observe(elapsed.as_secs_f64());
+}
+
+/// Record a storage provider call.
+pub fn observe_storage(provider: &str, operation: &str, ok: bool, elapsed: Duration) {
+    let result = if ok { "ok" } else { "error" };
+    metrics()
+        .storage_duration
+        .with_label_values(&[provider, operation, result])
+        .observe(elapsed.as_secs_f64());
+}
+
+/// Response extension naming the entity a handler served, for the `entity` label.
+#[derive(Clone, Debug)]
+pub struct EntityLabel(pub String);
+
+/// The [`EntityLabel`] of a resolved entity, to return among a handler's response parts.
+pub fn entity_label(entity: &ResolvedEntity) -> Extension<EntityLabel> {
+    Extension(EntityLabel(entity.path_segment.clone()))
+}
+
+/// Route layer recording `architect_http_requests_total` and
+/// `architect_http_request_duration_seconds`. Install with `Router::route_layer` so the matched
+/// route is known.
+pub async fn track_requests(matched: Option<MatchedPath>, req: Request, next: Next) -> Response {
+    let method = req.method().clone();
+    let start = Instant::now();
+    let response = next.run(req).await;
+    let status = response.status();
+    let route = matched.as_ref().map_or("", |m| m.as_str());
+    // Set by handlers once the entity resolved, so URLs cannot grow the label set.
+    let entity = response
+        .extensions()
+        .get::<EntityLabel>()
+        .map_or("-", |l| l.0.as_str());
+    let operation = operation_label(method.as_str(), route);
+    let status = status.as_str();
+    let m = metrics();
+    m.http_requests
+        .with_label_values(&[entity, &operation, status])
+        .inc();
+    m.http_duration
+        .with_label_values(&[entity, &operation, status])
+        .observe(start.elapsed().as_secs_f64());
+    response
+}
+
+/// The entity operation a route serves, else `"<METHOD> <route>"`.
+fn operation_label(method: &str, route: &str) -> String {
+    const ENTITY: &str = "/:path_segment";
+    let Some(idx) = route.find(ENTITY) else {
+        return format!("{} {}", method, route);
+    };
+    let op = match (method, &route[idx + ENTITY.len()..]) {
+        ("GET", "") => "list",
+        ("POST", "") => "create",
+        ("GET", "/:id") => "read",
+        ("PATCH", "/:id") => "update",
+        ("DELETE", "/:id") => "delete",
+        ("POST", "/bulk") => "bulk_create",
+        ("PATCH", "/bulk") => "bulk_update",
+        ("DELETE", "/bulk") => "bulk_delete",
+        ("POST", "/graph") => "create_graph",
+        ("POST", "/:id/archive") => "archive",
+        ("POST", "/:id/unarchive") => "unarchive",
+        ("GET", "/:id/history") => "list_history",
+        ("GET", "/:id/history/:version") => "read_history",
+        _ => return format!("{} {}", method, route),
+    };
+    op.to_string()
+}
+
+/// All metrics in the Prometheus text format, with pool gauges read from `state`.
+pub fn render(state: &AppState) -> String {
+    let m = metrics();
+    m.pool_connections.reset();
+    let set = |pool: &str, tenant: &str, open: u32, idle: usize, max: u32| {
+        for (label, value) in [
+            ("open", open as i64),
+            ("idle", idle as i64),
+            ("max", max as i64),
+        ] {
+            m.pool_connections
+                .with_label_values(&[pool, tenant, label])
+                .set(value);
+        }
+    };
+    set(
+        "central",
+        "",
+        state.pool.size(),
+        state.pool.num_idle(),
+        state.pool.options().get_max_connections(),
+    );
+    if let Ok(stats) = state.tenant_pools.stats() {
+        for p in stats.pools {
+            set("tenant", &p.tenant_id, p.size, p.idle, p.max_connections);
+        }
+    }
+    let mut buf = Vec::new();
+    if let Err(e) = TextEncoder::new().encode(&m.registry.gather(), &mut buf) {
+        tracing::warn!(error = %e, "encoding metrics failed");
+    }
+    String::from_utf8(buf).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod metrics_tests {
+    use super::*;
+
+    #[test]
+    fn entity_routes_map_to_operations() {
+        assert_eq!(operation_label("GET", "/api/v1/:path_segment"), "list");
+        assert_eq!(
+            operation_label("PATCH", "/api/v1/package/:package_id/:path_segment/bulk"),
+            "bulk_update"
+        );
+        assert_eq!(
+            operation_label("GET", "/api/v1/:path_segment/:id/history/:version"),
+            "read_history"
+        );
+        assert_eq!(
+            operation_label("POST", "/api/v1/config/package"),
+            "POST /api/v1/config/package"
+        );
+    }
+
+    #[test]
+    fn recorded_metrics_are_exported() {
+        record_migration_step("create_table", "applied");
+        record_event_publish("failed");
+        drop(sql_timer("list"));
+        let mut buf = Vec::new();
+        TextEncoder::new()
+            .encode(&metrics().registry.gather(), &mut buf)
+            .unwrap();
+        let text = String::from_utf8(buf).unwrap();
+        assert!(text.contains(
+            "architect_migration_steps_total{operation=\"create_table\",status=\"applied\"}"
+        ));
+        assert!(text.contains("architect_event_publish_total{result=\"failed\"}"));
+        assert!(text.contains("architect_sql_query_duration_seconds_count{method=\"list\"}"));
+    }
+    #[tokio::test]
+    async fn entity_label_comes_from_the_handler() {
+        use axum::response::IntoResponse;
+        use tower::Service;
+
+        async fn handler(axum::extract::Path(segment): axum::extract::Path<String>) -> Response {
+            if segment != "metric-widgets" {
+                return axum::http::StatusCode::NOT_FOUND.into_response();
+            }
+            let label = Extension(EntityLabel(segment));
+            (label, "ok").into_response()
+        }
+        let mut app = axum::Router::new()
+            .route("/metric-test/:path_segment", axum::routing::get(handler))
+            .route_layer(axum::middleware::from_fn(track_requests));
+        for path in ["/metric-test/metric-widgets", "/metric-test/unknown-1"] {
+            let req = Request::builder()
+                .uri(path)
+                .body(axum::body::Body::empty())
+                .unwrap();
+            app.call(req).await.unwrap();
+        }
+        let count = |entity: &str, status: &str| {
+            metrics()
+                .http_requests
+                .with_label_values(&[entity, "list", status])
+                .get()
+        };
+        assert_eq!(count("metric-widgets", "200"), 1);
+        assert_eq!(count("-", "404"), 1);
+        assert_eq!(count("unknown-1", "404"), 0);
+    }
+}
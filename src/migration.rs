@@ -4,19 +4,64 @@
 use crate::config::types::*;
 use crate::config::{validate, FullConfig};
 use crate::db::parse_canonical;
-use crate::db::pool::Pool;
+use crate::db::pool::{Connection, Pool};
 use crate::db::{ColumnFacts, DbSnapshot, Dialect};
 use crate::error::AppError;
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 
-fn quote(s: &str) -> String {
+pub(crate) fn quote(s: &str) -> String {
     format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
 }
 
 /// Name of the column added to app tables when RLS is enabled. Used by migration and CRUD.
 pub const RLS_TENANT_COLUMN: &str = "tenant_id";
 
+/// One statement of a fresh install, in execution order. See [`render_migrations`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DdlStatement {
+    pub sql: String,
+    /// Failures are ignored: the object may already exist (enum types, constraints) or be
+    /// optional (comments, secondary indexes).
+    pub best_effort: bool,
+}
+
+impl DdlStatement {
+    fn required(sql: String) -> Self {
+        Self {
+            sql,
+            best_effort: false,
+        }
+    }
+
+    fn best_effort(sql: String) -> Self {
+        Self {
+            sql,
+            best_effort: true,
+        }
+    }
+
+    async fn execute(&self, pool: &Pool) -> Result<(), AppError> {
+        let result = sqlx::query(&self.sql).execute(pool).await;
+        if !self.best_effort {
+            result?;
+        }
+        Ok(())
+    }
+}
+
+/// What [`apply_migrations`] does, step by step.
+enum InstallAction {
+    Run(DdlStatement),
+    /// Add the columns a table that existed before the install is missing
+    /// (see [`add_missing_columns`]).
+    AddMissingColumns {
+        schema: String,
+        table: String,
+        columns: Vec<(String, String)>,
+    },
+}
+
 /// Ensure every table in `config` has the RLS tenant column, row-level security enabled, and the
 /// four tenant-isolation policies. Tables flagged `global` instead get asymmetric policies — every
 /// tenant may read (`SELECT USING (true)`) but only the Platform Admin tenant may write — so they
@@ -35,6 +80,19 @@ pub async fn apply_rls_to_tables(
         tracing::warn!(dialect = %dialect.name(), "RLS requested but not supported by this dialect; skipping");
         return Ok(());
     }
+    for stmt in rls_statements(config, schema_override, rls_tenant_column) {
+        stmt.execute(pool).await?;
+    }
+    Ok(())
+}
+
+/// The statements [`apply_rls_to_tables`] runs, in order.
+fn rls_statements(
+    config: &FullConfig,
+    schema_override: Option<&str>,
+    rls_tenant_column: &str,
+) -> Vec<DdlStatement> {
+    let mut out = Vec::new();
     let default_sid = config.schemas.first().map(|s| s.id.as_str()).unwrap_or("");
     let platform_id = crate::tenant::platform_tenant_id();
     let schemas_by_id: HashMap<_, _> = config.schemas.iter().map(|s| (s.id.as_str(), s)).collect();
@@ -64,10 +122,10 @@ pub async fn apply_rls_to_tables(
                 full_name,
                 quote(col)
             );
-            sqlx::query(&add_col).execute(pool).await?;
+            out.push(DdlStatement::required(add_col));
         }
         let enable_rls = format!("ALTER TABLE {} ENABLE ROW LEVEL SECURITY", full_name);
-        sqlx::query(&enable_rls).execute(pool).await?;
+        out.push(DdlStatement::required(enable_rls));
         let q_col = quote(col);
         let setting = "current_setting('app.tenant_id', true)";
         // Tenant-isolated condition: a row belongs to the caller's tenant.
@@ -111,7 +169,7 @@ pub async fn apply_rls_to_tables(
                 quote(&policy_name),
                 full_name
             );
-            let _ = sqlx::query(&drop_sql).execute(pool).await;
+            out.push(DdlStatement::best_effort(drop_sql));
             let create_sql = match (using_cond, with_check) {
                 (Some(u), Some(w)) => format!(
                     "CREATE POLICY {} ON {} FOR {} USING ( {} ) WITH CHECK ( {} )",
@@ -137,10 +195,10 @@ pub async fn apply_rls_to_tables(
                 ),
                 (None, None) => continue,
             };
-            sqlx::query(&create_sql).execute(pool).await?;
+            out.push(DdlStatement::required(create_sql));
         }
     }
-    Ok(())
+    out
 }
 
 /// Add any of `desired` columns that the table does not have yet.
@@ -232,6 +290,79 @@ pub async fn apply_migrations(
     cross_package_configs: &HashMap<String, FullConfig>,
 ) -> Result<(), AppError> {
     validate(config)?;
+    // Which tables already exist, and with which columns? `CREATE TABLE IF NOT EXISTS` silently
+    // leaves a pre-existing table alone, so without this a table created by an older version of
+    // the package would never gain the columns added since. Taken before any DDL runs, so
+    // `has_table` means "existed before this call".
+    let target_schemas: Vec<String> = match schema_override {
+        Some(o) => vec![o.to_string()],
+        None => config.schemas.iter().map(|s| s.name.clone()).collect(),
+    };
+    let pre_snapshot = crate::db::introspect(pool, dialect, &target_schemas).await;
+    if rls_tenant_column.is_some() && !dialect.supports_rls() {
+        tracing::warn!(dialect = %dialect.name(), "RLS requested but not supported by this dialect; skipping");
+    }
+    let actions = install_actions(
+        config,
+        schema_override,
+        rls_tenant_column,
+        dialect,
+        cross_package_configs,
+        &pre_snapshot,
+    )?;
+    for action in actions {
+        match action {
+            InstallAction::Run(stmt) => stmt.execute(pool).await?,
+            InstallAction::AddMissingColumns {
+                schema,
+                table,
+                columns,
+            } => add_missing_columns(pool, &pre_snapshot, dialect, &schema, &table, &columns).await,
+        }
+    }
+    Ok(())
+}
+
+/// Render the DDL [`apply_migrations`] runs for `config` on an empty database, without executing
+/// anything. Works for any [`Dialect`], not only the one the pool was built for.
+pub fn render_migrations(
+    config: &FullConfig,
+    schema_override: Option<&str>,
+    rls_tenant_column: Option<&str>,
+    dialect: &dyn Dialect,
+    cross_package_configs: &HashMap<String, FullConfig>,
+) -> Result<Vec<DdlStatement>, AppError> {
+    validate(config)?;
+    let actions = install_actions(
+        config,
+        schema_override,
+        rls_tenant_column,
+        dialect,
+        cross_package_configs,
+        &DbSnapshot::default(),
+    )?;
+    Ok(actions
+        .into_iter()
+        .filter_map(|a| match a {
+            InstallAction::Run(stmt) => Some(stmt),
+            InstallAction::AddMissingColumns { .. } => None,
+        })
+        .collect())
+}
+
+/// The steps of [`apply_migrations`]. `pre_snapshot` is the database before the install; tables
+/// it already has get an [`InstallAction::AddMissingColumns`] after their `CREATE TABLE`.
+fn install_actions(
+    config: &FullConfig,
+    schema_override: Option<&str>,
+    rls_tenant_column: Option<&str>,
+    dialect: &dyn Dialect,
+    cross_package_configs: &HashMap<String, FullConfig>,
+    pre_snapshot: &DbSnapshot,
+) -> Result<Vec<InstallAction>, AppError> {
+    let mut actions = Vec::new();
+    let run = |sql: String| InstallAction::Run(DdlStatement::required(sql));
+    let try_run = |sql: String| InstallAction::Run(DdlStatement::best_effort(sql));
     let default_sid = config
         .schemas
         .first()
@@ -245,9 +376,7 @@ pub async fn apply_migrations(
     if dialect.supports_schemas() {
         if let Some(s) = schema_override {
             let name = quote(s);
-            sqlx::query(&format!("CREATE SCHEMA IF NOT EXISTS {}", name))
-                .execute(pool)
-                .await?;
+            actions.push(run(format!("CREATE SCHEMA IF NOT EXISTS {}", name)));
         }
     }
 
@@ -267,11 +396,9 @@ pub async fn apply_migrations(
                 .comment
                 .as_ref()
                 .map(|c| format!("COMMENT ON SCHEMA {} IS '{}'", name, c.replace('\'', "''")));
-            sqlx::query(&format!("CREATE SCHEMA IF NOT EXISTS {}", name))
-                .execute(pool)
-                .await?;
+            actions.push(run(format!("CREATE SCHEMA IF NOT EXISTS {}", name)));
             if let Some(sql) = comment {
-                let _ = sqlx::query(&sql).execute(pool).await;
+                actions.push(try_run(sql));
             }
         }
     }
@@ -298,20 +425,10 @@ pub async fn apply_migrations(
                 type_name,
                 values.join(", ")
             );
-            let _ = sqlx::query(&sql).execute(pool).await;
+            actions.push(try_run(sql));
         }
     }
 
-    // Which tables already exist, and with which columns? `CREATE TABLE IF NOT EXISTS` silently
-    // leaves a pre-existing table alone, so without this a table created by an older version of
-    // the package would never gain the columns added since. Taken before any DDL runs, so
-    // `has_table` means "existed before this call".
-    let target_schemas: Vec<String> = match schema_override {
-        Some(o) => vec![o.to_string()],
-        None => config.schemas.iter().map(|s| s.name.clone()).collect(),
-    };
-    let pre_snapshot = crate::db::introspect(pool, dialect, &target_schemas).await;
-
     for t in &config.tables {
         let sid = t.schema_id.as_deref().unwrap_or(default_sid);
         let schema = schemas_by_id.get(sid).ok_or_else(|| {
@@ -397,15 +514,19 @@ pub async fn apply_migrations(
             full_name,
             col_defs.join(",\n  ")
         );
-        sqlx::query(&sql).execute(pool).await?;
+        actions.push(run(sql));
 
         if table_pre_existed {
-            add_missing_columns(pool, &pre_snapshot, dialect, schema_raw, &t.name, &columns).await;
+            actions.push(InstallAction::AddMissingColumns {
+                schema: schema_raw.to_string(),
+                table: t.name.clone(),
+                columns: columns.clone(),
+            });
         }
 
         if t.audit_log {
             let audit_sql = audit_table_ddl(schema_raw, &t.name, cols, dialect);
-            sqlx::query(&audit_sql).execute(pool).await?;
+            actions.push(run(audit_sql));
             let pk_col = match &t.primary_key {
                 PrimaryKeyConfig::Single(s) => s.clone(),
                 PrimaryKeyConfig::Composite(v) => v[0].clone(),
@@ -422,19 +543,15 @@ pub async fn apply_migrations(
                 quote(&pk_col),
                 quote("audit_at")
             );
-            let _ = sqlx::query(&idx_sql).execute(pool).await;
+            actions.push(try_run(idx_sql));
 
             let audit_table = format!("{}_audit", t.name);
             if pre_snapshot.has_table(schema_raw, &audit_table) {
-                add_missing_columns(
-                    pool,
-                    &pre_snapshot,
-                    dialect,
-                    schema_raw,
-                    &audit_table,
-                    &companion_source_columns(cols, dialect),
-                )
-                .await;
+                actions.push(InstallAction::AddMissingColumns {
+                    schema: schema_raw.to_string(),
+                    table: audit_table,
+                    columns: companion_source_columns(cols, dialect),
+                });
             }
         }
 
@@ -450,27 +567,27 @@ pub async fn apply_migrations(
                 .take_while(|l| !l.trim_start().starts_with("-- index:"))
                 .collect::<Vec<_>>()
                 .join("\n");
-            sqlx::query(create_only.trim()).execute(pool).await?;
+            actions.push(run(create_only.trim().to_string()));
             let idx_sql = history_index_ddl(schema_raw, &t.name, &pk_col);
-            let _ = sqlx::query(&idx_sql).execute(pool).await;
+            actions.push(try_run(idx_sql));
 
             let history_table = format!("{}_history", t.name);
             if pre_snapshot.has_table(schema_raw, &history_table) {
-                add_missing_columns(
-                    pool,
-                    &pre_snapshot,
-                    dialect,
-                    schema_raw,
-                    &history_table,
-                    &companion_source_columns(cols, dialect),
-                )
-                .await;
+                actions.push(InstallAction::AddMissingColumns {
+                    schema: schema_raw.to_string(),
+                    table: history_table,
+                    columns: companion_source_columns(cols, dialect),
+                });
             }
         }
     }
 
-    if let Some(col) = rls_tenant_column {
-        apply_rls_to_tables(pool, config, schema_override, col, dialect).await?;
+    if let Some(col) = rls_tenant_column.filter(|_| dialect.supports_rls()) {
+        actions.extend(
+            rls_statements(config, schema_override, col)
+                .into_iter()
+                .map(InstallAction::Run),
+        );
     }
 
     for idx in &config.indexes {
@@ -532,7 +649,7 @@ pub async fn apply_migrations(
             include,
             where_clause
         );
-        let _ = sqlx::query(&sql).execute(pool).await;
+        actions.push(try_run(sql));
     }
 
     for rel in &config.relationships {
@@ -657,10 +774,10 @@ pub async fn apply_migrations(
             on_update,
             on_delete
         );
-        let _ = sqlx::query(&sql).execute(pool).await;
+        actions.push(try_run(sql));
     }
 
-    Ok(())
+    Ok(actions)
 }
 
 /// Revert migrations for a package: drop tables, enum types, and schema (if not public) in reverse order of apply.
@@ -747,7 +864,7 @@ pub async fn revert_migrations(
 /// plan is replayed after a partial failure, or broadcast to a tenant database that already has
 /// the column. Dialects without the syntax (MySQL, SQLite) rely on the executor's pre-flight
 /// introspection instead (see `db::introspect`).
-fn add_column_ddl(dialect: &dyn Dialect, full_table: &str, col_def: &str) -> String {
+pub(crate) fn add_column_ddl(dialect: &dyn Dialect, full_table: &str, col_def: &str) -> String {
     if dialect.supports_add_column_if_not_exists() {
         format!(
             "ALTER TABLE {} ADD COLUMN IF NOT EXISTS {}",
@@ -783,6 +900,17 @@ pub enum MigrationOperation {
     DropIndex,
     AddForeignKey,
     DropForeignKey,
+    /// `ADD CONSTRAINT … CHECK (…) NOT VALID` — only emitted by [`to_online_plan`].
+    AddCheckConstraint,
+    /// `VALIDATE CONSTRAINT` for a constraint added `NOT VALID` — only emitted by [`to_online_plan`].
+    ValidateConstraint,
+    /// `DROP CONSTRAINT IF EXISTS` for a helper constraint — only emitted by [`to_online_plan`].
+    DropConstraint,
+    /// Expand phase of an expand/contract type change: add the shadow column (see
+    /// [`crate::transition`]).
+    AddShadowColumn,
+    /// Batched copy of existing values into a shadow column (see [`crate::transition`]).
+    BackfillShadowColumn,
 }
 
 impl std::fmt::Display for MigrationOperation {
@@ -844,6 +972,155 @@ pub struct MigrationStep {
     pub safety: MigrationSafety,
     pub risk: MigrationRisk,
     pub risk_detail: Option<String>,
+    /// Heaviest table lock the statement is expected to take. Only reported by dialects with
+    /// online DDL support (see [`Dialect::supports_online_ddl`]); `None` everywhere else and in
+    /// plans saved before this field existed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lock_level: Option<LockLevel>,
+    /// Session settings and batching applied when the step runs. Empty for standard plans.
+    #[serde(flatten)]
+    pub execution: StepExecution,
+}
+
+impl MigrationStep {
+    /// A safe, risk-free step with no DDL, no lock level and default execution; `step` is
+    /// numbered when the plan is assembled. Set the rest with the `with_*` methods.
+    pub fn new(
+        operation: MigrationOperation,
+        schema: impl Into<String>,
+        table: Option<String>,
+        object: impl Into<String>,
+        object_type: impl Into<String>,
+        description: impl Into<String>,
+    ) -> Self {
+        Self {
+            step: 0,
+            operation,
+            schema: schema.into(),
+            table,
+            object: object.into(),
+            from_object: None,
+            object_type: object_type.into(),
+            description: description.into(),
+            ddl: None,
+            safety: MigrationSafety::Safe,
+            risk: MigrationRisk::None,
+            risk_detail: None,
+            lock_level: None,
+            execution: StepExecution::default(),
+        }
+    }
+
+    pub fn with_ddl(mut self, ddl: impl Into<String>) -> Self {
+        self.ddl = Some(ddl.into());
+        self
+    }
+
+    pub fn with_safety(mut self, safety: MigrationSafety) -> Self {
+        self.safety = safety;
+        self
+    }
+
+    pub fn with_risk(mut self, risk: MigrationRisk, detail: Option<String>) -> Self {
+        self.risk = risk;
+        self.risk_detail = detail;
+        self
+    }
+
+    pub fn with_from_object(mut self, from_object: Option<String>) -> Self {
+        self.from_object = from_object;
+        self
+    }
+
+    pub fn with_execution(mut self, execution: StepExecution) -> Self {
+        self.execution = execution;
+        self
+    }
+
+    pub fn with_step(mut self, step: usize) -> Self {
+        self.step = step;
+        self
+    }
+}
+
+/// PostgreSQL table lock modes a migration step can take, weakest first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LockLevel {
+    /// No table lock (schemas, enum types).
+    None,
+    /// Row writes (`UPDATE`). Blocks only conflicting DDL; reads and writes carry on.
+    RowExclusive,
+    /// `CREATE INDEX CONCURRENTLY`, `VALIDATE CONSTRAINT`. Reads and writes carry on.
+    ShareUpdateExclusive,
+    /// `CREATE INDEX`. Blocks writes for the whole build.
+    Share,
+    /// `ADD FOREIGN KEY`. Blocks writes on both tables.
+    ShareRowExclusive,
+    /// Most `ALTER TABLE` forms. Blocks reads and writes.
+    AccessExclusive,
+}
+
+/// How a step is executed, beyond its DDL. All fields are optional so standard plans — and plans
+/// saved before online mode existed — serialize exactly as before.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StepExecution {
+    /// `lock_timeout` for the step's session: give up instead of queueing behind a long
+    /// transaction (and blocking every query queued behind the step).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lock_timeout_ms: Option<u64>,
+    /// `statement_timeout` for the step's session.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub statement_timeout_ms: Option<u64>,
+    /// Rows per batch for a batched data step. The DDL is re-run until a batch touches fewer rows.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub batch_size: Option<u64>,
+    /// The statement cannot run inside a transaction block (`CREATE INDEX CONCURRENTLY`).
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub outside_transaction: bool,
+    /// Expand/contract bookkeeping: the column transition this step moves forward once it has
+    /// run (see [`crate::transition::record_transitions`]).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transition: Option<crate::transition::ColumnTransition>,
+}
+
+impl StepExecution {
+    fn is_session_scoped(&self) -> bool {
+        self.lock_timeout_ms.is_some() || self.statement_timeout_ms.is_some()
+    }
+}
+
+/// How `compute_migration_plan` output is turned into DDL.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MigrationMode {
+    /// Plain DDL, as computed.
+    #[default]
+    Standard,
+    /// Lock-minimising DDL for large, live tables (see [`to_online_plan`]).
+    Online,
+}
+
+/// Knobs for [`to_online_plan`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OnlineMigrationOptions {
+    /// Applied to every step.
+    pub lock_timeout_ms: u64,
+    /// Applied to steps that hold a blocking lock and to each backfill batch; long-running
+    /// non-blocking steps (concurrent index builds, constraint validation) are left unbounded.
+    pub statement_timeout_ms: Option<u64>,
+    /// Rows per `BackfillNulls` batch.
+    pub backfill_batch_size: u64,
+}
+
+impl Default for OnlineMigrationOptions {
+    fn default() -> Self {
+        Self {
+            lock_timeout_ms: 5_000,
+            statement_timeout_ms: None,
+            backfill_batch_size: 10_000,
+        }
+    }
 }
 
 /// Computed diff between two package versions expressed as ordered migration steps.
@@ -888,9 +1165,12 @@ pub struct MigrationExecutionResult {
     pub warnings: Vec<String>,
     /// One human-readable line per skipped step.
     pub skips: Vec<String>,
+    /// `(step number, rows affected)` for every step whose effect is now in place — applied, or
+    /// skipped because it already was. Rows are only counted for data steps.
+    pub completed_steps: Vec<(usize, u64)>,
 }
 
-fn default_str(d: &ColumnDefaultConfig) -> String {
+pub(crate) fn default_str(d: &ColumnDefaultConfig) -> String {
     match d {
         ColumnDefaultConfig::Literal(s) => s.clone(),
         ColumnDefaultConfig::Expression { expression } => expression.clone(),
@@ -983,79 +1263,76 @@ fn recreate_enum_steps(
         .collect();
 
     // 0. Informational summary of the destructive rebuild (no DDL).
-    steps.push(MigrationStep {
-        step: 0,
-        operation: MigrationOperation::RemoveEnumValue,
-        schema: schema.to_string(),
-        table: None,
-        object: format!("{}:{}", new_enum.name, removed.join(",")),
-        object_type: "enum".into(),
-        from_object: None,
-        description: format!(
-            "Rebuild enum \"{}\".\"{}\" to remove value(s): {}",
-            schema,
-            new_enum.name,
-            removed.join(", ")
-        ),
-        ddl: None,
-        safety: MigrationSafety::WarnOnly,
-        risk: MigrationRisk::ManualActionRequired,
-        risk_detail: Some(format!(
+    steps.push(
+        MigrationStep::new(
+            MigrationOperation::RemoveEnumValue,
+            schema.to_string(),
+            None,
+            format!("{}:{}", new_enum.name, removed.join(",")),
+            "enum",
+            format!(
+                "Rebuild enum \"{}\".\"{}\" to remove value(s): {}",
+                schema,
+                new_enum.name,
+                removed.join(", ")
+            ),
+        )
+        .with_safety(MigrationSafety::WarnOnly)
+        .with_risk(
+            MigrationRisk::ManualActionRequired,
+            Some(format!(
             "PostgreSQL cannot drop enum values in place. The type is rebuilt and {} dependent \
              column(s) are recast via a text cast. Any existing row holding a removed value ({}) \
              will make its recast fail — reassign those rows first.",
             dependents.len(),
             removed.join(", ")
         )),
-    });
+        ),
+    );
 
     // 1. Rename the live type aside.
-    steps.push(MigrationStep {
-        step: 0,
-        operation: MigrationOperation::DropEnum,
-        schema: schema.to_string(),
-        table: None,
-        object: new_enum.name.clone(),
-        object_type: "enum".into(),
-        from_object: None,
-        description: format!(
-            "Rename enum \"{}\".\"{}\" to \"{}\" before rebuild",
-            schema, new_enum.name, tmp_name
-        ),
-        ddl: Some(format!(
+    steps.push(
+        MigrationStep::new(
+            MigrationOperation::DropEnum,
+            schema.to_string(),
+            None,
+            new_enum.name.clone(),
+            "enum",
+            format!(
+                "Rename enum \"{}\".\"{}\" to \"{}\" before rebuild",
+                schema, new_enum.name, tmp_name
+            ),
+        )
+        .with_ddl(format!(
             "ALTER TYPE {} RENAME TO {}",
             type_q,
             quote(&tmp_name)
-        )),
-        safety: MigrationSafety::BestEffort,
-        risk: MigrationRisk::None,
-        risk_detail: None,
-    });
+        ))
+        .with_safety(MigrationSafety::BestEffort),
+    );
 
     // 2. Create the type afresh with the reduced value set (folds in any added values too).
-    steps.push(MigrationStep {
-        step: 0,
-        operation: MigrationOperation::CreateEnum,
-        schema: schema.to_string(),
-        table: None,
-        object: new_enum.name.clone(),
-        object_type: "enum".into(),
-        from_object: None,
-        description: format!(
-            "Recreate enum \"{}\".\"{}\" with {} value(s)",
-            schema,
-            new_enum.name,
-            new_enum.values.len()
-        ),
-        ddl: Some(format!(
+    steps.push(
+        MigrationStep::new(
+            MigrationOperation::CreateEnum,
+            schema.to_string(),
+            None,
+            new_enum.name.clone(),
+            "enum",
+            format!(
+                "Recreate enum \"{}\".\"{}\" with {} value(s)",
+                schema,
+                new_enum.name,
+                new_enum.values.len()
+            ),
+        )
+        .with_ddl(format!(
             "CREATE TYPE {} AS ENUM ({})",
             type_q,
             values.join(", ")
-        )),
-        safety: MigrationSafety::BestEffort,
-        risk: MigrationRisk::None,
-        risk_detail: None,
-    });
+        ))
+        .with_safety(MigrationSafety::BestEffort),
+    );
 
     // 3. Recast every dependent column from the renamed type onto the rebuilt one. A column with a
     //    default must drop it first (the default still references the renamed type) and restore it after.
@@ -1064,26 +1341,24 @@ fn recreate_enum_steps(
         let col_q = quote(&dep.column);
 
         if dep.default.is_some() {
-            steps.push(MigrationStep {
-                step: 0,
-                operation: MigrationOperation::DropDefault,
-                schema: dep.schema.clone(),
-                table: Some(dep.table.clone()),
-                object: dep.column.clone(),
-                object_type: "column".into(),
-                from_object: None,
-                description: format!(
-                    "Drop default on {}.{} before enum recast",
-                    dep.table, dep.column
-                ),
-                ddl: Some(format!(
+            steps.push(
+                MigrationStep::new(
+                    MigrationOperation::DropDefault,
+                    dep.schema.clone(),
+                    Some(dep.table.clone()),
+                    dep.column.clone(),
+                    "column",
+                    format!(
+                        "Drop default on {}.{} before enum recast",
+                        dep.table, dep.column
+                    ),
+                )
+                .with_ddl(format!(
                     "ALTER TABLE {} ALTER COLUMN {} DROP DEFAULT",
                     table_q, col_q
-                )),
-                safety: MigrationSafety::BestEffort,
-                risk: MigrationRisk::None,
-                risk_detail: None,
-            });
+                ))
+                .with_safety(MigrationSafety::BestEffort),
+            );
         }
 
         let (col_type, using) = if dep.is_array {
@@ -1094,73 +1369,120 @@ fn recreate_enum_steps(
         } else {
             (type_q.clone(), format!("{}::text::{}", col_q, type_q))
         };
-        steps.push(MigrationStep {
-            step: 0,
-            operation: MigrationOperation::AlterColumnType,
-            schema: dep.schema.clone(),
-            table: Some(dep.table.clone()),
-            object: dep.column.clone(),
-            object_type: "column".into(),
-            from_object: None,
-            description: format!(
-                "Recast {}.{} onto rebuilt enum \"{}\"",
-                dep.table, dep.column, new_enum.name
-            ),
-            ddl: Some(format!(
+        steps.push(
+            MigrationStep::new(
+                MigrationOperation::AlterColumnType,
+                dep.schema.clone(),
+                Some(dep.table.clone()),
+                dep.column.clone(),
+                "column",
+                format!(
+                    "Recast {}.{} onto rebuilt enum \"{}\"",
+                    dep.table, dep.column, new_enum.name
+                ),
+            )
+            .with_ddl(format!(
                 "ALTER TABLE {} ALTER COLUMN {} TYPE {} USING {}",
                 table_q, col_q, col_type, using
-            )),
-            safety: MigrationSafety::BestEffort,
-            risk: MigrationRisk::MayFail,
-            risk_detail: Some(format!(
-                "Cast fails if any row holds a removed value ({}).",
-                removed.join(", ")
-            )),
-        });
+            ))
+            .with_safety(MigrationSafety::BestEffort)
+            .with_risk(
+                MigrationRisk::MayFail,
+                Some(format!(
+                    "Cast fails if any row holds a removed value ({}).",
+                    removed.join(", ")
+                )),
+            ),
+        );
 
         if let Some(def) = &dep.default {
-            steps.push(MigrationStep {
-                step: 0,
-                operation: MigrationOperation::SetDefault,
-                schema: dep.schema.clone(),
-                table: Some(dep.table.clone()),
-                object: dep.column.clone(),
-                object_type: "column".into(),
-                from_object: None,
-                description: format!(
-                    "Restore default on {}.{} after enum recast",
-                    dep.table, dep.column
-                ),
-                ddl: Some(format!(
+            steps.push(
+                MigrationStep::new(
+                    MigrationOperation::SetDefault,
+                    dep.schema.clone(),
+                    Some(dep.table.clone()),
+                    dep.column.clone(),
+                    "column",
+                    format!(
+                        "Restore default on {}.{} after enum recast",
+                        dep.table, dep.column
+                    ),
+                )
+                .with_ddl(format!(
                     "ALTER TABLE {} ALTER COLUMN {} SET DEFAULT {}",
                     table_q, col_q, def
-                )),
-                safety: MigrationSafety::BestEffort,
-                risk: MigrationRisk::None,
-                risk_detail: None,
-            });
+                ))
+                .with_safety(MigrationSafety::BestEffort),
+            );
         }
     }
 
     // 4. Drop the renamed original type.
-    steps.push(MigrationStep {
-        step: 0,
-        operation: MigrationOperation::DropEnum,
-        schema: schema.to_string(),
-        table: None,
-        object: tmp_name.clone(),
-        object_type: "enum".into(),
-        from_object: None,
-        description: format!("Drop superseded enum \"{}\".\"{}\"", schema, tmp_name),
-        ddl: Some(format!(
+    steps.push(
+        MigrationStep::new(
+            MigrationOperation::DropEnum,
+            schema.to_string(),
+            None,
+            tmp_name.clone(),
+            "enum",
+            format!("Drop superseded enum \"{}\".\"{}\"", schema, tmp_name),
+        )
+        .with_ddl(format!(
             "DROP TYPE IF EXISTS {}.{}",
             quote(schema),
             quote(&tmp_name)
-        )),
-        safety: MigrationSafety::BestEffort,
-        risk: MigrationRisk::None,
-        risk_detail: None,
-    });
+        ))
+        .with_safety(MigrationSafety::BestEffort),
+    );
+}
+
+/// `CREATE INDEX IF NOT EXISTS` for a config index on `full_table` (already quoted).
+pub(crate) fn create_index_ddl(idx: &IndexConfig, full_table: &str) -> String {
+    let mut col_parts: Vec<String> = Vec::new();
+    for col in &idx.columns {
+        match col {
+            IndexColumnEntry::Name(n) => col_parts.push(quote(n)),
+            IndexColumnEntry::Spec {
+                name, direction, ..
+            } => {
+                let dir = direction
+                    .as_deref()
+                    .map(|d| format!(" {}", d.to_uppercase()))
+                    .unwrap_or_default();
+                col_parts.push(format!("{}{}", quote(name), dir));
+            }
+            IndexColumnEntry::Expression { expression } => col_parts.push(expression.clone()),
+        }
+    }
+    let method = idx.method.as_deref().unwrap_or("btree");
+    let unique_kw = if idx.unique { "UNIQUE " } else { "" };
+    let include = if idx.include.is_empty() {
+        String::new()
+    } else {
+        format!(
+            " INCLUDE ({})",
+            idx.include
+                .iter()
+                .map(|s| quote(s))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+    };
+    let where_clause = idx
+        .where_
+        .as_ref()
+        .map(|w| format!(" WHERE {}", w))
+        .unwrap_or_default();
+    format!(
+        "CREATE {}INDEX IF NOT EXISTS {} ON {} USING {} ({}){}{}",
+        unique_kw,
+        quote(&idx.name),
+        full_table,
+        method,
+        col_parts.join(", "),
+        include,
+        where_clause
+    )
 }
 
 // ─── compute_migration_plan ──────────────────────────────────────────────────
@@ -1225,20 +1547,17 @@ pub fn compute_migration_plan(
     if schema_override.is_none() {
         for s in &new.schemas {
             if !old_schemas.contains_key(s.id.as_str()) {
-                steps.push(MigrationStep {
-                    step: 0,
-                    operation: MigrationOperation::CreateSchema,
-                    schema: s.name.clone(),
-                    table: None,
-                    object: s.name.clone(),
-                    object_type: "schema".into(),
-                    from_object: None,
-                    description: format!("Create schema \"{}\"", s.name),
-                    ddl: Some(format!("CREATE SCHEMA IF NOT EXISTS {}", quote(&s.name))),
-                    safety: MigrationSafety::Safe,
-                    risk: MigrationRisk::None,
-                    risk_detail: None,
-                });
+                steps.push(
+                    MigrationStep::new(
+                        MigrationOperation::CreateSchema,
+                        s.name.clone(),
+                        None,
+                        s.name.clone(),
+                        "schema",
+                        format!("Create schema \"{}\"", s.name),
+                    )
+                    .with_ddl(format!("CREATE SCHEMA IF NOT EXISTS {}", quote(&s.name))),
+                );
             }
         }
     }
@@ -1266,28 +1585,25 @@ pub fn compute_migration_plan(
                     .map(String::as_str)
                     .filter(|v| !old_vals.contains(v))
                 {
-                    steps.push(MigrationStep {
-                        step: 0,
-                        operation: MigrationOperation::AddEnumValue,
-                        schema: schema.clone(),
-                        table: None,
-                        object: format!("{}:{}", new_enum.name, val),
-                        object_type: "enum_value".into(),
-                        from_object: None,
-                        description: format!(
-                            "Add value '{}' to enum \"{}\".\"{}\"",
-                            val, schema, new_enum.name
-                        ),
-                        ddl: Some(format!(
+                    steps.push(
+                        MigrationStep::new(
+                            MigrationOperation::AddEnumValue,
+                            schema.clone(),
+                            None,
+                            format!("{}:{}", new_enum.name, val),
+                            "enum_value",
+                            format!(
+                                "Add value '{}' to enum \"{}\".\"{}\"",
+                                val, schema, new_enum.name
+                            ),
+                        )
+                        .with_ddl(format!(
                             "ALTER TYPE {}.{} ADD VALUE IF NOT EXISTS '{}'",
                             quote(&schema),
                             quote(&new_enum.name),
                             val.replace('\'', "''")
                         )),
-                        safety: MigrationSafety::Safe,
-                        risk: MigrationRisk::None,
-                        risk_detail: None,
-                    });
+                    );
                 }
             } else {
                 // One or more values removed. PostgreSQL has no DROP VALUE, so rebuild the type and
@@ -1308,40 +1624,39 @@ pub fn compute_migration_plan(
                 .iter()
                 .map(|v| format!("'{}'", v.replace('\'', "''")))
                 .collect();
-            steps.push(MigrationStep {
-                step: 0,
-                operation: MigrationOperation::CreateEnum,
-                schema: schema.clone(),
-                table: None,
-                object: new_enum.name.clone(),
-                object_type: "enum".into(),
-                from_object: None,
-                description: format!("Create enum type \"{}\".\"{}\"", schema, new_enum.name),
-                ddl: Some(format!("CREATE TYPE {}.{} AS ENUM ({})", quote(&schema), quote(&new_enum.name), values.join(", "))),
-                safety: MigrationSafety::BestEffort,
-                risk: MigrationRisk::None,
-                risk_detail: Some("PostgreSQL has no CREATE TYPE IF NOT EXISTS; ignored if the type already exists.".into()),
-            });
+            steps.push(MigrationStep::new(
+                MigrationOperation::CreateEnum,
+                schema.clone(),
+                None,
+                new_enum.name.clone(),
+                "enum",
+                format!("Create enum type \"{}\".\"{}\"", schema, new_enum.name),
+            )
+            .with_ddl(format!("CREATE TYPE {}.{} AS ENUM ({})", quote(&schema), quote(&new_enum.name), values.join(", ")))
+            .with_safety(MigrationSafety::BestEffort)
+            .with_risk(
+                MigrationRisk::None,
+                Some("PostgreSQL has no CREATE TYPE IF NOT EXISTS; ignored if the type already exists.".into()),
+            ));
         }
     }
     for old_enum in &old.enums {
         if !new_enums.contains_key(old_enum.id.as_str()) {
             let sid = old_enum.schema_id.as_deref().unwrap_or(default_old_sid);
             let schema = schema_name_for(sid, &old_schemas);
-            steps.push(MigrationStep {
-                step: 0,
-                operation: MigrationOperation::DropEnum,
-                schema: schema.clone(),
-                table: None,
-                object: old_enum.name.clone(),
-                object_type: "enum".into(),
-                from_object: None,
-                description: format!("Enum \"{}\".\"{}\" removed from config", schema, old_enum.name),
-                ddl: None,
-                safety: MigrationSafety::WarnOnly,
-                risk: MigrationRisk::ManualActionRequired,
-                risk_detail: Some("Enum type NOT dropped from database (data safety). Run DROP TYPE manually if intended.".into()),
-            });
+            steps.push(MigrationStep::new(
+                MigrationOperation::DropEnum,
+                schema.clone(),
+                None,
+                old_enum.name.clone(),
+                "enum",
+                format!("Enum \"{}\".\"{}\" removed from config", schema, old_enum.name),
+            )
+            .with_safety(MigrationSafety::WarnOnly)
+            .with_risk(
+                MigrationRisk::ManualActionRequired,
+                Some("Enum type NOT dropped from database (data safety). Run DROP TYPE manually if intended.".into()),
+            ));
         }
     }
 
@@ -1422,43 +1737,37 @@ pub fn compute_migration_plan(
             ));
         }
 
-        steps.push(MigrationStep {
-            step: 0,
-            operation: MigrationOperation::CreateTable,
-            schema: schema.clone(),
-            table: Some(new_table.name.clone()),
-            object: new_table.name.clone(),
-            object_type: "table".into(),
-            from_object: None,
-            description: format!("Create table \"{}\".\"{}\"", schema, new_table.name),
-            ddl: Some(format!(
+        steps.push(
+            MigrationStep::new(
+                MigrationOperation::CreateTable,
+                schema.clone(),
+                Some(new_table.name.clone()),
+                new_table.name.clone(),
+                "table",
+                format!("Create table \"{}\".\"{}\"", schema, new_table.name),
+            )
+            .with_ddl(format!(
                 "CREATE TABLE IF NOT EXISTS {} (\n  {}\n)",
                 full,
                 col_defs.join(",\n  ")
             )),
-            safety: MigrationSafety::Safe,
-            risk: MigrationRisk::None,
-            risk_detail: None,
-        });
+        );
         if new_table.audit_log {
             let audit_ddl = audit_table_ddl(&schema, &new_table.name, cols, dialect);
-            steps.push(MigrationStep {
-                step: 0,
-                operation: MigrationOperation::CreateTable,
-                schema: schema.clone(),
-                table: Some(format!("{}_audit", new_table.name)),
-                object: format!("{}_audit", new_table.name),
-                object_type: "table".into(),
-                from_object: None,
-                description: format!(
-                    "Create audit table \"{}\".\"{}_audit\"",
-                    schema, new_table.name
-                ),
-                ddl: Some(audit_ddl),
-                safety: MigrationSafety::Safe,
-                risk: MigrationRisk::None,
-                risk_detail: None,
-            });
+            steps.push(
+                MigrationStep::new(
+                    MigrationOperation::CreateTable,
+                    schema.clone(),
+                    Some(format!("{}_audit", new_table.name)),
+                    format!("{}_audit", new_table.name),
+                    "table",
+                    format!(
+                        "Create audit table \"{}\".\"{}_audit\"",
+                        schema, new_table.name
+                    ),
+                )
+                .with_ddl(audit_ddl),
+            );
         }
         if new_table.versioning.as_ref().is_some_and(|v| v.enabled) {
             let pk_col = match &new_table.primary_key {
@@ -1484,40 +1793,34 @@ pub fn compute_migration_plan(
                         + "\n)"
                 }
             );
-            steps.push(MigrationStep {
-                step: 0,
-                operation: MigrationOperation::CreateTable,
-                schema: schema.clone(),
-                table: Some(format!("{}_history", new_table.name)),
-                object: format!("{}_history", new_table.name),
-                object_type: "table".into(),
-                from_object: None,
-                description: format!(
-                    "Create history table \"{}\".\"{}_history\" (versioning)",
-                    schema, new_table.name
-                ),
-                ddl: Some(history_create),
-                safety: MigrationSafety::Safe,
-                risk: MigrationRisk::None,
-                risk_detail: None,
-            });
-            steps.push(MigrationStep {
-                step: 0,
-                operation: MigrationOperation::CreateIndex,
-                schema: schema.clone(),
-                table: Some(format!("{}_history", new_table.name)),
-                object: format!("{}_history_{}_idx", new_table.name, pk_col),
-                object_type: "index".into(),
-                from_object: None,
-                description: format!(
-                    "Create index on history table \"{}\".\"{}\" ({pk_col}, _version DESC)",
-                    schema, new_table.name
-                ),
-                ddl: Some(history_index_ddl(&schema, &new_table.name, &pk_col)),
-                safety: MigrationSafety::Safe,
-                risk: MigrationRisk::None,
-                risk_detail: None,
-            });
+            steps.push(
+                MigrationStep::new(
+                    MigrationOperation::CreateTable,
+                    schema.clone(),
+                    Some(format!("{}_history", new_table.name)),
+                    format!("{}_history", new_table.name),
+                    "table",
+                    format!(
+                        "Create history table \"{}\".\"{}_history\" (versioning)",
+                        schema, new_table.name
+                    ),
+                )
+                .with_ddl(history_create),
+            );
+            steps.push(
+                MigrationStep::new(
+                    MigrationOperation::CreateIndex,
+                    schema.clone(),
+                    Some(format!("{}_history", new_table.name)),
+                    format!("{}_history_{}_idx", new_table.name, pk_col),
+                    "index",
+                    format!(
+                        "Create index on history table \"{}\".\"{}\" ({pk_col}, _version DESC)",
+                        schema, new_table.name
+                    ),
+                )
+                .with_ddl(history_index_ddl(&schema, &new_table.name, &pk_col)),
+            );
         }
     }
 
@@ -1536,23 +1839,20 @@ pub fn compute_migration_plan(
 
             if !old_table.audit_log && new_table.audit_log {
                 let audit_ddl = audit_table_ddl(&schema, &new_table.name, cols, dialect);
-                steps.push(MigrationStep {
-                    step: 0,
-                    operation: MigrationOperation::CreateTable,
-                    schema: schema.clone(),
-                    table: Some(format!("{}_audit", new_table.name)),
-                    object: format!("{}_audit", new_table.name),
-                    object_type: "table".into(),
-                    from_object: None,
-                    description: format!(
-                        "Enable audit log: create \"{}\".\"{}_audit\"",
-                        schema, new_table.name
-                    ),
-                    ddl: Some(audit_ddl),
-                    safety: MigrationSafety::Safe,
-                    risk: MigrationRisk::None,
-                    risk_detail: None,
-                });
+                steps.push(
+                    MigrationStep::new(
+                        MigrationOperation::CreateTable,
+                        schema.clone(),
+                        Some(format!("{}_audit", new_table.name)),
+                        format!("{}_audit", new_table.name),
+                        "table",
+                        format!(
+                            "Enable audit log: create \"{}\".\"{}_audit\"",
+                            schema, new_table.name
+                        ),
+                    )
+                    .with_ddl(audit_ddl),
+                );
             }
 
             let old_versioning_enabled = old_table.versioning.as_ref().is_some_and(|v| v.enabled);
@@ -1569,40 +1869,38 @@ pub fn compute_migration_plan(
                     .take_while(|l| !l.trim_start().starts_with("-- index:"))
                     .collect::<Vec<_>>()
                     .join("\n");
-                steps.push(MigrationStep {
-                    step: 0,
-                    operation: MigrationOperation::CreateTable,
-                    schema: schema.clone(),
-                    table: Some(format!("{}_history", new_table.name)),
-                    object: format!("{}_history", new_table.name),
-                    object_type: "table".into(),
-                    from_object: None,
-                    description: format!(
-                        "Enable versioning: create \"{}\".\"{}_history\"",
-                        schema, new_table.name
-                    ),
-                    ddl: Some(create_only.trim().to_string()),
-                    safety: MigrationSafety::Safe,
-                    risk: MigrationRisk::None,
-                    risk_detail: None,
-                });
-                steps.push(MigrationStep {
-                    step: 0,
-                    operation: MigrationOperation::CreateIndex,
-                    schema: schema.clone(),
-                    table: Some(format!("{}_history", new_table.name)),
-                    object: format!("{}_history_{}_idx", new_table.name, pk_col),
-                    object_type: "index".into(),
-                    from_object: None,
-                    description: format!(
-                        "Create history index on \"{}\".\"{}\"",
-                        schema, new_table.name
-                    ),
-                    ddl: Some(history_index_ddl(&schema, &new_table.name, &pk_col)),
-                    safety: MigrationSafety::Safe,
-                    risk: MigrationRisk::None,
-                    risk_detail: None,
-                });
+                steps.push(
+                    MigrationStep::new(
+                        MigrationOperation::CreateTable,
+                        schema.clone(),
+                        Some(format!("{}_history", new_table.name)),
+                        format!("{}_history", new_table.name),
+                        "table",
+                        format!(
+                            "Enable versioning: create \"{}\".\"{}_history\"",
+                            schema, new_table.name
+                        ),
+                    )
+                    .with_ddl(create_only.trim().to_string()),
+                );
+                steps.push(
+                    MigrationStep::new(
+                        MigrationOperation::CreateIndex,
+                        schema.clone(),
+                        Some(format!("{}_history", new_table.name)),
+                        format!("{}_history_{}_idx", new_table.name, pk_col),
+                        "index",
+                        format!(
+                            "Create history index on \"{}\".\"{}\"",
+                            schema, new_table.name
+                        ),
+                    )
+                    .with_ddl(history_index_ddl(
+                        &schema,
+                        &new_table.name,
+                        &pk_col,
+                    )),
+                );
             }
         }
     }
@@ -1611,20 +1909,19 @@ pub fn compute_migration_plan(
         if !new_tables.contains_key(old_table.id.as_str()) {
             let sid = old_table.schema_id.as_deref().unwrap_or(default_old_sid);
             let schema = schema_name_for(sid, &old_schemas);
-            steps.push(MigrationStep {
-                step: 0,
-                operation: MigrationOperation::DropTable,
-                schema: schema.clone(),
-                table: Some(old_table.name.clone()),
-                object: old_table.name.clone(),
-                object_type: "table".into(),
-                from_object: None,
-                description: format!("Table \"{}\".\"{}\" removed from config", schema, old_table.name),
-                ddl: None,
-                safety: MigrationSafety::WarnOnly,
-                risk: MigrationRisk::ManualActionRequired,
-                risk_detail: Some("Table NOT dropped from database (data safety). Run DROP TABLE manually if intended.".into()),
-            });
+            steps.push(MigrationStep::new(
+                MigrationOperation::DropTable,
+                schema.clone(),
+                Some(old_table.name.clone()),
+                old_table.name.clone(),
+                "table",
+                format!("Table \"{}\".\"{}\" removed from config", schema, old_table.name),
+            )
+            .with_safety(MigrationSafety::WarnOnly)
+            .with_risk(
+                MigrationRisk::ManualActionRequired,
+                Some("Table NOT dropped from database (data safety). Run DROP TABLE manually if intended.".into()),
+            ));
         }
     }
 
@@ -1643,47 +1940,44 @@ pub fn compute_migration_plan(
 
         if let Some(old_col) = old_columns.get(new_col.id.as_str()) {
             if old_col.table_id != new_col.table_id {
-                steps.push(MigrationStep {
-                    step: 0,
-                    operation: MigrationOperation::AddColumn,
-                    schema: schema.clone(),
-                    table: Some(table.name.clone()),
-                    object: new_col.name.clone(),
-                    object_type: "column".into(),
-                    from_object: None,
-                    description: format!("Column \"{}\" (id: {}) appears to have moved tables — manual migration required", new_col.name, new_col.id),
-                    ddl: None,
-                    safety: MigrationSafety::WarnOnly,
-                    risk: MigrationRisk::ManualActionRequired,
-                    risk_detail: Some(format!("Cannot automate column move from table {} to {}.", old_col.table_id, new_col.table_id)),
-                });
+                steps.push(MigrationStep::new(
+                    MigrationOperation::AddColumn,
+                    schema.clone(),
+                    Some(table.name.clone()),
+                    new_col.name.clone(),
+                    "column",
+                    format!("Column \"{}\" (id: {}) appears to have moved tables — manual migration required", new_col.name, new_col.id),
+                )
+                .with_safety(MigrationSafety::WarnOnly)
+                .with_risk(
+                    MigrationRisk::ManualActionRequired,
+                    Some(format!("Cannot automate column move from table {} to {}.", old_col.table_id, new_col.table_id)),
+                ));
                 continue;
             }
 
             // Rename
             if old_col.name != new_col.name {
-                steps.push(MigrationStep {
-                    step: 0,
-                    operation: MigrationOperation::RenameColumn,
-                    schema: schema.clone(),
-                    table: Some(table.name.clone()),
-                    object: new_col.name.clone(),
-                    object_type: "column".into(),
-                    from_object: Some(old_col.name.clone()),
-                    description: format!(
-                        "Rename column \"{}\" → \"{}\" on \"{}\".\"{}\"",
-                        old_col.name, new_col.name, schema, table.name
-                    ),
-                    ddl: Some(format!(
+                steps.push(
+                    MigrationStep::new(
+                        MigrationOperation::RenameColumn,
+                        schema.clone(),
+                        Some(table.name.clone()),
+                        new_col.name.clone(),
+                        "column",
+                        format!(
+                            "Rename column \"{}\" → \"{}\" on \"{}\".\"{}\"",
+                            old_col.name, new_col.name, schema, table.name
+                        ),
+                    )
+                    .with_ddl(format!(
                         "ALTER TABLE {} RENAME COLUMN {} TO {}",
                         full,
                         quote(&old_col.name),
                         quote(&new_col.name)
-                    )),
-                    safety: MigrationSafety::Safe,
-                    risk: MigrationRisk::None,
-                    risk_detail: None,
-                });
+                    ))
+                    .with_from_object(Some(old_col.name.clone())),
+                );
                 steps.extend(companion_column_steps(
                     &schema,
                     table,
@@ -1700,20 +1994,20 @@ pub fn compute_migration_plan(
             let new_type = dialect.ddl_type(&parse_canonical(&new_col.type_));
             if old_type.to_uppercase() != new_type.to_uppercase() {
                 let col_name = &new_col.name;
-                steps.push(MigrationStep {
-                    step: 0,
-                    operation: MigrationOperation::AlterColumnType,
-                    schema: schema.clone(),
-                    table: Some(table.name.clone()),
-                    object: col_name.clone(),
-                    object_type: "column".into(),
-                    from_object: None,
-                    description: format!("Change type of \"{}\".\"{}\".\"{}\": {} → {}", schema, table.name, col_name, old_type, new_type),
-                    ddl: Some(format!("ALTER TABLE {} ALTER COLUMN {} TYPE {} USING {}::{}", full, quote(col_name), new_type, quote(col_name), new_type)),
-                    safety: MigrationSafety::BestEffort,
-                    risk: MigrationRisk::MayFail,
-                    risk_detail: Some(format!("USING {}::{} cast may fail for incompatible values. Provide a custom USING expression if needed.", col_name, new_type)),
-                });
+                steps.push(MigrationStep::new(
+                    MigrationOperation::AlterColumnType,
+                    schema.clone(),
+                    Some(table.name.clone()),
+                    col_name.clone(),
+                    "column",
+                    format!("Change type of \"{}\".\"{}\".\"{}\": {} → {}", schema, table.name, col_name, old_type, new_type),
+                )
+                .with_ddl(format!("ALTER TABLE {} ALTER COLUMN {} TYPE {} USING {}::{}", full, quote(col_name), new_type, quote(col_name), new_type))
+                .with_safety(MigrationSafety::BestEffort)
+                .with_risk(
+                    MigrationRisk::MayFail,
+                    Some(format!("USING {}::{} cast may fail for incompatible values. Provide a custom USING expression if needed.", col_name, new_type)),
+                ));
                 steps.extend(companion_column_steps(
                     &schema,
                     table,
@@ -1730,80 +2024,71 @@ pub fn compute_migration_plan(
                 if let Some(ref d) = new_col.default {
                     let default_val = default_str(d);
                     // Backfill NULLs first using the configured default
-                    steps.push(MigrationStep {
-                        step: 0,
-                        operation: MigrationOperation::BackfillNulls,
-                        schema: schema.clone(),
-                        table: Some(table.name.clone()),
-                        object: new_col.name.clone(),
-                        object_type: "column".into(),
-                        from_object: None,
-                        description: format!("Backfill NULLs in \"{}\".\"{}\".\"{}\": SET {} = {} WHERE {} IS NULL", schema, table.name, new_col.name, new_col.name, default_val, new_col.name),
-                        ddl: Some(format!("UPDATE {} SET {} = {} WHERE {} IS NULL", full, quote(&new_col.name), default_val, quote(&new_col.name))),
-                        safety: MigrationSafety::Safe,
-                        risk: MigrationRisk::DataWillBeModified,
-                        risk_detail: Some(format!("Existing NULLs in column \"{}\" will be set to {} before NOT NULL is enforced.", new_col.name, default_val)),
-                    });
+                    steps.push(MigrationStep::new(
+                        MigrationOperation::BackfillNulls,
+                        schema.clone(),
+                        Some(table.name.clone()),
+                        new_col.name.clone(),
+                        "column",
+                        format!("Backfill NULLs in \"{}\".\"{}\".\"{}\": SET {} = {} WHERE {} IS NULL", schema, table.name, new_col.name, new_col.name, default_val, new_col.name),
+                    )
+                    .with_ddl(format!("UPDATE {} SET {} = {} WHERE {} IS NULL", full, quote(&new_col.name), default_val, quote(&new_col.name)))
+                    .with_risk(
+                        MigrationRisk::DataWillBeModified,
+                        Some(format!("Existing NULLs in column \"{}\" will be set to {} before NOT NULL is enforced.", new_col.name, default_val)),
+                    ));
                     // Then set NOT NULL — safe because NULLs are gone
-                    steps.push(MigrationStep {
-                        step: 0,
-                        operation: MigrationOperation::SetNotNull,
-                        schema: schema.clone(),
-                        table: Some(table.name.clone()),
-                        object: new_col.name.clone(),
-                        object_type: "column".into(),
-                        from_object: None,
-                        description: format!("Set NOT NULL on \"{}\".\"{}\".\"{}\": NULLs pre-filled with default ({})", schema, table.name, new_col.name, default_val),
-                        ddl: Some(format!("ALTER TABLE {} ALTER COLUMN {} SET NOT NULL", full, quote(&new_col.name))),
-                        safety: MigrationSafety::Safe,
-                        risk: MigrationRisk::None,
-                        risk_detail: None,
-                    });
+                    steps.push(MigrationStep::new(
+                        MigrationOperation::SetNotNull,
+                        schema.clone(),
+                        Some(table.name.clone()),
+                        new_col.name.clone(),
+                        "column",
+                        format!("Set NOT NULL on \"{}\".\"{}\".\"{}\": NULLs pre-filled with default ({})", schema, table.name, new_col.name, default_val),
+                    )
+                    .with_ddl(format!("ALTER TABLE {} ALTER COLUMN {} SET NOT NULL", full, quote(&new_col.name))));
                 } else {
                     // No default — best effort; will fail if NULLs exist
-                    steps.push(MigrationStep {
-                        step: 0,
-                        operation: MigrationOperation::SetNotNull,
-                        schema: schema.clone(),
-                        table: Some(table.name.clone()),
-                        object: new_col.name.clone(),
-                        object_type: "column".into(),
-                        from_object: None,
-                        description: format!("Set NOT NULL on \"{}\".\"{}\".\"{}\": no default configured — will fail if NULLs exist", schema, table.name, new_col.name),
-                        ddl: Some(format!("ALTER TABLE {} ALTER COLUMN {} SET NOT NULL", full, quote(&new_col.name))),
-                        safety: MigrationSafety::BestEffort,
-                        risk: MigrationRisk::ExistingNullsMustBeAbsent,
-                        risk_detail: Some(format!(
+                    steps.push(MigrationStep::new(
+                        MigrationOperation::SetNotNull,
+                        schema.clone(),
+                        Some(table.name.clone()),
+                        new_col.name.clone(),
+                        "column",
+                        format!("Set NOT NULL on \"{}\".\"{}\".\"{}\": no default configured — will fail if NULLs exist", schema, table.name, new_col.name),
+                    )
+                    .with_ddl(format!("ALTER TABLE {} ALTER COLUMN {} SET NOT NULL", full, quote(&new_col.name)))
+                    .with_safety(MigrationSafety::BestEffort)
+                    .with_risk(
+                        MigrationRisk::ExistingNullsMustBeAbsent,
+                        Some(format!(
                             "No default value configured for column \"{}\". Add a default to the config to enable automatic NULL backfill before enforcing NOT NULL.",
                             new_col.name
                         )),
-                    });
+                    ));
                 }
             }
 
             // Nullability: NOT NULL → nullable
             if !old_col.nullable && new_col.nullable {
-                steps.push(MigrationStep {
-                    step: 0,
-                    operation: MigrationOperation::DropNotNull,
-                    schema: schema.clone(),
-                    table: Some(table.name.clone()),
-                    object: new_col.name.clone(),
-                    object_type: "column".into(),
-                    from_object: None,
-                    description: format!(
-                        "Drop NOT NULL on \"{}\".\"{}\".\"{}\": column becomes nullable",
-                        schema, table.name, new_col.name
-                    ),
-                    ddl: Some(format!(
+                steps.push(
+                    MigrationStep::new(
+                        MigrationOperation::DropNotNull,
+                        schema.clone(),
+                        Some(table.name.clone()),
+                        new_col.name.clone(),
+                        "column",
+                        format!(
+                            "Drop NOT NULL on \"{}\".\"{}\".\"{}\": column becomes nullable",
+                            schema, table.name, new_col.name
+                        ),
+                    )
+                    .with_ddl(format!(
                         "ALTER TABLE {} ALTER COLUMN {} DROP NOT NULL",
                         full,
                         quote(&new_col.name)
                     )),
-                    safety: MigrationSafety::Safe,
-                    risk: MigrationRisk::None,
-                    risk_detail: None,
-                });
+                );
             }
 
             // Default change
@@ -1813,58 +2098,52 @@ pub fn compute_migration_plan(
                 match &new_col.default {
                     Some(d) => {
                         let val = default_str(d);
-                        steps.push(MigrationStep {
-                            step: 0,
-                            operation: MigrationOperation::SetDefault,
-                            schema: schema.clone(),
-                            table: Some(table.name.clone()),
-                            object: new_col.name.clone(),
-                            object_type: "column".into(),
-                            from_object: None,
-                            description: format!(
-                                "Set DEFAULT {} on \"{}\".\"{}\".\"{}\": was {}",
-                                val,
-                                schema,
-                                table.name,
-                                new_col.name,
-                                old_def.as_deref().unwrap_or("none")
-                            ),
-                            ddl: Some(format!(
+                        steps.push(
+                            MigrationStep::new(
+                                MigrationOperation::SetDefault,
+                                schema.clone(),
+                                Some(table.name.clone()),
+                                new_col.name.clone(),
+                                "column",
+                                format!(
+                                    "Set DEFAULT {} on \"{}\".\"{}\".\"{}\": was {}",
+                                    val,
+                                    schema,
+                                    table.name,
+                                    new_col.name,
+                                    old_def.as_deref().unwrap_or("none")
+                                ),
+                            )
+                            .with_ddl(format!(
                                 "ALTER TABLE {} ALTER COLUMN {} SET DEFAULT {}",
                                 full,
                                 quote(&new_col.name),
                                 val
                             )),
-                            safety: MigrationSafety::Safe,
-                            risk: MigrationRisk::None,
-                            risk_detail: None,
-                        });
+                        );
                     }
                     None => {
-                        steps.push(MigrationStep {
-                            step: 0,
-                            operation: MigrationOperation::DropDefault,
-                            schema: schema.clone(),
-                            table: Some(table.name.clone()),
-                            object: new_col.name.clone(),
-                            object_type: "column".into(),
-                            from_object: None,
-                            description: format!(
-                                "Drop DEFAULT on \"{}\".\"{}\".\"{}\": was {}",
-                                schema,
-                                table.name,
-                                new_col.name,
-                                old_def.as_deref().unwrap_or("none")
-                            ),
-                            ddl: Some(format!(
+                        steps.push(
+                            MigrationStep::new(
+                                MigrationOperation::DropDefault,
+                                schema.clone(),
+                                Some(table.name.clone()),
+                                new_col.name.clone(),
+                                "column",
+                                format!(
+                                    "Drop DEFAULT on \"{}\".\"{}\".\"{}\": was {}",
+                                    schema,
+                                    table.name,
+                                    new_col.name,
+                                    old_def.as_deref().unwrap_or("none")
+                                ),
+                            )
+                            .with_ddl(format!(
                                 "ALTER TABLE {} ALTER COLUMN {} DROP DEFAULT",
                                 full,
                                 quote(&new_col.name)
                             )),
-                            safety: MigrationSafety::Safe,
-                            risk: MigrationRisk::None,
-                            risk_detail: None,
-                        });
+                        );
                     }
                 }
             }
@@ -1882,23 +2161,20 @@ pub fn compute_migration_plan(
                     ColumnDefaultConfig::Expression { expression } => col_def.push_str(expression),
                 }
             }
-            steps.push(MigrationStep {
-                step: 0,
-                operation: MigrationOperation::AddColumn,
-                schema: schema.clone(),
-                table: Some(table.name.clone()),
-                object: new_col.name.clone(),
-                object_type: "column".into(),
-                from_object: None,
-                description: format!(
-                    "Add column \"{}\" {} to \"{}\".\"{}\"",
-                    new_col.name, new_type, schema, table.name
-                ),
-                ddl: Some(add_column_ddl(dialect, &full, &col_def)),
-                safety: MigrationSafety::Safe,
-                risk: MigrationRisk::None,
-                risk_detail: None,
-            });
+            steps.push(
+                MigrationStep::new(
+                    MigrationOperation::AddColumn,
+                    schema.clone(),
+                    Some(table.name.clone()),
+                    new_col.name.clone(),
+                    "column",
+                    format!(
+                        "Add column \"{}\" {} to \"{}\".\"{}\"",
+                        new_col.name, new_type, schema, table.name
+                    ),
+                )
+                .with_ddl(add_column_ddl(dialect, &full, &col_def)),
+            );
             steps.extend(companion_column_steps(
                 &schema,
                 table,
@@ -1928,20 +2204,19 @@ pub fn compute_migration_plan(
             .and_then(|t| t.schema_id.as_deref())
             .unwrap_or(default_old_sid);
         let schema = schema_name_for(sid, &old_schemas);
-        steps.push(MigrationStep {
-            step: 0,
-            operation: MigrationOperation::DropColumn,
-            schema: schema.clone(),
-            table: Some(table_name.to_string()),
-            object: old_col.name.clone(),
-            object_type: "column".into(),
-            from_object: None,
-            description: format!("Column \"{}\" removed from config on \"{}\".\"{}\"", old_col.name, schema, table_name),
-            ddl: None,
-            safety: MigrationSafety::WarnOnly,
-            risk: MigrationRisk::ManualActionRequired,
-            risk_detail: Some("Column NOT dropped from database (data safety). Run ALTER TABLE DROP COLUMN manually if intended.".into()),
-        });
+        steps.push(MigrationStep::new(
+            MigrationOperation::DropColumn,
+            schema.clone(),
+            Some(table_name.to_string()),
+            old_col.name.clone(),
+            "column",
+            format!("Column \"{}\" removed from config on \"{}\".\"{}\"", old_col.name, schema, table_name),
+        )
+        .with_safety(MigrationSafety::WarnOnly)
+        .with_risk(
+            MigrationRisk::ManualActionRequired,
+            Some("Column NOT dropped from database (data safety). Run ALTER TABLE DROP COLUMN manually if intended.".into()),
+        ));
     }
 
     // ── 5. Indexes ───────────────────────────────────────────────────────────
@@ -1949,26 +2224,23 @@ pub fn compute_migration_plan(
         if !new_indexes.contains_key(old_idx.id.as_str()) {
             let sid = old_idx.schema_id.as_deref().unwrap_or(default_old_sid);
             let schema = schema_name_for(sid, &old_schemas);
-            steps.push(MigrationStep {
-                step: 0,
-                operation: MigrationOperation::DropIndex,
-                schema: schema.clone(),
-                table: old_tables
-                    .get(old_idx.table_id.as_str())
-                    .map(|t| t.name.clone()),
-                object: old_idx.name.clone(),
-                object_type: "index".into(),
-                from_object: None,
-                description: format!("Drop index \"{}\" in schema \"{}\"", old_idx.name, schema),
-                ddl: Some(format!(
+            steps.push(
+                MigrationStep::new(
+                    MigrationOperation::DropIndex,
+                    schema.clone(),
+                    old_tables
+                        .get(old_idx.table_id.as_str())
+                        .map(|t| t.name.clone()),
+                    old_idx.name.clone(),
+                    "index",
+                    format!("Drop index \"{}\" in schema \"{}\"", old_idx.name, schema),
+                )
+                .with_ddl(format!(
                     "DROP INDEX IF EXISTS {}.{}",
                     quote(&schema),
                     quote(&old_idx.name)
                 )),
-                safety: MigrationSafety::Safe,
-                risk: MigrationRisk::None,
-                risk_detail: None,
-            });
+            );
         }
     }
     for new_idx in &new.indexes {
@@ -1987,71 +2259,23 @@ pub fn compute_migration_plan(
             None => continue,
         };
         let full_table = format!("{}.{}", quote(&schema), quote(&table.name));
-        let mut col_parts: Vec<String> = Vec::new();
-        for col in &new_idx.columns {
-            match col {
-                IndexColumnEntry::Name(n) => col_parts.push(quote(n)),
-                IndexColumnEntry::Spec {
-                    name, direction, ..
-                } => {
-                    let dir = direction
-                        .as_deref()
-                        .map(|d| format!(" {}", d.to_uppercase()))
-                        .unwrap_or_default();
-                    col_parts.push(format!("{}{}", quote(name), dir));
-                }
-                IndexColumnEntry::Expression { expression } => col_parts.push(expression.clone()),
-            }
-        }
-        let method = new_idx.method.as_deref().unwrap_or("btree");
-        let unique_kw = if new_idx.unique { "UNIQUE " } else { "" };
-        let include = if new_idx.include.is_empty() {
-            String::new()
-        } else {
-            format!(
-                " INCLUDE ({})",
-                new_idx
-                    .include
-                    .iter()
-                    .map(|s| quote(s))
-                    .collect::<Vec<_>>()
-                    .join(", ")
+        steps.push(
+            MigrationStep::new(
+                MigrationOperation::CreateIndex,
+                schema.clone(),
+                Some(table.name.clone()),
+                new_idx.name.clone(),
+                "index",
+                format!(
+                    "Create {}index \"{}\" on \"{}\".\"{}\"",
+                    if new_idx.unique { "unique " } else { "" },
+                    new_idx.name,
+                    schema,
+                    table.name
+                ),
             )
-        };
-        let where_clause = new_idx
-            .where_
-            .as_ref()
-            .map(|w| format!(" WHERE {}", w))
-            .unwrap_or_default();
-        steps.push(MigrationStep {
-            step: 0,
-            operation: MigrationOperation::CreateIndex,
-            schema: schema.clone(),
-            table: Some(table.name.clone()),
-            object: new_idx.name.clone(),
-            object_type: "index".into(),
-            from_object: None,
-            description: format!(
-                "Create {}index \"{}\" on \"{}\".\"{}\"",
-                if new_idx.unique { "unique " } else { "" },
-                new_idx.name,
-                schema,
-                table.name
-            ),
-            ddl: Some(format!(
-                "CREATE {}INDEX IF NOT EXISTS {} ON {} USING {} ({}){}{}",
-                unique_kw,
-                quote(&new_idx.name),
-                full_table,
-                method,
-                col_parts.join(", "),
-                include,
-                where_clause
-            )),
-            safety: MigrationSafety::Safe,
-            risk: MigrationRisk::None,
-            risk_detail: None,
-        });
+            .with_ddl(create_index_ddl(new_idx, &full_table)),
+        );
     }
 
     // ── 6. Foreign keys ──────────────────────────────────────────────────────
@@ -2068,30 +2292,27 @@ pub fn compute_migration_plan(
                 .unwrap_or(&old_rel.from_table_id);
             let constraint = old_rel.name.as_deref().unwrap_or(&old_rel.id);
             let schema_q = quote(schema_override.unwrap_or(from_schema));
-            steps.push(MigrationStep {
-                step: 0,
-                operation: MigrationOperation::DropForeignKey,
-                schema: schema_override.unwrap_or(from_schema).to_string(),
-                table: Some(from_table.to_string()),
-                object: constraint.to_string(),
-                object_type: "foreign_key".into(),
-                from_object: None,
-                description: format!(
-                    "Drop FK \"{}\" from \"{}\".\"{}\"",
-                    constraint,
-                    schema_override.unwrap_or(from_schema),
-                    from_table
-                ),
-                ddl: Some(format!(
+            steps.push(
+                MigrationStep::new(
+                    MigrationOperation::DropForeignKey,
+                    schema_override.unwrap_or(from_schema).to_string(),
+                    Some(from_table.to_string()),
+                    constraint.to_string(),
+                    "foreign_key",
+                    format!(
+                        "Drop FK \"{}\" from \"{}\".\"{}\"",
+                        constraint,
+                        schema_override.unwrap_or(from_schema),
+                        from_table
+                    ),
+                )
+                .with_ddl(format!(
                     "ALTER TABLE {}.{} DROP CONSTRAINT IF EXISTS {}",
                     schema_q,
                     quote(from_table),
                     quote(constraint)
                 )),
-                safety: MigrationSafety::Safe,
-                risk: MigrationRisk::None,
-                risk_detail: None,
-            });
+            );
         }
     }
     for new_rel in &new.relationships {
@@ -2179,40 +2400,272 @@ pub fn compute_migration_plan(
         let constraint = new_rel.name.as_deref().unwrap_or(&new_rel.id);
         let on_update = new_rel.on_update.as_deref().unwrap_or("NO ACTION");
         let on_delete = new_rel.on_delete.as_deref().unwrap_or("NO ACTION");
-        steps.push(MigrationStep {
-            step: 0,
-            operation: MigrationOperation::AddForeignKey,
-            schema: from_schema_str.to_string(),
-            table: Some(from_table.name.clone()),
-            object: constraint.to_string(),
-            object_type: "foreign_key".into(),
-            from_object: None,
-            description: format!(
+        steps.push(MigrationStep::new(
+            MigrationOperation::AddForeignKey,
+            from_schema_str.to_string(),
+            Some(from_table.name.clone()),
+            constraint.to_string(),
+            "foreign_key",
+            format!(
                 "Add FK \"{}\" on \"{}\".\"{}\" → \"{}\".\"{}\"",
                 constraint, from_schema_str, from_table.name, to_schema_name, to_table_name
             ),
-            ddl: Some(format!(
-                "ALTER TABLE {} ADD CONSTRAINT {} FOREIGN KEY ({}) REFERENCES {} ({}) ON UPDATE {} ON DELETE {}",
-                from_q, quote(constraint), quote(&from_col), to_q, quote(&to_col), on_update, on_delete
-            )),
-            safety: MigrationSafety::BestEffort,
-            risk: MigrationRisk::None,
-            risk_detail: Some("PostgreSQL has no ADD CONSTRAINT IF NOT EXISTS; ignored if constraint already exists.".into()),
-        });
+        )
+        .with_ddl(format!(
+            "ALTER TABLE {} ADD CONSTRAINT {} FOREIGN KEY ({}) REFERENCES {} ({}) ON UPDATE {} ON DELETE {}",
+            from_q, quote(constraint), quote(&from_col), to_q, quote(&to_col), on_update, on_delete
+        ))
+        .with_safety(MigrationSafety::BestEffort)
+        .with_risk(
+            MigrationRisk::None,
+            Some("PostgreSQL has no ADD CONSTRAINT IF NOT EXISTS; ignored if constraint already exists.".into()),
+        ));
     }
 
-    // Assign sequential step numbers
+    number_steps(&mut steps, dialect);
+    Ok(MigrationPlan { steps })
+}
+
+/// Assign sequential step numbers and, where the dialect reports them, expected lock levels.
+pub(crate) fn number_steps(steps: &mut [MigrationStep], dialect: &dyn Dialect) {
+    let report_locks = dialect.supports_online_ddl();
     for (i, s) in steps.iter_mut().enumerate() {
         s.step = i + 1;
+        if report_locks && s.ddl.is_some() {
+            s.lock_level = Some(expected_lock_level(s));
+        }
     }
+}
 
-    Ok(MigrationPlan { steps })
+// ─── Online (lock-minimising) plans ──────────────────────────────────────────
+
+/// The heaviest PostgreSQL table lock `step`'s DDL takes.
+pub fn expected_lock_level(step: &MigrationStep) -> LockLevel {
+    let concurrently = step
+        .ddl
+        .as_deref()
+        .is_some_and(|d| d.contains(" CONCURRENTLY "));
+    use MigrationOperation as Op;
+    match step.operation {
+        Op::CreateSchema
+        | Op::CreateEnum
+        | Op::DropEnum
+        | Op::AddEnumValue
+        | Op::RemoveEnumValue
+        | Op::CreateTable => LockLevel::None,
+        Op::BackfillNulls | Op::BackfillShadowColumn => LockLevel::RowExclusive,
+        Op::CreateIndex | Op::DropIndex if concurrently => LockLevel::ShareUpdateExclusive,
+        Op::ValidateConstraint => LockLevel::ShareUpdateExclusive,
+        Op::CreateIndex => LockLevel::Share,
+        Op::AddForeignKey => LockLevel::ShareRowExclusive,
+        Op::DropTable
+        | Op::AddColumn
+        | Op::DropColumn
+        | Op::RenameColumn
+        | Op::AlterColumnType
+        | Op::SetNotNull
+        | Op::DropNotNull
+        | Op::SetDefault
+        | Op::DropDefault
+        | Op::DropIndex
+        | Op::DropForeignKey
+        | Op::AddCheckConstraint
+        | Op::DropConstraint
+        | Op::AddShadowColumn => LockLevel::AccessExclusive,
+    }
+}
+
+/// Rewrite a standard plan for large, live PostgreSQL tables. Only meaningful for dialects where
+/// [`Dialect::supports_online_ddl`] is true; callers check that first.
+///
+/// - Indexes are created and dropped `CONCURRENTLY`, outside any transaction.
+/// - Foreign keys are added `NOT VALID` (a brief lock, no scan) and checked by a separate
+///   `VALIDATE CONSTRAINT` step that lets reads and writes carry on.
+/// - `SET NOT NULL` is preceded by a `CHECK (col IS NOT NULL) NOT VALID` constraint that is
+///   validated the same way; PostgreSQL 12+ then skips the full-table scan under
+///   `ACCESS EXCLUSIVE`, and the helper constraint is dropped afterwards.
+/// - `BackfillNulls` updates at most `backfill_batch_size` rows per statement.
+/// - Every step runs with `lock_timeout`; steps that block reads or writes (and each backfill
+///   batch) also get `statement_timeout`.
+///
+/// Steps come back renumbered and annotated with their [`LockLevel`].
+pub fn to_online_plan(plan: MigrationPlan, opts: &OnlineMigrationOptions) -> MigrationPlan {
+    let mut steps: Vec<MigrationStep> = Vec::with_capacity(plan.steps.len());
+    for step in plan.steps {
+        match step.operation {
+            MigrationOperation::CreateIndex => steps.push(concurrent_index_step(
+                step,
+                "INDEX IF NOT EXISTS",
+                "INDEX CONCURRENTLY IF NOT EXISTS",
+            )),
+            MigrationOperation::DropIndex => steps.push(concurrent_index_step(
+                step,
+                "DROP INDEX IF EXISTS",
+                "DROP INDEX CONCURRENTLY IF EXISTS",
+            )),
+            MigrationOperation::AddForeignKey if step.ddl.is_some() => {
+                steps.extend(not_valid_foreign_key_steps(step))
+            }
+            MigrationOperation::SetNotNull if step.ddl.is_some() => {
+                steps.extend(checked_not_null_steps(step))
+            }
+            MigrationOperation::BackfillNulls => {
+                steps.push(batched_backfill_step(step, opts.backfill_batch_size))
+            }
+            _ => steps.push(step),
+        }
+    }
+    for (i, s) in steps.iter_mut().enumerate() {
+        s.step = i + 1;
+        if s.ddl.is_none() {
+            continue;
+        }
+        let lock = expected_lock_level(s);
+        s.lock_level = Some(lock);
+        s.execution.lock_timeout_ms = Some(opts.lock_timeout_ms);
+        if lock != LockLevel::ShareUpdateExclusive {
+            s.execution.statement_timeout_ms = opts.statement_timeout_ms;
+        }
+    }
+    MigrationPlan { steps }
+}
+
+fn concurrent_index_step(mut step: MigrationStep, plain: &str, concurrent: &str) -> MigrationStep {
+    let Some(ddl) = step.ddl.as_deref().filter(|d| d.contains(plain)) else {
+        return step;
+    };
+    step.ddl = Some(ddl.replacen(plain, concurrent, 1));
+    step.execution.outside_transaction = true;
+    if matches!(step.operation, MigrationOperation::CreateIndex) {
+        step.risk_detail = Some(
+            "A failed concurrent build leaves an INVALID index behind that IF NOT EXISTS will \
+             not replace — drop it before retrying."
+                .into(),
+        );
+    }
+    step
+}
+
+fn quoted_step_table(step: &MigrationStep) -> String {
+    format!(
+        "{}.{}",
+        quote(&step.schema),
+        quote(step.table.as_deref().unwrap_or_default())
+    )
+}
+
+fn not_valid_foreign_key_steps(mut add: MigrationStep) -> Vec<MigrationStep> {
+    let constraint = add.object.clone();
+    add.ddl = add.ddl.map(|d| format!("{} NOT VALID", d));
+    let validate = MigrationStep::new(
+        MigrationOperation::ValidateConstraint,
+        add.schema.clone(),
+        add.table.clone(),
+        constraint.clone(),
+        "foreign_key",
+        format!(
+            "Validate FK \"{}\" on \"{}\".\"{}\" against existing rows",
+            constraint,
+            add.schema,
+            add.table.as_deref().unwrap_or_default()
+        ),
+    )
+    .with_ddl(format!(
+        "ALTER TABLE {} VALIDATE CONSTRAINT {}",
+        quoted_step_table(&add),
+        quote(&constraint)
+    ))
+    .with_safety(MigrationSafety::BestEffort)
+    .with_risk(
+        MigrationRisk::MayFail,
+        Some(
+            "Fails if existing rows violate the key. The constraint then stays NOT VALID: \
+             enforced for new writes only."
+                .into(),
+        ),
+    );
+    vec![add, validate]
+}
+
+/// Name of the helper `CHECK (col IS NOT NULL)` constraint. PostgreSQL truncates long names the
+/// same way in every statement, so all three helper steps agree on it.
+fn not_null_check_name(table: &str, column: &str) -> String {
+    format!("{}_{}_not_null", table, column)
+}
+
+fn checked_not_null_steps(set_not_null: MigrationStep) -> Vec<MigrationStep> {
+    let table = set_not_null.table.as_deref().unwrap_or_default();
+    let column = set_not_null.object.as_str();
+    let full = quoted_step_table(&set_not_null);
+    let check = quote(&not_null_check_name(table, column));
+    let helper = |operation, description: String, ddl: String| {
+        MigrationStep::new(
+            operation,
+            set_not_null.schema.clone(),
+            set_not_null.table.clone(),
+            // The column, not the constraint: reconciliation skips the helpers once it is NOT NULL.
+            column.to_string(),
+            "not_null_check",
+            description,
+        )
+        .with_ddl(ddl)
+    };
+    let target = format!("\"{}\".\"{}\".\"{}\"", set_not_null.schema, table, column);
+    let add = helper(
+        MigrationOperation::AddCheckConstraint,
+        format!("Add NOT VALID IS NOT NULL check on {}", target),
+        format!(
+            "ALTER TABLE {} ADD CONSTRAINT {} CHECK ({} IS NOT NULL) NOT VALID",
+            full,
+            check,
+            quote(column)
+        ),
+    );
+    // Validation is where existing NULLs surface, so it inherits the original step's risk.
+    let mut validate = helper(
+        MigrationOperation::ValidateConstraint,
+        format!(
+            "Validate IS NOT NULL check on {} against existing rows",
+            target
+        ),
+        format!("ALTER TABLE {} VALIDATE CONSTRAINT {}", full, check),
+    );
+    validate.safety = set_not_null.safety.clone();
+    validate.risk = set_not_null.risk.clone();
+    validate.risk_detail = set_not_null.risk_detail.clone();
+    let drop = helper(
+        MigrationOperation::DropConstraint,
+        format!("Drop the IS NOT NULL helper check on {}", target),
+        format!("ALTER TABLE {} DROP CONSTRAINT IF EXISTS {}", full, check),
+    );
+    vec![add, validate, set_not_null, drop]
+}
+
+fn batched_backfill_step(mut step: MigrationStep, batch_size: u64) -> MigrationStep {
+    let full = quoted_step_table(&step);
+    let where_clause = format!(" WHERE {} IS NULL", quote(&step.object));
+    let Some(head) = step
+        .ddl
+        .as_deref()
+        .and_then(|d| d.strip_suffix(where_clause.as_str()))
+    else {
+        return step;
+    };
+    step.ddl = Some(format!(
+        "{} WHERE ctid = ANY(ARRAY(SELECT ctid FROM {}{} LIMIT {}))",
+        head, full, where_clause, batch_size
+    ));
+    step.execution.batch_size = Some(batch_size);
+    step
 }
 
 // ─── Plan reconciliation against the physical database ───────────────────────
 
-/// Whether a plan step still has work to do against a particular database.
-#[derive(Debug, Clone, PartialEq, Eq)]
+/// Whether a plan step still has work to do against a particular database. Also the decision an
+/// approver records for a step (see [`crate::approval::StepOverride`]), serialized as
+/// `{"decision": "execute"}` or `{"decision": "skip", "reason": "…"}`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "decision", content = "reason", rename_all = "snake_case")]
 pub enum StepDecision {
     /// Run the step's DDL.
     Execute,
@@ -2256,17 +2709,30 @@ pub fn reconcile_step(step: &MigrationStep, snap: &DbSnapshot) -> StepDecision {
         Op::CreateTable if table_known => {
             Some(format!("table \"{}\".\"{}\" already exists", schema, table))
         }
-        Op::AddColumn if column.is_some() => Some(format!(
+        Op::AddColumn | Op::AddShadowColumn if column.is_some() => Some(format!(
             "column \"{}\" already exists on \"{}\".\"{}\"",
             object, schema, table
         )),
         Op::RenameColumn => rename_skip_reason(step, snap, schema, table, table_known),
 
         // A column that is gone cannot be retyped, defaulted or backfilled.
-        Op::AlterColumnType | Op::BackfillNulls | Op::SetDefault if column_gone => {
+        Op::AlterColumnType | Op::BackfillNulls | Op::BackfillShadowColumn | Op::SetDefault
+            if column_gone =>
+        {
             Some(missing_column())
         }
+        // Only expand/contract plans drop columns (standard plans leave them as warnings).
+        Op::DropColumn if column_gone => Some(missing_column()),
 
+        // The online-mode helpers around SET NOT NULL (their `object` is the column).
+        Op::AddCheckConstraint | Op::ValidateConstraint
+            if step.object_type == "not_null_check" && column.is_some_and(|f| !f.nullable) =>
+        {
+            Some(format!(
+                "column \"{}\".\"{}\".\"{}\" is already NOT NULL",
+                schema, table, object
+            ))
+        }
         Op::SetNotNull if column.is_some_and(|f| !f.nullable) => Some(format!(
             "column \"{}\".\"{}\".\"{}\" is already NOT NULL",
             schema, table, object
@@ -2347,6 +2813,17 @@ fn apply_step_to_snapshot(step: &MigrationStep, snap: &mut DbSnapshot) {
     let table = step.table.as_deref();
     // `from_object` rides in the tuple so the rename arm needs no nested conditional.
     match (step.operation.clone(), table, step.from_object.as_deref()) {
+        (MigrationOperation::AddShadowColumn, Some(t), _) => snap.add_column(
+            schema,
+            t,
+            object,
+            ColumnFacts {
+                data_type: String::new(),
+                nullable: true,
+                has_default: false,
+            },
+        ),
+        (MigrationOperation::DropColumn, Some(t), _) => snap.remove_column(schema, t, object),
         (MigrationOperation::AddColumn, Some(t), _) => {
             let ddl = step.ddl.as_deref().unwrap_or("").to_uppercase();
             snap.add_column(
@@ -2425,7 +2902,33 @@ async fn audit_step(
     step: &MigrationStep,
     status: &str,
     error_message: Option<&str>,
+    rows_affected: Option<u64>,
 ) {
+    audit_row(
+        config_pool,
+        ctx,
+        step,
+        status,
+        error_message,
+        rows_affected,
+        None,
+    )
+    .await;
+}
+
+/// [`audit_step`] plus the statement that undoes the step (see [`compensating_step`]).
+async fn audit_row(
+    config_pool: &Pool,
+    ctx: &AuditContext<'_>,
+    step: &MigrationStep,
+    status: &str,
+    error_message: Option<&str>,
+    rows_affected: Option<u64>,
+    compensating_ddl: Option<&str>,
+) {
+    if status != "in_progress" {
+        crate::metrics::record_migration_step(&step.operation.to_string(), status);
+    }
     let _ = crate::store::insert_migration_audit(
         config_pool,
         ctx.migration_plan_id,
@@ -2445,134 +2948,744 @@ async fn audit_step(
         &format!("{:?}", step.risk),
         status,
         error_message,
+        rows_affected.map(|n| n as i64),
+        compensating_ddl,
     )
     .await;
 }
 
-/// Execute a pre-computed `MigrationPlan` against the tenant database.
-///
-/// The plan is reconciled against the database's actual state first (see [`reconcile_step`]), so
-/// steps whose effect is already present are skipped instead of failing the whole migration.
-/// Writes per-step audit records to the config (architect) database.
-/// Returns counts and any warning messages collected from best-effort failures.
-#[allow(clippy::too_many_arguments)]
-pub async fn execute_migration_plan(
+/// Execute `sql` on `conn`. A batched step is re-run until a batch touches no rows; with
+/// `progress`, an `in_progress` audit row carries the running total after every batch. Returns
+/// the total rows affected.
+#[tracing::instrument(
+    name = "migration.step",
+    skip_all,
+    fields(step = step.step, operation = %step.operation, object = %step.object)
+)]
+async fn run_batches(
+    conn: &mut Connection,
+    step: &MigrationStep,
+    sql: &str,
+    progress: Option<(&Pool, &AuditContext<'_>)>,
+) -> Result<u64, sqlx::Error> {
+    let mut total = 0u64;
+    loop {
+        let rows = sqlx::query(sql).execute(&mut *conn).await?.rows_affected();
+        total += rows;
+        if step.execution.batch_size.is_none() || rows == 0 {
+            return Ok(total);
+        }
+        tracing::info!(
+            step = step.step,
+            rows,
+            total,
+            "migration step batch applied"
+        );
+        if let Some((config_pool, ctx)) = progress {
+            audit_step(config_pool, ctx, step, "in_progress", None, Some(total)).await;
+        }
+    }
+}
+
+/// Run one step's DDL on its own connection, so session settings from
+/// [`StepExecution`] apply to this step alone. Batched steps report progress (see
+/// [`run_batches`]). Returns the total rows affected.
+async fn run_step_sql(
+    pool: &Pool,
+    step: &MigrationStep,
+    sql: &str,
+    config_pool: &Pool,
+    ctx: &AuditContext<'_>,
+) -> Result<u64, sqlx::Error> {
+    let exec = &step.execution;
+    let mut conn = pool.acquire().await?;
+    if let Some(ms) = exec.lock_timeout_ms {
+        sqlx::query(&format!("SET lock_timeout = {}", ms))
+            .execute(&mut *conn)
+            .await?;
+    }
+    if let Some(ms) = exec.statement_timeout_ms {
+        sqlx::query(&format!("SET statement_timeout = {}", ms))
+            .execute(&mut *conn)
+            .await?;
+    }
+
+    let result = run_batches(&mut conn, step, sql, Some((config_pool, ctx))).await;
+
+    if exec.is_session_scoped() {
+        let mut reset_ok = true;
+        for sql in ["RESET lock_timeout", "RESET statement_timeout"] {
+            reset_ok &= sqlx::query(sql).execute(&mut *conn).await.is_ok();
+        }
+        // Never hand a connection with migration timeouts back to the pool.
+        if !reset_ok {
+            let _ = conn.close().await;
+        }
+    }
+    result
+}
+
+/// Schemas touched by the plan's executable steps, for the executor's pre-flight introspection.
+fn plan_schemas(plan: &MigrationPlan) -> Vec<String> {
+    let mut seen: Vec<String> = Vec::new();
+    for step in &plan.steps {
+        if step.ddl.is_some() && !seen.iter().any(|s| s == &step.schema) {
+            seen.push(step.schema.clone());
+        }
+    }
+    seen
+}
+
+/// Execute a pre-computed `MigrationPlan` against the tenant database.
+///
+/// The plan is reconciled against the database's actual state first (see [`reconcile_step`]), so
+/// steps whose effect is already present are skipped instead of failing the whole migration.
+/// Writes per-step audit records to the config (architect) database.
+/// Returns counts and any warning messages collected from best-effort failures.
+#[allow(clippy::too_many_arguments)]
+pub async fn execute_migration_plan(
+    migration_pool: &Pool,
+    config_pool: &Pool,
+    plan: &MigrationPlan,
+    migration_plan_id: &str,
+    package_id: &str,
+    tenant_id: &str,
+    from_version: Option<&str>,
+    to_version: &str,
+    dialect: &dyn Dialect,
+) -> Result<MigrationExecutionResult, AppError> {
+    let mut applied = 0usize;
+    let mut warned = 0usize;
+    let mut skipped = 0usize;
+    let mut warnings: Vec<String> = Vec::new();
+    let mut skips: Vec<String> = Vec::new();
+    let mut completed_steps: Vec<(usize, u64)> = Vec::new();
+
+    let ctx = AuditContext {
+        migration_plan_id,
+        package_id,
+        tenant_id,
+        from_version,
+        to_version,
+    };
+
+    // Pre-flight: what does this database actually look like? Best-effort — on failure the
+    // snapshot stays empty, nothing is skipped, and execution behaves exactly as before.
+    let mut snapshot = crate::db::introspect(migration_pool, dialect, &plan_schemas(plan)).await;
+
+    for step in &plan.steps {
+        let op = step.operation.to_string();
+
+        match step.safety {
+            MigrationSafety::WarnOnly => {
+                let msg = step
+                    .risk_detail
+                    .clone()
+                    .unwrap_or_else(|| step.description.clone());
+                tracing::warn!(step = step.step, %op, "migration plan warning (no DDL)");
+                warnings.push(format!("[Step {}] {}", step.step, msg));
+                audit_step(config_pool, &ctx, step, "skipped", None, None).await;
+                warned += 1;
+            }
+            MigrationSafety::Safe | MigrationSafety::BestEffort => {
+                let Some(ref sql) = step.ddl else {
+                    continue;
+                };
+
+                if let StepDecision::Skip(reason) = reconcile_step(step, &snapshot) {
+                    tracing::info!(step = step.step, %op, %reason, "migration step already satisfied — skipping");
+                    skips.push(format!(
+                        "[Step {}] {} — {}",
+                        step.step, step.description, reason
+                    ));
+                    audit_step(
+                        config_pool,
+                        &ctx,
+                        step,
+                        "skipped_exists",
+                        Some(&reason),
+                        None,
+                    )
+                    .await;
+                    completed_steps.push((step.step, 0));
+                    skipped += 1;
+                    continue;
+                }
+
+                tracing::info!(step = step.step, %op, %sql, "executing migration step");
+                match run_step_sql(migration_pool, step, sql, config_pool, &ctx).await {
+                    Ok(rows) => {
+                        apply_step_to_snapshot(step, &mut snapshot);
+                        let rows = step.execution.batch_size.map(|_| rows);
+                        audit_step(config_pool, &ctx, step, "applied", None, rows).await;
+                        completed_steps.push((step.step, rows.unwrap_or(0)));
+                        applied += 1;
+                    }
+                    Err(e) => {
+                        // The object already exists: the step's effect is in place, whatever the
+                        // config diff believed. Record it and carry on.
+                        if let Some(reason) = duplicate_object_reason(dialect, &e) {
+                            tracing::info!(step = step.step, %op, %reason, "migration step already satisfied — skipping");
+                            skips.push(format!(
+                                "[Step {}] {} — {}",
+                                step.step, step.description, reason
+                            ));
+                            audit_step(
+                                config_pool,
+                                &ctx,
+                                step,
+                                "skipped_exists",
+                                Some(&reason),
+                                None,
+                            )
+                            .await;
+                            apply_step_to_snapshot(step, &mut snapshot);
+                            completed_steps.push((step.step, 0));
+                            skipped += 1;
+                            continue;
+                        }
+
+                        let err_str = e.to_string();
+                        if matches!(step.safety, MigrationSafety::BestEffort) {
+                            tracing::warn!(step = step.step, %op, error = %e, "migration step failed (best-effort, continuing)");
+                            warnings.push(format!(
+                                "[Step {}] {} — Error: {}",
+                                step.step, step.description, err_str
+                            ));
+                            audit_step(config_pool, &ctx, step, "warned", Some(&err_str), None)
+                                .await;
+                            warned += 1;
+                        } else {
+                            audit_step(config_pool, &ctx, step, "failed", Some(&err_str), None)
+                                .await;
+                            return Err(AppError::Db(e));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(MigrationExecutionResult {
+        applied,
+        warned,
+        skipped,
+        warnings,
+        skips,
+        completed_steps,
+    })
+}
+
+// ─── All-or-nothing execution ────────────────────────────────────────────────
+
+/// The step that undoes `step` once it has run, for dialects without transactional DDL.
+/// `None` when the effect cannot be reversed from the plan alone: dropped objects, type
+/// changes, data updates, defaults and nullability whose previous state the step does not carry.
+pub fn compensating_step(step: &MigrationStep, dialect: &dyn Dialect) -> Option<MigrationStep> {
+    use MigrationOperation as Op;
+    step.ddl.as_ref()?;
+    let table = step.table.as_deref()?;
+    let full = format!("{}.{}", quote(&step.schema), quote(table));
+    let object = step.object.clone();
+    let (operation, object, from_object, ddl) = match step.operation {
+        Op::CreateTable => (
+            Op::DropTable,
+            object,
+            None,
+            format!("DROP TABLE IF EXISTS {}", full),
+        ),
+        Op::AddColumn | Op::AddShadowColumn => (
+            Op::DropColumn,
+            object,
+            None,
+            format!("ALTER TABLE {} DROP COLUMN {}", full, quote(&step.object)),
+        ),
+        Op::RenameColumn => {
+            let from = step.from_object.clone()?;
+            let ddl = format!(
+                "ALTER TABLE {} RENAME COLUMN {} TO {}",
+                full,
+                quote(&step.object),
+                quote(&from)
+            );
+            (Op::RenameColumn, from, Some(object), ddl)
+        }
+        Op::CreateIndex => (
+            Op::DropIndex,
+            object,
+            None,
+            dialect.drop_index_sql(&quote(&step.schema), &quote(table), &quote(&step.object)),
+        ),
+        Op::AddForeignKey | Op::AddCheckConstraint => (
+            if matches!(step.operation, Op::AddForeignKey) {
+                Op::DropForeignKey
+            } else {
+                Op::DropConstraint
+            },
+            object,
+            None,
+            format!(
+                "ALTER TABLE {} DROP CONSTRAINT {}",
+                full,
+                quote(&step.object)
+            ),
+        ),
+        _ => return None,
+    };
+    Some(
+        MigrationStep::new(
+            operation,
+            step.schema.clone(),
+            step.table.clone(),
+            object,
+            step.object_type.clone(),
+            format!("Revert step {}: {}", step.step, step.description),
+        )
+        .with_ddl(ddl)
+        .with_safety(MigrationSafety::BestEffort)
+        .with_from_object(from_object)
+        .with_step(step.step),
+    )
+}
+
+/// One audit row held back until an atomic run's transaction has been decided.
+struct PendingAudit<'p> {
+    step: &'p MigrationStep,
+    status: &'static str,
+    error: Option<String>,
+    rows: Option<u64>,
+}
+
+/// Write held-back audit rows. After a rollback, steps that ran are recorded as `rolled_back`.
+async fn flush_audits(
+    config_pool: &Pool,
+    ctx: &AuditContext<'_>,
+    pending: Vec<PendingAudit<'_>>,
+    rolled_back: bool,
+) {
+    for p in pending {
+        let status = if rolled_back && p.status == "applied" {
+            "rolled_back"
+        } else {
+            p.status
+        };
+        audit_step(config_pool, ctx, p.step, status, p.error.as_deref(), p.rows).await;
+    }
+}
+
+/// `SET LOCAL` (or, with `reset`, back to the session value) for the step's timeouts. Scoped to
+/// the enclosing transaction, and undone by `ROLLBACK TO SAVEPOINT`.
+async fn set_local_timeouts(
+    conn: &mut Connection,
+    exec: &StepExecution,
+    reset: bool,
+) -> Result<(), sqlx::Error> {
+    for (name, value) in [
+        ("lock_timeout", exec.lock_timeout_ms),
+        ("statement_timeout", exec.statement_timeout_ms),
+    ] {
+        let Some(ms) = value else { continue };
+        let sql = if reset {
+            format!("SET LOCAL {} TO DEFAULT", name)
+        } else {
+            format!("SET LOCAL {} = {}", name, ms)
+        };
+        sqlx::query(&sql).execute(&mut *conn).await?;
+    }
+    Ok(())
+}
+
+/// Execute a `MigrationPlan` all-or-nothing.
+///
+/// On dialects with transactional DDL (Postgres, SQLite) every step runs inside one transaction.
+/// Each step gets its own savepoint, so an object that already exists and a failed `BestEffort`
+/// step are rolled back alone and recorded as before, while a failed `Safe` step rolls back the
+/// whole plan: every step that had run is audited as `rolled_back`. Steps flagged
+/// `outside_transaction` (`CREATE INDEX CONCURRENTLY`) cannot run in a transaction block; they run
+/// one by one after the commit.
+///
+/// Elsewhere (MySQL, where DDL commits implicitly) steps run one by one and each applied step's
+/// [`compensating_step`] is written to its audit row. If a `Safe` step fails, the applied steps
+/// are undone in reverse order and audited as `compensated`, `compensation_failed`, or
+/// `not_reverted` when no compensating step exists.
+#[allow(clippy::too_many_arguments)]
+pub async fn execute_migration_plan_atomic(
+    migration_pool: &Pool,
+    config_pool: &Pool,
+    plan: &MigrationPlan,
+    migration_plan_id: &str,
+    package_id: &str,
+    tenant_id: &str,
+    from_version: Option<&str>,
+    to_version: &str,
+    dialect: &dyn Dialect,
+) -> Result<MigrationExecutionResult, AppError> {
+    let ctx = AuditContext {
+        migration_plan_id,
+        package_id,
+        tenant_id,
+        from_version,
+        to_version,
+    };
+    if dialect.supports_transactional_ddl() {
+        execute_in_transaction(migration_pool, config_pool, plan, &ctx, dialect).await
+    } else {
+        execute_with_compensation(migration_pool, config_pool, plan, &ctx, dialect).await
+    }
+}
+
+async fn execute_in_transaction(
+    migration_pool: &Pool,
+    config_pool: &Pool,
+    plan: &MigrationPlan,
+    ctx: &AuditContext<'_>,
+    dialect: &dyn Dialect,
+) -> Result<MigrationExecutionResult, AppError> {
+    let mut result = MigrationExecutionResult {
+        applied: 0,
+        warned: 0,
+        skipped: 0,
+        warnings: Vec::new(),
+        skips: Vec::new(),
+        completed_steps: Vec::new(),
+    };
+    let mut snapshot = crate::db::introspect(migration_pool, dialect, &plan_schemas(plan)).await;
+    let mut pending: Vec<PendingAudit> = Vec::new();
+    let mut deferred: Vec<&MigrationStep> = Vec::new();
+
+    let mut tx = migration_pool.begin().await?;
+    for step in &plan.steps {
+        let op = step.operation.to_string();
+        if matches!(step.safety, MigrationSafety::WarnOnly) {
+            let msg = step
+                .risk_detail
+                .clone()
+                .unwrap_or_else(|| step.description.clone());
+            result
+                .warnings
+                .push(format!("[Step {}] {}", step.step, msg));
+            pending.push(PendingAudit {
+                step,
+                status: "skipped",
+                error: None,
+                rows: None,
+            });
+            result.warned += 1;
+            continue;
+        }
+        let Some(ref sql) = step.ddl else {
+            continue;
+        };
+        if step.execution.outside_transaction {
+            deferred.push(step);
+            continue;
+        }
+        if let StepDecision::Skip(reason) = reconcile_step(step, &snapshot) {
+            tracing::info!(step = step.step, %op, %reason, "migration step already satisfied — skipping");
+            result.skips.push(format!(
+                "[Step {}] {} — {}",
+                step.step, step.description, reason
+            ));
+            pending.push(PendingAudit {
+                step,
+                status: "skipped_exists",
+                error: Some(reason),
+                rows: None,
+            });
+            result.completed_steps.push((step.step, 0));
+            result.skipped += 1;
+            continue;
+        }
+
+        tracing::info!(step = step.step, %op, %sql, "executing migration step in transaction");
+        sqlx::query("SAVEPOINT migration_step")
+            .execute(&mut *tx)
+            .await?;
+        let outcome = match set_local_timeouts(&mut tx, &step.execution, false).await {
+            Ok(()) => run_batches(&mut tx, step, sql, None).await,
+            Err(e) => Err(e),
+        };
+        match outcome {
+            Ok(rows) => {
+                sqlx::query("RELEASE SAVEPOINT migration_step")
+                    .execute(&mut *tx)
+                    .await?;
+                set_local_timeouts(&mut tx, &step.execution, true).await?;
+                apply_step_to_snapshot(step, &mut snapshot);
+                let rows = step.execution.batch_size.map(|_| rows);
+                pending.push(PendingAudit {
+                    step,
+                    status: "applied",
+                    error: None,
+                    rows,
+                });
+                result.completed_steps.push((step.step, rows.unwrap_or(0)));
+                result.applied += 1;
+            }
+            Err(e) => {
+                sqlx::query("ROLLBACK TO SAVEPOINT migration_step")
+                    .execute(&mut *tx)
+                    .await?;
+                if let Some(reason) = duplicate_object_reason(dialect, &e) {
+                    result.skips.push(format!(
+                        "[Step {}] {} — {}",
+                        step.step, step.description, reason
+                    ));
+                    pending.push(PendingAudit {
+                        step,
+                        status: "skipped_exists",
+                        error: Some(reason),
+                        rows: None,
+                    });
+                    apply_step_to_snapshot(step, &mut snapshot);
+                    result.completed_steps.push((step.step, 0));
+                    result.skipped += 1;
+                    continue;
+                }
+                let err_str = e.to_string();
+                if matches!(step.safety, MigrationSafety::BestEffort) {
+                    tracing::warn!(step = step.step, %op, error = %e, "migration step failed (best-effort, continuing)");
+                    result.warnings.push(format!(
+                        "[Step {}] {} — Error: {}",
+                        step.step, step.description, err_str
+                    ));
+                    pending.push(PendingAudit {
+                        step,
+                        status: "warned",
+                        error: Some(err_str),
+                        rows: None,
+                    });
+                    result.warned += 1;
+                    continue;
+                }
+                tracing::warn!(step = step.step, %op, error = %e, "migration step failed — rolling back the whole plan");
+                tx.rollback().await?;
+                flush_audits(config_pool, ctx, pending, true).await;
+                audit_step(config_pool, ctx, step, "failed", Some(&err_str), None).await;
+                return Err(AppError::Db(e));
+            }
+        }
+    }
+    tx.commit().await?;
+    flush_audits(config_pool, ctx, pending, false).await;
+
+    // Statements that refuse to run in a transaction block, one at a time after the commit.
+    for step in deferred {
+        let Some(ref sql) = step.ddl else {
+            continue;
+        };
+        if let StepDecision::Skip(reason) = reconcile_step(step, &snapshot) {
+            result.skips.push(format!(
+                "[Step {}] {} — {}",
+                step.step, step.description, reason
+            ));
+            audit_step(
+                config_pool,
+                ctx,
+                step,
+                "skipped_exists",
+                Some(&reason),
+                None,
+            )
+            .await;
+            result.completed_steps.push((step.step, 0));
+            result.skipped += 1;
+            continue;
+        }
+        match run_step_sql(migration_pool, step, sql, config_pool, ctx).await {
+            Ok(_) => {
+                apply_step_to_snapshot(step, &mut snapshot);
+                audit_step(config_pool, ctx, step, "applied", None, None).await;
+                result.completed_steps.push((step.step, 0));
+                result.applied += 1;
+            }
+            Err(e) => {
+                let err_str = e.to_string();
+                if let Some(reason) = duplicate_object_reason(dialect, &e) {
+                    result.skips.push(format!(
+                        "[Step {}] {} — {}",
+                        step.step, step.description, reason
+                    ));
+                    audit_step(
+                        config_pool,
+                        ctx,
+                        step,
+                        "skipped_exists",
+                        Some(&reason),
+                        None,
+                    )
+                    .await;
+                    result.completed_steps.push((step.step, 0));
+                    result.skipped += 1;
+                } else if matches!(step.safety, MigrationSafety::BestEffort) {
+                    result.warnings.push(format!(
+                        "[Step {}] {} — Error: {}",
+                        step.step, step.description, err_str
+                    ));
+                    audit_step(config_pool, ctx, step, "warned", Some(&err_str), None).await;
+                    result.warned += 1;
+                } else {
+                    audit_step(config_pool, ctx, step, "failed", Some(&err_str), None).await;
+                    return Err(AppError::Db(e));
+                }
+            }
+        }
+    }
+    Ok(result)
+}
+
+async fn execute_with_compensation(
     migration_pool: &Pool,
     config_pool: &Pool,
     plan: &MigrationPlan,
-    migration_plan_id: &str,
-    package_id: &str,
-    tenant_id: &str,
-    from_version: Option<&str>,
-    to_version: &str,
+    ctx: &AuditContext<'_>,
     dialect: &dyn Dialect,
 ) -> Result<MigrationExecutionResult, AppError> {
-    let mut applied = 0usize;
-    let mut warned = 0usize;
-    let mut skipped = 0usize;
-    let mut warnings: Vec<String> = Vec::new();
-    let mut skips: Vec<String> = Vec::new();
-
-    let ctx = AuditContext {
-        migration_plan_id,
-        package_id,
-        tenant_id,
-        from_version,
-        to_version,
-    };
-
-    // Pre-flight: what does this database actually look like? Best-effort — on failure the
-    // snapshot stays empty, nothing is skipped, and execution behaves exactly as before.
-    let schemas: Vec<String> = {
-        let mut seen: Vec<String> = Vec::new();
-        for step in &plan.steps {
-            if step.ddl.is_some() && !seen.iter().any(|s| s == &step.schema) {
-                seen.push(step.schema.clone());
-            }
-        }
-        seen
+    let mut result = MigrationExecutionResult {
+        applied: 0,
+        warned: 0,
+        skipped: 0,
+        warnings: Vec::new(),
+        skips: Vec::new(),
+        completed_steps: Vec::new(),
     };
-    let mut snapshot = crate::db::introspect(migration_pool, dialect, &schemas).await;
+    let mut snapshot = crate::db::introspect(migration_pool, dialect, &plan_schemas(plan)).await;
+    let mut undo: Vec<(&MigrationStep, Option<MigrationStep>)> = Vec::new();
 
     for step in &plan.steps {
         let op = step.operation.to_string();
+        if matches!(step.safety, MigrationSafety::WarnOnly) {
+            let msg = step
+                .risk_detail
+                .clone()
+                .unwrap_or_else(|| step.description.clone());
+            result
+                .warnings
+                .push(format!("[Step {}] {}", step.step, msg));
+            audit_step(config_pool, ctx, step, "skipped", None, None).await;
+            result.warned += 1;
+            continue;
+        }
+        let Some(ref sql) = step.ddl else {
+            continue;
+        };
+        if let StepDecision::Skip(reason) = reconcile_step(step, &snapshot) {
+            result.skips.push(format!(
+                "[Step {}] {} — {}",
+                step.step, step.description, reason
+            ));
+            audit_step(
+                config_pool,
+                ctx,
+                step,
+                "skipped_exists",
+                Some(&reason),
+                None,
+            )
+            .await;
+            result.completed_steps.push((step.step, 0));
+            result.skipped += 1;
+            continue;
+        }
 
-        match step.safety {
-            MigrationSafety::WarnOnly => {
-                let msg = step
-                    .risk_detail
-                    .clone()
-                    .unwrap_or_else(|| step.description.clone());
-                tracing::warn!(step = step.step, %op, "migration plan warning (no DDL)");
-                warnings.push(format!("[Step {}] {}", step.step, msg));
-                audit_step(config_pool, &ctx, step, "skipped", None).await;
-                warned += 1;
+        tracing::info!(step = step.step, %op, %sql, "executing migration step");
+        match run_step_sql(migration_pool, step, sql, config_pool, ctx).await {
+            Ok(rows) => {
+                apply_step_to_snapshot(step, &mut snapshot);
+                let rows = step.execution.batch_size.map(|_| rows);
+                let compensation = compensating_step(step, dialect);
+                audit_row(
+                    config_pool,
+                    ctx,
+                    step,
+                    "applied",
+                    None,
+                    rows,
+                    compensation.as_ref().and_then(|c| c.ddl.as_deref()),
+                )
+                .await;
+                undo.push((step, compensation));
+                result.completed_steps.push((step.step, rows.unwrap_or(0)));
+                result.applied += 1;
             }
-            MigrationSafety::Safe | MigrationSafety::BestEffort => {
-                let Some(ref sql) = step.ddl else {
-                    continue;
-                };
-
-                if let StepDecision::Skip(reason) = reconcile_step(step, &snapshot) {
-                    tracing::info!(step = step.step, %op, %reason, "migration step already satisfied — skipping");
-                    skips.push(format!(
+            Err(e) => {
+                let err_str = e.to_string();
+                if let Some(reason) = duplicate_object_reason(dialect, &e) {
+                    result.skips.push(format!(
                         "[Step {}] {} — {}",
                         step.step, step.description, reason
                     ));
-                    audit_step(config_pool, &ctx, step, "skipped_exists", Some(&reason)).await;
-                    skipped += 1;
+                    audit_step(
+                        config_pool,
+                        ctx,
+                        step,
+                        "skipped_exists",
+                        Some(&reason),
+                        None,
+                    )
+                    .await;
+                    apply_step_to_snapshot(step, &mut snapshot);
+                    result.completed_steps.push((step.step, 0));
+                    result.skipped += 1;
                     continue;
                 }
-
-                tracing::info!(step = step.step, %op, %sql, "executing migration step");
-                match sqlx::query(sql).execute(migration_pool).await {
-                    Ok(_) => {
-                        apply_step_to_snapshot(step, &mut snapshot);
-                        audit_step(config_pool, &ctx, step, "applied", None).await;
-                        applied += 1;
-                    }
-                    Err(e) => {
-                        // The object already exists: the step's effect is in place, whatever the
-                        // config diff believed. Record it and carry on.
-                        if let Some(reason) = duplicate_object_reason(dialect, &e) {
-                            tracing::info!(step = step.step, %op, %reason, "migration step already satisfied — skipping");
-                            skips.push(format!(
-                                "[Step {}] {} — {}",
-                                step.step, step.description, reason
-                            ));
-                            audit_step(config_pool, &ctx, step, "skipped_exists", Some(&reason))
-                                .await;
-                            apply_step_to_snapshot(step, &mut snapshot);
-                            skipped += 1;
-                            continue;
-                        }
-
-                        let err_str = e.to_string();
-                        if matches!(step.safety, MigrationSafety::BestEffort) {
-                            tracing::warn!(step = step.step, %op, error = %e, "migration step failed (best-effort, continuing)");
-                            warnings.push(format!(
-                                "[Step {}] {} — Error: {}",
-                                step.step, step.description, err_str
-                            ));
-                            audit_step(config_pool, &ctx, step, "warned", Some(&err_str)).await;
-                            warned += 1;
-                        } else {
-                            audit_step(config_pool, &ctx, step, "failed", Some(&err_str)).await;
-                            return Err(AppError::Db(e));
-                        }
-                    }
+                if matches!(step.safety, MigrationSafety::BestEffort) {
+                    tracing::warn!(step = step.step, %op, error = %e, "migration step failed (best-effort, continuing)");
+                    result.warnings.push(format!(
+                        "[Step {}] {} — Error: {}",
+                        step.step, step.description, err_str
+                    ));
+                    audit_step(config_pool, ctx, step, "warned", Some(&err_str), None).await;
+                    result.warned += 1;
+                    continue;
                 }
+                tracing::warn!(step = step.step, %op, error = %e, "migration step failed — reverting applied steps");
+                audit_step(config_pool, ctx, step, "failed", Some(&err_str), None).await;
+                revert_applied(migration_pool, config_pool, ctx, undo).await;
+                return Err(AppError::Db(e));
             }
         }
     }
+    Ok(result)
+}
 
-    Ok(MigrationExecutionResult {
-        applied,
-        warned,
-        skipped,
-        warnings,
-        skips,
-    })
+/// Run compensating steps for the applied steps of a failed plan, newest first.
+async fn revert_applied(
+    migration_pool: &Pool,
+    config_pool: &Pool,
+    ctx: &AuditContext<'_>,
+    undo: Vec<(&MigrationStep, Option<MigrationStep>)>,
+) {
+    for (step, compensation) in undo.into_iter().rev() {
+        let Some(comp) = compensation else {
+            audit_step(
+                config_pool,
+                ctx,
+                step,
+                "not_reverted",
+                Some("no compensating step for this operation"),
+                None,
+            )
+            .await;
+            continue;
+        };
+        let sql = comp.ddl.as_deref().unwrap_or_default();
+        match run_step_sql(migration_pool, &comp, sql, config_pool, ctx).await {
+            Ok(_) => audit_step(config_pool, ctx, &comp, "compensated", None, None).await,
+            Err(e) => {
+                tracing::warn!(step = step.step, error = %e, "compensating step failed");
+                audit_step(
+                    config_pool,
+                    ctx,
+                    &comp,
+                    "compensation_failed",
+                    Some(&e.to_string()),
+                    None,
+                )
+                .await;
+            }
+        }
+    }
 }
 
 /// Build CREATE TABLE DDL for the `{table}_history` companion table used by row versioning.
@@ -2759,20 +3872,20 @@ fn companion_column_steps(
                 )),
             ),
         };
-        steps.push(MigrationStep {
-            step: 0,
-            operation,
-            schema: schema.to_string(),
-            table: Some(companion.clone()),
-            object,
-            object_type: "column".into(),
-            from_object,
-            description,
-            ddl: Some(ddl),
-            safety,
-            risk,
-            risk_detail,
-        });
+        steps.push(
+            MigrationStep::new(
+                operation,
+                schema.to_string(),
+                Some(companion.clone()),
+                object,
+                "column",
+                description,
+            )
+            .with_ddl(ddl)
+            .with_safety(safety)
+            .with_risk(risk, risk_detail)
+            .with_from_object(from_object),
+        );
     }
     steps
 }
@@ -2837,6 +3950,7 @@ fn audit_table_ddl(
 }
 
 #[cfg(test)]
+#[allow(clippy::field_reassign_with_default)]
 mod enum_recreate_tests {
     use super::*;
 
@@ -2986,6 +4100,10 @@ mod enum_recreate_tests {
 }
 
 #[cfg(all(test, feature = "sqlite"))]
+#[allow(
+    clippy::field_reassign_with_default,
+    clippy::unnecessary_lazy_evaluations
+)]
 mod companion_sync_tests {
     use super::*;
     use crate::db::sqlite::SqliteDialect;
@@ -3144,20 +4262,16 @@ mod reconcile_tests {
     }
 
     fn step(op: MigrationOperation, table: Option<&str>, object: &str) -> MigrationStep {
-        MigrationStep {
-            step: 1,
-            operation: op,
-            schema: "app".into(),
-            table: table.map(String::from),
-            object: object.into(),
-            object_type: "column".into(),
-            from_object: None,
-            description: "test step".into(),
-            ddl: Some("SELECT 1".into()),
-            safety: MigrationSafety::Safe,
-            risk: MigrationRisk::None,
-            risk_detail: None,
-        }
+        MigrationStep::new(
+            op,
+            "app",
+            table.map(String::from),
+            object,
+            "column",
+            "test step",
+        )
+        .with_ddl("SELECT 1")
+        .with_step(1)
     }
 
     fn skipped(d: StepDecision) -> bool {
@@ -3396,6 +4510,251 @@ mod reconcile_tests {
     }
 }
 
+#[cfg(test)]
+mod online_plan_tests {
+    use super::*;
+
+    fn step(op: MigrationOperation, object: &str, ddl: &str) -> MigrationStep {
+        MigrationStep::new(
+            op,
+            "app",
+            Some("orders".into()),
+            object,
+            "column",
+            "test step",
+        )
+        .with_ddl(ddl)
+        .with_safety(MigrationSafety::BestEffort)
+    }
+
+    fn online(steps: Vec<MigrationStep>) -> Vec<MigrationStep> {
+        let opts = OnlineMigrationOptions {
+            lock_timeout_ms: 2_000,
+            statement_timeout_ms: Some(30_000),
+            backfill_batch_size: 500,
+        };
+        to_online_plan(MigrationPlan { steps }, &opts).steps
+    }
+
+    #[test]
+    fn indexes_are_built_concurrently_outside_a_transaction() {
+        let steps = online(vec![step(
+            MigrationOperation::CreateIndex,
+            "idx_orders_status",
+            r#"CREATE INDEX IF NOT EXISTS "idx_orders_status" ON "app"."orders" USING btree ("status")"#,
+        )]);
+        let s = &steps[0];
+        assert!(s
+            .ddl
+            .as_deref()
+            .unwrap()
+            .starts_with("CREATE INDEX CONCURRENTLY IF NOT EXISTS"));
+        assert!(s.execution.outside_transaction);
+        assert_eq!(s.lock_level, Some(LockLevel::ShareUpdateExclusive));
+        assert_eq!(s.execution.lock_timeout_ms, Some(2_000));
+        // A long concurrent build blocks nobody; a statement timeout would only abort it.
+        assert_eq!(s.execution.statement_timeout_ms, None);
+    }
+
+    #[test]
+    fn a_standard_index_build_reports_a_share_lock() {
+        let s = step(
+            MigrationOperation::CreateIndex,
+            "idx",
+            r#"CREATE UNIQUE INDEX IF NOT EXISTS "idx" ON "app"."orders" USING btree ("id")"#,
+        );
+        assert_eq!(expected_lock_level(&s), LockLevel::Share);
+    }
+
+    #[test]
+    fn foreign_keys_are_added_not_valid_then_validated() {
+        let steps = online(vec![step(
+            MigrationOperation::AddForeignKey,
+            "fk_orders_customer",
+            r#"ALTER TABLE "app"."orders" ADD CONSTRAINT "fk_orders_customer" FOREIGN KEY ("customer_id") REFERENCES "app"."customers" ("id") ON UPDATE NO ACTION ON DELETE NO ACTION"#,
+        )]);
+        assert_eq!(steps.len(), 2);
+        assert!(steps[0].ddl.as_deref().unwrap().ends_with(" NOT VALID"));
+        assert_eq!(steps[0].lock_level, Some(LockLevel::ShareRowExclusive));
+        assert!(matches!(
+            steps[1].operation,
+            MigrationOperation::ValidateConstraint
+        ));
+        assert_eq!(
+            steps[1].ddl.as_deref(),
+            Some(r#"ALTER TABLE "app"."orders" VALIDATE CONSTRAINT "fk_orders_customer""#)
+        );
+        assert_eq!(steps[1].lock_level, Some(LockLevel::ShareUpdateExclusive));
+        assert_eq!(steps.iter().map(|s| s.step).collect::<Vec<_>>(), vec![1, 2]);
+    }
+
+    #[test]
+    fn set_not_null_is_backed_by_a_validated_check_constraint() {
+        let mut set = step(
+            MigrationOperation::SetNotNull,
+            "status",
+            r#"ALTER TABLE "app"."orders" ALTER COLUMN "status" SET NOT NULL"#,
+        );
+        set.risk = MigrationRisk::ExistingNullsMustBeAbsent;
+        let steps = online(vec![set]);
+        let ops: Vec<String> = steps.iter().map(|s| s.operation.to_string()).collect();
+        assert_eq!(
+            ops,
+            vec![
+                "add_check_constraint",
+                "validate_constraint",
+                "set_not_null",
+                "drop_constraint"
+            ]
+        );
+        assert_eq!(
+            steps[0].ddl.as_deref(),
+            Some(
+                r#"ALTER TABLE "app"."orders" ADD CONSTRAINT "orders_status_not_null" CHECK ("status" IS NOT NULL) NOT VALID"#
+            )
+        );
+        // Existing NULLs surface at validation, so that is where the risk is reported.
+        assert!(matches!(
+            steps[1].risk,
+            MigrationRisk::ExistingNullsMustBeAbsent
+        ));
+        assert_eq!(steps[1].execution.statement_timeout_ms, None);
+        assert_eq!(steps[2].execution.statement_timeout_ms, Some(30_000));
+    }
+
+    #[test]
+    fn not_null_helpers_are_skipped_once_the_column_is_not_null() {
+        let mut snap = DbSnapshot::default();
+        snap.introspected = true;
+        snap.add_column(
+            "app",
+            "orders",
+            "status",
+            ColumnFacts {
+                data_type: "text".into(),
+                nullable: false,
+                has_default: false,
+            },
+        );
+        let steps = online(vec![step(
+            MigrationOperation::SetNotNull,
+            "status",
+            r#"ALTER TABLE "app"."orders" ALTER COLUMN "status" SET NOT NULL"#,
+        )]);
+        let decisions: Vec<bool> = steps
+            .iter()
+            .map(|s| matches!(reconcile_step(s, &snap), StepDecision::Skip(_)))
+            .collect();
+        // The helper constraint's DROP ... IF EXISTS always runs: it cleans up after a failed run.
+        assert_eq!(decisions, vec![true, true, true, false]);
+    }
+
+    #[test]
+    fn backfills_run_in_bounded_batches() {
+        let steps = online(vec![step(
+            MigrationOperation::BackfillNulls,
+            "status",
+            r#"UPDATE "app"."orders" SET "status" = 'new' WHERE "status" IS NULL"#,
+        )]);
+        assert_eq!(
+            steps[0].ddl.as_deref(),
+            Some(
+                r#"UPDATE "app"."orders" SET "status" = 'new' WHERE ctid = ANY(ARRAY(SELECT ctid FROM "app"."orders" WHERE "status" IS NULL LIMIT 500))"#
+            )
+        );
+        assert_eq!(steps[0].execution.batch_size, Some(500));
+        assert_eq!(steps[0].lock_level, Some(LockLevel::RowExclusive));
+    }
+
+    #[test]
+    fn standard_steps_serialize_without_execution_fields() {
+        let s = step(MigrationOperation::AddColumn, "note", "SELECT 1");
+        let v = serde_json::to_value(&s).unwrap();
+        for key in [
+            "lock_level",
+            "lock_timeout_ms",
+            "statement_timeout_ms",
+            "batch_size",
+            "outside_transaction",
+        ] {
+            assert!(v.get(key).is_none(), "{} should be omitted", key);
+        }
+        let back: MigrationStep = serde_json::from_value(v).unwrap();
+        assert_eq!(back.execution, StepExecution::default());
+    }
+}
+
+#[cfg(test)]
+mod compensation_tests {
+    use super::*;
+
+    fn step(op: MigrationOperation, object: &str, from: Option<&str>) -> MigrationStep {
+        MigrationStep::new(
+            op,
+            "app",
+            Some("orders".into()),
+            object,
+            "column",
+            "test step",
+        )
+        .with_ddl("-- forward")
+        .with_from_object(from.map(String::from))
+        .with_step(3)
+    }
+
+    fn undo(s: &MigrationStep) -> Option<MigrationStep> {
+        compensating_step(s, crate::db::active_dialect().as_ref())
+    }
+
+    #[test]
+    fn additive_steps_are_reverted_by_their_drop() {
+        let c = undo(&step(MigrationOperation::AddColumn, "note", None)).unwrap();
+        assert!(matches!(c.operation, MigrationOperation::DropColumn));
+        assert_eq!(c.step, 3);
+        assert_eq!(
+            c.ddl.as_deref(),
+            Some(r#"ALTER TABLE "app"."orders" DROP COLUMN "note""#)
+        );
+        let c = undo(&step(MigrationOperation::CreateTable, "orders", None)).unwrap();
+        assert_eq!(
+            c.ddl.as_deref(),
+            Some(r#"DROP TABLE IF EXISTS "app"."orders""#)
+        );
+        let c = undo(&step(
+            MigrationOperation::AddForeignKey,
+            "fk_orders_user",
+            None,
+        ))
+        .unwrap();
+        assert!(matches!(c.operation, MigrationOperation::DropForeignKey));
+    }
+
+    #[test]
+    fn a_rename_is_reverted_by_the_reverse_rename() {
+        let c = undo(&step(
+            MigrationOperation::RenameColumn,
+            "total",
+            Some("amount"),
+        ))
+        .unwrap();
+        assert_eq!(c.object, "amount");
+        assert_eq!(c.from_object.as_deref(), Some("total"));
+        assert_eq!(
+            c.ddl.as_deref(),
+            Some(r#"ALTER TABLE "app"."orders" RENAME COLUMN "total" TO "amount""#)
+        );
+    }
+
+    #[test]
+    fn destructive_and_non_ddl_steps_have_no_compensation() {
+        assert!(undo(&step(MigrationOperation::DropColumn, "note", None)).is_none());
+        assert!(undo(&step(MigrationOperation::AlterColumnType, "note", None)).is_none());
+        let mut warn_only = step(MigrationOperation::AddColumn, "note", None);
+        warn_only.ddl = None;
+        assert!(undo(&warn_only).is_none());
+    }
+}
+
 #[cfg(all(test, feature = "postgres"))]
 mod postgres_idempotent_ddl_tests {
     use super::*;
@@ -3422,3 +4781,96 @@ mod postgres_idempotent_ddl_tests {
         assert!(!d.is_duplicate_object_code("42703"));
     }
 }
+
+#[cfg(test)]
+mod render_tests {
+    use super::*;
+    use crate::db::{MySqlDialect, PostgresDialect, SqliteDialect};
+
+    fn config() -> FullConfig {
+        let col = |id: &str, name: &str, ty: &str| ColumnConfig {
+            id: id.into(),
+            table_id: "t1".into(),
+            name: name.into(),
+            type_: ColumnTypeConfig::Simple(ty.into()),
+            nullable: true,
+            default: None,
+            comment: None,
+            asset: None,
+            extensible: false,
+        };
+        FullConfig {
+            schemas: vec![SchemaConfig {
+                id: "s1".into(),
+                name: "app".into(),
+                comment: None,
+            }],
+            enums: vec![EnumConfig {
+                id: "e1".into(),
+                schema_id: Some("s1".into()),
+                name: "order_status".into(),
+                values: vec!["open".into(), "closed".into()],
+                comment: None,
+            }],
+            tables: vec![TableConfig {
+                id: "t1".into(),
+                schema_id: Some("s1".into()),
+                name: "orders".into(),
+                comment: None,
+                primary_key: PrimaryKeyConfig::Single("id".into()),
+                unique: vec![],
+                check: vec![],
+                audit_log: false,
+                versioning: None,
+                global: false,
+            }],
+            columns: vec![col("c0", "id", "uuid"), col("c1", "doc", "jsonb")],
+            ..Default::default()
+        }
+    }
+
+    fn render(dialect: &dyn Dialect, rls: Option<&str>) -> Vec<DdlStatement> {
+        render_migrations(&config(), None, rls, dialect, &HashMap::new()).unwrap()
+    }
+
+    #[test]
+    fn every_dialect_renders_regardless_of_the_compiled_one() {
+        let pg = render(&PostgresDialect, None);
+        assert_eq!(pg[0].sql, r#"CREATE SCHEMA IF NOT EXISTS "app""#);
+        assert!(pg.iter().any(|s| s.best_effort
+            && s.sql == r#"CREATE TYPE "app"."order_status" AS ENUM ('open', 'closed')"#));
+        let create = pg
+            .iter()
+            .find(|s| s.sql.starts_with("CREATE TABLE"))
+            .unwrap();
+        assert!(!create.best_effort);
+        assert!(create.sql.contains(r#""doc" JSONB"#), "{}", create.sql);
+
+        let lite = render(&SqliteDialect, None);
+        assert!(!lite.iter().any(|s| s.sql.starts_with("CREATE SCHEMA")));
+        assert!(!lite.iter().any(|s| s.sql.starts_with("CREATE TYPE")));
+        assert!(lite.iter().any(|s| s.sql.contains(r#""doc" TEXT"#)));
+
+        let my = render(&MySqlDialect, None);
+        assert!(!my.iter().any(|s| s.sql.starts_with("CREATE TYPE")));
+        assert!(my.iter().any(|s| s.sql.contains(r#""doc" JSON"#)));
+    }
+
+    #[test]
+    fn rls_policies_render_only_where_supported() {
+        let pg = render(&PostgresDialect, Some(RLS_TENANT_COLUMN));
+        assert!(pg
+            .iter()
+            .any(|s| s.sql.contains("ENABLE ROW LEVEL SECURITY")));
+        assert_eq!(
+            pg.iter()
+                .filter(|s| s.sql.starts_with("CREATE POLICY"))
+                .count(),
+            4
+        );
+        assert_eq!(
+            render(&SqliteDialect, Some(RLS_TENANT_COLUMN)),
+            render(&SqliteDialect, None)
+        );
+    }
+}
@@ -189,6 +189,23 @@ fn package_id_param() -> Parameter {
         .build()
 }
 
+/// `?consistency=strong` reads from the primary instead of a read replica.
+fn consistency_param() -> utoipa::openapi::path::Parameter {
+    ParameterBuilder::new()
+        .name("consistency")
+        .parameter_in(ParameterIn::Query)
+        .required(Required::False)
+        .description(Some(
+            "`strong` reads from the primary; default `eventual` may read from a replica",
+        ))
+        .schema(Some(RefOr::T(Schema::Object(
+            utoipa::openapi::schema::ObjectBuilder::new()
+                .schema_type(SchemaType::new(Type::String))
+                .into(),
+        ))))
+        .build()
+}
+
 fn list_operation(
     entity: &ResolvedEntity,
     op_suffix: &str,
@@ -234,6 +251,7 @@ fn list_operation(
                     .into(),
             ))))
             .build(),
+        consistency_param(),
     ]);
     for col in &entity.columns {
         if entity.sensitive_columns.contains(&col.name) {
@@ -335,6 +353,7 @@ fn read_operation(
         .build();
     params.push(id_param);
     params.push(include_param);
+    params.push(consistency_param());
     OperationBuilder::new()
         .summary(Some(format!("Get {} by id", entity.path_segment)))
         .description(Some(format!("Get a single {} by id.", entity.path_segment)))
@@ -1030,7 +1049,7 @@ fn add_config_paths(mut builder: PathsBuilder, base: &str) -> PathsBuilder {
     let install_op = OperationBuilder::new()
         .summary(Some("Install package"))
         .description(Some(
-            "Upload a package zip. Zip must contain manifest.json (id, name, version, schema) at root and config JSON files. Use multipart/form-data with field 'file' or 'package' (ZIP file).",
+            "Upload a package zip. Zip must contain manifest.json (id, name, version, schema, optional dependencies as package id → semver range) at root and config JSON files. Use multipart/form-data with field 'file' or 'package' (ZIP file).",
         ))
         .operation_id(Some("config_install_package"))
         .parameters(Some(vec![x_tenant_id_header()]))
@@ -1070,6 +1089,71 @@ fn add_config_paths(mut builder: PathsBuilder, base: &str) -> PathsBuilder {
     let install_item = PathItemBuilder::new().operation(HttpMethod::Post, install_op);
     builder = builder.path(install_path, install_item.build());
 
+    let bulk_path = format!("{}/config/package/bulk", base);
+    let bulk_op = OperationBuilder::new()
+        .summary(Some("Install several packages"))
+        .description(Some(
+            "Upload several package zips, one 'file' or 'package' field each. They are installed in dependency order (manifest 'dependencies'); the first failure stops the batch and the remaining packages are reported as skipped.",
+        ))
+        .operation_id(Some("config_install_packages_bulk"))
+        .parameters(Some(vec![x_tenant_id_header()]))
+        .responses(
+            ResponsesBuilder::new()
+                .response("200", Response::new("Per-package install results"))
+                .response("400", Response::new("Bad Request"))
+                .build(),
+        )
+        .build();
+    builder = builder.path(
+        bulk_path,
+        PathItemBuilder::new()
+            .operation(HttpMethod::Post, bulk_op)
+            .build(),
+    );
+
+    let render_path = format!("{}/config/package/render-ddl", base);
+    let render_op = OperationBuilder::new()
+        .summary(Some("Render package DDL"))
+        .description(Some(
+            "Upload a package zip ('file' or 'package' field). Returns the DDL a fresh install would run on the dialect given by ?dialect= (postgres, mysql or sqlite; default: the server's), in order. Nothing is executed. ?rls=true also renders the RLS tenant column and policies.",
+        ))
+        .operation_id(Some("config_render_package_ddl"))
+        .responses(
+            ResponsesBuilder::new()
+                .response("200", Response::new("Ordered DDL statements"))
+                .response("400", Response::new("Bad Request"))
+                .build(),
+        )
+        .build();
+    builder = builder.path(
+        render_path,
+        PathItemBuilder::new()
+            .operation(HttpMethod::Post, render_op)
+            .build(),
+    );
+
+    let pools_path = format!("{}/config/tenant-pools", base);
+    let pools_op = OperationBuilder::new()
+        .summary(Some("Tenant pool statistics"))
+        .description(Some(
+            "Open and idle connections, limits and usage of each cached tenant pool, plus the idle/LRU eviction counters and connection budget. Platform Admin only.",
+        ))
+        .operation_id(Some("config_tenant_pool_stats"))
+        .parameters(Some(vec![x_tenant_id_header()]))
+        .responses(
+            ResponsesBuilder::new()
+                .response("200", Response::new("Pool statistics"))
+                .response("403", Response::new("Forbidden"))
+                .build(),
+        )
+        .build();
+    builder = builder.path(
+        pools_path,
+        PathItemBuilder::new()
+            .operation(HttpMethod::Get, pools_op)
+            .build(),
+    );
+
     let uninstall_path = format!("{}/config/package/{{packageId}}", base);
     let uninstall_op = OperationBuilder::new()
         .summary(Some("Uninstall package"))
@@ -1234,6 +1318,7 @@ mod tests {
             versioning: None,
             mcp: None,
             extensible_columns,
+            shadow_columns: Vec::new(),
         }
     }
 
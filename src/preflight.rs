@@ -0,0 +1,388 @@
+//! Data-loss pre-flight probes for migration preview.
+//!
+//! A computed plan only labels its risky steps (`ExistingNullsMustBeAbsent`, `MayFail`, …). The
+//! probes here look at the rows actually in the tenant database and append what they find —
+//! offending row counts and a few sample primary keys — to each step's `risk_detail`:
+//!
+//! - `SetNotNull` without a backfill: rows that are NULL.
+//! - `BackfillNulls`: rows the backfill will overwrite.
+//! - `AlterColumnType`: the cast is tried on a sample of rows; failing rows are located by
+//!   bisecting the sample.
+//! - `CreateIndex` on a unique index: rows that share their key with another row.
+//! - `RemoveEnumValue`: rows of each dependent column that hold a removed value.
+//!
+//! Probes are read-only and best-effort: a probe that fails (missing table, unsupported
+//! expression) leaves the step as it was. In a database shared by several tenants (RLS), counts
+//! cover every tenant's rows, so sample primary keys are left out.
+
+use crate::config::{ColumnConfig, FullConfig, IndexColumnEntry, PrimaryKeyConfig, TableConfig};
+use crate::db::pool::Pool;
+use crate::db::{parse_canonical, CanonicalType, Dialect};
+use crate::migration::{quote, MigrationOperation, MigrationPlan, MigrationRisk, MigrationStep};
+use serde_json::Value;
+
+/// Primary keys listed per finding.
+pub const SAMPLE_PKS: usize = 5;
+/// Non-null rows a type-change cast is tried on.
+pub const CAST_SAMPLE_ROWS: u64 = 200;
+
+/// Run the pre-flight probes for every risky step of `plan` against `pool` and append the
+/// findings to the steps' `risk_detail`. `config` is the target config the plan migrates to.
+/// `sample_pks` lists a few offending primary keys; pass false when `pool` holds other tenants'
+/// rows.
+pub async fn probe_plan(
+    pool: &Pool,
+    plan: &mut MigrationPlan,
+    config: &FullConfig,
+    dialect: &dyn Dialect,
+    sample_pks: bool,
+) {
+    for i in 0..plan.steps.len() {
+        let finding = match plan.steps[i].operation {
+            MigrationOperation::SetNotNull
+                if matches!(plan.steps[i].risk, MigrationRisk::ExistingNullsMustBeAbsent) =>
+            {
+                probe_nulls(pool, &plan.steps[i], config, "hold NULL", sample_pks).await
+            }
+            MigrationOperation::BackfillNulls => {
+                let step = &plan.steps[i];
+                probe_nulls(pool, step, config, "will be overwritten", sample_pks).await
+            }
+            MigrationOperation::AlterColumnType => {
+                probe_cast(pool, &plan.steps[i], config, dialect, sample_pks).await
+            }
+            MigrationOperation::CreateIndex => {
+                probe_duplicates(pool, &plan.steps[i], config, sample_pks).await
+            }
+            MigrationOperation::RemoveEnumValue => {
+                let (step, rest) = (&plan.steps[i], &plan.steps[i + 1..]);
+                probe_enum_values(pool, step, rest, config, sample_pks).await
+            }
+            _ => None,
+        };
+        if let Some(finding) = finding {
+            let step = &mut plan.steps[i];
+            step.risk_detail = Some(match step.risk_detail.take() {
+                Some(detail) => format!("{} Pre-flight: {}", detail, finding),
+                None => format!("Pre-flight: {}", finding),
+            });
+        }
+    }
+}
+
+async fn probe_nulls(
+    pool: &Pool,
+    step: &MigrationStep,
+    config: &FullConfig,
+    verb: &str,
+    sample_pks: bool,
+) -> Option<String> {
+    step.ddl.as_ref()?;
+    let table = find_table(config, step.table.as_deref()?)?;
+    let from = format!("{}.{}", quote(&step.schema), quote(&table.name));
+    let filter = format!("{} IS NULL", quote(&step.object));
+    let (count, pks) = count_and_sample(pool, &from, &filter, table, sample_pks).await?;
+    Some(match count {
+        0 => format!("no rows hold NULL in \"{}\".", step.object),
+        n => format!(
+            "{} row(s) {} in \"{}\"{}.",
+            n,
+            verb,
+            step.object,
+            sample_suffix(&pks)
+        ),
+    })
+}
+
+async fn probe_cast(
+    pool: &Pool,
+    step: &MigrationStep,
+    config: &FullConfig,
+    dialect: &dyn Dialect,
+    sample_pks: bool,
+) -> Option<String> {
+    step.ddl.as_ref()?;
+    let table = find_table(config, step.table.as_deref()?)?;
+    let column = find_column(config, table, &step.object)?;
+    let canonical = parse_canonical(&column.type_);
+    // Enum recasts target a type that only exists once the plan has run.
+    if matches!(
+        canonical,
+        CanonicalType::Custom(_) | CanonicalType::Array(_)
+    ) {
+        return None;
+    }
+    let ddl_type = dialect.ddl_type(&canonical);
+    let from = format!("{}.{}", quote(&step.schema), quote(&table.name));
+    let col = quote(&step.object);
+    let order = pk_columns(table).join(", ");
+    let cast = dialect.assignment_cast(&col, &ddl_type);
+    let window = |offset: u64, len: u64| {
+        format!(
+            "SELECT COUNT({}) FROM (SELECT {} FROM {} WHERE {} IS NOT NULL ORDER BY {} LIMIT {} OFFSET {}) s",
+            cast, col, from, col, order, len, offset
+        )
+    };
+
+    let (sampled,): (i64,) = sqlx::query_as(&format!(
+        "SELECT COUNT(*) FROM (SELECT {} FROM {} WHERE {} IS NOT NULL LIMIT {}) s",
+        col, from, col, CAST_SAMPLE_ROWS
+    ))
+    .fetch_one(pool)
+    .await
+    .ok()?;
+    let sampled = sampled as u64;
+    if sampled == 0 {
+        return Some("no non-null rows to cast.".into());
+    }
+    let first_error = match sqlx::query(&window(0, sampled)).execute(pool).await {
+        Ok(_) => {
+            return Some(format!(
+                "the cast to {} succeeded on all {} sampled non-null row(s).",
+                ddl_type, sampled
+            ))
+        }
+        Err(e) => e,
+    };
+
+    // Bisect the sample for failing rows: a window whose cast errors holds at least one.
+    let mut failing: Vec<u64> = Vec::new();
+    let mut pending = vec![(0u64, sampled)];
+    while let Some((offset, len)) = pending.pop() {
+        if len == 0
+            || sqlx::query(&window(offset, len))
+                .execute(pool)
+                .await
+                .is_ok()
+        {
+            continue;
+        }
+        if len == 1 {
+            failing.push(offset);
+            continue;
+        }
+        let half = len / 2;
+        pending.push((offset + half, len - half));
+        pending.push((offset, half));
+    }
+    failing.sort_unstable();
+
+    let mut pks = Vec::new();
+    let shown = if sample_pks { SAMPLE_PKS } else { 0 };
+    for offset in failing.iter().take(shown) {
+        let sql = format!(
+            "SELECT {} FROM {} WHERE {} IS NOT NULL ORDER BY {} LIMIT 1 OFFSET {}",
+            order, from, col, order, offset
+        );
+        if let Ok(row) = sqlx::query(&sql).fetch_one(pool).await {
+            pks.push(pk_value(&row));
+        }
+    }
+    Some(format!(
+        "{} of {} sampled non-null row(s) fail the cast to {}{} (first error: {}).",
+        failing.len(),
+        sampled,
+        ddl_type,
+        sample_suffix(&pks),
+        first_error
+    ))
+}
+
+async fn probe_duplicates(
+    pool: &Pool,
+    step: &MigrationStep,
+    config: &FullConfig,
+    sample_pks: bool,
+) -> Option<String> {
+    step.ddl.as_ref()?;
+    let index = config
+        .indexes
+        .iter()
+        .find(|i| i.name == step.object && i.unique)?;
+    let table = config.tables.iter().find(|t| t.id == index.table_id)?;
+    let mut cols = Vec::new();
+    for entry in &index.columns {
+        match entry {
+            IndexColumnEntry::Name(n) | IndexColumnEntry::Spec { name: n, .. } => {
+                cols.push(quote(n))
+            }
+            IndexColumnEntry::Expression { .. } => return None,
+        }
+    }
+    let from = format!("{}.{}", quote(&step.schema), quote(&table.name));
+    let mut key_filter: Vec<String> = cols.iter().map(|c| format!("{} IS NOT NULL", c)).collect();
+    if let Some(w) = index.where_clause() {
+        key_filter.push(format!("({})", w));
+    }
+    let group = cols.join(", ");
+    let on: Vec<String> = cols.iter().map(|c| format!("a.{c} = d.{c}")).collect();
+    let pks: Vec<String> = pk_columns(table)
+        .iter()
+        .map(|pk| format!("a.{}", pk))
+        .collect();
+    let joined = format!(
+        "{} a JOIN (SELECT {} FROM {} WHERE {} GROUP BY {} HAVING COUNT(*) > 1) d ON {}",
+        from,
+        group,
+        from,
+        key_filter.join(" AND "),
+        group,
+        on.join(" AND ")
+    );
+
+    let (count,): (i64,) = sqlx::query_as(&format!("SELECT COUNT(*) FROM {}", joined))
+        .fetch_one(pool)
+        .await
+        .ok()?;
+    if count == 0 {
+        return Some(format!("no duplicate values in ({}).", group));
+    }
+    if !sample_pks {
+        return Some(format!(
+            "{} row(s) share their ({}) value with another row.",
+            count, group
+        ));
+    }
+    let sample = format!(
+        "SELECT {} FROM {} ORDER BY {} LIMIT {}",
+        pks.join(", "),
+        joined,
+        pks.join(", "),
+        SAMPLE_PKS
+    );
+    let rows = sqlx::query(&sample).fetch_all(pool).await.ok()?;
+    let pk_values: Vec<Value> = rows.iter().map(pk_value).collect();
+    Some(format!(
+        "{} row(s) share their ({}) value with another row{}.",
+        count,
+        group,
+        sample_suffix(&pk_values)
+    ))
+}
+
+/// Rows still holding a removed value, for each column recast by the enum rebuild that follows.
+async fn probe_enum_values(
+    pool: &Pool,
+    step: &MigrationStep,
+    rest: &[MigrationStep],
+    config: &FullConfig,
+    sample_pks: bool,
+) -> Option<String> {
+    let (enum_name, removed) = step.object.split_once(':')?;
+    let values: Vec<String> = removed
+        .split(',')
+        .map(|v| format!("'{}'", v.replace('\'', "''")))
+        .collect();
+    let mut findings = Vec::new();
+    for recast in rest
+        .iter()
+        .filter(|s| matches!(s.operation, MigrationOperation::AlterColumnType))
+    {
+        let Some(table) = recast.table.as_deref().and_then(|t| find_table(config, t)) else {
+            continue;
+        };
+        let Some(column) = find_column(config, table, &recast.object) else {
+            continue;
+        };
+        let is_dependent = matches!(
+            parse_canonical(&column.type_),
+            CanonicalType::Custom(ref t) if t.rsplit('.').next() == Some(enum_name)
+        );
+        if !is_dependent {
+            continue;
+        }
+        let from = format!("{}.{}", quote(&recast.schema), quote(&table.name));
+        let filter = format!("{} IN ({})", quote(&column.name), values.join(", "));
+        let Some((count, pks)) = count_and_sample(pool, &from, &filter, table, sample_pks).await
+        else {
+            continue;
+        };
+        findings.push(format!(
+            "{} row(s) in \"{}\".\"{}\"{}",
+            count,
+            table.name,
+            column.name,
+            sample_suffix(&pks)
+        ));
+    }
+    if findings.is_empty() {
+        return None;
+    }
+    Some(format!(
+        "rows holding a removed value: {}.",
+        findings.join("; ")
+    ))
+}
+
+/// `COUNT(*)` of rows matching `filter`, and the primary keys of the first few when `sample_pks`.
+async fn count_and_sample(
+    pool: &Pool,
+    from: &str,
+    filter: &str,
+    table: &TableConfig,
+    sample_pks: bool,
+) -> Option<(i64, Vec<Value>)> {
+    let (count,): (i64,) =
+        sqlx::query_as(&format!("SELECT COUNT(*) FROM {} WHERE {}", from, filter))
+            .fetch_one(pool)
+            .await
+            .ok()?;
+    if count == 0 || !sample_pks {
+        return Some((count, Vec::new()));
+    }
+    let pks = pk_columns(table).join(", ");
+    let rows = sqlx::query(&format!(
+        "SELECT {} FROM {} WHERE {} ORDER BY {} LIMIT {}",
+        pks, from, filter, pks, SAMPLE_PKS
+    ))
+    .fetch_all(pool)
+    .await
+    .ok()?;
+    Some((count, rows.iter().map(pk_value).collect()))
+}
+
+fn find_table<'c>(config: &'c FullConfig, name: &str) -> Option<&'c TableConfig> {
+    config.tables.iter().find(|t| t.name == name)
+}
+
+fn find_column<'c>(
+    config: &'c FullConfig,
+    table: &TableConfig,
+    name: &str,
+) -> Option<&'c ColumnConfig> {
+    config
+        .columns
+        .iter()
+        .find(|c| c.table_id == table.id && c.name == name)
+}
+
+fn pk_columns(table: &TableConfig) -> Vec<String> {
+    match &table.primary_key {
+        PrimaryKeyConfig::Single(c) => vec![quote(c)],
+        PrimaryKeyConfig::Composite(cs) => cs.iter().map(|c| quote(c)).collect(),
+    }
+}
+
+/// A sampled primary key: the bare value, or an object for composite keys.
+fn pk_value(row: &crate::db::pool::DbRow) -> Value {
+    match crate::service::row_to_json(row) {
+        Value::Object(map) if map.len() == 1 => {
+            map.into_iter().next().map_or(Value::Null, |(_, v)| v)
+        }
+        other => other,
+    }
+}
+
+fn sample_suffix(pks: &[Value]) -> String {
+    if pks.is_empty() {
+        return String::new();
+    }
+    let shown: Vec<String> = pks
+        .iter()
+        .map(|v| match v {
+            Value::String(s) => s.clone(),
+            other => other.to_string(),
+        })
+        .collect();
+    format!(" (sample primary keys: {})", shown.join(", "))
+}
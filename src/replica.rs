@@ -0,0 +1,190 @@
+//! Read-replica routing.
+//!
+//! Entity `list`, `read` and history reads, and package exports, may be served by a read replica
+//! while every write (and every RLS transaction that writes) stays on the primary:
+//!
+//! - `DATABASE_REPLICA_URL` is the replica of the central DB. It serves RLS tenants that share the
+//!   central DB and package exports.
+//! - `_sys_tenants.replica_url` is the replica of a tenant's own database (Database strategy, or
+//!   RLS with a dedicated `database_url`). For an RLS tenant on the central DB it overrides
+//!   `DATABASE_REPLICA_URL`.
+//!
+//! A replica is used only while it answers a probe, re-checked at most every
+//! [`REPLICA_CHECK_INTERVAL`]. On dialects that report replay lag (Postgres) a replica further
+//! behind than `ARCHITECT_REPLICA_MAX_LAG_MS` (default 5000) is skipped too. Reads then fall back to
+//! the primary. `?consistency=strong` always reads from the primary, e.g. to read your own write.
+
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+use crate::db::pool::Pool;
+use crate::db::Dialect;
+use crate::error::AppError;
+
+/// How long a replica's probe result is trusted.
+pub const REPLICA_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+/// Replay lag beyond which a replica is skipped when `ARCHITECT_REPLICA_MAX_LAG_MS` is not set.
+pub const DEFAULT_MAX_REPLICA_LAG: Duration = Duration::from_millis(5_000);
+
+/// Read consistency requested with `?consistency=`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Consistency {
+    /// A replica may serve the read (the default).
+    #[default]
+    Eventual,
+    /// Read from the primary.
+    Strong,
+}
+
+impl Consistency {
+    /// Parse `?consistency=` (`strong` | `eventual`); absent means eventual.
+    pub fn parse(value: Option<&str>) -> Result<Self, AppError> {
+        match value.map(str::trim) {
+            None | Some("") | Some("eventual") => Ok(Self::Eventual),
+            Some("strong") => Ok(Self::Strong),
+            Some(other) => Err(AppError::BadRequest(format!(
+                "invalid consistency '{}' (expected strong or eventual)",
+                other
+            ))),
+        }
+    }
+}
+
+/// The central DB's replica (env `DATABASE_REPLICA_URL`).
+pub fn central_replica_url() -> Option<String> {
+    std::env::var("DATABASE_REPLICA_URL")
+        .ok()
+        .filter(|s| !s.trim().is_empty())
+}
+
+/// The configured lag limit (env `ARCHITECT_REPLICA_MAX_LAG_MS`).
+pub fn max_replica_lag() -> Duration {
+    std::env::var("ARCHITECT_REPLICA_MAX_LAG_MS")
+        .ok()
+        .and_then(|v| v.trim().parse().ok())
+        .map(Duration::from_millis)
+        .unwrap_or(DEFAULT_MAX_REPLICA_LAG)
+}
+
+struct Replica {
+    pool: Pool,
+    /// When the replica was last probed and whether it was usable then.
+    checked: Option<(Instant, bool)>,
+}
+
+/// Replica pools keyed by URL, created on first use. Construct with `Default::default()`.
+#[derive(Clone, Default)]
+pub struct ReplicaPools {
+    replicas: Arc<RwLock<HashMap<String, Replica>>>,
+}
+
+impl ReplicaPools {
+    /// The pool to read from for the replica at `url`, or `None` when the replica cannot be used
+    /// right now (unreachable, lagging, or a URL this build cannot connect to) and the read should
+    /// go to the primary.
+    pub async fn read_pool(&self, url: &str, dialect: &dyn Dialect) -> Option<Pool> {
+        let (pool, fresh) = match self.cached(url) {
+            Some((pool, Some((at, usable)))) if at.elapsed() < REPLICA_CHECK_INTERVAL => {
+                return usable.then_some(pool);
+            }
+            Some((pool, _)) => (pool, false),
+            None => (self.connect(url)?, true),
+        };
+        let usable = probe(&pool, dialect, max_replica_lag()).await;
+        if let Ok(mut guard) = self.replicas.write() {
+            if let Some(replica) = guard.get_mut(url) {
+                if fresh || replica.checked.map_or(true, |(_, was)| was != usable) {
+                    tracing::info!(usable, "read replica status");
+                }
+                replica.checked = Some((Instant::now(), usable));
+            }
+        }
+        usable.then_some(pool)
+    }
+
+    fn cached(&self, url: &str) -> Option<(Pool, Option<(Instant, bool)>)> {
+        let guard = self.replicas.read().ok()?;
+        guard.get(url).map(|r| (r.pool.clone(), r.checked))
+    }
+
+    fn connect(&self, url: &str) -> Option<Pool> {
+        let pool: Pool = match sqlx::pool::PoolOptions::new()
+            .max_connections(5)
+            .acquire_timeout(Duration::from_secs(2))
+            .connect_lazy(url)
+        {
+            Ok(pool) => pool,
+            Err(e) => {
+                tracing::warn!(error = %e, "invalid replica URL; reading from the primary");
+                return None;
+            }
+        };
+        let mut guard = self.replicas.write().ok()?;
+        let replica = guard.entry(url.to_string()).or_insert(Replica {
+            pool,
+            checked: None,
+        });
+        Some(replica.pool.clone())
+    }
+}
+
+/// Whether the replica answers and, where the dialect reports it, is within `max_lag`.
+async fn probe(pool: &Pool, dialect: &dyn Dialect, max_lag: Duration) -> bool {
+    let Some(sql) = dialect.replica_lag_sql() else {
+        return match sqlx::query("SELECT 1").execute(pool).await {
+            Ok(_) => true,
+            Err(e) => {
+                tracing::warn!(error = %e, "read replica unreachable; reading from the primary");
+                false
+            }
+        };
+    };
+    match sqlx::query_scalar::<_, f64>(sql).fetch_one(pool).await {
+        Ok(lag_ms) if lag_ms <= max_lag.as_millis() as f64 => true,
+        Ok(lag_ms) => {
+            tracing::warn!(
+                lag_ms,
+                max_lag_ms = max_lag.as_millis() as u64,
+                "read replica lagging; reading from the primary"
+            );
+            false
+        }
+        Err(e) => {
+            tracing::warn!(error = %e, "read replica unreachable; reading from the primary");
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod replica_tests {
+    use super::*;
+    use crate::db::active_dialect;
+
+    #[test]
+    fn consistency_defaults_to_eventual() {
+        assert_eq!(Consistency::parse(None).unwrap(), Consistency::Eventual);
+        assert_eq!(
+            Consistency::parse(Some("strong")).unwrap(),
+            Consistency::Strong
+        );
+        assert!(Consistency::parse(Some("linearizable")).is_err());
+    }
+
+    #[tokio::test]
+    async fn unusable_replicas_fall_back_to_the_primary() {
+        let pools = ReplicaPools::default();
+        let dialect = active_dialect();
+        assert!(pools
+            .read_pool("ftp://replica", dialect.as_ref())
+            .await
+            .is_none());
+        if dialect.name() == "sqlite" {
+            let pool = pools.read_pool("sqlite::memory:", dialect.as_ref()).await;
+            assert!(pool.is_some());
+            // The probe result is cached.
+            assert!(pools.cached("sqlite::memory:").unwrap().1.unwrap().1);
+        }
+    }
+}
@@ -0,0 +1,894 @@
+//! Reverse-engineer a package from an existing database.
+//!
+//! Adopting the SDK on a legacy schema otherwise means hand-writing `tables.json`,
+//! `columns.json`, `relationships.json` and the rest for every table. [`generate_package`] maps a
+//! [`DbCatalog`] read by [`introspect_catalog`] to a [`FullConfig`] — physical types become
+//! canonical type names, serial/identity columns become `serial`/`bigserial`, enum-typed columns
+//! reference generated enums — and adds a default API entity per table. [`write_package_zip`]
+//! packs it with a manifest into the archive `POST /config/package` installs.
+//!
+//! Installing the result on the database it was read from creates nothing but the SDK's system
+//! columns (`created_at`, `updated_at`, …): every table, column and index already exists.
+
+use std::collections::{HashMap, HashSet};
+use std::io::Write;
+
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::config::{
+    ApiEntityConfig, ColumnConfig, ColumnDefaultConfig, ColumnTypeConfig, EnumConfig, FullConfig,
+    IndexColumnEntry, IndexConfig, PrimaryKeyConfig, RelationshipConfig, SchemaConfig, TableConfig,
+    ValidationRule,
+};
+use crate::db::pool::Pool;
+use crate::db::{
+    canonical_name, introspect_catalog, CanonicalType, CatalogColumn, CatalogIndexKey,
+    CatalogTable, DbCatalog, Dialect,
+};
+use crate::error::AppError;
+use crate::handlers::package::DEFAULT_SCHEMA_ID;
+
+/// Operations every generated API entity exposes.
+const DEFAULT_OPERATIONS: &[&str] = &[
+    "create",
+    "read",
+    "update",
+    "delete",
+    "bulk_create",
+    "bulk_update",
+    "bulk_delete",
+];
+
+/// Column-name fragments that mark a column as sensitive in the generated API entity.
+const SENSITIVE_NAME_PARTS: &[&str] = &["password", "passwd", "secret", "token", "api_key"];
+
+/// Config kinds written to the archive, in apply order. Schemas are derived from the manifest.
+const ZIP_KINDS: &[&str] = &[
+    "enums",
+    "tables",
+    "columns",
+    "indexes",
+    "relationships",
+    "api_entities",
+    "kv_stores",
+];
+
+/// Manifest fields for a generated package.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ReverseOptions {
+    /// Package id.
+    pub id: String,
+    /// Display name. Defaults to the id.
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Defaults to `1.0.0`.
+    #[serde(default)]
+    pub version: Option<String>,
+    /// Schema the package installs into. Defaults to the introspected schema.
+    #[serde(default)]
+    pub schema: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+/// A package generated from a catalog.
+#[derive(Debug, Clone)]
+pub struct GeneratedPackage {
+    /// `manifest.json`: `{ id, name, version, schema, description }`.
+    pub manifest: Value,
+    pub config: FullConfig,
+    /// Objects that package config cannot express and were left out, one line each.
+    pub skipped: Vec<String>,
+}
+
+/// Hands out config ids derived from object names, unique within one package.
+#[derive(Default)]
+struct Ids(HashSet<String>);
+
+impl Ids {
+    fn next(&mut self, prefix: &str, parts: &[&str]) -> String {
+        let base = format!("{}_{}", prefix, slug(&parts.join("_")));
+        let mut id = base.clone();
+        let mut n = 2;
+        while !self.0.insert(id.clone()) {
+            id = format!("{}_{}", base, n);
+            n += 1;
+        }
+        id
+    }
+}
+
+/// Lowercase with every run of non-alphanumerics collapsed to one `_`.
+fn slug(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for ch in s.chars() {
+        if ch.is_ascii_alphanumeric() {
+            out.push(ch.to_ascii_lowercase());
+        } else if !out.ends_with('_') {
+            out.push('_');
+        }
+    }
+    out.trim_matches('_').to_string()
+}
+
+/// Values of an inline MySQL `enum('a','b')` column type.
+fn inline_enum_values(physical: &str) -> Option<Vec<String>> {
+    let t = physical.trim();
+    if !t.get(..5)?.eq_ignore_ascii_case("enum(") || !t.ends_with(')') {
+        return None;
+    }
+    let inner = &t[5..t.len() - 1];
+    let mut values = Vec::new();
+    let mut chars = inner.chars().peekable();
+    while let Some(ch) = chars.next() {
+        if ch != '\'' {
+            continue;
+        }
+        let mut v = String::new();
+        while let Some(c) = chars.next() {
+            if c == '\'' {
+                if chars.peek() == Some(&'\'') {
+                    chars.next();
+                    v.push('\'');
+                } else {
+                    break;
+                }
+            } else {
+                v.push(c);
+            }
+        }
+        values.push(v);
+    }
+    Some(values)
+}
+
+/// Map a database-reported DEFAULT expression to column config. A quoted literal loses any
+/// trailing cast (`'active'::text` → `'active'`) so the package stays portable; NULL means no
+/// default; anything that is not a plain literal is kept as an expression.
+fn map_default(expr: &str) -> Option<ColumnDefaultConfig> {
+    let e = expr.trim();
+    if e.is_empty() || e.eq_ignore_ascii_case("null") || e.to_lowercase().starts_with("null::") {
+        return None;
+    }
+    if let Some(literal) = quoted_literal_prefix(e) {
+        let rest = e[literal.len()..].trim();
+        let plain_cast = rest.strip_prefix("::").is_some_and(|ty| {
+            !ty.is_empty()
+                && ty
+                    .chars()
+                    .all(|c| c.is_alphanumeric() || " _.\"[]()".contains(c))
+        });
+        if rest.is_empty() || plain_cast {
+            return Some(ColumnDefaultConfig::Literal(literal.to_string()));
+        }
+    }
+    let is_number = e.parse::<f64>().is_ok();
+    let is_bool = e.eq_ignore_ascii_case("true") || e.eq_ignore_ascii_case("false");
+    if is_number || is_bool {
+        return Some(ColumnDefaultConfig::Literal(e.to_string()));
+    }
+    Some(ColumnDefaultConfig::Expression {
+        expression: e.to_string(),
+    })
+}
+
+/// The leading `'...'` of `s` (with `''` escapes), if `s` starts with a string literal.
+fn quoted_literal_prefix(s: &str) -> Option<&str> {
+    let bytes = s.as_bytes();
+    if bytes.first() != Some(&b'\'') {
+        return None;
+    }
+    let mut i = 1;
+    while i < bytes.len() {
+        if bytes[i] == b'\'' {
+            if bytes.get(i + 1) == Some(&b'\'') {
+                i += 2;
+                continue;
+            }
+            return Some(&s[..=i]);
+        }
+        i += 1;
+    }
+    None
+}
+
+/// Unqualified, unquoted name of a (possibly array) custom type: `app."Status"[]` → `Status`.
+fn bare_type_name(physical: &str) -> (String, bool) {
+    let t = physical.trim();
+    let (t, is_array) = match t.strip_suffix("[]") {
+        Some(inner) => (inner, true),
+        None => (t, false),
+    };
+    let name = t.rsplit('.').next().unwrap_or(t).trim_matches('"');
+    (name.to_string(), is_array)
+}
+
+fn is_sensitive(column: &str) -> bool {
+    let lower = column.to_lowercase();
+    SENSITIVE_NAME_PARTS.iter().any(|p| lower.contains(p))
+}
+
+/// Map `catalog` to a complete package config.
+///
+/// Tables without a primary key and SDK system tables (`_sys_*`) are left out, as are
+/// composite foreign keys and those pointing outside the schema — relationship config has no
+/// way to express them — and indexes whose keys could not be read. Each is listed in
+/// [`GeneratedPackage::skipped`].
+pub fn generate_package(
+    catalog: &DbCatalog,
+    dialect: &dyn Dialect,
+    opts: &ReverseOptions,
+) -> GeneratedPackage {
+    let schema = opts
+        .schema
+        .clone()
+        .unwrap_or_else(|| catalog.schema.clone());
+    let sid = Some(DEFAULT_SCHEMA_ID.to_string());
+    let mut ids = Ids::default();
+    let mut skipped: Vec<String> = Vec::new();
+    let mut config = FullConfig {
+        schemas: vec![SchemaConfig {
+            id: DEFAULT_SCHEMA_ID.to_string(),
+            name: schema.clone(),
+            comment: None,
+        }],
+        ..Default::default()
+    };
+
+    // Named enum types (Postgres). Inline MySQL enums are added per column below.
+    let mut enum_values: HashMap<String, Vec<String>> = HashMap::new();
+    for e in &catalog.enums {
+        config.enums.push(EnumConfig {
+            id: ids.next("enum", &[&e.name]),
+            schema_id: sid.clone(),
+            name: e.name.clone(),
+            values: e.values.clone(),
+            comment: None,
+        });
+        enum_values.insert(e.name.clone(), e.values.clone());
+    }
+
+    let tables: Vec<&CatalogTable> = catalog
+        .tables
+        .iter()
+        .filter(|t| {
+            if t.name.starts_with("_sys_") {
+                return false;
+            }
+            if t.primary_key.is_empty() {
+                skipped.push(format!("table {}: no primary key", t.name));
+                return false;
+            }
+            true
+        })
+        .collect();
+
+    // table name → (table id, column name → column id)
+    let mut table_ids: HashMap<&str, (String, HashMap<&str, String>)> = HashMap::new();
+
+    for t in &tables {
+        let table_id = ids.next("tbl", &[&t.name]);
+        let mut column_ids: HashMap<&str, String> = HashMap::new();
+        let mut validation: HashMap<String, ValidationRule> = HashMap::new();
+
+        for c in &t.columns {
+            let column_id = ids.next("col", &[&t.name, &c.name]);
+            let (type_, allowed) = column_type(
+                dialect,
+                &schema,
+                t,
+                c,
+                &enum_values,
+                &mut config.enums,
+                &mut ids,
+            );
+            let default = if c.auto_increment {
+                None
+            } else {
+                c.default.as_deref().and_then(map_default)
+            };
+
+            let mut rule = ValidationRule::default();
+            let is_pk = t.primary_key.iter().any(|p| p == &c.name);
+            if !c.nullable && default.is_none() && !c.auto_increment {
+                rule.required = Some(true);
+            }
+            match dialect.canonical_from_physical(&c.data_type) {
+                CanonicalType::Varchar(Some(n)) if allowed.is_none() => rule.max_length = Some(n),
+                CanonicalType::Uuid => rule.format = Some("uuid".into()),
+                _ => {}
+            }
+            rule.allowed = allowed.map(|v| v.into_iter().map(Value::String).collect());
+            // A generated key (uuid default) needs no rule beyond its format.
+            if is_pk && default.is_some() {
+                rule.required = None;
+            }
+            if rule.required.is_some()
+                || rule.max_length.is_some()
+                || rule.format.is_some()
+                || rule.allowed.is_some()
+            {
+                validation.insert(c.name.clone(), rule);
+            }
+
+            config.columns.push(ColumnConfig {
+                id: column_id.clone(),
+                table_id: table_id.clone(),
+                name: c.name.clone(),
+                type_,
+                nullable: c.nullable,
+                default,
+                comment: None,
+                asset: None,
+                extensible: false,
+            });
+            column_ids.insert(c.name.as_str(), column_id);
+        }
+
+        config.tables.push(TableConfig {
+            id: table_id.clone(),
+            schema_id: sid.clone(),
+            name: t.name.clone(),
+            comment: None,
+            primary_key: if t.primary_key.len() == 1 {
+                PrimaryKeyConfig::Single(t.primary_key[0].clone())
+            } else {
+                PrimaryKeyConfig::Composite(t.primary_key.clone())
+            },
+            unique: t.unique.clone(),
+            check: Vec::new(),
+            audit_log: false,
+            versioning: None,
+            global: false,
+        });
+
+        for idx in &t.indexes {
+            if idx.incomplete {
+                skipped.push(format!(
+                    "index {}.{}: a key expression could not be read",
+                    t.name, idx.name
+                ));
+                continue;
+            }
+            config.indexes.push(IndexConfig {
+                id: ids.next("idx", &[&t.name, &idx.name]),
+                schema_id: sid.clone(),
+                table_id: table_id.clone(),
+                name: idx.name.clone(),
+                method: idx.method.clone().filter(|m| m != "btree"),
+                unique: idx.unique,
+                columns: idx
+                    .keys
+                    .iter()
+                    .map(|k| match k {
+                        CatalogIndexKey::Column(c) => IndexColumnEntry::Name(c.clone()),
+                        CatalogIndexKey::Expression(e) => IndexColumnEntry::Expression {
+                            expression: e.clone(),
+                        },
+                    })
+                    .collect(),
+                include: Vec::new(),
+                where_: idx.where_clause.clone(),
+                comment: None,
+            });
+        }
+
+        config.api_entities.push(ApiEntityConfig {
+            entity_id: table_id.clone(),
+            path_segment: slug(&t.name),
+            operations: DEFAULT_OPERATIONS.iter().map(|s| s.to_string()).collect(),
+            sensitive_columns: t
+                .columns
+                .iter()
+                .filter(|c| is_sensitive(&c.name))
+                .map(|c| c.name.clone())
+                .collect(),
+            validation,
+            archive_field: None,
+            events: Vec::new(),
+            parent_ref_column: None,
+            mcp: None,
+        });
+
+        table_ids.insert(t.name.as_str(), (table_id, column_ids));
+    }
+
+    for t in &tables {
+        for fk in &t.foreign_keys {
+            let label = format!("foreign key {}.{}", t.name, fk.name);
+            if fk.columns.len() != 1 {
+                skipped.push(format!("{}: composite keys are not supported", label));
+                continue;
+            }
+            if let Some(other) = &fk.ref_schema {
+                skipped.push(format!("{}: references schema {}", label, other));
+                continue;
+            }
+            let Some((to_table_id, to_columns)) = table_ids.get(fk.ref_table.as_str()) else {
+                skipped.push(format!(
+                    "{}: references skipped table {}",
+                    label, fk.ref_table
+                ));
+                continue;
+            };
+            // An implicit reference targets the parent's (single-column) primary key.
+            let to_column = match fk.ref_columns.first() {
+                Some(c) => Some(c.as_str()),
+                None => catalog
+                    .table(&fk.ref_table)
+                    .filter(|p| p.primary_key.len() == 1)
+                    .map(|p| p.primary_key[0].as_str()),
+            };
+            let Some(to_column_id) = to_column.and_then(|c| to_columns.get(c)) else {
+                skipped.push(format!("{}: referenced column not found", label));
+                continue;
+            };
+            let (from_table_id, from_columns) = &table_ids[t.name.as_str()];
+            config.relationships.push(RelationshipConfig {
+                id: ids.next("rel", &[&t.name, &fk.name]),
+                from_schema_id: sid.clone(),
+                from_table_id: from_table_id.clone(),
+                from_column_id: from_columns[fk.columns[0].as_str()].clone(),
+                to_package_id: None,
+                to_schema_id: sid.clone(),
+                to_table_id: to_table_id.clone(),
+                to_column_id: to_column_id.clone(),
+                on_update: fk.on_update.clone(),
+                on_delete: fk.on_delete.clone(),
+                name: Some(fk.name.clone()),
+            });
+        }
+    }
+
+    let manifest = json!({
+        "id": opts.id,
+        "name": opts.name.clone().unwrap_or_else(|| opts.id.clone()),
+        "version": opts.version.clone().unwrap_or_else(|| "1.0.0".into()),
+        "schema": schema,
+        "description": opts.description,
+    });
+
+    GeneratedPackage {
+        manifest,
+        config,
+        skipped,
+    }
+}
+
+/// Config type for one column, plus the allowed values when it is enum-typed.
+fn column_type(
+    dialect: &dyn Dialect,
+    schema: &str,
+    table: &CatalogTable,
+    column: &CatalogColumn,
+    enum_values: &HashMap<String, Vec<String>>,
+    enums: &mut Vec<EnumConfig>,
+    ids: &mut Ids,
+) -> (ColumnTypeConfig, Option<Vec<String>>) {
+    if let Some(values) = inline_enum_values(&column.data_type) {
+        // MySQL has no named enum types: the column keeps its inline type and the values are
+        // recorded as an enum for documentation and validation.
+        enums.push(EnumConfig {
+            id: ids.next("enum", &[&table.name, &column.name]),
+            schema_id: Some(DEFAULT_SCHEMA_ID.to_string()),
+            name: format!("{}_{}", table.name, column.name),
+            values: values.clone(),
+            comment: None,
+        });
+        return (
+            ColumnTypeConfig::Simple(column.data_type.clone()),
+            Some(values),
+        );
+    }
+
+    let canonical = dialect.canonical_from_physical(&column.data_type);
+    if let CanonicalType::Custom(_) | CanonicalType::Array(_) = canonical {
+        let (name, is_array) = bare_type_name(&column.data_type);
+        if let Some(values) = enum_values.get(&name) {
+            let suffix = if is_array { "[]" } else { "" };
+            return (
+                ColumnTypeConfig::Simple(format!("{}.{}{}", schema, name, suffix)),
+                (!is_array).then(|| values.clone()),
+            );
+        }
+    }
+
+    let canonical = match canonical {
+        CanonicalType::SmallInt | CanonicalType::Int if column.auto_increment => {
+            CanonicalType::Serial
+        }
+        CanonicalType::BigInt if column.auto_increment => CanonicalType::BigSerial,
+        other => other,
+    };
+    (ColumnTypeConfig::Simple(canonical_name(&canonical)), None)
+}
+
+/// Drop `null` object members recursively. Every optional config field defaults when absent, so
+/// the archive reads back the same while staying free of noise.
+fn strip_nulls(v: &mut Value) {
+    match v {
+        Value::Object(map) => {
+            map.retain(|_, v| !v.is_null());
+            map.values_mut().for_each(strip_nulls);
+        }
+        Value::Array(items) => items.iter_mut().for_each(strip_nulls),
+        _ => {}
+    }
+}
+
+fn kind_records(config: &FullConfig, kind: &str) -> Result<Value, serde_json::Error> {
+    match kind {
+        "enums" => serde_json::to_value(&config.enums),
+        "tables" => serde_json::to_value(&config.tables),
+        "columns" => serde_json::to_value(&config.columns),
+        "indexes" => serde_json::to_value(&config.indexes),
+        "relationships" => serde_json::to_value(&config.relationships),
+        "api_entities" => serde_json::to_value(&config.api_entities),
+        "kv_stores" => serde_json::to_value(&config.kv_stores),
+        _ => Ok(Value::Array(Vec::new())),
+    }
+}
+
+/// Pack `manifest` and every config kind of `config` into a package archive: `manifest.json`
+/// plus one `{kind}.json` per kind. The output depends only on the inputs — entries are written
+/// in a fixed order with a fixed timestamp and object keys are sorted — so packing the same
+/// package twice yields identical bytes.
+pub fn write_package_zip(manifest: &Value, config: &FullConfig) -> Result<Vec<u8>, AppError> {
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated)
+        .last_modified_time(zip::DateTime::default());
+    let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    let zip_err = |e: zip::result::ZipError| AppError::BadRequest(format!("package zip: {}", e));
+    let json_err = |e: serde_json::Error| AppError::BadRequest(format!("package zip: {}", e));
+
+    let mut entries: Vec<(String, Value)> = vec![("manifest.json".into(), manifest.clone())];
+    for kind in ZIP_KINDS {
+        entries.push((
+            format!("{}.json", kind),
+            kind_records(config, kind).map_err(json_err)?,
+        ));
+    }
+    for (name, mut body) in entries {
+        strip_nulls(&mut body);
+        let mut bytes = serde_json::to_vec_pretty(&body).map_err(json_err)?;
+        bytes.push(b'\n');
+        zip.start_file(name, options).map_err(zip_err)?;
+        zip.write_all(&bytes)
+            .map_err(|e| AppError::BadRequest(format!("package zip: {}", e)))?;
+    }
+    Ok(zip.finish().map_err(zip_err)?.into_inner())
+}
+
+/// The manifest exported with a package's stored config: the installed `manifest.json` when the
+/// package was installed, else one derived from the config (posted through the granular config
+/// endpoints), whose single schema becomes the manifest `schema`.
+pub fn export_manifest(
+    package_id: &str,
+    installed: Option<&Value>,
+    config: &FullConfig,
+) -> Result<Value, AppError> {
+    if let Some(manifest) = installed {
+        return Ok(manifest.clone());
+    }
+    match config.schemas.as_slice() {
+        [schema] => Ok(json!({
+            "id": package_id,
+            "name": package_id,
+            "version": "0.1.0",
+            "schema": schema.name,
+        })),
+        schemas => Err(AppError::BadRequest(format!(
+            "package '{}' spans {} schemas; a package ZIP carries exactly one",
+            package_id,
+            schemas.len()
+        ))),
+    }
+}
+
+/// Introspect `schema` and generate a package from it in one step.
+pub async fn reverse_engineer(
+    pool: &Pool,
+    dialect: &dyn Dialect,
+    schema: &str,
+    opts: &ReverseOptions,
+) -> Result<GeneratedPackage, AppError> {
+    let catalog = introspect_catalog(pool, dialect, schema).await?;
+    Ok(generate_package(&catalog, dialect, opts))
+}
+
+#[cfg(test)]
+mod reverse_tests {
+    use super::*;
+    use crate::config::validate;
+    use crate::db::{
+        active_dialect, parse_canonical, CatalogEnum, CatalogForeignKey, CatalogIndex,
+    };
+
+    fn col(name: &str, ty: &str, nullable: bool, default: Option<&str>) -> CatalogColumn {
+        CatalogColumn {
+            name: name.into(),
+            data_type: ty.into(),
+            nullable,
+            default: default.map(String::from),
+            auto_increment: false,
+        }
+    }
+
+    fn opts() -> ReverseOptions {
+        ReverseOptions {
+            id: "legacy".into(),
+            name: None,
+            version: None,
+            schema: Some("app".into()),
+            description: None,
+        }
+    }
+
+    fn catalog() -> DbCatalog {
+        let mut order_id = col("id", "integer", false, Some("nextval('orders_id_seq')"));
+        order_id.auto_increment = true;
+        DbCatalog {
+            schema: "public".into(),
+            tables: vec![
+                CatalogTable {
+                    name: "orders".into(),
+                    columns: vec![
+                        order_id,
+                        col("user_id", "uuid", false, None),
+                        col("status", "order_status", false, Some("'new'::order_status")),
+                        col("total", "numeric(10,2)", true, Some("0")),
+                    ],
+                    primary_key: vec!["id".into()],
+                    unique: vec![],
+                    foreign_keys: vec![CatalogForeignKey {
+                        name: "orders_user_fk".into(),
+                        columns: vec!["user_id".into()],
+                        ref_schema: None,
+                        ref_table: "users".into(),
+                        ref_columns: vec!["id".into()],
+                        on_update: None,
+                        on_delete: Some("CASCADE".into()),
+                    }],
+                    indexes: vec![CatalogIndex {
+                        name: "orders_user_idx".into(),
+                        keys: vec![CatalogIndexKey::Column("user_id".into())],
+                        unique: false,
+                        method: Some("btree".into()),
+                        where_clause: None,
+                        incomplete: false,
+                    }],
+                },
+                CatalogTable {
+                    name: "users".into(),
+                    columns: vec![
+                        col("id", "uuid", false, Some("gen_random_uuid()")),
+                        col("email", "varchar(255)", false, None),
+                        col("password_hash", "text", true, None),
+                    ],
+                    primary_key: vec!["id".into()],
+                    unique: vec![vec!["email".into()]],
+                    ..Default::default()
+                },
+            ],
+            enums: vec![CatalogEnum {
+                name: "order_status".into(),
+                values: vec!["new".into(), "paid".into()],
+            }],
+        }
+    }
+
+    fn column<'a>(config: &'a FullConfig, id: &str) -> &'a ColumnConfig {
+        config.columns.iter().find(|c| c.id == id).unwrap()
+    }
+
+    fn type_str(c: &ColumnConfig) -> &str {
+        match &c.type_ {
+            ColumnTypeConfig::Simple(s) => s,
+            ColumnTypeConfig::Parameterized { name, .. } => name,
+        }
+    }
+
+    #[test]
+    fn maps_catalog_to_a_valid_package() {
+        let dialect = active_dialect();
+        let pkg = generate_package(&catalog(), dialect.as_ref(), &opts());
+        let config = &pkg.config;
+        validate(config).expect("generated config validates");
+        assert!(pkg.skipped.is_empty(), "{:?}", pkg.skipped);
+        assert_eq!(pkg.manifest["schema"], "app");
+        assert_eq!(pkg.manifest["version"], "1.0.0");
+
+        let id = column(config, "col_orders_id");
+        assert_eq!(type_str(id), "serial");
+        assert!(
+            id.default.is_none(),
+            "sequence default is implied by serial"
+        );
+        let status = column(config, "col_orders_status");
+        assert_eq!(type_str(status), "app.order_status");
+        assert!(matches!(&status.default, Some(ColumnDefaultConfig::Literal(s)) if s == "'new'"));
+        assert_eq!(
+            type_str(column(config, "col_orders_total")),
+            "numeric(10,2)"
+        );
+        assert!(matches!(
+            &column(config, "col_users_id").default,
+            Some(ColumnDefaultConfig::Expression { expression }) if expression == "gen_random_uuid()"
+        ));
+
+        assert_eq!(config.enums[0].id, "enum_order_status");
+        let users = config.tables.iter().find(|t| t.name == "users").unwrap();
+        assert_eq!(users.unique, vec![vec!["email".to_string()]]);
+
+        let rel = &config.relationships[0];
+        assert_eq!(rel.from_column_id, "col_orders_user_id");
+        assert_eq!(rel.to_column_id, "col_users_id");
+        assert_eq!(rel.on_delete.as_deref(), Some("CASCADE"));
+        assert_eq!(rel.name.as_deref(), Some("orders_user_fk"));
+
+        let idx = &config.indexes[0];
+        assert_eq!(idx.id, "idx_orders_orders_user_idx");
+        assert!(idx.method.is_none(), "btree is the default method");
+
+        let users_api = config
+            .api_entities
+            .iter()
+            .find(|a| a.path_segment == "users")
+            .unwrap();
+        assert_eq!(users_api.operations.len(), DEFAULT_OPERATIONS.len());
+        assert_eq!(users_api.sensitive_columns, vec!["password_hash"]);
+        let email = &users_api.validation["email"];
+        assert_eq!(email.required, Some(true));
+        assert_eq!(email.max_length, Some(255));
+        assert!(
+            users_api.validation["id"].required.is_none(),
+            "generated keys are not required"
+        );
+        let orders_api = config
+            .api_entities
+            .iter()
+            .find(|a| a.path_segment == "orders")
+            .unwrap();
+        assert_eq!(
+            orders_api.validation["status"].allowed,
+            Some(vec![json!("new"), json!("paid")])
+        );
+    }
+
+    #[test]
+    fn skips_what_package_config_cannot_express() {
+        let mut cat = catalog();
+        cat.tables.push(CatalogTable {
+            name: "event_log".into(),
+            columns: vec![col("message", "text", true, None)],
+            ..Default::default()
+        });
+        cat.tables.push(CatalogTable {
+            name: "_sys_packages".into(),
+            columns: vec![col("id", "text", false, None)],
+            primary_key: vec!["id".into()],
+            ..Default::default()
+        });
+        cat.tables[0].foreign_keys.push(CatalogForeignKey {
+            name: "orders_pair_fk".into(),
+            columns: vec!["id".into(), "user_id".into()],
+            ref_schema: None,
+            ref_table: "users".into(),
+            ref_columns: vec![],
+            on_update: None,
+            on_delete: None,
+        });
+        cat.tables[0].indexes[0].incomplete = true;
+
+        let pkg = generate_package(&cat, active_dialect().as_ref(), &opts());
+        assert_eq!(pkg.config.tables.len(), 2);
+        assert_eq!(pkg.config.relationships.len(), 1);
+        assert!(pkg.config.indexes.is_empty());
+        assert_eq!(pkg.skipped.len(), 3, "{:?}", pkg.skipped);
+        assert!(pkg.skipped[0].contains("event_log"));
+    }
+
+    #[test]
+    fn defaults_keep_literals_portable() {
+        let lit = |s: &str| match map_default(s) {
+            Some(ColumnDefaultConfig::Literal(l)) => l,
+            other => panic!("{} → {:?}", s, other),
+        };
+        assert_eq!(lit("'active'::text"), "'active'");
+        assert_eq!(lit("'it''s'::character varying"), "'it''s'");
+        assert_eq!(lit("'{}'::jsonb"), "'{}'");
+        assert_eq!(lit("42"), "42");
+        assert_eq!(lit("false"), "false");
+        assert!(map_default("NULL::text").is_none());
+        assert!(matches!(
+            map_default("'a'::text || 'b'::text"),
+            Some(ColumnDefaultConfig::Expression { .. })
+        ));
+        assert!(matches!(
+            map_default("CURRENT_TIMESTAMP"),
+            Some(ColumnDefaultConfig::Expression { .. })
+        ));
+    }
+
+    #[test]
+    fn inline_enum_values_are_lifted_out() {
+        assert_eq!(
+            inline_enum_values("enum('new','it''s')"),
+            Some(vec!["new".to_string(), "it's".to_string()])
+        );
+        assert_eq!(inline_enum_values("varchar(5)"), None);
+    }
+
+    #[test]
+    fn canonical_names_round_trip() {
+        for t in [
+            CanonicalType::Varchar(Some(64)),
+            CanonicalType::Decimal(Some((10, 2))),
+            CanonicalType::Timestamp,
+            CanonicalType::TimestampNtz,
+            CanonicalType::Double,
+            CanonicalType::BigSerial,
+            CanonicalType::Array(Box::new(CanonicalType::Uuid)),
+            CanonicalType::AssetArray,
+        ] {
+            let name = canonical_name(&t);
+            assert_eq!(
+                parse_canonical(&ColumnTypeConfig::Simple(name.clone())),
+                t,
+                "{}",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn export_manifest_prefers_the_installed_one() {
+        let pkg = generate_package(&catalog(), active_dialect().as_ref(), &opts());
+        let installed = json!({"id": "legacy", "version": "2.0.0", "schema": "app"});
+        assert_eq!(
+            export_manifest("legacy", Some(&installed), &pkg.config).unwrap(),
+            installed
+        );
+        let derived = export_manifest("_default", None, &pkg.config).unwrap();
+        assert_eq!(derived["schema"], "app");
+        assert_eq!(derived["id"], "_default");
+
+        let mut two = pkg.config.clone();
+        two.schemas.push(two.schemas[0].clone());
+        assert!(export_manifest("_default", None, &two).is_err());
+    }
+
+    #[test]
+    fn zip_reads_back_and_is_byte_stable() {
+        let pkg = generate_package(&catalog(), active_dialect().as_ref(), &opts());
+        let bytes = write_package_zip(&pkg.manifest, &pkg.config).unwrap();
+        assert_eq!(
+            bytes,
+            write_package_zip(&pkg.manifest, &pkg.config).unwrap()
+        );
+
+        let mut archive = zip::ZipArchive::new(std::io::Cursor::new(bytes)).unwrap();
+        let mut read = |name: &str| -> Value {
+            let mut s = String::new();
+            std::io::Read::read_to_string(&mut archive.by_name(name).unwrap(), &mut s).unwrap();
+            serde_json::from_str(&s).unwrap()
+        };
+        assert_eq!(read("manifest.json")["id"], "legacy");
+        let columns: Vec<ColumnConfig> = serde_json::from_value(read("columns.json")).unwrap();
+        assert_eq!(columns.len(), pkg.config.columns.len());
+        let users_id = columns.iter().find(|c| c.id == "col_users_id").unwrap();
+        assert!(matches!(
+            &users_id.default,
+            Some(ColumnDefaultConfig::Expression { expression }) if expression == "gen_random_uuid()"
+        ));
+        let tables = read("tables.json");
+        assert!(
+            tables[0].get("comment").is_none(),
+            "null members are dropped"
+        );
+        let _: Vec<ApiEntityConfig> = serde_json::from_value(read("api_entities.json")).unwrap();
+        let _: Vec<RelationshipConfig> =
+            serde_json::from_value(read("relationships.json")).unwrap();
+    }
+}
@@ -1,9 +1,18 @@
-//! Common routes: health, readiness, version, OpenAPI spec.
+//! Common routes: health, readiness, diagnostics, version, OpenAPI spec, Prometheus metrics.
 
+use crate::diagnostics::ComponentStatus;
+use crate::error::AppError;
+use crate::extractors::tenant::TenantId;
 use crate::openapi::spec_handler;
 use crate::state::AppState;
-use axum::{extract::State, routing::get, Json, Router};
-use serde::Serialize;
+use axum::{
+    extract::{Query, State},
+    response::IntoResponse,
+    routing::get,
+    Json, Router,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 #[derive(Serialize)]
 struct HealthBody {
@@ -17,32 +26,96 @@ struct ReadyBody {
     database: Option<&'static str>,
 }
 
+#[derive(Deserialize)]
+struct ReadyQuery {
+    #[serde(default)]
+    deep: bool,
+}
+
+#[derive(Serialize)]
+struct DeepReadyBody {
+    status: ComponentStatus,
+    components: BTreeMap<String, ComponentStatus>,
+}
+
 async fn health() -> Json<HealthBody> {
     Json(HealthBody { status: "ok" })
 }
 
+/// `GET /ready`: `SELECT 1` on the central pool. With `?deep=true`, the components of
+/// [`crate::diagnostics::readiness`] (all but `models`, cached for a few seconds) are listed by
+/// status; 503 only when the central database is down.
 async fn ready(
     State(state): State<AppState>,
-) -> Result<Json<ReadyBody>, (axum::http::StatusCode, Json<ReadyBody>)> {
+    Query(query): Query<ReadyQuery>,
+) -> axum::response::Response {
+    if query.deep {
+        let report = crate::diagnostics::readiness(&state).await;
+        let code = if report.status == ComponentStatus::Down {
+            axum::http::StatusCode::SERVICE_UNAVAILABLE
+        } else {
+            axum::http::StatusCode::OK
+        };
+        let body = DeepReadyBody {
+            status: report.status,
+            components: report.summary(),
+        };
+        return (code, Json(body)).into_response();
+    }
     if sqlx::query("SELECT 1")
         .fetch_optional(&state.pool)
         .await
         .is_err()
     {
-        return Err((
+        return (
             axum::http::StatusCode::SERVICE_UNAVAILABLE,
             Json(ReadyBody {
                 status: "degraded",
                 database: Some("unavailable"),
             }),
-        ));
+        )
+            .into_response();
     }
-    Ok(Json(ReadyBody {
+    Json(ReadyBody {
         status: "ok",
         database: Some("ok"),
+    })
+    .into_response()
+}
+
+/// `GET /diagnostics`: the full [`crate::diagnostics`] report, with latencies, errors and details.
+/// Restricted to the Platform Admin tenant.
+async fn diagnostics(
+    State(state): State<AppState>,
+    TenantId(tenant_id_opt): TenantId,
+) -> Result<impl IntoResponse, AppError> {
+    let tenant_id = tenant_id_opt
+        .as_deref()
+        .filter(|s| !s.is_empty())
+        .ok_or_else(|| AppError::BadRequest("X-Tenant-ID header is required".into()))?;
+    if tenant_id != crate::tenant::platform_tenant_id() {
+        return Err(AppError::Forbidden(
+            "diagnostics are restricted to the Platform Admin".into(),
+        ));
+    }
+    let report = crate::diagnostics::run(&state).await;
+    Ok(Json(crate::response::SuccessOne {
+        data: serde_json::to_value(report).map_err(|e| AppError::BadRequest(e.to_string()))?,
+        meta: None,
     }))
 }
 
+/// Prometheus text exposition of [`crate::metrics`].
+async fn metrics(State(state): State<AppState>) -> impl axum::response::IntoResponse {
+    (
+        [(
+            axum::http::header::CONTENT_TYPE,
+            "text/plain; version=0.0.4; charset=utf-8",
+        )],
+        crate::metrics::render(&state),
+    )
+}
+
 async fn version() -> Json<serde_json::Value> {
     Json(serde_json::json!({
         "name": env!("CARGO_PKG_NAME"),
@@ -58,13 +131,17 @@ pub fn common_routes() -> Router {
         .route("/info", get(version))
 }
 
-/// Common routes including readiness with DB check and GET /spec (OpenAPI). Requires AppState.
+/// Common routes including readiness with DB check (`?deep=true` for every component),
+/// GET /diagnostics (Platform Admin), GET /spec (OpenAPI) and GET /metrics (Prometheus).
+/// Requires AppState.
 pub fn common_routes_with_ready(state: AppState) -> Router {
     Router::new()
         .route("/health", get(health))
         .route("/ready", get(ready))
+        .route("/diagnostics", get(diagnostics))
         .route("/version", get(version))
         .route("/info", get(version))
         .route("/spec", get(spec_handler))
+        .route("/metrics", get(metrics))
         .with_state(state)
 }
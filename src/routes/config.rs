@@ -3,11 +3,14 @@
 use crate::handlers::config::{
     get_api_entities, get_columns, get_enums, get_indexes, get_kv_stores, get_relationships,
     get_schemas, get_tables, post_api_entities, post_columns, post_enums, post_indexes,
-    post_kv_stores, post_relationships, post_schemas, post_tables,
+    post_kv_stores, post_relationships, post_schemas, post_tables, tenant_pool_stats_handler,
 };
 use crate::handlers::package::{
-    apply_migration_handler, bootstrap_tenant_handler, get_package_handler, install_package,
-    list_packages_handler, preview_migration_handler, uninstall_package,
+    apply_migration_handler, apply_transition_handler, approve_migration_handler,
+    bootstrap_tenant_handler, export_package_handler, get_package_handler, install_package,
+    install_packages_bulk, list_packages_handler, list_transitions_handler, package_drift_handler,
+    preview_migration_handler, preview_transition_handler, render_ddl_handler,
+    reverse_engineer_handler, rollback_package_handler, uninstall_package,
 };
 use crate::state::AppState;
 use axum::{routing::delete, routing::get, routing::post, Router};
@@ -15,13 +18,44 @@ use axum::{routing::delete, routing::get, routing::post, Router};
 pub fn config_routes(state: AppState) -> Router {
     Router::new()
         .route("/config/packages", get(list_packages_handler))
+        .route("/config/tenant-pools", get(tenant_pool_stats_handler))
         .route("/config/packages/:package_id", get(get_package_handler))
+        .route(
+            "/config/packages/:package_id/drift",
+            get(package_drift_handler),
+        )
+        .route(
+            "/config/packages/:package_id/export",
+            get(export_package_handler),
+        )
+        .route(
+            "/config/packages/:package_id/transitions",
+            get(list_transitions_handler),
+        )
+        .route(
+            "/config/packages/:package_id/transitions/:phase/preview",
+            get(preview_transition_handler),
+        )
+        .route(
+            "/config/packages/:package_id/transitions/:phase/apply",
+            post(apply_transition_handler),
+        )
         .route("/config/package", post(install_package))
+        .route("/config/package/bulk", post(install_packages_bulk))
+        .route("/config/package/render-ddl", post(render_ddl_handler))
         .route("/config/package/:package_id", delete(uninstall_package))
+        .route(
+            "/config/package/reverse-engineer",
+            post(reverse_engineer_handler),
+        )
         .route(
             "/config/package/migration/preview",
             post(preview_migration_handler),
         )
+        .route(
+            "/config/package/migration/approve/:migration_id",
+            post(approve_migration_handler),
+        )
         .route(
             "/config/package/migration/apply/:migration_id",
             post(apply_migration_handler),
@@ -30,6 +64,10 @@ pub fn config_routes(state: AppState) -> Router {
             "/config/package/:package_id/bootstrap",
             post(bootstrap_tenant_handler),
         )
+        .route(
+            "/config/package/:package_id/rollback",
+            post(rollback_package_handler),
+        )
         .route("/config/schemas", post(post_schemas).get(get_schemas))
         .route("/config/enums", post(post_enums).get(get_enums))
         .route("/config/tables", post(post_tables).get(get_tables))
@@ -44,5 +82,7 @@ pub fn config_routes(state: AppState) -> Router {
             post(post_api_entities).get(get_api_entities),
         )
         .route("/config/kv_stores", post(post_kv_stores).get(get_kv_stores))
+        .route_layer(axum::middleware::from_fn(crate::metrics::track_requests))
+        .route_layer(axum::middleware::from_fn(crate::telemetry::trace_requests))
         .with_state(state)
 }
@@ -2,7 +2,10 @@
 //! Uses parameterized paths so Path extractors receive the segment and id; handlers resolve the entity by path.
 //! Unprefixed routes use the default/active model; /package/:package_id/... use that package's model (same entity names, different packages).
 
-use crate::handlers::asset::sign_asset;
+use crate::handlers::asset::{
+    collect_asset_garbage, download_asset, download_local_asset, presign_asset_upload, sign_asset,
+    upload_local_asset,
+};
 use crate::handlers::entity::{
     archive, archive_package, bulk_create, bulk_create_package, bulk_delete, bulk_delete_package,
     bulk_update, bulk_update_package, create, create_graph, create_graph_package, create_package,
@@ -20,8 +23,15 @@ use axum::{routing::get, routing::post, Router};
 
 pub fn entity_routes(state: AppState) -> Router {
     Router::new()
-        // /assets/sign must be declared before /:path_segment to avoid being captured.
+        // /assets/* must be declared before /:path_segment to avoid being captured.
         .route("/assets/sign", get(sign_asset))
+        .route("/assets/upload-url", post(presign_asset_upload))
+        .route("/assets/download", get(download_asset))
+        .route("/assets/gc", post(collect_asset_garbage))
+        .route(
+            "/assets/local/*path",
+            get(download_local_asset).put(upload_local_asset),
+        )
         .route("/:path_segment", get(list).post(create))
         .route(
             "/:path_segment/bulk",
@@ -93,6 +103,8 @@ pub fn entity_routes(state: AppState) -> Router {
             "/package/:package_id/:path_segment/:id/unarchive",
             post(unarchive_package),
         )
+        .route_layer(axum::middleware::from_fn(crate::metrics::track_requests))
+        .route_layer(axum::middleware::from_fn(crate::telemetry::trace_requests))
         .with_state(state)
 }
 
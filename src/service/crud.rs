@@ -7,8 +7,9 @@ use crate::error::AppError;
 use crate::extensible_fields::ExtensibleRegistry;
 use crate::sql::{
     archive, coerce_json_value_for_pg_array, delete, insert, insert_history_snapshot,
-    prune_history, select_by_column_in, select_by_id, select_list, select_list_with_includes,
-    unarchive, update, BindValue, FilterNode, IncludeSelect, QueryBuf, SortSpec,
+    prune_history, select_asset_page, select_asset_reference, select_by_column_in, select_by_id,
+    select_list, select_list_with_includes, unarchive, update, BindValue, FilterNode,
+    IncludeSelect, QueryBuf, SortSpec,
 };
 use serde_json::Value;
 use std::collections::HashMap;
@@ -43,12 +44,17 @@ impl<'a> TenantExecutor<'a> {
 /// child entity, and the list of child bodies to insert under it.
 pub type GraphChild = (IncludeSpec, ResolvedEntity, Vec<HashMap<String, Value>>);
 
+/// Result of the `*_collecting` bulk methods: the rows written, and the failed items as
+/// `(index, error)`.
+pub type BulkOutcome = (Vec<Value>, Vec<(usize, AppError)>);
+
 pub struct CrudService;
 
 impl CrudService {
     /// List rows with optional RSQL filter and sort, limit (default 100, max 1000), offset (default 0).
     /// `filter_includes` supplies related-entity metadata for dotted-field EXISTS filters; pass `&[]` when unused.
     #[allow(clippy::too_many_arguments)]
+    #[tracing::instrument(name = "CrudService::list", skip_all)]
     pub async fn list<'a>(
         executor: &mut TenantExecutor<'a>,
         entity: &ResolvedEntity,
@@ -61,6 +67,7 @@ impl CrudService {
         dialect: &dyn Dialect,
         registry: Option<&ExtensibleRegistry>,
     ) -> Result<Vec<Value>, AppError> {
+        let _timer = crate::metrics::sql_timer("list");
         const DEFAULT_LIMIT: u32 = 100;
         let limit = limit.unwrap_or(DEFAULT_LIMIT).min(1000);
         let offset = offset.unwrap_or(0);
@@ -81,6 +88,7 @@ impl CrudService {
     /// List rows with includes in a single query (scalar subqueries with json_agg/row_to_json). Returns rows with include keys already set (JSON).
     /// `includes` drives scalar subqueries for response data; `filter_includes` is the superset used for EXISTS generation.
     #[allow(clippy::too_many_arguments)]
+    #[tracing::instrument(name = "CrudService::list_with_includes", skip_all)]
     pub async fn list_with_includes<'a>(
         executor: &mut TenantExecutor<'a>,
         entity: &ResolvedEntity,
@@ -94,6 +102,7 @@ impl CrudService {
         dialect: &dyn Dialect,
         registry: Option<&ExtensibleRegistry>,
     ) -> Result<Vec<Value>, AppError> {
+        let _timer = crate::metrics::sql_timer("list_with_includes");
         const DEFAULT_LIMIT: u32 = 100;
         let limit = limit.unwrap_or(DEFAULT_LIMIT).min(1000);
         let offset = offset.unwrap_or(0);
@@ -113,6 +122,7 @@ impl CrudService {
     }
 
     /// Fetch one row by primary key. Returns JSON object or None.
+    #[tracing::instrument(name = "CrudService::read", skip_all)]
     pub async fn read<'a>(
         executor: &mut TenantExecutor<'a>,
         entity: &ResolvedEntity,
@@ -120,11 +130,13 @@ impl CrudService {
         schema_override: Option<&str>,
         dialect: &dyn Dialect,
     ) -> Result<Option<Value>, AppError> {
+        let _timer = crate::metrics::sql_timer("read");
         let q = select_by_id(entity, schema_override, dialect);
         Self::query_one_exec(executor, &q.sql, std::slice::from_ref(id)).await
     }
 
     /// Fetch rows from entity where column IN (values). Used for batch-loading related rows.
+    #[tracing::instrument(name = "CrudService::fetch_where_column_in", skip_all)]
     pub async fn fetch_where_column_in<'a>(
         executor: &mut TenantExecutor<'a>,
         entity: &ResolvedEntity,
@@ -133,6 +145,7 @@ impl CrudService {
         schema_override: Option<&str>,
         dialect: &dyn Dialect,
     ) -> Result<Vec<Value>, AppError> {
+        let _timer = crate::metrics::sql_timer("fetch_where_column_in");
         if values.is_empty() {
             return Ok(Vec::new());
         }
@@ -143,6 +156,7 @@ impl CrudService {
     /// Insert one row; body may include or omit PK (if has default). Returns created row.
     /// When rls_tenant_id is Some (RLS strategy), tenant_id column is set automatically.
     /// When caller_user_id is Some, created_by is set to that value.
+    #[tracing::instrument(name = "CrudService::create", skip_all)]
     pub async fn create<'a>(
         executor: &mut TenantExecutor<'a>,
         entity: &ResolvedEntity,
@@ -152,6 +166,7 @@ impl CrudService {
         caller_user_id: Option<&str>,
         dialect: &dyn Dialect,
     ) -> Result<Value, AppError> {
+        let _timer = crate::metrics::sql_timer("create");
         let include_pk = body.contains_key(&entity.pk_columns[0]);
         let q = insert(
             entity,
@@ -193,6 +208,7 @@ impl CrudService {
     /// Returns `(parent_row, child_rows_by_include_name)` as raw DB rows (snake_case keys,
     /// sensitive columns NOT stripped — the caller shapes the response).
     #[allow(clippy::too_many_arguments)]
+    #[tracing::instrument(name = "CrudService::create_graph", skip_all)]
     pub async fn create_graph(
         pool: &Pool,
         parent: &ResolvedEntity,
@@ -204,6 +220,7 @@ impl CrudService {
         caller_user_id: Option<&str>,
         dialect: &dyn Dialect,
     ) -> Result<(Value, HashMap<String, Vec<Value>>), AppError> {
+        let _timer = crate::metrics::sql_timer("create_graph");
         let mut tx = pool.begin().await?;
         // RLS: scope the tenant to THIS transaction (SET LOCAL only lasts the transaction).
         if let Some(sql) = set_local_sql {
@@ -266,6 +283,7 @@ impl CrudService {
     /// Update one row by id. Returns updated row.
     /// When caller_user_id is Some, updated_by is set to that value.
     /// When entity has versioning enabled, a history snapshot is written atomically before the update.
+    #[tracing::instrument(name = "CrudService::update", skip_all)]
     pub async fn update<'a>(
         executor: &mut TenantExecutor<'a>,
         entity: &ResolvedEntity,
@@ -275,6 +293,7 @@ impl CrudService {
         caller_user_id: Option<&str>,
         dialect: &dyn Dialect,
     ) -> Result<Option<Value>, AppError> {
+        let _timer = crate::metrics::sql_timer("update");
         let versioning_enabled = entity.versioning.as_ref().is_some_and(|v| v.enabled);
 
         let pre_row = if entity.audit_log || versioning_enabled {
@@ -316,6 +335,7 @@ impl CrudService {
     /// Delete one row by id. Returns deleted row or None.
     /// When caller_user_id is Some, audit_by is set on the audit record.
     /// When entity has versioning enabled, a history snapshot is written atomically before the delete.
+    #[tracing::instrument(name = "CrudService::delete", skip_all)]
     pub async fn delete<'a>(
         executor: &mut TenantExecutor<'a>,
         entity: &ResolvedEntity,
@@ -324,6 +344,7 @@ impl CrudService {
         caller_user_id: Option<&str>,
         dialect: &dyn Dialect,
     ) -> Result<Option<Value>, AppError> {
+        let _timer = crate::metrics::sql_timer("delete");
         let versioning_enabled = entity.versioning.as_ref().is_some_and(|v| v.enabled);
 
         let result = if versioning_enabled {
@@ -355,6 +376,7 @@ impl CrudService {
 
     /// Archive one row by id: stamps archive_field with NOW() if it is currently NULL.
     /// Returns the updated row, or None if the record was not found or already archived.
+    #[tracing::instrument(name = "CrudService::archive", skip_all)]
     pub async fn archive<'a>(
         executor: &mut TenantExecutor<'a>,
         entity: &ResolvedEntity,
@@ -363,6 +385,7 @@ impl CrudService {
         schema_override: Option<&str>,
         dialect: &dyn Dialect,
     ) -> Result<Option<Value>, AppError> {
+        let _timer = crate::metrics::sql_timer("archive");
         let q = archive(entity, archive_field, schema_override, dialect);
         Self::execute_returning_one_with_params_exec(executor, &q.sql, std::slice::from_ref(id))
             .await
@@ -370,6 +393,7 @@ impl CrudService {
 
     /// Unarchive one row by id: clears archive_field (sets to NULL) if it is currently NOT NULL.
     /// Returns the updated row, or None if the record was not found or not archived.
+    #[tracing::instrument(name = "CrudService::unarchive", skip_all)]
     pub async fn unarchive<'a>(
         executor: &mut TenantExecutor<'a>,
         entity: &ResolvedEntity,
@@ -378,6 +402,7 @@ impl CrudService {
         schema_override: Option<&str>,
         dialect: &dyn Dialect,
     ) -> Result<Option<Value>, AppError> {
+        let _timer = crate::metrics::sql_timer("unarchive");
         let q = unarchive(entity, archive_field, schema_override, dialect);
         Self::execute_returning_one_with_params_exec(executor, &q.sql, std::slice::from_ref(id))
             .await
@@ -386,6 +411,7 @@ impl CrudService {
     /// Bulk create in a transaction (when using pool) or on the same connection (when using conn). Returns vec of created rows.
     /// When rls_tenant_id is Some (RLS strategy), tenant_id column is set automatically on each row.
     /// When caller_user_id is Some, created_by is set on each row.
+    #[tracing::instrument(name = "CrudService::bulk_create", skip_all)]
     pub async fn bulk_create<'a>(
         executor: &mut TenantExecutor<'a>,
         entity: &ResolvedEntity,
@@ -395,6 +421,7 @@ impl CrudService {
         caller_user_id: Option<&str>,
         dialect: &dyn Dialect,
     ) -> Result<Vec<Value>, AppError> {
+        let _timer = crate::metrics::sql_timer("bulk_create");
         const BULK_LIMIT: usize = 100;
         if items.len() > BULK_LIMIT {
             return Err(AppError::BadRequest(format!(
@@ -449,6 +476,7 @@ impl CrudService {
     /// Like `bulk_create` but uses savepoints to isolate per-row DB errors.
     /// Returns `(successful_rows, row_errors)`. If any errors occur the transaction is
     /// rolled back and successful_rows will be empty — call site decides how to surface errors.
+    #[tracing::instrument(name = "CrudService::bulk_create_collecting", skip_all)]
     pub async fn bulk_create_collecting<'a>(
         executor: &mut TenantExecutor<'a>,
         entity: &ResolvedEntity,
@@ -457,7 +485,8 @@ impl CrudService {
         rls_tenant_id: Option<&str>,
         caller_user_id: Option<&str>,
         dialect: &dyn Dialect,
-    ) -> Result<(Vec<Value>, Vec<(usize, AppError)>), AppError> {
+    ) -> Result<BulkOutcome, AppError> {
+        let _timer = crate::metrics::sql_timer("bulk_create_collecting");
         const BULK_LIMIT: usize = 100;
         if items.len() > BULK_LIMIT {
             return Err(AppError::BadRequest(format!(
@@ -548,6 +577,7 @@ impl CrudService {
 
     /// Bulk update in a transaction (when using pool) or on the same connection (when using conn). Each item must have id. Returns vec of updated rows.
     /// When caller_user_id is Some, updated_by is set on each row.
+    #[tracing::instrument(name = "CrudService::bulk_update", skip_all)]
     pub async fn bulk_update<'a>(
         executor: &mut TenantExecutor<'a>,
         entity: &ResolvedEntity,
@@ -556,6 +586,7 @@ impl CrudService {
         caller_user_id: Option<&str>,
         dialect: &dyn Dialect,
     ) -> Result<Vec<Value>, AppError> {
+        let _timer = crate::metrics::sql_timer("bulk_update");
         const BULK_LIMIT: usize = 100;
         if items.len() > BULK_LIMIT {
             return Err(AppError::BadRequest(format!(
@@ -616,6 +647,7 @@ impl CrudService {
     /// Missing pk on an item is recorded as a row error rather than aborting early.
     /// Returns `(successful_rows, row_errors)`. If any errors occur the transaction is
     /// rolled back and successful_rows will be empty.
+    #[tracing::instrument(name = "CrudService::bulk_update_collecting", skip_all)]
     pub async fn bulk_update_collecting<'a>(
         executor: &mut TenantExecutor<'a>,
         entity: &ResolvedEntity,
@@ -623,7 +655,8 @@ impl CrudService {
         schema_override: Option<&str>,
         caller_user_id: Option<&str>,
         dialect: &dyn Dialect,
-    ) -> Result<(Vec<Value>, Vec<(usize, AppError)>), AppError> {
+    ) -> Result<BulkOutcome, AppError> {
+        let _timer = crate::metrics::sql_timer("bulk_update_collecting");
         const BULK_LIMIT: usize = 100;
         if items.len() > BULK_LIMIT {
             return Err(AppError::BadRequest(format!(
@@ -749,6 +782,7 @@ impl CrudService {
     /// skipped silently (no row and no error), matching single-delete semantics.
     /// Returns `(deleted_rows, row_errors)`. If any error occurs the transaction is rolled back and
     /// deleted_rows is cleared (all-or-nothing).
+    #[tracing::instrument(name = "CrudService::bulk_delete_collecting", skip_all)]
     pub async fn bulk_delete_collecting<'a>(
         executor: &mut TenantExecutor<'a>,
         entity: &ResolvedEntity,
@@ -756,7 +790,8 @@ impl CrudService {
         schema_override: Option<&str>,
         caller_user_id: Option<&str>,
         dialect: &dyn Dialect,
-    ) -> Result<(Vec<Value>, Vec<(usize, AppError)>), AppError> {
+    ) -> Result<BulkOutcome, AppError> {
+        let _timer = crate::metrics::sql_timer("bulk_delete_collecting");
         const BULK_LIMIT: usize = 100;
         if ids.len() > BULK_LIMIT {
             return Err(AppError::BadRequest(format!(
@@ -857,24 +892,76 @@ impl CrudService {
         Ok((out, row_errors))
     }
 
+    /// One keyset page of an entity's key and asset columns; see [`select_asset_page`]. Pass the
+    /// last row's key values ([`crate::sql::asset_page_key`]) as `after` for the next.
+    #[tracing::instrument(name = "CrudService::list_asset_page", skip_all)]
+    pub async fn list_asset_page<'a>(
+        executor: &mut TenantExecutor<'a>,
+        entity: &ResolvedEntity,
+        history: bool,
+        after: Option<Vec<Value>>,
+        limit: u32,
+        schema_override: Option<&str>,
+    ) -> Result<Vec<Value>, AppError> {
+        let _timer = crate::metrics::sql_timer("list_asset_page");
+        let q = select_asset_page(
+            entity,
+            history,
+            after,
+            limit,
+            schema_override,
+            executor.dialect,
+        );
+        Self::query_many_exec(executor, &q.sql, &q.params).await
+    }
+
+    /// Whether a row of the entity (or its `_history` table) holds `path` in asset column
+    /// `column`; see [`select_asset_reference`].
+    #[tracing::instrument(name = "CrudService::asset_referenced", skip_all)]
+    pub async fn asset_referenced<'a>(
+        executor: &mut TenantExecutor<'a>,
+        entity: &ResolvedEntity,
+        column: &crate::config::ColumnInfo,
+        history: bool,
+        path: &str,
+        schema_override: Option<&str>,
+    ) -> Result<bool, AppError> {
+        let _timer = crate::metrics::sql_timer("asset_referenced");
+        let q = select_asset_reference(
+            entity,
+            column,
+            history,
+            path,
+            schema_override,
+            executor.dialect,
+        );
+        Ok(!Self::query_many_exec(executor, &q.sql, &q.params)
+            .await?
+            .is_empty())
+    }
+
     /// Execute a history SELECT that returns multiple rows (used by list_history handler).
     /// Binds: params[0] = pk value.
+    #[tracing::instrument(name = "CrudService::query_history_many", skip_all)]
     pub async fn query_history_many<'a>(
         executor: &mut TenantExecutor<'a>,
         sql: &str,
         params: &[Value],
     ) -> Result<Vec<Value>, AppError> {
+        let _timer = crate::metrics::sql_timer("query_history_many");
         Self::query_many_exec(executor, sql, params).await
     }
 
     /// Execute a history SELECT that returns one row (used by read_history_version handler).
     /// Binds: $1 = pk value, $2 = version (i64).
+    #[tracing::instrument(name = "CrudService::query_history_one", skip_all)]
     pub async fn query_history_one<'a>(
         executor: &mut TenantExecutor<'a>,
         sql: &str,
         id: &Value,
         version: i64,
     ) -> Result<Option<Value>, AppError> {
+        let _timer = crate::metrics::sql_timer("query_history_one");
         tracing::debug!(sql = %sql, "history query");
         let mut query = sqlx::query(sql);
         query = query.bind(Self::to_sqlx_param(id));
@@ -886,6 +973,7 @@ impl CrudService {
         Ok(row.map(|r| row_to_json(&r)))
     }
 
+    #[tracing::instrument(name = "db.query", skip_all, fields(db.statement = %sql))]
     async fn query_one_exec<'a>(
         executor: &mut TenantExecutor<'a>,
         sql: &str,
@@ -907,6 +995,7 @@ impl CrudService {
         Ok(row.map(|r| row_to_json(&r)))
     }
 
+    #[tracing::instrument(name = "db.query", skip_all, fields(db.statement = %sql))]
     async fn query_many_exec<'a>(
         executor: &mut TenantExecutor<'a>,
         sql: &str,
@@ -924,6 +1013,7 @@ impl CrudService {
         Ok(rows.iter().map(row_to_json).collect())
     }
 
+    #[tracing::instrument(name = "db.query", skip_all, fields(db.statement = %q.sql))]
     async fn execute_returning_one_exec<'a>(
         executor: &mut TenantExecutor<'a>,
         q: &QueryBuf,
@@ -940,6 +1030,7 @@ impl CrudService {
         Ok(row.map(|r| row_to_json(&r)))
     }
 
+    #[tracing::instrument(name = "db.query", skip_all, fields(db.statement = %sql))]
     async fn execute_returning_one_with_params_exec<'a>(
         executor: &mut TenantExecutor<'a>,
         sql: &str,
@@ -957,6 +1048,7 @@ impl CrudService {
         Ok(row.map(|r| row_to_json(&r)))
     }
 
+    #[tracing::instrument(name = "db.query", skip_all, fields(db.statement = %q.sql))]
     async fn execute_returning_one_conn(
         conn: &mut Connection,
         q: &QueryBuf,
@@ -1229,7 +1321,7 @@ fn compute_changed_fields(pre: &Value, post: &Value, entity: &ResolvedEntity) ->
     Value::Object(changes)
 }
 
-fn row_to_json(row: &DbRow) -> Value {
+pub(crate) fn row_to_json(row: &DbRow) -> Value {
     use sqlx::Column;
     use sqlx::Row;
     let mut map = serde_json::Map::new();
@@ -2,5 +2,6 @@
 
 mod crud;
 mod validation;
+pub(crate) use crud::row_to_json;
 pub use crud::{CrudService, GraphChild, TenantExecutor, TenantExecutorInner};
 pub use validation::RequestValidator;
@@ -148,6 +148,33 @@ fn value_eq(a: &Value, b: &Value) -> bool {
     }
 }
 
+fn validate_format(col: &str, v: &Value, format: &str) -> Result<(), AppError> {
+    match format.to_lowercase().as_str() {
+        "email" => {
+            if let Some(s) = v.as_str() {
+                if !s.contains('@') || s.len() < 3 {
+                    return Err(AppError::Validation(format!(
+                        "{} must be a valid email",
+                        col
+                    )));
+                }
+            }
+        }
+        "uuid" => {
+            if let Some(s) = v.as_str() {
+                if uuid::Uuid::parse_str(s).is_err() {
+                    return Err(AppError::Validation(format!(
+                        "{} must be a valid UUID",
+                        col
+                    )));
+                }
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -381,30 +408,3 @@ mod tests {
         assert!(errors.is_empty());
     }
 }
-
-fn validate_format(col: &str, v: &Value, format: &str) -> Result<(), AppError> {
-    match format.to_lowercase().as_str() {
-        "email" => {
-            if let Some(s) = v.as_str() {
-                if !s.contains('@') || s.len() < 3 {
-                    return Err(AppError::Validation(format!(
-                        "{} must be a valid email",
-                        col
-                    )));
-                }
-            }
-        }
-        "uuid" => {
-            if let Some(s) = v.as_str() {
-                if uuid::Uuid::parse_str(s).is_err() {
-                    return Err(AppError::Validation(format!(
-                        "{} must be a valid UUID",
-                        col
-                    )));
-                }
-            }
-        }
-        _ => {}
-    }
-    Ok(())
-}
@@ -0,0 +1,307 @@
+//! Package signing and integrity verification.
+//!
+//! Every installed package gets a content hash: SHA-256 over its ZIP entries in name order, each
+//! contributing its name, a NUL byte, its length as a big-endian u64 and its bytes. Directories
+//! and the `signature` entry are left out, and names are taken relative to `manifest.json`, so
+//! re-zipping the same files gives the same hash. A ZIP with files outside the folder holding
+//! `manifest.json` is rejected.
+//!
+//! A package is signed by adding a `signature` entry next to `manifest.json`:
+//! `{"key_id": "release-2026", "signer": "Acme Release", "signature": "<hex>"}`, where the
+//! signature is ed25519 over the content hash as a lowercase hex string.
+//!
+//! Trusted public keys come from `ARCHITECT_PACKAGE_TRUSTED_KEYS`
+//! (`key_id=<hex public key>,…`). With keys configured, install rejects unsigned packages, unknown
+//! key ids and signatures that do not verify; `ARCHITECT_PACKAGE_SIGNATURES=optional` still admits
+//! unsigned packages. With no keys configured, signatures are not checked.
+
+use crate::error::AppError;
+use ring::signature::{UnparsedPublicKey, ED25519};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::io::{Cursor, Read};
+use zip::ZipArchive;
+
+/// Name of the signature entry, relative to `manifest.json`.
+pub const SIGNATURE_ENTRY: &str = "signature";
+
+/// Contents of the `signature` entry.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PackageSignature {
+    pub key_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signer: Option<String>,
+    /// ed25519 signature over the content hash (hex string), hex.
+    pub signature: String,
+}
+
+/// What install records about a package's provenance in `_sys_packages`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct PackageIntegrity {
+    pub content_hash: String,
+    /// Verified key id; `None` for packages installed unsigned or without verification.
+    pub key_id: Option<String>,
+    /// The signature's `signer`, else the key id.
+    pub signer: Option<String>,
+}
+
+/// Trusted keys and whether a signature is required.
+#[derive(Debug, Clone, Default)]
+pub struct SigningPolicy {
+    pub trusted_keys: HashMap<String, Vec<u8>>,
+    pub require_signature: bool,
+}
+
+impl SigningPolicy {
+    /// The configured policy (env `ARCHITECT_PACKAGE_TRUSTED_KEYS`, `ARCHITECT_PACKAGE_SIGNATURES`).
+    pub fn from_env() -> Self {
+        let keys = std::env::var("ARCHITECT_PACKAGE_TRUSTED_KEYS").unwrap_or_default();
+        let mode = std::env::var("ARCHITECT_PACKAGE_SIGNATURES").ok();
+        Self::parse(&keys, mode.as_deref())
+    }
+
+    /// Parse `key_id=<hex>,…` and a mode (`required` | `optional`). The mode defaults to
+    /// `required` when keys are configured. Malformed keys are skipped with a warning.
+    pub fn parse(keys: &str, mode: Option<&str>) -> Self {
+        let mut trusted_keys = HashMap::new();
+        for entry in keys.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            match entry
+                .split_once('=')
+                .map(|(id, key)| (id.trim(), hex::decode(key.trim())))
+            {
+                Some((id, Ok(key))) if !id.is_empty() && key.len() == 32 => {
+                    trusted_keys.insert(id.to_string(), key);
+                }
+                _ => tracing::warn!(
+                    entry,
+                    "ignoring malformed ARCHITECT_PACKAGE_TRUSTED_KEYS entry (expected key_id=<64 hex chars>)"
+                ),
+            }
+        }
+        let require_signature = match mode.map(|m| m.trim().to_ascii_lowercase()) {
+            Some(m) if m == "optional" => false,
+            Some(m) if m == "required" => true,
+            _ => !trusted_keys.is_empty(),
+        };
+        Self {
+            trusted_keys,
+            require_signature,
+        }
+    }
+
+    /// Verify a package ZIP and return what to record for it.
+    pub fn verify(&self, zip_bytes: &[u8]) -> Result<PackageIntegrity, AppError> {
+        let (content_hash, signature) = read_package_contents(zip_bytes)?;
+        let Some(signature) = signature else {
+            if self.require_signature {
+                return Err(AppError::Forbidden(
+                    "package is not signed; a signature from a trusted key is required".into(),
+                ));
+            }
+            return Ok(PackageIntegrity {
+                content_hash,
+                ..Default::default()
+            });
+        };
+        if self.trusted_keys.is_empty() && !self.require_signature {
+            return Ok(PackageIntegrity {
+                content_hash,
+                ..Default::default()
+            });
+        }
+        let key = self.trusted_keys.get(&signature.key_id).ok_or_else(|| {
+            AppError::Forbidden(format!(
+                "package is signed with untrusted key '{}'",
+                signature.key_id
+            ))
+        })?;
+        let sig = hex::decode(signature.signature.trim())
+            .map_err(|e| AppError::BadRequest(format!("invalid package signature: {}", e)))?;
+        UnparsedPublicKey::new(&ED25519, key)
+            .verify(content_hash.as_bytes(), &sig)
+            .map_err(|_| {
+                AppError::Forbidden(format!(
+                    "package signature does not verify with key '{}'; the package was modified \
+                     after signing",
+                    signature.key_id
+                ))
+            })?;
+        Ok(PackageIntegrity {
+            content_hash,
+            signer: Some(signature.signer.unwrap_or_else(|| signature.key_id.clone())),
+            key_id: Some(signature.key_id),
+        })
+    }
+}
+
+/// The content hash of a package ZIP, and its parsed `signature` entry if present. Fails when a
+/// file lies outside the folder holding `manifest.json`.
+pub fn read_package_contents(
+    zip_bytes: &[u8],
+) -> Result<(String, Option<PackageSignature>), AppError> {
+    let mut archive = ZipArchive::new(Cursor::new(zip_bytes))
+        .map_err(|e| AppError::BadRequest(format!("invalid zip: {}", e)))?;
+    let root = archive
+        .file_names()
+        .find(|n| *n == "manifest.json" || n.ends_with("/manifest.json"))
+        .map(|n| n.trim_end_matches("manifest.json").to_string())
+        .ok_or_else(|| AppError::BadRequest("zip must contain manifest.json at root".into()))?;
+
+    let mut entries: Vec<(String, Vec<u8>)> = Vec::new();
+    let mut signature = None;
+    for i in 0..archive.len() {
+        let mut file = archive
+            .by_index(i)
+            .map_err(|e| AppError::BadRequest(e.to_string()))?;
+        if file.is_dir() {
+            continue;
+        }
+        // Install reads some entries by their full archive name, so an entry outside the hashed
+        // root could change what is installed without changing the hash.
+        let Some(name) = file.name().strip_prefix(root.as_str()).map(String::from) else {
+            return Err(AppError::BadRequest(format!(
+                "zip entry '{}' is outside the folder holding manifest.json",
+                file.name()
+            )));
+        };
+        let mut bytes = Vec::new();
+        file.read_to_end(&mut bytes)
+            .map_err(|e| AppError::BadRequest(e.to_string()))?;
+        if name == SIGNATURE_ENTRY {
+            signature = Some(serde_json::from_slice(&bytes).map_err(|e| {
+                AppError::BadRequest(format!("invalid {}: {}", SIGNATURE_ENTRY, e))
+            })?);
+        } else {
+            entries.push((name, bytes));
+        }
+    }
+    entries.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let mut hasher = Sha256::new();
+    for (name, bytes) in &entries {
+        hasher.update(name.as_bytes());
+        hasher.update([0u8]);
+        hasher.update((bytes.len() as u64).to_be_bytes());
+        hasher.update(bytes);
+    }
+    Ok((hex::encode(hasher.finalize()), signature))
+}
+
+#[cfg(test)]
+mod signing_tests {
+    use super::*;
+    use ring::signature::{Ed25519KeyPair, KeyPair};
+    use std::io::Write;
+
+    fn zip(entries: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut out = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, bytes) in entries {
+            out.start_file(*name, zip::write::SimpleFileOptions::default())
+                .unwrap();
+            out.write_all(bytes).unwrap();
+        }
+        out.finish().unwrap().into_inner()
+    }
+
+    fn signed(key: &Ed25519KeyPair, entries: &[(&str, &[u8])]) -> Vec<u8> {
+        let (hash, _) = read_package_contents(&zip(entries)).unwrap();
+        let signature = serde_json::to_vec(&PackageSignature {
+            key_id: "release".into(),
+            signer: Some("Release Team".into()),
+            signature: hex::encode(key.sign(hash.as_bytes())),
+        })
+        .unwrap();
+        let mut all = entries.to_vec();
+        all.push((SIGNATURE_ENTRY, &signature));
+        zip(&all)
+    }
+
+    fn key() -> Ed25519KeyPair {
+        Ed25519KeyPair::from_seed_unchecked(&[7u8; 32]).unwrap()
+    }
+
+    fn policy(mode: Option<&str>) -> SigningPolicy {
+        let keys = format!("release={}", hex::encode(key().public_key().as_ref()));
+        SigningPolicy::parse(&keys, mode)
+    }
+
+    #[test]
+    fn content_hash_ignores_entry_order_and_signature() {
+        let a = zip(&[("manifest.json", b"{}"), ("tables.json", b"[]")]);
+        let b = zip(&[("tables.json", b"[]"), ("manifest.json", b"{}")]);
+        assert_eq!(
+            read_package_contents(&a).unwrap().0,
+            read_package_contents(&b).unwrap().0
+        );
+        let files: &[(&str, &[u8])] = &[("manifest.json", b"{}"), ("tables.json", b"[]")];
+        assert_eq!(
+            read_package_contents(&signed(&key(), files)).unwrap().0,
+            read_package_contents(&a).unwrap().0
+        );
+    }
+
+    #[test]
+    fn trusted_signatures_verify_and_tampering_is_rejected() {
+        let files: &[(&str, &[u8])] = &[("manifest.json", b"{}"), ("tables.json", b"[]")];
+        let integrity = policy(None).verify(&signed(&key(), files)).unwrap();
+        assert_eq!(integrity.key_id.as_deref(), Some("release"));
+        assert_eq!(integrity.signer.as_deref(), Some("Release Team"));
+
+        // Re-sign the original files but ship a different tables.json.
+        let (hash, _) = read_package_contents(&zip(files)).unwrap();
+        let signature = serde_json::to_vec(&PackageSignature {
+            key_id: "release".into(),
+            signer: None,
+            signature: hex::encode(key().sign(hash.as_bytes())),
+        })
+        .unwrap();
+        let tampered = zip(&[
+            ("manifest.json", b"{}"),
+            ("tables.json", b"[{}]"),
+            (SIGNATURE_ENTRY, &signature),
+        ]);
+        assert!(matches!(
+            policy(None).verify(&tampered),
+            Err(AppError::Forbidden(_))
+        ));
+    }
+
+    #[test]
+    fn files_outside_the_manifest_folder_are_rejected() {
+        let files: &[(&str, &[u8])] = &[("manifest.json", b"{}"), ("tables.json", b"[]")];
+        let (hash, _) = read_package_contents(&zip(files)).unwrap();
+        let signature = serde_json::to_vec(&PackageSignature {
+            key_id: "release".into(),
+            signer: None,
+            signature: hex::encode(key().sign(hash.as_bytes())),
+        })
+        .unwrap();
+        // The signed files moved under x/, with an unsigned tables.json at the archive root.
+        let rerooted = zip(&[
+            ("x/manifest.json", b"{}"),
+            ("x/tables.json", b"[]"),
+            ("x/signature", &signature),
+            ("tables.json", b"[{}]"),
+        ]);
+        assert!(matches!(
+            policy(None).verify(&rerooted),
+            Err(AppError::BadRequest(_))
+        ));
+        assert!(read_package_contents(&rerooted).is_err());
+        // The same files under one folder still hash as at the root.
+        let nested = zip(&[("x/manifest.json", b"{}"), ("x/tables.json", b"[]")]);
+        assert_eq!(read_package_contents(&nested).unwrap().0, hash);
+    }
+
+    #[test]
+    fn unsigned_packages_need_an_optional_policy() {
+        let unsigned = zip(&[("manifest.json", b"{}")]);
+        assert!(policy(None).verify(&unsigned).is_err());
+        assert!(policy(Some("optional")).verify(&unsigned).is_ok());
+        let open = SigningPolicy::parse("", None);
+        assert!(!open.require_signature);
+        assert_eq!(open.verify(&unsigned).unwrap().key_id, None);
+        assert!(SigningPolicy::parse("bad=zz", None).trusted_keys.is_empty());
+    }
+}
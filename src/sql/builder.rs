@@ -1,6 +1,6 @@
 //! Builds parameterized INSERT, SELECT, UPDATE, DELETE from resolved entity.
 
-use crate::config::{IncludeDirection, PkType, ResolvedEntity};
+use crate::config::{ColumnInfo, IncludeDirection, PkType, ResolvedEntity};
 use crate::db::{type_category_from_cast, CanonicalType, Dialect, TypeCategory};
 use crate::error::AppError;
 use crate::extensible_fields::ExtensibleRegistry;
@@ -807,6 +807,28 @@ pub fn select_by_column_in(
     q
 }
 
+/// Mirror a write to `column` into its shadow column while an expand/contract type change is in
+/// flight: binds the value again and returns `(quoted shadow, converted expression)`.
+fn shadow_write(
+    entity: &ResolvedEntity,
+    column: &str,
+    val: &Value,
+    q: &mut QueryBuf,
+    dialect: &dyn Dialect,
+) -> Option<(String, String)> {
+    let shadow = entity.shadow_columns.iter().find(|s| s.column == column)?;
+    let param_num = q.push_param(val.clone());
+    let ph = shadow
+        .source_cast
+        .as_deref()
+        .map(|t| dialect.cast_expr(&dialect.placeholder(param_num as usize), t))
+        .unwrap_or_else(|| dialect.placeholder(param_num as usize));
+    Some((
+        quoted(&shadow.shadow),
+        dialect.assignment_cast(&ph, &shadow.target_type),
+    ))
+}
+
 /// INSERT: columns and placeholders from entity; values from body. Excludes PK if has_default.
 /// Omits columns with DB default when body does not provide a value (so DB uses default).
 /// Uses SQL cast (e.g. $n::timestamptz) for timestamp columns so string values bind correctly.
@@ -850,7 +872,7 @@ pub fn insert(
         }
         let val = val.unwrap_or(Value::Null);
         let val = coerce_json_value_for_pg_array(val, c.pg_type.as_deref());
-        let param_num = q.push_param(val);
+        let param_num = q.push_param(val.clone());
         let ph = c
             .pg_type
             .as_deref()
@@ -858,6 +880,10 @@ pub fn insert(
             .unwrap_or_else(|| dialect.placeholder(param_num as usize));
         cols.push(quoted(name));
         placeholders.push(ph);
+        if let Some((shadow, expr)) = shadow_write(entity, name, &val, &mut q, dialect) {
+            cols.push(shadow);
+            placeholders.push(expr);
+        }
     }
     if let Some(tid) = rls_tenant_id {
         let param_num = q.push_param(Value::String(tid.to_string()));
@@ -916,13 +942,16 @@ pub fn update(
             continue;
         };
         let v = coerce_json_value_for_pg_array(v.clone(), c.pg_type.as_deref());
-        let param_num = q.push_param(v);
+        let param_num = q.push_param(v.clone());
         let rhs = c
             .pg_type
             .as_deref()
             .map(|t| dialect.cast_expr(&dialect.placeholder(param_num as usize), t))
             .unwrap_or_else(|| dialect.placeholder(param_num as usize));
         sets.push(format!("{} = {}", quoted(k), rhs));
+        if let Some((shadow, expr)) = shadow_write(entity, k, &v, &mut q, dialect) {
+            sets.push(format!("{} = {}", shadow, expr));
+        }
     }
     sets.push(format!("{} = {}", quoted("updated_at"), dialect.now_fn()));
     if let Some(uid) = caller_user_id {
@@ -1155,6 +1184,161 @@ pub fn prune_history(
     q
 }
 
+/// The columns [`select_asset_page`] orders and pages by: the full primary key, or `_history_id`
+/// for the `_history` companion.
+pub fn asset_page_key(entity: &ResolvedEntity, history: bool) -> Vec<String> {
+    if history {
+        vec!["_history_id".to_string()]
+    } else {
+        entity.pk_columns.clone()
+    }
+}
+
+/// SELECT the key and every asset/asset[] column of one page of rows, for finding which storage
+/// objects are still referenced. Reads the entity's table or, with `history`, its `_history`
+/// companion, which is keyed by `_history_id` instead of the primary key. Keyset-paginated on the
+/// whole key ([`asset_page_key`]) so concurrent deletes cannot make a scan skip rows: rows whose
+/// key tuple sorts after `after` (one value per key column), in key order, at most `limit`.
+pub fn select_asset_page(
+    entity: &ResolvedEntity,
+    history: bool,
+    after: Option<Vec<Value>>,
+    limit: u32,
+    schema_override: Option<&str>,
+    dialect: &dyn Dialect,
+) -> QueryBuf {
+    let mut q = QueryBuf::new();
+    let schema = resolve_schema(entity, schema_override);
+    let table = if history {
+        format!("{}_history", entity.table_name)
+    } else {
+        entity.table_name.clone()
+    };
+    let key = asset_page_key(entity, history);
+    // Bind each key value with its column's cast, as writes do; `_history_id` is a UUID.
+    let key_cast = |column: &str| -> Option<String> {
+        if history {
+            dialect.cast_name(&crate::db::CanonicalType::Uuid)
+        } else {
+            entity
+                .columns
+                .iter()
+                .find(|c| c.name == column)
+                .and_then(|c| c.pg_type.clone())
+        }
+    };
+    let cols: Vec<String> = key
+        .iter()
+        .map(|k| quoted(k))
+        .chain(
+            entity
+                .columns
+                .iter()
+                .filter(|c| c.is_asset && !key.contains(&c.name))
+                .map(|c| quoted(&c.name)),
+        )
+        .collect();
+    // (k1 > v1) OR (k1 = v1 AND k2 > v2) OR …: a row-value comparison every dialect accepts.
+    let where_clause = match after {
+        Some(values) if values.len() == key.len() => {
+            let mut branches = Vec::with_capacity(key.len());
+            for last in 0..key.len() {
+                let mut terms = Vec::with_capacity(last + 1);
+                for (i, column) in key.iter().enumerate().take(last + 1) {
+                    let n = q.push_param(values[i].clone());
+                    let ph = dialect.placeholder(n as usize);
+                    let ph = match key_cast(column) {
+                        Some(cast) => dialect.cast_expr(&ph, &cast),
+                        None => ph,
+                    };
+                    let op = if i == last { ">" } else { "=" };
+                    terms.push(format!("{} {} {}", quoted(column), op, ph));
+                }
+                branches.push(format!("({})", terms.join(" AND ")));
+            }
+            format!(" WHERE {}", branches.join(" OR "))
+        }
+        _ => String::new(),
+    };
+    q.sql = format!(
+        "SELECT {} FROM {}{} ORDER BY {} LIMIT {}",
+        cols.join(", "),
+        qualified_table(schema, &table),
+        where_clause,
+        key.iter().map(|k| quoted(k)).collect::<Vec<_>>().join(", "),
+        limit
+    );
+    q
+}
+
+/// SELECT whether any row of the entity's table (or, with `history`, its `_history` companion)
+/// holds `path` in asset column `column`: equal to it, or an element of it for `asset[]`.
+/// Returns at most one row.
+pub fn select_asset_reference(
+    entity: &ResolvedEntity,
+    column: &ColumnInfo,
+    history: bool,
+    path: &str,
+    schema_override: Option<&str>,
+    dialect: &dyn Dialect,
+) -> QueryBuf {
+    let mut q = QueryBuf::new();
+    let schema = resolve_schema(entity, schema_override);
+    let table = if history {
+        format!("{}_history", entity.table_name)
+    } else {
+        entity.table_name.clone()
+    };
+    let n = q.push_param(Value::String(path.to_string()));
+    let ph = dialect.placeholder(n as usize);
+    let condition = if column.asset_is_array {
+        dialect.json_array_contains_text(&quoted(&column.name), &ph)
+    } else {
+        format!("{} = {}", quoted(&column.name), ph)
+    };
+    q.sql = format!(
+        "SELECT 1 AS {} FROM {} WHERE {} LIMIT 1",
+        quoted("referenced"),
+        qualified_table(schema, &table),
+        condition
+    );
+    q
+}
+
+/// UPDATE by id: stamp archive_field with NOW() where it is currently NULL.
+/// Returns the updated row or None (record not found or already archived).
+pub fn archive(
+    entity: &ResolvedEntity,
+    archive_field: &str,
+    schema_override: Option<&str>,
+    dialect: &dyn Dialect,
+) -> QueryBuf {
+    let mut q = QueryBuf::new();
+    let schema = resolve_schema(entity, schema_override);
+    let table = qualified_table(schema, &entity.table_name);
+    let pk = &entity.pk_columns[0];
+    let ph = pk_placeholder(entity, 1, dialect);
+    q.params.push(Value::Null); // placeholder; caller passes real id via execute_returning_one_with_params_exec
+    let col_list = select_column_list(entity);
+    let ret = dialect.returning_clause(&col_list);
+    let suffix = if ret.is_empty() {
+        String::new()
+    } else {
+        format!(" {}", ret)
+    };
+    q.sql = format!(
+        "UPDATE {} SET {} = {} WHERE {} = {} AND {} IS NULL{}",
+        table,
+        quoted(archive_field),
+        dialect.now_fn(),
+        quoted(pk),
+        ph,
+        quoted(archive_field),
+        suffix
+    );
+    q
+}
+
 // ─── History builder unit tests ───────────────────────────────────────────────
 
 #[cfg(test)]
@@ -1306,6 +1490,7 @@ mod versioning_tests {
             versioning: None,
             mcp: None,
             extensible_columns: vec![],
+            shadow_columns: vec![],
         }
     }
 
@@ -1361,6 +1546,60 @@ mod versioning_tests {
         assert!(q.sql.contains("$2"));
     }
 
+    #[test]
+    fn select_asset_page_is_keyset_paginated() {
+        let mut entity = make_entity();
+        entity.columns[1].is_asset = true;
+        let d = PgDialect;
+        let first = select_asset_page(&entity, false, None, 50, None, &d);
+        assert!(!first.sql.contains("WHERE"));
+        assert!(first.sql.contains("ORDER BY \"id\" LIMIT 50"));
+        assert!(first.params.is_empty());
+        let next = select_asset_page(
+            &entity,
+            true,
+            Some(vec![Value::String("k".into())]),
+            50,
+            None,
+            &d,
+        );
+        assert!(next.sql.contains("\"users_history\""));
+        assert!(next.sql.contains("WHERE (\"_history_id\" > $1"));
+        assert!(next
+            .sql
+            .starts_with("SELECT \"_history_id\", \"name\" FROM"));
+        assert_eq!(next.params.len(), 1);
+    }
+
+    #[test]
+    fn select_asset_page_pages_on_the_whole_primary_key() {
+        let mut entity = make_entity();
+        entity.columns[1].is_asset = true;
+        entity.pk_columns = vec!["id".into(), "name".into()];
+        let d = PgDialect;
+        let q = select_asset_page(
+            &entity,
+            false,
+            Some(vec![serde_json::json!(1), serde_json::json!("a")]),
+            50,
+            None,
+            &d,
+        );
+        assert!(q.sql.starts_with("SELECT \"id\", \"name\" FROM"));
+        assert!(q
+            .sql
+            .contains("WHERE (\"id\" > $1) OR (\"id\" = $2 AND \"name\" > $3)"));
+        assert!(q.sql.ends_with("ORDER BY \"id\", \"name\" LIMIT 50"));
+        assert_eq!(
+            q.params,
+            vec![
+                serde_json::json!(1),
+                serde_json::json!(1),
+                serde_json::json!("a")
+            ]
+        );
+    }
+
     #[test]
     fn history_table_uses_entity_schema() {
         let entity = make_entity();
@@ -1484,6 +1723,54 @@ mod versioning_tests {
         );
     }
 
+    fn entity_with_shadow() -> ResolvedEntity {
+        let mut e = make_entity();
+        e.shadow_columns = vec![crate::config::ShadowColumn {
+            column: "name".into(),
+            shadow: "name__new".into(),
+            source_cast: None,
+            target_type: "VARCHAR(64)".into(),
+        }];
+        e
+    }
+
+    #[test]
+    fn insert_mirrors_shadow_column() {
+        let mut body = HashMap::new();
+        body.insert("name".to_string(), Value::String("ada".into()));
+        let q = insert(
+            &entity_with_shadow(),
+            &body,
+            false,
+            None,
+            None,
+            None,
+            &PgDialect,
+        );
+        assert!(
+            q.sql
+                .contains("(\"name\", \"name__new\") VALUES ($1, CAST($2 AS VARCHAR(64)))"),
+            "got: {}",
+            q.sql
+        );
+        assert_eq!(q.params, vec![Value::from("ada"), Value::from("ada")]);
+    }
+
+    #[test]
+    fn update_mirrors_shadow_column() {
+        let mut body = HashMap::new();
+        body.insert("name".to_string(), Value::String("ada".into()));
+        let id = Value::String("00000000-0000-0000-0000-000000000001".into());
+        let q = update(&entity_with_shadow(), &id, &body, None, None, &PgDialect);
+        assert!(
+            q.sql
+                .contains("\"name\" = $1, \"name__new\" = CAST($2 AS VARCHAR(64))"),
+            "got: {}",
+            q.sql
+        );
+        assert_eq!(q.params.len(), 3);
+    }
+
     fn entity_with_bag() -> ResolvedEntity {
         let mut e = make_entity();
         e.extensible_columns = vec!["attributes".into()];
@@ -1613,37 +1900,3 @@ mod versioning_tests {
         assert!(r.is_err());
     }
 }
-
-/// UPDATE by id: stamp archive_field with NOW() where it is currently NULL.
-/// Returns the updated row or None (record not found or already archived).
-pub fn archive(
-    entity: &ResolvedEntity,
-    archive_field: &str,
-    schema_override: Option<&str>,
-    dialect: &dyn Dialect,
-) -> QueryBuf {
-    let mut q = QueryBuf::new();
-    let schema = resolve_schema(entity, schema_override);
-    let table = qualified_table(schema, &entity.table_name);
-    let pk = &entity.pk_columns[0];
-    let ph = pk_placeholder(entity, 1, dialect);
-    q.params.push(Value::Null); // placeholder; caller passes real id via execute_returning_one_with_params_exec
-    let col_list = select_column_list(entity);
-    let ret = dialect.returning_clause(&col_list);
-    let suffix = if ret.is_empty() {
-        String::new()
-    } else {
-        format!(" {}", ret)
-    };
-    q.sql = format!(
-        "UPDATE {} SET {} = {} WHERE {} = {} AND {} IS NULL{}",
-        table,
-        quoted(archive_field),
-        dialect.now_fn(),
-        quoted(pk),
-        ph,
-        quoted(archive_field),
-        suffix
-    );
-    q
-}
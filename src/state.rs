@@ -17,8 +17,9 @@ pub struct AppState {
     pub model: Arc<RwLock<ResolvedModel>>,
     /// Resolved model per package_id. For database tenants, key is "package_id:tenant_id".
     pub package_models: Arc<RwLock<HashMap<String, ResolvedModel>>>,
-    /// Pools for database-strategy tenants, keyed by tenant_id.
-    pub tenant_pools: Arc<RwLock<HashMap<String, Pool>>>,
+    /// Pools for tenants with their own database, keyed by tenant_id, with idle/LRU eviction (see
+    /// [`crate::tenant_pool`]). Construct with `Default::default()`.
+    pub tenant_pools: crate::tenant_pool::TenantPools,
     /// Tenant registry (strategy + config per tenant), loaded from central DB at startup.
     pub tenant_registry: Arc<TenantRegistry>,
     /// Optional blob storage provider for asset columns.
@@ -36,4 +37,7 @@ pub struct AppState {
     /// invalidated (set to `None`) on model reload and package install/uninstall. Initialize with
     /// `Arc::new(RwLock::new(None))`.
     pub cross_package_index: Arc<RwLock<Option<Arc<crate::config::CrossPackageIndex>>>>,
+    /// Read-replica pools (see [`crate::replica`]), created on first use. Construct with
+    /// `Default::default()`.
+    pub replica_pools: crate::replica::ReplicaPools,
 }
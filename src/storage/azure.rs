@@ -13,14 +13,19 @@ use async_trait::async_trait;
 use azure_storage::shared_access_signature::service_sas::BlobSasPermissions;
 use azure_storage::ConnectionString;
 use azure_storage::StorageCredentials;
-use azure_storage_blobs::prelude::BlobServiceClient;
+use azure_storage_blobs::blob::{BlobBlockType, BlockList};
+use azure_storage_blobs::prelude::{BlobServiceClient, BlockId};
 use chrono::{Duration, Utc};
+use futures_util::{StreamExt, TryStreamExt};
 use std::sync::Arc;
 use time::OffsetDateTime;
 
-use super::PresignResult;
+use super::{next_chunk, ByteStream, ObjectMeta, PresignResult, PresignedUpload, StoredObject};
 use crate::storage::StorageProvider;
 
+/// Size of each staged block; blobs smaller than this are uploaded with a single Put Blob.
+const BLOCK_SIZE: usize = 8 * 1024 * 1024;
+
 // ── Provider ──────────────────────────────────────────────────────────────────
 
 pub struct AzureProvider {
@@ -81,30 +86,17 @@ impl AzureProvider {
     }
 }
 
-#[async_trait]
-impl StorageProvider for AzureProvider {
-    async fn upload(&self, path: &str, data: Vec<u8>, content_type: &str) -> Result<(), AppError> {
-        // Convert &str to String so BlobContentType (Cow<'static, str>) can own the value
-        let ct = content_type.to_string();
-        self.service_client
-            .container_client(&self.container)
-            .blob_client(path)
-            .put_block_blob(data)
-            .content_type(ct)
-            .await
-            .map_err(|e| AppError::Storage(format!("Azure upload error: {}", e)))?;
-        Ok(())
-    }
-
-    async fn presign_url(&self, path: &str, expires_secs: u64) -> Result<PresignResult, AppError> {
-        let expires_at = Utc::now() + Duration::seconds(expires_secs as i64);
+impl AzureProvider {
+    /// SAS URL for `path` with `permissions`: User Delegation SAS for token credentials, Service
+    /// SAS for SharedKey.
+    async fn signed_url(
+        &self,
+        path: &str,
+        permissions: BlobSasPermissions,
+        expires_secs: u64,
+    ) -> Result<String, AppError> {
         let expiry = OffsetDateTime::now_utc() + time::Duration::seconds(expires_secs as i64);
 
-        let permissions = BlobSasPermissions {
-            read: true,
-            ..Default::default()
-        };
-
         let blob_client = self
             .service_client
             .container_client(&self.container)
@@ -143,7 +135,86 @@ impl StorageProvider for AzureProvider {
                 .map_err(|e| AppError::Storage(format!("Azure URL generation error: {}", e)))?
                 .to_string()
         };
+        Ok(url)
+    }
+}
+
+#[async_trait]
+impl StorageProvider for AzureProvider {
+    async fn upload(
+        &self,
+        path: &str,
+        mut data: ByteStream<'_>,
+        content_type: &str,
+    ) -> Result<(), AppError> {
+        let blob = self
+            .service_client
+            .container_client(&self.container)
+            .blob_client(path);
+        // Convert &str to String so BlobContentType (Cow<'static, str>) can own the value
+        let ct = content_type.to_string();
+        let first = next_chunk(&mut data, BLOCK_SIZE).await?.unwrap_or_default();
+        if first.len() < BLOCK_SIZE {
+            // The whole blob fits in one request.
+            blob.put_block_blob(first)
+                .content_type(ct)
+                .await
+                .map_err(|e| AppError::Storage(format!("Azure upload error: {}", e)))?;
+            return Ok(());
+        }
+
+        // Stage each chunk as a block, then commit them in order.
+        let mut blocks = Vec::new();
+        let mut chunk = Some(first);
+        while let Some(body) = chunk {
+            let id = BlockId::new(format!("{:08}", blocks.len()));
+            blob.put_block(id.clone(), body)
+                .await
+                .map_err(|e| AppError::Storage(format!("Azure upload error: {}", e)))?;
+            blocks.push(BlobBlockType::Uncommitted(id));
+            chunk = next_chunk(&mut data, BLOCK_SIZE).await?;
+        }
+        blob.put_block_list(BlockList { blocks })
+            .content_type(ct)
+            .await
+            .map_err(|e| AppError::Storage(format!("Azure upload error: {}", e)))?;
+        Ok(())
+    }
+
+    async fn download(&self, path: &str) -> Result<ByteStream<'static>, AppError> {
+        let mut pages = self
+            .service_client
+            .container_client(&self.container)
+            .blob_client(path)
+            .get()
+            .into_stream();
+        // Wait for the first range so a missing blob surfaces as NotFound, not a broken stream.
+        let first = match pages.next().await {
+            Some(Ok(page)) => page,
+            Some(Err(e)) if e.as_http_error().is_some_and(|h| h.status() as u16 == 404) => {
+                return Err(AppError::NotFound(format!("asset not found: {}", path)));
+            }
+            Some(Err(e)) => return Err(AppError::Storage(format!("Azure download error: {}", e))),
+            None => return Ok(Box::pin(futures_util::stream::empty())),
+        };
+        fn to_io(e: impl std::fmt::Display) -> std::io::Error {
+            std::io::Error::other(e.to_string())
+        }
+        let stream = futures_util::stream::once(async move { Ok(first) })
+            .chain(pages)
+            .map_err(to_io)
+            .map_ok(move |page| page.data.map_err(to_io))
+            .try_flatten();
+        Ok(Box::pin(stream))
+    }
 
+    async fn presign_url(&self, path: &str, expires_secs: u64) -> Result<PresignResult, AppError> {
+        let expires_at = Utc::now() + Duration::seconds(expires_secs as i64);
+        let permissions = BlobSasPermissions {
+            read: true,
+            ..Default::default()
+        };
+        let url = self.signed_url(path, permissions, expires_secs).await?;
         Ok(PresignResult {
             url,
             expires_at,
@@ -160,4 +231,72 @@ impl StorageProvider for AzureProvider {
             .map_err(|e| AppError::Storage(format!("Azure delete error: {}", e)))?;
         Ok(())
     }
+
+    async fn presign_upload(
+        &self,
+        path: &str,
+        content_type: &str,
+        expires_secs: u64,
+    ) -> Result<PresignedUpload, AppError> {
+        let expires_at = Utc::now() + Duration::seconds(expires_secs as i64);
+        let permissions = BlobSasPermissions {
+            create: true,
+            write: true,
+            ..Default::default()
+        };
+        let url = self.signed_url(path, permissions, expires_secs).await?;
+        Ok(PresignedUpload {
+            url,
+            method: "PUT",
+            // Put Blob needs the blob type; the content type becomes the blob's Content-Type.
+            headers: vec![
+                ("x-ms-blob-type".into(), "BlockBlob".into()),
+                ("content-type".into(), content_type.into()),
+            ],
+            expires_at,
+            expires_in: expires_secs,
+        })
+    }
+
+    async fn head(&self, path: &str) -> Result<Option<ObjectMeta>, AppError> {
+        let result = self
+            .service_client
+            .container_client(&self.container)
+            .blob_client(path)
+            .get_properties()
+            .await;
+        match result {
+            Ok(props) => Ok(Some(ObjectMeta {
+                size: props.blob.properties.content_length,
+                content_type: Some(props.blob.properties.content_type).filter(|ct| !ct.is_empty()),
+            })),
+            Err(e) if e.as_http_error().is_some_and(|h| h.status() as u16 == 404) => Ok(None),
+            Err(e) => Err(AppError::Storage(format!("Azure properties error: {}", e))),
+        }
+    }
+
+    async fn list(&self, prefix: &str) -> Result<Vec<StoredObject>, AppError> {
+        let mut pages = self
+            .service_client
+            .container_client(&self.container)
+            .list_blobs()
+            .prefix(prefix.to_string())
+            .into_stream();
+        let mut out = Vec::new();
+        while let Some(page) = pages.next().await {
+            let page = page.map_err(|e| AppError::Storage(format!("Azure list error: {}", e)))?;
+            for blob in page.blobs.blobs() {
+                let modified = blob.properties.last_modified;
+                out.push(StoredObject {
+                    path: blob.name.clone(),
+                    size: blob.properties.content_length,
+                    last_modified: chrono::DateTime::from_timestamp(
+                        modified.unix_timestamp(),
+                        modified.nanosecond(),
+                    ),
+                });
+            }
+        }
+        Ok(out)
+    }
 }
@@ -0,0 +1,451 @@
+//! Orphaned asset garbage collection.
+//!
+//! Objects can outlive every row that pointed at them: a create that fails after its files were
+//! uploaded, a rolled-back transaction, a presigned upload that is never attached, a hard delete.
+//! [`collect_orphaned_assets`] lists the objects under every asset column's prefix, compares them
+//! against the paths stored in `asset` / `asset[]` columns of every tenant and installed package —
+//! including `_history` tables of versioned entities — and reports (optionally deletes) the ones
+//! nothing references and that are older than a grace period.
+//!
+//! The pass errs on the side of keeping objects:
+//! - only objects shaped like SDK uploads (`<resolved prefix>/<uuid>[.ext]`) are considered, so
+//!   anything else sharing the bucket is never touched;
+//! - objects without a modification time, or modified within the grace period, are kept — this
+//!   also protects uploads whose row is still being written;
+//! - when a tenant's table cannot be read, objects its column's prefix could have produced are
+//!   kept (and the failure is reported) rather than judged on incomplete references.
+
+use crate::config::resolved::ResolvedEntity;
+use crate::error::AppError;
+use crate::handlers::entity::{get_or_load_package_model, resolve_tenant_context, TenantContext};
+use crate::service::{CrudService, TenantExecutor};
+use crate::state::AppState;
+use chrono::{DateTime, Duration, Utc};
+use regex::Regex;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeSet, HashSet};
+
+/// Rows read per page while collecting referenced paths.
+const PAGE_SIZE: u32 = 1000;
+use super::DEFAULT_ASSET_PREFIX;
+
+pub struct GcOptions {
+    /// Objects modified more recently than this are never orphans.
+    pub grace: Duration,
+    /// Delete the orphans found; when false the pass only reports them.
+    pub delete: bool,
+}
+
+impl Default for GcOptions {
+    fn default() -> Self {
+        GcOptions {
+            grace: Duration::hours(24),
+            delete: false,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct OrphanedAsset {
+    pub path: String,
+    pub size: u64,
+    pub last_modified: Option<DateTime<Utc>>,
+    /// Whether this pass deleted it.
+    pub deleted: bool,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct GcReport {
+    /// Storage prefixes that were listed.
+    pub prefixes: Vec<String>,
+    /// Objects listed that look like SDK uploads.
+    pub objects_scanned: usize,
+    pub referenced: usize,
+    /// Unreferenced objects kept because they are younger than the grace period.
+    pub within_grace: usize,
+    /// Unreferenced objects kept because a table that could reference them was unreadable.
+    pub unverified: usize,
+    pub orphans: Vec<OrphanedAsset>,
+    pub deleted: usize,
+    /// Tables that could not be read and deletes that failed.
+    pub errors: Vec<String>,
+}
+
+/// Where one asset column of one tenant puts its objects.
+pub(super) struct ScanTarget {
+    /// Literal start of every path the column produces; what gets listed.
+    pub(super) prefix: String,
+    /// Full shape of those paths.
+    pub(super) pattern: Regex,
+}
+
+/// Scan target for uploads to an asset column with prefix template `template`, for one tenant and
+/// table: `{tenant_id}` and `{entity}` are substituted, the date tokens match any digits, and the
+/// object name must be the UUID (plus optional extension) that uploads are given.
+pub(super) fn scan_target(template: &str, tenant_id: &str, table: &str) -> ScanTarget {
+    let template = template
+        .replace("{tenant_id}", tenant_id)
+        .replace("{entity}", table);
+    let mut prefix = String::new();
+    let mut pattern = String::from("^");
+    let mut literal = true;
+    let mut rest = template.as_str();
+    while !rest.is_empty() {
+        let (token, re) = [
+            ("{yyyy}", r"\d{4}"),
+            ("{mm}", r"\d{2}"),
+            ("{dd}", r"\d{2}"),
+            ("{hh}", r"\d{2}"),
+        ]
+        .into_iter()
+        .find(|(t, _)| rest.starts_with(t))
+        .map(|(t, re)| (t.len(), Some(re)))
+        .unwrap_or_else(|| (rest.chars().next().map_or(1, char::len_utf8), None));
+        match re {
+            Some(re) => {
+                literal = false;
+                pattern.push_str(re);
+            }
+            None => {
+                let ch = &rest[..token];
+                if literal {
+                    prefix.push_str(ch);
+                }
+                pattern.push_str(&regex::escape(ch));
+            }
+        }
+        rest = &rest[token..];
+    }
+    pattern.push_str(r"/[0-9a-f]{8}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{12}(\.[^/]*)?$");
+    ScanTarget {
+        prefix,
+        pattern: Regex::new(&pattern).expect("escaped prefix template is a valid regex"),
+    }
+}
+
+/// Storage paths held in one asset cell: a path string, a JSON array of paths (`asset[]`), or the
+/// array's text form on databases without a JSON type.
+fn cell_paths(cell: Option<&Value>, out: &mut HashSet<String>) {
+    match cell {
+        Some(Value::String(s)) if s.starts_with('[') => match serde_json::from_str::<Value>(s) {
+            Ok(v @ Value::Array(_)) => cell_paths(Some(&v), out),
+            _ => {
+                out.insert(s.clone());
+            }
+        },
+        Some(Value::String(s)) if !s.is_empty() => {
+            out.insert(s.clone());
+        }
+        Some(Value::Array(arr)) => {
+            for v in arr {
+                if let Value::String(s) = v {
+                    out.insert(s.clone());
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Drop prefixes covered by a shorter one, so no object is listed twice.
+fn minimal_prefixes(prefixes: BTreeSet<String>) -> Vec<String> {
+    let mut out: Vec<String> = Vec::new();
+    for p in prefixes {
+        if !out.iter().any(|kept| p.starts_with(kept.as_str())) {
+            out.push(p);
+        }
+    }
+    out
+}
+
+/// One GC pass over every tenant and installed package. See the module docs for what counts as an
+/// orphan. Fails without deleting anything when storage is not configured, packages cannot be
+/// listed, or a tenant's context or package model cannot be resolved.
+pub async fn collect_orphaned_assets(
+    state: &AppState,
+    options: &GcOptions,
+) -> Result<GcReport, AppError> {
+    let storage = state.storage.as_ref().ok_or_else(|| {
+        AppError::BadRequest("storage is not configured (set STORAGE_PROVIDER env var)".into())
+    })?;
+    let installed: Vec<String> = crate::store::list_packages(&state.pool)
+        .await?
+        .into_iter()
+        .map(|p| p.id)
+        .collect();
+    let mut package_ids = installed.clone();
+    if !package_ids
+        .iter()
+        .any(|p| p == crate::store::DEFAULT_PACKAGE_ID)
+    {
+        package_ids.push(crate::store::DEFAULT_PACKAGE_ID.to_string());
+    }
+
+    let mut report = GcReport::default();
+    let mut references: HashSet<String> = HashSet::new();
+    let mut targets: Vec<ScanTarget> = Vec::new();
+    let mut unverified: Vec<ScanTarget> = Vec::new();
+    let mut seen_targets: HashSet<String> = HashSet::new();
+    // Without database-enforced RLS, RLS tenants that share a database see the same rows; read
+    // each shared database once.
+    let mut shared_seen: HashSet<(Option<String>, String)> = HashSet::new();
+
+    for tenant_id in state.tenant_registry.tenant_ids() {
+        for package_id in &package_ids {
+            let ctx =
+                resolve_tenant_context(state, Some(&tenant_id), None, Some(package_id)).await?;
+            let model = match get_or_load_package_model(
+                state,
+                ctx.config_pool(),
+                ctx.package_cache_key(),
+                package_id,
+            )
+            .await
+            {
+                Ok(m) => m,
+                // The default model may simply not exist; an installed package must load.
+                Err(_) if !installed.contains(package_id) => continue,
+                Err(e) => return Err(e),
+            };
+            let mut read_rows = true;
+            if matches!(ctx, TenantContext::Rls { .. }) && !state.dialect.supports_rls() {
+                let db = state
+                    .tenant_registry
+                    .get(&tenant_id)
+                    .and_then(|e| e.database_url.clone());
+                read_rows = shared_seen.insert((db, package_id.clone()));
+            }
+
+            for entity in model
+                .entities
+                .iter()
+                .filter(|e| e.columns.iter().any(|c| c.is_asset))
+            {
+                let entity_targets: Vec<ScanTarget> = entity
+                    .columns
+                    .iter()
+                    .filter(|c| c.is_asset)
+                    .map(|c| {
+                        let template = c
+                            .asset_config
+                            .as_ref()
+                            .and_then(|a| a.prefix.as_deref())
+                            .unwrap_or(DEFAULT_ASSET_PREFIX);
+                        scan_target(template, &tenant_id, &entity.table_name)
+                    })
+                    .collect();
+                let outcome = if read_rows {
+                    collect_references(state, &ctx, entity, &mut references).await
+                } else {
+                    Ok(())
+                };
+                match outcome {
+                    Ok(()) => {
+                        for t in entity_targets {
+                            if seen_targets.insert(t.pattern.as_str().to_string()) {
+                                targets.push(t);
+                            }
+                        }
+                    }
+                    Err(e) => {
+                        tracing::warn!(
+                            tenant_id = %tenant_id,
+                            package_id = %package_id,
+                            entity = %entity.path_segment,
+                            error = %e,
+                            "asset gc: table unreadable, its prefixes are kept"
+                        );
+                        report.errors.push(format!(
+                            "tenant '{}', package '{}', entity '{}': {}",
+                            tenant_id, package_id, entity.path_segment, e
+                        ));
+                        unverified.extend(entity_targets);
+                    }
+                }
+            }
+        }
+    }
+
+    report.prefixes = minimal_prefixes(targets.iter().map(|t| t.prefix.clone()).collect());
+    let cutoff = Utc::now() - options.grace;
+    for prefix in &report.prefixes {
+        for obj in storage.list(prefix).await? {
+            if !targets.iter().any(|t| t.pattern.is_match(&obj.path)) {
+                continue;
+            }
+            report.objects_scanned += 1;
+            if references.contains(&obj.path) {
+                report.referenced += 1;
+                continue;
+            }
+            if unverified.iter().any(|t| t.pattern.is_match(&obj.path)) {
+                report.unverified += 1;
+                continue;
+            }
+            if !obj.last_modified.is_some_and(|t| t < cutoff) {
+                report.within_grace += 1;
+                continue;
+            }
+            let mut deleted = false;
+            if options.delete {
+                match storage.delete(&obj.path).await {
+                    Ok(()) => {
+                        deleted = true;
+                        report.deleted += 1;
+                    }
+                    Err(e) => report.errors.push(format!("delete {}: {}", obj.path, e)),
+                }
+            }
+            report.orphans.push(OrphanedAsset {
+                path: obj.path,
+                size: obj.size,
+                last_modified: obj.last_modified,
+                deleted,
+            });
+        }
+    }
+    tracing::info!(
+        scanned = report.objects_scanned,
+        orphans = report.orphans.len(),
+        deleted = report.deleted,
+        "asset gc finished"
+    );
+    Ok(report)
+}
+
+/// Add every path held in `entity`'s asset columns — and in its `_history` table when versioning
+/// is enabled — to `out`.
+async fn collect_references(
+    state: &AppState,
+    ctx: &TenantContext,
+    entity: &ResolvedEntity,
+    out: &mut HashSet<String>,
+) -> Result<(), AppError> {
+    let versioned = entity.versioning.as_ref().is_some_and(|v| v.enabled);
+    for history in [false, true] {
+        if history && !versioned {
+            continue;
+        }
+        let key = crate::sql::asset_page_key(entity, history);
+        let mut after: Option<Vec<Value>> = None;
+        loop {
+            let rows = fetch_asset_page(state, ctx, entity, history, after.take()).await?;
+            for row in &rows {
+                for col in entity.columns.iter().filter(|c| c.is_asset) {
+                    cell_paths(row.get(&col.name), out);
+                }
+            }
+            if rows.len() < PAGE_SIZE as usize {
+                break;
+            }
+            after = rows.last().and_then(|r| {
+                key.iter()
+                    .map(|k| r.get(k).filter(|v| !v.is_null()).cloned())
+                    .collect::<Option<Vec<Value>>>()
+            });
+            if after.is_none() {
+                break;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// One page of asset columns, read inside a tenant-scoped transaction for RLS tenants.
+async fn fetch_asset_page(
+    state: &AppState,
+    ctx: &TenantContext,
+    entity: &ResolvedEntity,
+    history: bool,
+    after: Option<Vec<Value>>,
+) -> Result<Vec<Value>, AppError> {
+    let pool = ctx.migration_pool();
+    let dialect = state.dialect.as_ref();
+    let mut rls_tx = match ctx.rls_tenant_id() {
+        Some(tenant) => {
+            let mut tx = pool.begin().await?;
+            if let Some(sql) = dialect.set_tenant_session_sql(tenant) {
+                sqlx::query(&sql).execute(&mut *tx).await?;
+            }
+            Some(tx)
+        }
+        None => None,
+    };
+    let rows = {
+        let mut executor = match rls_tx.as_mut() {
+            Some(tx) => TenantExecutor::conn(tx, dialect),
+            None => TenantExecutor::pool(pool, dialect),
+        };
+        CrudService::list_asset_page(
+            &mut executor,
+            entity,
+            history,
+            after,
+            PAGE_SIZE,
+            ctx.schema_override(),
+        )
+        .await?
+    };
+    if let Some(tx) = rls_tx {
+        tx.commit().await?;
+    }
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod gc_tests {
+    use super::*;
+
+    #[test]
+    fn scan_target_matches_generated_paths_only() {
+        let t = scan_target("{tenant_id}/{entity}/{yyyy}/{mm}", "acme", "invoices");
+        assert_eq!(t.prefix, "acme/invoices/");
+        let id = uuid::Uuid::new_v4();
+        assert!(t
+            .pattern
+            .is_match(&format!("acme/invoices/2026/10/{}.pdf", id)));
+        assert!(t.pattern.is_match(&format!("acme/invoices/2026/10/{}", id)));
+        assert!(!t
+            .pattern
+            .is_match(&format!("beta/invoices/2026/10/{}.pdf", id)));
+        assert!(!t.pattern.is_match("acme/invoices/2026/10/report.pdf"));
+        assert!(!t
+            .pattern
+            .is_match(&format!("acme/invoices/2026/10/x/{}.pdf", id)));
+    }
+
+    #[test]
+    fn scan_target_escapes_literals_and_stops_prefix_at_first_date_token() {
+        let t = scan_target("up.loads/{yyyy}-{entity}", "t1", "docs");
+        assert_eq!(t.prefix, "up.loads/");
+        let id = uuid::Uuid::new_v4();
+        assert!(t
+            .pattern
+            .is_match(&format!("up.loads/2026-docs/{}.json", id)));
+        assert!(!t
+            .pattern
+            .is_match(&format!("upXloads/2026-docs/{}.json", id)));
+    }
+
+    #[test]
+    fn cell_paths_reads_strings_arrays_and_array_text() {
+        let mut out = HashSet::new();
+        cell_paths(Some(&Value::String("a/1.png".into())), &mut out);
+        cell_paths(Some(&serde_json::json!(["a/2.png", "a/3.png"])), &mut out);
+        cell_paths(Some(&Value::String(r#"["a/4.png"]"#.into())), &mut out);
+        cell_paths(Some(&Value::Null), &mut out);
+        let mut paths: Vec<_> = out.into_iter().collect();
+        paths.sort();
+        assert_eq!(paths, ["a/1.png", "a/2.png", "a/3.png", "a/4.png"]);
+    }
+
+    #[test]
+    fn minimal_prefixes_drops_covered_ones() {
+        let set: BTreeSet<String> = ["a/b/", "a/", "c/", "a/c/"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        assert_eq!(minimal_prefixes(set), ["a/", "c/"]);
+    }
+}
@@ -11,13 +11,18 @@
 
 use crate::error::AppError;
 use async_trait::async_trait;
+use bytes::Bytes;
 use chrono::{Duration, Utc};
+use futures_util::{StreamExt, TryStreamExt};
 use google_cloud_storage::client::{Client, ClientConfig};
 use google_cloud_storage::http::objects::delete::DeleteObjectRequest;
+use google_cloud_storage::http::objects::download::Range;
+use google_cloud_storage::http::objects::get::GetObjectRequest;
+use google_cloud_storage::http::objects::list::ListObjectsRequest;
 use google_cloud_storage::http::objects::upload::{Media, UploadObjectRequest, UploadType};
 use google_cloud_storage::sign::{SignedURLMethod, SignedURLOptions};
 
-use super::PresignResult;
+use super::{ByteStream, ObjectMeta, PresignResult, PresignedUpload, StoredObject};
 use crate::storage::StorageProvider;
 
 // ── Provider ──────────────────────────────────────────────────────────────────
@@ -61,25 +66,60 @@ impl GcsProvider {
 
 #[async_trait]
 impl StorageProvider for GcsProvider {
-    async fn upload(&self, path: &str, data: Vec<u8>, content_type: &str) -> Result<(), AppError> {
+    async fn upload(
+        &self,
+        path: &str,
+        mut data: ByteStream<'_>,
+        content_type: &str,
+    ) -> Result<(), AppError> {
         let mut media = Media::new(path.to_string());
         media.content_type = std::borrow::Cow::Owned(content_type.to_string());
-
         let upload_type = UploadType::Simple(media);
-        self.client
-            .upload_object(
-                &UploadObjectRequest {
-                    bucket: self.bucket.clone(),
-                    ..Default::default()
-                },
-                data,
-                &upload_type,
-            )
-            .await
-            .map_err(|e| AppError::Storage(format!("GCS upload error: {}", e)))?;
+
+        // The client wants a `'static` body, while `data` may borrow the request. Pipe it through
+        // a small channel and drive both ends on this task.
+        let (tx, mut rx) = tokio::sync::mpsc::channel::<std::io::Result<Bytes>>(4);
+        let body = futures_util::stream::poll_fn(move |cx| rx.poll_recv(cx));
+        let request = UploadObjectRequest {
+            bucket: self.bucket.clone(),
+            ..Default::default()
+        };
+        let upload = self
+            .client
+            .upload_streamed_object(&request, body, &upload_type);
+        let feed = async move {
+            while let Some(chunk) = data.next().await {
+                if tx.send(chunk).await.is_err() {
+                    break;
+                }
+            }
+        };
+        let (result, ()) = tokio::join!(upload, feed);
+        result.map_err(|e| AppError::Storage(format!("GCS upload error: {}", e)))?;
         Ok(())
     }
 
+    async fn download(&self, path: &str) -> Result<ByteStream<'static>, AppError> {
+        let request = GetObjectRequest {
+            bucket: self.bucket.clone(),
+            object: path.to_string(),
+            ..Default::default()
+        };
+        match self
+            .client
+            .download_streamed_object(&request, &Range::default())
+            .await
+        {
+            Ok(stream) => Ok(Box::pin(
+                stream.map_err(|e| std::io::Error::other(e.to_string())),
+            )),
+            Err(google_cloud_storage::http::Error::Response(e)) if e.code == 404 => {
+                Err(AppError::NotFound(format!("asset not found: {}", path)))
+            }
+            Err(e) => Err(AppError::Storage(format!("GCS download error: {}", e))),
+        }
+    }
+
     async fn presign_url(&self, path: &str, expires_secs: u64) -> Result<PresignResult, AppError> {
         let expires_at = Utc::now() + Duration::seconds(expires_secs as i64);
 
@@ -115,4 +155,82 @@ impl StorageProvider for GcsProvider {
             .map_err(|e| AppError::Storage(format!("GCS delete error: {}", e)))?;
         Ok(())
     }
+
+    async fn presign_upload(
+        &self,
+        path: &str,
+        content_type: &str,
+        expires_secs: u64,
+    ) -> Result<PresignedUpload, AppError> {
+        let expires_at = Utc::now() + Duration::seconds(expires_secs as i64);
+
+        let opts = SignedURLOptions {
+            method: SignedURLMethod::PUT,
+            expires: std::time::Duration::from_secs(expires_secs),
+            content_type: Some(content_type.to_string()),
+            ..Default::default()
+        };
+        let url = self
+            .client
+            .signed_url(&self.bucket, path, None, None, opts)
+            .await
+            .map_err(|e| AppError::Storage(format!("GCS signed URL error: {}", e)))?;
+
+        Ok(PresignedUpload {
+            url,
+            method: "PUT",
+            headers: vec![("content-type".into(), content_type.into())],
+            expires_at,
+            expires_in: expires_secs,
+        })
+    }
+
+    async fn head(&self, path: &str) -> Result<Option<ObjectMeta>, AppError> {
+        let result = self
+            .client
+            .get_object(&GetObjectRequest {
+                bucket: self.bucket.clone(),
+                object: path.to_string(),
+                ..Default::default()
+            })
+            .await;
+        match result {
+            Ok(obj) => Ok(Some(ObjectMeta {
+                size: obj.size.max(0) as u64,
+                content_type: obj.content_type,
+            })),
+            Err(google_cloud_storage::http::Error::Response(e)) if e.code == 404 => Ok(None),
+            Err(e) => Err(AppError::Storage(format!("GCS metadata error: {}", e))),
+        }
+    }
+
+    async fn list(&self, prefix: &str) -> Result<Vec<StoredObject>, AppError> {
+        let mut out = Vec::new();
+        let mut page_token = None;
+        loop {
+            let page = self
+                .client
+                .list_objects(&ListObjectsRequest {
+                    bucket: self.bucket.clone(),
+                    prefix: Some(prefix.to_string()),
+                    page_token,
+                    ..Default::default()
+                })
+                .await
+                .map_err(|e| AppError::Storage(format!("GCS list error: {}", e)))?;
+            for obj in page.items.unwrap_or_default() {
+                out.push(StoredObject {
+                    path: obj.name,
+                    size: obj.size.max(0) as u64,
+                    last_modified: obj.updated.and_then(|t| {
+                        chrono::DateTime::from_timestamp(t.unix_timestamp(), t.nanosecond())
+                    }),
+                });
+            }
+            match page.next_page_token {
+                Some(token) => page_token = Some(token),
+                None => return Ok(out),
+            }
+        }
+    }
 }
@@ -0,0 +1,452 @@
+//! Local filesystem storage provider, for development and CI where no bucket is available.
+//!
+//! Objects are plain files under `STORAGE_LOCAL_ROOT`; each file's content type is kept next to
+//! it in a `<name>.content-type` sidecar. Presigned URLs point at the SDK's own route
+//! (`GET`/`PUT {STORAGE_LOCAL_PUBLIC_URL}/assets/local/<path>?expires=<unix>&sig=<hex>`), where
+//! `sig` is an HMAC-SHA256 of the method, path and expiry under `STORAGE_LOCAL_SECRET` — a
+//! download link cannot be replayed as an upload.
+//!
+//! Env vars:
+//!   - `STORAGE_LOCAL_ROOT`       — directory holding the objects (default `./storage`)
+//!   - `STORAGE_LOCAL_SECRET`     — URL signing key; a random per-process key is used when unset,
+//!     so signed URLs stop working after a restart
+//!   - `STORAGE_LOCAL_PUBLIC_URL` — prefix the download route is mounted under (default `/api/v1`)
+//!   - `STORAGE_LOCAL_MAX_UPLOAD_MB` — largest body the signed upload route accepts (default 100)
+
+use crate::error::AppError;
+use async_trait::async_trait;
+use chrono::Utc;
+use futures_util::StreamExt;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::path::{Component, Path, PathBuf};
+use tokio::io::AsyncWriteExt;
+use tokio_util::io::ReaderStream;
+
+use super::{ByteStream, ObjectMeta, PresignResult, PresignedUpload, StoredObject};
+use crate::storage::StorageProvider;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Suffix of the sidecar file holding an object's content type.
+pub(crate) const CONTENT_TYPE_SUFFIX: &str = ".content-type";
+
+/// Default for `STORAGE_LOCAL_MAX_UPLOAD_MB`.
+const DEFAULT_MAX_UPLOAD_MB: u64 = 100;
+
+// ── Provider ──────────────────────────────────────────────────────────────────
+
+pub struct LocalProvider {
+    root: PathBuf,
+    secret: Vec<u8>,
+    public_url: String,
+    max_upload_bytes: u64,
+}
+
+impl LocalProvider {
+    pub fn new(root: impl Into<PathBuf>, secret: impl Into<Vec<u8>>, public_url: &str) -> Self {
+        LocalProvider {
+            root: root.into(),
+            secret: secret.into(),
+            public_url: public_url.trim_end_matches('/').to_string(),
+            max_upload_bytes: DEFAULT_MAX_UPLOAD_MB * 1024 * 1024,
+        }
+    }
+
+    /// Cap the body size of the signed upload route (`PUT /assets/local/*path`).
+    pub fn with_max_upload_bytes(mut self, max_upload_bytes: u64) -> Self {
+        self.max_upload_bytes = max_upload_bytes;
+        self
+    }
+
+    /// Largest body the signed upload route accepts. Presigned uploads skip the column's
+    /// `max_size_mb` until the path is saved, so this bounds what reaches the disk first.
+    pub fn max_upload_bytes(&self) -> u64 {
+        self.max_upload_bytes
+    }
+
+    /// Build from environment variables. Never fails: every variable has a default.
+    pub fn from_env() -> Self {
+        let root = std::env::var("STORAGE_LOCAL_ROOT").unwrap_or_else(|_| "./storage".into());
+        let secret = match std::env::var("STORAGE_LOCAL_SECRET") {
+            Ok(s) if !s.is_empty() => s.into_bytes(),
+            _ => {
+                tracing::warn!(
+                    "STORAGE_LOCAL_SECRET not set; using a random key — signed asset URLs will not survive a restart"
+                );
+                let mut key = uuid::Uuid::new_v4().as_bytes().to_vec();
+                key.extend_from_slice(uuid::Uuid::new_v4().as_bytes());
+                key
+            }
+        };
+        let public_url =
+            std::env::var("STORAGE_LOCAL_PUBLIC_URL").unwrap_or_else(|_| "/api/v1".into());
+        let max_upload_mb = std::env::var("STORAGE_LOCAL_MAX_UPLOAD_MB")
+            .ok()
+            .and_then(|s| s.parse::<u64>().ok())
+            .unwrap_or(DEFAULT_MAX_UPLOAD_MB);
+        tracing::info!(root = %root, "local filesystem storage enabled");
+        LocalProvider::new(root, secret, &public_url)
+            .with_max_upload_bytes(max_upload_mb * 1024 * 1024)
+    }
+
+    /// Map an object path onto the root directory. Rejects anything that could escape it
+    /// (`..`, absolute paths, drive prefixes) and empty paths.
+    fn file_path(&self, path: &str) -> Result<PathBuf, AppError> {
+        let rel = Path::new(path);
+        let safe = !path.is_empty()
+            && rel
+                .components()
+                .all(|c| matches!(c, Component::Normal(_) | Component::CurDir));
+        if !safe {
+            return Err(AppError::BadRequest(format!(
+                "invalid asset path: {}",
+                path
+            )));
+        }
+        Ok(self.root.join(rel))
+    }
+
+    fn signature(&self, method: &str, path: &str, expires: i64) -> HmacSha256 {
+        let mut mac =
+            HmacSha256::new_from_slice(&self.secret).expect("HMAC accepts keys of any length");
+        mac.update(method.as_bytes());
+        mac.update(b"\n");
+        mac.update(path.as_bytes());
+        mac.update(b"\n");
+        mac.update(expires.to_string().as_bytes());
+        mac
+    }
+
+    /// Signed URL for `method` on `path`, and when it expires.
+    fn signed_url(
+        &self,
+        method: &str,
+        path: &str,
+        expires_secs: u64,
+    ) -> (String, chrono::DateTime<Utc>) {
+        let expires_at = Utc::now() + chrono::Duration::seconds(expires_secs as i64);
+        let expires = expires_at.timestamp();
+        let sig = hex::encode(
+            self.signature(method, path, expires)
+                .finalize()
+                .into_bytes(),
+        );
+        let url = format!(
+            "{}/assets/local/{}?expires={}&sig={}",
+            self.public_url,
+            encode_path(path),
+            expires,
+            sig
+        );
+        (url, expires_at)
+    }
+
+    /// Check a signed URL's `expires` / `sig` pair for `method` (`GET` or `PUT`) on `path`.
+    /// Expired and forged links are both rejected with 403 and the same message, so a caller
+    /// cannot probe which one it was.
+    pub fn verify(
+        &self,
+        method: &str,
+        path: &str,
+        expires: i64,
+        sig: &str,
+    ) -> Result<(), AppError> {
+        let forbidden = || AppError::Forbidden("invalid or expired asset link".into());
+        if expires < Utc::now().timestamp() {
+            return Err(forbidden());
+        }
+        let sig = hex::decode(sig).map_err(|_| forbidden())?;
+        self.signature(method, path, expires)
+            .verify_slice(&sig)
+            .map_err(|_| forbidden())
+    }
+}
+
+fn sidecar(file: &Path) -> PathBuf {
+    let mut name = file.as_os_str().to_owned();
+    name.push(CONTENT_TYPE_SUFFIX);
+    PathBuf::from(name)
+}
+
+/// Percent-encode each segment of an object path for use in a URL, keeping the `/` separators.
+fn encode_path(path: &str) -> String {
+    let mut out = String::with_capacity(path.len());
+    for b in path.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' => {
+                out.push(b as char)
+            }
+            _ => out.push_str(&format!("%{:02X}", b)),
+        }
+    }
+    out
+}
+
+#[async_trait]
+impl StorageProvider for LocalProvider {
+    async fn upload(
+        &self,
+        path: &str,
+        mut data: ByteStream<'_>,
+        content_type: &str,
+    ) -> Result<(), AppError> {
+        let file = self.file_path(path)?;
+        if let Some(dir) = file.parent() {
+            tokio::fs::create_dir_all(dir)
+                .await
+                .map_err(|e| AppError::Storage(format!("create {}: {}", dir.display(), e)))?;
+        }
+        let write_err = |e: std::io::Error| AppError::Storage(format!("write {}: {}", path, e));
+        let mut out = tokio::fs::File::create(&file).await.map_err(write_err)?;
+        while let Some(chunk) = data.next().await {
+            out.write_all(&chunk.map_err(write_err)?)
+                .await
+                .map_err(write_err)?;
+        }
+        out.flush().await.map_err(write_err)?;
+        tokio::fs::write(sidecar(&file), content_type)
+            .await
+            .map_err(write_err)?;
+        Ok(())
+    }
+
+    async fn download(&self, path: &str) -> Result<ByteStream<'static>, AppError> {
+        let file = self.file_path(path)?;
+        match tokio::fs::File::open(&file).await {
+            Ok(f) => Ok(Box::pin(ReaderStream::new(f))),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                Err(AppError::NotFound(format!("asset not found: {}", path)))
+            }
+            Err(e) => Err(AppError::Storage(format!("read {}: {}", path, e))),
+        }
+    }
+
+    async fn presign_url(&self, path: &str, expires_secs: u64) -> Result<PresignResult, AppError> {
+        self.file_path(path)?;
+        let (url, expires_at) = self.signed_url("GET", path, expires_secs);
+        Ok(PresignResult {
+            url,
+            expires_at,
+            expires_in: expires_secs,
+        })
+    }
+
+    async fn delete(&self, path: &str) -> Result<(), AppError> {
+        let file = self.file_path(path)?;
+        for f in [sidecar(&file), file] {
+            match tokio::fs::remove_file(&f).await {
+                Ok(()) => {}
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => return Err(AppError::Storage(format!("delete {}: {}", path, e))),
+            }
+        }
+        Ok(())
+    }
+
+    async fn presign_upload(
+        &self,
+        path: &str,
+        content_type: &str,
+        expires_secs: u64,
+    ) -> Result<PresignedUpload, AppError> {
+        self.file_path(path)?;
+        let (url, expires_at) = self.signed_url("PUT", path, expires_secs);
+        Ok(PresignedUpload {
+            url,
+            method: "PUT",
+            headers: vec![("content-type".into(), content_type.into())],
+            expires_at,
+            expires_in: expires_secs,
+        })
+    }
+
+    async fn head(&self, path: &str) -> Result<Option<ObjectMeta>, AppError> {
+        let file = self.file_path(path)?;
+        let meta = match tokio::fs::metadata(&file).await {
+            Ok(m) if m.is_file() => m,
+            Ok(_) => return Ok(None),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(AppError::Storage(format!("stat {}: {}", path, e))),
+        };
+        let content_type = tokio::fs::read_to_string(sidecar(&file))
+            .await
+            .ok()
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty());
+        Ok(Some(ObjectMeta {
+            size: meta.len(),
+            content_type,
+        }))
+    }
+
+    async fn list(&self, prefix: &str) -> Result<Vec<StoredObject>, AppError> {
+        let list_err = |e: std::io::Error| AppError::Storage(format!("list {}: {}", prefix, e));
+        let mut out = Vec::new();
+        let mut dirs = vec![self.root.clone()];
+        while let Some(dir) = dirs.pop() {
+            let mut entries = match tokio::fs::read_dir(&dir).await {
+                Ok(entries) => entries,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(list_err(e)),
+            };
+            while let Some(entry) = entries.next_entry().await.map_err(list_err)? {
+                let file = entry.path();
+                let Some(rel) = file.strip_prefix(&self.root).ok().and_then(|p| p.to_str()) else {
+                    continue;
+                };
+                let rel = rel.replace(std::path::MAIN_SEPARATOR, "/");
+                let meta = entry.metadata().await.map_err(list_err)?;
+                if meta.is_dir() {
+                    // Only descend where matches can be.
+                    let dir_prefix = format!("{}/", rel);
+                    if dir_prefix.starts_with(prefix) || prefix.starts_with(&dir_prefix) {
+                        dirs.push(file);
+                    }
+                } else if rel.starts_with(prefix) && !rel.ends_with(CONTENT_TYPE_SUFFIX) {
+                    out.push(StoredObject {
+                        path: rel,
+                        size: meta.len(),
+                        last_modified: meta.modified().ok().map(chrono::DateTime::<Utc>::from),
+                    });
+                }
+            }
+        }
+        Ok(out)
+    }
+
+    fn as_local(&self) -> Option<&LocalProvider> {
+        Some(self)
+    }
+}
+
+#[cfg(test)]
+mod local_storage_tests {
+    use super::*;
+    use crate::storage::{bytes_stream, collect_bytes};
+
+    fn provider(root: &Path) -> LocalProvider {
+        LocalProvider::new(root, b"test-secret".to_vec(), "/api/v1/")
+    }
+
+    fn temp_root() -> PathBuf {
+        std::env::temp_dir().join(format!("architect-local-{}", uuid::Uuid::new_v4()))
+    }
+
+    fn query_param<'a>(url: &'a str, name: &str) -> &'a str {
+        url.split(['?', '&'])
+            .find_map(|kv| kv.strip_prefix(&format!("{}=", name)))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn upload_download_delete_round_trip() {
+        let root = temp_root();
+        let p = provider(&root);
+        let chunks = futures_util::stream::iter([
+            Ok(bytes::Bytes::from_static(b"%P")),
+            Ok(bytes::Bytes::from_static(b"DF")),
+        ]);
+        p.upload("acme/docs/a.pdf", Box::pin(chunks), "application/pdf")
+            .await
+            .unwrap();
+        let data = collect_bytes(p.download("acme/docs/a.pdf").await.unwrap())
+            .await
+            .unwrap();
+        assert_eq!(data, b"%PDF");
+        let meta = p.head("acme/docs/a.pdf").await.unwrap().unwrap();
+        assert_eq!(meta.size, 4);
+        assert_eq!(meta.content_type.as_deref(), Some("application/pdf"));
+        p.delete("acme/docs/a.pdf").await.unwrap();
+        assert!(matches!(
+            p.download("acme/docs/a.pdf").await,
+            Err(AppError::NotFound(_))
+        ));
+        assert!(p.head("acme/docs/a.pdf").await.unwrap().is_none());
+        let _ = std::fs::remove_dir_all(root);
+    }
+
+    #[tokio::test]
+    async fn list_filters_by_prefix_and_skips_sidecars() {
+        let root = temp_root();
+        let p = provider(&root);
+        for path in [
+            "acme/docs/a.pdf",
+            "acme/docs/b.pdf",
+            "acme/img/c.png",
+            "other/d.txt",
+        ] {
+            p.upload(path, bytes_stream(&b"xy"[..]), "text/plain")
+                .await
+                .unwrap();
+        }
+        let mut paths: Vec<String> = p
+            .list("acme/d")
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|o| o.path)
+            .collect();
+        paths.sort();
+        assert_eq!(paths, ["acme/docs/a.pdf", "acme/docs/b.pdf"]);
+        let all = p.list("").await.unwrap();
+        assert_eq!(all.len(), 4);
+        assert!(all.iter().all(|o| o.size == 2 && o.last_modified.is_some()));
+        let _ = std::fs::remove_dir_all(root);
+    }
+
+    #[tokio::test]
+    async fn presigned_url_verifies_and_rejects_tampering() {
+        let p = provider(&temp_root());
+        let url = p.presign_url("acme/my file.png", 60).await.unwrap().url;
+        assert!(url.starts_with("/api/v1/assets/local/acme/my%20file.png?"));
+        let expires: i64 = query_param(&url, "expires").parse().unwrap();
+        let sig = query_param(&url, "sig");
+        assert!(p.verify("GET", "acme/my file.png", expires, sig).is_ok());
+        assert!(p.verify("GET", "acme/other.png", expires, sig).is_err());
+        assert!(p
+            .verify("GET", "acme/my file.png", expires + 1, sig)
+            .is_err());
+        assert!(p.verify("GET", "acme/my file.png", expires, "zz").is_err());
+        // A download link is not an upload link.
+        assert!(p.verify("PUT", "acme/my file.png", expires, sig).is_err());
+    }
+
+    #[tokio::test]
+    async fn presigned_upload_is_put_only() {
+        let p = provider(&temp_root());
+        let up = p
+            .presign_upload("acme/a.csv", "text/csv", 60)
+            .await
+            .unwrap();
+        assert_eq!(up.method, "PUT");
+        let expires: i64 = query_param(&up.url, "expires").parse().unwrap();
+        let sig = query_param(&up.url, "sig");
+        assert!(p.verify("PUT", "acme/a.csv", expires, sig).is_ok());
+        assert!(p.verify("GET", "acme/a.csv", expires, sig).is_err());
+    }
+
+    #[tokio::test]
+    async fn expired_link_is_rejected() {
+        let p = provider(&temp_root());
+        let past = Utc::now().timestamp() - 10;
+        let sig = hex::encode(p.signature("GET", "a.txt", past).finalize().into_bytes());
+        assert!(matches!(
+            p.verify("GET", "a.txt", past, &sig),
+            Err(AppError::Forbidden(_))
+        ));
+    }
+
+    #[test]
+    fn paths_cannot_escape_root() {
+        let p = provider(Path::new("/srv/assets"));
+        assert!(p.file_path("../etc/passwd").is_err());
+        assert!(p.file_path("a/../../b").is_err());
+        assert!(p.file_path("/etc/passwd").is_err());
+        assert!(p.file_path("").is_err());
+        assert_eq!(
+            p.file_path("t1/x.png").unwrap(),
+            PathBuf::from("/srv/assets/t1/x.png")
+        );
+    }
+}
@@ -1,4 +1,5 @@
-//! Storage provider abstraction: upload, presign, delete.
+//! Storage provider abstraction: streaming upload/download, presign, delete, presigned uploads,
+//! listing (for the orphaned-asset GC in [`gc`]).
 //!
 //! Enable backends via Cargo features:
 //!   - `storage-s3`   — AWS S3 and S3-compatible endpoints (RustFS, MinIO)
@@ -6,36 +7,188 @@
 //!   - `storage-gcs`  — Google Cloud Storage
 //!   - `storage-all`  — all three
 //!
-//! Set `STORAGE_PROVIDER` env var to `s3`, `rustfs`, `azure`, or `gcs` at runtime.
+//! The `local` filesystem backend (see [`local`]) is always available.
+//!
+//! Set `STORAGE_PROVIDER` env var to `s3`, `rustfs`, `azure`, `gcs`, or `local` at runtime.
 
 use crate::error::AppError;
 use async_trait::async_trait;
+use bytes::Bytes;
 use chrono::{DateTime, Utc};
+use futures_util::Stream;
+use std::pin::Pin;
 use std::sync::Arc;
+use tokio_util::io::{ReaderStream, StreamReader};
 
 #[cfg(feature = "storage-azure")]
 pub mod azure;
+pub mod gc;
 #[cfg(feature = "storage-gcs")]
 pub mod gcs;
+pub mod local;
 
 // ── Public result types ───────────────────────────────────────────────────────
 
+/// A stream of object bytes. Uploads may borrow their source (e.g. a multipart field) for `'a`;
+/// downloads are always `'static`.
+pub type ByteStream<'a> = Pin<Box<dyn Stream<Item = std::io::Result<Bytes>> + Send + 'a>>;
+
 pub struct PresignResult {
     pub url: String,
     pub expires_at: DateTime<Utc>,
     pub expires_in: u64,
 }
 
+/// A presigned URL the client uploads an object to directly, bypassing the API.
+pub struct PresignedUpload {
+    pub url: String,
+    /// HTTP method the client must use (always `PUT` for the built-in providers).
+    pub method: &'static str,
+    /// Headers the client must send with the upload; they are part of the signature.
+    pub headers: Vec<(String, String)>,
+    pub expires_at: DateTime<Utc>,
+    pub expires_in: u64,
+}
+
+/// What the provider knows about a stored object.
+pub struct ObjectMeta {
+    pub size: u64,
+    pub content_type: Option<String>,
+}
+
+/// One object returned by [`StorageProvider::list`].
+#[derive(Clone, Debug)]
+pub struct StoredObject {
+    pub path: String,
+    pub size: u64,
+    /// When the object was last written, if the backend reports it.
+    pub last_modified: Option<DateTime<Utc>>,
+}
+
 // ── Trait ─────────────────────────────────────────────────────────────────────
 
 #[async_trait]
 pub trait StorageProvider: Send + Sync {
-    /// Upload `data` to `path` in the configured bucket. Returns the stored path.
-    async fn upload(&self, path: &str, data: Vec<u8>, content_type: &str) -> Result<(), AppError>;
+    /// Upload `data` to `path` in the configured bucket, consuming the stream as it goes; the
+    /// object is never held in memory as a whole. Wrap an in-memory buffer with [`bytes_stream`].
+    async fn upload(
+        &self,
+        path: &str,
+        data: ByteStream<'_>,
+        content_type: &str,
+    ) -> Result<(), AppError>;
+    /// Stream the object at `path`. Fails with `NotFound` when it does not exist.
+    async fn download(&self, path: &str) -> Result<ByteStream<'static>, AppError>;
     /// Generate a presigned GET URL for `path` valid for `expires_secs` seconds.
     async fn presign_url(&self, path: &str, expires_secs: u64) -> Result<PresignResult, AppError>;
     /// Delete the object at `path`.
     async fn delete(&self, path: &str) -> Result<(), AppError>;
+    /// Generate a presigned URL the client can upload `path` to directly, valid for
+    /// `expires_secs` seconds. `content_type` is bound into the signature where the backend
+    /// supports it. The default reports that the provider cannot presign uploads.
+    async fn presign_upload(
+        &self,
+        path: &str,
+        content_type: &str,
+        expires_secs: u64,
+    ) -> Result<PresignedUpload, AppError> {
+        let _ = (path, content_type, expires_secs);
+        Err(AppError::Storage(
+            "this storage provider does not support presigned uploads".into(),
+        ))
+    }
+    /// Size and content type of the object at `path`, or `None` when it does not exist.
+    async fn head(&self, path: &str) -> Result<Option<ObjectMeta>, AppError> {
+        let _ = path;
+        Err(AppError::Storage(
+            "this storage provider cannot inspect stored objects".into(),
+        ))
+    }
+    /// Every object whose path starts with `prefix` (`""` lists the whole bucket). The default
+    /// reports that the provider cannot list objects.
+    async fn list(&self, prefix: &str) -> Result<Vec<StoredObject>, AppError> {
+        let _ = prefix;
+        Err(AppError::Storage(
+            "this storage provider cannot list stored objects".into(),
+        ))
+    }
+    /// The local filesystem provider, when this is one. Its presigned URLs point back at the SDK's
+    /// own download route, which needs the provider to verify and serve them.
+    fn as_local(&self) -> Option<&local::LocalProvider> {
+        None
+    }
+}
+
+// ── Metrics ───────────────────────────────────────────────────────────────────
+
+/// Wraps a provider to record `architect_storage_operation_duration_seconds` for every call (see
+/// [`crate::metrics`]). For downloads this is the time until the stream is open.
+/// [`init_storage_provider`] wraps the provider it builds.
+pub struct InstrumentedStorage {
+    provider: &'static str,
+    inner: Arc<dyn StorageProvider>,
+}
+
+impl InstrumentedStorage {
+    /// `provider` labels the metrics, e.g. `s3` or `local`.
+    pub fn new(provider: &'static str, inner: Arc<dyn StorageProvider>) -> Self {
+        Self { provider, inner }
+    }
+
+    async fn observe<T>(
+        &self,
+        operation: &str,
+        call: impl std::future::Future<Output = Result<T, AppError>>,
+    ) -> Result<T, AppError> {
+        let start = std::time::Instant::now();
+        let result = call.await;
+        crate::metrics::observe_storage(self.provider, operation, result.is_ok(), start.elapsed());
+        result
+    }
+}
+
+#[async_trait]
+impl StorageProvider for InstrumentedStorage {
+    async fn upload(
+        &self,
+        path: &str,
+        data: ByteStream<'_>,
+        content_type: &str,
+    ) -> Result<(), AppError> {
+        self.observe("upload", self.inner.upload(path, data, content_type))
+            .await
+    }
+    async fn download(&self, path: &str) -> Result<ByteStream<'static>, AppError> {
+        self.observe("download", self.inner.download(path)).await
+    }
+    async fn presign_url(&self, path: &str, expires_secs: u64) -> Result<PresignResult, AppError> {
+        self.observe("presign_url", self.inner.presign_url(path, expires_secs))
+            .await
+    }
+    async fn delete(&self, path: &str) -> Result<(), AppError> {
+        self.observe("delete", self.inner.delete(path)).await
+    }
+    async fn presign_upload(
+        &self,
+        path: &str,
+        content_type: &str,
+        expires_secs: u64,
+    ) -> Result<PresignedUpload, AppError> {
+        self.observe(
+            "presign_upload",
+            self.inner.presign_upload(path, content_type, expires_secs),
+        )
+        .await
+    }
+    async fn head(&self, path: &str) -> Result<Option<ObjectMeta>, AppError> {
+        self.observe("head", self.inner.head(path)).await
+    }
+    async fn list(&self, prefix: &str) -> Result<Vec<StoredObject>, AppError> {
+        self.observe("list", self.inner.list(prefix)).await
+    }
+    fn as_local(&self) -> Option<&local::LocalProvider> {
+        self.inner.as_local()
+    }
 }
 
 // ── S3 / RustFS provider ──────────────────────────────────────────────────────
@@ -50,6 +203,11 @@ pub struct S3Provider {
 #[allow(clippy::wildcard_imports)]
 use std::time::Duration;
 
+/// Size of each part of a multipart upload; objects smaller than this are sent in one request.
+/// S3 requires at least 5 MiB for every part but the last.
+#[cfg(feature = "storage-s3")]
+const S3_PART_SIZE: usize = 8 * 1024 * 1024;
+
 #[cfg(feature = "storage-s3")]
 impl S3Provider {
     /// Construct from environment variables.
@@ -73,24 +231,132 @@ impl S3Provider {
         let client = aws_sdk_s3::Client::from_conf(builder.build());
         Some(S3Provider { client, bucket })
     }
+
+    /// Upload `first` and the rest of `data` as parts of multipart upload `upload_id`.
+    async fn upload_parts(
+        &self,
+        path: &str,
+        upload_id: &str,
+        first: Bytes,
+        data: &mut ByteStream<'_>,
+    ) -> Result<Vec<aws_sdk_s3::types::CompletedPart>, AppError> {
+        let mut parts = Vec::new();
+        let mut chunk = Some(first);
+        while let Some(body) = chunk {
+            let part_number = parts.len() as i32 + 1;
+            let out = self
+                .client
+                .upload_part()
+                .bucket(&self.bucket)
+                .key(path)
+                .upload_id(upload_id)
+                .part_number(part_number)
+                .body(aws_sdk_s3::primitives::ByteStream::from(body))
+                .send()
+                .await
+                .map_err(|e| AppError::Storage(e.to_string()))?;
+            parts.push(
+                aws_sdk_s3::types::CompletedPart::builder()
+                    .set_e_tag(out.e_tag().map(str::to_string))
+                    .part_number(part_number)
+                    .build(),
+            );
+            chunk = next_chunk(data, S3_PART_SIZE).await?;
+        }
+        Ok(parts)
+    }
 }
 
 #[cfg(feature = "storage-s3")]
 #[async_trait]
 impl StorageProvider for S3Provider {
-    async fn upload(&self, path: &str, data: Vec<u8>, content_type: &str) -> Result<(), AppError> {
-        self.client
-            .put_object()
+    async fn upload(
+        &self,
+        path: &str,
+        mut data: ByteStream<'_>,
+        content_type: &str,
+    ) -> Result<(), AppError> {
+        let first = next_chunk(&mut data, S3_PART_SIZE)
+            .await?
+            .unwrap_or_default();
+        if first.len() < S3_PART_SIZE {
+            // The whole object fits in one request.
+            self.client
+                .put_object()
+                .bucket(&self.bucket)
+                .key(path)
+                .body(aws_sdk_s3::primitives::ByteStream::from(first))
+                .content_type(content_type)
+                .send()
+                .await
+                .map_err(|e| AppError::Storage(e.to_string()))?;
+            return Ok(());
+        }
+
+        let created = self
+            .client
+            .create_multipart_upload()
             .bucket(&self.bucket)
             .key(path)
-            .body(aws_sdk_s3::primitives::ByteStream::from(data))
             .content_type(content_type)
             .send()
             .await
             .map_err(|e| AppError::Storage(e.to_string()))?;
+        let upload_id = created
+            .upload_id()
+            .ok_or_else(|| AppError::Storage("S3 returned no multipart upload id".into()))?
+            .to_string();
+        let parts = match self.upload_parts(path, &upload_id, first, &mut data).await {
+            Ok(parts) => parts,
+            Err(e) => {
+                let _ = self
+                    .client
+                    .abort_multipart_upload()
+                    .bucket(&self.bucket)
+                    .key(path)
+                    .upload_id(&upload_id)
+                    .send()
+                    .await;
+                return Err(e);
+            }
+        };
+        self.client
+            .complete_multipart_upload()
+            .bucket(&self.bucket)
+            .key(path)
+            .upload_id(&upload_id)
+            .multipart_upload(
+                aws_sdk_s3::types::CompletedMultipartUpload::builder()
+                    .set_parts(Some(parts))
+                    .build(),
+            )
+            .send()
+            .await
+            .map_err(|e| AppError::Storage(e.to_string()))?;
         Ok(())
     }
 
+    async fn download(&self, path: &str) -> Result<ByteStream<'static>, AppError> {
+        match self
+            .client
+            .get_object()
+            .bucket(&self.bucket)
+            .key(path)
+            .send()
+            .await
+        {
+            Ok(out) => Ok(Box::pin(ReaderStream::new(out.body.into_async_read()))),
+            Err(e) => {
+                let e = e.into_service_error();
+                if e.is_no_such_key() {
+                    Err(AppError::NotFound(format!("asset not found: {}", path)))
+                } else {
+                    Err(AppError::Storage(e.to_string()))
+                }
+            }
+        }
+    }
+
     async fn presign_url(&self, path: &str, expires_secs: u64) -> Result<PresignResult, AppError> {
         let cfg =
             aws_sdk_s3::presigning::PresigningConfig::expires_in(Duration::from_secs(expires_secs))
@@ -122,6 +388,87 @@ impl StorageProvider for S3Provider {
             .map_err(|e| AppError::Storage(e.to_string()))?;
         Ok(())
     }
+
+    async fn presign_upload(
+        &self,
+        path: &str,
+        content_type: &str,
+        expires_secs: u64,
+    ) -> Result<PresignedUpload, AppError> {
+        let cfg =
+            aws_sdk_s3::presigning::PresigningConfig::expires_in(Duration::from_secs(expires_secs))
+                .map_err(|e| AppError::Storage(e.to_string()))?;
+
+        let presigned = self
+            .client
+            .put_object()
+            .bucket(&self.bucket)
+            .key(path)
+            .content_type(content_type)
+            .presigned(cfg)
+            .await
+            .map_err(|e| AppError::Storage(e.to_string()))?;
+
+        Ok(PresignedUpload {
+            url: presigned.uri().to_string(),
+            method: "PUT",
+            headers: presigned
+                .headers()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+            expires_at: Utc::now() + chrono::Duration::seconds(expires_secs as i64),
+            expires_in: expires_secs,
+        })
+    }
+
+    async fn head(&self, path: &str) -> Result<Option<ObjectMeta>, AppError> {
+        match self
+            .client
+            .head_object()
+            .bucket(&self.bucket)
+            .key(path)
+            .send()
+            .await
+        {
+            Ok(out) => Ok(Some(ObjectMeta {
+                size: out.content_length().unwrap_or(0).max(0) as u64,
+                content_type: out.content_type().map(str::to_string),
+            })),
+            Err(e) => {
+                let e = e.into_service_error();
+                if e.is_not_found() {
+                    Ok(None)
+                } else {
+                    Err(AppError::Storage(e.to_string()))
+                }
+            }
+        }
+    }
+
+    async fn list(&self, prefix: &str) -> Result<Vec<StoredObject>, AppError> {
+        let mut pages = self
+            .client
+            .list_objects_v2()
+            .bucket(&self.bucket)
+            .prefix(prefix)
+            .into_paginator()
+            .send();
+        let mut out = Vec::new();
+        while let Some(page) = pages.next().await {
+            let page = page.map_err(|e| AppError::Storage(e.to_string()))?;
+            for obj in page.contents() {
+                let Some(key) = obj.key() else { continue };
+                out.push(StoredObject {
+                    path: key.to_string(),
+                    size: obj.size().unwrap_or(0).max(0) as u64,
+                    last_modified: obj
+                        .last_modified()
+                        .and_then(|t| DateTime::from_timestamp(t.secs(), t.subsec_nanos())),
+                });
+            }
+        }
+        Ok(out)
+    }
 }
 
 // ── Initialisation ────────────────────────────────────────────────────────────
@@ -132,33 +479,41 @@ impl StorageProvider for S3Provider {
 ///   - `s3` / `rustfs` — AWS S3 or S3-compatible (requires feature `storage-s3`)
 ///   - `azure`         — Azure Blob Storage      (requires feature `storage-azure`)
 ///   - `gcs`           — Google Cloud Storage    (requires feature `storage-gcs`)
+///   - `local`         — local filesystem        (always available)
 pub async fn init_storage_provider() -> Option<Arc<dyn StorageProvider>> {
     let provider_type = std::env::var("STORAGE_PROVIDER").ok()?.to_lowercase();
-    match provider_type.as_str() {
+    let (label, provider): (&'static str, Arc<dyn StorageProvider>) = match provider_type.as_str() {
         #[cfg(feature = "storage-s3")]
-        "s3" | "rustfs" => {
-            let p = S3Provider::from_env().await?;
-            Some(Arc::new(p) as Arc<dyn StorageProvider>)
-        }
+        "s3" | "rustfs" => ("s3", Arc::new(S3Provider::from_env().await?)),
         #[cfg(feature = "storage-azure")]
-        "azure" => {
-            let p = azure::AzureProvider::from_env()?;
-            Some(Arc::new(p) as Arc<dyn StorageProvider>)
-        }
+        "azure" => ("azure", Arc::new(azure::AzureProvider::from_env()?)),
         #[cfg(feature = "storage-gcs")]
-        "gcs" => {
-            let p = gcs::GcsProvider::from_env().await?;
-            Some(Arc::new(p) as Arc<dyn StorageProvider>)
-        }
+        "gcs" => ("gcs", Arc::new(gcs::GcsProvider::from_env().await?)),
+        "local" => ("local", Arc::new(local::LocalProvider::from_env())),
         _ => {
             tracing::warn!(provider = %provider_type, "unknown STORAGE_PROVIDER or feature not enabled; storage disabled");
-            None
+            return None;
         }
-    }
+    };
+    Some(Arc::new(InstrumentedStorage::new(label, provider)))
 }
 
 // ── Prefix resolution ─────────────────────────────────────────────────────────
 
+/// Prefix template of an asset column without `prefix` configured.
+pub const DEFAULT_ASSET_PREFIX: &str = "{entity}/{yyyy}/{mm}/{dd}";
+
+/// Whether `path` is shaped like an upload by `tenant_id` to an asset column of `table` with
+/// prefix template `template`: the resolved prefix, then a UUID object name. A template without
+/// `{tenant_id}` does not say which tenant uploaded an object, so no path matches it.
+pub fn is_tenant_upload(template: &str, tenant_id: &str, table: &str, path: &str) -> bool {
+    template.contains("{tenant_id}")
+        && !tenant_id.is_empty()
+        && gc::scan_target(template, tenant_id, table)
+            .pattern
+            .is_match(path)
+}
+
 /// Resolve a prefix template at upload time.
 /// Supported tokens: {yyyy}, {mm}, {dd}, {hh}, {tenant_id}, {entity}.
 pub fn resolve_prefix(template: &str, tenant_id: &str, entity: &str) -> String {
@@ -172,8 +527,67 @@ pub fn resolve_prefix(template: &str, tenant_id: &str, entity: &str) -> String {
         .replace("{entity}", entity)
 }
 
+// ── Streams ───────────────────────────────────────────────────────────────────
+
+/// Wrap an in-memory buffer as a single-chunk [`ByteStream`].
+pub fn bytes_stream(data: impl Into<Bytes>) -> ByteStream<'static> {
+    let data = data.into();
+    Box::pin(futures_util::stream::once(async move { Ok(data) }))
+}
+
+/// Read a whole stream into memory. Meant for small objects and tests; large objects should be
+/// forwarded as a stream.
+pub async fn collect_bytes(mut data: ByteStream<'_>) -> Result<Vec<u8>, AppError> {
+    use futures_util::StreamExt;
+    let mut out = Vec::new();
+    while let Some(chunk) = data.next().await {
+        out.extend_from_slice(&chunk.map_err(|e| AppError::Storage(e.to_string()))?);
+    }
+    Ok(out)
+}
+
+/// Read from `data` until `size` bytes are buffered or the stream ends. `None` once it is
+/// exhausted. Backends that upload in fixed-size parts use this to re-chunk their input.
+#[cfg(any(feature = "storage-s3", feature = "storage-azure"))]
+pub(crate) async fn next_chunk(
+    data: &mut ByteStream<'_>,
+    size: usize,
+) -> Result<Option<Bytes>, AppError> {
+    use futures_util::StreamExt;
+    let mut buf = bytes::BytesMut::new();
+    while buf.len() < size {
+        match data.next().await {
+            Some(chunk) => buf.extend_from_slice(
+                &chunk.map_err(|e| AppError::Storage(format!("upload stream: {}", e)))?,
+            ),
+            None => break,
+        }
+    }
+    Ok((!buf.is_empty()).then(|| buf.freeze()))
+}
+
 // ── Compression ───────────────────────────────────────────────────────────────
 
+/// Compress a stream on the fly before upload. Same values as [`compress`].
+pub fn compress_stream<'a>(data: ByteStream<'a>, compression: &str) -> ByteStream<'a> {
+    use async_compression::tokio::bufread::{GzipEncoder, ZstdEncoder};
+    match compression.to_lowercase().as_str() {
+        "gzip" => Box::pin(ReaderStream::new(GzipEncoder::new(StreamReader::new(data)))),
+        "zstd" => Box::pin(ReaderStream::new(ZstdEncoder::new(StreamReader::new(data)))),
+        _ => data,
+    }
+}
+
+/// Undo [`compress_stream`] (or [`compress`]) on the fly, e.g. when serving a stored asset.
+pub fn decompress_stream<'a>(data: ByteStream<'a>, compression: &str) -> ByteStream<'a> {
+    use async_compression::tokio::bufread::{GzipDecoder, ZstdDecoder};
+    match compression.to_lowercase().as_str() {
+        "gzip" => Box::pin(ReaderStream::new(GzipDecoder::new(StreamReader::new(data)))),
+        "zstd" => Box::pin(ReaderStream::new(ZstdDecoder::new(StreamReader::new(data)))),
+        _ => data,
+    }
+}
+
 /// Apply byte-level compression before upload.
 /// Supported: "gzip", "zstd", "none" (or any unrecognised value → pass-through).
 pub fn compress(data: Vec<u8>, compression: &str) -> Result<Vec<u8>, AppError> {
@@ -203,6 +617,18 @@ pub fn validate_asset_field(
     content_type: &str,
     size_bytes: usize,
     rule: &crate::config::ValidationRule,
+) -> Result<(), AppError> {
+    validate_asset_metadata(col, filename, content_type, rule)?;
+    validate_asset_size(col, size_bytes as u64, rule)
+}
+
+/// The checks of [`validate_asset_field`] that do not need the file's size: mime type,
+/// extension and filename length. Run before an upload starts.
+pub fn validate_asset_metadata(
+    col: &str,
+    filename: &str,
+    content_type: &str,
+    rule: &crate::config::ValidationRule,
 ) -> Result<(), AppError> {
     if let Some(ref allowed) = rule.allowed_mime_types {
         let ct = content_type
@@ -234,8 +660,27 @@ pub fn validate_asset_field(
             )));
         }
     }
+    if let Some(max_len) = rule.max_filename_length {
+        if filename.len() > max_len as usize {
+            return Err(AppError::Validation(format!(
+                "{}: filename length {} exceeds maximum of {}",
+                col,
+                filename.len(),
+                max_len
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// The size checks of [`validate_asset_field`]: `max_size_mb` and `min_size_kb`.
+pub fn validate_asset_size(
+    col: &str,
+    size_bytes: u64,
+    rule: &crate::config::ValidationRule,
+) -> Result<(), AppError> {
     if let Some(max_mb) = rule.max_size_mb {
-        let limit = (max_mb * 1024.0 * 1024.0) as usize;
+        let limit = (max_mb * 1024.0 * 1024.0) as u64;
         if size_bytes > limit {
             return Err(AppError::Validation(format!(
                 "{}: file size {} bytes exceeds maximum of {:.1} MB",
@@ -244,7 +689,7 @@ pub fn validate_asset_field(
         }
     }
     if let Some(min_kb) = rule.min_size_kb {
-        let floor = (min_kb * 1024.0) as usize;
+        let floor = (min_kb * 1024.0) as u64;
         if size_bytes < floor {
             return Err(AppError::Validation(format!(
                 "{}: file size {} bytes is below minimum of {:.1} KB",
@@ -252,15 +697,68 @@ pub fn validate_asset_field(
             )));
         }
     }
-    if let Some(max_len) = rule.max_filename_length {
-        if filename.len() > max_len as usize {
-            return Err(AppError::Validation(format!(
-                "{}: filename length {} exceeds maximum of {}",
-                col,
-                filename.len(),
-                max_len
-            )));
+    Ok(())
+}
+
+#[cfg(test)]
+mod storage_tests {
+    use super::*;
+
+    #[test]
+    fn tenant_uploads_match_the_tenants_resolved_prefix() {
+        const TENANT_PREFIX: &str = "{tenant_id}/{entity}/{yyyy}/{mm}/{dd}";
+        let id = uuid::Uuid::new_v4();
+        let path = format!("acme/invoices/2026/10/18/{}.pdf", id);
+        assert!(is_tenant_upload(TENANT_PREFIX, "acme", "invoices", &path));
+        assert!(!is_tenant_upload(TENANT_PREFIX, "beta", "invoices", &path));
+        assert!(!is_tenant_upload(TENANT_PREFIX, "acme", "orders", &path));
+        assert!(!is_tenant_upload(
+            TENANT_PREFIX,
+            "acme",
+            "invoices",
+            "acme/invoices/2026/10/18/report.pdf"
+        ));
+        // Without {tenant_id} the path cannot be tied to a tenant.
+        let shared = format!("invoices/2026/10/18/{}.pdf", id);
+        assert!(!is_tenant_upload(
+            "{entity}/{yyyy}/{mm}/{dd}",
+            "acme",
+            "invoices",
+            &shared
+        ));
+    }
+
+    fn chunked(data: &[u8]) -> ByteStream<'static> {
+        let chunks: Vec<std::io::Result<Bytes>> = data
+            .chunks(7)
+            .map(|c| Ok(Bytes::copy_from_slice(c)))
+            .collect();
+        Box::pin(futures_util::stream::iter(chunks))
+    }
+
+    #[tokio::test]
+    async fn stream_compression_round_trips() {
+        let data: Vec<u8> = (0..10_000u32)
+            .flat_map(|i| (i % 97).to_le_bytes())
+            .collect();
+        for compression in ["gzip", "zstd", "none"] {
+            let packed = compress_stream(chunked(&data), compression);
+            let unpacked = collect_bytes(decompress_stream(packed, compression))
+                .await
+                .unwrap();
+            assert_eq!(unpacked, data, "{}", compression);
+        }
+    }
+
+    #[tokio::test]
+    async fn buffered_compression_decompresses_as_stream() {
+        let data = b"{\"hello\":\"world\"}".repeat(50);
+        for compression in ["gzip", "zstd"] {
+            let packed = compress(data.clone(), compression).unwrap();
+            let unpacked = collect_bytes(decompress_stream(bytes_stream(packed), compression))
+                .await
+                .unwrap();
+            assert_eq!(unpacked, data, "{}", compression);
         }
     }
-    Ok(())
 }
@@ -98,12 +98,17 @@ pub async fn ensure_sys_tables(pool: &Pool, dialect: &dyn Dialect) -> Result<(),
             payload {} NOT NULL, \
             updated_at {} NOT NULL DEFAULT {}, \
             version BIGINT NOT NULL DEFAULT 1, \
-            semantic_version TEXT\
+            semantic_version TEXT, \
+            content_hash TEXT, \
+            signer TEXT, \
+            key_id TEXT, \
+            config_versions {}\
         )",
         q_packages,
         dialect.sys_json_type(),
         dialect.sys_timestamp_type(),
         dialect.now_fn(),
+        dialect.sys_json_type(),
     );
     sqlx::query(&packages_ddl).execute(pool).await?;
     let alter_pkg_semver = format!(
@@ -112,6 +117,21 @@ pub async fn ensure_sys_tables(pool: &Pool, dialect: &dyn Dialect) -> Result<(),
     );
     let _ = sqlx::query(&alter_pkg_semver).execute(pool).await;
     let q_packages_history = qualified_sys_table("_sys_packages_history");
+    for table in [&q_packages, &q_packages_history] {
+        for column in ["content_hash", "signer", "key_id"] {
+            let alter_pkg_integrity = format!(
+                "ALTER TABLE {} ADD COLUMN IF NOT EXISTS {} TEXT",
+                table, column
+            );
+            let _ = sqlx::query(&alter_pkg_integrity).execute(pool).await;
+        }
+        let alter_pkg_config_versions = format!(
+            "ALTER TABLE {} ADD COLUMN IF NOT EXISTS config_versions {}",
+            table,
+            dialect.sys_json_type()
+        );
+        let _ = sqlx::query(&alter_pkg_config_versions).execute(pool).await;
+    }
     let packages_history_ddl = format!(
         "CREATE TABLE IF NOT EXISTS {} (\
             id TEXT NOT NULL, \
@@ -119,12 +139,17 @@ pub async fn ensure_sys_tables(pool: &Pool, dialect: &dyn Dialect) -> Result<(),
             version BIGINT NOT NULL, \
             created_at {} NOT NULL DEFAULT {}, \
             semantic_version TEXT, \
+            content_hash TEXT, \
+            signer TEXT, \
+            key_id TEXT, \
+            config_versions {}, \
             PRIMARY KEY (id, version)\
         )",
         q_packages_history,
         dialect.sys_json_type(),
         dialect.sys_timestamp_type(),
         dialect.now_fn(),
+        dialect.sys_json_type(),
     );
     sqlx::query(&packages_history_ddl).execute(pool).await?;
     let alter_pkg_hist_semver = format!(
@@ -160,7 +185,11 @@ pub async fn ensure_sys_tables(pool: &Pool, dialect: &dyn Dialect) -> Result<(),
             strategy TEXT NOT NULL, \
             database_url TEXT, \
             updated_at {} NOT NULL DEFAULT {}, \
-            comment TEXT\
+            comment TEXT, \
+            replica_url TEXT, \
+            max_connections INTEGER, \
+            acquire_timeout_secs INTEGER, \
+            idle_timeout_secs INTEGER\
         )",
         q_tenants,
         dialect.sys_timestamp_type(),
@@ -172,6 +201,18 @@ pub async fn ensure_sys_tables(pool: &Pool, dialect: &dyn Dialect) -> Result<(),
         q_tenants
     );
     let _ = sqlx::query(&drop_schema_name).execute(pool).await;
+    for column in [
+        "replica_url TEXT",
+        "max_connections INTEGER",
+        "acquire_timeout_secs INTEGER",
+        "idle_timeout_secs INTEGER",
+    ] {
+        let add_column = format!(
+            "ALTER TABLE {} ADD COLUMN IF NOT EXISTS {}",
+            q_tenants, column
+        );
+        let _ = sqlx::query(&add_column).execute(pool).await;
+    }
 
     // Auto-provision the Platform Admin tenant (sole writer of `global` shared tables) so global
     // tables are writable out of the box. Idempotent; only meaningful where RLS is supported.
@@ -231,11 +272,74 @@ pub async fn ensure_sys_tables(pool: &Pool, dialect: &dyn Dialect) -> Result<(),
     }
 
     ensure_migration_tables(pool, dialect).await?;
+    ensure_data_migration_table(pool, dialect).await?;
+    ensure_event_schedule_tables(pool, dialect).await?;
+    ensure_asset_upload_table(pool, dialect).await?;
+
+    Ok(())
+}
+
+/// Create `_sys_asset_uploads`: one row per object path handed out by `POST /assets/upload-url`,
+/// recording which tenant it was issued to and for which table and asset column. Create/update
+/// accept such a path from that tenant even when the column's prefix does not name the tenant.
+async fn ensure_asset_upload_table(pool: &Pool, dialect: &dyn Dialect) -> Result<(), AppError> {
+    sqlx::query(&format!(
+        "CREATE TABLE IF NOT EXISTS {} (\
+            tenant_id TEXT NOT NULL, \
+            path TEXT NOT NULL, \
+            table_name TEXT NOT NULL, \
+            column_name TEXT NOT NULL, \
+            issued_at {} NOT NULL DEFAULT {}, \
+            PRIMARY KEY (tenant_id, path)\
+        )",
+        qualified_sys_table("_sys_asset_uploads"),
+        dialect.sys_timestamp_type(),
+        dialect.now_fn(),
+    ))
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Create the bookkeeping tables for scheduled event triggers (see `events::scheduler`).
+///
+/// `_sys_event_schedule_state` holds one cursor per (tenant, trigger): the last claimed cron
+/// occurrence, or the `due_column` watermark. `_sys_event_schedule_log` holds one row per fired
+/// (row, occurrence) so a row is never published twice, even across replicas.
+async fn ensure_event_schedule_tables(pool: &Pool, dialect: &dyn Dialect) -> Result<(), AppError> {
+    sqlx::query(&format!(
+        "CREATE TABLE IF NOT EXISTS {} (\
+            tenant_id TEXT NOT NULL, \
+            state_key TEXT NOT NULL, \
+            position TEXT NOT NULL, \
+            PRIMARY KEY (tenant_id, state_key)\
+        )",
+        qualified_sys_table("_sys_event_schedule_state"),
+    ))
+    .execute(pool)
+    .await?;
+
+    sqlx::query(&format!(
+        "CREATE TABLE IF NOT EXISTS {} (\
+            tenant_id TEXT NOT NULL, \
+            trigger_key TEXT NOT NULL, \
+            row_key TEXT NOT NULL, \
+            fire_key TEXT NOT NULL, \
+            fired_at {} NOT NULL DEFAULT {}, \
+            PRIMARY KEY (tenant_id, trigger_key, row_key, fire_key)\
+        )",
+        qualified_sys_table("_sys_event_schedule_log"),
+        dialect.sys_timestamp_type(),
+        dialect.now_fn(),
+    ))
+    .execute(pool)
+    .await?;
 
     Ok(())
 }
 
-/// Create _sys_migration_plans and _sys_migration_audit tables if they don't exist.
+/// Create _sys_migration_plans, _sys_migration_audit and _sys_column_transitions tables if they
+/// don't exist.
 async fn ensure_migration_tables(pool: &Pool, dialect: &dyn Dialect) -> Result<(), AppError> {
     let q_plans = qualified_sys_table("_sys_migration_plans");
     let expires_at_col = match dialect.default_now_plus_hours(24) {
@@ -258,7 +362,11 @@ async fn ensure_migration_tables(pool: &Pool, dialect: &dyn Dialect) -> Result<(
             status TEXT NOT NULL DEFAULT 'pending', \
             created_at {} NOT NULL DEFAULT {}, \
             {}, \
-            applied_at {}\
+            applied_at {}, \
+            requested_by TEXT, \
+            approved_by TEXT, \
+            approved_at {}, \
+            approval_json {}\
         )",
         q_plans,
         dialect.sys_json_type(),
@@ -267,9 +375,22 @@ async fn ensure_migration_tables(pool: &Pool, dialect: &dyn Dialect) -> Result<(
         dialect.now_fn(),
         expires_at_col,
         dialect.sys_timestamp_type(),
+        dialect.sys_timestamp_type(),
+        dialect.sys_json_type(),
     ))
     .execute(pool)
     .await?;
+    // Previewing user and approval recorded before a gated plan may be applied (see
+    // `crate::approval`).
+    for col in [
+        "requested_by TEXT".to_string(),
+        "approved_by TEXT".to_string(),
+        format!("approved_at {}", dialect.sys_timestamp_type()),
+        format!("approval_json {}", dialect.sys_json_type()),
+    ] {
+        let alter = format!("ALTER TABLE {} ADD COLUMN IF NOT EXISTS {}", q_plans, col);
+        let _ = sqlx::query(&alter).execute(pool).await;
+    }
 
     let q_audit = qualified_sys_table("_sys_migration_audit");
     sqlx::query(&format!(
@@ -292,6 +413,8 @@ async fn ensure_migration_tables(pool: &Pool, dialect: &dyn Dialect) -> Result<(
             risk TEXT NOT NULL, \
             status TEXT NOT NULL, \
             error_message TEXT, \
+            rows_affected BIGINT, \
+            compensating_ddl TEXT, \
             executed_at {} NOT NULL DEFAULT {}\
         )",
         q_audit,
@@ -301,6 +424,42 @@ async fn ensure_migration_tables(pool: &Pool, dialect: &dyn Dialect) -> Result<(
     ))
     .execute(pool)
     .await?;
+    // Running total for batched data steps (backfills), one `in_progress` row per batch.
+    let alter_audit_rows = format!(
+        "ALTER TABLE {} ADD COLUMN IF NOT EXISTS rows_affected BIGINT",
+        q_audit
+    );
+    let _ = sqlx::query(&alter_audit_rows).execute(pool).await;
+    // Undo statement for an applied step when the dialect cannot roll DDL back (MySQL).
+    let alter_audit_undo = format!(
+        "ALTER TABLE {} ADD COLUMN IF NOT EXISTS compensating_ddl TEXT",
+        q_audit
+    );
+    let _ = sqlx::query(&alter_audit_undo).execute(pool).await;
+
+    sqlx::query(&format!(
+        "CREATE TABLE IF NOT EXISTS {} (\
+            target TEXT NOT NULL, \
+            package_id TEXT NOT NULL, \
+            tenant_id TEXT NOT NULL, \
+            schema_name TEXT NOT NULL, \
+            table_name TEXT NOT NULL, \
+            column_name TEXT NOT NULL, \
+            shadow_column TEXT NOT NULL, \
+            from_type TEXT NOT NULL, \
+            to_type TEXT NOT NULL, \
+            source_cast TEXT, \
+            phase TEXT NOT NULL, \
+            rows_backfilled BIGINT NOT NULL DEFAULT 0, \
+            updated_at {} NOT NULL DEFAULT {}, \
+            PRIMARY KEY (target, schema_name, table_name, column_name)\
+        )",
+        qualified_sys_table(COLUMN_TRANSITIONS_TABLE),
+        dialect.sys_timestamp_type(),
+        dialect.now_fn(),
+    ))
+    .execute(pool)
+    .await?;
 
     Ok(())
 }
@@ -318,6 +477,12 @@ pub struct MigrationPlanRow {
     pub created_at: DateTime<Utc>,
     pub expires_at: DateTime<Utc>,
     pub applied_at: Option<DateTime<Utc>>,
+    /// `X-User-ID` of the preview that created the plan; it may not approve the plan.
+    pub requested_by: Option<String>,
+    pub approved_by: Option<String>,
+    pub approved_at: Option<DateTime<Utc>>,
+    /// Serialized [`crate::approval::PlanApproval`]; `None` until the plan is approved.
+    pub approval_json: Option<serde_json::Value>,
 }
 
 /// Persist a migration plan (zip bytes + serialized steps) for later confirmation.
@@ -331,11 +496,12 @@ pub async fn save_migration_plan(
     to_version: &str,
     plan_json: &serde_json::Value,
     zip_bytes: &[u8],
+    requested_by: Option<&str>,
 ) -> Result<(), AppError> {
     let q = qualified_sys_table("_sys_migration_plans");
     sqlx::query(&format!(
-        "INSERT INTO {} (id, package_id, tenant_id, from_version, to_version, plan_json, zip_bytes, status, created_at, expires_at) \
-         VALUES ($1, $2, $3, $4, $5, $6, $7, 'pending', NOW(), NOW() + INTERVAL '24 hours')",
+        "INSERT INTO {} (id, package_id, tenant_id, from_version, to_version, plan_json, zip_bytes, status, created_at, expires_at, requested_by) \
+         VALUES ($1, $2, $3, $4, $5, $6, $7, 'pending', NOW(), NOW() + INTERVAL '24 hours', $8)",
         q
     ))
     .bind(id)
@@ -345,6 +511,7 @@ pub async fn save_migration_plan(
     .bind(to_version)
     .bind(plan_json)
     .bind(zip_bytes)
+    .bind(requested_by)
     .execute(pool)
     .await?;
     Ok(())
@@ -357,9 +524,9 @@ pub async fn get_migration_plan(
 ) -> Result<Option<MigrationPlanRow>, AppError> {
     let q = qualified_sys_table("_sys_migration_plans");
     #[allow(clippy::type_complexity)]
-    let row: Option<(String, String, String, Option<String>, String, serde_json::Value, Vec<u8>, String, DateTime<Utc>, DateTime<Utc>, Option<DateTime<Utc>>)> =
+    let row: Option<(String, String, String, Option<String>, String, serde_json::Value, Vec<u8>, String, DateTime<Utc>, DateTime<Utc>, Option<DateTime<Utc>>, Option<String>, Option<String>, Option<DateTime<Utc>>, Option<serde_json::Value>)> =
         sqlx::query_as(&format!(
-            "SELECT id, package_id, tenant_id, from_version, to_version, plan_json, zip_bytes, status, created_at, expires_at, applied_at FROM {} WHERE id = $1",
+            "SELECT id, package_id, tenant_id, from_version, to_version, plan_json, zip_bytes, status, created_at, expires_at, applied_at, requested_by, approved_by, approved_at, approval_json FROM {} WHERE id = $1",
             q
         ))
         .bind(id)
@@ -379,6 +546,10 @@ pub async fn get_migration_plan(
             created_at,
             expires_at,
             applied_at,
+            requested_by,
+            approved_by,
+            approved_at,
+            approval_json,
         )| {
             MigrationPlanRow {
                 id,
@@ -392,11 +563,69 @@ pub async fn get_migration_plan(
                 created_at,
                 expires_at,
                 applied_at,
+                requested_by,
+                approved_by,
+                approved_at,
+                approval_json,
             }
         },
     ))
 }
 
+/// Record an approval on a pending migration plan, replacing any earlier one. Returns false if
+/// the plan is not pending.
+pub async fn approve_migration_plan(
+    pool: &Pool,
+    id: &str,
+    approved_by: &str,
+    approval_json: &serde_json::Value,
+) -> Result<bool, AppError> {
+    let q = qualified_sys_table("_sys_migration_plans");
+    let result = sqlx::query(&format!(
+        "UPDATE {} SET approved_by = $1, approved_at = CURRENT_TIMESTAMP, approval_json = $2 WHERE id = $3 AND status = 'pending'",
+        q
+    ))
+    .bind(approved_by)
+    .bind(approval_json)
+    .bind(id)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Mark one pending migration plan as `expired`.
+pub async fn expire_migration_plan(pool: &Pool, id: &str) -> Result<(), AppError> {
+    let q = qualified_sys_table("_sys_migration_plans");
+    sqlx::query(&format!(
+        "UPDATE {} SET status = 'expired' WHERE id = $1 AND status = 'pending'",
+        q
+    ))
+    .bind(id)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Mark every pending plan for a package as `expired`, except `keep`. Called when the installed
+/// version changes: those plans were computed against the previous version. Returns the number
+/// of plans expired.
+pub async fn expire_pending_migration_plans(
+    pool: &Pool,
+    package_id: &str,
+    keep: Option<&str>,
+) -> Result<u64, AppError> {
+    let q = qualified_sys_table("_sys_migration_plans");
+    let result = sqlx::query(&format!(
+        "UPDATE {} SET status = 'expired' WHERE package_id = $1 AND status = 'pending' AND id <> $2",
+        q
+    ))
+    .bind(package_id)
+    .bind(keep.unwrap_or(""))
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected())
+}
+
 /// Atomically mark a migration plan as applied. Returns false if already applied or not found.
 pub async fn mark_migration_plan_applied(pool: &Pool, id: &str) -> Result<bool, AppError> {
     let q = qualified_sys_table("_sys_migration_plans");
@@ -410,6 +639,60 @@ pub async fn mark_migration_plan_applied(pool: &Pool, id: &str) -> Result<bool,
     Ok(result.rows_affected() > 0)
 }
 
+/// A plan with failed steps in `_sys_migration_audit`, most recent failure first.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct FailedMigrationPlan {
+    pub migration_plan_id: String,
+    pub package_id: String,
+    pub tenant_id: String,
+    pub failed_steps: i64,
+    pub last_failed_at: DateTime<Utc>,
+}
+
+/// Number of `pending` migration plans awaiting apply.
+pub async fn count_pending_migration_plans(pool: &Pool) -> Result<i64, AppError> {
+    let q = qualified_sys_table("_sys_migration_plans");
+    let (count,): (i64,) = sqlx::query_as(&format!(
+        "SELECT COUNT(*) FROM {} WHERE status = 'pending'",
+        q
+    ))
+    .fetch_one(pool)
+    .await?;
+    Ok(count)
+}
+
+/// Plans with `failed` or `compensation_failed` audit rows, most recent failure first, at most
+/// `limit`.
+pub async fn list_failed_migration_plans(
+    pool: &Pool,
+    limit: i64,
+) -> Result<Vec<FailedMigrationPlan>, AppError> {
+    let q = qualified_sys_table("_sys_migration_audit");
+    let rows: Vec<(String, String, String, i64, DateTime<Utc>)> = sqlx::query_as(&format!(
+        "SELECT migration_plan_id, package_id, tenant_id, COUNT(*), MAX(executed_at) FROM {} \
+         WHERE status IN ('failed', 'compensation_failed') \
+         GROUP BY migration_plan_id, package_id, tenant_id \
+         ORDER BY MAX(executed_at) DESC LIMIT {}",
+        q, limit
+    ))
+    .fetch_all(pool)
+    .await?;
+    Ok(rows
+        .into_iter()
+        .map(
+            |(migration_plan_id, package_id, tenant_id, failed_steps, last_failed_at)| {
+                FailedMigrationPlan {
+                    migration_plan_id,
+                    package_id,
+                    tenant_id,
+                    failed_steps,
+                    last_failed_at,
+                }
+            },
+        )
+        .collect())
+}
+
 /// Append one audit record for a migration step execution.
 #[allow(clippy::too_many_arguments)]
 pub async fn insert_migration_audit(
@@ -431,11 +714,13 @@ pub async fn insert_migration_audit(
     risk: &str,
     status: &str,
     error_message: Option<&str>,
+    rows_affected: Option<i64>,
+    compensating_ddl: Option<&str>,
 ) -> Result<(), AppError> {
     let q = qualified_sys_table("_sys_migration_audit");
     sqlx::query(&format!(
-        "INSERT INTO {} (migration_plan_id, package_id, tenant_id, from_version, to_version, step_number, operation, schema_name, table_name, object_name, object_type, description, ddl, safety, risk, status, error_message, executed_at) \
-         VALUES ($1,$2,$3,$4,$5,$6,$7,$8,$9,$10,$11,$12,$13,$14,$15,$16,$17,NOW())",
+        "INSERT INTO {} (migration_plan_id, package_id, tenant_id, from_version, to_version, step_number, operation, schema_name, table_name, object_name, object_type, description, ddl, safety, risk, status, error_message, rows_affected, compensating_ddl, executed_at) \
+         VALUES ($1,$2,$3,$4,$5,$6,$7,$8,$9,$10,$11,$12,$13,$14,$15,$16,$17,$18,$19,NOW())",
         q
     ))
     .bind(migration_plan_id)
@@ -455,11 +740,401 @@ pub async fn insert_migration_audit(
     .bind(risk)
     .bind(status)
     .bind(error_message)
+    .bind(rows_affected)
+    .bind(compensating_ddl)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+const DATA_MIGRATIONS_TABLE: &str = "_sys_package_data_migrations";
+
+/// Create _sys_package_data_migrations: one row per package data migration applied to a tenant.
+async fn ensure_data_migration_table(pool: &Pool, dialect: &dyn Dialect) -> Result<(), AppError> {
+    sqlx::query(&format!(
+        "CREATE TABLE IF NOT EXISTS {} (\
+            package_id TEXT NOT NULL, \
+            tenant_id TEXT NOT NULL, \
+            migration_id TEXT NOT NULL, \
+            kind TEXT NOT NULL, \
+            checksum TEXT NOT NULL, \
+            package_version TEXT, \
+            applied_at {} NOT NULL DEFAULT {}, \
+            PRIMARY KEY (package_id, tenant_id, migration_id)\
+        )",
+        qualified_sys_table(DATA_MIGRATIONS_TABLE),
+        dialect.sys_timestamp_type(),
+        dialect.now_fn(),
+    ))
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Data migrations already applied for a package and tenant, as `(migration_id, checksum)`.
+pub async fn list_data_migrations(
+    pool: &Pool,
+    package_id: &str,
+    tenant_id: &str,
+) -> Result<Vec<(String, String)>, AppError> {
+    let rows: Vec<(String, String)> = sqlx::query_as(&format!(
+        "SELECT migration_id, checksum FROM {} WHERE package_id = $1 AND tenant_id = $2 ORDER BY migration_id",
+        qualified_sys_table(DATA_MIGRATIONS_TABLE)
+    ))
+    .bind(package_id)
+    .bind(tenant_id)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows)
+}
+
+/// Record that a data migration has been applied for a tenant.
+pub async fn insert_data_migration(
+    pool: &Pool,
+    package_id: &str,
+    tenant_id: &str,
+    migration_id: &str,
+    kind: &str,
+    checksum: &str,
+    package_version: Option<&str>,
+) -> Result<(), AppError> {
+    sqlx::query(&format!(
+        "INSERT INTO {} (package_id, tenant_id, migration_id, kind, checksum, package_version, applied_at) \
+         VALUES ($1, $2, $3, $4, $5, $6, CURRENT_TIMESTAMP)",
+        qualified_sys_table(DATA_MIGRATIONS_TABLE)
+    ))
+    .bind(package_id)
+    .bind(tenant_id)
+    .bind(migration_id)
+    .bind(kind)
+    .bind(checksum)
+    .bind(package_version)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+const COLUMN_TRANSITIONS_TABLE: &str = "_sys_column_transitions";
+
+/// Row in _sys_column_transitions: one column part-way through an expand/contract type change.
+/// `target` is the package cache key (package, or package:tenant for database-per-tenant).
+#[derive(Debug, Clone)]
+pub struct ColumnTransitionRow {
+    pub target: String,
+    pub package_id: String,
+    pub tenant_id: String,
+    pub schema_name: String,
+    pub table_name: String,
+    pub column_name: String,
+    pub shadow_column: String,
+    pub from_type: String,
+    pub to_type: String,
+    pub source_cast: Option<String>,
+    pub phase: String,
+    pub rows_backfilled: i64,
+}
+
+/// Insert or replace the transition row for (target, schema, table, column).
+pub async fn upsert_column_transition(
+    pool: &Pool,
+    row: &ColumnTransitionRow,
+) -> Result<(), AppError> {
+    let q = qualified_sys_table(COLUMN_TRANSITIONS_TABLE);
+    let mut tx = pool.begin().await?;
+    sqlx::query(&format!(
+        "DELETE FROM {} WHERE target = $1 AND schema_name = $2 AND table_name = $3 AND column_name = $4",
+        q
+    ))
+    .bind(&row.target)
+    .bind(&row.schema_name)
+    .bind(&row.table_name)
+    .bind(&row.column_name)
+    .execute(&mut *tx)
+    .await?;
+    sqlx::query(&format!(
+        "INSERT INTO {} (target, package_id, tenant_id, schema_name, table_name, column_name, shadow_column, from_type, to_type, source_cast, phase, rows_backfilled, updated_at) \
+         VALUES ($1,$2,$3,$4,$5,$6,$7,$8,$9,$10,$11,$12,CURRENT_TIMESTAMP)",
+        q
+    ))
+    .bind(&row.target)
+    .bind(&row.package_id)
+    .bind(&row.tenant_id)
+    .bind(&row.schema_name)
+    .bind(&row.table_name)
+    .bind(&row.column_name)
+    .bind(&row.shadow_column)
+    .bind(&row.from_type)
+    .bind(&row.to_type)
+    .bind(&row.source_cast)
+    .bind(&row.phase)
+    .bind(row.rows_backfilled)
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Remove the transition row once the column swap has completed.
+pub async fn delete_column_transition(
+    pool: &Pool,
+    target: &str,
+    schema_name: &str,
+    table_name: &str,
+    column_name: &str,
+) -> Result<(), AppError> {
+    let q = qualified_sys_table(COLUMN_TRANSITIONS_TABLE);
+    sqlx::query(&format!(
+        "DELETE FROM {} WHERE target = $1 AND schema_name = $2 AND table_name = $3 AND column_name = $4",
+        q
+    ))
+    .bind(target)
+    .bind(schema_name)
+    .bind(table_name)
+    .bind(column_name)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// All open transitions for a target, ordered by schema, table and column.
+pub async fn list_column_transitions(
+    pool: &Pool,
+    target: &str,
+) -> Result<Vec<ColumnTransitionRow>, AppError> {
+    let q = qualified_sys_table(COLUMN_TRANSITIONS_TABLE);
+    #[allow(clippy::type_complexity)]
+    let rows: Vec<(
+        String,
+        String,
+        String,
+        String,
+        String,
+        String,
+        String,
+        String,
+        String,
+        Option<String>,
+        String,
+        i64,
+    )> = sqlx::query_as(&format!(
+        "SELECT target, package_id, tenant_id, schema_name, table_name, column_name, shadow_column, from_type, to_type, source_cast, phase, rows_backfilled \
+         FROM {} WHERE target = $1 ORDER BY schema_name, table_name, column_name",
+        q
+    ))
+    .bind(target)
+    .fetch_all(pool)
+    .await
+    .map_err(AppError::Db)?;
+    Ok(rows
+        .into_iter()
+        .map(
+            |(
+                target,
+                package_id,
+                tenant_id,
+                schema_name,
+                table_name,
+                column_name,
+                shadow_column,
+                from_type,
+                to_type,
+                source_cast,
+                phase,
+                rows_backfilled,
+            )| ColumnTransitionRow {
+                target,
+                package_id,
+                tenant_id,
+                schema_name,
+                table_name,
+                column_name,
+                shadow_column,
+                from_type,
+                to_type,
+                source_cast,
+                phase,
+                rows_backfilled,
+            },
+        )
+        .collect())
+}
+
+/// True when `e` is a primary-key / unique violation — the "someone already claimed it" outcome
+/// of the insert-to-claim pattern below.
+fn is_unique_violation(e: &sqlx::Error) -> bool {
+    e.as_database_error()
+        .is_some_and(|d| matches!(d.kind(), sqlx::error::ErrorKind::UniqueViolation))
+}
+
+/// Read the scheduler cursor for (tenant, state_key), or None when the trigger has never run.
+pub async fn get_schedule_cursor(
+    pool: &Pool,
+    tenant_id: &str,
+    state_key: &str,
+) -> Result<Option<String>, AppError> {
+    let q = qualified_sys_table("_sys_event_schedule_state");
+    let row: Option<(String,)> = sqlx::query_as(&format!(
+        "SELECT position FROM {} WHERE tenant_id = $1 AND state_key = $2",
+        q
+    ))
+    .bind(tenant_id)
+    .bind(state_key)
+    .fetch_optional(pool)
+    .await
+    .map_err(AppError::Db)?;
+    Ok(row.map(|(c,)| c))
+}
+
+/// Move the scheduler cursor for (tenant, state_key) forward to `cursor`.
+///
+/// Returns true only for the caller that actually advanced it: a concurrent replica that already
+/// moved the cursor to (or past) `cursor` makes this return false, which is how a cron occurrence
+/// is claimed exactly once. Cursors are RFC 3339 UTC strings, so text order is time order.
+pub async fn advance_schedule_cursor(
+    pool: &Pool,
+    tenant_id: &str,
+    state_key: &str,
+    cursor: &str,
+) -> Result<bool, AppError> {
+    let q = qualified_sys_table("_sys_event_schedule_state");
+    let inserted = sqlx::query(&format!(
+        "INSERT INTO {} (tenant_id, state_key, position) VALUES ($1, $2, $3)",
+        q
+    ))
+    .bind(tenant_id)
+    .bind(state_key)
+    .bind(cursor)
+    .execute(pool)
+    .await;
+    match inserted {
+        Ok(_) => return Ok(true),
+        Err(e) if is_unique_violation(&e) => {}
+        Err(e) => return Err(AppError::Db(e)),
+    }
+    let result = sqlx::query(&format!(
+        "UPDATE {} SET position = $3 WHERE tenant_id = $1 AND state_key = $2 AND position < $3",
+        q
+    ))
+    .bind(tenant_id)
+    .bind(state_key)
+    .bind(cursor)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Record that `trigger_key` fired for `row_key` at occurrence `fire_key`. Returns false when it
+/// was already recorded — the caller must then not publish (at-most-once delivery).
+pub async fn claim_schedule_fire(
+    pool: &Pool,
+    tenant_id: &str,
+    trigger_key: &str,
+    row_key: &str,
+    fire_key: &str,
+) -> Result<bool, AppError> {
+    let q = qualified_sys_table("_sys_event_schedule_log");
+    let inserted = sqlx::query(&format!(
+        "INSERT INTO {} (tenant_id, trigger_key, row_key, fire_key) VALUES ($1, $2, $3, $4)",
+        q
+    ))
+    .bind(tenant_id)
+    .bind(trigger_key)
+    .bind(row_key)
+    .bind(fire_key)
+    .execute(pool)
+    .await;
+    match inserted {
+        Ok(_) => Ok(true),
+        Err(e) if is_unique_violation(&e) => Ok(false),
+        Err(e) => Err(AppError::Db(e)),
+    }
+}
+
+/// Delete `trigger_key`'s fire records older than `older_than_secs`: rows that fell behind the
+/// trigger's window can no longer be fetched, so their records are not needed to deduplicate.
+/// Returns the number of records deleted.
+pub async fn prune_schedule_log(
+    pool: &Pool,
+    tenant_id: &str,
+    trigger_key: &str,
+    older_than_secs: i64,
+    dialect: &dyn Dialect,
+) -> Result<u64, AppError> {
+    let q = qualified_sys_table("_sys_event_schedule_log");
+    let result = sqlx::query(&format!(
+        "DELETE FROM {} WHERE tenant_id = $1 AND trigger_key = $2 AND fired_at < {}",
+        q,
+        dialect.now_minus_seconds(older_than_secs.max(0))
+    ))
+    .bind(tenant_id)
+    .bind(trigger_key)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected())
+}
+
+/// How long an issued upload path stays acceptable on create/update (see
+/// [`record_asset_upload`]).
+pub const ASSET_UPLOAD_RETENTION_SECS: i64 = 7 * 24 * 3600;
+
+/// Record that `path` was issued to `tenant_id` for an upload to `table_name`.`column_name`, and
+/// drop the tenant's records older than [`ASSET_UPLOAD_RETENTION_SECS`].
+pub async fn record_asset_upload(
+    pool: &Pool,
+    tenant_id: &str,
+    path: &str,
+    table_name: &str,
+    column_name: &str,
+    dialect: &dyn Dialect,
+) -> Result<(), AppError> {
+    let q = qualified_sys_table("_sys_asset_uploads");
+    sqlx::query(&format!(
+        "DELETE FROM {} WHERE tenant_id = $1 AND issued_at < {}",
+        q,
+        dialect.now_minus_seconds(ASSET_UPLOAD_RETENTION_SECS)
+    ))
+    .bind(tenant_id)
+    .execute(pool)
+    .await?;
+    sqlx::query(&format!(
+        "INSERT INTO {} (tenant_id, path, table_name, column_name) VALUES ($1, $2, $3, $4)",
+        q
+    ))
+    .bind(tenant_id)
+    .bind(path)
+    .bind(table_name)
+    .bind(column_name)
     .execute(pool)
     .await?;
     Ok(())
 }
 
+/// Whether `path` was issued to `tenant_id` for `table_name`.`column_name` within the last
+/// [`ASSET_UPLOAD_RETENTION_SECS`].
+pub async fn asset_upload_issued(
+    pool: &Pool,
+    tenant_id: &str,
+    path: &str,
+    table_name: &str,
+    column_name: &str,
+    dialect: &dyn Dialect,
+) -> Result<bool, AppError> {
+    let q = qualified_sys_table("_sys_asset_uploads");
+    let found: Option<(String,)> = sqlx::query_as(&format!(
+        "SELECT path FROM {} WHERE tenant_id = $1 AND path = $2 AND table_name = $3 \
+         AND column_name = $4 AND issued_at >= {}",
+        q,
+        dialect.now_minus_seconds(ASSET_UPLOAD_RETENTION_SECS)
+    ))
+    .bind(tenant_id)
+    .bind(path)
+    .bind(table_name)
+    .bind(column_name)
+    .fetch_optional(pool)
+    .await?;
+    Ok(found.is_some())
+}
+
 /// Resolve the storage id for a config record. For api_entities, entity_id is used when id is absent.
 fn config_record_id(table: &str, rec: &serde_json::Value) -> Result<String, AppError> {
     let id = rec.get("id").and_then(|v| v.as_str());
@@ -571,66 +1246,67 @@ pub struct PackageRow {
     pub version: i64,
     pub updated_at: DateTime<Utc>,
     pub semantic_version: Option<String>,
+    /// SHA-256 of the package contents at install (see `signing`).
+    pub content_hash: Option<String>,
+    /// Who signed the installed package, when it was signed with a trusted key.
+    pub signer: Option<String>,
+    pub key_id: Option<String>,
+}
+
+type PackageTuple = (
+    String,
+    serde_json::Value,
+    i64,
+    DateTime<Utc>,
+    Option<String>,
+    Option<String>,
+    Option<String>,
+    Option<String>,
+);
+
+const PACKAGE_COLUMNS: &str =
+    "id, payload, version, updated_at, semantic_version, content_hash, signer, key_id";
+
+fn package_row(
+    (id, payload, version, updated_at, semantic_version, content_hash, signer, key_id): PackageTuple,
+) -> PackageRow {
+    PackageRow {
+        id,
+        payload,
+        version,
+        updated_at,
+        semantic_version,
+        content_hash,
+        signer,
+        key_id,
+    }
 }
 
 /// List all rows from _sys_packages ordered by id.
 pub async fn list_packages(pool: &Pool) -> Result<Vec<PackageRow>, AppError> {
     let q = qualified_sys_table(PACKAGES_TABLE);
-    #[allow(clippy::type_complexity)]
-    let rows: Vec<(
-        String,
-        serde_json::Value,
-        i64,
-        DateTime<Utc>,
-        Option<String>,
-    )> = sqlx::query_as(&format!(
-        "SELECT id, payload, version, updated_at, semantic_version FROM {} ORDER BY id",
-        q
+    let rows: Vec<PackageTuple> = sqlx::query_as(&format!(
+        "SELECT {} FROM {} ORDER BY id",
+        PACKAGE_COLUMNS, q
     ))
     .fetch_all(pool)
     .await
     .map_err(AppError::Db)?;
-    Ok(rows
-        .into_iter()
-        .map(
-            |(id, payload, version, updated_at, semantic_version)| PackageRow {
-                id,
-                payload,
-                version,
-                updated_at,
-                semantic_version,
-            },
-        )
-        .collect())
+    Ok(rows.into_iter().map(package_row).collect())
 }
 
 /// Fetch a single package row by id, or None if not installed.
 pub async fn get_package(pool: &Pool, id: &str) -> Result<Option<PackageRow>, AppError> {
     let q = qualified_sys_table(PACKAGES_TABLE);
-    #[allow(clippy::type_complexity)]
-    let row: Option<(
-        String,
-        serde_json::Value,
-        i64,
-        DateTime<Utc>,
-        Option<String>,
-    )> = sqlx::query_as(&format!(
-        "SELECT id, payload, version, updated_at, semantic_version FROM {} WHERE id = $1",
-        q
+    let row: Option<PackageTuple> = sqlx::query_as(&format!(
+        "SELECT {} FROM {} WHERE id = $1",
+        PACKAGE_COLUMNS, q
     ))
     .bind(id)
     .fetch_optional(pool)
     .await
     .map_err(AppError::Db)?;
-    Ok(row.map(
-        |(id, payload, version, updated_at, semantic_version)| PackageRow {
-            id,
-            payload,
-            version,
-            updated_at,
-            semantic_version,
-        },
-    ))
+    Ok(row.map(package_row))
 }
 
 /// Count rows in a config table for a given package.
@@ -692,15 +1368,13 @@ pub async fn upsert_package(
         None => 1,
     };
 
-    if let Some((old_payload, old_version, old_semver)) = current {
+    if current.is_some() {
         sqlx::query(&format!(
-            "INSERT INTO {} (id, payload, version, created_at, semantic_version) VALUES ($1, $2, $3, NOW(), $4)",
-            q_packages_history
+            "INSERT INTO {} (id, payload, version, created_at, semantic_version, {}) \
+             SELECT id, payload, version, NOW(), semantic_version, {} FROM {} WHERE id = $1",
+            q_packages_history, PACKAGE_VERSION_COLUMNS, PACKAGE_VERSION_COLUMNS, q_packages
         ))
         .bind(id)
-        .bind(old_payload)
-        .bind(old_version)
-        .bind(old_semver)
         .execute(&mut *tx)
         .await?;
     }
@@ -730,6 +1404,126 @@ pub async fn upsert_package(
     Ok(new_version)
 }
 
+/// Record the content hash and verified signer of the package version just stored by
+/// [`upsert_package`].
+pub async fn set_package_integrity(
+    pool: &Pool,
+    id: &str,
+    integrity: &crate::signing::PackageIntegrity,
+) -> Result<(), AppError> {
+    sqlx::query(&format!(
+        "UPDATE {} SET content_hash = $1, signer = $2, key_id = $3 WHERE id = $4",
+        qualified_sys_table(PACKAGES_TABLE)
+    ))
+    .bind(&integrity.content_hash)
+    .bind(integrity.signer.as_deref())
+    .bind(integrity.key_id.as_deref())
+    .bind(id)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Columns a package version carries into `_sys_packages_history` besides its manifest.
+const PACKAGE_VERSION_COLUMNS: &str = "config_versions, content_hash, signer, key_id";
+
+/// Record which version of each `_sys_*` config table makes up the package version just stored
+/// by [`upsert_package`], so a rollback can reassemble it from the history tables.
+pub async fn record_package_config_versions(pool: &Pool, package_id: &str) -> Result<(), AppError> {
+    let mut versions = serde_json::Map::new();
+    for table in CONFIG_TABLES {
+        let (version,): (Option<i64>,) = sqlx::query_as(&format!(
+            "SELECT MAX(version) FROM {} WHERE package_id = $1",
+            qualified_sys_table(table)
+        ))
+        .bind(package_id)
+        .fetch_one(pool)
+        .await?;
+        if let Some(version) = version {
+            versions.insert(table.to_string(), serde_json::Value::from(version));
+        }
+    }
+    sqlx::query(&format!(
+        "UPDATE {} SET config_versions = $1 WHERE id = $2",
+        qualified_sys_table(PACKAGES_TABLE)
+    ))
+    .bind(serde_json::Value::Object(versions))
+    .bind(package_id)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// A previously installed package version from `_sys_packages_history`.
+#[derive(Debug, Clone)]
+pub struct PackageVersionRow {
+    pub payload: serde_json::Value,
+    /// `_sys_*` config table → config version (see [`record_package_config_versions`]). `None`
+    /// for versions installed before config versions were recorded.
+    pub config_versions: Option<serde_json::Value>,
+    pub integrity: crate::signing::PackageIntegrity,
+}
+
+/// The most recent history entry for `semantic_version` of a package.
+pub async fn get_package_history_version(
+    pool: &Pool,
+    package_id: &str,
+    semantic_version: &str,
+) -> Result<Option<PackageVersionRow>, AppError> {
+    #[allow(clippy::type_complexity)]
+    let row: Option<(
+        serde_json::Value,
+        Option<serde_json::Value>,
+        Option<String>,
+        Option<String>,
+        Option<String>,
+    )> = sqlx::query_as(&format!(
+        "SELECT payload, {} FROM {} WHERE id = $1 AND semantic_version = $2 \
+         ORDER BY created_at DESC, version DESC LIMIT 1",
+        PACKAGE_VERSION_COLUMNS,
+        qualified_sys_table(PACKAGES_HISTORY_TABLE)
+    ))
+    .bind(package_id)
+    .bind(semantic_version)
+    .fetch_optional(pool)
+    .await?;
+    Ok(row.map(
+        |(payload, config_versions, content_hash, signer, key_id)| PackageVersionRow {
+            payload,
+            config_versions,
+            integrity: crate::signing::PackageIntegrity {
+                content_hash: content_hash.unwrap_or_default(),
+                signer,
+                key_id,
+            },
+        },
+    ))
+}
+
+/// Payloads of one config table at `version` for a package: the live rows when that version is
+/// current, else the copies in `<table>_history`.
+pub async fn load_config_rows_at_version(
+    pool: &Pool,
+    table: &str,
+    package_id: &str,
+    version: i64,
+) -> Result<Vec<serde_json::Value>, AppError> {
+    for source in [table.to_string(), format!("{}_history", table)] {
+        let rows: Vec<(serde_json::Value,)> = sqlx::query_as(&format!(
+            "SELECT payload FROM {} WHERE package_id = $1 AND version = $2 ORDER BY id",
+            qualified_sys_table(&source)
+        ))
+        .bind(package_id)
+        .bind(version)
+        .fetch_all(pool)
+        .await?;
+        if !rows.is_empty() {
+            return Ok(rows.into_iter().map(|(payload,)| payload).collect());
+        }
+    }
+    Ok(Vec::new())
+}
+
 /// Delete all config rows and KV data for a package, then remove the package record.
 /// Copies the current package row to _sys_packages_history before delete. Call after reverting migrations on the tenant DB.
 pub async fn delete_package_and_config(pool: &Pool, package_id: &str) -> Result<(), AppError> {
@@ -785,6 +1579,23 @@ pub async fn delete_package_and_config(pool: &Pool, package_id: &str) -> Result<
         .execute(&mut *tx)
         .await?;
 
+    // Open expand/contract transitions die with the package's tables
+    sqlx::query(&format!(
+        "DELETE FROM {} WHERE package_id = $1",
+        qualified_sys_table(COLUMN_TRANSITIONS_TABLE)
+    ))
+    .bind(package_id)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query(&format!(
+        "DELETE FROM {} WHERE package_id = $1",
+        qualified_sys_table(DATA_MIGRATIONS_TABLE)
+    ))
+    .bind(package_id)
+    .execute(&mut *tx)
+    .await?;
+
     // Delete package row
     sqlx::query(&format!("DELETE FROM {} WHERE id = $1", q_packages))
         .bind(package_id)
@@ -0,0 +1,215 @@
+//! Tracing setup and W3C trace-context propagation.
+//!
+//! [`init_tracing`] installs the `fmt` subscriber and, with the `otel` feature and
+//! `OTEL_EXPORTER_OTLP_ENDPOINT` set (e.g. `http://localhost:4318` for a local collector), exports
+//! spans over OTLP/HTTP. The service name is `OTEL_SERVICE_NAME`, else `architect`.
+//!
+//! Spans: `http.request` per entity/config request ([`trace_requests`], which continues an
+//! incoming `traceparent`), one per `CrudService` method with a `db.query` child carrying the
+//! generated SQL (placeholders only, never values), `migration.step` per executed step, and
+//! `decision_hub.publish` / `authrs.check` for outbound calls, which send `traceparent`
+//! ([`inject_trace_context`]). Without the `otel` feature the spans still reach the `fmt` logs.
+
+use axum::extract::{MatchedPath, Request};
+use axum::middleware::Next;
+use axum::response::Response;
+use tracing::Instrument;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::EnvFilter;
+
+/// Flushes pending spans when dropped. Keep it alive for the life of the process.
+#[must_use = "dropping the guard shuts down trace export"]
+pub struct TelemetryGuard {
+    #[cfg(feature = "otel")]
+    provider: Option<opentelemetry_sdk::trace::TracerProvider>,
+}
+
+impl Drop for TelemetryGuard {
+    fn drop(&mut self) {
+        #[cfg(feature = "otel")]
+        if let Some(provider) = self.provider.take() {
+            if let Err(e) = provider.shutdown() {
+                eprintln!("OpenTelemetry shutdown failed: {}", e);
+            }
+        }
+    }
+}
+
+/// Install the global subscriber: `fmt` output filtered by `filter`, plus OTLP export when
+/// enabled (see the module docs). Call once, inside the Tokio runtime.
+pub fn init_tracing(filter: EnvFilter) -> TelemetryGuard {
+    let registry = tracing_subscriber::registry()
+        .with(filter)
+        .with(tracing_subscriber::fmt::layer());
+
+    #[cfg(feature = "otel")]
+    {
+        let provider = otel::tracer_provider();
+        let layer = provider.as_ref().map(|provider| {
+            use opentelemetry::trace::TracerProvider as _;
+            tracing_opentelemetry::layer().with_tracer(provider.tracer("architect_sdk"))
+        });
+        registry.with(layer).init();
+        if provider.is_some() {
+            tracing::info!("OpenTelemetry trace export enabled");
+        }
+        TelemetryGuard { provider }
+    }
+    #[cfg(not(feature = "otel"))]
+    {
+        registry.init();
+        TelemetryGuard {}
+    }
+}
+
+/// Route layer opening an `http.request` span around the handler, continuing the caller's trace
+/// when the request carries `traceparent`. Install with `Router::route_layer`.
+pub async fn trace_requests(matched: Option<MatchedPath>, req: Request, next: Next) -> Response {
+    let route = matched.as_ref().map_or("", |m| m.as_str());
+    let span = tracing::info_span!(
+        "http.request",
+        otel.name = %format!("{} {}", req.method(), route),
+        otel.kind = "server",
+        http.request.method = %req.method(),
+        http.route = %route,
+        http.response.status_code = tracing::field::Empty,
+    );
+    #[cfg(feature = "otel")]
+    otel::set_parent_from_headers(&span, req.headers());
+    let response = next.run(req).instrument(span.clone()).await;
+    span.record("http.response.status_code", response.status().as_u16());
+    response
+}
+
+/// Add `traceparent` (and `tracestate`) for the current span to an outbound request. A no-op
+/// without the `otel` feature or when trace export is disabled.
+pub fn inject_trace_context(builder: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+    #[cfg(feature = "otel")]
+    {
+        otel::current_trace_headers()
+            .into_iter()
+            .fold(builder, |builder, (name, value)| {
+                builder.header(name, value)
+            })
+    }
+    #[cfg(not(feature = "otel"))]
+    builder
+}
+
+#[cfg(feature = "otel")]
+mod otel {
+    use opentelemetry::propagation::{Extractor, Injector};
+    use opentelemetry::KeyValue;
+    use opentelemetry_sdk::propagation::TraceContextPropagator;
+    use opentelemetry_sdk::trace::TracerProvider;
+    use opentelemetry_sdk::Resource;
+    use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+    /// An OTLP/HTTP batch exporter when `OTEL_EXPORTER_OTLP_ENDPOINT` (or the traces-specific
+    /// variant) is set.
+    pub(super) fn tracer_provider() -> Option<TracerProvider> {
+        let configured = [
+            "OTEL_EXPORTER_OTLP_ENDPOINT",
+            "OTEL_EXPORTER_OTLP_TRACES_ENDPOINT",
+        ]
+        .iter()
+        .any(|name| std::env::var(name).is_ok_and(|v| !v.trim().is_empty()));
+        if !configured {
+            return None;
+        }
+        let exporter = match opentelemetry_otlp::SpanExporter::builder()
+            .with_http()
+            .build()
+        {
+            Ok(exporter) => exporter,
+            Err(e) => {
+                eprintln!("OpenTelemetry disabled: {}", e);
+                return None;
+            }
+        };
+        let service_name = std::env::var("OTEL_SERVICE_NAME")
+            .ok()
+            .filter(|s| !s.trim().is_empty())
+            .unwrap_or_else(|| "architect".to_string());
+        opentelemetry::global::set_text_map_propagator(TraceContextPropagator::new());
+        Some(
+            TracerProvider::builder()
+                .with_batch_exporter(exporter, opentelemetry_sdk::runtime::Tokio)
+                .with_resource(Resource::new([KeyValue::new("service.name", service_name)]))
+                .build(),
+        )
+    }
+
+    struct HeaderExtractor<'a>(&'a axum::http::HeaderMap);
+
+    impl Extractor for HeaderExtractor<'_> {
+        fn get(&self, key: &str) -> Option<&str> {
+            self.0.get(key).and_then(|v| v.to_str().ok())
+        }
+        fn keys(&self) -> Vec<&str> {
+            self.0.keys().map(|k| k.as_str()).collect()
+        }
+    }
+
+    #[derive(Default)]
+    struct HeaderInjector(Vec<(String, String)>);
+
+    impl Injector for HeaderInjector {
+        fn set(&mut self, key: &str, value: String) {
+            self.0.push((key.to_string(), value));
+        }
+    }
+
+    pub(super) fn set_parent_from_headers(span: &tracing::Span, headers: &axum::http::HeaderMap) {
+        let parent = opentelemetry::global::get_text_map_propagator(|propagator| {
+            propagator.extract(&HeaderExtractor(headers))
+        });
+        span.set_parent(parent);
+    }
+
+    pub(super) fn current_trace_headers() -> Vec<(String, String)> {
+        let cx = tracing::Span::current().context();
+        let mut injector = HeaderInjector::default();
+        opentelemetry::global::get_text_map_propagator(|propagator| {
+            propagator.inject_context(&cx, &mut injector)
+        });
+        injector.0
+    }
+}
+
+#[cfg(all(test, feature = "otel"))]
+mod telemetry_tests {
+    use super::otel::*;
+    use opentelemetry::trace::TracerProvider as _;
+    use opentelemetry_sdk::propagation::TraceContextPropagator;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    #[test]
+    fn outbound_headers_carry_the_incoming_trace() {
+        opentelemetry::global::set_text_map_propagator(TraceContextPropagator::new());
+        let provider = opentelemetry_sdk::trace::TracerProvider::builder().build();
+        let subscriber = tracing_subscriber::registry()
+            .with(tracing_opentelemetry::layer().with_tracer(provider.tracer("test")));
+        tracing::subscriber::with_default(subscriber, || {
+            let mut headers = axum::http::HeaderMap::new();
+            headers.insert(
+                "traceparent",
+                "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01"
+                    .parse()
+                    .unwrap(),
+            );
+            let span = tracing::info_span!("http.request");
+            set_parent_from_headers(&span, &headers);
+            let _entered = span.enter();
+            let outbound = current_trace_headers();
+            let traceparent = outbound
+                .iter()
+                .find(|(k, _)| k == "traceparent")
+                .map(|(_, v)| v.as_str())
+                .unwrap();
+            assert!(traceparent.starts_with("00-4bf92f3577b34da6a3ce929d0e0e4736-"));
+            assert!(!traceparent.contains("00f067aa0ba902b7"));
+        });
+    }
+}
@@ -3,6 +3,7 @@
 use crate::db::pool::Pool;
 use crate::error::AppError;
 use crate::store::qualified_sys_table;
+use crate::tenant_pool::TenantPoolOptions;
 use std::collections::HashMap;
 
 /// Default tenant id that identifies the Platform Admin — the only principal allowed to write
@@ -66,21 +67,32 @@ pub struct TenantEntry {
     pub strategy: TenantStrategy,
     /// Required when strategy = Database. Optional for RLS (when set, app data uses that DB; config stays in architect DB).
     pub database_url: Option<String>,
+    /// Read replica of the tenant's database, for reads (see [`crate::replica`]).
+    pub replica_url: Option<String>,
+    /// Limits for the tenant's own pool (see [`crate::tenant_pool`]).
+    pub pool: TenantPoolOptions,
 }
 
 /// In-memory tenant registry loaded from central DB. Thread-safe via Arc.
 #[derive(Clone, Default)]
 pub struct TenantRegistry {
     by_id: HashMap<String, TenantEntry>,
+    loaded_at: Option<std::time::Instant>,
 }
 
 impl TenantRegistry {
     pub fn new() -> Self {
         TenantRegistry {
             by_id: HashMap::new(),
+            loaded_at: None,
         }
     }
 
+    /// When the registry was read from `_sys_tenants`; `None` for one built in code.
+    pub fn loaded_at(&self) -> Option<std::time::Instant> {
+        self.loaded_at
+    }
+
     pub fn get(&self, tenant_id: &str) -> Option<&TenantEntry> {
         self.by_id.get(tenant_id)
     }
@@ -89,6 +101,13 @@ impl TenantRegistry {
         self.by_id.is_empty()
     }
 
+    /// Every registered tenant id, sorted so background sweeps visit tenants in a stable order.
+    pub fn tenant_ids(&self) -> Vec<String> {
+        let mut ids: Vec<String> = self.by_id.keys().cloned().collect();
+        ids.sort();
+        ids
+    }
+
     /// All Database-strategy tenants as (tenant_id, database_url).
     /// Used by the DDL broadcast to know which dedicated databases need migration.
     pub fn database_tenant_targets(&self) -> Vec<(String, String)> {
@@ -134,16 +153,32 @@ impl TenantRegistry {
     }
 }
 
+/// A positive `_sys_tenants` seconds column as a duration.
+fn secs(value: Option<i32>) -> Option<std::time::Duration> {
+    value
+        .and_then(|n| u64::try_from(n).ok())
+        .filter(|n| *n > 0)
+        .map(std::time::Duration::from_secs)
+}
+
 /// Load tenant registry from architect._sys_tenants. Invalid rows are skipped (missing database_url for database strategy).
 pub async fn load_registry_from_pool(pool: &Pool) -> Result<TenantRegistry, AppError> {
     let q_table = qualified_sys_table("_sys_tenants");
     let sql = format!(
-        "SELECT id, strategy, database_url FROM {} ORDER BY id",
+        "SELECT id, strategy, database_url, replica_url, max_connections, acquire_timeout_secs, \
+         idle_timeout_secs FROM {} ORDER BY id",
         q_table
     );
-    let rows = sqlx::query_as::<_, (String, String, Option<String>)>(&sql)
-        .fetch_all(pool)
-        .await?;
+    type TenantRow = (
+        String,
+        String,
+        Option<String>,
+        Option<String>,
+        Option<i32>,
+        Option<i32>,
+        Option<i32>,
+    );
+    let rows = sqlx::query_as::<_, TenantRow>(&sql).fetch_all(pool).await?;
 
     let forced = forced_tenant_strategy();
     if let Some(s) = &forced {
@@ -158,7 +193,9 @@ pub async fn load_registry_from_pool(pool: &Pool) -> Result<TenantRegistry, AppE
     }
 
     let mut by_id = HashMap::new();
-    for (id, strategy_str, database_url) in rows {
+    for (id, strategy_str, database_url, replica_url, max_connections, acquire_secs, idle_secs) in
+        rows
+    {
         // Effective strategy: the app-wide override when set, else the per-tenant stored value.
         let strategy = match &forced {
             Some(s) => s.clone(),
@@ -194,11 +231,22 @@ pub async fn load_registry_from_pool(pool: &Pool) -> Result<TenantRegistry, AppE
             TenantEntry {
                 strategy,
                 database_url,
+                replica_url: replica_url.filter(|s| !s.is_empty()),
+                pool: TenantPoolOptions {
+                    max_connections: max_connections
+                        .and_then(|n| u32::try_from(n).ok())
+                        .filter(|n| *n > 0),
+                    acquire_timeout: secs(acquire_secs),
+                    idle_timeout: secs(idle_secs),
+                },
             },
         );
     }
 
-    Ok(TenantRegistry { by_id })
+    Ok(TenantRegistry {
+        by_id,
+        loaded_at: Some(std::time::Instant::now()),
+    })
 }
 
 #[cfg(test)]
@@ -0,0 +1,396 @@
+//! Lifecycle of per-tenant connection pools.
+//!
+//! Tenants with their own `database_url` (Database strategy, or RLS on a dedicated DB) get a pool
+//! of their own, created on first use. [`TenantPools`] bounds what those pools may hold:
+//!
+//! - A pool unused for `ARCHITECT_TENANT_POOL_IDLE_SECS` (default 600) is evicted.
+//! - At most `ARCHITECT_TENANT_POOLS_MAX` pools (default 256) are kept; the least recently used
+//!   is evicted to make room.
+//! - `ARCHITECT_TENANT_CONNECTION_BUDGET`, when set, caps the summed `max_connections` of all
+//!   cached pools. Least recently used pools are evicted until a new pool fits.
+//!
+//! Per-tenant `max_connections`, `acquire_timeout_secs` and `idle_timeout_secs` come from
+//! `_sys_tenants` ([`TenantPoolOptions`]). An evicted pool is closed, so its connections count
+//! against the budget no longer than it takes them to drain: idle connections close at once, and
+//! connections still checked out by in-flight requests close as they are returned. A request that
+//! outlives its pool's eviction fails its next acquire and can be retried.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+use crate::db::pool::Pool;
+use crate::error::AppError;
+
+/// Connections per tenant pool when `_sys_tenants.max_connections` is not set.
+pub const DEFAULT_TENANT_MAX_CONNECTIONS: u32 = 5;
+/// Pools kept when `ARCHITECT_TENANT_POOLS_MAX` is not set.
+pub const DEFAULT_MAX_TENANT_POOLS: usize = 256;
+/// Idle time after which a pool is evicted when `ARCHITECT_TENANT_POOL_IDLE_SECS` is not set.
+pub const DEFAULT_TENANT_POOL_IDLE: Duration = Duration::from_secs(600);
+
+/// Pool settings for one tenant (`_sys_tenants.max_connections`, `acquire_timeout_secs`,
+/// `idle_timeout_secs`). Unset values use the defaults.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TenantPoolOptions {
+    pub max_connections: Option<u32>,
+    /// How long a request waits for a free connection.
+    pub acquire_timeout: Option<Duration>,
+    /// How long an unused connection stays open.
+    pub idle_timeout: Option<Duration>,
+}
+
+/// Limits on all tenant pools together.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PoolLimits {
+    pub max_pools: usize,
+    pub idle_ttl: Duration,
+    /// Cap on the summed `max_connections` of cached pools; `None` means no cap.
+    pub connection_budget: Option<u32>,
+}
+
+impl Default for PoolLimits {
+    fn default() -> Self {
+        Self {
+            max_pools: DEFAULT_MAX_TENANT_POOLS,
+            idle_ttl: DEFAULT_TENANT_POOL_IDLE,
+            connection_budget: None,
+        }
+    }
+}
+
+impl PoolLimits {
+    /// The configured limits (env `ARCHITECT_TENANT_POOLS_MAX`, `ARCHITECT_TENANT_POOL_IDLE_SECS`,
+    /// `ARCHITECT_TENANT_CONNECTION_BUDGET`). Unparseable values fall back to the defaults.
+    pub fn from_env() -> Self {
+        fn var<T: std::str::FromStr>(name: &str) -> Option<T> {
+            std::env::var(name).ok().and_then(|v| v.trim().parse().ok())
+        }
+        let defaults = Self::default();
+        Self {
+            max_pools: var("ARCHITECT_TENANT_POOLS_MAX")
+                .filter(|n| *n > 0)
+                .unwrap_or(defaults.max_pools),
+            idle_ttl: var("ARCHITECT_TENANT_POOL_IDLE_SECS")
+                .map(Duration::from_secs)
+                .unwrap_or(defaults.idle_ttl),
+            connection_budget: var("ARCHITECT_TENANT_CONNECTION_BUDGET").filter(|n| *n > 0),
+        }
+    }
+}
+
+struct TenantPool {
+    pool: Pool,
+    max_connections: u32,
+    created: Instant,
+    last_used: Instant,
+    requests: u64,
+}
+
+#[derive(Default)]
+struct Inner {
+    pools: HashMap<String, TenantPool>,
+    evicted_idle: u64,
+    evicted_lru: u64,
+}
+
+/// Cached tenant pools keyed by tenant id. Construct with `Default::default()` (limits from the
+/// environment) or [`TenantPools::with_limits`].
+#[derive(Clone)]
+pub struct TenantPools {
+    inner: Arc<Mutex<Inner>>,
+    limits: PoolLimits,
+}
+
+impl Default for TenantPools {
+    fn default() -> Self {
+        Self::with_limits(PoolLimits::from_env())
+    }
+}
+
+/// One cached pool, as reported by [`TenantPools::stats`].
+#[derive(Debug, Clone, Serialize)]
+pub struct TenantPoolStat {
+    pub tenant_id: String,
+    /// Open connections.
+    pub size: u32,
+    pub idle: usize,
+    pub max_connections: u32,
+    /// Requests served from the cache since the pool was created.
+    pub requests: u64,
+    pub age_secs: u64,
+    pub idle_secs: u64,
+}
+
+/// Snapshot of all tenant pools and the limits they run under.
+#[derive(Debug, Clone, Serialize)]
+pub struct TenantPoolStats {
+    pub pools: Vec<TenantPoolStat>,
+    pub open_connections: u32,
+    /// Summed `max_connections` of the cached pools, counted against the budget.
+    pub reserved_connections: u32,
+    pub connection_budget: Option<u32>,
+    pub max_pools: usize,
+    pub idle_ttl_secs: u64,
+    pub evicted_idle: u64,
+    pub evicted_lru: u64,
+}
+
+impl TenantPools {
+    pub fn with_limits(limits: PoolLimits) -> Self {
+        Self {
+            inner: Arc::default(),
+            limits,
+        }
+    }
+
+    pub fn limits(&self) -> &PoolLimits {
+        &self.limits
+    }
+
+    fn lock(&self) -> Result<std::sync::MutexGuard<'_, Inner>, AppError> {
+        self.inner
+            .lock()
+            .map_err(|_| AppError::BadRequest("state lock".into()))
+    }
+
+    /// The tenant's pool, connecting on first use. Evicts idle pools, then least recently used
+    /// ones while the pool count or connection budget would be exceeded.
+    pub async fn get_or_connect(
+        &self,
+        tenant_id: &str,
+        database_url: &str,
+        options: &TenantPoolOptions,
+    ) -> Result<Pool, AppError> {
+        {
+            let mut inner = self.lock()?;
+            self.evict_idle(&mut inner);
+            if let Some(entry) = inner.pools.get_mut(tenant_id) {
+                entry.last_used = Instant::now();
+                entry.requests += 1;
+                return Ok(entry.pool.clone());
+            }
+        }
+        let mut max_connections = options
+            .max_connections
+            .unwrap_or(DEFAULT_TENANT_MAX_CONNECTIONS)
+            .max(1);
+        if let Some(budget) = self.limits.connection_budget {
+            if max_connections > budget {
+                tracing::warn!(
+                    tenant_id,
+                    max_connections,
+                    budget,
+                    "tenant max_connections exceeds ARCHITECT_TENANT_CONNECTION_BUDGET; capping"
+                );
+                max_connections = budget;
+            }
+        }
+        let mut pool_options = sqlx::pool::PoolOptions::new().max_connections(max_connections);
+        if let Some(timeout) = options.acquire_timeout {
+            pool_options = pool_options.acquire_timeout(timeout);
+        }
+        pool_options =
+            pool_options.idle_timeout(options.idle_timeout.or(Some(self.limits.idle_ttl)));
+        let pool: Pool = pool_options.connect(database_url).await?;
+
+        let mut inner = self.lock()?;
+        // Another request may have connected this tenant meanwhile; keep the pool already cached.
+        if let Some(entry) = inner.pools.get_mut(tenant_id) {
+            entry.last_used = Instant::now();
+            entry.requests += 1;
+            return Ok(entry.pool.clone());
+        }
+        self.make_room(&mut inner, max_connections);
+        let now = Instant::now();
+        inner.pools.insert(
+            tenant_id.to_string(),
+            TenantPool {
+                pool: pool.clone(),
+                max_connections,
+                created: now,
+                last_used: now,
+                requests: 1,
+            },
+        );
+        Ok(pool)
+    }
+
+    /// Current pools (sorted by tenant id) and limits. Idle pools are evicted first.
+    pub fn stats(&self) -> Result<TenantPoolStats, AppError> {
+        let mut inner = self.lock()?;
+        self.evict_idle(&mut inner);
+        let mut pools: Vec<TenantPoolStat> = inner
+            .pools
+            .iter()
+            .map(|(tenant_id, entry)| TenantPoolStat {
+                tenant_id: tenant_id.clone(),
+                size: entry.pool.size(),
+                idle: entry.pool.num_idle(),
+                max_connections: entry.max_connections,
+                requests: entry.requests,
+                age_secs: entry.created.elapsed().as_secs(),
+                idle_secs: entry.last_used.elapsed().as_secs(),
+            })
+            .collect();
+        pools.sort_by(|a, b| a.tenant_id.cmp(&b.tenant_id));
+        Ok(TenantPoolStats {
+            open_connections: pools.iter().map(|p| p.size).sum(),
+            reserved_connections: pools.iter().map(|p| p.max_connections).sum(),
+            pools,
+            connection_budget: self.limits.connection_budget,
+            max_pools: self.limits.max_pools,
+            idle_ttl_secs: self.limits.idle_ttl.as_secs(),
+            evicted_idle: inner.evicted_idle,
+            evicted_lru: inner.evicted_lru,
+        })
+    }
+
+    /// The cached pools (sorted by tenant id) without marking them used, for health probes.
+    pub fn cached(&self) -> Result<Vec<(String, Pool)>, AppError> {
+        let inner = self.lock()?;
+        let mut pools: Vec<(String, Pool)> = inner
+            .pools
+            .iter()
+            .map(|(tenant_id, entry)| (tenant_id.clone(), entry.pool.clone()))
+            .collect();
+        pools.sort_by(|a, b| a.0.cmp(&b.0));
+        Ok(pools)
+    }
+
+    fn evict_idle(&self, inner: &mut Inner) {
+        let ttl = self.limits.idle_ttl;
+        let idle: Vec<String> = inner
+            .pools
+            .iter()
+            .filter(|(_, entry)| entry.last_used.elapsed() >= ttl)
+            .map(|(tenant_id, _)| tenant_id.clone())
+            .collect();
+        for tenant_id in idle {
+            tracing::debug!(tenant_id = %tenant_id, "evicting idle tenant pool");
+            if let Some(entry) = inner.pools.remove(&tenant_id) {
+                close_evicted(entry.pool);
+            }
+            inner.evicted_idle += 1;
+        }
+    }
+
+    /// Evict least recently used pools until one more pool with `max_connections` fits.
+    fn make_room(&self, inner: &mut Inner, max_connections: u32) {
+        loop {
+            let reserved: u32 = inner.pools.values().map(|p| p.max_connections).sum();
+            let over_budget = self
+                .limits
+                .connection_budget
+                .is_some_and(|budget| reserved + max_connections > budget);
+            if inner.pools.len() < self.limits.max_pools && !over_budget {
+                return;
+            }
+            let Some(lru) = inner
+                .pools
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(tenant_id, _)| tenant_id.clone())
+            else {
+                return;
+            };
+            tracing::debug!(tenant_id = %lru, "evicting least recently used tenant pool");
+            if let Some(entry) = inner.pools.remove(&lru) {
+                close_evicted(entry.pool);
+            }
+            inner.evicted_lru += 1;
+        }
+    }
+}
+
+/// Close an evicted pool. Clones held by in-flight requests would otherwise keep its connections
+/// open past the connection budget. Closing refuses new acquires; the spawned drain closes idle
+/// connections and waits for checked-out ones, which close as they are returned.
+fn close_evicted(pool: Pool) {
+    match tokio::runtime::Handle::try_current() {
+        Ok(runtime) => {
+            runtime.spawn(async move { pool.close().await });
+        }
+        // No runtime to drain on: mark it closed; connections close as their holders drop them.
+        Err(_) => drop(pool.close()),
+    }
+}
+
+#[cfg(all(test, feature = "sqlite"))]
+mod tenant_pool_tests {
+    use super::*;
+
+    const URL: &str = "sqlite::memory:";
+
+    fn pools(max_pools: usize, connection_budget: Option<u32>) -> TenantPools {
+        TenantPools::with_limits(PoolLimits {
+            max_pools,
+            idle_ttl: Duration::from_secs(600),
+            connection_budget,
+        })
+    }
+
+    fn cached(pools: &TenantPools) -> Vec<String> {
+        let stats = pools.stats().unwrap();
+        stats.pools.into_iter().map(|p| p.tenant_id).collect()
+    }
+
+    #[tokio::test]
+    async fn least_recently_used_pool_is_evicted_at_the_pool_limit() {
+        let pools = pools(2, None);
+        let options = TenantPoolOptions::default();
+        pools.get_or_connect("a", URL, &options).await.unwrap();
+        let b = pools.get_or_connect("b", URL, &options).await.unwrap();
+        // Touch "a" so "b" is the least recently used.
+        pools.get_or_connect("a", URL, &options).await.unwrap();
+        pools.get_or_connect("c", URL, &options).await.unwrap();
+        assert_eq!(cached(&pools), vec!["a", "c"]);
+        // A clone held by an in-flight request does not keep the evicted pool open.
+        tokio::task::yield_now().await;
+        assert!(b.is_closed());
+        let stats = pools.stats().unwrap();
+        assert_eq!(stats.evicted_lru, 1);
+        assert_eq!(stats.pools[0].requests, 2);
+    }
+
+    #[tokio::test]
+    async fn connection_budget_evicts_pools_and_caps_max_connections() {
+        let pools = pools(10, Some(6));
+        let small = TenantPoolOptions {
+            max_connections: Some(3),
+            ..Default::default()
+        };
+        pools.get_or_connect("a", URL, &small).await.unwrap();
+        pools.get_or_connect("b", URL, &small).await.unwrap();
+        pools.get_or_connect("c", URL, &small).await.unwrap();
+        assert_eq!(cached(&pools), vec!["b", "c"]);
+
+        let large = TenantPoolOptions {
+            max_connections: Some(50),
+            ..Default::default()
+        };
+        pools.get_or_connect("d", URL, &large).await.unwrap();
+        let stats = pools.stats().unwrap();
+        assert_eq!(cached(&pools), vec!["d"]);
+        assert_eq!(stats.reserved_connections, 6);
+    }
+
+    #[tokio::test]
+    async fn idle_pools_are_evicted() {
+        let pools = TenantPools::with_limits(PoolLimits {
+            idle_ttl: Duration::ZERO,
+            ..PoolLimits::default()
+        });
+        let a = pools
+            .get_or_connect("a", URL, &TenantPoolOptions::default())
+            .await
+            .unwrap();
+        let stats = pools.stats().unwrap();
+        assert!(stats.pools.is_empty());
+        assert_eq!(stats.evicted_idle, 1);
+        tokio::task::yield_now().await;
+        assert!(a.is_closed());
+    }
+}
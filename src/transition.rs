@@ -0,0 +1,824 @@
+//! Expand/contract column type changes.
+//!
+//! An in-place `ALTER COLUMN … TYPE` rewrites the whole table under an exclusive lock. The
+//! expand/contract strategy spreads the same change over three phases that can each be
+//! previewed and applied on their own:
+//!
+//! 1. **expand** — part of the upgrade plan ([`to_expand_contract_plan`]): the retyped column
+//!    keeps its old type and a nullable shadow column (`<column>__new`) with the new type is
+//!    added next to it. From then on the CRUD layer writes every value to both columns (see
+//!    [`crate::config::ShadowColumn`]).
+//! 2. **backfill** — [`backfill_plan`]: rows written before the expand are copied into the shadow
+//!    column in batches. Progress is written to `_sys_migration_audit` after every batch.
+//! 3. **contract** — [`contract_plan`]: a catch-up batch, then the shadow column takes the
+//!    original name, constraints and indexes are restored and the old column is dropped.
+//!
+//! Open transitions live in `_sys_column_transitions`, one row per column, keyed by the package
+//! cache key so database-per-tenant installs progress independently. [`record_transitions`]
+//! moves them forward after a plan has run.
+
+use crate::config::{
+    ColumnConfig, FullConfig, IndexColumnEntry, PrimaryKeyConfig, ResolvedModel, ShadowColumn,
+    TableConfig,
+};
+use crate::db::pool::Pool;
+use crate::db::{parse_canonical, Dialect};
+use crate::error::AppError;
+use crate::migration::{
+    add_column_ddl, create_index_ddl, default_str, number_steps, quote, MigrationExecutionResult,
+    MigrationOperation, MigrationPlan, MigrationRisk, MigrationSafety, MigrationStep,
+    StepExecution,
+};
+use crate::store::{
+    delete_column_transition, list_column_transitions, upsert_column_transition,
+    ColumnTransitionRow,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// How an upgrade plan applies column type changes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TypeChangeStrategy {
+    /// `ALTER COLUMN … TYPE … USING` in a single step.
+    #[default]
+    InPlace,
+    /// Shadow column, dual writes, batched backfill, then swap (see the module docs).
+    ExpandContract,
+}
+
+/// Where a column transition stands. Stored as text in `_sys_column_transitions.phase`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransitionPhase {
+    /// Shadow column added; new writes go to both columns.
+    Expanded,
+    /// Existing rows copied; the swap can run.
+    Backfilled,
+    /// Columns swapped and the old one dropped. Never stored: the row is deleted instead.
+    Contracted,
+}
+
+impl TransitionPhase {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TransitionPhase::Expanded => "expanded",
+            TransitionPhase::Backfilled => "backfilled",
+            TransitionPhase::Contracted => "contracted",
+        }
+    }
+}
+
+/// The column transition a plan step moves forward, carried in
+/// [`StepExecution::transition`] so saved plans can be recorded after they run.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ColumnTransition {
+    pub column: String,
+    pub shadow_column: String,
+    /// DDL type of the original column.
+    pub from_type: String,
+    /// DDL type of the shadow column.
+    pub to_type: String,
+    /// Cast for binding values to the original column, if the dialect needs one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_cast: Option<String>,
+    /// Phase reached once the step has run.
+    pub phase: TransitionPhase,
+}
+
+/// Name of the shadow column that receives the new type.
+pub fn shadow_column_name(column: &str) -> String {
+    format!("{}__new", column)
+}
+
+/// Name the original column is parked under during the swap, just before it is dropped.
+fn retired_column_name(column: &str) -> String {
+    format!("{}__old", column)
+}
+
+fn find_table<'a>(config: &'a FullConfig, table: &str) -> Option<&'a TableConfig> {
+    config.tables.iter().find(|t| t.name == table)
+}
+
+fn find_column<'a>(
+    config: &'a FullConfig,
+    table: &TableConfig,
+    column: &str,
+) -> Option<&'a ColumnConfig> {
+    config
+        .columns
+        .iter()
+        .find(|c| c.table_id == table.id && c.name == column)
+}
+
+/// Why `col` has to keep the in-place type change: the swap would have to rebuild a key or
+/// constraint that other objects depend on. `None` when the column is eligible.
+fn ineligible_reason(
+    config: &FullConfig,
+    table: &TableConfig,
+    col: &ColumnConfig,
+) -> Option<String> {
+    let in_pk = match &table.primary_key {
+        PrimaryKeyConfig::Single(c) => c == &col.name,
+        PrimaryKeyConfig::Composite(cs) => cs.contains(&col.name),
+    };
+    if in_pk {
+        return Some("primary key columns".into());
+    }
+    if table.unique.iter().any(|group| group.contains(&col.name)) {
+        return Some("columns in a UNIQUE constraint".into());
+    }
+    if config
+        .relationships
+        .iter()
+        .any(|r| r.from_column_id == col.id || r.to_column_id == col.id)
+    {
+        return Some("foreign key columns".into());
+    }
+    if table.check.iter().any(|c| c.expression.contains(&col.name)) {
+        return Some("columns used in a CHECK constraint".into());
+    }
+    None
+}
+
+/// Rewrite the column type changes of an upgrade plan into expand steps.
+///
+/// Each `AlterColumnType` step for a configured column becomes an `AddShadowColumn` step; the
+/// copy and the swap are left to [`backfill_plan`] and [`contract_plan`]. Columns that are part
+/// of a primary key, UNIQUE, foreign key or CHECK constraint keep the in-place step, with the
+/// reason appended to its `risk_detail`. Steps come back renumbered.
+pub fn to_expand_contract_plan(
+    plan: MigrationPlan,
+    old: &FullConfig,
+    new: &FullConfig,
+    dialect: &dyn Dialect,
+) -> MigrationPlan {
+    let mut steps: Vec<MigrationStep> = Vec::with_capacity(plan.steps.len());
+    for mut step in plan.steps {
+        if !matches!(step.operation, MigrationOperation::AlterColumnType) || step.ddl.is_none() {
+            steps.push(step);
+            continue;
+        }
+        let Some(table) = step.table.as_deref().and_then(|t| find_table(new, t)) else {
+            steps.push(step);
+            continue;
+        };
+        let Some(new_col) = find_column(new, table, &step.object) else {
+            steps.push(step);
+            continue;
+        };
+        // Enum recasts are AlterColumnType steps too, but the configured type is unchanged.
+        let Some(old_col) = old.columns.iter().find(|c| c.id == new_col.id) else {
+            steps.push(step);
+            continue;
+        };
+        let old_canonical = parse_canonical(&old_col.type_);
+        let from_type = dialect.ddl_type(&old_canonical);
+        let to_type = dialect.ddl_type(&parse_canonical(&new_col.type_));
+        if from_type.eq_ignore_ascii_case(&to_type) {
+            steps.push(step);
+            continue;
+        }
+        if let Some(reason) = ineligible_reason(new, table, new_col) {
+            let note = format!(
+                "Expand/contract is not available for {}; the type is changed in place.",
+                reason
+            );
+            step.risk_detail = Some(match step.risk_detail.take() {
+                Some(detail) => format!("{} {}", detail, note),
+                None => note,
+            });
+            steps.push(step);
+            continue;
+        }
+
+        let shadow = shadow_column_name(&new_col.name);
+        let full = format!("{}.{}", quote(&step.schema), quote(&table.name));
+        steps.push(MigrationStep::new(
+            MigrationOperation::AddShadowColumn,
+            step.schema.clone(),
+            Some(table.name.clone()),
+            shadow.clone(),
+            "column",
+            format!(
+                "Add shadow column \"{}\" ({}) to \"{}\".\"{}\" for the type change of \"{}\": {} → {}",
+                shadow, to_type, step.schema, table.name, new_col.name, from_type, to_type
+            ),
+        )
+        .with_ddl(add_column_ddl(
+            dialect,
+            &full,
+            &format!("{} {}", quote(&shadow), to_type),
+        ))
+        .with_risk(
+            MigrationRisk::None,
+            Some(format!(
+                "\"{}\" keeps its current type until the contract phase; writes are mirrored into \"{}\". Run the backfill phase next.",
+                new_col.name, shadow
+            )),
+        )
+        .with_execution(StepExecution {
+            transition: Some(ColumnTransition {
+                column: new_col.name.clone(),
+                shadow_column: shadow,
+                from_type,
+                to_type,
+                source_cast: dialect.cast_name(&old_canonical),
+                phase: TransitionPhase::Expanded,
+            }),
+            ..StepExecution::default()
+        }));
+    }
+    number_steps(&mut steps, dialect);
+    MigrationPlan { steps }
+}
+
+/// Quoted key the backfill batches are picked by: the primary key, as a row value when composite.
+fn batch_key(table: &TableConfig) -> String {
+    match &table.primary_key {
+        PrimaryKeyConfig::Single(c) => quote(c),
+        PrimaryKeyConfig::Composite(cs) => format!(
+            "({})",
+            cs.iter().map(|c| quote(c)).collect::<Vec<_>>().join(", ")
+        ),
+    }
+}
+
+fn row_transition(row: &ColumnTransitionRow, phase: TransitionPhase) -> ColumnTransition {
+    ColumnTransition {
+        column: row.column_name.clone(),
+        shadow_column: row.shadow_column.clone(),
+        from_type: row.from_type.clone(),
+        to_type: row.to_type.clone(),
+        source_cast: row.source_cast.clone(),
+        phase,
+    }
+}
+
+fn backfill_step(
+    row: &ColumnTransitionRow,
+    table: &TableConfig,
+    batch_size: u64,
+    dialect: &dyn Dialect,
+) -> MigrationStep {
+    let full = format!("{}.{}", quote(&row.schema_name), quote(&row.table_name));
+    let column = quote(&row.column_name);
+    let shadow = quote(&row.shadow_column);
+    let set = format!(
+        "{} = {}",
+        shadow,
+        dialect.assignment_cast(&column, &row.to_type)
+    );
+    let filter = format!("{} IS NULL AND {} IS NOT NULL", shadow, column);
+    MigrationStep::new(
+        MigrationOperation::BackfillShadowColumn,
+        row.schema_name.clone(),
+        Some(row.table_name.clone()),
+        row.column_name.clone(),
+        "column",
+        format!(
+            "Copy \"{}\".\"{}\".\"{}\" into \"{}\" as {} ({} rows per batch)",
+            row.schema_name,
+            row.table_name,
+            row.column_name,
+            row.shadow_column,
+            row.to_type,
+            batch_size
+        ),
+    )
+    .with_ddl(dialect.batched_update_sql(
+        &full,
+        &set,
+        &filter,
+        &batch_key(table),
+        batch_size,
+    ))
+    .with_risk(
+        MigrationRisk::MayFail,
+        Some(format!(
+            "A batch fails if a value does not convert to {}; fix those rows and re-run the backfill. Batches already copied are kept.",
+            row.to_type
+        )),
+    )
+    .with_execution(StepExecution {
+        batch_size: Some(batch_size),
+        ..StepExecution::default()
+    })
+}
+
+fn table_for<'a>(
+    config: &'a FullConfig,
+    row: &ColumnTransitionRow,
+) -> Result<&'a TableConfig, AppError> {
+    find_table(config, &row.table_name).ok_or_else(|| {
+        AppError::BadRequest(format!(
+            "column transition on \"{}\".\"{}\" refers to a table that is no longer configured",
+            row.table_name, row.column_name
+        ))
+    })
+}
+
+/// Backfill phase: one batched copy step per open transition. Re-running it is harmless — only
+/// rows whose shadow is still empty are touched.
+pub fn backfill_plan(
+    rows: &[ColumnTransitionRow],
+    config: &FullConfig,
+    batch_size: u64,
+    dialect: &dyn Dialect,
+) -> Result<MigrationPlan, AppError> {
+    if rows.is_empty() {
+        return Err(AppError::BadRequest(
+            "no column type changes are in progress".into(),
+        ));
+    }
+    let mut steps = Vec::with_capacity(rows.len());
+    for row in rows {
+        let table = table_for(config, row)?;
+        let mut step = backfill_step(row, table, batch_size, dialect);
+        step.execution.transition = Some(row_transition(row, TransitionPhase::Backfilled));
+        steps.push(step);
+    }
+    number_steps(&mut steps, dialect);
+    Ok(MigrationPlan { steps })
+}
+
+/// Contract phase: swap every backfilled shadow column into place.
+///
+/// Per column: a catch-up batch for rows written by instances that did not dual-write yet, drop
+/// the indexes on the column, rename `<column>` → `<column>__old` and the shadow →
+/// `<column>`, restore NOT NULL and the default, drop the old column, then recreate the
+/// indexes. Every transition must have been backfilled first.
+pub fn contract_plan(
+    rows: &[ColumnTransitionRow],
+    config: &FullConfig,
+    batch_size: u64,
+    dialect: &dyn Dialect,
+) -> Result<MigrationPlan, AppError> {
+    if rows.is_empty() {
+        return Err(AppError::BadRequest(
+            "no column type changes are in progress".into(),
+        ));
+    }
+    let pending: Vec<&str> = rows
+        .iter()
+        .filter(|r| r.phase != TransitionPhase::Backfilled.as_str())
+        .map(|r| r.column_name.as_str())
+        .collect();
+    if !pending.is_empty() {
+        return Err(AppError::BadRequest(format!(
+            "run the backfill phase before contracting: {} not backfilled yet",
+            pending.join(", ")
+        )));
+    }
+
+    let mut steps = Vec::new();
+    for row in rows {
+        let table = table_for(config, row)?;
+        let col = find_column(config, table, &row.column_name).ok_or_else(|| {
+            AppError::BadRequest(format!(
+                "column transition on \"{}\".\"{}\" refers to a column that is no longer configured",
+                row.table_name, row.column_name
+            ))
+        })?;
+        let schema = &row.schema_name;
+        let full = format!("{}.{}", quote(schema), quote(&row.table_name));
+        let retired = retired_column_name(&row.column_name);
+        let indexes: Vec<_> = config
+            .indexes
+            .iter()
+            .filter(|i| i.table_id == table.id && index_uses_column(i, &row.column_name))
+            .collect();
+        let step = |operation, object: &str, from_object: Option<&str>, description: String| {
+            MigrationStep::new(
+                operation,
+                schema.clone(),
+                Some(row.table_name.clone()),
+                object.to_string(),
+                "column",
+                description,
+            )
+            .with_from_object(from_object.map(String::from))
+        };
+
+        steps.push(backfill_step(row, table, batch_size, dialect));
+
+        for idx in &indexes {
+            let mut s = step(
+                MigrationOperation::DropIndex,
+                &idx.name,
+                None,
+                format!(
+                    "Drop index \"{}\" before swapping \"{}\"",
+                    idx.name, row.column_name
+                ),
+            );
+            s.object_type = "index".into();
+            s.ddl = Some(format!(
+                "DROP INDEX IF EXISTS {}.{}",
+                quote(schema),
+                quote(&idx.name)
+            ));
+            steps.push(s);
+        }
+
+        let mut s = step(
+            MigrationOperation::RenameColumn,
+            &retired,
+            Some(&row.column_name),
+            format!(
+                "Retire \"{}\".\"{}\".\"{}\" ({}) as \"{}\"",
+                schema, row.table_name, row.column_name, row.from_type, retired
+            ),
+        );
+        s.ddl = Some(format!(
+            "ALTER TABLE {} RENAME COLUMN {} TO {}",
+            full,
+            quote(&row.column_name),
+            quote(&retired)
+        ));
+        steps.push(s);
+
+        let mut s = step(
+            MigrationOperation::RenameColumn,
+            &row.column_name,
+            Some(&row.shadow_column),
+            format!(
+                "Rename shadow column \"{}\" ({}) to \"{}\"",
+                row.shadow_column, row.to_type, row.column_name
+            ),
+        );
+        s.ddl = Some(format!(
+            "ALTER TABLE {} RENAME COLUMN {} TO {}",
+            full,
+            quote(&row.shadow_column),
+            quote(&row.column_name)
+        ));
+        s.execution.transition = Some(row_transition(row, TransitionPhase::Contracted));
+        steps.push(s);
+
+        if !col.nullable {
+            let mut s = step(
+                MigrationOperation::SetNotNull,
+                &row.column_name,
+                None,
+                format!(
+                    "Restore NOT NULL on \"{}\".\"{}\".\"{}\"",
+                    schema, row.table_name, row.column_name
+                ),
+            );
+            s.ddl = Some(format!(
+                "ALTER TABLE {} ALTER COLUMN {} SET NOT NULL",
+                full,
+                quote(&row.column_name)
+            ));
+            s.safety = MigrationSafety::BestEffort;
+            s.risk = MigrationRisk::ExistingNullsMustBeAbsent;
+            steps.push(s);
+        }
+        if let Some(d) = &col.default {
+            let val = default_str(d);
+            let mut s = step(
+                MigrationOperation::SetDefault,
+                &row.column_name,
+                None,
+                format!(
+                    "Restore DEFAULT {} on \"{}\".\"{}\".\"{}\"",
+                    val, schema, row.table_name, row.column_name
+                ),
+            );
+            s.ddl = Some(format!(
+                "ALTER TABLE {} ALTER COLUMN {} SET DEFAULT {}",
+                full,
+                quote(&row.column_name),
+                val
+            ));
+            s.safety = MigrationSafety::BestEffort;
+            steps.push(s);
+        }
+
+        let mut s = step(
+            MigrationOperation::DropColumn,
+            &retired,
+            None,
+            format!(
+                "Drop retired column \"{}\".\"{}\".\"{}\"",
+                schema, row.table_name, retired
+            ),
+        );
+        s.ddl = Some(format!(
+            "ALTER TABLE {} DROP COLUMN {}",
+            full,
+            quote(&retired)
+        ));
+        s.safety = MigrationSafety::BestEffort;
+        s.risk_detail = Some(format!(
+            "Holds the {} values; every row was copied to \"{}\" by the backfill.",
+            row.from_type, row.column_name
+        ));
+        steps.push(s);
+
+        for idx in &indexes {
+            let mut s = step(
+                MigrationOperation::CreateIndex,
+                &idx.name,
+                None,
+                format!(
+                    "Recreate index \"{}\" on the swapped \"{}\"",
+                    idx.name, row.column_name
+                ),
+            );
+            s.object_type = "index".into();
+            s.ddl = Some(create_index_ddl(idx, &full));
+            steps.push(s);
+        }
+    }
+    number_steps(&mut steps, dialect);
+    Ok(MigrationPlan { steps })
+}
+
+fn index_uses_column(idx: &crate::config::IndexConfig, column: &str) -> bool {
+    idx.include.iter().any(|c| c == column)
+        || idx.columns.iter().any(|entry| match entry {
+            IndexColumnEntry::Name(n) => n == column,
+            IndexColumnEntry::Spec { name, .. } => name == column,
+            IndexColumnEntry::Expression { expression } => expression.contains(column),
+        })
+        || idx.where_.as_deref().is_some_and(|w| w.contains(column))
+}
+
+/// Move `_sys_column_transitions` forward for every transition step of `plan` that completed.
+/// `target` is the package cache key the transitions are tracked under.
+pub async fn record_transitions(
+    config_pool: &Pool,
+    target: &str,
+    package_id: &str,
+    tenant_id: &str,
+    plan: &MigrationPlan,
+    result: &MigrationExecutionResult,
+) -> Result<(), AppError> {
+    let completed: HashMap<usize, u64> = result.completed_steps.iter().copied().collect();
+    let existing = list_column_transitions(config_pool, target).await?;
+    for step in &plan.steps {
+        let (Some(t), Some(rows)) = (&step.execution.transition, completed.get(&step.step)) else {
+            continue;
+        };
+        let table = step.table.as_deref().unwrap_or_default();
+        if t.phase == TransitionPhase::Contracted {
+            delete_column_transition(config_pool, target, &step.schema, table, &t.column).await?;
+            continue;
+        }
+        let previous = existing
+            .iter()
+            .find(|r| {
+                r.schema_name == step.schema && r.table_name == table && r.column_name == t.column
+            })
+            .map(|r| r.rows_backfilled)
+            .unwrap_or(0);
+        let row = ColumnTransitionRow {
+            target: target.to_string(),
+            package_id: package_id.to_string(),
+            tenant_id: tenant_id.to_string(),
+            schema_name: step.schema.clone(),
+            table_name: table.to_string(),
+            column_name: t.column.clone(),
+            shadow_column: t.shadow_column.clone(),
+            from_type: t.from_type.clone(),
+            to_type: t.to_type.clone(),
+            source_cast: t.source_cast.clone(),
+            phase: t.phase.as_str().to_string(),
+            rows_backfilled: previous + *rows as i64,
+        };
+        upsert_column_transition(config_pool, &row).await?;
+    }
+    Ok(())
+}
+
+/// Attach open transitions to a resolved model so writes are mirrored into the shadow columns.
+/// The configured column type is already the new one; the source column is still the old type,
+/// so its binding cast is put back to the old one.
+pub fn attach_shadow_columns(
+    mut model: ResolvedModel,
+    rows: &[ColumnTransitionRow],
+) -> ResolvedModel {
+    if rows.is_empty() {
+        return model;
+    }
+    let entities = model
+        .entities
+        .iter_mut()
+        .chain(model.entity_by_path.values_mut());
+    for entity in entities {
+        entity.shadow_columns = rows
+            .iter()
+            .filter(|r| r.table_name == entity.table_name)
+            .map(|r| ShadowColumn {
+                column: r.column_name.clone(),
+                shadow: r.shadow_column.clone(),
+                source_cast: r.source_cast.clone(),
+                target_type: r.to_type.clone(),
+            })
+            .collect();
+        for shadow in &entity.shadow_columns {
+            if let Some(c) = entity.columns.iter_mut().find(|c| c.name == shadow.column) {
+                c.pg_type = shadow.source_cast.clone();
+            }
+        }
+    }
+    model
+}
+
+/// Open transitions for a package cache key, or none if the table cannot be read.
+pub async fn load_transitions(config_pool: &Pool, target: &str) -> Vec<ColumnTransitionRow> {
+    list_column_transitions(config_pool, target)
+        .await
+        .unwrap_or_default()
+}
+
+#[cfg(all(test, feature = "sqlite"))]
+mod transition_tests {
+    use super::*;
+    use crate::config::{ColumnTypeConfig, IndexConfig, SchemaConfig};
+    use crate::db::sqlite::SqliteDialect;
+    use crate::migration::compute_migration_plan;
+
+    fn col(id: &str, name: &str, ty: &str) -> ColumnConfig {
+        ColumnConfig {
+            id: id.into(),
+            table_id: "t1".into(),
+            name: name.into(),
+            type_: ColumnTypeConfig::Simple(ty.into()),
+            nullable: true,
+            default: None,
+            comment: None,
+            asset: None,
+            extensible: false,
+        }
+    }
+
+    fn config(amount_type: &str) -> FullConfig {
+        FullConfig {
+            schemas: vec![SchemaConfig {
+                id: "s1".into(),
+                name: "app".into(),
+                comment: None,
+            }],
+            tables: vec![TableConfig {
+                id: "t1".into(),
+                schema_id: Some("s1".into()),
+                name: "orders".into(),
+                comment: None,
+                primary_key: PrimaryKeyConfig::Single("id".into()),
+                unique: vec![],
+                check: vec![],
+                audit_log: false,
+                versioning: None,
+                global: false,
+            }],
+            columns: vec![col("c0", "id", "integer"), col("c1", "amount", amount_type)],
+            ..Default::default()
+        }
+    }
+
+    fn expand(old: &FullConfig, new: &FullConfig) -> MigrationPlan {
+        let dialect = SqliteDialect;
+        let plan = compute_migration_plan(old, new, None, None, &dialect, &HashMap::new()).unwrap();
+        to_expand_contract_plan(plan, old, new, &dialect)
+    }
+
+    fn row(phase: TransitionPhase) -> ColumnTransitionRow {
+        ColumnTransitionRow {
+            target: "pkg".into(),
+            package_id: "pkg".into(),
+            tenant_id: "t1".into(),
+            schema_name: "app".into(),
+            table_name: "orders".into(),
+            column_name: "amount".into(),
+            shadow_column: "amount__new".into(),
+            from_type: "TEXT".into(),
+            to_type: "INTEGER".into(),
+            source_cast: None,
+            phase: phase.as_str().into(),
+            rows_backfilled: 0,
+        }
+    }
+
+    #[test]
+    fn a_type_change_becomes_a_shadow_column() {
+        let plan = expand(&config("text"), &config("integer"));
+        assert_eq!(plan.steps.len(), 1);
+        let s = &plan.steps[0];
+        assert!(matches!(s.operation, MigrationOperation::AddShadowColumn));
+        assert_eq!(s.object, "amount__new");
+        assert_eq!(
+            s.ddl.as_deref(),
+            Some(r#"ALTER TABLE "app"."orders" ADD COLUMN "amount__new" INTEGER"#)
+        );
+        let t = s.execution.transition.as_ref().unwrap();
+        assert_eq!(t.column, "amount");
+        assert_eq!(t.phase, TransitionPhase::Expanded);
+        assert_eq!(
+            (t.from_type.as_str(), t.to_type.as_str()),
+            ("TEXT", "INTEGER")
+        );
+    }
+
+    #[test]
+    fn constrained_columns_keep_the_in_place_change() {
+        let old = config("text");
+        let mut new = config("integer");
+        new.tables[0].unique = vec![vec!["amount".into()]];
+        let plan = expand(&old, &new);
+        let s = &plan.steps[0];
+        assert!(matches!(s.operation, MigrationOperation::AlterColumnType));
+        assert!(s
+            .risk_detail
+            .as_deref()
+            .unwrap()
+            .contains("UNIQUE constraint"));
+    }
+
+    #[test]
+    fn backfill_copies_in_primary_key_batches() {
+        let plan = backfill_plan(
+            &[row(TransitionPhase::Expanded)],
+            &config("integer"),
+            250,
+            &SqliteDialect,
+        )
+        .unwrap();
+        let s = &plan.steps[0];
+        assert_eq!(
+            s.ddl.as_deref(),
+            Some(
+                r#"UPDATE "app"."orders" SET "amount__new" = CAST("amount" AS INTEGER) WHERE "id" IN (SELECT "id" FROM "app"."orders" WHERE "amount__new" IS NULL AND "amount" IS NOT NULL LIMIT 250)"#
+            )
+        );
+        assert_eq!(s.execution.batch_size, Some(250));
+        assert_eq!(
+            s.execution.transition.as_ref().map(|t| t.phase),
+            Some(TransitionPhase::Backfilled)
+        );
+    }
+
+    #[test]
+    fn contract_requires_a_backfill_first() {
+        let err = contract_plan(
+            &[row(TransitionPhase::Expanded)],
+            &config("integer"),
+            100,
+            &SqliteDialect,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("backfill"), "{}", err);
+    }
+
+    #[test]
+    fn contract_swaps_columns_around_the_indexes() {
+        let mut cfg = config("integer");
+        cfg.columns[1].nullable = false;
+        cfg.indexes.push(IndexConfig {
+            id: "i1".into(),
+            schema_id: Some("s1".into()),
+            table_id: "t1".into(),
+            name: "idx_orders_amount".into(),
+            method: None,
+            unique: false,
+            columns: vec![IndexColumnEntry::Name("amount".into())],
+            include: vec![],
+            where_: None,
+            comment: None,
+        });
+        let plan = contract_plan(
+            &[row(TransitionPhase::Backfilled)],
+            &cfg,
+            100,
+            &SqliteDialect,
+        )
+        .unwrap();
+        let ops: Vec<String> = plan
+            .steps
+            .iter()
+            .map(|s| format!("{} {}", s.operation, s.object))
+            .collect();
+        assert_eq!(
+            ops,
+            [
+                "backfill_shadow_column amount",
+                "drop_index idx_orders_amount",
+                "rename_column amount__old",
+                "rename_column amount",
+                "set_not_null amount",
+                "drop_column amount__old",
+                "create_index idx_orders_amount",
+            ]
+        );
+        let swap = &plan.steps[3];
+        assert_eq!(swap.from_object.as_deref(), Some("amount__new"));
+        assert_eq!(
+            swap.execution.transition.as_ref().map(|t| t.phase),
+            Some(TransitionPhase::Contracted)
+        );
+        // Only the swap moves the transition; the catch-up batch leaves it alone.
+        assert!(plan.steps[0].execution.transition.is_none());
+    }
+}
@@ -16,12 +16,18 @@ use std::collections::HashMap;
 use architect_sdk::{
     apply_migrations, compute_migration_plan,
     config::{
-        ApiEntityConfig, ColumnConfig, ColumnTypeConfig, FullConfig, PrimaryKeyConfig,
-        SchemaConfig, TableConfig, ValidationRule,
+        ApiEntityConfig, ColumnConfig, ColumnTypeConfig, FullConfig, IndexColumnEntry, IndexConfig,
+        PrimaryKeyConfig, SchemaConfig, TableConfig, ValidationRule,
     },
-    db::active_dialect,
-    ensure_sys_tables, execute_migration_plan, resolve,
+    data_migration::{apply_data_migrations, parse_data_migrations, DataMigrationTarget},
+    db::{active_dialect, introspect_catalog},
+    detect_drift, drift_repair_plan, ensure_sys_tables, execute_migration_plan,
+    execute_migration_plan_atomic, generate_package, introspect,
+    preflight::probe_plan,
+    resolve,
     service::{CrudService, TenantExecutor},
+    store::load_config_rows_at_version,
+    transition::{attach_shadow_columns, backfill_plan, contract_plan, to_expand_contract_plan},
 };
 use serde_json::json;
 use sqlx::SqlitePool;
@@ -423,6 +429,482 @@ async fn a_column_the_database_already_has_does_not_fail_the_upgrade() {
     );
 }
 
+#[tokio::test]
+async fn drift_repair_plan_brings_the_database_back_in_line() {
+    let pool = memory_pool().await;
+    let dialect = active_dialect();
+    apply_migrations(
+        &pool,
+        &notes_config(),
+        None,
+        None,
+        dialect.as_ref(),
+        &HashMap::new(),
+    )
+    .await
+    .expect("install v1");
+    sqlx::query(r#"CREATE TABLE "main"."scratch" ("id" INTEGER)"#)
+        .execute(&pool)
+        .await
+        .expect("unowned table");
+
+    // The installed config says v2, the database still holds v1.
+    let v2 = notes_config_v2();
+    let schemas = vec!["main".to_string()];
+    let snap = introspect(&pool, dialect.as_ref(), &schemas).await;
+    let report = detect_drift(&v2, &snap, None, None, &HashMap::new(), dialect.as_ref());
+    let found: Vec<(String, String)> = report
+        .items
+        .iter()
+        .map(|i| (format!("{:?}", i.kind), i.object.clone()))
+        .collect();
+    assert_eq!(
+        found,
+        vec![
+            ("MissingColumn".to_string(), "project_id".to_string()),
+            ("ExtraTable".to_string(), "scratch".to_string()),
+        ]
+    );
+
+    let plan = drift_repair_plan(&v2, &snap, None, None, &HashMap::new(), dialect.as_ref())
+        .expect("repair plan");
+    execute_migration_plan(
+        &pool,
+        &pool,
+        &plan,
+        "drift-1",
+        "pkg",
+        "t1",
+        None,
+        "2.0.0",
+        dialect.as_ref(),
+    )
+    .await
+    .expect("repair plan executes");
+
+    let snap = introspect(&pool, dialect.as_ref(), &schemas).await;
+    let report = detect_drift(&v2, &snap, None, None, &HashMap::new(), dialect.as_ref());
+    assert_eq!(report.items.len(), 1, "{:?}", report.items);
+    assert_eq!(
+        report.items[0].object, "scratch",
+        "extra tables are never dropped"
+    );
+}
+
+#[tokio::test]
+async fn a_legacy_schema_reverse_engineers_into_an_installable_package() {
+    let pool = memory_pool().await;
+    let dialect = active_dialect();
+    for sql in [
+        "CREATE TABLE customers (id INTEGER PRIMARY KEY, email VARCHAR(120) NOT NULL UNIQUE, \
+         api_token TEXT, status TEXT NOT NULL DEFAULT 'active', \
+         joined DATETIME DEFAULT CURRENT_TIMESTAMP)",
+        "CREATE TABLE invoices (id TEXT PRIMARY KEY, \
+         customer_id INTEGER NOT NULL REFERENCES customers(id) ON DELETE CASCADE, \
+         amount NUMERIC(10,2) NOT NULL DEFAULT 0, note TEXT)",
+        "CREATE INDEX invoices_customer_idx ON invoices (customer_id)",
+        "CREATE INDEX invoices_open_idx ON invoices (customer_id, amount) WHERE note IS NULL",
+        "CREATE TABLE event_log (message TEXT)",
+    ] {
+        sqlx::query(sql).execute(&pool).await.expect(sql);
+    }
+
+    let catalog = introspect_catalog(&pool, dialect.as_ref(), "main")
+        .await
+        .expect("catalog");
+    let customers = catalog.table("customers").expect("customers");
+    assert!(customers.column("id").unwrap().auto_increment);
+    assert_eq!(customers.unique, vec![vec!["email".to_string()]]);
+    let invoices = catalog.table("invoices").expect("invoices");
+    assert_eq!(invoices.foreign_keys[0].ref_table, "customers");
+    assert_eq!(
+        invoices.foreign_keys[0].on_delete.as_deref(),
+        Some("CASCADE")
+    );
+    let partial = &invoices.indexes[1];
+    assert_eq!(partial.name, "invoices_open_idx");
+    assert_eq!(partial.where_clause.as_deref(), Some("note IS NULL"));
+
+    let opts = architect_sdk::ReverseOptions {
+        id: "legacy".into(),
+        name: None,
+        version: None,
+        schema: None,
+        description: None,
+    };
+    let pkg = generate_package(&catalog, dialect.as_ref(), &opts);
+    assert_eq!(pkg.skipped, vec!["table event_log: no primary key"]);
+    let config = &pkg.config;
+    let type_of = |name: &str| {
+        let c = config.columns.iter().find(|c| c.name == name).unwrap();
+        match &c.type_ {
+            ColumnTypeConfig::Simple(s) => s.clone(),
+            other => panic!("{:?}", other),
+        }
+    };
+    assert_eq!(type_of("id"), "serial");
+    assert_eq!(type_of("email"), "varchar(120)");
+    assert_eq!(type_of("joined"), "timestamptz");
+    assert_eq!(type_of("amount"), "numeric(10,2)");
+    assert_eq!(config.relationships.len(), 1);
+    assert_eq!(config.indexes.len(), 2);
+    let api = &config.api_entities[0];
+    assert_eq!(api.path_segment, "customers");
+    assert_eq!(api.sensitive_columns, vec!["api_token"]);
+
+    // Installing on the source database only adds the SDK's system columns.
+    apply_migrations(&pool, config, None, None, dialect.as_ref(), &HashMap::new())
+        .await
+        .expect("generated package installs over the legacy schema");
+    let snap = introspect(&pool, dialect.as_ref(), &["main".to_string()]).await;
+    assert!(snap.has_column("main", "customers", "created_at"));
+    assert!(snap.has_column("main", "customers", "joined"));
+    resolve(config).expect("generated package resolves");
+}
+
+#[tokio::test]
+async fn expand_contract_type_change_dual_writes_then_swaps() {
+    use architect_sdk::store::list_column_transitions;
+    use architect_sdk::transition::record_transitions;
+
+    let pool = memory_pool().await;
+    let dialect = active_dialect();
+    ensure_sys_tables(&pool, dialect.as_ref()).await.unwrap();
+    let v1 = notes_config();
+    let mut v2 = notes_config();
+    v2.columns[1].type_ = ColumnTypeConfig::Simple("integer".into());
+
+    apply_migrations(&pool, &v1, None, None, dialect.as_ref(), &HashMap::new())
+        .await
+        .expect("install v1");
+    sqlx::query(r#"INSERT INTO "main"."notes" ("body") VALUES ('7')"#)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    // Expand: the upgrade only adds the shadow column.
+    let plan = compute_migration_plan(&v1, &v2, None, None, dialect.as_ref(), &HashMap::new())
+        .expect("migration plan");
+    let plan = to_expand_contract_plan(plan, &v1, &v2, dialect.as_ref());
+    let run = |plan: architect_sdk::MigrationPlan, id: &'static str| {
+        let pool = pool.clone();
+        let dialect = active_dialect();
+        async move {
+            let result = execute_migration_plan(
+                &pool,
+                &pool,
+                &plan,
+                id,
+                "pkg",
+                "t1",
+                Some("1.0.0"),
+                "2.0.0",
+                dialect.as_ref(),
+            )
+            .await
+            .expect(id);
+            record_transitions(&pool, "pkg", "pkg", "t1", &plan, &result)
+                .await
+                .expect("record transitions");
+            result
+        }
+    };
+    run(plan, "expand").await;
+    let rows = list_column_transitions(&pool, "pkg").await.unwrap();
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0].phase, "expanded");
+
+    // Writes through the CRUD layer land in both columns.
+    let model = attach_shadow_columns(resolve(&v2).unwrap(), &rows);
+    let entity = model.entity_by_path.get("notes").unwrap();
+    let mut exec = TenantExecutor::pool(&pool, dialect.as_ref());
+    let mut body = HashMap::new();
+    body.insert("body".to_string(), json!("42"));
+    CrudService::create(&mut exec, entity, &body, None, None, None, dialect.as_ref())
+        .await
+        .expect("create during transition");
+    let mirrored: (i64,) =
+        sqlx::query_as(r#"SELECT "body__new" FROM "main"."notes" WHERE "body" = '42'"#)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(mirrored.0, 42);
+
+    // Backfill copies the row written before the expand, one row per batch.
+    let plan = backfill_plan(&rows, &v2, 1, dialect.as_ref()).unwrap();
+    let result = run(plan, "backfill").await;
+    assert_eq!(result.completed_steps, vec![(1, 1)]);
+    let rows = list_column_transitions(&pool, "pkg").await.unwrap();
+    assert_eq!(rows[0].phase, "backfilled");
+    assert_eq!(rows[0].rows_backfilled, 1);
+
+    // Contract swaps the shadow column into place and drops the old one.
+    let plan = contract_plan(&rows, &v2, 100, dialect.as_ref()).unwrap();
+    run(plan, "contract").await;
+    assert!(list_column_transitions(&pool, "pkg")
+        .await
+        .unwrap()
+        .is_empty());
+    let types: Vec<(String,)> =
+        sqlx::query_as(r#"SELECT typeof("body") FROM "main"."notes" ORDER BY "id""#)
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+    assert_eq!(types, vec![("integer".to_string(),); 2]);
+    let snap = introspect(&pool, dialect.as_ref(), &["main".to_string()]).await;
+    assert!(!snap.has_column("main", "notes", "body__new"));
+    assert!(!snap.has_column("main", "notes", "body__old"));
+}
+
+#[tokio::test]
+async fn atomic_apply_rolls_back_earlier_steps_when_a_safe_step_fails() {
+    let pool = memory_pool().await;
+    let dialect = active_dialect();
+    ensure_sys_tables(&pool, dialect.as_ref()).await.unwrap();
+    let v1 = notes_config();
+    let v2 = notes_config_v2();
+    apply_migrations(&pool, &v1, None, None, dialect.as_ref(), &HashMap::new())
+        .await
+        .expect("install v1");
+
+    let mut plan = compute_migration_plan(&v1, &v2, None, None, dialect.as_ref(), &HashMap::new())
+        .expect("migration plan");
+    assert_eq!(plan.steps.len(), 1, "{:?}", plan.steps);
+    let mut broken = plan.steps[0].clone();
+    broken.step = 2;
+    broken.object = "missing".into();
+    broken.ddl = Some(r#"ALTER TABLE "main"."no_such_table" ADD COLUMN "x" TEXT"#.into());
+    plan.steps.push(broken);
+
+    let err = execute_migration_plan_atomic(
+        &pool,
+        &pool,
+        &plan,
+        "atomic",
+        "pkg",
+        "t1",
+        Some("1.0.0"),
+        "2.0.0",
+        dialect.as_ref(),
+    )
+    .await;
+    assert!(err.is_err());
+    let snap = introspect(&pool, dialect.as_ref(), &["main".to_string()]).await;
+    assert!(!snap.has_column("main", "notes", "project_id"));
+
+    // Without the failing step the same plan commits.
+    plan.steps.pop();
+    let result = execute_migration_plan_atomic(
+        &pool,
+        &pool,
+        &plan,
+        "atomic-2",
+        "pkg",
+        "t1",
+        Some("1.0.0"),
+        "2.0.0",
+        dialect.as_ref(),
+    )
+    .await
+    .expect("atomic apply");
+    assert_eq!(result.applied, 1);
+    let snap = introspect(&pool, dialect.as_ref(), &["main".to_string()]).await;
+    assert!(snap.has_column("main", "notes", "project_id"));
+}
+
+#[tokio::test]
+async fn preview_probes_report_offending_rows() {
+    let pool = memory_pool().await;
+    let dialect = active_dialect();
+    let v1 = notes_config();
+    apply_migrations(&pool, &v1, None, None, dialect.as_ref(), &HashMap::new())
+        .await
+        .expect("install v1");
+    for body in ["a", "a", "b"] {
+        sqlx::query(r#"INSERT INTO "main"."notes" ("body") VALUES ($1)"#)
+            .bind(body)
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+    sqlx::query(r#"INSERT INTO "main"."notes" ("body") VALUES (NULL)"#)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let mut v2 = notes_config();
+    v2.columns[1].nullable = false;
+    v2.indexes.push(IndexConfig {
+        id: "i_notes_body".into(),
+        schema_id: Some("s1".into()),
+        table_id: "t_notes".into(),
+        name: "notes_body_key".into(),
+        method: None,
+        unique: true,
+        columns: vec![IndexColumnEntry::Name("body".into())],
+        include: vec![],
+        where_: None,
+        comment: None,
+    });
+    let mut plan = compute_migration_plan(&v1, &v2, None, None, dialect.as_ref(), &HashMap::new())
+        .expect("migration plan");
+    probe_plan(&pool, &mut plan, &v2, dialect.as_ref(), true).await;
+
+    let detail = |object: &str| {
+        plan.steps
+            .iter()
+            .find(|s| s.object == object)
+            .and_then(|s| s.risk_detail.clone())
+            .unwrap_or_default()
+    };
+    assert!(
+        detail("body")
+            .contains("Pre-flight: 1 row(s) hold NULL in \"body\" (sample primary keys: 4)"),
+        "{}",
+        detail("body")
+    );
+    assert!(
+        detail("notes_body_key")
+            .contains("Pre-flight: 2 row(s) share their (\"body\") value with another row (sample primary keys: 1, 2)"),
+        "{}",
+        detail("notes_body_key")
+    );
+
+    // A database shared by several tenants (RLS) keeps the counts but lists no primary keys.
+    let mut shared =
+        compute_migration_plan(&v1, &v2, None, None, dialect.as_ref(), &HashMap::new())
+            .expect("migration plan");
+    probe_plan(&pool, &mut shared, &v2, dialect.as_ref(), false).await;
+    let details: Vec<String> = shared
+        .steps
+        .iter()
+        .filter_map(|s| s.risk_detail.clone())
+        .collect();
+    assert!(details
+        .iter()
+        .any(|d| d.contains("Pre-flight: 1 row(s) hold NULL in \"body\".")));
+    assert!(details
+        .iter()
+        .any(|d| d.contains("2 row(s) share their (\"body\") value with another row.")));
+    assert!(details.iter().all(|d| !d.contains("sample primary keys")));
+}
+
+#[tokio::test]
+async fn data_migrations_apply_once_per_tenant() {
+    let pool = memory_pool().await;
+    let dialect = active_dialect();
+    ensure_sys_tables(&pool, dialect.as_ref())
+        .await
+        .expect("ensure_sys_tables");
+    let config = notes_config();
+    apply_migrations(
+        &pool,
+        &config,
+        None,
+        None,
+        dialect.as_ref(),
+        &HashMap::new(),
+    )
+    .await
+    .expect("install");
+    let model = resolve(&config).unwrap();
+
+    let files = |fix: &str| {
+        vec![
+            (
+                "data/001_seed.json".to_string(),
+                r#"{"entity": "notes", "rows": [{"body": "draft"}, {"body": "draft"}]}"#
+                    .to_string(),
+            ),
+            ("data/002_rename.sql".to_string(), fix.to_string()),
+        ]
+    };
+    let migrations = parse_data_migrations(
+        &files("UPDATE {{schema}}.\"notes\" SET \"body\" = {{tenant_id}} WHERE \"body\" = 'draft'"),
+        dialect.as_ref(),
+    )
+    .expect("parse");
+    let target = DataMigrationTarget {
+        pool: &pool,
+        tenant_id: "acme",
+        schema_override: None,
+        schema: "main",
+        rls: false,
+    };
+    let first = apply_data_migrations(
+        &pool,
+        &target,
+        "notes_pkg",
+        Some("1.0.0"),
+        &model,
+        &migrations,
+        dialect.as_ref(),
+    )
+    .await;
+    assert_eq!(first.error, None);
+    assert_eq!(first.applied, vec!["001_seed", "002_rename"]);
+    let bodies: Vec<(String,)> = sqlx::query_as(r#"SELECT "body" FROM "main"."notes""#)
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+    assert_eq!(bodies, vec![("acme".to_string(),), ("acme".to_string(),)]);
+
+    // A second run applies nothing; an edited file is reported instead of re-run.
+    let edited =
+        parse_data_migrations(&files("DELETE FROM {{schema}}.\"notes\""), dialect.as_ref())
+            .expect("parse");
+    let second = apply_data_migrations(
+        &pool,
+        &target,
+        "notes_pkg",
+        Some("1.0.1"),
+        &model,
+        &edited,
+        dialect.as_ref(),
+    )
+    .await;
+    assert!(second.applied.is_empty());
+    assert_eq!(second.changed, vec!["002_rename"]);
+    let count: (i64,) = sqlx::query_as(r#"SELECT COUNT(*) FROM "main"."notes""#)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(count.0, 2);
+}
+
+#[tokio::test]
+async fn config_rows_at_version_come_from_live_or_history_tables() {
+    let pool = memory_pool().await;
+    let dialect = active_dialect();
+    ensure_sys_tables(&pool, dialect.as_ref())
+        .await
+        .expect("ensure_sys_tables");
+    for (table, version, body) in [
+        ("_sys_tables_history", 1_i64, "v1"),
+        ("_sys_tables", 2, "v2"),
+    ] {
+        sqlx::query(&format!(
+            "INSERT INTO main.{} (id, package_id, payload, version) VALUES ('t1', 'pkg', $1, $2)",
+            table
+        ))
+        .bind(json!({ "id": "t1", "name": body }))
+        .bind(version)
+        .execute(&pool)
+        .await
+        .unwrap();
+    }
+    for (version, name) in [(1, "v1"), (2, "v2")] {
+        let rows = load_config_rows_at_version(&pool, "_sys_tables", "pkg", version)
+            .await
+            .unwrap();
+        assert_eq!(rows, vec![json!({ "id": "t1", "name": name })]);
+    }
+    assert!(load_config_rows_at_version(&pool, "_sys_tables", "pkg", 3)
+        .await
+        .unwrap()
+        .is_empty());
+}
+
 // ── CrudService: notes (serial / integer PK) ─────────────────────────────────
 
 async fn notes_executor(pool: &SqlitePool) -> (SqlitePool, architect_sdk::config::ResolvedModel) {
@@ -764,6 +1246,295 @@ async fn sys_tenants_table_exists() {
     assert_eq!(count, 0);
 }
 
+#[tokio::test]
+async fn tenant_registry_loads_pool_limits() {
+    let pool = memory_pool().await;
+    let dialect = active_dialect();
+    ensure_sys_tables(&pool, dialect.as_ref()).await.unwrap();
+    sqlx::query(
+        "INSERT INTO main._sys_tenants (id, strategy, database_url, max_connections, idle_timeout_secs) \
+         VALUES ('acme', 'database', 'sqlite::memory:', 12, 30), ('beta', 'database', 'sqlite::memory:', 0, NULL)",
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    let registry = architect_sdk::tenant::load_registry_from_pool(&pool)
+        .await
+        .unwrap();
+    let acme = &registry.get("acme").unwrap().pool;
+    assert_eq!(acme.max_connections, Some(12));
+    assert_eq!(acme.idle_timeout, Some(std::time::Duration::from_secs(30)));
+    assert_eq!(acme.acquire_timeout, None);
+    // Non-positive limits fall back to the defaults.
+    assert_eq!(
+        registry.get("beta").unwrap().pool,
+        architect_sdk::tenant_pool::TenantPoolOptions::default()
+    );
+}
+
+#[tokio::test]
+async fn asset_pages_cover_rows_sharing_the_first_key_column() {
+    let pool = memory_pool().await;
+    let dialect = active_dialect();
+    let mut config = notes_config();
+    config.tables[0].primary_key = PrimaryKeyConfig::Composite(vec!["id".into(), "body".into()]);
+    config.columns[0].type_ = ColumnTypeConfig::Simple("integer".into());
+    config.columns.push(ColumnConfig {
+        id: "c_notes_file".into(),
+        table_id: "t_notes".into(),
+        name: "file".into(),
+        type_: ColumnTypeConfig::Simple("asset".into()),
+        nullable: true,
+        default: None,
+        comment: None,
+        asset: None,
+        extensible: false,
+    });
+    apply_migrations(
+        &pool,
+        &config,
+        None,
+        None,
+        dialect.as_ref(),
+        &HashMap::new(),
+    )
+    .await
+    .unwrap();
+    // Five rows share id 1, so a page of two ends mid-way through them.
+    for (id, body) in [(1, "a"), (1, "b"), (1, "c"), (1, "d"), (1, "e"), (2, "a")] {
+        sqlx::query(r#"INSERT INTO "main"."notes" ("id", "body", "file") VALUES ($1, $2, $3)"#)
+            .bind(id)
+            .bind(body)
+            .bind(format!("notes/{}{}.pdf", id, body))
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+    let model = resolve(&config).unwrap();
+    let entity = model.entity_by_path.get("notes").unwrap();
+    let key = architect_sdk::sql::asset_page_key(entity, false);
+    let mut exec = TenantExecutor::pool(&pool, dialect.as_ref());
+    let mut seen = Vec::new();
+    let mut after = None;
+    loop {
+        let rows = CrudService::list_asset_page(&mut exec, entity, false, after.take(), 2, None)
+            .await
+            .unwrap();
+        seen.extend(rows.iter().map(|r| r["file"].as_str().unwrap().to_string()));
+        if rows.len() < 2 {
+            break;
+        }
+        after = rows
+            .last()
+            .map(|r| key.iter().map(|k| r[k.as_str()].clone()).collect());
+    }
+    assert_eq!(
+        seen,
+        [
+            "notes/1a.pdf",
+            "notes/1b.pdf",
+            "notes/1c.pdf",
+            "notes/1d.pdf",
+            "notes/1e.pdf",
+            "notes/2a.pdf"
+        ]
+    );
+}
+
+#[tokio::test]
+async fn asset_ownership_sees_issued_and_referenced_paths() {
+    let pool = memory_pool().await;
+    let dialect = active_dialect();
+    ensure_sys_tables(&pool, dialect.as_ref()).await.unwrap();
+    let mut config = notes_config();
+    for (id, name, ty) in [
+        ("c_notes_file", "file", "asset"),
+        ("c_notes_files", "files", "asset[]"),
+    ] {
+        config.columns.push(ColumnConfig {
+            id: id.into(),
+            table_id: "t_notes".into(),
+            name: name.into(),
+            type_: ColumnTypeConfig::Simple(ty.into()),
+            nullable: true,
+            default: None,
+            comment: None,
+            asset: None,
+            extensible: false,
+        });
+    }
+    apply_migrations(
+        &pool,
+        &config,
+        None,
+        None,
+        dialect.as_ref(),
+        &HashMap::new(),
+    )
+    .await
+    .unwrap();
+    sqlx::query(
+        r#"INSERT INTO "main"."notes" ("id", "body", "file", "files") VALUES (1, 'x', $1, $2)"#,
+    )
+    .bind("notes/a.pdf")
+    .bind(r#"["notes/b.pdf","notes/c.pdf"]"#)
+    .execute(&pool)
+    .await
+    .unwrap();
+    let model = resolve(&config).unwrap();
+    let entity = model.entity_by_path.get("notes").unwrap();
+    let column = |name: &str| entity.columns.iter().find(|c| c.name == name).unwrap();
+    let mut exec = TenantExecutor::pool(&pool, dialect.as_ref());
+    for (col, path, expected) in [
+        ("file", "notes/a.pdf", true),
+        ("file", "notes/b.pdf", false),
+        ("files", "notes/c.pdf", true),
+        ("files", "notes/a.pdf", false),
+    ] {
+        let found =
+            CrudService::asset_referenced(&mut exec, entity, column(col), false, path, None)
+                .await
+                .unwrap();
+        assert_eq!(found, expected, "{col} holds {path}");
+    }
+
+    use architect_sdk::store::{asset_upload_issued, record_asset_upload};
+    record_asset_upload(
+        &pool,
+        "acme",
+        "notes/new.pdf",
+        "notes",
+        "file",
+        dialect.as_ref(),
+    )
+    .await
+    .unwrap();
+    assert!(asset_upload_issued(
+        &pool,
+        "acme",
+        "notes/new.pdf",
+        "notes",
+        "file",
+        dialect.as_ref()
+    )
+    .await
+    .unwrap());
+    // Issued to another tenant, or for another column, does not count.
+    assert!(!asset_upload_issued(
+        &pool,
+        "beta",
+        "notes/new.pdf",
+        "notes",
+        "file",
+        dialect.as_ref()
+    )
+    .await
+    .unwrap());
+    assert!(!asset_upload_issued(
+        &pool,
+        "acme",
+        "notes/new.pdf",
+        "notes",
+        "files",
+        dialect.as_ref()
+    )
+    .await
+    .unwrap());
+}
+
+#[tokio::test]
+async fn diagnostics_report_failed_migration_plans() {
+    use architect_sdk::diagnostics::ComponentStatus;
+    use std::sync::{Arc, RwLock};
+
+    let pool = memory_pool().await;
+    let dialect = active_dialect();
+    ensure_sys_tables(&pool, dialect.as_ref()).await.unwrap();
+    sqlx::query(
+        "INSERT INTO main._sys_migration_audit (migration_plan_id, package_id, tenant_id, to_version, \
+         step_number, operation, schema_name, object_name, object_type, description, safety, risk, status) \
+         VALUES ('plan-1', 'pkg', 'acme', '2.0.0', 1, 'add_column', 'main', 'notes.title', 'column', \
+         'add title', 'safe', 'low', 'failed')",
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+    let model = resolve(&notes_config()).unwrap();
+    let state = architect_sdk::state::AppState {
+        pool,
+        model: Arc::new(RwLock::new(model)),
+        package_models: Arc::new(RwLock::new(HashMap::new())),
+        tenant_pools: Default::default(),
+        tenant_registry: Arc::new(architect_sdk::tenant::TenantRegistry::new()),
+        storage: None,
+        event_client: None,
+        authrs_client: None,
+        dialect,
+        extensible_cache: Default::default(),
+        cross_package_index: Arc::new(RwLock::new(None)),
+        replica_pools: Default::default(),
+    };
+
+    let report = architect_sdk::diagnostics::run(&state).await;
+    assert_eq!(report.status, ComponentStatus::Degraded);
+    assert_eq!(report.components["database"].status, ComponentStatus::Ok);
+    assert_eq!(
+        report.components["storage"].status,
+        ComponentStatus::Skipped
+    );
+    assert_eq!(report.components["models"].status, ComponentStatus::Ok);
+    let migrations = &report.components["migrations"];
+    assert_eq!(migrations.status, ComponentStatus::Degraded);
+    assert_eq!(migrations.detail["pending_plans"], 0);
+    assert_eq!(
+        migrations.detail["failed_plans"][0]["migration_plan_id"],
+        "plan-1"
+    );
+    // The unauthenticated readiness report leaves out model loading and is reused.
+    let ready = architect_sdk::diagnostics::readiness(&state).await;
+    assert!(!ready.components.contains_key("models"));
+    assert_eq!(ready.status, ComponentStatus::Degraded);
+    let again = architect_sdk::diagnostics::readiness(&state).await;
+    assert!(Arc::ptr_eq(&ready, &again));
+}
+
+#[tokio::test]
+async fn schedule_log_prunes_records_behind_the_window() {
+    use architect_sdk::store::{claim_schedule_fire, prune_schedule_log};
+
+    let pool = memory_pool().await;
+    let dialect = active_dialect();
+    ensure_sys_tables(&pool, dialect.as_ref()).await.unwrap();
+    let trigger = "default.invoices:remind";
+    for fire in ["old", "new"] {
+        assert!(claim_schedule_fire(&pool, "acme", trigger, "1", fire)
+            .await
+            .unwrap());
+    }
+    claim_schedule_fire(&pool, "globex", trigger, "1", "old")
+        .await
+        .unwrap();
+    sqlx::query(
+        "UPDATE main._sys_event_schedule_log SET fired_at = datetime('now', '-2 hours') \
+         WHERE fire_key = 'old'",
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    let pruned = prune_schedule_log(&pool, "acme", trigger, 3600, dialect.as_ref())
+        .await
+        .unwrap();
+    assert_eq!(pruned, 1);
+    let (left,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM main._sys_event_schedule_log")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    // acme's recent record and globex's record (another tenant) remain.
+    assert_eq!(left, 2);
+}
+
 // ── config resolution ─────────────────────────────────────────────────────────
 
 #[tokio::test]
@@ -928,3 +1699,161 @@ async fn extensible_index_ddl_applies_and_is_idempotent() {
     .expect("count indexes");
     assert_eq!(count, 1, "expected exactly one generated index");
 }
+
+#[tokio::test]
+async fn local_signed_uploads_stop_at_the_size_limit() {
+    use architect_sdk::error::AppError;
+    use architect_sdk::handlers::asset::upload_local_asset;
+    use architect_sdk::storage::{local::LocalProvider, StorageProvider};
+    use axum::extract::{Path, Query, Request, State};
+    use std::sync::{Arc, RwLock};
+
+    let root = std::env::temp_dir().join(format!("architect-local-{}", uuid::Uuid::new_v4()));
+    let local = Arc::new(
+        LocalProvider::new(&root, b"test-secret".to_vec(), "/api/v1").with_max_upload_bytes(8),
+    );
+    let state = architect_sdk::state::AppState {
+        pool: memory_pool().await,
+        model: Arc::new(RwLock::new(resolve(&notes_config()).unwrap())),
+        package_models: Arc::new(RwLock::new(HashMap::new())),
+        tenant_pools: Default::default(),
+        tenant_registry: Arc::new(architect_sdk::tenant::TenantRegistry::new()),
+        storage: Some(local.clone()),
+        event_client: None,
+        authrs_client: None,
+        dialect: active_dialect(),
+        extensible_cache: Default::default(),
+        cross_package_index: Arc::new(RwLock::new(None)),
+        replica_pools: Default::default(),
+    };
+    let path = "acme/notes/a.txt".to_string();
+    let signed = local.presign_upload(&path, "text/plain", 60).await.unwrap();
+    let params: HashMap<String, String> = signed
+        .url
+        .split_once('?')
+        .unwrap()
+        .1
+        .split('&')
+        .filter_map(|kv| kv.split_once('='))
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+    let put = |body: &'static [u8], declared: bool| {
+        let mut req = Request::builder().method("PUT");
+        if declared {
+            req = req.header("content-length", body.len());
+        }
+        upload_local_asset(
+            State(state.clone()),
+            Path(path.clone()),
+            Query(params.clone()),
+            req.body(axum::body::Body::from(body)).unwrap(),
+        )
+    };
+
+    let err = put(b"0123456789abcdef", true).await.unwrap_err();
+    assert!(matches!(err, AppError::PayloadTooLarge(_)), "{err}");
+    // Without Content-Length the stream is cut off and the partial file removed.
+    let err = put(b"0123456789abcdef", false).await.unwrap_err();
+    assert!(matches!(err, AppError::PayloadTooLarge(_)), "{err}");
+    assert!(local.head(&path).await.unwrap().is_none());
+
+    put(b"01234567", false).await.unwrap();
+    assert_eq!(local.head(&path).await.unwrap().unwrap().size, 8);
+    let _ = std::fs::remove_dir_all(&root);
+}
+
+#[tokio::test]
+async fn asset_downloads_require_a_readable_row_holding_the_path() {
+    use architect_sdk::error::AppError;
+    use architect_sdk::extractors::{tenant::TenantId, user::UserId};
+    use architect_sdk::handlers::asset::{download_asset, DownloadAssetQuery};
+    use architect_sdk::storage::{bytes_stream, local::LocalProvider, StorageProvider};
+    use axum::extract::{Query, State};
+    use std::sync::{Arc, RwLock};
+
+    let pool = memory_pool().await;
+    let dialect = active_dialect();
+    ensure_sys_tables(&pool, dialect.as_ref()).await.unwrap();
+    let mut config = notes_config();
+    config.columns.push(ColumnConfig {
+        id: "c_notes_file".into(),
+        table_id: "t_notes".into(),
+        name: "file".into(),
+        type_: ColumnTypeConfig::Simple("asset".into()),
+        nullable: true,
+        default: None,
+        comment: None,
+        asset: None,
+        extensible: false,
+    });
+    apply_migrations(
+        &pool,
+        &config,
+        None,
+        None,
+        dialect.as_ref(),
+        &HashMap::new(),
+    )
+    .await
+    .unwrap();
+    sqlx::query("INSERT INTO main._sys_tenants (id, strategy) VALUES ('acme', 'rls')")
+        .execute(&pool)
+        .await
+        .unwrap();
+    // Stored under the default prefix, which carries no tenant segment.
+    sqlx::query(r#"INSERT INTO "main"."notes" ("id", "body", "file") VALUES (1, 'x', $1)"#)
+        .bind("notes/2024/01/02/held.txt")
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let root = std::env::temp_dir().join(format!("architect-local-{}", uuid::Uuid::new_v4()));
+    let local = Arc::new(LocalProvider::new(
+        &root,
+        b"test-secret".to_vec(),
+        "/api/v1",
+    ));
+    for path in ["notes/2024/01/02/held.txt", "notes/2024/01/02/loose.txt"] {
+        local
+            .upload(path, bytes_stream(b"hello".to_vec()), "text/plain")
+            .await
+            .unwrap();
+    }
+    let state = architect_sdk::state::AppState {
+        tenant_registry: Arc::new(
+            architect_sdk::tenant::load_registry_from_pool(&pool)
+                .await
+                .unwrap(),
+        ),
+        pool,
+        model: Arc::new(RwLock::new(resolve(&config).unwrap())),
+        package_models: Arc::new(RwLock::new(HashMap::new())),
+        tenant_pools: Default::default(),
+        storage: Some(local.clone()),
+        event_client: None,
+        authrs_client: None,
+        dialect,
+        extensible_cache: Default::default(),
+        cross_package_index: Arc::new(RwLock::new(None)),
+        replica_pools: Default::default(),
+    };
+    let download = |path: &str| {
+        download_asset(
+            State(state.clone()),
+            TenantId(Some("acme".into())),
+            UserId(None),
+            Query(DownloadAssetQuery {
+                path: path.into(),
+                entity: "notes".into(),
+                column: "file".into(),
+                package_id: None,
+            }),
+        )
+    };
+
+    let res = download("notes/2024/01/02/held.txt").await.unwrap();
+    assert_eq!(res.status(), axum::http::StatusCode::OK);
+    let err = download("notes/2024/01/02/loose.txt").await.unwrap_err();
+    assert!(matches!(err, AppError::Forbidden(_)), "{err}");
+    let _ = std::fs::remove_dir_all(&root);
+}